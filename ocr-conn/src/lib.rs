@@ -1,5 +1,6 @@
 pub mod ocr;
 pub mod preprocess;
+pub mod table;
 
 use pdf2image::{Pages, RenderOptionsBuilder, DPI};
 use std::env::current_dir;
@@ -5,7 +5,7 @@ use crossbeam::channel::{self, bounded};
 use crossbeam::channel::{Receiver, Sender};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::io::{BufRead, BufReader, Write};
 use std::ops::DerefMut;
 use std::path::{Path, PathBuf};
@@ -28,6 +28,13 @@ const SLOW_CALL_WARN_THRESHOLD_MS: u128 = 8_000;
 const DEFAULT_MAX_INPUT_BYTES: usize = 10 * 1024 * 1024; // 10MB
 const DEFAULT_MAX_PIXELS: u64 = 25_000_000; // ~25MP, 5000x5000
 const DEFAULT_MIN_DIMENSION: u32 = 16;
+const ORIENTATION_THUMBNAIL_MAX: u32 = 640;
+/// 原方向缩略图得分（置信度 × 字数）超过该值时不再尝试其他方向
+const ORIENTATION_GOOD_ENOUGH_SCORE: f64 = 40.0;
+/// 估算可用内存能容纳的引擎数时，每个引擎默认预留的内存
+const DEFAULT_ENGINE_MEMORY_MB: u64 = 512;
+const AUTO_POOL_MAX: usize = 32;
+const DEFAULT_REAP_INTERVAL_SECS: u64 = 30;
 
 pub mod error_code {
     pub const OK_WITH_TEXT: u32 = 100;
@@ -97,7 +104,7 @@ pub struct Content {
     data: serde_json::Value,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ContentData {
     #[serde(rename = "box")]
     pub rect: Rectangle,
@@ -176,11 +183,14 @@ impl From<Vec<u8>> for ImageData {
 }
 
 pub struct Extractor {
+    id: u64,
     process: Child,
     receiver: Receiver<String>,
     stderr_recent: Arc<Mutex<VecDeque<String>>>,
     engine_opts: OcrEngineOptions,
     last_used: Instant,
+    started_at: Instant,
+    requests_served: u64,
     consecutive_failures: u32,
     last_failure_at: Option<SystemTime>,
 }
@@ -201,11 +211,14 @@ impl Extractor {
     pub fn new_with_options(opts: OcrEngineOptions) -> io::Result<Self> {
         let (process, receiver, stderr_recent) = Self::spawn_process(&opts)?;
         Ok(Self {
+            id: NEXT_ENGINE_ID.fetch_add(1, Ordering::Relaxed),
             process,
             receiver,
             stderr_recent,
             engine_opts: opts,
             last_used: Instant::now(),
+            started_at: Instant::now(),
+            requests_served: 0,
             consecutive_failures: 0,
             last_failure_at: None,
         })
@@ -334,6 +347,7 @@ impl Extractor {
         };
 
         let s = serde_json::to_string(&image)?;
+        self.requests_served = self.requests_served.saturating_add(1);
         self.write_fmt(format_args!("{}\n", s.trim()))?;
         let result = self.read_line();
 
@@ -527,6 +541,8 @@ impl Extractor {
                 self.receiver = recv;
                 self.stderr_recent = stderr_recent;
                 self.last_used = Instant::now();
+                self.started_at = Instant::now();
+                self.requests_served = 0;
                 self.consecutive_failures = 0;
                 self.last_failure_at = None;
                 ENGINE_RESTARTED.fetch_add(1, Ordering::Relaxed);
@@ -571,6 +587,75 @@ impl Extractor {
             self.consecutive_failures = 0;
         }
     }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.process.id()
+    }
+
+    /// 子进程当前常驻内存（KB），读取 `/proc/{pid}/status` 的 VmRSS
+    pub fn rss_kb(&self) -> Option<u64> {
+        read_process_rss_kb(self.process.id())
+    }
+
+    pub fn requests_served(&self) -> u64 {
+        self.requests_served
+    }
+
+    /// 达到内存上限或服务次数上限时返回退役原因
+    fn retire_reason(&self, tuning: &PoolTuning) -> Option<String> {
+        if let Some(limit) = tuning.max_requests_per_engine.filter(|n| *n > 0) {
+            if self.requests_served >= limit {
+                return Some(format!("已处理{}次请求", self.requests_served));
+            }
+        }
+        if let Some(limit_mb) = tuning.max_engine_rss_mb.filter(|n| *n > 0) {
+            if let Some(rss_kb) = self.rss_kb() {
+                if rss_kb / 1024 >= limit_mb {
+                    return Some(format!("RSS {}MB 超过上限 {}MB", rss_kb / 1024, limit_mb));
+                }
+            }
+        }
+        None
+    }
+
+    fn stats(&self, state: EngineState) -> EngineStats {
+        EngineStats {
+            id: self.id,
+            pid: self.process.id(),
+            state,
+            requests_served: self.requests_served,
+            rss_kb: self.rss_kb(),
+            age_secs: self.started_at.elapsed().as_secs(),
+            idle_secs: match state {
+                EngineState::Idle => self.last_used.elapsed().as_secs(),
+                EngineState::Busy => 0,
+            },
+        }
+    }
+}
+
+fn read_process_rss_kb(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_proc_kb_field(&status, "VmRSS:")
+}
+
+/// 读取 `/proc/meminfo` 中的 MemAvailable（MB）
+fn read_mem_available_mb() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    parse_proc_kb_field(&meminfo, "MemAvailable:").map(|kb| kb / 1024)
+}
+
+/// 解析 `/proc` 中形如 `VmRSS:	  123456 kB` 的行
+fn parse_proc_kb_field(content: &str, field: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(field))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
 }
 
 fn is_data_error_message(err: &str) -> bool {
@@ -686,7 +771,10 @@ static IMAGE_LIMITS: LazyLock<ImageLimits> = LazyLock::new(|| {
 
 impl Drop for Extractor {
     fn drop(&mut self) {
-        self.process.kill().ok();
+        if self.process.kill().is_ok() {
+            // 回收子进程，避免退役引擎残留僵尸进程
+            let _ = self.process.wait();
+        }
     }
 }
 
@@ -720,8 +808,77 @@ impl CircuitState {
     }
 }
 
+/// 引擎池回收策略；`None` 或 0 表示不限制
+#[derive(Debug, Clone)]
+pub struct PoolTuning {
+    /// 自动确定容量时每个引擎预留的内存
+    pub engine_memory_mb: u64,
+    pub max_engine_rss_mb: Option<u64>,
+    pub max_requests_per_engine: Option<u64>,
+    pub idle_timeout_secs: Option<u64>,
+    /// 空闲回收时至少保留的空闲引擎数
+    pub min_idle_engines: usize,
+    pub reap_interval_secs: u64,
+}
+
+impl Default for PoolTuning {
+    fn default() -> Self {
+        Self {
+            engine_memory_mb: DEFAULT_ENGINE_MEMORY_MB,
+            max_engine_rss_mb: None,
+            max_requests_per_engine: None,
+            idle_timeout_secs: None,
+            min_idle_engines: 0,
+            reap_interval_secs: DEFAULT_REAP_INTERVAL_SECS,
+        }
+    }
+}
+
+static POOL_TUNING: LazyLock<parking_lot::RwLock<PoolTuning>> =
+    LazyLock::new(|| parking_lot::RwLock::new(PoolTuning::default()));
+
+pub fn configure_pool_tuning(tuning: PoolTuning) {
+    *POOL_TUNING.write() = tuning;
+}
+
+pub fn pool_tuning() -> PoolTuning {
+    POOL_TUNING.read().clone()
+}
+
+/// 按 CPU 核数与可用内存给出的引擎池容量建议
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct PoolSizing {
+    pub recommended: usize,
+    pub cpu_based: usize,
+    pub memory_based: usize,
+    pub available_memory_mb: Option<u64>,
+}
+
+/// 每个 PaddleOCR 进程内部约占 3 个物理核；读不到可用内存时只按 CPU 估算
+pub fn recommend_pool_size(engine_memory_mb: u64) -> PoolSizing {
+    let cpu_based = (num_cpus::get_physical().max(1) / 3).max(1);
+    let available_memory_mb = read_mem_available_mb();
+    let memory_based = available_memory_mb
+        .map(|mb| ((mb / engine_memory_mb.max(1)) as usize).max(1))
+        .unwrap_or(cpu_based);
+    PoolSizing {
+        recommended: cpu_based.min(memory_based).clamp(1, AUTO_POOL_MAX),
+        cpu_based,
+        memory_based,
+        available_memory_mb,
+    }
+}
+
+/// 被借出引擎的快照，借出期间无法直接访问引擎本身
+struct BusyEngine {
+    pid: u32,
+    requests_served: u64,
+    started_at: Instant,
+}
+
 struct PoolInner {
     engines: parking_lot::Mutex<Vec<Option<Extractor>>>,
+    busy: parking_lot::Mutex<HashMap<u64, BusyEngine>>,
     opts: parking_lot::Mutex<Option<OcrEngineOptions>>,
     circuit: parking_lot::Mutex<CircuitState>,
     max: usize,
@@ -805,6 +962,11 @@ pub struct ExtractorHandle {
 
 impl ExtractorHandle {
     pub fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        let image = if preprocess::current_options().ocr_orientation {
+            self.auto_orient(image)
+        } else {
+            image
+        };
         if let Some(ref mut eng) = self.engine {
             eng.ensure_running();
             let start = Instant::now();
@@ -846,9 +1008,79 @@ impl ExtractorHandle {
     }
 }
 
+impl ExtractorHandle {
+    /// 用当前引擎识别四个方向的缩略图，按“置信度 × 字数”选出需要的顺时针旋转角度
+    pub fn detect_orientation(&mut self, image: &image::DynamicImage) -> preprocess::Rotation {
+        let Some(eng) = self.engine.as_mut() else {
+            return preprocess::Rotation::None;
+        };
+        preprocess::detect_orientation_by_score(
+            image,
+            ORIENTATION_THUMBNAIL_MAX,
+            ORIENTATION_GOOD_ENOUGH_SCORE,
+            |thumbnail| {
+                let bytes = preprocess::encode_image(thumbnail, ImageFormat::Png)?;
+                let data = ImageData::ImageBase64Dict {
+                    image_base64: base64::engine::general_purpose::STANDARD.encode(bytes),
+                };
+                eng.ocr_and_parse(data)
+                    .ok()
+                    .map(|contents| orientation_score(&contents))
+            },
+        )
+    }
+
+    fn auto_orient(&mut self, image: ImageData) -> ImageData {
+        let bytes = match &image {
+            ImageData::ImagePathDict { image_path } => std::fs::read(image_path).ok(),
+            ImageData::ImageBase64Dict { image_base64 } => base64::engine::general_purpose::STANDARD
+                .decode(image_base64)
+                .ok(),
+        };
+        let Some(decoded) = bytes.and_then(|b| image::load_from_memory(&b).ok()) else {
+            return image;
+        };
+
+        let started = Instant::now();
+        let rotation = self.detect_orientation(&decoded);
+        debug!(
+            rotation = rotation.degrees(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "OCR方向检测完成"
+        );
+        if rotation == preprocess::Rotation::None {
+            return image;
+        }
+        match preprocess::encode_image(&rotation.apply(&decoded), ImageFormat::Png) {
+            Some(encoded) => ImageData::ImageBase64Dict {
+                image_base64: base64::engine::general_purpose::STANDARD.encode(encoded),
+            },
+            None => image,
+        }
+    }
+}
+
+fn orientation_score(contents: &[ContentData]) -> f64 {
+    contents
+        .iter()
+        .map(|c| c.score * c.text.chars().count() as f64)
+        .sum()
+}
+
 impl Drop for ExtractorHandle {
     fn drop(&mut self) {
         if let Some(engine) = self.engine.take() {
+            self.pool.busy.lock().remove(&engine.id);
+            if let Some(reason) = engine.retire_reason(&pool_tuning()) {
+                ENGINE_RETIRED.fetch_add(1, Ordering::Relaxed);
+                info!(
+                    engine_id = engine.id,
+                    pid = engine.pid(),
+                    reason = %reason,
+                    "OCR引擎达到回收条件，已退役"
+                );
+                return;
+            }
             let mut engines = self.pool.engines.lock();
             if let Some(slot) = engines.iter_mut().find(|e| e.is_none()) {
                 *slot = Some(engine);
@@ -864,6 +1096,7 @@ impl ExtractorPool {
         Self {
             inner: Arc::new(PoolInner {
                 engines: parking_lot::Mutex::new(Vec::with_capacity(max)),
+                busy: parking_lot::Mutex::new(HashMap::new()),
                 opts: parking_lot::Mutex::new(None),
                 circuit: parking_lot::Mutex::new(CircuitState::new()),
                 max,
@@ -873,7 +1106,14 @@ impl ExtractorPool {
     }
 
     fn auto_size() -> usize {
-        6
+        let sizing = recommend_pool_size(pool_tuning().engine_memory_mb);
+        info!(
+            recommended = sizing.recommended,
+            cpu_based = sizing.cpu_based,
+            memory_based = sizing.memory_based,
+            "OCR引擎池容量按CPU与可用内存自动确定"
+        );
+        sizing.recommended
     }
 
     pub fn new_auto() -> Self {
@@ -906,6 +1146,7 @@ impl ExtractorPool {
             drop(engines);
             if let Some(ref mut engine) = eng {
                 engine.ensure_running();
+                self.inner.track_busy(engine);
             }
             return Ok(ExtractorHandle {
                 pool: self.inner.clone(),
@@ -922,6 +1163,7 @@ impl ExtractorPool {
             let mut eng = Extractor::new_with_options(opts)
                 .map_err(|e| format!("spawn OCR engine failed: {}", e))?;
             eng.ensure_running();
+            self.inner.track_busy(&eng);
             return Ok(ExtractorHandle {
                 pool: self.inner.clone(),
                 engine: Some(eng),
@@ -933,6 +1175,83 @@ impl ExtractorPool {
         drop(permit);
         Err("no engine available".to_string())
     }
+
+    /// 回收空闲超时的引擎（保留 `min_idle_engines` 个），并退役超出内存或次数上限的空闲引擎
+    pub fn reap_idle(&self) -> usize {
+        let tuning = pool_tuning();
+        let idle_timeout = tuning
+            .idle_timeout_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        let mut retired = Vec::new();
+        {
+            let mut engines = self.inner.engines.lock();
+            let mut idle = engines.iter().filter(|e| e.is_some()).count();
+            for slot in engines.iter_mut() {
+                let Some(engine) = slot.as_ref() else {
+                    continue;
+                };
+                let reason = engine.retire_reason(&tuning).or_else(|| {
+                    let timeout = idle_timeout?;
+                    let idle_for = engine.last_used.elapsed();
+                    (idle > tuning.min_idle_engines && idle_for >= timeout)
+                        .then(|| format!("空闲{}秒", idle_for.as_secs()))
+                });
+                if let Some(reason) = reason {
+                    idle -= 1;
+                    if let Some(engine) = slot.take() {
+                        retired.push((engine, reason));
+                    }
+                }
+            }
+        }
+
+        let count = retired.len();
+        for (engine, reason) in retired {
+            info!(
+                engine_id = engine.id,
+                pid = engine.pid(),
+                reason = %reason,
+                "回收空闲OCR引擎"
+            );
+        }
+        if count > 0 {
+            ENGINE_RETIRED.fetch_add(count as u64, Ordering::Relaxed);
+        }
+        count
+    }
+}
+
+impl PoolInner {
+    fn track_busy(&self, engine: &Extractor) {
+        self.busy.lock().insert(
+            engine.id,
+            BusyEngine {
+                pid: engine.pid(),
+                requests_served: engine.requests_served,
+                started_at: engine.started_at,
+            },
+        );
+    }
+}
+
+static REAPER_STARTED: OnceLock<()> = OnceLock::new();
+
+/// 启动全局引擎池的后台回收线程，重复调用无副作用
+pub fn spawn_pool_reaper() {
+    if REAPER_STARTED.set(()).is_err() {
+        return;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("ocr-pool-reaper".to_string())
+        .spawn(|| loop {
+            let interval = pool_tuning().reap_interval_secs.max(1);
+            std::thread::sleep(Duration::from_secs(interval));
+            GLOBAL_POOL.reap_idle();
+        });
+    if let Err(err) = spawned {
+        warn!("启动OCR引擎池回收线程失败: {}", err);
+    }
 }
 
 static POOL_CAPACITY_OVERRIDE: OnceLock<usize> = OnceLock::new();
@@ -949,6 +1268,27 @@ pub static GLOBAL_POOL: LazyLock<ExtractorPool> = LazyLock::new(|| {
 static ENGINE_STARTED: AtomicU64 = AtomicU64::new(0);
 static ENGINE_RESTARTED: AtomicU64 = AtomicU64::new(0);
 static ENGINE_FAILURES: AtomicU64 = AtomicU64::new(0);
+static ENGINE_RETIRED: AtomicU64 = AtomicU64::new(0);
+static NEXT_ENGINE_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineState {
+    Idle,
+    Busy,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EngineStats {
+    pub id: u64,
+    pub pid: u32,
+    pub state: EngineState,
+    /// 自上次启动（或重启）以来处理的请求数
+    pub requests_served: u64,
+    pub rss_kb: Option<u64>,
+    pub age_secs: u64,
+    pub idle_secs: u64,
+}
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct PoolStats {
@@ -958,9 +1298,12 @@ pub struct PoolStats {
     pub total_started: u64,
     pub total_restarted: u64,
     pub total_failures: u64,
+    pub total_retired: u64,
     pub consecutive_failures: u32,
     pub circuit_open: bool,
     pub circuit_open_until_epoch: Option<u64>,
+    pub total_rss_kb: u64,
+    pub engines: Vec<EngineStats>,
 }
 
 impl ExtractorPool {
@@ -972,6 +1315,27 @@ impl ExtractorPool {
         let circuit_open_until_epoch = circuit_until_epoch
             .and_then(|ts| ts.duration_since(UNIX_EPOCH).ok())
             .map(|d| d.as_secs());
+
+        let mut engines: Vec<EngineStats> = self
+            .inner
+            .engines
+            .lock()
+            .iter()
+            .flatten()
+            .map(|engine| engine.stats(EngineState::Idle))
+            .collect();
+        engines.extend(self.inner.busy.lock().iter().map(|(id, busy)| EngineStats {
+            id: *id,
+            pid: busy.pid,
+            state: EngineState::Busy,
+            requests_served: busy.requests_served,
+            rss_kb: read_process_rss_kb(busy.pid),
+            age_secs: busy.started_at.elapsed().as_secs(),
+            idle_secs: 0,
+        }));
+        engines.sort_by_key(|engine| engine.id);
+        let total_rss_kb = engines.iter().filter_map(|engine| engine.rss_kb).sum();
+
         PoolStats {
             capacity,
             available,
@@ -979,9 +1343,12 @@ impl ExtractorPool {
             total_started: ENGINE_STARTED.load(Ordering::Relaxed),
             total_restarted: ENGINE_RESTARTED.load(Ordering::Relaxed),
             total_failures: ENGINE_FAILURES.load(Ordering::Relaxed),
+            total_retired: ENGINE_RETIRED.load(Ordering::Relaxed),
             consecutive_failures,
             circuit_open,
             circuit_open_until_epoch,
+            total_rss_kb,
+            engines,
         }
     }
 }
@@ -994,3 +1361,28 @@ pub fn configure_pool_capacity(capacity: usize) {
     let normalized = capacity.clamp(1, 128);
     let _ = POOL_CAPACITY_OVERRIDE.set(normalized);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_kb_fields() {
+        let status = "Name:\tPaddleOCR-json\nVmPeak:\t  901234 kB\nVmRSS:\t  412345 kB\n";
+        assert_eq!(parse_proc_kb_field(status, "VmRSS:"), Some(412_345));
+        let meminfo = "MemTotal:       16314372 kB\nMemAvailable:    8123456 kB\n";
+        assert_eq!(parse_proc_kb_field(meminfo, "MemAvailable:"), Some(8_123_456));
+        assert_eq!(parse_proc_kb_field(meminfo, "SwapTotal:"), None);
+    }
+
+    #[test]
+    fn recommendation_stays_in_bounds() {
+        let sizing = recommend_pool_size(u64::MAX);
+        assert!((1..=AUTO_POOL_MAX).contains(&sizing.recommended));
+        assert!(sizing.recommended <= sizing.cpu_based);
+        if sizing.available_memory_mb.is_some() {
+            assert_eq!(sizing.memory_based, 1);
+            assert_eq!(sizing.recommended, 1);
+        }
+    }
+}
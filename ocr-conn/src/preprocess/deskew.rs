@@ -0,0 +1,120 @@
+use super::geometry::{analysis_thumbnail, otsu_threshold};
+use image::DynamicImage;
+
+const COARSE_STEP_DEGREES: f32 = 1.0;
+const FINE_STEP_DEGREES: f32 = 0.1;
+/// 前景像素占比过低（空白页）或过高（整图暗底）时不做纠偏
+const MIN_INK_RATIO: f64 = 0.002;
+const MAX_INK_RATIO: f64 = 0.5;
+
+/// 基于投影轮廓估计文字行倾角（度，y 轴向下时顺时针为正）。
+///
+/// 对每个候选角度把前景像素投影到旋转后的纵轴上，文字行对齐时行投影最“尖锐”，
+/// 以相邻行差值平方和作为评分，先粗搜再在最优角附近细搜。
+pub(crate) fn estimate_skew_degrees(
+    image: &DynamicImage,
+    analysis_max_dimension: u32,
+    max_degrees: f32,
+) -> Option<f32> {
+    let (gray, _) = analysis_thumbnail(image, analysis_max_dimension);
+    let (w, h) = gray.dimensions();
+    if w < 32 || h < 32 {
+        return None;
+    }
+
+    let threshold = otsu_threshold(&gray);
+    let ink: Vec<(f32, f32)> = gray
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p.0[0] < threshold)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    let ratio = ink.len() as f64 / (w as f64 * h as f64);
+    if !(MIN_INK_RATIO..=MAX_INK_RATIO).contains(&ratio) {
+        return None;
+    }
+
+    let max_degrees = max_degrees.abs().clamp(0.5, 45.0);
+    let bins = (h + 2 * w) as usize;
+    let score = |degrees: f32| profile_sharpness(&ink, degrees, w as f32, bins);
+
+    let mut best = (0.0f32, score(0.0));
+    let mut angle = -max_degrees;
+    while angle <= max_degrees {
+        let s = score(angle);
+        if s > best.1 {
+            best = (angle, s);
+        }
+        angle += COARSE_STEP_DEGREES;
+    }
+
+    let mut angle = best.0 - COARSE_STEP_DEGREES;
+    let end = best.0 + COARSE_STEP_DEGREES;
+    while angle <= end {
+        let s = score(angle);
+        if s > best.1 {
+            best = (angle, s);
+        }
+        angle += FINE_STEP_DEGREES;
+    }
+
+    Some(best.0)
+}
+
+fn profile_sharpness(ink: &[(f32, f32)], degrees: f32, width: f32, bins: usize) -> f64 {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // 平移保证投影坐标非负
+    let offset = width * sin.abs();
+    let mut profile = vec![0u32; bins + 1];
+    for &(x, y) in ink {
+        let projected = y * cos - x * sin + offset;
+        if projected >= 0.0 {
+            let idx = projected as usize;
+            if idx < profile.len() {
+                profile[idx] += 1;
+            }
+        }
+    }
+    profile
+        .windows(2)
+        .map(|pair| {
+            let diff = pair[1] as f64 - pair[0] as f64;
+            diff * diff
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn skewed_lines(degrees: f32) -> DynamicImage {
+        let tan = degrees.to_radians().tan();
+        let img = GrayImage::from_fn(400, 300, |x, y| {
+            let base = y as f32 - x as f32 * tan;
+            // 每 30 像素一行、行高 6 像素的“文字”
+            let in_line = base > 20.0 && (base as i32).rem_euclid(30) < 6 && x > 20 && x < 380;
+            if in_line {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        });
+        DynamicImage::ImageLuma8(img)
+    }
+
+    #[test]
+    fn detects_synthetic_skew() {
+        let detected = estimate_skew_degrees(&skewed_lines(4.0), 1000, 10.0).unwrap();
+        assert!((detected - 4.0).abs() < 0.5, "detected {}", detected);
+
+        let detected = estimate_skew_degrees(&skewed_lines(-3.0), 1000, 10.0).unwrap();
+        assert!((detected + 3.0).abs() < 0.5, "detected {}", detected);
+    }
+
+    #[test]
+    fn blank_page_is_skipped() {
+        let blank = DynamicImage::ImageLuma8(GrayImage::from_pixel(200, 200, Luma([255])));
+        assert!(estimate_skew_degrees(&blank, 1000, 10.0).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, Rgba, RgbaImage};
+
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// 生成用于检测的灰度缩略图，返回缩略图及其相对原图的缩放比例
+pub(crate) fn analysis_thumbnail(image: &DynamicImage, max_dimension: u32) -> (GrayImage, f32) {
+    let gray = image.to_luma8();
+    let (w, h) = gray.dimensions();
+    let longest = w.max(h).max(1);
+    let max_dimension = max_dimension.max(64);
+    if longest <= max_dimension {
+        return (gray, 1.0);
+    }
+    let scale = max_dimension as f32 / longest as f32;
+    let tw = ((w as f32 * scale).round() as u32).max(1);
+    let th = ((h as f32 * scale).round() as u32).max(1);
+    (imageops::resize(&gray, tw, th, FilterType::Triangle), scale)
+}
+
+/// Otsu 阈值，返回亮类的最小灰度：`< 阈值` 为暗像素
+pub(crate) fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 128;
+    }
+    let sum_all: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| value as f64 * *count as f64)
+        .sum();
+
+    let mut sum_bg = 0.0;
+    let mut weight_bg = 0u64;
+    let mut best_threshold = 128u8;
+    let mut best_variance = 0.0;
+    for (value, count) in histogram.iter().enumerate() {
+        weight_bg += count;
+        if weight_bg == 0 {
+            continue;
+        }
+        let weight_fg = total - weight_bg;
+        if weight_fg == 0 {
+            break;
+        }
+        sum_bg += value as f64 * *count as f64;
+        let mean_bg = sum_bg / weight_bg as f64;
+        let mean_fg = (sum_all - sum_bg) / weight_fg as f64;
+        let variance = weight_bg as f64 * weight_fg as f64 * (mean_bg - mean_fg).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = (value as u8).saturating_add(1);
+        }
+    }
+    best_threshold
+}
+
+fn sample_bilinear(source: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (w, h) = source.dimensions();
+    if x < 0.0 || y < 0.0 || x > (w - 1) as f32 || y > (h - 1) as f32 {
+        return BACKGROUND;
+    }
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(w - 1);
+    let y1 = (y0 + 1).min(h - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let p00 = source.get_pixel(x0, y0).0;
+    let p10 = source.get_pixel(x1, y0).0;
+    let p01 = source.get_pixel(x0, y1).0;
+    let p11 = source.get_pixel(x1, y1).0;
+    let mut out = [0u8; 4];
+    for channel in 0..4 {
+        let top = p00[channel] as f32 * (1.0 - fx) + p10[channel] as f32 * fx;
+        let bottom = p01[channel] as f32 * (1.0 - fx) + p11[channel] as f32 * fx;
+        out[channel] = (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+    }
+    Rgba(out)
+}
+
+/// 围绕图片中心旋转，使原图中倾角为 `degrees`（y 轴向下）的直线变为水平；
+/// 画布尺寸不变，空白处填白
+pub(crate) fn rotate_degrees(image: &DynamicImage, degrees: f32) -> DynamicImage {
+    let source = image.to_rgba8();
+    let (w, h) = source.dimensions();
+    if w < 2 || h < 2 {
+        return image.clone();
+    }
+    let radians = degrees.to_radians();
+    let (sin, cos) = radians.sin_cos();
+    let cx = (w as f32 - 1.0) / 2.0;
+    let cy = (h as f32 - 1.0) / 2.0;
+
+    let rotated = RgbaImage::from_fn(w, h, |x, y| {
+        let dx = x as f32 - cx;
+        let dy = y as f32 - cy;
+        // 目标像素反向映射回原图坐标
+        let sx = cos * dx - sin * dy + cx;
+        let sy = sin * dx + cos * dy + cy;
+        sample_bilinear(&source, sx, sy)
+    });
+    DynamicImage::ImageRgba8(rotated)
+}
+
+/// 3x3 单应矩阵（行优先，h33 固定为 1）
+pub(crate) type Homography = [f64; 9];
+
+/// 由四组对应点求解单应矩阵：src[i] -> dst[i]
+pub(crate) fn solve_homography(src: &[(f64, f64); 4], dst: &[(f64, f64); 4]) -> Option<Homography> {
+    let mut a = [[0.0f64; 9]; 8];
+    for i in 0..4 {
+        let (x, y) = src[i];
+        let (u, v) = dst[i];
+        a[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        a[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
+
+    // 高斯消元（部分主元）
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&l, &r| {
+            a[l][col]
+                .abs()
+                .partial_cmp(&a[r][col].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if a[pivot][col].abs() < 1e-10 {
+            return None;
+        }
+        a.swap(col, pivot);
+        for row in 0..8 {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col] / a[col][col];
+            if factor == 0.0 {
+                continue;
+            }
+            let pivot_row = a[col];
+            for (value, pivot) in a[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+
+    let mut h = [0.0f64; 9];
+    for i in 0..8 {
+        h[i] = a[i][8] / a[i][i];
+    }
+    h[8] = 1.0;
+    Some(h)
+}
+
+pub(crate) fn apply_homography(h: &Homography, x: f64, y: f64) -> Option<(f64, f64)> {
+    let w = h[6] * x + h[7] * y + h[8];
+    if w.abs() < 1e-12 {
+        return None;
+    }
+    Some((
+        (h[0] * x + h[1] * y + h[2]) / w,
+        (h[3] * x + h[4] * y + h[5]) / w,
+    ))
+}
+
+/// 将原图中的四边形区域（左上、右上、右下、左下）拉正为 width x height 的矩形
+pub(crate) fn warp_quad(
+    image: &DynamicImage,
+    quad: &[(f64, f64); 4],
+    width: u32,
+    height: u32,
+) -> Option<DynamicImage> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+    let target = [
+        (0.0, 0.0),
+        ((width - 1) as f64, 0.0),
+        ((width - 1) as f64, (height - 1) as f64),
+        (0.0, (height - 1) as f64),
+    ];
+    // 目标坐标 -> 原图坐标，便于逐像素反向采样
+    let h = solve_homography(&target, quad)?;
+    let source = image.to_rgba8();
+    let warped = RgbaImage::from_fn(width, height, |x, y| {
+        match apply_homography(&h, x as f64, y as f64) {
+            Some((sx, sy)) => sample_bilinear(&source, sx as f32, sy as f32),
+            None => BACKGROUND,
+        }
+    });
+    Some(DynamicImage::ImageRgba8(warped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn homography_maps_corners() {
+        let src = [(0.0, 0.0), (100.0, 0.0), (100.0, 50.0), (0.0, 50.0)];
+        let dst = [(10.0, 5.0), (120.0, 0.0), (110.0, 70.0), (0.0, 60.0)];
+        let h = solve_homography(&src, &dst).expect("solvable");
+        for (s, d) in src.iter().zip(dst.iter()) {
+            let (u, v) = apply_homography(&h, s.0, s.1).unwrap();
+            assert!((u - d.0).abs() < 1e-6 && (v - d.1).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn degenerate_quad_is_rejected() {
+        let src = [(0.0, 0.0), (0.0, 0.0), (0.0, 0.0), (0.0, 0.0)];
+        let dst = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        assert!(solve_homography(&src, &dst).is_none());
+    }
+
+    #[test]
+    fn otsu_splits_bimodal_image() {
+        let gray = GrayImage::from_fn(20, 20, |x, _| {
+            if x < 10 {
+                image::Luma([30])
+            } else {
+                image::Luma([220])
+            }
+        });
+        let threshold = otsu_threshold(&gray);
+        assert!((30..220).contains(&threshold));
+    }
+}
@@ -0,0 +1,383 @@
+mod deskew;
+pub(crate) mod geometry;
+mod orientation;
+mod perspective;
+
+pub use orientation::{detect_orientation_by_score, Rotation};
+
+use image::imageops;
+use image::metadata::Orientation;
+use image::{codecs::jpeg::JpegDecoder, DynamicImage, ImageDecoder, ImageFormat};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+const CONTRAST_BOOST: f32 = 12.0;
+const DEFAULT_MAX_DESKEW_DEGREES: f32 = 15.0;
+const DEFAULT_MIN_DESKEW_DEGREES: f32 = 0.3;
+const DEFAULT_MIN_DOCUMENT_AREA_RATIO: f32 = 0.25;
+const DEFAULT_ANALYSIS_MAX_DIMENSION: u32 = 1000;
+
+/// 预处理流水线中的单个步骤，按配置顺序依次执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessStep {
+    /// 按 EXIF 方向标记旋转（仅 JPEG）；与其他步骤一样只在配置的位置执行一次，
+    /// 未配置时不读取标记
+    ExifOrientation,
+    /// 基于投影轮廓判断横竖版并旋转 90°
+    Orientation,
+    /// 文档边框检测 + 透视校正
+    Perspective,
+    /// 基于投影轮廓的小角度纠偏
+    Deskew,
+    /// 固定幅度的对比度增强
+    Contrast,
+}
+
+impl PreprocessStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreprocessStep::ExifOrientation => "exif_orientation",
+            PreprocessStep::Orientation => "orientation",
+            PreprocessStep::Perspective => "perspective",
+            PreprocessStep::Deskew => "deskew",
+            PreprocessStep::Contrast => "contrast",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "exif_orientation" | "exif" => Some(PreprocessStep::ExifOrientation),
+            "orientation" | "rotate" => Some(PreprocessStep::Orientation),
+            "perspective" | "dewarp" => Some(PreprocessStep::Perspective),
+            "deskew" => Some(PreprocessStep::Deskew),
+            "contrast" => Some(PreprocessStep::Contrast),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessOptions {
+    pub enabled: bool,
+    pub steps: Vec<PreprocessStep>,
+    pub contrast_boost: f32,
+    /// 纠偏搜索的最大角度（度）
+    pub max_deskew_degrees: f32,
+    /// 小于该角度不做纠偏，避免无意义的重采样
+    pub min_deskew_degrees: f32,
+    /// 文档区域占整图比例下限，低于该值视为未检测到边框
+    pub min_document_area_ratio: f32,
+    /// 检测用缩略图的最长边
+    pub analysis_max_dimension: u32,
+    /// 是否在识别前用 OCR 对四个方向的缩略图打分选择方向
+    pub ocr_orientation: bool,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            // 与引入可配置流水线前的行为一致；几何校正步骤按需显式开启
+            steps: vec![PreprocessStep::ExifOrientation, PreprocessStep::Contrast],
+            contrast_boost: CONTRAST_BOOST,
+            max_deskew_degrees: DEFAULT_MAX_DESKEW_DEGREES,
+            min_deskew_degrees: DEFAULT_MIN_DESKEW_DEGREES,
+            min_document_area_ratio: DEFAULT_MIN_DOCUMENT_AREA_RATIO,
+            analysis_max_dimension: DEFAULT_ANALYSIS_MAX_DIMENSION,
+            ocr_orientation: false,
+        }
+    }
+}
+
+/// 单个步骤的执行记录
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub step: PreprocessStep,
+    pub applied: bool,
+    pub duration_ms: f64,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreprocessReport {
+    pub steps: Vec<StepReport>,
+    pub total_ms: f64,
+}
+
+/// 各步骤的累计耗时统计，供监控接口展示
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StepStats {
+    pub step: String,
+    pub runs: u64,
+    pub applied: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+static OPTIONS: LazyLock<RwLock<PreprocessOptions>> =
+    LazyLock::new(|| RwLock::new(PreprocessOptions::default()));
+static STATS: LazyLock<Mutex<BTreeMap<PreprocessStep, StepStats>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// 替换全局预处理配置，服务启动时根据配置文件调用
+pub fn configure(options: PreprocessOptions) {
+    debug!(
+        steps = ?options.steps,
+        enabled = options.enabled,
+        ocr_orientation = options.ocr_orientation,
+        "OCR预处理流水线已配置"
+    );
+    *OPTIONS.write() = options;
+}
+
+pub fn current_options() -> PreprocessOptions {
+    OPTIONS.read().clone()
+}
+
+pub fn preprocess_stats() -> Vec<StepStats> {
+    STATS.lock().values().cloned().collect()
+}
+
+pub fn preprocess_bytes(input: &[u8]) -> Option<Vec<u8>> {
+    let options = current_options();
+    preprocess_bytes_with(input, &options).map(|(bytes, _)| bytes)
+}
+
+pub fn preprocess_bytes_with(
+    input: &[u8],
+    options: &PreprocessOptions,
+) -> Option<(Vec<u8>, PreprocessReport)> {
+    if !options.enabled || options.steps.is_empty() {
+        return None;
+    }
+    let format = image::guess_format(input).ok()?;
+    let image = image::load_from_memory(input).ok()?;
+    let (processed, report) = run_pipeline(image, input, format, options);
+    let encoded = encode_image(&processed, ImageFormat::Png)?;
+    Some((encoded, report))
+}
+
+/// 解码并按当前配置预处理，得到送入引擎的同一张图；表格线等需与文本框同一坐标系的分析用它。
+/// 未启用预处理时返回原图
+pub fn preprocessed_image(input: &[u8]) -> Option<DynamicImage> {
+    let options = current_options();
+    let image = image::load_from_memory(input).ok()?;
+    if !options.enabled || options.steps.is_empty() {
+        return Some(image);
+    }
+    let format = image::guess_format(input).ok()?;
+    Some(run_pipeline(image, input, format, &options).0)
+}
+
+pub fn preprocess_file_in_place(path: &Path) -> std::io::Result<bool> {
+    let bytes = fs::read(path)?;
+    let Some(processed) = preprocess_bytes(&bytes) else {
+        return Ok(false);
+    };
+    fs::write(path, processed)?;
+    Ok(true)
+}
+
+fn run_pipeline(
+    mut image: DynamicImage,
+    raw: &[u8],
+    format: ImageFormat,
+    options: &PreprocessOptions,
+) -> (DynamicImage, PreprocessReport) {
+    let pipeline_start = Instant::now();
+    let mut report = PreprocessReport::default();
+
+    for step in &options.steps {
+        let started = Instant::now();
+        let (next, applied, detail) = match step {
+            PreprocessStep::ExifOrientation => match read_orientation(raw, format) {
+                Some(orientation) => {
+                    let mut rotated = image;
+                    rotated.apply_orientation(orientation);
+                    (rotated, true, Some(format!("{:?}", orientation)))
+                }
+                None => (image, false, None),
+            },
+            PreprocessStep::Orientation => {
+                match orientation::detect_orientation_by_profile(
+                    &image,
+                    options.analysis_max_dimension,
+                ) {
+                    Some(rotation) if rotation != Rotation::None => (
+                        rotation.apply(&image),
+                        true,
+                        Some(format!("rotate {}°", rotation.degrees())),
+                    ),
+                    _ => (image, false, None),
+                }
+            }
+            PreprocessStep::Perspective => match perspective::correct_perspective(
+                &image,
+                options.analysis_max_dimension,
+                options.min_document_area_ratio,
+            ) {
+                Some((warped, detail)) => (warped, true, Some(detail)),
+                None => (image, false, None),
+            },
+            PreprocessStep::Deskew => {
+                let angle = deskew::estimate_skew_degrees(
+                    &image,
+                    options.analysis_max_dimension,
+                    options.max_deskew_degrees,
+                );
+                match angle {
+                    Some(angle) if angle.abs() >= options.min_deskew_degrees => (
+                        geometry::rotate_degrees(&image, angle),
+                        true,
+                        Some(format!("skew {:.2}°", angle)),
+                    ),
+                    _ => (image, false, None),
+                }
+            }
+            PreprocessStep::Contrast => {
+                (enhance_contrast(image, options.contrast_boost), true, None)
+            }
+        };
+        image = next;
+
+        let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+        record_step(*step, applied, duration_ms);
+        report.steps.push(StepReport {
+            step: *step,
+            applied,
+            duration_ms,
+            detail,
+        });
+    }
+
+    report.total_ms = pipeline_start.elapsed().as_secs_f64() * 1000.0;
+    debug!(
+        total_ms = report.total_ms,
+        steps = %report
+            .steps
+            .iter()
+            .map(|s| format!(
+                "{}{}={:.1}ms",
+                s.step.as_str(),
+                if s.applied { "*" } else { "" },
+                s.duration_ms
+            ))
+            .collect::<Vec<_>>()
+            .join(","),
+        "OCR图片预处理完成"
+    );
+    if report.total_ms > 2_000.0 {
+        warn!(total_ms = report.total_ms, "OCR图片预处理耗时过长");
+    }
+
+    (image, report)
+}
+
+fn record_step(step: PreprocessStep, applied: bool, duration_ms: f64) {
+    let mut stats = STATS.lock();
+    let entry = stats.entry(step).or_insert_with(|| StepStats {
+        step: step.as_str().to_string(),
+        ..StepStats::default()
+    });
+    entry.runs += 1;
+    if applied {
+        entry.applied += 1;
+    }
+    entry.total_ms += duration_ms;
+    entry.max_ms = entry.max_ms.max(duration_ms);
+}
+
+fn enhance_contrast(image: DynamicImage, boost: f32) -> DynamicImage {
+    let rgba = image.to_rgba8();
+    let adjusted = imageops::contrast(&rgba, boost);
+    DynamicImage::ImageRgba8(adjusted)
+}
+
+pub fn encode_image(image: &DynamicImage, format: ImageFormat) -> Option<Vec<u8>> {
+    let mut cursor = Cursor::new(Vec::new());
+    let preferred = match format {
+        ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Bmp | ImageFormat::Tiff => format,
+        _ => ImageFormat::Png,
+    };
+    image.write_to(&mut cursor, preferred).ok()?;
+    Some(cursor.into_inner())
+}
+
+fn read_orientation(bytes: &[u8], format: ImageFormat) -> Option<Orientation> {
+    match format {
+        ImageFormat::Jpeg => {
+            let cursor = Cursor::new(bytes);
+            let mut decoder = JpegDecoder::new(cursor).ok()?;
+            decoder
+                .orientation()
+                .ok()
+                .filter(|orientation| *orientation != Orientation::NoTransforms)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, Rgb, RgbImage};
+
+    /// 4x2 的 JPEG，带 EXIF 方向标记 6（需顺时针旋转 90°）
+    fn tagged_jpeg() -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 2, Rgb([200, 200, 200])));
+        let jpeg = encode_image(&image, ImageFormat::Jpeg).unwrap();
+        let mut app1 = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        app1.extend_from_slice(&[0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0]);
+
+        let mut tagged = jpeg[..2].to_vec();
+        tagged.extend_from_slice(&[0xFF, 0xE1]);
+        tagged.extend_from_slice(&(app1.len() as u16 + 2).to_be_bytes());
+        tagged.extend_from_slice(&app1);
+        tagged.extend_from_slice(&jpeg[2..]);
+        tagged
+    }
+
+    fn run(raw: &[u8], steps: Vec<PreprocessStep>) -> (DynamicImage, PreprocessReport) {
+        let options = PreprocessOptions {
+            steps,
+            ..PreprocessOptions::default()
+        };
+        let image = image::load_from_memory(raw).unwrap();
+        run_pipeline(image, raw, ImageFormat::Jpeg, &options)
+    }
+
+    #[test]
+    fn default_steps_exclude_geometric_correction() {
+        assert_eq!(
+            PreprocessOptions::default().steps,
+            vec![PreprocessStep::ExifOrientation, PreprocessStep::Contrast]
+        );
+    }
+
+    #[test]
+    fn steps_run_only_where_configured() {
+        let raw = tagged_jpeg();
+        assert_eq!(
+            read_orientation(&raw, ImageFormat::Jpeg),
+            Some(Orientation::Rotate90)
+        );
+
+        let order = vec![PreprocessStep::Contrast, PreprocessStep::ExifOrientation];
+        let (image, report) = run(&raw, order.clone());
+        assert_eq!(image.dimensions(), (2, 4));
+        let executed: Vec<_> = report.steps.iter().map(|s| s.step).collect();
+        assert_eq!(executed, order);
+
+        let (image, report) = run(&raw, vec![PreprocessStep::Contrast]);
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(report.steps.len(), 1);
+    }
+}
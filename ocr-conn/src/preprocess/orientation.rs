@@ -0,0 +1,144 @@
+use super::geometry::{analysis_thumbnail, otsu_threshold};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::Serialize;
+
+/// 行投影与列投影锐度之比超过该值才认为图片是竖版文字
+const PROFILE_ROTATE_RATIO: f64 = 1.6;
+/// OCR 打分时，其他方向需比原方向高出该比例才旋转，避免抖动
+const SCORE_MARGIN: f64 = 1.2;
+
+/// 顺时针旋转角度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::None,
+        Rotation::Clockwise90,
+        Rotation::Clockwise180,
+        Rotation::Clockwise270,
+    ];
+
+    pub fn degrees(&self) -> u32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 90,
+            Rotation::Clockwise180 => 180,
+            Rotation::Clockwise270 => 270,
+        }
+    }
+
+    pub fn apply(&self, image: &DynamicImage) -> DynamicImage {
+        match self {
+            Rotation::None => image.clone(),
+            Rotation::Clockwise90 => image.rotate90(),
+            Rotation::Clockwise180 => image.rotate180(),
+            Rotation::Clockwise270 => image.rotate270(),
+        }
+    }
+}
+
+/// 通过投影轮廓区分横排/竖排：横排文字的行投影起伏明显，列投影平缓。
+///
+/// 只能判断是否需要转 90°，无法区分 90°/270° 及 180°，这类情况需借助
+/// [`detect_orientation_by_score`] 用 OCR 置信度判断。
+pub(crate) fn detect_orientation_by_profile(
+    image: &DynamicImage,
+    analysis_max_dimension: u32,
+) -> Option<Rotation> {
+    let (gray, _) = analysis_thumbnail(image, analysis_max_dimension);
+    let (w, h) = gray.dimensions();
+    if w < 32 || h < 32 {
+        return None;
+    }
+    let threshold = otsu_threshold(&gray);
+    let mut rows = vec![0u32; h as usize];
+    let mut cols = vec![0u32; w as usize];
+    let mut ink = 0u64;
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        if pixel.0[0] < threshold {
+            rows[y as usize] += 1;
+            cols[x as usize] += 1;
+            ink += 1;
+        }
+    }
+    if ink == 0 {
+        return None;
+    }
+
+    let row_sharpness = normalized_sharpness(&rows, w);
+    let col_sharpness = normalized_sharpness(&cols, h);
+    if col_sharpness > row_sharpness * PROFILE_ROTATE_RATIO {
+        Some(Rotation::Clockwise90)
+    } else {
+        Some(Rotation::None)
+    }
+}
+
+fn normalized_sharpness(profile: &[u32], span: u32) -> f64 {
+    if profile.len() < 2 || span == 0 {
+        return 0.0;
+    }
+    let sum: f64 = profile
+        .windows(2)
+        .map(|pair| {
+            let diff = (pair[1] as f64 - pair[0] as f64) / span as f64;
+            diff * diff
+        })
+        .sum();
+    sum / (profile.len() - 1) as f64
+}
+
+/// 对四个方向的缩略图打分并选择得分最高的方向。
+///
+/// `scorer` 通常是一次 OCR 调用（如“置信度 × 字符数”），返回 `None` 表示该方向识别失败。
+/// 原方向得分足够高时直接返回，省去其余三次调用。
+pub fn detect_orientation_by_score<F>(
+    image: &DynamicImage,
+    thumbnail_max_dimension: u32,
+    good_enough: f64,
+    mut scorer: F,
+) -> Rotation
+where
+    F: FnMut(&DynamicImage) -> Option<f64>,
+{
+    let (w, h) = (image.width(), image.height());
+    let longest = w.max(h).max(1);
+    let thumbnail = if longest > thumbnail_max_dimension {
+        let scale = thumbnail_max_dimension as f32 / longest as f32;
+        image.resize(
+            ((w as f32 * scale) as u32).max(1),
+            ((h as f32 * scale) as u32).max(1),
+            FilterType::Triangle,
+        )
+    } else {
+        image.clone()
+    };
+
+    let base = scorer(&thumbnail).unwrap_or(0.0);
+    if base >= good_enough {
+        return Rotation::None;
+    }
+
+    let mut best = (Rotation::None, base);
+    for rotation in Rotation::ALL.iter().skip(1) {
+        let Some(score) = scorer(&rotation.apply(&thumbnail)) else {
+            continue;
+        };
+        if score > best.1 {
+            best = (*rotation, score);
+        }
+    }
+
+    if best.0 != Rotation::None && best.1 > base * SCORE_MARGIN {
+        best.0
+    } else {
+        Rotation::None
+    }
+}
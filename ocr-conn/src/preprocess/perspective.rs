@@ -0,0 +1,136 @@
+use super::geometry::{analysis_thumbnail, otsu_threshold, warp_quad};
+use image::{DynamicImage, GrayImage};
+
+/// 文档区域几乎占满整图时说明已是扫描件，无需校正
+const MAX_DOCUMENT_AREA_RATIO: f64 = 0.95;
+/// 背景采样边框宽度（占短边比例）
+const BORDER_SAMPLE_RATIO: f32 = 0.03;
+/// 每行/列至少需要的文档像素占比，过滤零星噪点
+const MIN_LINE_FILL_RATIO: f32 = 0.05;
+
+/// 检测拍照文档的四边形边框并做透视拉正，返回校正后的图片与描述信息。
+///
+/// 以图片边框的平均亮度作为背景，与之相反一侧的 Otsu 前景视为文档；
+/// 四个角分别取 x+y、x−y 的极值点，适用于纸张与桌面对比明显的照片。
+pub(crate) fn correct_perspective(
+    image: &DynamicImage,
+    analysis_max_dimension: u32,
+    min_area_ratio: f32,
+) -> Option<(DynamicImage, String)> {
+    let (gray, scale) = analysis_thumbnail(image, analysis_max_dimension);
+    let (w, h) = gray.dimensions();
+    if w < 32 || h < 32 {
+        return None;
+    }
+
+    let threshold = otsu_threshold(&gray);
+    let document_is_bright = border_mean(&gray) < threshold as f64;
+    let is_document = |value: u8| {
+        if document_is_bright {
+            value >= threshold
+        } else {
+            value < threshold
+        }
+    };
+
+    let row_fill = filled_lines(&gray, &is_document, true);
+    let col_fill = filled_lines(&gray, &is_document, false);
+
+    let mut tl = (f64::MAX, (0.0, 0.0));
+    let mut br = (f64::MIN, (0.0, 0.0));
+    let mut tr = (f64::MIN, (0.0, 0.0));
+    let mut bl = (f64::MAX, (0.0, 0.0));
+    let mut count = 0u64;
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        if !is_document(pixel.0[0]) || !row_fill[y as usize] || !col_fill[x as usize] {
+            continue;
+        }
+        count += 1;
+        let (fx, fy) = (x as f64, y as f64);
+        let sum = fx + fy;
+        let diff = fx - fy;
+        if sum < tl.0 {
+            tl = (sum, (fx, fy));
+        }
+        if sum > br.0 {
+            br = (sum, (fx, fy));
+        }
+        if diff > tr.0 {
+            tr = (diff, (fx, fy));
+        }
+        if diff < bl.0 {
+            bl = (diff, (fx, fy));
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+
+    let quad = [tl.1, tr.1, br.1, bl.1];
+    let area_ratio = polygon_area(&quad) / (w as f64 * h as f64);
+    if area_ratio < min_area_ratio as f64 || area_ratio > MAX_DOCUMENT_AREA_RATIO {
+        return None;
+    }
+
+    // 换算回原图坐标
+    let inv = 1.0 / scale as f64;
+    let quad = quad.map(|(x, y)| (x * inv, y * inv));
+    let width = distance(quad[0], quad[1]).max(distance(quad[3], quad[2]));
+    let height = distance(quad[0], quad[3]).max(distance(quad[1], quad[2]));
+    let warped = warp_quad(image, &quad, width.round() as u32, height.round() as u32)?;
+    let detail = format!(
+        "document {:.0}% of frame, warped to {}x{}",
+        area_ratio * 100.0,
+        warped.width(),
+        warped.height()
+    );
+    Some((warped, detail))
+}
+
+fn border_mean(gray: &GrayImage) -> f64 {
+    let (w, h) = gray.dimensions();
+    let band = ((w.min(h) as f32 * BORDER_SAMPLE_RATIO) as u32).max(1);
+    let mut sum = 0u64;
+    let mut count = 0u64;
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        if x < band || y < band || x >= w - band || y >= h - band {
+            sum += pixel.0[0] as u64;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        0.0
+    } else {
+        sum as f64 / count as f64
+    }
+}
+
+fn filled_lines<F>(gray: &GrayImage, is_document: &F, rows: bool) -> Vec<bool>
+where
+    F: Fn(u8) -> bool,
+{
+    let (w, h) = gray.dimensions();
+    let (len, span) = if rows { (h, w) } else { (w, h) };
+    let mut counts = vec![0u32; len as usize];
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        if is_document(pixel.0[0]) {
+            counts[if rows { y } else { x } as usize] += 1;
+        }
+    }
+    let min_fill = (span as f32 * MIN_LINE_FILL_RATIO) as u32;
+    counts.into_iter().map(|c| c > min_fill).collect()
+}
+
+fn polygon_area(points: &[(f64, f64); 4]) -> f64 {
+    let mut acc = 0.0;
+    for i in 0..4 {
+        let (x1, y1) = points[i];
+        let (x2, y2) = points[(i + 1) % 4];
+        acc += x1 * y2 - x2 * y1;
+    }
+    acc.abs() / 2.0
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
@@ -0,0 +1,517 @@
+//! 表格结构还原
+//!
+//! 清单、登记表类材料的 OCR 结果只是一组散落的文本框：按纵向重叠把文本框聚成行，
+//! 按横向区间合并出列，得到单元格网格。若能从图片中检测到表格线且与文本框吻合，
+//! 则优先以表格线划分行列。
+
+use crate::ocr::{ContentData, Rectangle};
+use crate::preprocess::geometry::{analysis_thumbnail, otsu_threshold};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 文本框与当前行纵向重叠超过较矮者高度的该比例即视为同一行
+const ROW_OVERLAP_RATIO: f32 = 0.5;
+/// 暗像素连续长度超过图片宽/高的该比例视为表格线
+const RULING_MIN_LENGTH_RATIO: f32 = 0.5;
+/// 跨越表格线的文本框占比超过该值时，认为表格线与文本框坐标不一致
+const MAX_STRADDLING_RATIO: f32 = 0.2;
+/// 至少有该比例的行包含多个单元格才认为是表格
+const MIN_MULTI_CELL_ROW_RATIO: f32 = 0.5;
+const MIN_ROWS: usize = 2;
+const MIN_COLS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl BoundingBox {
+    pub fn from_rect(rect: &Rectangle) -> Self {
+        let xs = rect.iter().map(|p| p[0] as u32);
+        let ys = rect.iter().map(|p| p[1] as u32);
+        Self {
+            left: xs.clone().min().unwrap_or(0),
+            right: xs.max().unwrap_or(0),
+            top: ys.clone().min().unwrap_or(0),
+            bottom: ys.max().unwrap_or(0),
+        }
+    }
+
+    fn height(&self) -> u32 {
+        self.bottom.saturating_sub(self.top).max(1)
+    }
+
+    fn center_x(&self) -> f32 {
+        (self.left + self.right) as f32 / 2.0
+    }
+
+    fn center_y(&self) -> f32 {
+        (self.top + self.bottom) as f32 / 2.0
+    }
+
+    fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCell {
+    pub row: usize,
+    pub col: usize,
+    pub text: String,
+    pub score: f64,
+    pub bbox: BoundingBox,
+}
+
+/// 还原出的单元格网格，第 0 行按表头处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableGrid {
+    pub rows: usize,
+    pub cols: usize,
+    /// 行列是否由检测到的表格线划分
+    pub ruled: bool,
+    pub cells: Vec<TableCell>,
+}
+
+impl TableGrid {
+    pub fn cell(&self, row: usize, col: usize) -> Option<&TableCell> {
+        self.cells.iter().find(|c| c.row == row && c.col == col)
+    }
+
+    /// 某一行各列文本，缺失的单元格为空串
+    pub fn row_texts(&self, row: usize) -> Vec<String> {
+        (0..self.cols)
+            .map(|col| {
+                self.cell(row, col)
+                    .map(|c| c.text.clone())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    pub fn header(&self) -> Vec<String> {
+        self.row_texts(0)
+    }
+
+    /// 按表头文本查找列，忽略空白，表头包含候选名即匹配
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        let needle = normalize(name);
+        if needle.is_empty() {
+            return None;
+        }
+        self.header()
+            .iter()
+            .position(|header| normalize(header).contains(&needle))
+    }
+
+    /// 指定列除表头外的非空取值
+    pub fn column_values(&self, col: usize) -> Vec<String> {
+        (1..self.rows)
+            .filter_map(|row| self.cell(row, col))
+            .map(|c| c.text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+}
+
+/// 图片中检测到的水平/竖直表格线位置（所检测图片的坐标，须与 OCR 文本框一致）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Rulings {
+    pub width: u32,
+    pub height: u32,
+    pub horizontal: Vec<u32>,
+    pub vertical: Vec<u32>,
+}
+
+/// 以“最长连续暗像素段”检测表格线：文字笔画很短，只有表格线能横跨大半幅图片。
+pub fn detect_rulings(image: &DynamicImage, analysis_max_dimension: u32) -> Rulings {
+    let (gray, scale) = analysis_thumbnail(image, analysis_max_dimension);
+    let (w, h) = gray.dimensions();
+    let mut rulings = Rulings {
+        width: image.width(),
+        height: image.height(),
+        ..Default::default()
+    };
+    if w < 32 || h < 32 {
+        return rulings;
+    }
+
+    let threshold = otsu_threshold(&gray);
+    let dark = |x: u32, y: u32| gray.get_pixel(x, y).0[0] < threshold;
+    let longest_run = |len: u32, at: &dyn Fn(u32) -> bool| {
+        let (mut best, mut run) = (0u32, 0u32);
+        for i in 0..len {
+            if at(i) {
+                run += 1;
+                best = best.max(run);
+            } else {
+                run = 0;
+            }
+        }
+        best
+    };
+
+    let min_h = (w as f32 * RULING_MIN_LENGTH_RATIO) as u32;
+    let rows: Vec<bool> = (0..h)
+        .map(|y| longest_run(w, &|x| dark(x, y)) >= min_h)
+        .collect();
+    let min_v = (h as f32 * RULING_MIN_LENGTH_RATIO) as u32;
+    let cols: Vec<bool> = (0..w)
+        .map(|x| longest_run(h, &|y| dark(x, y)) >= min_v)
+        .collect();
+
+    let inv = 1.0 / scale;
+    rulings.horizontal = merge_lines(&rows, inv);
+    rulings.vertical = merge_lines(&cols, inv);
+    rulings
+}
+
+/// 相邻命中的像素行/列合并为一条线，取中点并换算回原图坐标
+fn merge_lines(hits: &[bool], inv_scale: f32) -> Vec<u32> {
+    let mut lines = Vec::new();
+    let mut start = None;
+    for (i, hit) in hits.iter().chain(std::iter::once(&false)).enumerate() {
+        match (start, *hit) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let mid = (s + i - 1) as f32 / 2.0;
+                lines.push((mid * inv_scale).round() as u32);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// 粗判文本框是否可能构成表格（至少两行各含多个文本框），用于决定是否值得检测表格线
+pub fn looks_tabular(contents: &[ContentData]) -> bool {
+    let items = collect_items(contents);
+    if items.len() < MIN_ROWS * MIN_COLS {
+        return false;
+    }
+    cluster_rows(&items)
+        .iter()
+        .filter(|row| row.len() >= MIN_COLS)
+        .count()
+        >= MIN_ROWS
+}
+
+/// 从 OCR 文本框还原表格；不满足表格特征时返回 `None`
+pub fn reconstruct_table(contents: &[ContentData], rulings: Option<&Rulings>) -> Option<TableGrid> {
+    let items = collect_items(contents);
+    if items.len() < MIN_ROWS * MIN_COLS {
+        return None;
+    }
+
+    let ruled = rulings.filter(|r| rulings_match(r, &items));
+    let (rows, ruled_flag) = match ruled {
+        Some(r) => (assign_rows(&items, &r.horizontal), true),
+        None => (trim_single_rows(cluster_rows(&items)), false),
+    };
+    let rows: Vec<Vec<usize>> = rows.into_iter().filter(|row| !row.is_empty()).collect();
+    if rows.len() < MIN_ROWS {
+        return None;
+    }
+    let multi = rows.iter().filter(|row| row.len() >= MIN_COLS).count();
+    if (multi as f32) < rows.len() as f32 * MIN_MULTI_CELL_ROW_RATIO {
+        return None;
+    }
+
+    let col_of: Vec<Option<usize>> = match ruled {
+        Some(r) => {
+            let bands = bands(&r.vertical);
+            items
+                .iter()
+                .map(|(b, _)| band_index(&bands, b.center_x()))
+                .collect()
+        }
+        None => {
+            let columns = merge_columns(&items, &rows);
+            items
+                .iter()
+                .map(|(b, _)| best_column(&columns, b))
+                .collect()
+        }
+    };
+
+    // 以出现过的列重新编号，去掉空列
+    let mut used_cols: Vec<usize> = rows.iter().flatten().filter_map(|&i| col_of[i]).collect();
+    used_cols.sort_unstable();
+    used_cols.dedup();
+    if used_cols.len() < MIN_COLS {
+        return None;
+    }
+
+    let mut cells = Vec::new();
+    for (row_idx, row) in rows.iter().enumerate() {
+        let mut grouped: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for &i in row {
+            if let Some(col) = col_of[i] {
+                if let Ok(col_idx) = used_cols.binary_search(&col) {
+                    grouped.entry(col_idx).or_default().push(i);
+                }
+            }
+        }
+        for (col_idx, mut members) in grouped {
+            members.sort_by(|a, b| {
+                let (ba, bb) = (&items[*a].0, &items[*b].0);
+                (ba.top, ba.left).cmp(&(bb.top, bb.left))
+            });
+            let text = members
+                .iter()
+                .map(|&i| items[i].1.text.trim())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let score =
+                members.iter().map(|&i| items[i].1.score).sum::<f64>() / members.len() as f64;
+            let bbox = members
+                .iter()
+                .skip(1)
+                .fold(items[members[0]].0, |acc, &i| acc.union(&items[i].0));
+            cells.push(TableCell {
+                row: row_idx,
+                col: col_idx,
+                text,
+                score,
+                bbox,
+            });
+        }
+    }
+
+    Some(TableGrid {
+        rows: rows.len(),
+        cols: used_cols.len(),
+        ruled: ruled_flag,
+        cells,
+    })
+}
+
+fn collect_items(contents: &[ContentData]) -> Vec<(BoundingBox, &ContentData)> {
+    contents
+        .iter()
+        .filter(|c| !c.text.trim().is_empty())
+        .map(|c| (BoundingBox::from_rect(&c.rect), c))
+        .collect()
+}
+
+/// 按中心纵坐标排序后贪心聚行
+fn cluster_rows(items: &[(BoundingBox, &ContentData)]) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|a, b| items[*a].0.center_y().total_cmp(&items[*b].0.center_y()));
+
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    let mut span: Option<(u32, u32, u32)> = None; // (top, bottom, 最矮高度)
+    for i in order {
+        let b = items[i].0;
+        if let Some((top, bottom, min_height)) = span {
+            let overlap = bottom.min(b.bottom).saturating_sub(top.max(b.top));
+            if overlap as f32 >= min_height.min(b.height()) as f32 * ROW_OVERLAP_RATIO {
+                if let Some(row) = rows.last_mut() {
+                    row.push(i);
+                }
+                span = Some((
+                    top.min(b.top),
+                    bottom.max(b.bottom),
+                    min_height.min(b.height()),
+                ));
+                continue;
+            }
+        }
+        rows.push(vec![i]);
+        span = Some((b.top, b.bottom, b.height()));
+    }
+    for row in rows.iter_mut() {
+        row.sort_by_key(|&i| items[i].0.left);
+    }
+    rows
+}
+
+/// 去掉表格上下方只有一个文本框的行（标题、落款）
+fn trim_single_rows(mut rows: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    while rows.first().is_some_and(|row| row.len() < MIN_COLS) {
+        rows.remove(0);
+    }
+    while rows.last().is_some_and(|row| row.len() < MIN_COLS) {
+        rows.pop();
+    }
+    rows
+}
+
+/// 多单元格行的横向区间合并成列
+fn merge_columns(items: &[(BoundingBox, &ContentData)], rows: &[Vec<usize>]) -> Vec<(u32, u32)> {
+    let mut spans: Vec<(u32, u32)> = rows
+        .iter()
+        .filter(|row| row.len() >= MIN_COLS)
+        .flatten()
+        .map(|&i| (items[i].0.left, items[i].0.right))
+        .collect();
+    spans.sort_unstable();
+    let mut columns: Vec<(u32, u32)> = Vec::new();
+    for (left, right) in spans {
+        match columns.last_mut() {
+            Some(last) if left <= last.1 => last.1 = last.1.max(right),
+            _ => columns.push((left, right)),
+        }
+    }
+    columns
+}
+
+fn best_column(columns: &[(u32, u32)], b: &BoundingBox) -> Option<usize> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(idx, (left, right))| {
+            let overlap = (*right).min(b.right).saturating_sub((*left).max(b.left));
+            (idx, overlap)
+        })
+        .filter(|(_, overlap)| *overlap > 0)
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(idx, _)| idx)
+        .or_else(|| {
+            let cx = b.center_x();
+            columns
+                .iter()
+                .position(|(left, right)| cx >= *left as f32 && cx <= *right as f32)
+        })
+}
+
+fn bands(lines: &[u32]) -> Vec<(u32, u32)> {
+    lines.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+fn band_index(bands: &[(u32, u32)], value: f32) -> Option<usize> {
+    bands
+        .iter()
+        .position(|(start, end)| value >= *start as f32 && value < *end as f32)
+}
+
+fn assign_rows(items: &[(BoundingBox, &ContentData)], lines: &[u32]) -> Vec<Vec<usize>> {
+    let bands = bands(lines);
+    let mut rows = vec![Vec::new(); bands.len()];
+    for (i, (b, _)) in items.iter().enumerate() {
+        if let Some(idx) = band_index(&bands, b.center_y()) {
+            rows[idx].push(i);
+        }
+    }
+    for row in rows.iter_mut() {
+        row.sort_by_key(|&i| items[i].0.left);
+    }
+    rows
+}
+
+/// 表格线可用的条件：行列线足够、文本框都落在图片范围内且很少跨线。
+/// 自动转向不落盘，转向后表格线与文本框对不上，此时退回纯聚类。
+fn rulings_match(rulings: &Rulings, items: &[(BoundingBox, &ContentData)]) -> bool {
+    if rulings.horizontal.len() < MIN_ROWS + 1 || rulings.vertical.len() < MIN_COLS + 1 {
+        return false;
+    }
+    if items
+        .iter()
+        .any(|(b, _)| b.right > rulings.width || b.bottom > rulings.height)
+    {
+        return false;
+    }
+    let straddling = items
+        .iter()
+        .filter(|(b, _)| {
+            rulings
+                .horizontal
+                .iter()
+                .any(|&y| y > b.top + 1 && y + 1 < b.bottom)
+                || rulings
+                    .vertical
+                    .iter()
+                    .any(|&x| x > b.left + 1 && x + 1 < b.right)
+        })
+        .count();
+    (straddling as f32) <= items.len() as f32 * MAX_STRADDLING_RATIO
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(|c| c.to_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn content(text: &str, left: usize, top: usize, right: usize, bottom: usize) -> ContentData {
+        ContentData {
+            rect: [[left, top], [right, top], [right, bottom], [left, bottom]],
+            score: 0.9,
+            text: text.to_string(),
+        }
+    }
+
+    fn vehicle_list() -> Vec<ContentData> {
+        vec![
+            content("车辆清单", 150, 10, 250, 30),
+            content("序号", 10, 50, 50, 70),
+            content("号牌号码", 100, 50, 180, 70),
+            content("车辆类型", 220, 52, 300, 72),
+            content("1", 20, 90, 30, 110),
+            content("京A12345", 95, 91, 185, 111),
+            content("小型轿车", 220, 90, 300, 110),
+            content("2", 20, 130, 30, 150),
+            content("京B67890", 98, 129, 182, 149),
+            content("重型货车", 218, 131, 298, 151),
+        ]
+    }
+
+    #[test]
+    fn clusters_boxes_into_grid() {
+        let grid = reconstruct_table(&vehicle_list(), None).unwrap();
+        assert_eq!((grid.rows, grid.cols), (3, 3));
+        assert!(!grid.ruled);
+        assert_eq!(grid.header(), vec!["序号", "号牌号码", "车辆类型"]);
+        let col = grid.column_index("号牌").unwrap();
+        assert_eq!(grid.column_values(col), vec!["京A12345", "京B67890"]);
+    }
+
+    #[test]
+    fn plain_paragraph_is_not_a_table() {
+        let lines = vec![
+            content("第一行文字", 10, 10, 300, 30),
+            content("第二行文字", 10, 40, 300, 60),
+            content("第三行文字", 10, 70, 300, 90),
+            content("第四行文字", 10, 100, 300, 120),
+        ];
+        assert!(!looks_tabular(&lines));
+        assert!(reconstruct_table(&lines, None).is_none());
+    }
+
+    #[test]
+    fn detects_rulings_and_uses_them() {
+        let img = GrayImage::from_fn(320, 170, |x, y| {
+            let on_h = [40, 80, 120, 160].contains(&y) && (5..=310).contains(&x);
+            let on_v = [5, 90, 200, 310].contains(&x) && (40..=160).contains(&y);
+            if on_h || on_v {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        });
+        let rulings = detect_rulings(&DynamicImage::ImageLuma8(img), 2000);
+        assert_eq!(rulings.horizontal, vec![40, 80, 120, 160]);
+        assert_eq!(rulings.vertical, vec![5, 90, 200, 310]);
+
+        let grid = reconstruct_table(&vehicle_list(), Some(&rulings)).unwrap();
+        assert!(grid.ruled);
+        assert_eq!((grid.rows, grid.cols), (3, 3));
+        assert_eq!(grid.cell(2, 1).unwrap().text, "京B67890");
+    }
+}
@@ -1,8 +1,14 @@
 
-use axum::{extract::State, response::Json, routing::get, Router};
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::db::FailoverDatabase;
 use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,6 +34,10 @@ pub struct DatabaseStatus {
     pub health_check_result: bool,
     pub last_failover_time: Option<String>,
     pub auto_recovery_enabled: bool,
+    /// 降级期间写入、尚未回放到主库的条目数
+    pub pending_replay: u64,
+    /// 回放时与主库冲突、需人工核对的条目数
+    pub replay_conflicts: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,6 +55,7 @@ pub fn configure_failover_status_routes() -> Router<AppState> {
         .route("/api/failover/status", get(get_failover_status))
         .route("/api/failover/database", get(get_database_status))
         .route("/api/failover/storage", get(get_storage_status))
+        .route("/api/failover/conflicts", get(get_replay_conflicts))
         .route(
             "/api/failover/trigger-recovery",
             get(trigger_manual_recovery),
@@ -52,14 +63,14 @@ pub fn configure_failover_status_routes() -> Router<AppState> {
 }
 
 pub async fn get_failover_status(
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
 ) -> Json<FailoverStatusResponse> {
     info!(
         target: "failover.status",
         event = "failover.status.query"
     );
 
-    let database_status = get_database_status_internal().await;
+    let database_status = get_database_status_internal(&app_state).await;
     let storage_status = get_storage_status_internal().await;
 
     let overall_health = determine_overall_health(&database_status, &storage_status);
@@ -79,12 +90,56 @@ pub async fn get_failover_status(
     Json(response)
 }
 
-pub async fn get_database_status(State(_app_state): State<AppState>) -> Json<DatabaseStatus> {
+pub async fn get_database_status(State(app_state): State<AppState>) -> Json<DatabaseStatus> {
     info!(
         target: "failover.status",
         event = "failover.database.query"
     );
-    Json(get_database_status_internal().await)
+    Json(get_database_status_internal(&app_state).await)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConflictQuery {
+    pub limit: Option<u32>,
+}
+
+/// 回放冲突明细：主库数据已保留，降级库中的对应数据需人工核对
+pub async fn get_replay_conflicts(
+    State(app_state): State<AppState>,
+    Query(query): Query<ConflictQuery>,
+) -> Json<serde_json::Value> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let Some(failover) = app_state
+        .database
+        .as_any()
+        .downcast_ref::<FailoverDatabase>()
+    else {
+        return Json(serde_json::json!({
+            "success": true,
+            "message": "未启用数据库故障转移",
+            "data": [],
+        }));
+    };
+
+    match failover.journal_conflicts(limit).await {
+        Ok(entries) => Json(serde_json::json!({
+            "success": true,
+            "message": "查询成功",
+            "data": entries,
+        })),
+        Err(e) => {
+            warn!(
+                target: "failover.status",
+                event = "failover.conflicts.error",
+                error = %e
+            );
+            Json(serde_json::json!({
+                "success": false,
+                "message": format!("查询回放冲突失败: {}", e),
+                "data": [],
+            }))
+        }
+    }
 }
 
 pub async fn get_storage_status(State(_app_state): State<AppState>) -> Json<StorageStatus> {
@@ -116,15 +171,52 @@ pub async fn trigger_manual_recovery(
     }))
 }
 
-async fn get_database_status_internal() -> DatabaseStatus {
+async fn get_database_status_internal(app_state: &AppState) -> DatabaseStatus {
+    let health_check_result = app_state.database.health_check().await.unwrap_or(false);
+    let Some(failover) = app_state
+        .database
+        .as_any()
+        .downcast_ref::<FailoverDatabase>()
+    else {
+        return DatabaseStatus {
+            current_state: "主数据库".to_string(),
+            is_using_primary: true,
+            has_primary_configured: true,
+            health_check_result,
+            last_failover_time: None,
+            auto_recovery_enabled: false,
+            pending_replay: 0,
+            replay_conflicts: 0,
+        };
+    };
+
+    let state = failover.state_name().await;
+    let counts = match failover.journal_counts().await {
+        Ok(counts) => counts.unwrap_or_default(),
+        Err(e) => {
+            warn!(
+                target: "failover.status",
+                event = "failover.journal.error",
+                error = %e
+            );
+            Default::default()
+        }
+    };
 
     DatabaseStatus {
-        current_state: "主数据库".to_string(),
-        is_using_primary: true,
+        current_state: match state {
+            "primary" => "主数据库",
+            "recovering" => "恢复中（回放降级期间写入）",
+            _ => "本地降级数据库",
+        }
+        .to_string(),
+        is_using_primary: state == "primary",
         has_primary_configured: true,
-        health_check_result: true,
+        health_check_result,
         last_failover_time: None,
         auto_recovery_enabled: true,
+        pending_replay: counts.open(),
+        replay_conflicts: counts.conflict,
     }
 }
 
@@ -166,6 +258,13 @@ fn generate_recommendations(
         recommendations.push("建议检查OSS存储连接并尝试手动恢复".to_string());
     }
 
+    if db_status.replay_conflicts > 0 {
+        recommendations.push(format!(
+            "有{}条降级期间的写入与主库冲突，请通过 /api/failover/conflicts 核对",
+            db_status.replay_conflicts
+        ));
+    }
+
     if storage_status.pending_sync_files > 0 {
         recommendations.push(format!(
             "有{}个文件待同步到主存储，建议监控同步进度",
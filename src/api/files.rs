@@ -5,12 +5,14 @@ use crate::model::preview::PreviewBody;
 use crate::model::Goto;
 use crate::util::config::types::is_internal_host;
 use crate::util::config::Config;
+use crate::util::http_range::{self, RangeRequest};
+use crate::util::ocr_export::{self, ExportFormat};
 use crate::util::report::{pdf::PdfGenerator, PreviewReportGenerator};
 use crate::util::{IntoJson, ServerError};
 use crate::AppState;
 use axum::body::Body;
 use axum::extract::{Multipart, Path, Query, State};
-use axum::http::{header, StatusCode};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use chrono::Utc;
@@ -29,8 +31,9 @@ pub async fn upload(multipart: Multipart) -> impl IntoResponse {
     result.into_json()
 }
 
-pub async fn download(Query(goto): Query<Goto>) -> impl IntoResponse {
-    let result = PreviewBody::download(goto).await;
+pub async fn download(headers: HeaderMap, Query(goto): Query<Goto>) -> impl IntoResponse {
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let result = PreviewBody::download(goto, range).await;
     result.map_err(|err| ServerError::Custom(err.to_string()))
 }
 
@@ -151,6 +154,7 @@ pub async fn get_preview_result(
 
             if let Some(eval) = evaluation_struct.as_mut() {
                 crate::api::utils::sanitize_evaluation_result(eval);
+                super::worker_proxy::sign_preview_links(&state.database, &preview_id, eval).await;
             }
 
             if preview.status == PreviewStatus::Completed && preview.evaluation_result.is_none() {
@@ -285,6 +289,7 @@ async fn render_report_html_enriched(
                         "Failed to enrich attachment URLs, continuing with original URLs"
                     );
                 }
+                super::worker_proxy::sign_preview_links(database, &preview.id, &mut result).await;
                 Some(PreviewReportGenerator::generate_html(&result))
             }
             Err(err) => {
@@ -328,6 +333,7 @@ fn build_missing_evaluation_message(preview: &PreviewRecord) -> String {
                 "预审任务失败，未生成报告，请联系运维人员处理。".to_string()
             }
         }
+        PreviewStatus::Cancelled => "预审任务已取消，未生成报告。".to_string(),
         PreviewStatus::Completed => {
             "预审任务已标记为完成，但系统未收到评估结果。请稍后重试，如问题持续请联系运维人员。"
                 .to_string()
@@ -1053,6 +1059,118 @@ pub async fn get_material_preview(
     }
 }
 
+/// 导出预审的 OCR 结果（可检索 PDF / hOCR / ALTO）。
+/// `material` 指定材料编码时只导出该材料，否则整份预审按材料顺序合并为一个文件。
+pub async fn export_preview_ocr(
+    Path(preview_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Response {
+    let format = match params.get("format") {
+        None => ExportFormat::Pdf,
+        Some(raw) => match ExportFormat::parse(raw) {
+            Some(format) => format,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "不支持的导出格式，可选 pdf / hocr / alto",
+                )
+                    .into_response()
+            }
+        },
+    };
+
+    let records = match state
+        .database
+        .list_material_files(&MaterialFileFilter {
+            preview_id: Some(preview_id.clone()),
+            material_code: None,
+        })
+        .await
+    {
+        Ok(records) => records,
+        Err(err) => {
+            tracing::error!(preview_id = %preview_id, error = %err, "查询材料文件失败");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "查询材料文件失败").into_response();
+        }
+    };
+    let mut material_codes: Vec<String> = Vec::new();
+    for record in records {
+        if !material_codes.contains(&record.material_code) {
+            material_codes.push(record.material_code);
+        }
+    }
+    if let Some(material) = params.get("material") {
+        if !material_codes.contains(material) {
+            return (StatusCode::NOT_FOUND, "材料不存在").into_response();
+        }
+        material_codes = vec![material.clone()];
+    }
+
+    let mut pages = Vec::new();
+    for code in &material_codes {
+        match ocr_export::collect_material_pages(&state.storage, &preview_id, code).await {
+            Ok(material_pages) => pages.extend(material_pages),
+            Err(err) => warn!(
+                preview_id = %preview_id,
+                material_code = %code,
+                error = %err,
+                "读取OCR页面失败"
+            ),
+        }
+    }
+    if pages.is_empty() {
+        return (StatusCode::NOT_FOUND, "未找到可导出的OCR页面").into_response();
+    }
+
+    let name = match params.get("material") {
+        Some(material) => format!("{}_{}", preview_id, material),
+        None => preview_id.clone(),
+    };
+    let title = name.clone();
+    let rendered =
+        tokio::task::spawn_blocking(move || ocr_export::render(format, &title, &pages)).await;
+    let bytes = match rendered {
+        Ok(Ok(bytes)) => bytes,
+        Ok(Err(err)) => {
+            tracing::error!(preview_id = %preview_id, error = %err, "生成OCR导出文件失败");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "生成导出文件失败").into_response();
+        }
+        Err(err) => {
+            tracing::error!(preview_id = %preview_id, error = %err, "OCR导出任务异常");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "生成导出文件失败").into_response();
+        }
+    };
+
+    let filename = format!("{}_ocr.{}", name, format.file_extension());
+    let disposition = format!(
+        "attachment; filename=\"{}_ocr.{}\"; filename*=UTF-8''{}",
+        sanitize_ascii_filename(&preview_id),
+        format.file_extension(),
+        urlencoding::encode(&filename)
+    );
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(Body::from(bytes))
+        .unwrap_or_else(|e| {
+            tracing::error!("构建OCR导出响应失败: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        })
+}
+
+fn sanitize_ascii_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 fn find_material_record<'a>(
     records: &'a [MaterialFileRecord],
     material_name: &str,
@@ -1089,43 +1207,53 @@ async fn serve_material_from_storage(
 
     let storage_key = key.trim_start_matches('/').to_string();
 
-    match state.storage.get(&storage_key).await {
-        Ok(Some(bytes)) => {
-            let content_type = record
-                .mime_type
-                .clone()
-                .or_else(|| {
-                    MimeGuess::from_path(&storage_key)
-                        .first()
-                        .map(|m| m.to_string())
-                })
-                .unwrap_or_else(|| "application/octet-stream".to_string());
-
-            match Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, content_type)
-                .header(header::CACHE_CONTROL, "public, max-age=600")
-                .body(Body::from(bytes))
-            {
-                Ok(resp) => Some(resp),
-                Err(e) => {
-                    warn!("构建材料预览响应失败: {}", e);
-                    None
-                }
-            }
+    let size = match state.storage.get_metadata(&storage_key).await {
+        Ok(metadata) => metadata.size,
+        Err(e) => {
+            warn!(
+                "读取材料预览文件失败: preview={} key={} err={}",
+                record.preview_id, storage_key, e
+            );
+            return None;
         }
+    };
+    let reader = match state.storage.get_reader(&storage_key, None).await {
+        Ok(Some(reader)) => reader,
         Ok(None) => {
             warn!(
                 "材料预览文件不存在: preview={} key={}",
                 record.preview_id, storage_key
             );
-            None
+            return None;
         }
         Err(e) => {
             warn!(
                 "读取材料预览文件失败: preview={} key={} err={}",
                 record.preview_id, storage_key, e
             );
+            return None;
+        }
+    };
+
+    let content_type = record
+        .mime_type
+        .clone()
+        .or_else(|| {
+            MimeGuess::from_path(&storage_key)
+                .first()
+                .map(|m| m.to_string())
+        })
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    match RangeRequest::Full
+        .response(size)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "public, max-age=600")
+        .body(http_range::stream_body(reader))
+    {
+        Ok(resp) => Some(resp),
+        Err(e) => {
+            warn!("构建材料预览响应失败: {}", e);
             None
         }
     }
@@ -1133,7 +1261,9 @@ async fn serve_material_from_storage(
 
 pub async fn proxy_storage_file(
     Path(encoded_key): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let decoded = match urlencoding::decode(&encoded_key) {
         Ok(path) => path.to_string(),
@@ -1141,25 +1271,58 @@ pub async fn proxy_storage_file(
     };
     let storage_key = decoded.trim_start_matches('/');
 
-    let bytes = match state.storage.get(storage_key).await {
-        Ok(Some(data)) => data,
-        Ok(None) => return (StatusCode::NOT_FOUND, "文件不存在").into_response(),
+    let expires = params.get("expires").and_then(|v| v.parse::<i64>().ok());
+    let signature = params.get("signature").map(String::as_str);
+    if let Err(err) = crate::storage::signing::verify_request(storage_key, expires, signature) {
+        warn!(storage_key = %storage_key, error = %err, "存储文件链接校验失败");
+        return (StatusCode::FORBIDDEN, err.to_string()).into_response();
+    }
+
+    let metadata = match state.storage.get_metadata(storage_key).await {
+        Ok(metadata) => metadata,
         Err(err) => {
+            if let Ok(false) = state.storage.exists(storage_key).await {
+                return (StatusCode::NOT_FOUND, "文件不存在").into_response();
+            }
             tracing::error!(
                 storage_key = %storage_key,
                 error = %err,
-                "读取存储文件失败"
+                "读取存储文件元数据失败"
             );
             return (StatusCode::INTERNAL_SERVER_ERROR, "读取存储文件失败").into_response();
         }
     };
 
-    let content_type = state
+    let range = RangeRequest::parse(
+        headers.get(header::RANGE).and_then(|v| v.to_str().ok()),
+        metadata.size,
+    );
+    if range == RangeRequest::Unsatisfiable {
+        return range
+            .response(metadata.size)
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::RANGE_NOT_SATISFIABLE.into_response());
+    }
+
+    let reader = match state
         .storage
-        .get_metadata(storage_key)
+        .get_reader(storage_key, range.as_range())
         .await
-        .ok()
-        .and_then(|meta| meta.content_type)
+    {
+        Ok(Some(reader)) => reader,
+        Ok(None) => return (StatusCode::NOT_FOUND, "文件不存在").into_response(),
+        Err(err) => {
+            tracing::error!(
+                storage_key = %storage_key,
+                error = %err,
+                "读取存储文件失败"
+            );
+            return (StatusCode::INTERNAL_SERVER_ERROR, "读取存储文件失败").into_response();
+        }
+    };
+
+    let content_type = metadata
+        .content_type
         .or_else(|| {
             MimeGuess::from_path(storage_key)
                 .first()
@@ -1167,11 +1330,11 @@ pub async fn proxy_storage_file(
         })
         .unwrap_or_else(|| "application/octet-stream".to_string());
 
-    match Response::builder()
-        .status(StatusCode::OK)
+    match range
+        .response(metadata.size)
         .header(header::CONTENT_TYPE, content_type)
         .header(header::CACHE_CONTROL, "private, max-age=60")
-        .body(Body::from(bytes))
+        .body(http_range::stream_body(reader))
     {
         Ok(resp) => resp,
         Err(err) => {
@@ -4,6 +4,8 @@ mod files;
 mod meta;
 mod monitoring;
 pub mod preview;
+mod preview_batch;
+mod preview_control;
 mod rules;
 pub use preview::{LocalPreviewTaskHandler, RemotePreviewTaskHandler};
 mod utils;
@@ -175,6 +177,11 @@ pub fn routes(app_state: AppState) -> Router {
 
     let preview_routes = Router::new()
         .route("/api/preview", post(preview::preview))
+        .route("/api/preview/batch", post(preview_batch::submit_batch))
+        .route(
+            "/api/preview/batch/:batch_id",
+            get(preview_batch::get_batch),
+        )
         .layer(from_fn(crate::util::auth::third_party_auth_middleware))
         .layer(axum::middleware::from_fn(
             enhancement::api_enhancement_middleware,
@@ -196,6 +203,18 @@ pub fn routes(app_state: AppState) -> Router {
             get(lookup_preview_url),
         )
         .route("/api/preview/status/:preview_id", get(query_preview_status))
+        .route(
+            "/api/preview/:preview_id/cancel",
+            post(preview_control::cancel_preview),
+        )
+        .route(
+            "/api/preview/:preview_id/rerun",
+            post(preview_control::rerun_preview),
+        )
+        .route(
+            "/api/preview/progress/:preview_id",
+            get(preview_control::stream_progress),
+        )
         .route(
             "/api/preview/result/:preview_id",
             get(files::get_preview_result),
@@ -220,6 +239,10 @@ pub fn routes(app_state: AppState) -> Router {
             "/api/files/material-preview/:preview_id/:material_name",
             get(files::get_material_preview),
         )
+        .route(
+            "/api/files/ocr-export/:preview_id",
+            get(files::export_preview_ocr),
+        )
         .route("/api/storage/files/*key", get(files::proxy_storage_file))
         .route("/api/logs/stats", get(monitoring::get_log_stats))
         .route("/api/logs/cleanup", post(monitoring::cleanup_logs))
@@ -811,6 +834,12 @@ async fn get_preview_record(
                     }
 
                     crate::api::utils::sanitize_evaluation_result(&mut evaluation_struct);
+                    crate::api::worker_proxy::sign_preview_links(
+                        &app_state.database,
+                        preview_id,
+                        &mut evaluation_struct,
+                    )
+                    .await;
                     evaluation_obj =
                         serde_json::to_value(&evaluation_struct).unwrap_or_else(|_| evaluation_obj);
                 }
@@ -2,6 +2,9 @@
 use crate::api::monitor_auth::{LoginRequest, LoginResponse, MonitorAuthService};
 use crate::api::worker_proxy;
 use crate::db::models::MonitorUser;
+use crate::db::traits::{WorkerControlRecord, WorkerControlState};
+use crate::db::PreviewFilter;
+use crate::util::worker_fleet;
 use crate::AppState;
 use axum::{
     extract::{Path, Query, State},
@@ -10,8 +13,9 @@ use axum::{
     routing::{delete, get, post, put},
     Router,
 };
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct SessionQuery {
@@ -104,6 +108,28 @@ pub fn monitor_routes() -> Router<AppState> {
         .route("/system/throttle/status", get(throttle_status))
         .route("/system/throttle/enable", post(throttle_enable))
         .route("/system/throttle/disable", post(throttle_disable))
+        .route("/ocr-cache/stats", get(ocr_cache_stats))
+        .route("/ocr-cache/purge", post(ocr_cache_purge))
+        .route("/dead-letters", get(list_dead_letters))
+        .route("/dead-letters/:id", get(get_dead_letter))
+        .route("/dead-letters/:id/requeue", post(requeue_dead_letter))
+        .route("/dead-letters/:id/discard", post(discard_dead_letter))
+        .route("/retention/run", post(retention_run))
+        .route("/retention/audit", get(retention_audit))
+        .route("/retention/holds", get(list_legal_holds))
+        .route(
+            "/previews/:preview_id/legal-hold",
+            post(set_legal_hold).delete(release_legal_hold),
+        )
+        .route("/pii/rotate", post(rotate_pii_keys))
+        .route("/search", get(search_previews))
+        .route("/search/reindex", post(rebuild_search_index))
+        .route("/workers", get(list_workers))
+        .route("/workers/:worker_id/cordon", post(cordon_worker))
+        .route("/workers/:worker_id/uncordon", post(uncordon_worker))
+        .route("/workers/:worker_id/drain", post(drain_worker))
+        .route("/workers/:worker_id/revoke", post(revoke_worker))
+        .route("/workers/:worker_id/jobs", get(list_worker_jobs))
 }
 
 pub async fn login(
@@ -209,26 +235,27 @@ pub async fn cleanup_sessions(
     }
 }
 
+/// 角色比较前统一大小写，旧的 admin 角色视为 super_admin
+fn canonical_role(role: &str) -> String {
+    let lower = role.trim().to_ascii_lowercase();
+    if lower == "admin" {
+        "super_admin".to_string()
+    } else {
+        lower
+    }
+}
+
 async fn require_role(
     auth_service: &MonitorAuthService,
     session_id: &str,
     allowed_roles: &[&str],
 ) -> Result<MonitorUser, StatusCode> {
-    let normalize = |r: &str| -> String {
-        let lower = r.trim().to_ascii_lowercase();
-        if lower == "admin" {
-            "super_admin".to_string()
-        } else {
-            lower
-        }
-    };
-
     match auth_service.verify_session(session_id).await {
         Ok(Some(session)) => {
-            let user_role = normalize(&session.user.role);
+            let user_role = canonical_role(&session.user.role);
             let allowed = allowed_roles
                 .iter()
-                .map(|r| normalize(r))
+                .map(|r| canonical_role(r))
                 .any(|r| r == user_role);
             if allowed {
                 Ok(session.user)
@@ -541,3 +568,766 @@ pub async fn throttle_disable(
         "duration_secs": duration
     }))))
 }
+
+#[derive(Debug, Deserialize, Default)]
+pub struct OcrCachePurgeRequest {
+    /// 仅清理早于该小时数的条目；为空时清空全部缓存
+    #[serde(default)]
+    pub older_than_hours: Option<u64>,
+}
+
+pub async fn ocr_cache_stats(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<crate::util::ocr_cache::OcrCacheStats>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(&auth_service, &query.session_id, &["super_admin", "sys_admin"]).await?;
+
+    Ok(Json(ApiResponse::success(crate::util::ocr_cache::stats())))
+}
+
+pub async fn ocr_cache_purge(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<OcrCachePurgeRequest>>,
+) -> Result<Json<ApiResponse<crate::util::ocr_cache::OcrCachePurgeSummary>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, &query.session_id, &["super_admin"]).await?;
+
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let older_than = req
+        .older_than_hours
+        .map(|hours| std::time::Duration::from_secs(hours * 3600));
+
+    match crate::util::ocr_cache::purge(&state.storage, older_than).await {
+        Ok(summary) => {
+            tracing::info!(
+                operator = %session.username,
+                older_than_hours = ?req.older_than_hours,
+                removed = summary.removed,
+                removed_bytes = summary.removed_bytes,
+                "管理员清理OCR缓存"
+            );
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            tracing::error!("清理OCR缓存失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    #[serde(
+        alias = "monitor_session_id",
+        alias = "monitorSessionId",
+        alias = "sessionId",
+        alias = "session_id"
+    )]
+    session_id: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default, alias = "previewId")]
+    preview_id: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DeadLetterRequeueRequest {
+    /// 修改后的完整任务载荷；为空时按原载荷重新入队
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::db::traits::DeadLetterRecord>>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin", "ops_admin"],
+    )
+    .await?;
+
+    let status = match query.status.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(raw) => match raw.parse() {
+            Ok(status) => Some(status),
+            Err(e) => return Ok(Json(ApiResponse::error(format!("{}", e)))),
+        },
+        None => None,
+    };
+    let filter = crate::db::traits::DeadLetterFilter {
+        status,
+        preview_id: query.preview_id.clone(),
+        source: query.source.clone(),
+        limit: Some(query.limit.unwrap_or(50).clamp(1, 500)),
+        offset: query.offset,
+    };
+
+    match state.database.list_dead_letters(&filter).await {
+        Ok(records) => Ok(Json(ApiResponse::success(records))),
+        Err(e) => {
+            tracing::error!("查询死信失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn get_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<crate::db::traits::DeadLetterRecord>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin", "ops_admin"],
+    )
+    .await?;
+
+    match state.database.get_dead_letter(&id).await {
+        Ok(Some(record)) => Ok(Json(ApiResponse::success(record))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("查询死信详情失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<DeadLetterRequeueRequest>>,
+) -> Result<Json<ApiResponse<crate::db::traits::DeadLetterRecord>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin"],
+    )
+    .await?;
+
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    match crate::util::dead_letter::requeue(
+        &state.database,
+        &state.task_queue,
+        &id,
+        req.payload,
+        &session.username,
+    )
+    .await
+    {
+        Ok(record) => Ok(Json(ApiResponse::success(record))),
+        Err(e) => {
+            tracing::warn!(dead_letter_id = %id, "死信重新入队失败: {:#}", e);
+            Ok(Json(ApiResponse::error(format!("{:#}", e))))
+        }
+    }
+}
+
+pub async fn discard_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin"],
+    )
+    .await?;
+
+    match crate::util::dead_letter::discard(&state.database, &id, &session.username).await {
+        Ok(true) => Ok(Json(ApiResponse::success(()))),
+        Ok(false) => Ok(Json(ApiResponse::error("死信不存在或已处理"))),
+        Err(e) => {
+            tracing::error!("丢弃死信失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionAuditQuery {
+    #[serde(
+        alias = "monitor_session_id",
+        alias = "monitorSessionId",
+        alias = "sessionId",
+        alias = "session_id"
+    )]
+    session_id: String,
+    #[serde(default, alias = "previewId")]
+    preview_id: Option<String>,
+    #[serde(default)]
+    artifact: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct LegalHoldRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// 立即按当前留存配置执行一轮清理，不要求后台清理已启用
+pub async fn retention_run(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<crate::util::retention::RetentionRunSummary>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, &query.session_id, &["super_admin"]).await?;
+
+    let reason = format!("manual:{}", session.username);
+    match crate::util::retention::run_once(
+        &state.database,
+        &state.storage,
+        &state.config.retention,
+        &reason,
+    )
+    .await
+    {
+        Ok(summary) => {
+            tracing::info!(
+                operator = %session.username,
+                purged = summary.purged,
+                storage_keys = summary.storage_keys,
+                db_rows = summary.db_rows,
+                "管理员执行留存清理"
+            );
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            tracing::error!("留存清理失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn retention_audit(
+    State(state): State<AppState>,
+    Query(query): Query<RetentionAuditQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::db::traits::PurgeAuditRecord>>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin"],
+    )
+    .await?;
+
+    let artifact = match query.artifact.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(raw) => match raw.parse() {
+            Ok(artifact) => Some(artifact),
+            Err(e) => return Ok(Json(ApiResponse::error(format!("{}", e)))),
+        },
+        None => None,
+    };
+    let filter = crate::db::traits::PurgeAuditFilter {
+        preview_id: query.preview_id.clone(),
+        artifact,
+        limit: Some(query.limit.unwrap_or(50).clamp(1, 500)),
+        offset: query.offset,
+    };
+
+    match state.database.list_purge_audit(&filter).await {
+        Ok(records) => Ok(Json(ApiResponse::success(records))),
+        Err(e) => {
+            tracing::error!("查询清理审计失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn list_legal_holds(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::db::traits::LegalHoldRecord>>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin"],
+    )
+    .await?;
+
+    match state.database.list_legal_holds().await {
+        Ok(records) => Ok(Json(ApiResponse::success(records))),
+        Err(e) => {
+            tracing::error!("查询法律保全失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+async fn update_legal_hold(
+    state: &AppState,
+    session_id: &str,
+    preview_id: String,
+    hold: bool,
+    reason: Option<String>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, session_id, &["super_admin"]).await?;
+
+    let update = crate::db::traits::LegalHoldUpdate {
+        preview_id,
+        hold,
+        reason,
+        updated_by: session.username.clone(),
+    };
+    match state.database.set_preview_legal_hold(&update).await {
+        Ok(true) => {
+            tracing::info!(
+                operator = %session.username,
+                preview_id = %update.preview_id,
+                hold,
+                reason = ?update.reason,
+                "管理员变更法律保全"
+            );
+            Ok(Json(ApiResponse::success(())))
+        }
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("变更法律保全失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn set_legal_hold(
+    State(state): State<AppState>,
+    Path(preview_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<LegalHoldRequest>>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let reason = req.reason.filter(|r| !r.trim().is_empty());
+    update_legal_hold(&state, &query.session_id, preview_id, true, reason).await
+}
+
+pub async fn release_legal_hold(
+    State(state): State<AppState>,
+    Path(preview_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    update_legal_hold(&state, &query.session_id, preview_id, false, None).await
+}
+
+pub async fn rotate_pii_keys(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<crate::db::traits::PiiRotationSummary>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, &query.session_id, &["super_admin"]).await?;
+
+    let batch_size = state.config.pii_encryption.rotation_batch_size;
+    match state.database.rotate_pii_keys(batch_size).await {
+        Ok(summary) => {
+            tracing::info!(
+                operator = %session.username,
+                scanned = summary.scanned,
+                updated = summary.updated,
+                failures = summary.failures,
+                "管理员执行敏感字段密钥轮换"
+            );
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            tracing::error!("敏感字段密钥轮换失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchPreviewsQuery {
+    #[serde(
+        alias = "monitor_session_id",
+        alias = "monitorSessionId",
+        alias = "sessionId",
+        alias = "session_id"
+    )]
+    session_id: String,
+    #[serde(default)]
+    q: Option<String>,
+    #[serde(default, alias = "matterId", alias = "matter_id")]
+    matter: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default, alias = "dateFrom", alias = "date_from")]
+    from: Option<String>,
+    #[serde(default, alias = "dateTo", alias = "date_to")]
+    to: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+/// 接受 `YYYY-MM-DD`（按整天计）或 RFC 3339 时间
+fn parse_search_date(raw: Option<&str>, end_of_day: bool) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(raw) = raw.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        return Ok(time.map(|t| t.and_utc()));
+    }
+    DateTime::parse_from_rfc3339(raw)
+        .map(|dt| Some(dt.with_timezone(&Utc)))
+        .map_err(|_| format!("无效的日期: {}", raw))
+}
+
+pub async fn search_previews(
+    State(state): State<AppState>,
+    Query(query): Query<SearchPreviewsQuery>,
+) -> Result<Json<ApiResponse<crate::util::search::SearchResults>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin", "ops_admin"],
+    )
+    .await?;
+
+    let Some(index) = crate::util::search::index() else {
+        return Ok(Json(ApiResponse::error("全文检索未启用".to_string())));
+    };
+
+    let status = match query.status.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(raw) => match raw.trim().parse::<crate::db::PreviewStatus>() {
+            Ok(status) => Some(status),
+            Err(_) => return Ok(Json(ApiResponse::error(format!("无效的状态: {}", raw)))),
+        },
+        None => None,
+    };
+    let (start_date, end_date) = match (
+        parse_search_date(query.from.as_deref(), false),
+        parse_search_date(query.to.as_deref(), true),
+    ) {
+        (Ok(start), Ok(end)) => (start, end),
+        (Err(e), _) | (_, Err(e)) => return Ok(Json(ApiResponse::error(e))),
+    };
+
+    let max_results = state.config.search.max_results.max(1);
+    let search = crate::util::search::SearchQuery {
+        text: query.q.clone(),
+        matter: query.matter.clone(),
+        status,
+        start_date,
+        end_date,
+        limit: query.limit.unwrap_or(20).clamp(1, max_results),
+        offset: query.offset.unwrap_or(0),
+    };
+
+    match index.search(&search).await {
+        Ok(results) => Ok(Json(ApiResponse::success(results))),
+        Err(e) => {
+            tracing::error!("全文检索失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn rebuild_search_index(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<crate::util::search::BackfillSummary>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, &query.session_id, &["super_admin"]).await?;
+
+    let Some(index) = crate::util::search::index() else {
+        return Ok(Json(ApiResponse::error("全文检索未启用".to_string())));
+    };
+
+    match crate::util::search::backfill(index, &state.database, true).await {
+        Ok(summary) => {
+            tracing::info!(
+                operator = %session.username,
+                indexed = summary.indexed,
+                failures = summary.failures,
+                "管理员重建全文检索索引"
+            );
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            tracing::error!("重建全文检索索引失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerFleetEntry {
+    pub worker_id: String,
+    pub description: Option<String>,
+    /// 是否在 worker_proxy.workers 中配置且启用
+    pub configured: bool,
+    pub state: WorkerControlState,
+    pub control: Option<WorkerControlRecord>,
+    pub heartbeat: Option<worker_proxy::WorkerHeartbeatSnapshot>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkerControlRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkerJobsQuery {
+    #[serde(
+        alias = "monitor_session_id",
+        alias = "monitorSessionId",
+        alias = "sessionId",
+        alias = "session_id"
+    )]
+    session_id: String,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerJobSummary {
+    pub preview_id: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub processing_started_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub retry_count: i32,
+    pub failure_reason: Option<String>,
+    pub last_error_code: Option<String>,
+}
+
+pub async fn list_workers(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<ApiResponse<Vec<WorkerFleetEntry>>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin", "ops_admin"],
+    )
+    .await?;
+
+    let mut heartbeats: HashMap<String, worker_proxy::WorkerHeartbeatSnapshot> =
+        worker_proxy::collect_worker_heartbeat_snapshot()
+            .await
+            .into_iter()
+            .map(|snapshot| (snapshot.worker_id.clone(), snapshot))
+            .collect();
+
+    let mut entries: Vec<WorkerFleetEntry> = state
+        .config
+        .worker_proxy
+        .workers
+        .iter()
+        .map(|worker| WorkerFleetEntry {
+            worker_id: worker.worker_id.clone(),
+            description: worker.description.clone(),
+            configured: worker.enabled,
+            state: worker_fleet::state(&worker.worker_id),
+            control: worker_fleet::record(&worker.worker_id),
+            heartbeat: heartbeats.remove(&worker.worker_id),
+        })
+        .collect();
+    // 配置已移除但仍有心跳记录的 worker
+    entries.extend(heartbeats.into_values().map(|snapshot| WorkerFleetEntry {
+        worker_id: snapshot.worker_id.clone(),
+        description: None,
+        configured: false,
+        state: worker_fleet::state(&snapshot.worker_id),
+        control: worker_fleet::record(&snapshot.worker_id),
+        heartbeat: Some(snapshot),
+    }));
+    entries.sort_by(|a, b| a.worker_id.cmp(&b.worker_id));
+
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+pub async fn cordon_worker(
+    State(state): State<AppState>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<WorkerControlRequest>>,
+) -> Result<Json<ApiResponse<WorkerControlRecord>>, StatusCode> {
+    change_worker_state(
+        state,
+        worker_id,
+        query,
+        body,
+        WorkerControlState::Cordoned,
+        &["super_admin", "sys_admin"],
+    )
+    .await
+}
+
+pub async fn uncordon_worker(
+    State(state): State<AppState>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<WorkerControlRequest>>,
+) -> Result<Json<ApiResponse<WorkerControlRecord>>, StatusCode> {
+    // 恢复已吊销的凭证需要超级管理员
+    let roles: &[&str] = if worker_fleet::is_revoked(&worker_id) {
+        &["super_admin"]
+    } else {
+        &["super_admin", "sys_admin"]
+    };
+    change_worker_state(
+        state,
+        worker_id,
+        query,
+        body,
+        WorkerControlState::Active,
+        roles,
+    )
+    .await
+}
+
+pub async fn drain_worker(
+    State(state): State<AppState>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<WorkerControlRequest>>,
+) -> Result<Json<ApiResponse<WorkerControlRecord>>, StatusCode> {
+    change_worker_state(
+        state,
+        worker_id,
+        query,
+        body,
+        WorkerControlState::Draining,
+        &["super_admin", "sys_admin"],
+    )
+    .await
+}
+
+pub async fn revoke_worker(
+    State(state): State<AppState>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+    body: Option<Json<WorkerControlRequest>>,
+) -> Result<Json<ApiResponse<WorkerControlRecord>>, StatusCode> {
+    change_worker_state(
+        state,
+        worker_id,
+        query,
+        body,
+        WorkerControlState::Revoked,
+        &["super_admin"],
+    )
+    .await
+}
+
+async fn change_worker_state(
+    state: AppState,
+    worker_id: String,
+    query: SessionQuery,
+    body: Option<Json<WorkerControlRequest>>,
+    target: WorkerControlState,
+    allowed_roles: &[&str],
+) -> Result<Json<ApiResponse<WorkerControlRecord>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    let session = require_role(&auth_service, &query.session_id, allowed_roles).await?;
+
+    let worker_id = worker_id.trim().to_string();
+    let known = state
+        .config
+        .worker_proxy
+        .workers
+        .iter()
+        .any(|worker| worker.worker_id == worker_id)
+        || worker_fleet::record(&worker_id).is_some();
+    if !known {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let reason = body
+        .and_then(|Json(req)| req.reason)
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    // 与 uncordon_worker 的角色要求一致：只有超级管理员能恢复已吊销的凭证
+    let allow_unrevoke = canonical_role(&session.role) == "super_admin";
+
+    match worker_fleet::set_state(
+        &state.database,
+        &worker_id,
+        target,
+        reason,
+        &session.username,
+        allow_unrevoke,
+    )
+    .await
+    {
+        Ok(record) => Ok(Json(ApiResponse::success(record))),
+        Err(e) => {
+            tracing::warn!(worker_id = %worker_id, "变更 worker 状态失败: {:#}", e);
+            Ok(Json(ApiResponse::error(format!("{:#}", e))))
+        }
+    }
+}
+
+pub async fn list_worker_jobs(
+    State(state): State<AppState>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<WorkerJobsQuery>,
+) -> Result<Json<ApiResponse<Vec<WorkerJobSummary>>>, StatusCode> {
+    let auth_service = MonitorAuthService::new(state.database.clone());
+    require_role(
+        &auth_service,
+        &query.session_id,
+        &["super_admin", "sys_admin", "ops_admin"],
+    )
+    .await?;
+
+    let filter = PreviewFilter {
+        worker_id: Some(worker_id.trim().to_string()),
+        limit: Some(query.limit.unwrap_or(50).clamp(1, 500)),
+        offset: query.offset,
+        ..PreviewFilter::default()
+    };
+
+    match state.database.list_preview_records(&filter).await {
+        Ok(records) => Ok(Json(ApiResponse::success(
+            records
+                .into_iter()
+                .map(|record| WorkerJobSummary {
+                    preview_id: record.id,
+                    status: record.status.as_str().to_string(),
+                    created_at: record.created_at,
+                    processing_started_at: record.processing_started_at,
+                    updated_at: record.updated_at,
+                    retry_count: record.retry_count,
+                    failure_reason: record.failure_reason,
+                    last_error_code: record.last_error_code,
+                })
+                .collect(),
+        ))),
+        Err(e) => {
+            tracing::error!("查询 worker 任务记录失败: {}", e);
+            Ok(Json(ApiResponse::error(e.to_string())))
+        }
+    }
+}
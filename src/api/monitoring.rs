@@ -8,8 +8,10 @@ use crate::model::{
     ComponentStatus, ComponentsHealth, DetailedHealthStatus, ErrorInfo, HealthStatus, QueueStatus,
 };
 use crate::util::logging::runtime::ATTACHMENT_LOGGING_RUNTIME;
+use crate::util::system_info;
+use crate::util::task_queue::{DatabaseTaskQueue, LocalTaskQueue, NatsTaskQueue};
+use crate::util::task_routing;
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
-use crate::util::{system_info, task_queue::NatsTaskQueue};
 use crate::{AppState, CONFIG};
 use axum::extract::{Path, Query, State};
 use axum::response::IntoResponse;
@@ -240,6 +242,7 @@ pub async fn get_preview_stats(
         status: None,
         theme_id: None,
         third_party_request_id: None,
+        worker_id: None,
         start_date: None,
         end_date: None,
         limit: Some(limit),
@@ -373,6 +376,7 @@ pub async fn get_preview_records_list(
         status: None,
         theme_id: None,
         third_party_request_id: None,
+        worker_id: None,
         start_date: None,
         end_date: None,
         limit: None,
@@ -387,6 +391,7 @@ pub async fn get_preview_records_list(
                 "processing" => Some(PreviewStatus::Processing),
                 "completed" => Some(PreviewStatus::Completed),
                 "failed" => Some(PreviewStatus::Failed),
+                "cancelled" => Some(PreviewStatus::Cancelled),
                 _ => None,
             };
         }
@@ -416,6 +421,7 @@ pub async fn get_preview_records_list(
         status: filter.status.clone(),
         theme_id: filter.theme_id.clone(),
         third_party_request_id: filter.third_party_request_id.clone(),
+        worker_id: filter.worker_id.clone(),
         start_date: filter.start_date,
         end_date: filter.end_date,
         limit: None,
@@ -537,6 +543,7 @@ pub async fn get_preview_requests_list(
             "processing" => Some(PreviewStatus::Processing),
             "completed" => Some(PreviewStatus::Completed),
             "failed" => Some(PreviewStatus::Failed),
+            "cancelled" => Some(PreviewStatus::Cancelled),
             _ => None,
         };
     }
@@ -844,6 +851,7 @@ pub async fn calculate_preview_statistics(
     let failed = counts.failed as usize;
     let pending = counts.pending as usize;
     let queued = counts.queued as usize;
+    let cancelled = counts.cancelled as usize;
 
     Ok(serde_json::json!({
         "total": total,
@@ -852,6 +860,7 @@ pub async fn calculate_preview_statistics(
         "failed": failed,
         "pending": pending,
         "queued": queued,
+        "cancelled": cancelled,
         "success_rate": if total > 0 { (completed as f64 / total as f64 * 100.0).round() } else { 0.0 }
     }))
 }
@@ -1174,6 +1183,47 @@ pub async fn get_queue_status(State(app_state): State<AppState>) -> impl IntoRes
             "status": queue_status
         }),
     );
+    // &dyn Any 不是 Send，先落到具体类型的引用，借用不跨 await
+    let (local_queue, nats_queue, database_queue) = {
+        let task_queue_any = app_state.task_queue.as_any();
+        (
+            task_queue_any.downcast_ref::<LocalTaskQueue>(),
+            task_queue_any.downcast_ref::<NatsTaskQueue>(),
+            task_queue_any.downcast_ref::<DatabaseTaskQueue>(),
+        )
+    };
+    let lane_depths = if let Some(local_queue) = local_queue {
+        Some(local_queue.lane_depths())
+    } else if let Some(nats_queue) = nats_queue {
+        match nats_queue.lane_depths().await {
+            Ok(depths) => Some(depths),
+            Err(err) => {
+                tracing::warn!("获取优先级通道深度失败: {:#}", err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    data.insert(
+        "lanes".to_string(),
+        lane_depths.map_or(Value::Null, |depths| json!(depths)),
+    );
+    let router = nats_queue
+        .and_then(|queue| queue.router())
+        .or_else(|| database_queue.and_then(|queue| queue.router()));
+    data.insert(
+        "routing".to_string(),
+        router.map_or(Value::Null, |router| {
+            let workers: Vec<Value> = task_routing::live_workers()
+                .into_iter()
+                .map(|(worker_id, capabilities)| {
+                    json!({ "worker_id": worker_id, "capabilities": capabilities })
+                })
+                .collect();
+            json!({ "held": router.held_count(), "workers": workers })
+        }),
+    );
     data.insert(
         "system_info".to_string(),
         json!({
@@ -9,13 +9,21 @@ use crate::model::preview::{
     Attachment, MaterialValue, Preview, PreviewBody, SceneValue, UserInfo,
 };
 use crate::model::{Goto, SessionUser};
+use crate::util::auth::AuthenticatedClient;
+use crate::util::lane_scheduler::LanePolicy;
 use crate::util::logging::standards::events;
 use crate::util::material_cache::{self, WORKER_CACHE_SCHEME};
+use crate::util::pdf_shard;
+use crate::util::preview_cancel;
+use crate::util::preview_progress;
 use crate::util::rules::{RuleRepository, WorkerRuleCache};
-use crate::util::task_queue::{PreviewTask, PreviewTaskHandler, TaskQueue, PREVIEW_QUEUE_NAME};
+use crate::util::task_queue::{
+    PreviewTask, PreviewTaskHandler, TaskQueue, TaskReturned, TaskScheduling, PREVIEW_QUEUE_NAME,
+};
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 use crate::util::worker::{
     build_result_payload, WorkerJobActivityGuard, WorkerJobStatus, WorkerProxyClient,
+    WorkerRejected,
 };
 use crate::util::IntoJson;
 use crate::{AppState, CONFIG};
@@ -291,23 +299,7 @@ async fn preview_internal(
     };
 
     let mut preview_body: PreviewBody = match serde_json::from_slice::<Value>(&bytes) {
-        Ok(json_value) => {
-            if let Ok(standard_body) = serde_json::from_value::<PreviewBody>(json_value.clone()) {
-                tracing::debug!(" 解析为标准PreviewBody格式成功");
-                standard_body
-            }
-            else if let Ok(prod_request) = serde_json::from_value::<
-                crate::model::preview::ProductionPreviewRequest,
-            >(json_value.clone())
-            {
-                tracing::debug!(" 解析为生产环境格式成功，正在转换...");
-                prod_request.to_preview_body()
-            }
-            else {
-                tracing::warn!(" 无法解析为已知格式，创建兼容结构...");
-                create_fallback_preview_body(&json_value)
-            }
-        }
+        Ok(json_value) => parse_preview_value(json_value),
         Err(e) => {
             tracing::error!("无法解析JSON: {}", e);
             return finalize_preview_response(
@@ -338,9 +330,7 @@ async fn preview_internal(
     )
     .await;
 
-    let debug_mode = CONFIG.debug.enabled
-        || CONFIG.runtime_mode.mode == "development"
-        || std::env::var("ENABLE_AUTH_BYPASS").unwrap_or("false".to_string()) == "true";
+    let debug_mode = auth_bypass_enabled();
 
     let api_source = headers
         .get("X-API-Source")
@@ -530,6 +520,120 @@ async fn preview_internal(
         }
     }
 
+    let third_party_request_id = preview_body.preview.request_id.clone();
+    let client_id = parts
+        .extensions
+        .get::<AuthenticatedClient>()
+        .map(|client| client.client_id.as_str());
+
+    let our_preview_id = match submit_preview(
+        &app_state,
+        &mut preview_body,
+        &bytes,
+        resolved_user.as_ref(),
+        client_id,
+    )
+    .await
+    {
+        Ok(SubmissionOutcome::Accepted { preview_id }) => preview_id,
+        Ok(SubmissionOutcome::Duplicate { preview_id }) => {
+            let view_url = CONFIG.preview_view_url(&preview_id);
+            let response = Json(serde_json::json!({
+                "success": true,
+                "errorCode": 200,
+                "errorMsg": "相同材料请求已完成，返回最近结果",
+                "data": {
+                    "previewId": preview_id,
+                    "thirdPartyRequestId": third_party_request_id,
+                    "status": "completed",
+                    "message": "重复请求已折叠，复用最近一次的预审结果",
+                    "previewUrl": view_url
+                }
+            }))
+            .into_response();
+
+            return finalize_preview_response(response, "duplicate_skipped", request_start);
+        }
+        Err(err) => {
+            let response = crate::util::WebResult::err_custom(err.message)
+                .into_json()
+                .into_response();
+            return finalize_preview_response(response, err.reason, request_start);
+        }
+    };
+
+    let view_url = format!("{}/api/preview/view/{}", CONFIG.host, our_preview_id);
+
+    tracing::debug!("立即返回预审访问URL: {}", view_url);
+
+    let response_data = serde_json::json!({
+        "success": true,
+        "errorCode": 200,
+        "errorMsg": "",
+        "data": {
+            "previewId": our_preview_id,
+            "thirdPartyRequestId": third_party_request_id,
+            "status": "submitted",
+        "message": "预审任务已提交，正在后台处理"
+    }
+    });
+
+    tracing::debug!("用户预审访问URL: {}", view_url);
+
+    let response = Json(response_data).into_response();
+    finalize_preview_response(response, "accepted", request_start)
+}
+
+/// 调试模式下用户身份不一致或未登录时仅记录告警
+pub(crate) fn auth_bypass_enabled() -> bool {
+    CONFIG.debug.enabled
+        || CONFIG.runtime_mode.mode == "development"
+        || std::env::var("ENABLE_AUTH_BYPASS").unwrap_or("false".to_string()) == "true"
+}
+
+/// 依次尝试标准格式与生产环境格式，均失败时构造兼容结构
+pub(crate) fn parse_preview_value(json_value: Value) -> PreviewBody {
+    if let Ok(standard_body) = serde_json::from_value::<PreviewBody>(json_value.clone()) {
+        tracing::debug!(" 解析为标准PreviewBody格式成功");
+        standard_body
+    } else if let Ok(prod_request) = serde_json::from_value::<
+        crate::model::preview::ProductionPreviewRequest,
+    >(json_value.clone())
+    {
+        tracing::debug!(" 解析为生产环境格式成功，正在转换...");
+        prod_request.to_preview_body()
+    } else {
+        tracing::warn!(" 无法解析为已知格式，创建兼容结构...");
+        create_fallback_preview_body(&json_value)
+    }
+}
+
+pub(crate) enum SubmissionOutcome {
+    Accepted {
+        preview_id: String,
+    },
+    /// 相同材料重复提交超过上限，折叠到最近一次预审
+    Duplicate {
+        preview_id: String,
+    },
+}
+
+/// `reason` 计入请求指标，`message` 返回给调用方
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SubmissionError {
+    pub reason: &'static str,
+    pub message: &'static str,
+}
+
+/// 分配预审ID、去重、保存映射与原始请求并入队材料下载；
+/// 调用方负责校验与用户认证，`preview_body.preview.request_id` 会被替换为新的预审ID
+pub(crate) async fn submit_preview(
+    app_state: &AppState,
+    preview_body: &mut PreviewBody,
+    raw: &[u8],
+    resolved_user: Option<&SessionUser>,
+    client_id: Option<&str>,
+) -> Result<SubmissionOutcome, SubmissionError> {
     let third_party_request_id = preview_body.preview.request_id.clone();
 
     let our_preview_id = crate::api::utils::generate_secure_preview_id();
@@ -547,10 +651,10 @@ async fn preview_internal(
             " 无效的用户ID格式: {}",
             mask_identifier(&preview_body.user_id)
         );
-        let response = crate::util::WebResult::err_custom("无效的用户ID")
-            .into_json()
-            .into_response();
-        return finalize_preview_response(response, "invalid_user_id", request_start);
+        return Err(SubmissionError {
+            reason: "invalid_user_id",
+            message: "无效的用户ID",
+        });
     }
 
     let materials_hash = compute_materials_hash(&preview_body.preview.material_data);
@@ -559,7 +663,7 @@ async fn preview_internal(
         &preview_body.preview.matter_id,
         &materials_hash,
     );
-    let payload_hash = hex::encode(Sha256::digest(&bytes));
+    let payload_hash = hex::encode(Sha256::digest(raw));
 
     match app_state
         .database
@@ -580,8 +684,6 @@ async fn preview_internal(
             preview_id: reused_preview_id,
             repeat_count,
         }) => {
-            let view_url = CONFIG.preview_view_url(&reused_preview_id);
-
             tracing::warn!(
                 event = "preview.duplicate_rejected",
                 preview_id = %our_preview_id,
@@ -590,22 +692,9 @@ async fn preview_internal(
                 materials_hash = %materials_hash,
                 "检测到重复材料请求，返回最近结果"
             );
-
-            let response = Json(serde_json::json!({
-                "success": true,
-                "errorCode": 200,
-                "errorMsg": "相同材料请求已完成，返回最近结果",
-                "data": {
-                    "previewId": reused_preview_id,
-                    "thirdPartyRequestId": third_party_request_id,
-                    "status": "completed",
-                    "message": "重复请求已折叠，复用最近一次的预审结果",
-                    "previewUrl": view_url
-                }
-            }))
-            .into_response();
-
-            return finalize_preview_response(response, "duplicate_skipped", request_start);
+            return Ok(SubmissionOutcome::Duplicate {
+                preview_id: reused_preview_id,
+            });
         }
         Ok(PreviewDedupDecision::Allowed { repeat_count }) => {
             tracing::info!(
@@ -631,19 +720,19 @@ async fn preview_internal(
         &our_preview_id,
         &third_party_request_id,
         &preview_body.user_id,
-        resolved_user.as_ref(),
+        resolved_user,
     )
     .await
     {
         tracing::error!("保存ID映射失败: {}", e);
         METRICS_COLLECTOR.record_preview_persistence_failure("save_id_mapping");
-        let response = crate::util::WebResult::err_custom("系统错误")
-            .into_json()
-            .into_response();
-        return finalize_preview_response(response, "id_mapping_failed", request_start);
+        return Err(SubmissionError {
+            reason: "id_mapping_failed",
+            message: "系统错误",
+        });
     }
 
-    let original_request_body = String::from_utf8_lossy(&bytes).to_string();
+    let original_request_body = String::from_utf8_lossy(raw).to_string();
     if let Err(e) = save_original_request_to_database(
         &app_state.database,
         &our_preview_id,
@@ -656,40 +745,33 @@ async fn preview_internal(
         METRICS_COLLECTOR.record_preview_persistence_failure("save_original_request");
     }
 
+    let scheduling = TaskScheduling::resolve(&LanePolicy::from_global_config(), client_id);
+    let download_payload = scheduling
+        .embed(raw)
+        .unwrap_or_else(|| original_request_body.clone());
+
     if let Err(e) = app_state
         .database
-        .enqueue_material_download(&our_preview_id, &original_request_body)
+        .enqueue_material_download(&our_preview_id, &download_payload)
         .await
     {
         tracing::error!("入队材料下载任务失败: {}", e);
-        let response = crate::util::WebResult::err_custom("系统内部错误: 任务入队失败")
-            .into_json()
-            .into_response();
-        return finalize_preview_response(response, "enqueue_failed", request_start);
-    }
-
-    tracing::info!(preview_id = %our_preview_id, "预审任务已入队(材料下载队列)");
-
-    let view_url = format!("{}/api/preview/view/{}", CONFIG.host, our_preview_id);
-
-    tracing::debug!("立即返回预审访问URL: {}", view_url);
-
-    let response_data = serde_json::json!({
-        "success": true,
-        "errorCode": 200,
-        "errorMsg": "",
-        "data": {
-            "previewId": our_preview_id,
-            "thirdPartyRequestId": third_party_request_id,
-            "status": "submitted",
-        "message": "预审任务已提交，正在后台处理"
+        return Err(SubmissionError {
+            reason: "enqueue_failed",
+            message: "系统内部错误: 任务入队失败",
+        });
     }
-    });
 
-    tracing::debug!("用户预审访问URL: {}", view_url);
+    tracing::info!(
+        preview_id = %our_preview_id,
+        lane = scheduling.priority.as_str(),
+        client_id = scheduling.client_id.as_deref().unwrap_or(""),
+        "预审任务已入队(材料下载队列)"
+    );
 
-    let response = Json(response_data).into_response();
-    finalize_preview_response(response, "accepted", request_start)
+    Ok(SubmissionOutcome::Accepted {
+        preview_id: our_preview_id,
+    })
 }
 
 fn bad_request_response(msg: impl Into<String>) -> Response {
@@ -702,7 +784,7 @@ fn bad_request_response(msg: impl Into<String>) -> Response {
     (StatusCode::BAD_REQUEST, Json(payload)).into_response()
 }
 
-fn validate_preview_body(body: &PreviewBody) -> Result<(), Vec<String>> {
+pub(crate) fn validate_preview_body(body: &PreviewBody) -> Result<(), Vec<String>> {
     let mut errors = Vec::new();
     check_required("userId", &body.user_id, &mut errors);
 
@@ -867,6 +949,48 @@ fn finalize_preview_response(response: Response, reason: &str, start: Instant) -
     response
 }
 
+/// 将事项当前的规则 JSON 打包进任务载荷，worker 无需再查询规则库
+pub(crate) async fn embed_matter_rule(
+    database: &Arc<dyn crate::db::Database>,
+    preview_body: &mut PreviewBody,
+) {
+    let repo = RuleRepository::new(Arc::clone(database));
+    match repo.fetch(&preview_body.preview.matter_id).await {
+        Ok(Some(config)) => {
+            match serde_json::from_str::<serde_json::Value>(&config.record.rule_payload) {
+                Ok(payload) => {
+                    tracing::info!(
+                        matter_id = %preview_body.preview.matter_id,
+                        mode = %config.mode.as_str(),
+                        "已将事项规则JSON打包进预审任务载荷"
+                    );
+                    preview_body.rule_definition = Some(payload);
+                }
+                Err(parse_err) => {
+                    tracing::warn!(
+                        matter_id = %preview_body.preview.matter_id,
+                        error = %parse_err,
+                        "事项规则JSON解析失败，任务将回退到默认规则"
+                    );
+                }
+            }
+        }
+        Ok(None) => {
+            tracing::info!(
+                matter_id = %preview_body.preview.matter_id,
+                "事项未配置规则记录，沿用默认规则"
+            );
+        }
+        Err(err) => {
+            tracing::warn!(
+                matter_id = %preview_body.preview.matter_id,
+                error = %err,
+                "加载事项规则配置失败，任务将回退到默认规则"
+            );
+        }
+    }
+}
+
 async fn process_preview_submission_async(
     database: Arc<dyn crate::db::Database>,
     task_queue: Arc<dyn TaskQueue>,
@@ -1007,41 +1131,7 @@ async fn process_preview_submission_async(
     }
 
     if preview_body.rule_definition.is_none() {
-        let repo = RuleRepository::new(Arc::clone(&database));
-        match repo.fetch(&preview_body.preview.matter_id).await {
-            Ok(Some(config)) => {
-                match serde_json::from_str::<serde_json::Value>(&config.record.rule_payload) {
-                    Ok(payload) => {
-                        tracing::info!(
-                            matter_id = %preview_body.preview.matter_id,
-                            mode = %config.mode.as_str(),
-                            "已将事项规则JSON打包进预审任务载荷"
-                        );
-                        preview_body.rule_definition = Some(payload);
-                    }
-                    Err(parse_err) => {
-                        tracing::warn!(
-                            matter_id = %preview_body.preview.matter_id,
-                            error = %parse_err,
-                            "事项规则JSON解析失败，任务将回退到默认规则"
-                        );
-                    }
-                }
-            }
-            Ok(None) => {
-                tracing::info!(
-                    matter_id = %preview_body.preview.matter_id,
-                    "事项未配置规则记录，沿用默认规则"
-                );
-            }
-            Err(err) => {
-                tracing::warn!(
-                    matter_id = %preview_body.preview.matter_id,
-                    error = %err,
-                    "加载事项规则配置失败，任务将回退到默认规则"
-                );
-            }
-        }
+        embed_matter_rule(&database, &mut preview_body).await;
     }
 
     let material_total = preview_body.preview.material_data.len();
@@ -1193,6 +1283,7 @@ async fn prepare_material_tokens(
         return Ok(Vec::new());
     }
 
+    let download_total = tasks.len();
     let mut download_tasks = FuturesUnordered::new();
     let per_attachment_timeout = Duration::from_secs(MATERIAL_ATTACHMENT_TIMEOUT_SECS);
 
@@ -1272,6 +1363,7 @@ async fn prepare_material_tokens(
                         slow_notes.push(note);
                     }
                     collected.push(outcome.attachment);
+                    preview_progress::downloading(preview_id, collected.len(), download_total);
                 }
                 Err(err) => {
                     return Err(err.into_failure(slow_notes));
@@ -1679,7 +1771,16 @@ impl LocalPreviewTaskHandler {
 #[async_trait]
 impl PreviewTaskHandler for LocalPreviewTaskHandler {
     async fn handle_preview_task(&self, task: PreviewTask) -> Result<()> {
+        if let Some(shard) = task.shard {
+            return pdf_shard::handle_shard_task(
+                &task.preview_id,
+                shard,
+                Some(self.storage.clone()),
+            )
+            .await;
+        }
         let mut preview_body = task.preview_body;
+        preview_body.force_reocr = task.force_reocr;
         let preview_id = task.preview_id;
         let third_party_request_id = task.third_party_request_id;
         let database_clone = self.database.clone();
@@ -1696,6 +1797,12 @@ impl PreviewTaskHandler for LocalPreviewTaskHandler {
 
         let third_party_request_id_ref = optional_non_empty(&third_party_request_id);
 
+        if preview_cancelled(&database_clone, &preview_id).await {
+            tracing::info!(preview_id = %preview_id, "预审已取消，跳过执行");
+            return Ok(());
+        }
+        let running = preview_cancel::track(&preview_id);
+
         let mut permit = match crate::OCR_SEMAPHORE.try_acquire() {
             Ok(permit) => {
                 tracing::debug!(
@@ -1707,12 +1814,17 @@ impl PreviewTaskHandler for LocalPreviewTaskHandler {
             }
             Err(_) => {
                 tracing::warn!(preview_id = %preview_id, "系统繁忙，OCR任务排队等待");
-                match tokio::time::timeout(
-                    Duration::from_secs(SEMAPHORE_ACQUIRE_TIMEOUT_SECS),
-                    crate::OCR_SEMAPHORE.acquire(),
-                )
-                .await
-                {
+                let acquired = tokio::select! {
+                    result = tokio::time::timeout(
+                        Duration::from_secs(SEMAPHORE_ACQUIRE_TIMEOUT_SECS),
+                        crate::OCR_SEMAPHORE.acquire(),
+                    ) => result,
+                    _ = running.cancelled() => {
+                        tracing::info!(preview_id = %preview_id, "等待OCR处理许可期间预审被取消");
+                        return Ok(());
+                    }
+                };
+                match acquired {
                     Ok(result) => match result {
                         Ok(permit) => {
                             tracing::debug!(
@@ -1812,6 +1924,11 @@ impl PreviewTaskHandler for LocalPreviewTaskHandler {
             preview_body.preview.matter_name
         );
 
+        if running.is_cancelled() {
+            tracing::info!(preview_id = %preview_id, "预审已取消，释放OCR处理许可");
+            return Ok(());
+        }
+
         let attempt_id = Uuid::new_v4().to_string();
         if let Err(e) = database_clone
             .mark_preview_processing(&preview_id, "master", &attempt_id)
@@ -1895,6 +2012,19 @@ impl PreviewTaskHandler for LocalPreviewTaskHandler {
             }
         };
 
+        // 取消接口已写入 cancelled 状态并通知第三方，这里不再覆盖
+        let cancelled = running.is_cancelled()
+            || matches!(&execution_result, Err(err) if preview_cancel::is_cancellation(err));
+        if cancelled {
+            drop(permit);
+            tracing::info!(
+                preview_id = %preview_id,
+                elapsed_ms = job_start.elapsed().as_millis() as u64,
+                "预审任务已取消，停止处理"
+            );
+            return Ok(());
+        }
+
         let success = execution_result
             .as_ref()
             .map(|output| output.evaluation_result.is_some())
@@ -2077,7 +2207,11 @@ impl RemotePreviewTaskHandler {
 #[async_trait]
 impl PreviewTaskHandler for RemotePreviewTaskHandler {
     async fn handle_preview_task(&self, task: PreviewTask) -> Result<()> {
+        if let Some(shard) = task.shard {
+            return pdf_shard::handle_shard_task(&task.preview_id, shard, None).await;
+        }
         let mut preview_body = task.preview_body;
+        preview_body.force_reocr = task.force_reocr;
         let preview_id = task.preview_id;
         let third_party_request_id = task.third_party_request_id;
         let job_start = Instant::now();
@@ -2125,6 +2259,9 @@ impl PreviewTaskHandler for RemotePreviewTaskHandler {
         );
         let _worker_guard = worker_span.enter();
 
+        // 主节点在心跳应答中下发取消，由心跳循环写入本进程的取消登记
+        let running = preview_cancel::track(&preview_id);
+
         let permit = match crate::OCR_SEMAPHORE.try_acquire() {
             Ok(permit) => {
                 tracing::debug!(
@@ -2136,7 +2273,14 @@ impl PreviewTaskHandler for RemotePreviewTaskHandler {
             }
             Err(_) => {
                 tracing::warn!(preview_id = %preview_id, "Worker 系统繁忙，OCR任务排队等待");
-                match crate::OCR_SEMAPHORE.acquire().await {
+                let acquired = tokio::select! {
+                    result = crate::OCR_SEMAPHORE.acquire() => result,
+                    _ = running.cancelled() => {
+                        tracing::info!(preview_id = %preview_id, "Worker 等待OCR处理许可期间预审被取消");
+                        return Ok(());
+                    }
+                };
+                match acquired {
                     Ok(permit) => {
                         tracing::debug!(
                             preview_id = %preview_id,
@@ -2171,6 +2315,18 @@ impl PreviewTaskHandler for RemotePreviewTaskHandler {
             .notify_job_started(&preview_id, &attempt_id)
             .await
         {
+            if preview_cancel::is_cancellation(&err) {
+                tracing::info!(preview_id = %preview_id, "主节点拒绝开始：预审已取消");
+                return Ok(());
+            }
+            // 凭证被拒时本节点已暂停接单，任务交还队列由其他 worker 处理
+            if let Some(rejected) = err.downcast_ref::<WorkerRejected>() {
+                return Err(TaskReturned {
+                    preview_id: preview_id.clone(),
+                    reason: rejected.to_string(),
+                }
+                .into());
+            }
             tracing::error!(
                 preview_id = %preview_id,
                 attempt_id = %attempt_id,
@@ -2195,6 +2351,14 @@ impl PreviewTaskHandler for RemotePreviewTaskHandler {
             .execute_preview_with_options(None, None, false)
             .await;
 
+        if running.is_cancelled()
+            || matches!(&execution_result, Err(err) if preview_cancel::is_cancellation(err))
+        {
+            drop(permit);
+            tracing::info!(preview_id = %preview_id, "Worker 预审任务已取消，不上报结果");
+            return Ok(());
+        }
+
         let mut failure_reason: Option<String> = None;
         let mut evaluation_result = None;
         let mut web_result = None;
@@ -2382,12 +2546,32 @@ pub(crate) async fn persist_preview_request_record(
     Err(last_error.unwrap_or_else(|| anyhow!("未知错误：save_preview_request 失败")))
 }
 
-async fn sync_preview_request_status_inner(
+/// 取消标记只保存在进程内存中，重启后以记录状态为准
+pub(crate) async fn preview_cancelled(
+    database: &Arc<dyn crate::db::Database>,
+    preview_id: &str,
+) -> bool {
+    if preview_cancel::is_cancelled(preview_id) {
+        return true;
+    }
+    matches!(
+        database.get_preview_record(preview_id).await,
+        Ok(Some(record)) if record.status == PreviewStatus::Cancelled
+    )
+}
+
+pub(crate) async fn sync_preview_request_status_inner(
     database: &Arc<dyn crate::db::Database>,
     preview_id: &str,
     third_party_request_id: Option<&str>,
     status: PreviewStatus,
 ) {
+    // 所有状态变更都经过这里，入队与终态事件在此推送给进度订阅者
+    preview_progress::status(preview_id, status.as_str());
+    if status.is_terminal() {
+        crate::util::search::schedule_index(Arc::clone(database), preview_id.to_string());
+    }
+
     let request_id = third_party_request_id.and_then(|id| {
         let trimmed = id.trim();
         if trimmed.is_empty() {
@@ -2560,7 +2744,13 @@ pub async fn get_id_mapping_from_database(
     database.get_preview_record(preview_id).await
 }
 
-pub async fn download_latest_pdf(Path(preview_id): Path<String>) -> Response {
+pub async fn download_latest_pdf(
+    Path(preview_id): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> Response {
+    let range = headers
+        .get(axum::http::header::RANGE)
+        .and_then(|v| v.to_str().ok());
     let pdf_path = ocr_conn::CURRENT_DIR
         .join("preview")
         .join(format!("{}.pdf", preview_id));
@@ -2568,7 +2758,7 @@ pub async fn download_latest_pdf(Path(preview_id): Path<String>) -> Response {
         goto: pdf_path.to_string_lossy().to_string(),
     };
 
-    match PreviewBody::download_local(pdf_goto).await {
+    match PreviewBody::download_local(pdf_goto, range).await {
         Ok(response) => response,
         Err(pdf_err) => {
             tracing::debug!(
@@ -2582,7 +2772,7 @@ pub async fn download_latest_pdf(Path(preview_id): Path<String>) -> Response {
             let html_goto = Goto {
                 goto: html_path.to_string_lossy().to_string(),
             };
-            match PreviewBody::download_local(html_goto).await {
+            match PreviewBody::download_local(html_goto, range).await {
                 Ok(response) => response,
                 Err(html_err) => {
                     tracing::warn!(
@@ -2672,7 +2862,9 @@ pub async fn notify_third_party_system(
         payload,
         true,
     )
-    .await
+    .await?;
+    preview_progress::callback(preview_id);
+    Ok(())
 }
 
 
@@ -2747,6 +2939,7 @@ fn build_preview_body_from_any_json(json: &Value) -> anyhow::Result<PreviewBody>
         preview,
         rule_definition: None,
         parsed_rule_definition: None,
+        force_reocr: false,
     };
 
     Ok(preview_body)
@@ -3220,6 +3413,7 @@ fn create_fallback_preview_body(json: &Value) -> PreviewBody {
         preview,
         rule_definition: None,
         parsed_rule_definition: None,
+        force_reocr: false,
     }
 }
 
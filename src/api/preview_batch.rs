@@ -0,0 +1,497 @@
+//! 批量预审提交
+//!
+//! 请求体为 JSON 数组，或每行一条的 NDJSON（`Content-Type: application/x-ndjson`）。
+//! 请求体大小、NDJSON 单行长度与条数在入队前检查，超限时整批拒绝；
+//! 每一项按单条提交的流程校验、去重并入队，单项失败不影响其余项；受理结果按批次落库，
+//! 查询批次时再合并各预审的最新状态。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use axum::body::Body;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use futures::StreamExt;
+use serde::de::{IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::preview::{
+    auth_bypass_enabled, parse_preview_value, submit_preview, validate_preview_body,
+    SubmissionOutcome,
+};
+use crate::db::traits::{BatchItemStatus, PreviewBatchItemRecord};
+use crate::model::SessionUser;
+use crate::util::auth::AuthenticatedClient;
+use crate::util::WebResult;
+use crate::AppState;
+
+/// 单批最多受理的条数，超出时整批返回 413
+const BATCH_MAX_ITEMS: usize = 500;
+/// 请求体上限，JSON 数组与 NDJSON 相同
+const BATCH_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+/// NDJSON 单行上限
+const BATCH_MAX_LINE_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchItemView {
+    index: i32,
+    status: BatchItemStatus,
+    preview_id: Option<String>,
+    third_party_request_id: Option<String>,
+    error: Option<String>,
+    /// 预审当前状态，仅查询批次时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview_status: Option<String>,
+}
+
+impl From<&PreviewBatchItemRecord> for BatchItemView {
+    fn from(item: &PreviewBatchItemRecord) -> Self {
+        Self {
+            index: item.item_index,
+            status: item.status,
+            preview_id: item.preview_id.clone(),
+            third_party_request_id: item.third_party_request_id.clone(),
+            error: item.error.clone(),
+            preview_status: None,
+        }
+    }
+}
+
+fn batch_error(status: StatusCode, msg: impl ToString) -> Response {
+    (
+        status,
+        Json(WebResult::err_with_code(status.as_u16() as u32, msg)),
+    )
+        .into_response()
+}
+
+/// 提交方标识：优先第三方客户端，其次会话用户；调试模式下允许匿名
+fn resolve_submitter(
+    client: Option<&AuthenticatedClient>,
+    session_user: Option<&SessionUser>,
+    debug_mode: bool,
+) -> Option<String> {
+    client
+        .map(|client| client.client_id.clone())
+        .or_else(|| session_user.map(|user| user.user_id.clone()))
+        .or_else(|| debug_mode.then(|| "anonymous".to_string()))
+}
+
+/// 单项受理结果
+struct ItemOutcome {
+    status: BatchItemStatus,
+    preview_id: Option<String>,
+    third_party_request_id: Option<String>,
+    error: Option<String>,
+}
+
+impl ItemOutcome {
+    fn rejected(third_party_request_id: Option<String>, error: impl Into<String>) -> Self {
+        Self {
+            status: BatchItemStatus::Rejected,
+            preview_id: None,
+            third_party_request_id,
+            error: Some(error.into()),
+        }
+    }
+}
+
+struct BatchSubmission<'a> {
+    app_state: &'a AppState,
+    batch_id: String,
+    submitted_by: String,
+    session_user: Option<SessionUser>,
+    client_id: Option<String>,
+    debug_mode: bool,
+    items: Vec<PreviewBatchItemRecord>,
+}
+
+impl BatchSubmission<'_> {
+    async fn push(&mut self, raw: &[u8]) {
+        let outcome = match serde_json::from_slice::<Value>(raw) {
+            Ok(value) => self.submit(value, raw).await,
+            Err(err) => ItemOutcome::rejected(None, format!("无效的JSON格式: {}", err)),
+        };
+
+        self.items.push(PreviewBatchItemRecord {
+            batch_id: self.batch_id.clone(),
+            item_index: self.items.len() as i32,
+            submitted_by: self.submitted_by.clone(),
+            status: outcome.status,
+            preview_id: outcome.preview_id,
+            third_party_request_id: outcome.third_party_request_id,
+            error: outcome.error,
+            created_at: Utc::now(),
+        });
+    }
+
+    async fn submit(&self, value: Value, raw: &[u8]) -> ItemOutcome {
+        let mut preview_body = parse_preview_value(value);
+        let third_party_request_id = Some(preview_body.preview.request_id.clone())
+            .filter(|request_id| !request_id.trim().is_empty());
+
+        if let Err(errors) = validate_preview_body(&preview_body) {
+            return ItemOutcome::rejected(third_party_request_id, errors.join("; "));
+        }
+
+        if let Some(session_user) = self.session_user.as_ref() {
+            if preview_body.user_id != session_user.user_id && !self.debug_mode {
+                return ItemOutcome::rejected(third_party_request_id, "用户身份验证失败");
+            }
+        }
+
+        let (status, preview_id) = match submit_preview(
+            self.app_state,
+            &mut preview_body,
+            raw,
+            self.session_user.as_ref(),
+            self.client_id.as_deref(),
+        )
+        .await
+        {
+            Ok(SubmissionOutcome::Accepted { preview_id }) => {
+                (BatchItemStatus::Accepted, preview_id)
+            }
+            Ok(SubmissionOutcome::Duplicate { preview_id }) => {
+                (BatchItemStatus::Duplicate, preview_id)
+            }
+            Err(err) => return ItemOutcome::rejected(third_party_request_id, err.message),
+        };
+
+        ItemOutcome {
+            status,
+            preview_id: Some(preview_id),
+            third_party_request_id,
+            error: None,
+        }
+    }
+}
+
+fn too_large(msg: impl ToString) -> Response {
+    batch_error(StatusCode::PAYLOAD_TOO_LARGE, msg)
+}
+
+/// 读取请求体并拆出各条目；大小与条数超限时一条也不返回
+async fn read_batch_items(body: Body, is_ndjson: bool) -> Result<Vec<Vec<u8>>, Response> {
+    let mut stream = body.into_data_stream();
+    let mut received = 0usize;
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut lines: Vec<Vec<u8>> = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| {
+            batch_error(StatusCode::BAD_REQUEST, format!("读取请求体失败: {}", err))
+        })?;
+        received += chunk.len();
+        if received > BATCH_MAX_BODY_BYTES {
+            return Err(too_large(format!(
+                "请求体超过 {} 字节",
+                BATCH_MAX_BODY_BYTES
+            )));
+        }
+        buffer.extend_from_slice(&chunk);
+        if !is_ndjson {
+            continue;
+        }
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            push_line(&mut lines, &line)?;
+        }
+        if buffer.len() > BATCH_MAX_LINE_BYTES {
+            return Err(too_large(format!(
+                "第 {} 行超过 {} 字节",
+                lines.len() + 1,
+                BATCH_MAX_LINE_BYTES
+            )));
+        }
+    }
+    if !is_ndjson {
+        return parse_json_array(&buffer);
+    }
+    push_line(&mut lines, &buffer)?;
+    Ok(lines)
+}
+
+fn push_line(lines: &mut Vec<Vec<u8>>, line: &[u8]) -> Result<(), Response> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return Ok(());
+    }
+    if line.len() > BATCH_MAX_LINE_BYTES {
+        return Err(too_large(format!(
+            "第 {} 行超过 {} 字节",
+            lines.len() + 1,
+            BATCH_MAX_LINE_BYTES
+        )));
+    }
+    if lines.len() >= BATCH_MAX_ITEMS {
+        return Err(too_large(format!(
+            "单批最多 {} 条，未受理任何条目",
+            BATCH_MAX_ITEMS
+        )));
+    }
+    lines.push(line.to_vec());
+    Ok(())
+}
+
+fn parse_json_array(bytes: &[u8]) -> Result<Vec<Vec<u8>>, Response> {
+    let items = serde_json::from_slice::<BatchItems>(bytes).map_err(|err| {
+        batch_error(
+            StatusCode::BAD_REQUEST,
+            format!("请求体须为JSON数组或NDJSON: {}", err),
+        )
+    })?;
+    if items.total > BATCH_MAX_ITEMS {
+        return Err(too_large(format!(
+            "单批最多 {} 条，实际 {} 条",
+            BATCH_MAX_ITEMS, items.total
+        )));
+    }
+    Ok(items
+        .values
+        .iter()
+        .map(|value| serde_json::to_vec(value).unwrap_or_default())
+        .collect())
+}
+
+/// JSON 数组的前 [`BATCH_MAX_ITEMS`] 项；其余项只计数不构造
+struct BatchItems {
+    values: Vec<Value>,
+    total: usize,
+}
+
+impl<'de> Deserialize<'de> for BatchItems {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemsVisitor;
+
+        impl<'de> Visitor<'de> for ItemsVisitor {
+            type Value = BatchItems;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("JSON数组")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BatchItems, A::Error> {
+                let mut values = Vec::new();
+                let mut total = 0;
+                while total < BATCH_MAX_ITEMS {
+                    match seq.next_element::<Value>()? {
+                        Some(value) => values.push(value),
+                        None => return Ok(BatchItems { values, total }),
+                    }
+                    total += 1;
+                }
+                while seq.next_element::<IgnoredAny>()?.is_some() {
+                    total += 1;
+                }
+                Ok(BatchItems { values, total })
+            }
+        }
+
+        deserializer.deserialize_seq(ItemsVisitor)
+    }
+}
+
+/// POST /api/preview/batch
+pub async fn submit_batch(State(app_state): State<AppState>, req: Request) -> Response {
+    let (parts, body) = req.into_parts();
+    let client = parts.extensions.get::<AuthenticatedClient>().cloned();
+    let session_user = parts.extensions.get::<SessionUser>().cloned();
+    let debug_mode = auth_bypass_enabled();
+
+    let Some(submitted_by) = resolve_submitter(client.as_ref(), session_user.as_ref(), debug_mode)
+    else {
+        return batch_error(StatusCode::UNAUTHORIZED, "需要第三方客户端认证或用户登录");
+    };
+
+    let is_ndjson = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("ndjson") || v.contains("jsonl"))
+        .unwrap_or(false);
+
+    let mut batch = BatchSubmission {
+        app_state: &app_state,
+        batch_id: Uuid::new_v4().to_string(),
+        submitted_by,
+        session_user,
+        client_id: client.map(|client| client.client_id),
+        debug_mode,
+        items: Vec::new(),
+    };
+
+    let items = match read_batch_items(body, is_ndjson).await {
+        Ok(items) => items,
+        Err(response) => return response,
+    };
+    for raw in &items {
+        batch.push(raw).await;
+    }
+
+    if batch.items.is_empty() {
+        return batch_error(StatusCode::BAD_REQUEST, "批量提交不能为空");
+    }
+
+    if let Err(err) = app_state
+        .database
+        .save_preview_batch_items(&batch.items)
+        .await
+    {
+        // 预审已入队，批次记录缺失只影响批次查询
+        warn!(batch_id = %batch.batch_id, error = %err, "保存批量提交记录失败");
+    }
+
+    let count = |status: BatchItemStatus| {
+        batch
+            .items
+            .iter()
+            .filter(|item| item.status == status)
+            .count()
+    };
+    let accepted = count(BatchItemStatus::Accepted);
+    let duplicate = count(BatchItemStatus::Duplicate);
+    let rejected = count(BatchItemStatus::Rejected);
+    info!(
+        batch_id = %batch.batch_id,
+        submitted_by = %batch.submitted_by,
+        total = batch.items.len(),
+        accepted,
+        duplicate,
+        rejected,
+        "批量预审提交完成"
+    );
+
+    let items: Vec<BatchItemView> = batch.items.iter().map(BatchItemView::from).collect();
+    Json(WebResult::ok(json!({
+        "batchId": batch.batch_id,
+        "total": items.len(),
+        "accepted": accepted,
+        "duplicate": duplicate,
+        "rejected": rejected,
+        "items": items,
+    })))
+    .into_response()
+}
+
+/// GET /api/preview/batch/:batch_id
+pub async fn get_batch(
+    State(app_state): State<AppState>,
+    Path(batch_id): Path<String>,
+    req: Request,
+) -> Response {
+    let debug_mode = auth_bypass_enabled();
+    let Some(requester) = resolve_submitter(
+        req.extensions().get::<AuthenticatedClient>(),
+        req.extensions().get::<SessionUser>(),
+        debug_mode,
+    ) else {
+        return batch_error(StatusCode::UNAUTHORIZED, "需要第三方客户端认证或用户登录");
+    };
+
+    let records = match app_state.database.list_preview_batch_items(&batch_id).await {
+        Ok(records) if records.is_empty() => {
+            return batch_error(StatusCode::NOT_FOUND, "批次不存在")
+        }
+        Ok(records) => records,
+        Err(err) => {
+            return batch_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("查询批次失败: {}", err),
+            )
+        }
+    };
+
+    if !debug_mode && records[0].submitted_by != requester {
+        warn!(batch_id = %batch_id, requester = %requester, "无权查询他人提交的批次");
+        return batch_error(StatusCode::FORBIDDEN, "无权限访问");
+    }
+
+    let mut progress: BTreeMap<String, usize> = BTreeMap::new();
+    let mut items = Vec::with_capacity(records.len());
+    for record in &records {
+        let mut view = BatchItemView::from(record);
+        if let Some(preview_id) = record.preview_id.as_deref() {
+            let status = match app_state.database.get_preview_record(preview_id).await {
+                Ok(Some(preview)) => preview.status.as_str().to_string(),
+                Ok(None) => "unknown".to_string(),
+                Err(err) => {
+                    warn!(preview_id = %preview_id, error = %err, "查询批次内预审状态失败");
+                    "unknown".to_string()
+                }
+            };
+            *progress.entry(status.clone()).or_default() += 1;
+            view.preview_status = Some(status);
+        }
+        items.push(view);
+    }
+
+    Json(WebResult::ok(json!({
+        "batchId": batch_id,
+        "total": items.len(),
+        "createdAt": records[0].created_at,
+        "progress": progress,
+        "items": items,
+    })))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submitter_prefers_client_then_session_user() {
+        let client = AuthenticatedClient {
+            client_id: "import-job".to_string(),
+            client_name: "导入任务".to_string(),
+            source_type: "direct_api".to_string(),
+            permissions: Vec::new(),
+        };
+        assert_eq!(
+            resolve_submitter(Some(&client), None, false).as_deref(),
+            Some("import-job")
+        );
+        assert_eq!(resolve_submitter(None, None, false), None);
+        assert_eq!(
+            resolve_submitter(None, None, true).as_deref(),
+            Some("anonymous")
+        );
+    }
+
+    #[tokio::test]
+    async fn over_limit_batches_are_rejected_before_any_item() {
+        let array = format!("[{}]", vec!["{}"; BATCH_MAX_ITEMS + 1].join(","));
+        let err = read_batch_items(Body::from(array), false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let ndjson = "{}\n".repeat(BATCH_MAX_ITEMS + 1);
+        let err = read_batch_items(Body::from(ndjson), true)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let long_line = format!("{{\"a\":\"{}\"}}", "x".repeat(BATCH_MAX_LINE_BYTES));
+        let err = read_batch_items(Body::from(long_line), true)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let items = read_batch_items(Body::from("{\"a\":1}\n\n{\"a\":2}"), true)
+            .await
+            .unwrap();
+        assert_eq!(items, vec![br#"{"a":1}"#.to_vec(), br#"{"a":2}"#.to_vec()]);
+        let items = read_batch_items(Body::from(r#"[{"a":1},{"a":2}]"#), false)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 2);
+    }
+}
@@ -0,0 +1,523 @@
+//! 预审取消与重新执行
+//!
+//! 取消会移除尚未出队的任务、向执行中的评估发出取消信号，并将状态置为 cancelled；
+//! 重新执行优先复用任务载荷（含已缓存的材料），没有载荷或要求重新下载时回到材料下载队列。
+//! 进度订阅以 SSE 推送各阶段事件，预审结束后关闭。
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{Path, Request, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use super::preview::{
+    embed_matter_rule, notify_third_party_system, sync_preview_request_status_inner,
+};
+use crate::db::traits::PreviewRecord;
+use crate::db::{Database, PreviewStatus};
+use crate::model::SessionUser;
+use crate::util::preview_cancel;
+use crate::util::preview_progress::{self, ProgressEvent, ProgressStage};
+use crate::util::task_queue::{PreviewTask, TaskScheduling};
+use crate::util::WebResult;
+use crate::AppState;
+
+/// 重新执行选项，请求体为空时全部取默认值
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RerunOptions {
+    /// 忽略已缓存的材料，按原始请求重新下载附件
+    pub force_redownload: bool,
+    /// 忽略 OCR 缓存与已识别附件，重新识别
+    pub force_reocr: bool,
+    /// 丢弃载荷中打包的规则，改用事项当前配置的规则
+    pub use_current_rules: bool,
+}
+
+fn control_error(status: StatusCode, msg: impl ToString) -> Response {
+    (
+        status,
+        Json(WebResult::err_with_code(status.as_u16() as u32, msg)),
+    )
+        .into_response()
+}
+
+/// 监控证书可操作任意预审，其余用户只能操作自己提交的预审。
+/// 会话需在 await 前从请求中克隆出来：Body 不是 Sync，&Request 跨 await 的 handler 不是 Send
+async fn load_owned_record(
+    app_state: &AppState,
+    preview_id: &str,
+    session_user: Option<SessionUser>,
+) -> Result<PreviewRecord, Response> {
+    let Some(session_user) = session_user else {
+        return Err(control_error(StatusCode::UNAUTHORIZED, "认证信息缺失"));
+    };
+
+    let record = match app_state.database.get_preview_record(preview_id).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(control_error(StatusCode::NOT_FOUND, "预审记录不存在")),
+        Err(err) => {
+            return Err(control_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("查询预审记录失败: {}", err),
+            ))
+        }
+    };
+
+    let is_monitor = session_user
+        .certificate_type
+        .eq_ignore_ascii_case("monitor");
+    if !is_monitor && record.user_id != session_user.user_id {
+        warn!(
+            preview_id = %preview_id,
+            user_id = %session_user.user_id,
+            "无权操作他人提交的预审"
+        );
+        return Err(control_error(StatusCode::FORBIDDEN, "无权限访问"));
+    }
+    Ok(record)
+}
+
+async fn set_status(
+    app_state: &AppState,
+    record: &PreviewRecord,
+    status: PreviewStatus,
+) -> Result<()> {
+    app_state
+        .database
+        .update_preview_status(&record.id, status.clone())
+        .await
+        .context("更新预审状态失败")?;
+    // 终态由同步函数刷新检索索引；重新执行回到排队时同样刷新，索引不再显示旧结果状态
+    if !status.is_terminal() {
+        crate::util::search::schedule_index(Arc::clone(&app_state.database), record.id.clone());
+    }
+    sync_preview_request_status_inner(
+        &app_state.database,
+        &record.id,
+        record.third_party_request_id.as_deref(),
+        status,
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn cancel_preview(
+    State(app_state): State<AppState>,
+    Path(preview_id): Path<String>,
+    req: Request,
+) -> Response {
+    let session_user = req.extensions().get::<SessionUser>().cloned();
+    let record = match load_owned_record(&app_state, &preview_id, session_user).await {
+        Ok(record) => record,
+        Err(resp) => return resp,
+    };
+
+    match record.status {
+        PreviewStatus::Completed | PreviewStatus::Failed => {
+            return control_error(StatusCode::CONFLICT, "预审已结束，无法取消");
+        }
+        PreviewStatus::Cancelled => {
+            // 重复取消时补发信号，覆盖主节点重启后登记丢失的情况
+            preview_cancel::cancel(&preview_id);
+            return Json(WebResult::ok(json!({
+                "previewId": preview_id,
+                "status": PreviewStatus::Cancelled.as_str(),
+                "removedFromQueue": 0,
+                "signalledRunning": false,
+            })))
+            .into_response();
+        }
+        _ => {}
+    }
+
+    let signalled = preview_cancel::cancel(&preview_id);
+
+    let mut removed = match app_state.task_queue.cancel(&preview_id).await {
+        Ok(count) => count,
+        Err(err) => {
+            warn!(preview_id = %preview_id, error = %err, "移除排队任务失败，依赖出队时的取消检查");
+            0
+        }
+    };
+    match app_state
+        .database
+        .cancel_material_downloads(&preview_id)
+        .await
+    {
+        Ok(count) => removed += count,
+        Err(err) => warn!(preview_id = %preview_id, error = %err, "移除材料下载任务失败"),
+    }
+
+    if let Err(err) = set_status(&app_state, &record, PreviewStatus::Cancelled).await {
+        return control_error(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err));
+    }
+
+    let third_party_request_id = record.third_party_request_id.as_deref().unwrap_or("");
+    if let Err(err) = notify_third_party_system(
+        &preview_id,
+        third_party_request_id,
+        PreviewStatus::Cancelled.as_str(),
+        None,
+        Some("PREVIEW_CANCELLED"),
+    )
+    .await
+    {
+        warn!(preview_id = %preview_id, error = %err, "取消后通知第三方失败");
+    }
+
+    info!(
+        preview_id = %preview_id,
+        previous_status = %record.status,
+        removed_from_queue = removed,
+        signalled_running = signalled,
+        "预审已取消"
+    );
+
+    Json(WebResult::ok(json!({
+        "previewId": preview_id,
+        "status": PreviewStatus::Cancelled.as_str(),
+        "removedFromQueue": removed,
+        "signalledRunning": signalled,
+    })))
+    .into_response()
+}
+
+pub async fn rerun_preview(
+    State(app_state): State<AppState>,
+    Path(preview_id): Path<String>,
+    req: Request,
+) -> Response {
+    let session_user = req.extensions().get::<SessionUser>().cloned();
+    let record = match load_owned_record(&app_state, &preview_id, session_user).await {
+        Ok(record) => record,
+        Err(resp) => return resp,
+    };
+
+    let body = match axum::body::to_bytes(req.into_body(), 64 * 1024).await {
+        Ok(body) => body,
+        Err(err) => {
+            return control_error(StatusCode::BAD_REQUEST, format!("读取请求体失败: {}", err))
+        }
+    };
+    let options = if body.iter().all(u8::is_ascii_whitespace) {
+        RerunOptions::default()
+    } else {
+        match serde_json::from_slice::<RerunOptions>(&body) {
+            Ok(options) => options,
+            Err(err) => {
+                return control_error(StatusCode::BAD_REQUEST, format!("请求参数无效: {}", err))
+            }
+        }
+    };
+
+    if matches!(
+        record.status,
+        PreviewStatus::Pending | PreviewStatus::Queued | PreviewStatus::Processing
+    ) {
+        return control_error(StatusCode::CONFLICT, "预审尚未结束，请先取消后再重新执行");
+    }
+
+    preview_cancel::clear(&preview_id);
+
+    let task = if options.force_redownload {
+        None
+    } else {
+        load_task(&app_state, &preview_id).await
+    };
+    let (mode, result) = match task {
+        Some(task) => (
+            "reuse_payload",
+            requeue_task(&app_state, task, &options).await,
+        ),
+        None => (
+            "redownload",
+            requeue_download(&app_state.database, &preview_id, &options).await,
+        ),
+    };
+
+    let status = match result {
+        Ok(status) => status,
+        Err(err) => {
+            warn!(preview_id = %preview_id, mode, "重新执行预审失败: {:#}", err);
+            return control_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("重新执行失败: {:#}", err),
+            );
+        }
+    };
+
+    if let Err(err) = set_status(&app_state, &record, status.clone()).await {
+        warn!(preview_id = %preview_id, error = %err, "重新执行已入队，但更新状态失败");
+    }
+
+    info!(
+        preview_id = %preview_id,
+        previous_status = %record.status,
+        mode,
+        force_reocr = options.force_reocr,
+        use_current_rules = options.use_current_rules,
+        "预审已重新执行"
+    );
+
+    Json(WebResult::ok(json!({
+        "previewId": preview_id,
+        "status": status.as_str(),
+        "mode": mode,
+        "forceReocr": options.force_reocr,
+        "useCurrentRules": options.use_current_rules,
+    })))
+    .into_response()
+}
+
+/// 终态事件之后继续等待回调事件的时长
+const PROGRESS_LINGER: Duration = Duration::from_secs(3);
+/// 长时间没有事件时回查数据库状态，覆盖主节点重启或事件未经本进程的情况
+const PROGRESS_STATUS_POLL: Duration = Duration::from_secs(15);
+
+pub async fn stream_progress(
+    State(app_state): State<AppState>,
+    Path(preview_id): Path<String>,
+    req: Request,
+) -> Response {
+    let session_user = req.extensions().get::<SessionUser>().cloned();
+    let record = match load_owned_record(&app_state, &preview_id, session_user).await {
+        Ok(record) => record,
+        Err(resp) => return resp,
+    };
+
+    let (last, receiver) = preview_progress::subscribe(&preview_id);
+    let pending = last
+        .or_else(|| preview_progress::snapshot_from_status(&preview_id, record.status.as_str()));
+    // 订阅时已经结束的预审只推送一次最终状态
+    let finished = matches!(
+        record.status,
+        PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled
+    );
+    let closing_at = pending
+        .as_ref()
+        .filter(|event| {
+            event.stage.is_terminal() || (event.stage == ProgressStage::Callback && finished)
+        })
+        .map(|_| Instant::now());
+
+    let stream = ProgressStream {
+        app_state,
+        preview_id,
+        receiver,
+        pending,
+        closing_at,
+    };
+    let events = futures::stream::unfold(stream, |mut stream| async move {
+        let event = stream.next_event().await?;
+        Some((Ok::<_, Infallible>(event), stream))
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+struct ProgressStream {
+    app_state: AppState,
+    preview_id: String,
+    receiver: broadcast::Receiver<ProgressEvent>,
+    pending: Option<ProgressEvent>,
+    closing_at: Option<Instant>,
+}
+
+impl ProgressStream {
+    async fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.take() {
+                match (event.stage, self.closing_at) {
+                    (ProgressStage::Callback, Some(_)) => self.closing_at = Some(Instant::now()),
+                    (stage, None) if stage.is_terminal() => {
+                        self.closing_at = Some(Instant::now() + PROGRESS_LINGER)
+                    }
+                    _ => {}
+                }
+                return Some(progress_event(&event));
+            }
+
+            let deadline = match self.closing_at {
+                Some(at) if at <= Instant::now() => return None,
+                Some(at) => at,
+                None => Instant::now() + PROGRESS_STATUS_POLL,
+            };
+
+            tokio::select! {
+                received = self.receiver.recv() => match received {
+                    Ok(event) => self.pending = Some(event),
+                    // 落后的订阅者直接跳到最新事件
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = tokio::time::sleep_until(deadline) => {
+                    if self.closing_at.is_some() {
+                        return None;
+                    }
+                    if let Ok(Some(record)) =
+                        self.app_state.database.get_preview_record(&self.preview_id).await
+                    {
+                        self.pending = preview_progress::snapshot_from_status(
+                            &self.preview_id,
+                            record.status.as_str(),
+                        )
+                        .filter(|event| event.stage.is_terminal());
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn progress_event(event: &ProgressEvent) -> Event {
+    Event::default()
+        .id(event.seq.to_string())
+        .data(serde_json::to_string(event).unwrap_or_default())
+}
+
+/// 成功完成的预审不保留任务载荷，此时返回 None 并改为重新下载
+async fn load_task(app_state: &AppState, preview_id: &str) -> Option<PreviewTask> {
+    match app_state.database.load_task_payload(preview_id).await {
+        Ok(Some(payload)) => match serde_json::from_str::<PreviewTask>(&payload) {
+            Ok(task) => Some(task),
+            Err(err) => {
+                warn!(preview_id = %preview_id, error = %err, "任务载荷无法解析，改为重新下载");
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(err) => {
+            warn!(preview_id = %preview_id, error = %err, "读取任务载荷失败，改为重新下载");
+            None
+        }
+    }
+}
+
+async fn requeue_task(
+    app_state: &AppState,
+    mut task: PreviewTask,
+    options: &RerunOptions,
+) -> Result<PreviewStatus> {
+    if options.use_current_rules {
+        task.preview_body.rule_definition = None;
+        task.preview_body.parsed_rule_definition = None;
+        embed_matter_rule(&app_state.database, &mut task.preview_body).await;
+    }
+    task.force_reocr = options.force_reocr;
+
+    let payload = serde_json::to_string(&task).context("序列化预审任务失败")?;
+    app_state
+        .database
+        .save_task_payload(&task.preview_id, &payload)
+        .await
+        .context("保存任务载荷失败")?;
+    app_state
+        .task_queue
+        .enqueue(task)
+        .await
+        .context("预审任务入队失败")?;
+    Ok(PreviewStatus::Queued)
+}
+
+async fn requeue_download(
+    database: &Arc<dyn Database>,
+    preview_id: &str,
+    options: &RerunOptions,
+) -> Result<PreviewStatus> {
+    let payload = database
+        .latest_material_download_payload(preview_id)
+        .await
+        .context("读取原始请求失败")?
+        .ok_or_else(|| anyhow!("未找到可重新下载的原始请求"))?;
+
+    let mut scheduling = TaskScheduling::extract(&payload).unwrap_or_default();
+    scheduling.force_reocr = options.force_reocr;
+
+    let mut body: Value = serde_json::from_str(&payload).context("原始请求不是有效的 JSON")?;
+    if options.use_current_rules {
+        if let Some(object) = body.as_object_mut() {
+            object.remove("rule_definition");
+        }
+    }
+    let body = serde_json::to_vec(&body).context("序列化原始请求失败")?;
+    let payload = scheduling
+        .embed(&body)
+        .ok_or_else(|| anyhow!("原始请求不是 JSON 对象"))?;
+
+    database
+        .enqueue_material_download(preview_id, &payload)
+        .await
+        .context("材料下载任务入队失败")?;
+    Ok(PreviewStatus::Pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+
+    #[test]
+    fn rerun_options_default_to_reuse() {
+        let options: RerunOptions = serde_json::from_str("{}").unwrap();
+        assert!(!options.force_redownload && !options.force_reocr && !options.use_current_rules);
+
+        let options: RerunOptions =
+            serde_json::from_str(r#"{"forceReocr":true,"useCurrentRules":true}"#).unwrap();
+        assert!(options.force_reocr && options.use_current_rules);
+        assert!(!options.force_redownload);
+    }
+
+    #[tokio::test]
+    async fn rerun_of_completed_preview_redownloads_on_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite = SqliteDatabase::new(dir.path().join("rerun.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.initialize().await.unwrap();
+        MigrationRunner::new(&sqlite).migrate_up().await.unwrap();
+        let database: Arc<dyn Database> = Arc::new(sqlite);
+
+        // 提交时写入的原始请求已被下载服务处理完成
+        let original = r#"{"preview":{"matterId":"m1"},"rule_definition":{"rules":[]}}"#;
+        database
+            .enqueue_material_download("p1", original)
+            .await
+            .unwrap();
+        let first = database.fetch_pending_material_downloads(10).await.unwrap();
+        database
+            .update_material_download_status(&first[0].id, "completed", None)
+            .await
+            .unwrap();
+
+        let options = RerunOptions {
+            force_reocr: true,
+            use_current_rules: true,
+            ..Default::default()
+        };
+        let status = requeue_download(&database, "p1", &options).await.unwrap();
+        assert_eq!(status, PreviewStatus::Pending);
+
+        let pending = database.fetch_pending_material_downloads(10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        let body: Value = serde_json::from_str(&pending[0].payload).unwrap();
+        assert!(body.get("rule_definition").is_none());
+        let scheduling = TaskScheduling::extract(&pending[0].payload).unwrap();
+        assert!(scheduling.force_reocr);
+
+        assert_eq!(database.cancel_material_downloads("p1").await.unwrap(), 1);
+    }
+}
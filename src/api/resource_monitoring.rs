@@ -26,6 +26,7 @@ pub struct ResourceMonitoringResponse {
     pub health_score: u8,
     pub timestamp: u64,
     pub ocr_pool: Option<OcrPoolStats>,
+    pub ocr_preprocess: Vec<ocr_conn::preprocess::StepStats>,
     pub worker_heartbeats: Vec<WorkerStatusSummary>,
     pub worker_summary: WorkerClusterSummary,
 }
@@ -117,9 +118,12 @@ pub struct OcrPoolStats {
     pub total_started: u64,
     pub total_restarted: u64,
     pub total_failures: u64,
+    pub total_retired: u64,
     pub consecutive_failures: u32,
     pub circuit_open: bool,
     pub circuit_open_until_epoch: Option<u64>,
+    pub total_rss_kb: u64,
+    pub engines: Vec<ocr_conn::ocr::EngineStats>,
 }
 
 #[derive(Debug, Serialize)]
@@ -206,6 +210,7 @@ pub async fn get_monitoring_status(
         health_score,
         timestamp,
         ocr_pool,
+        ocr_preprocess: ocr_conn::preprocess::preprocess_stats(),
         worker_heartbeats,
         worker_summary,
     };
@@ -463,9 +468,12 @@ fn collect_ocr_pool_stats() -> Option<OcrPoolStats> {
         total_started: stats.total_started,
         total_restarted: stats.total_restarted,
         total_failures: stats.total_failures,
+        total_retired: stats.total_retired,
         consecutive_failures: stats.consecutive_failures,
         circuit_open: stats.circuit_open,
         circuit_open_until_epoch: stats.circuit_open_until_epoch,
+        total_rss_kb: stats.total_rss_kb,
+        engines: stats.engines,
     })
 }
 
@@ -653,6 +661,7 @@ mod tests {
                 bytes_out_per_sec: 1000000,
                 active_connections: 10,
             },
+            watchdog_states: Vec::new(),
         };
 
         let tracing = TracingStatus {
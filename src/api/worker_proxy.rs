@@ -7,7 +7,7 @@ use anyhow::{anyhow, Context};
 use axum::extract::State;
 use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
-use axum::routing::{post, put};
+use axum::routing::{get, post, put};
 use axum::{extract::Path, Json, Router};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -17,15 +17,23 @@ use tracing::{error, info, warn};
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 
 use crate::db::traits::{MaterialFileFilter, PreviewFailureUpdate};
+use crate::db::PreviewStatus;
 use crate::model::evaluation::{AttachmentInfo, PreviewEvaluationResult};
 use crate::model::preview::PreviewBody;
-use crate::storage::Storage;
+use crate::storage::{Storage, StorageReader};
+use crate::util::blob_store;
 use crate::util::config::types::DeploymentRole;
+use crate::util::http_range;
 use crate::util::material_cache;
+use crate::util::pdf_shard::{self, ShardJobRequest, ShardReport};
+use crate::util::preview_cancel;
+use crate::util::preview_progress::{self, ProgressEvent};
 use crate::util::report::PreviewReportGenerator;
 use crate::util::rules::matches_ocr_failure;
 use crate::util::task_queue::{PreviewTask, PreviewTaskHandler};
+use crate::util::task_routing::{self, WorkerCapabilities};
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
+use crate::util::worker_fleet;
 use crate::util::{IntoJson, WebResult};
 use crate::AppState;
 use image::codecs::jpeg::JpegEncoder;
@@ -33,10 +41,10 @@ use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView};
 use ocr_conn::CURRENT_DIR;
 use url::Url;
-use urlencoding::encode;
 
 use super::preview::{
-    notify_third_party_system, sync_preview_request_status_with_hint, LocalPreviewTaskHandler,
+    notify_third_party_system, preview_cancelled, sync_preview_request_status_with_hint,
+    LocalPreviewTaskHandler,
 };
 
 use base64::{engine::general_purpose::STANDARD as BASE64_ENGINE, Engine as _};
@@ -180,6 +188,9 @@ pub struct WorkerHeartbeatRequest {
     pub last_job_started_at: Option<String>,
     #[serde(default)]
     pub last_job_finished_at: Option<String>,
+    /// 旧版本 worker 不上报，按默认能力处理
+    #[serde(default)]
+    pub capabilities: Option<WorkerCapabilities>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
@@ -227,6 +238,7 @@ struct WorkerHeartbeatState {
     restart_cooldown_until: Option<DateTime<Utc>>,
     last_job_started_at: Option<DateTime<Utc>>,
     last_job_finished_at: Option<DateTime<Utc>>,
+    capabilities: WorkerCapabilities,
 }
 
 pub fn routes() -> Router<AppState> {
@@ -240,11 +252,28 @@ pub fn routes() -> Router<AppState> {
             "/internal/worker/previews/:preview_id/start",
             post(worker_start_handler),
         )
+        .route(
+            "/internal/worker/previews/:preview_id/progress",
+            post(worker_progress_handler),
+        )
         .route(
             "/internal/worker/previews/:preview_id/result",
             put(worker_result_handler),
         )
         .route("/internal/worker/heartbeat", post(heartbeat_handler))
+        .route("/internal/worker/shards", post(shard_open_handler))
+        .route(
+            "/internal/worker/shards/:job_id",
+            get(shard_status_handler).delete(shard_close_handler),
+        )
+        .route(
+            "/internal/worker/shards/:job_id/:index/claim",
+            post(shard_claim_handler),
+        )
+        .route(
+            "/internal/worker/shards/:job_id/:index/result",
+            post(shard_result_handler),
+        )
 }
 
 async fn fetch_material_handler(
@@ -270,8 +299,8 @@ async fn fetch_material_handler(
         "worker 请求材料缓存"
     );
 
-    match material_cache::read_material(&payload.token).await {
-        Ok(bytes) => {
+    match material_cache::open_material(&payload.token).await {
+        Ok((file, size)) => {
             METRICS_COLLECTOR.record_preview_download(
                 true,
                 download_start.elapsed(),
@@ -282,8 +311,11 @@ async fn fetch_material_handler(
                 .await
                 .unwrap_or_else(|| ("attachment.bin".to_string(), None));
 
-            let mut response = Response::new(bytes.into());
+            let mut response = Response::new(http_range::stream_body(file));
             *response.status_mut() = StatusCode::OK;
+            response
+                .headers_mut()
+                .insert(header::CONTENT_LENGTH, header::HeaderValue::from(size));
 
             let ct_value = content_type.unwrap_or_else(|| "application/octet-stream".to_string());
             response.headers_mut().insert(
@@ -321,18 +353,31 @@ async fn fetch_material_handler(
                         .iter()
                         .find(|r| r.stored_original_key.trim().len() > 0)
                     {
-                        match app_state.storage.get(&record.stored_original_key).await {
-                            Ok(Some(bytes)) => {
-                                if let Some(path) =
-                                    material_cache::get_material_path(&payload.token).await
-                                {
-                                    if let Some(parent) = path.parent() {
-                                        let _ = tokio::fs::create_dir_all(parent).await;
-                                    }
-                                    let _ = tokio::fs::write(&path, &bytes).await;
-                                }
-
-                                let mut response = Response::new(bytes.into());
+                        match app_state
+                            .storage
+                            .get_reader(&record.stored_original_key, None)
+                            .await
+                        {
+                            Ok(Some(reader)) => {
+                                let Some(body) = cache_stored_material(
+                                    &app_state.storage,
+                                    &record.stored_original_key,
+                                    &payload.token,
+                                    reader,
+                                )
+                                .await
+                                else {
+                                    METRICS_COLLECTOR.record_preview_download(
+                                        false,
+                                        download_start.elapsed(),
+                                        "worker_cache_fallback",
+                                    );
+                                    return error_response(
+                                        StatusCode::NOT_FOUND,
+                                        format!("材料未找到: {}", err),
+                                    );
+                                };
+                                let mut response = Response::new(body);
                                 *response.status_mut() = StatusCode::OK;
                                 let ct_value = record
                                     .mime_type
@@ -397,6 +442,39 @@ async fn fetch_material_handler(
     }
 }
 
+/// 将存储中的材料写入本地缓存后从缓存文件流式返回；写缓存失败时重新打开存储流直接转发
+async fn cache_stored_material(
+    storage: &Arc<dyn Storage>,
+    key: &str,
+    token: &str,
+    mut reader: StorageReader,
+) -> Option<axum::body::Body> {
+    let Some(path) = material_cache::get_material_path(token).await else {
+        return Some(http_range::stream_body(reader));
+    };
+    let cached = async {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::File::create(&path).await?;
+        tokio::io::copy(&mut reader, &mut file).await?;
+        file.sync_all().await?;
+        tokio::fs::File::open(&path).await
+    }
+    .await;
+    match cached {
+        Ok(file) => Some(http_range::stream_body(file)),
+        Err(err) => {
+            warn!(token = %token, key = %key, error = %err, "写入材料缓存失败，直接转发存储流");
+            let _ = tokio::fs::remove_file(&path).await;
+            match storage.get_reader(key, None).await {
+                Ok(Some(reader)) => Some(http_range::stream_body(reader)),
+                _ => None,
+            }
+        }
+    }
+}
+
 pub async fn enrich_preview_attachments(
     database: &Arc<dyn crate::db::Database>,
     storage: &Arc<dyn Storage>,
@@ -590,6 +668,68 @@ pub async fn enrich_preview_attachments(
     Ok(())
 }
 
+/// 返回结果前为附件里的文件代理链接现签签名，只签本预审材料记录登记过的存储键
+pub async fn sign_preview_links(
+    database: &Arc<dyn crate::db::Database>,
+    preview_id: &str,
+    result: &mut PreviewEvaluationResult,
+) {
+    let filter = MaterialFileFilter {
+        preview_id: Some(preview_id.to_string()),
+        material_code: None,
+    };
+    let records = match database.list_material_files(&filter).await {
+        Ok(records) => records,
+        Err(err) => {
+            warn!(
+                preview_id = %preview_id,
+                error = %err,
+                "查询材料文件记录失败，附件链接未签名"
+            );
+            return;
+        }
+    };
+
+    let mut owned = HashSet::new();
+    for record in records {
+        if let Some(keys) = record
+            .stored_processed_keys
+            .as_deref()
+            .and_then(|raw| serde_json::from_str::<Vec<String>>(raw).ok())
+        {
+            owned.extend(keys);
+        }
+        owned.insert(record.stored_original_key);
+    }
+    owned.remove("");
+    if owned.is_empty() {
+        return;
+    }
+
+    let sign = |url: &mut String| {
+        if let Some(signed) = crate::storage::signing::resign_proxy_url(url, &owned) {
+            *url = signed;
+        }
+    };
+
+    for attachment in result
+        .material_results
+        .iter_mut()
+        .flat_map(|material| material.attachments.iter_mut())
+    {
+        sign(&mut attachment.file_url);
+        attachment.preview_url.iter_mut().for_each(sign);
+        attachment.thumbnail_url.iter_mut().for_each(sign);
+        if let Some(JsonValue::Object(extra)) = attachment.extra.as_mut() {
+            for value in extra.values_mut() {
+                if let JsonValue::String(url) = value {
+                    sign(url);
+                }
+            }
+        }
+    }
+}
+
 async fn apply_record_links(
     attachment: &mut AttachmentInfo,
     extra_map: &mut JsonMap<String, JsonValue>,
@@ -1174,7 +1314,7 @@ fn guess_extension_from_mime(mime: &str) -> Option<String> {
     }
 }
 
-fn sanitize_for_fs(input: &str) -> String {
+pub(crate) fn sanitize_for_fs(input: &str) -> String {
     input
         .chars()
         .map(|ch| {
@@ -1187,13 +1327,9 @@ fn sanitize_for_fs(input: &str) -> String {
         .collect()
 }
 
+/// 落库只保存不带签名的代理地址，返回给调用方时由 [`sign_preview_links`] 签发
 fn resolve_public_url(storage_key: &str, _raw_url: Option<String>) -> String {
-    let base = crate::CONFIG.base_url();
-    format!(
-        "{}/api/storage/files/{}",
-        base.trim_end_matches('/'),
-        encode(storage_key.trim_start_matches('/'))
-    )
+    crate::storage::signing::proxy_url(storage_key)
 }
 
 #[allow(dead_code)]
@@ -1258,15 +1394,32 @@ async fn persist_material_file_from_worker_cache(
         return Err(anyhow!("生成存储key为空，拒绝上传"));
     }
 
-    if let Err(err) = storage.put(&storage_key, &bytes).await {
-        warn!(
-            preview_id = %preview_id,
-            material = %material_code,
-            error = %err,
-            "上传附件到存储失败，继续使用缓存链接"
-        );
-        return Err(anyhow!(err).context("上传附件到存储失败"));
-    }
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    let blob_extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .unwrap_or(&extension);
+    let storage_key = match blob_store::store_original(
+        Some(database.as_ref()),
+        storage.as_ref(),
+        &storage_key,
+        &checksum,
+        blob_extension,
+        &bytes,
+    )
+    .await
+    {
+        Ok(key) => key,
+        Err(err) => {
+            warn!(
+                preview_id = %preview_id,
+                material = %material_code,
+                error = %err,
+                "上传附件到存储失败，继续使用缓存链接"
+            );
+            return Err(anyhow!(err).context("上传附件到存储失败"));
+        }
+    };
 
     let mut processed_keys: Vec<String> = Vec::new();
     if let Some((preview_key, preview_bytes_len, preview_url)) = generate_preview_variant(
@@ -1327,7 +1480,6 @@ async fn persist_material_file_from_worker_cache(
         JsonValue::Number(bytes.len().into()),
     );
 
-    let checksum = hex::encode(Sha256::digest(&bytes));
     let now = Utc::now();
     let record = crate::db::traits::MaterialFileRecord {
         id: Uuid::new_v4().to_string(),
@@ -1751,14 +1903,18 @@ fn authorize_worker(headers: &HeaderMap, app_state: &AppState) -> Result<String,
         worker.enabled && worker.worker_id == worker_id && worker.secret == worker_key
     });
 
-    if authorized {
-        Ok(worker_id)
-    } else {
-        Err(error_response(
+    if !authorized {
+        return Err(error_response(
             StatusCode::UNAUTHORIZED,
             "无效的 worker 凭证",
-        ))
+        ));
+    }
+
+    if worker_fleet::is_revoked(&worker_id) {
+        return Err(error_response(StatusCode::FORBIDDEN, "worker 凭证已吊销"));
     }
+
+    Ok(worker_id)
 }
 
 fn error_response(status: StatusCode, msg: impl ToString) -> Response {
@@ -1838,6 +1994,11 @@ pub async fn process_worker_result_logic(
         }
     };
 
+    if record.status == PreviewStatus::Cancelled {
+        info!(worker_id = %worker_id, preview_id = %preview_id, "预审已取消，忽略 Worker 结果");
+        return Ok(());
+    }
+
     if let Some(expected_attempt) = record.last_attempt_id.as_deref() {
         if let Some(request_attempt) = payload.attempt_id.as_deref() {
             if request_attempt != expected_attempt {
@@ -2192,6 +2353,17 @@ async fn worker_result_handler(
         }
     };
 
+    if record.status == PreviewStatus::Cancelled {
+        info!(worker_id = %worker_id, preview_id = %preview_id, "预审已取消，忽略 Worker 结果");
+        return Json(serde_json::json!({
+            "success": false,
+            "preview_id": preview_id,
+            "status": "ignored",
+            "reason": "cancelled",
+        }))
+        .into_response();
+    }
+
     if let Some(expected_attempt) = record.last_attempt_id.as_deref() {
         if let Some(request_attempt) = payload.attempt_id.as_deref() {
             if request_attempt != expected_attempt {
@@ -2331,6 +2503,16 @@ async fn worker_start_handler(
         return error_response(StatusCode::BAD_REQUEST, "attempt_id 不能为空");
     }
 
+    // worker 收到 409 后放弃执行，不再上报结果
+    if preview_cancelled(&app_state.database, &preview_id).await {
+        return error_response(StatusCode::CONFLICT, "预审已取消");
+    }
+
+    // 已停止接单的 worker 拿到的任务退回队列，由其他 worker 处理
+    if !worker_fleet::accepts_jobs(&worker_id) {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "worker 已停止接单");
+    }
+
     if let Err(resp) = ensure_worker_capacity(&worker_id).await {
         return resp;
     }
@@ -2357,6 +2539,98 @@ async fn worker_start_handler(
     .into_response()
 }
 
+/// worker 执行过程中上报的进度，由主节点转发给 SSE 订阅者
+async fn worker_progress_handler(
+    State(app_state): State<AppState>,
+    Path(preview_id): Path<String>,
+    headers: HeaderMap,
+    Json(event): Json<ProgressEvent>,
+) -> Response {
+    if let Err(resp) = authorize_worker(&headers, &app_state) {
+        return resp;
+    }
+
+    if event.preview_id != preview_id {
+        return error_response(StatusCode::BAD_REQUEST, "preview_id 与进度事件不匹配");
+    }
+
+    preview_progress::relay(event);
+    Json(WebResult::ok(json!({ "preview_id": preview_id }))).into_response()
+}
+
+/// worker 执行预审时发起 PDF 分片；不满足分片条件时 data 为 null，由 worker 整份识别
+async fn shard_open_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ShardJobRequest>,
+) -> Response {
+    if let Err(resp) = authorize_worker(&headers, &app_state) {
+        return resp;
+    }
+
+    let shards = pdf_shard::open_job(request, &app_state.config.task_queue.pdf_sharding).await;
+    Json(WebResult::ok(shards)).into_response()
+}
+
+async fn shard_status_handler(
+    State(app_state): State<AppState>,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = authorize_worker(&headers, &app_state) {
+        return resp;
+    }
+
+    match pdf_shard::status(&job_id) {
+        Ok(status) => Json(WebResult::ok(status)).into_response(),
+        Err(err) => error_response(StatusCode::NOT_FOUND, err),
+    }
+}
+
+async fn shard_close_handler(
+    State(app_state): State<AppState>,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = authorize_worker(&headers, &app_state) {
+        return resp;
+    }
+
+    pdf_shard::close(&job_id);
+    Json(WebResult::ok(())).into_response()
+}
+
+async fn shard_claim_handler(
+    State(app_state): State<AppState>,
+    Path((job_id, index)): Path<(String, usize)>,
+    headers: HeaderMap,
+) -> Response {
+    let worker_id = match authorize_worker(&headers, &app_state) {
+        Ok(id) => id,
+        Err(resp) => return resp,
+    };
+
+    let claimed = pdf_shard::claim(&job_id, index, &worker_id);
+    Json(WebResult::ok(claimed)).into_response()
+}
+
+async fn shard_result_handler(
+    State(app_state): State<AppState>,
+    Path((job_id, index)): Path<(String, usize)>,
+    headers: HeaderMap,
+    Json(report): Json<ShardReport>,
+) -> Response {
+    let worker_id = match authorize_worker(&headers, &app_state) {
+        Ok(id) => id,
+        Err(resp) => return resp,
+    };
+
+    match pdf_shard::report(&job_id, index, &worker_id, report).await {
+        Ok(()) => Json(WebResult::ok(())).into_response(),
+        Err(err) => error_response(StatusCode::NOT_FOUND, err),
+    }
+}
+
 #[axum::debug_handler]
 async fn heartbeat_handler(
     State(app_state): State<AppState>,
@@ -2385,12 +2659,17 @@ async fn heartbeat_handler(
         interval_secs,
         last_job_started_at,
         last_job_finished_at,
+        capabilities,
     } = payload;
 
     let parsed_last_job_started = parse_optional_datetime(last_job_started_at);
     let parsed_last_job_finished = parse_optional_datetime(last_job_finished_at);
 
     let running_task_count = running_tasks.len();
+    let cancelled = preview_cancel::cancelled_among(&running_tasks);
+    let directive =
+        worker_fleet::heartbeat_directive(&app_state.database, &worker_id, running_task_count)
+            .await;
 
     let previous_interval = {
         let guard = WORKER_HEARTBEATS.read().await;
@@ -2409,6 +2688,9 @@ async fn heartbeat_handler(
         })
         .unwrap_or(30);
 
+    let capabilities = capabilities.unwrap_or_default();
+    task_routing::record_worker(&worker_id, capabilities.clone(), computed_interval);
+
     let mut guard = WORKER_HEARTBEATS.write().await;
     let previous_state = guard.get(&worker_id).cloned();
     let was_timed_out = previous_state
@@ -2454,6 +2736,7 @@ async fn heartbeat_handler(
             restart_cooldown_until,
             last_job_started_at: parsed_last_job_started,
             last_job_finished_at: parsed_last_job_finished,
+            capabilities,
         },
     );
     drop(guard);
@@ -2482,6 +2765,9 @@ async fn heartbeat_handler(
         "ack": true,
         "timestamp": Utc::now(),
         "interval_secs": computed_interval,
+        "cancelled": cancelled,
+        "intake_paused": directive.intake_paused,
+        "shutdown": directive.shutdown,
     })))
     .into_response()
 }
@@ -2778,6 +3064,7 @@ pub struct WorkerHeartbeatSnapshot {
     pub metrics: Option<WorkerHeartbeatMetrics>,
     pub timed_out: bool,
     pub restart_cooldown_until: Option<DateTime<Utc>>,
+    pub capabilities: WorkerCapabilities,
 }
 
 pub async fn collect_worker_heartbeat_snapshot() -> Vec<WorkerHeartbeatSnapshot> {
@@ -2795,6 +3082,7 @@ pub async fn collect_worker_heartbeat_snapshot() -> Vec<WorkerHeartbeatSnapshot>
             metrics: state.metrics.clone(),
             timed_out: state.was_timed_out,
             restart_cooldown_until: state.restart_cooldown_until,
+            capabilities: state.capabilities.clone(),
         })
         .collect()
 }
@@ -7,7 +7,10 @@ use tracing::{error, info, warn};
 
 use ocr_server::db::{PreviewFilter, PreviewStatus};
 use ocr_server::server::{ConfigManager, DatabaseInitializer};
-use ocr_server::util::task_queue::{initialize_task_queue, PreviewTask, PreviewTaskHandler};
+use ocr_server::util::config::types::TaskQueueDriver;
+use ocr_server::util::task_queue::{
+    initialize_producer_task_queue, PreviewTask, PreviewTaskHandler,
+};
 
 struct NoopHandler;
 
@@ -51,6 +54,12 @@ async fn main() -> Result<()> {
 
     let _guard = ConfigManager::initialize_logging(&config).context("初始化日志系统失败")?;
 
+    if config.distributed.enabled && config.task_queue.driver == TaskQueueDriver::Local {
+        return Err(anyhow::anyhow!(
+            "本地任务队列只存在于服务进程内，重启服务即可从任务日志重放 queued 任务"
+        ));
+    }
+
     ocr_server::initialize_globals();
 
     let database = DatabaseInitializer::create_from_config(&config)
@@ -59,9 +68,15 @@ async fn main() -> Result<()> {
 
     let handler: Arc<dyn PreviewTaskHandler> = Arc::new(NoopHandler);
 
-    let task_queue = initialize_task_queue(config.distributed.enabled, &config.task_queue, handler)
-        .await
-        .context("初始化任务队列失败")?;
+    // 只负责重新投递，不能在本进程内启动消费者或后台循环
+    let task_queue = initialize_producer_task_queue(
+        config.distributed.enabled,
+        &config.task_queue,
+        database.clone(),
+        handler,
+    )
+    .await
+    .context("初始化任务队列失败")?;
 
     let mut filter = PreviewFilter::default();
     filter.status = Some(PreviewStatus::Queued);
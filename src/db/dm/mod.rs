@@ -11,6 +11,7 @@ use std::str::FromStr;
 use uuid::Uuid;
 
 use super::factory::DmConfig;
+use super::migrations::{Migration, MigrationDialect, SchemaObject};
 use super::traits::*;
 
 use crate::api::monitor_auth::DEFAULT_MONITOR_ADMIN_PASSWORD;
@@ -439,6 +440,24 @@ impl DmGoConnection {
         Ok(resp.data.unwrap_or_default())
     }
 
+    async fn query_values(
+        &self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> Result<Vec<HashMap<String, Value>>> {
+        let resp = self
+            .db_call(
+                sql,
+                Some(params),
+                Some(DbOptions {
+                    timeout_ms: Some(30_000),
+                    limit: None,
+                }),
+            )
+            .await?;
+        Ok(resp.data.unwrap_or_default())
+    }
+
     async fn execute_update(&self, sql: &str, str_params: Option<Vec<String>>) -> Result<u64> {
         let params = str_params.map(|v| v.into_iter().map(|s| Value::String(s)).collect());
         let resp = self.db_call(sql, params, None).await?;
@@ -482,6 +501,19 @@ impl DmGoConnection {
         Ok(count > 0)
     }
 
+    async fn index_exists(&self, index: &str) -> Result<bool> {
+        let sql = "SELECT COUNT(*) AS COUNT FROM USER_INDEXES WHERE INDEX_NAME = ?";
+        let rows = self
+            .query_rows(sql, Some(vec![index.to_uppercase()]))
+            .await?;
+        let count = rows
+            .get(0)
+            .and_then(|r| r.get("COUNT").or_else(|| r.get("count")))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+        Ok(count > 0)
+    }
+
     async fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
         if !self.column_exists(table, column).await? {
             let sql = format!("ALTER TABLE {} ADD ({})", table, definition);
@@ -1547,6 +1579,90 @@ impl DmGoConnection {
                 .ok();
         }
 
+        if !self.table_exists("PREVIEW_DEAD_LETTERS").await? {
+            let create = r#"
+                CREATE TABLE PREVIEW_DEAD_LETTERS (
+                    ID VARCHAR(100) PRIMARY KEY,
+                    PREVIEW_ID VARCHAR(100) NOT NULL,
+                    QUEUE VARCHAR(100) NOT NULL,
+                    SOURCE VARCHAR(20) NOT NULL,
+                    PAYLOAD CLOB NOT NULL,
+                    FAILURE_REASON VARCHAR(500) NOT NULL,
+                    ATTEMPTS INTEGER DEFAULT 0,
+                    LAST_ERROR CLOB,
+                    STATUS VARCHAR(20) DEFAULT 'dead',
+                    RESOLVED_BY VARCHAR(200),
+                    RESOLVED_AT TIMESTAMP,
+                    CREATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    UPDATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                )
+            "#;
+            let _ = self.execute_update(create, None).await?;
+            let _ = self
+                .execute_update(
+                    "CREATE INDEX IDX_DEAD_LETTERS_STATUS ON PREVIEW_DEAD_LETTERS(STATUS, CREATED_AT)",
+                    None,
+                )
+                .await
+                .ok();
+            let _ = self
+                .execute_update(
+                    "CREATE INDEX IDX_DEAD_LETTERS_PREVIEW ON PREVIEW_DEAD_LETTERS(PREVIEW_ID)",
+                    None,
+                )
+                .await
+                .ok();
+        }
+
+        if !self.table_exists("WORKER_CONTROLS").await? {
+            let create = r#"
+                CREATE TABLE WORKER_CONTROLS (
+                    WORKER_ID VARCHAR(100) PRIMARY KEY,
+                    STATE VARCHAR(20) DEFAULT 'active',
+                    REASON VARCHAR(500),
+                    UPDATED_BY VARCHAR(200) NOT NULL,
+                    UPDATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                )
+            "#;
+            let _ = self.execute_update(create, None).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_schema_migrations_table(&self) -> Result<()> {
+        if self.table_exists("SCHEMA_MIGRATIONS").await? {
+            return Ok(());
+        }
+        let create = r#"
+            CREATE TABLE SCHEMA_MIGRATIONS (
+                VERSION BIGINT PRIMARY KEY,
+                NAME VARCHAR(200) NOT NULL,
+                CHECKSUM VARCHAR(64) NOT NULL,
+                APPLIED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                EXECUTION_MS BIGINT DEFAULT 0
+            )
+        "#;
+        self.execute_update(create, None).await?;
+        Ok(())
+    }
+
+    async fn insert_purge_audit(&self, purge: &ArtifactPurge, db_rows: i64) -> Result<()> {
+        self.execute_update_values(
+            "INSERT INTO RETENTION_PURGE_LOG (ID, PREVIEW_ID, MATTER_ID, ARTIFACT, STORAGE_KEYS, \
+             DB_ROWS, REASON, PURGED_AT) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            vec![
+                Value::String(Uuid::new_v4().to_string()),
+                str_option_to_value(&purge.preview_id),
+                str_option_to_value(&purge.matter_id),
+                Value::String(purge.artifact.as_str().to_string()),
+                Value::String(serde_json::to_string(&purge.storage_keys)?),
+                Value::from(db_rows),
+                Value::String(purge.reason.clone()),
+                Value::String(format_dm_datetime(&Utc::now())),
+            ],
+        )
+        .await?;
         Ok(())
     }
 }
@@ -1592,6 +1708,7 @@ fn parse_status_opt(value: Option<&serde_json::Value>) -> Option<PreviewStatus>
         "processing" => Some(PreviewStatus::Processing),
         "completed" => Some(PreviewStatus::Completed),
         "failed" => Some(PreviewStatus::Failed),
+        "cancelled" => Some(PreviewStatus::Cancelled),
         _ => None,
     })
 }
@@ -1664,6 +1781,7 @@ fn map_preview_row(
             "queued" => PreviewStatus::Queued,
             "completed" => PreviewStatus::Completed,
             "failed" => PreviewStatus::Failed,
+            "cancelled" => PreviewStatus::Cancelled,
             _ => PreviewStatus::Processing,
         },
         created_at: parse_dt(row.get("CREATED_AT")),
@@ -2234,6 +2352,64 @@ impl Database for DmDatabase {
         Err(anyhow!("DM-Go save_preview_record not implemented"))
     }
 
+    async fn reconcile_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        #[cfg(feature = "dm_go")]
+        if let DmConnectionType::Go(conn) = &self.connection {
+            let sql = "UPDATE PREVIEW_RECORDS SET USER_ID = ?, USER_INFO_JSON = ?, FILE_NAME = ?, OCR_TEXT = ?, THEME_ID = ?, EVALUATION_RESULT = ?, PREVIEW_URL = ?, PREVIEW_VIEW_URL = ?, PREVIEW_DOWNLOAD_URL = ?, STATUS = ?, CREATED_AT = ?, UPDATED_AT = ?, THIRD_PARTY_REQUEST_ID = ?, QUEUED_AT = ?, PROCESSING_STARTED_AT = ?, RETRY_COUNT = ?, LAST_WORKER_ID = ?, LAST_ATTEMPT_ID = ?, FAILURE_REASON = ?, OCR_STDERR_SUMMARY = ?, FAILURE_CONTEXT = ?, LAST_ERROR_CODE = ?, SLOW_ATTACHMENT_INFO_JSON = ?, CALLBACK_URL = ?, CALLBACK_STATUS = ?, CALLBACK_ATTEMPTS = ?, CALLBACK_SUCCESSES = ?, CALLBACK_FAILURES = ?, LAST_CALLBACK_AT = ?, LAST_CALLBACK_STATUS_CODE = ?, LAST_CALLBACK_RESPONSE = ?, LAST_CALLBACK_ERROR = ?, CALLBACK_PAYLOAD = ?, NEXT_CALLBACK_AFTER = ? WHERE ID = ?";
+
+            let format_opt = |dt: &Option<DateTime<Utc>>| dt.as_ref().map(format_dm_datetime);
+            let params: Vec<Value> = vec![
+                Value::String(record.user_id.clone()),
+                str_option_to_value(&record.user_info_json),
+                Value::String(record.file_name.clone()),
+                Value::String(record.ocr_text.clone()),
+                str_option_to_value(&record.theme_id),
+                str_option_to_value(&record.evaluation_result),
+                Value::String(record.preview_url.clone()),
+                str_option_to_value(&record.preview_view_url),
+                str_option_to_value(&record.preview_download_url),
+                Value::String(record.status.to_string()),
+                Value::String(format_dm_datetime(&record.created_at)),
+                Value::String(format_dm_datetime(&record.updated_at)),
+                str_option_to_value(&record.third_party_request_id),
+                str_option_to_value(&format_opt(&record.queued_at)),
+                str_option_to_value(&format_opt(&record.processing_started_at)),
+                Value::from(record.retry_count),
+                str_option_to_value(&record.last_worker_id),
+                str_option_to_value(&record.last_attempt_id),
+                str_option_to_value(&record.failure_reason),
+                str_option_to_value(&record.ocr_stderr_summary),
+                str_option_to_value(&record.failure_context),
+                str_option_to_value(&record.last_error_code),
+                str_option_to_value(&record.slow_attachment_info_json),
+                str_option_to_value(&record.callback_url),
+                str_option_to_value(&record.callback_status),
+                Value::from(record.callback_attempts),
+                Value::from(record.callback_successes),
+                Value::from(record.callback_failures),
+                str_option_to_value(&format_opt(&record.last_callback_at)),
+                record
+                    .last_callback_status_code
+                    .map(Value::from)
+                    .unwrap_or(Value::Null),
+                str_option_to_value(&record.last_callback_response),
+                str_option_to_value(&record.last_callback_error),
+                str_option_to_value(&record.callback_payload),
+                str_option_to_value(&format_opt(&record.next_callback_after)),
+                Value::String(record.id.clone()),
+            ];
+
+            if conn.execute_update_values(sql, params.clone()).await? > 0 {
+                return Ok(());
+            }
+            // 主库缺少该记录：先插入，再用同一条 UPDATE 写回原有时间戳
+            self.save_preview_record(record).await?;
+            conn.execute_update_values(sql, params).await?;
+            return Ok(());
+        }
+        Err(anyhow!("DM-Go reconcile_preview_record not implemented"))
+    }
+
     async fn get_preview_record(&self, id: &str) -> Result<Option<PreviewRecord>> {
         #[cfg(feature = "dm_go")]
         if let DmConnectionType::Go(conn) = &self.connection {
@@ -2265,7 +2441,7 @@ impl Database for DmDatabase {
                     params = vec![status_str, id.to_string()];
                     "UPDATE PREVIEW_RECORDS SET STATUS = ?, UPDATED_AT = CURRENT_TIMESTAMP, PROCESSING_STARTED_AT = CURRENT_TIMESTAMP, RETRY_COUNT = RETRY_COUNT + 1 WHERE ID = ?"
                 }
-                PreviewStatus::Completed | PreviewStatus::Failed => {
+                PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled => {
                     params = vec![status_str, id.to_string()];
                     "UPDATE PREVIEW_RECORDS SET STATUS = ?, UPDATED_AT = CURRENT_TIMESTAMP WHERE ID = ?"
                 }
@@ -2508,6 +2684,10 @@ impl Database for DmDatabase {
                 sql.push_str(" AND THIRD_PARTY_REQUEST_ID = ?");
                 params.push(tp_id.clone());
             }
+            if let Some(worker_id) = &filter.worker_id {
+                sql.push_str(" AND LAST_WORKER_ID = ?");
+                params.push(worker_id.clone());
+            }
             if let Some(start) = &filter.start_date {
                 sql.push_str(" AND CREATED_AT >= ?");
                 params.push(format_dm_datetime(start));
@@ -2631,6 +2811,7 @@ impl Database for DmDatabase {
                     PreviewStatus::Failed => counts.failed += cnt,
                     PreviewStatus::Pending => counts.pending += cnt,
                     PreviewStatus::Queued => counts.queued += cnt,
+                    PreviewStatus::Cancelled => counts.cancelled += cnt,
                 }
             }
             return Ok(counts);
@@ -3837,6 +4018,42 @@ impl Database for DmDatabase {
         Ok(())
     }
 
+    async fn cancel_material_downloads(&self, preview_id: &str) -> Result<u64> {
+        let sql = "UPDATE MATERIAL_DOWNLOAD_QUEUE \
+                   SET STATUS = 'cancelled', UPDATED_AT = CURRENT_TIMESTAMP \
+                   WHERE PREVIEW_ID = ? AND STATUS = 'pending'";
+
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                conn.execute_with_params(sql, vec![preview_id.to_string()])
+                    .await
+            }
+        }
+    }
+
+    async fn latest_material_download_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        let sql = "SELECT PAYLOAD FROM MATERIAL_DOWNLOAD_QUEUE \
+                   WHERE PREVIEW_ID = ? \
+                   ORDER BY CREATED_AT DESC \
+                   LIMIT 1";
+
+        let rows = match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                conn.query_rows(sql, Some(vec![preview_id.to_string()]))
+                    .await?
+            }
+        };
+
+        Ok(rows.first().and_then(|row| {
+            row.get("PAYLOAD")
+                .or_else(|| row.get("payload"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        }))
+    }
+
     async fn get_download_cache_token(
         &self,
         url: &str,
@@ -4352,68 +4569,1342 @@ impl Database for DmDatabase {
             Err(anyhow!("DM-Go monitor session统计不可用"))
         }
     }
-}
 
-#[cfg(feature = "dm_go")]
-fn map_material_file_row(
-    row: &std::collections::HashMap<String, serde_json::Value>,
-) -> Result<MaterialFileRecord> {
-    Ok(MaterialFileRecord {
-        id: as_str(row.get("ID")).unwrap_or_default(),
-        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
-        material_code: as_str(row.get("MATERIAL_CODE")).unwrap_or_default(),
-        attachment_name: opt_str(row.get("ATTACHMENT_NAME")),
-        source_url: opt_str(row.get("SOURCE_URL")),
-        stored_original_key: as_str(row.get("STORED_ORIGINAL_KEY")).unwrap_or_default(),
-        stored_processed_keys: opt_str(row.get("STORED_PROCESSED_KEYS")),
-        mime_type: opt_str(row.get("MIME_TYPE")),
-        size_bytes: as_i64(row.get("SIZE_BYTES")),
-        checksum_sha256: opt_str(row.get("CHECKSUM_SHA256")),
-        ocr_text_key: opt_str(row.get("OCR_TEXT_KEY")),
-        ocr_text_length: as_i64(row.get("OCR_TEXT_LENGTH")),
-        status: as_str(row.get("STATUS")).unwrap_or_else(|| "pending".to_string()),
-        error_message: opt_str(row.get("ERROR_MESSAGE")),
-        created_at: parse_dt(row.get("CREATED_AT")),
-        updated_at: parse_dt(row.get("UPDATED_AT")),
-    })
-}
+    async fn ensure_task_queue_table(&self, table: &str) -> Result<()> {
+        validate_queue_table_name(table)?;
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                if conn.table_exists(table).await? {
+                    return Ok(());
+                }
+                let create = format!(
+                    "CREATE TABLE {} (
+                        ID VARCHAR(100) PRIMARY KEY,
+                        QUEUE VARCHAR(100) NOT NULL,
+                        PREVIEW_ID VARCHAR(100) NOT NULL,
+                        PAYLOAD CLOB NOT NULL,
+                        STATUS VARCHAR(20) DEFAULT 'pending',
+                        ATTEMPTS INTEGER DEFAULT 0,
+                        LEASE_OWNER VARCHAR(200),
+                        LEASE_TOKEN VARCHAR(100),
+                        LEASE_EXPIRES_AT BIGINT,
+                        VISIBLE_AT BIGINT NOT NULL,
+                        LAST_ERROR CLOB,
+                        CREATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UPDATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                    )",
+                    table
+                );
+                conn.execute_update(&create, None).await?;
+                let _ = conn
+                    .execute_update(
+                        &format!(
+                            "CREATE UNIQUE INDEX IDX_{0}_PREVIEW ON {0}(QUEUE, PREVIEW_ID)",
+                            table
+                        ),
+                        None,
+                    )
+                    .await
+                    .ok();
+                let _ = conn
+                    .execute_update(
+                        &format!(
+                            "CREATE INDEX IDX_{0}_CLAIM ON {0}(QUEUE, STATUS, VISIBLE_AT)",
+                            table
+                        ),
+                        None,
+                    )
+                    .await
+                    .ok();
+                tracing::info!("[init] Created {} table", table);
+            }
+        }
+        Ok(())
+    }
 
-#[cfg(feature = "dm_go")]
-fn map_cached_material_row(
-    row: &std::collections::HashMap<String, serde_json::Value>,
-) -> Result<CachedMaterialRecord> {
-    let status = as_str(row.get("UPLOAD_STATUS")).unwrap_or_else(|| "downloaded".to_string());
-    Ok(CachedMaterialRecord {
-        id: as_str(row.get("ID")).unwrap_or_default(),
-        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
-        material_code: as_str(row.get("MATERIAL_CODE")).unwrap_or_default(),
-        attachment_index: as_i64(row.get("ATTACHMENT_INDEX")).unwrap_or(0) as i32,
-        token: as_str(row.get("TOKEN")).unwrap_or_default(),
-        local_path: as_str(row.get("LOCAL_PATH")).unwrap_or_default(),
-        upload_status: CachedMaterialStatus::from_str(&status),
-        oss_key: opt_str(row.get("OSS_KEY")),
-        last_error: opt_str(row.get("LAST_ERROR")),
-        file_size: as_i64(row.get("FILE_SIZE")),
-        checksum_sha256: opt_str(row.get("CHECKSUM_SHA256")),
-        created_at: parse_dt(row.get("CREATED_AT")),
-        updated_at: parse_dt(row.get("UPDATED_AT")),
-    })
-}
+    async fn enqueue_queued_task(&self, table: &str, task: &NewQueuedTask) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                // 同一预审已在队列中时刷新载荷；正在被领取的任务保持不变
+                let update_sql = format!(
+                    "UPDATE {} SET PAYLOAD = ?, STATUS = 'pending', ATTEMPTS = 0, VISIBLE_AT = ?, \
+                     LAST_ERROR = NULL, UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE QUEUE = ? AND PREVIEW_ID = ? AND STATUS <> 'leased'",
+                    table
+                );
+                let updated = conn
+                    .execute_update_values(
+                        &update_sql,
+                        vec![
+                            Value::String(task.payload.clone()),
+                            Value::from(task.visible_at),
+                            Value::String(task.queue.clone()),
+                            Value::String(task.preview_id.clone()),
+                        ],
+                    )
+                    .await?;
+                if updated > 0 {
+                    return Ok(());
+                }
 
-#[cfg(feature = "dm_go")]
-fn map_outbox_row(
-    row: &std::collections::HashMap<String, serde_json::Value>,
-) -> Result<OutboxEvent> {
-    Ok(OutboxEvent {
-        id: as_str(row.get("ID")).unwrap_or_default(),
-        table_name: as_str(row.get("TABLE_NAME")).unwrap_or_default(),
-        op_type: as_str(row.get("OP_TYPE")).unwrap_or_default(),
-        pk_value: as_str(row.get("PK_VALUE")).unwrap_or_default(),
-        idempotency_key: as_str(row.get("IDEMPOTENCY_KEY")).unwrap_or_default(),
-        payload: as_str(row.get("PAYLOAD")).unwrap_or_default(),
-        created_at: parse_dt(row.get("CREATED_AT")),
-        applied_at: parse_dt_opt(row.get("APPLIED_AT")),
-        retries: as_i64(row.get("RETRIES")).unwrap_or(0) as i32,
-        last_error: opt_str(row.get("LAST_ERROR")),
+                let exists_sql = format!(
+                    "SELECT COUNT(*) AS COUNT FROM {} WHERE QUEUE = ? AND PREVIEW_ID = ?",
+                    table
+                );
+                let rows = conn
+                    .query_rows(
+                        &exists_sql,
+                        Some(vec![task.queue.clone(), task.preview_id.clone()]),
+                    )
+                    .await?;
+                let exists = rows
+                    .first()
+                    .and_then(|row| as_i64(row.get("COUNT").or_else(|| row.get("count"))))
+                    .unwrap_or(0)
+                    > 0;
+                if exists {
+                    return Ok(());
+                }
+
+                let insert_sql = format!(
+                    "INSERT INTO {} (ID, QUEUE, PREVIEW_ID, PAYLOAD, STATUS, ATTEMPTS, VISIBLE_AT, CREATED_AT, UPDATED_AT) \
+                     VALUES (?, ?, ?, ?, 'pending', 0, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+                    table
+                );
+                conn.execute_update_values(
+                    &insert_sql,
+                    vec![
+                        Value::String(task.id.clone()),
+                        Value::String(task.queue.clone()),
+                        Value::String(task.preview_id.clone()),
+                        Value::String(task.payload.clone()),
+                        Value::from(task.visible_at),
+                    ],
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn claim_queued_tasks(
+        &self,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let expire_sql = format!(
+                    "UPDATE {} SET STATUS = 'failed', \
+                     LAST_ERROR = COALESCE(LAST_ERROR, '租约过期且已达最大尝试次数'), \
+                     LEASE_OWNER = NULL, LEASE_TOKEN = NULL, LEASE_EXPIRES_AT = NULL, \
+                     UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE QUEUE = ? AND STATUS = 'leased' AND LEASE_EXPIRES_AT <= ? AND ATTEMPTS >= ?",
+                    table
+                );
+                conn.execute_update_values(
+                    &expire_sql,
+                    vec![
+                        Value::String(claim.queue.clone()),
+                        Value::from(claim.now),
+                        Value::from(claim.max_attempts),
+                    ],
+                )
+                .await?;
+
+                let select_sql = format!(
+                    "SELECT ID, QUEUE, PREVIEW_ID, PAYLOAD, STATUS, ATTEMPTS, LEASE_OWNER, LEASE_TOKEN, \
+                     LEASE_EXPIRES_AT, VISIBLE_AT, LAST_ERROR, CREATED_AT, UPDATED_AT \
+                     FROM {} \
+                     WHERE QUEUE = ? AND ((STATUS = 'pending' AND VISIBLE_AT <= ?) \
+                        OR (STATUS = 'leased' AND LEASE_EXPIRES_AT <= ?)) \
+                     ORDER BY VISIBLE_AT ASC, CREATED_AT ASC \
+                     LIMIT {}",
+                    table,
+                    claim.limit.saturating_mul(2).max(1)
+                );
+                let candidates = conn
+                    .query_values(
+                        &select_sql,
+                        vec![
+                            Value::String(claim.queue.clone()),
+                            Value::from(claim.now),
+                            Value::from(claim.now),
+                        ],
+                    )
+                    .await?;
+
+                // 网关调用之间没有事务，逐条按原状态做条件更新，更新成功才算领取到
+                let claim_sql = format!(
+                    "UPDATE {} SET STATUS = 'leased', ATTEMPTS = ATTEMPTS + 1, LEASE_OWNER = ?, \
+                     LEASE_TOKEN = ?, LEASE_EXPIRES_AT = ?, UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE ID = ? AND ATTEMPTS = ? AND ((STATUS = 'pending' AND VISIBLE_AT <= ?) \
+                        OR (STATUS = 'leased' AND LEASE_EXPIRES_AT <= ?))",
+                    table
+                );
+                let mut claimed = Vec::new();
+                for row in candidates {
+                    if claimed.len() >= claim.limit as usize {
+                        break;
+                    }
+                    let mut task = map_queued_task_row(&row);
+                    let affected = conn
+                        .execute_update_values(
+                            &claim_sql,
+                            vec![
+                                Value::String(claim.consumer_id.clone()),
+                                Value::String(claim.lease_token.clone()),
+                                Value::from(claim.lease_expires_at),
+                                Value::String(task.id.clone()),
+                                Value::from(task.attempts),
+                                Value::from(claim.now),
+                                Value::from(claim.now),
+                            ],
+                        )
+                        .await?;
+                    if affected == 0 {
+                        continue;
+                    }
+                    task.status = "leased".to_string();
+                    task.attempts += 1;
+                    task.lease_owner = Some(claim.consumer_id.clone());
+                    task.lease_token = Some(claim.lease_token.clone());
+                    task.lease_expires_at = Some(claim.lease_expires_at);
+                    claimed.push(task);
+                }
+                Ok(claimed)
+            }
+        }
+    }
+
+    async fn extend_queued_task_lease(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!(
+                    "UPDATE {} SET LEASE_EXPIRES_AT = ?, UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE ID = ? AND LEASE_TOKEN = ? AND STATUS = 'leased'",
+                    table
+                );
+                let affected = conn
+                    .execute_update_values(
+                        &sql,
+                        vec![
+                            Value::from(lease_expires_at),
+                            Value::String(task_id.to_string()),
+                            Value::String(lease_token.to_string()),
+                        ],
+                    )
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn complete_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!("DELETE FROM {} WHERE ID = ? AND LEASE_TOKEN = ?", table);
+                let affected = conn
+                    .execute_with_params(&sql, vec![task_id.to_string(), lease_token.to_string()])
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn release_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let status = if retry_at.is_some() {
+                    "pending"
+                } else {
+                    "failed"
+                };
+                let sql = format!(
+                    "UPDATE {} SET STATUS = ?, VISIBLE_AT = COALESCE(?, VISIBLE_AT), LAST_ERROR = ?, \
+                     LEASE_OWNER = NULL, LEASE_TOKEN = NULL, LEASE_EXPIRES_AT = NULL, \
+                     UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE ID = ? AND LEASE_TOKEN = ?",
+                    table
+                );
+                let affected = conn
+                    .execute_update_values(
+                        &sql,
+                        vec![
+                            Value::String(status.to_string()),
+                            i64_option_to_value(retry_at),
+                            Value::String(error.to_string()),
+                            Value::String(task_id.to_string()),
+                            Value::String(lease_token.to_string()),
+                        ],
+                    )
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn return_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!(
+                    "UPDATE {} SET STATUS = 'pending', \
+                     ATTEMPTS = CASE WHEN ATTEMPTS > 0 THEN ATTEMPTS - 1 ELSE 0 END, \
+                     LEASE_OWNER = NULL, LEASE_TOKEN = NULL, LEASE_EXPIRES_AT = NULL, \
+                     UPDATED_AT = CURRENT_TIMESTAMP \
+                     WHERE ID = ? AND LEASE_TOKEN = ?",
+                    table
+                );
+                let affected = conn
+                    .execute_with_params(&sql, vec![task_id.to_string(), lease_token.to_string()])
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn queued_task_stats(&self, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!(
+                    "SELECT STATUS, COUNT(*) AS TOTAL FROM {} WHERE QUEUE = ? GROUP BY STATUS",
+                    table
+                );
+                let rows = conn.query_rows(&sql, Some(vec![queue.to_string()])).await?;
+                let mut stats = QueuedTaskStats::default();
+                for row in rows {
+                    let total = as_i64(row.get("TOTAL").or_else(|| row.get("total")))
+                        .unwrap_or(0)
+                        .max(0) as u64;
+                    match as_str(row.get("STATUS").or_else(|| row.get("status"))).as_deref() {
+                        Some("pending") => stats.pending = total,
+                        Some("leased") => stats.leased = total,
+                        Some("failed") => stats.failed = total,
+                        _ => {}
+                    }
+                }
+                Ok(stats)
+            }
+        }
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!(
+                    "SELECT * FROM {} WHERE QUEUE = ? AND STATUS = 'failed' \
+                     ORDER BY UPDATED_AT ASC LIMIT {}",
+                    table,
+                    limit.max(1)
+                );
+                let rows = conn.query_rows(&sql, Some(vec![queue.to_string()])).await?;
+                Ok(rows.iter().map(map_queued_task_row).collect())
+            }
+        }
+    }
+
+    async fn delete_queued_task(&self, table: &str, task_id: &str) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!("DELETE FROM {} WHERE ID = ?", table);
+                let affected = conn
+                    .execute_with_params(&sql, vec![task_id.to_string()])
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn cancel_queued_tasks(&self, table: &str, queue: &str, preview_id: &str) -> Result<u64> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = format!(
+                    "DELETE FROM {} WHERE QUEUE = ? AND STATUS = 'pending' \
+                     AND (PREVIEW_ID = ? OR PREVIEW_ID LIKE ? ESCAPE '\\')",
+                    table
+                );
+                let affected = conn
+                    .execute_with_params(
+                        &sql,
+                        vec![
+                            queue.to_string(),
+                            preview_id.to_string(),
+                            shard_queue_key_pattern(preview_id),
+                        ],
+                    )
+                    .await?;
+                Ok(affected)
+            }
+        }
+    }
+
+    async fn save_dead_letter(&self, record: &DeadLetterRecord) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let update_sql = "UPDATE PREVIEW_DEAD_LETTERS SET PAYLOAD = ?, FAILURE_REASON = ?, \
+                                  ATTEMPTS = ?, LAST_ERROR = ?, STATUS = ?, RESOLVED_BY = NULL, \
+                                  RESOLVED_AT = NULL, UPDATED_AT = ? WHERE ID = ?";
+                let updated = conn
+                    .execute_update_values(
+                        update_sql,
+                        vec![
+                            Value::String(record.payload.clone()),
+                            Value::String(record.failure_reason.clone()),
+                            Value::from(record.attempts),
+                            str_option_to_value(&record.last_error),
+                            Value::String(record.status.as_str().to_string()),
+                            Value::String(format_dm_datetime(&record.updated_at)),
+                            Value::String(record.id.clone()),
+                        ],
+                    )
+                    .await?;
+                if updated > 0 {
+                    return Ok(());
+                }
+
+                let insert_sql = "INSERT INTO PREVIEW_DEAD_LETTERS (ID, PREVIEW_ID, QUEUE, SOURCE, \
+                                  PAYLOAD, FAILURE_REASON, ATTEMPTS, LAST_ERROR, STATUS, CREATED_AT, \
+                                  UPDATED_AT) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+                conn.execute_update_values(
+                    insert_sql,
+                    vec![
+                        Value::String(record.id.clone()),
+                        Value::String(record.preview_id.clone()),
+                        Value::String(record.queue.clone()),
+                        Value::String(record.source.clone()),
+                        Value::String(record.payload.clone()),
+                        Value::String(record.failure_reason.clone()),
+                        Value::from(record.attempts),
+                        str_option_to_value(&record.last_error),
+                        Value::String(record.status.as_str().to_string()),
+                        Value::String(format_dm_datetime(&record.created_at)),
+                        Value::String(format_dm_datetime(&record.updated_at)),
+                    ],
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_dead_letters(&self, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let mut sql = String::from("SELECT * FROM PREVIEW_DEAD_LETTERS WHERE 1 = 1");
+                let mut params = Vec::new();
+                if let Some(status) = filter.status {
+                    sql.push_str(" AND STATUS = ?");
+                    params.push(status.as_str().to_string());
+                }
+                if let Some(preview_id) = &filter.preview_id {
+                    sql.push_str(" AND PREVIEW_ID = ?");
+                    params.push(preview_id.clone());
+                }
+                if let Some(source) = &filter.source {
+                    sql.push_str(" AND SOURCE = ?");
+                    params.push(source.clone());
+                }
+                sql.push_str(&format!(
+                    " ORDER BY CREATED_AT DESC LIMIT {} OFFSET {}",
+                    filter.limit.unwrap_or(50),
+                    filter.offset.unwrap_or(0)
+                ));
+                let rows = conn.query_rows(&sql, Some(params)).await?;
+                rows.iter().map(map_dead_letter_row).collect()
+            }
+        }
+    }
+
+    async fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetterRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let rows = conn
+                    .query_rows(
+                        "SELECT * FROM PREVIEW_DEAD_LETTERS WHERE ID = ?",
+                        Some(vec![id.to_string()]),
+                    )
+                    .await?;
+                rows.first().map(map_dead_letter_row).transpose()
+            }
+        }
+    }
+
+    async fn resolve_dead_letter(
+        &self,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = "UPDATE PREVIEW_DEAD_LETTERS SET STATUS = ?, PAYLOAD = COALESCE(?, PAYLOAD), \
+                           RESOLVED_BY = ?, RESOLVED_AT = CURRENT_TIMESTAMP, \
+                           UPDATED_AT = CURRENT_TIMESTAMP WHERE ID = ? AND STATUS = 'dead'";
+                let affected = conn
+                    .execute_update_values(
+                        sql,
+                        vec![
+                            Value::String(status.as_str().to_string()),
+                            str_ref_option_to_value(payload),
+                            Value::String(resolved_by.to_string()),
+                            Value::String(id.to_string()),
+                        ],
+                    )
+                    .await?;
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn save_worker_control(&self, record: &WorkerControlRecord) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let params = vec![
+                    Value::String(record.state.as_str().to_string()),
+                    str_option_to_value(&record.reason),
+                    Value::String(record.updated_by.clone()),
+                    Value::String(format_dm_datetime(&record.updated_at)),
+                    Value::String(record.worker_id.clone()),
+                ];
+                let update_sql = "UPDATE WORKER_CONTROLS SET STATE = ?, REASON = ?, UPDATED_BY = ?, \
+                                  UPDATED_AT = ? WHERE WORKER_ID = ?";
+                if conn.execute_update_values(update_sql, params.clone()).await? > 0 {
+                    return Ok(());
+                }
+
+                let insert_sql = "INSERT INTO WORKER_CONTROLS (STATE, REASON, UPDATED_BY, \
+                                  UPDATED_AT, WORKER_ID) VALUES (?, ?, ?, ?, ?)";
+                conn.execute_update_values(insert_sql, params).await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let rows = conn
+                    .query_rows("SELECT * FROM WORKER_CONTROLS ORDER BY WORKER_ID", None)
+                    .await?;
+                rows.iter().map(map_worker_control_row).collect()
+            }
+        }
+    }
+
+    async fn save_preview_batch_items(&self, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let sql = "INSERT INTO PREVIEW_BATCH_ITEMS (BATCH_ID, ITEM_INDEX, SUBMITTED_BY, \
+                           STATUS, PREVIEW_ID, THIRD_PARTY_REQUEST_ID, ERROR, CREATED_AT) \
+                           VALUES (?, ?, ?, ?, ?, ?, ?, ?)";
+                for item in items {
+                    conn.execute_update_values(
+                        sql,
+                        vec![
+                            Value::String(item.batch_id.clone()),
+                            Value::from(item.item_index),
+                            Value::String(item.submitted_by.clone()),
+                            Value::String(item.status.as_str().to_string()),
+                            str_option_to_value(&item.preview_id),
+                            str_option_to_value(&item.third_party_request_id),
+                            str_option_to_value(&item.error),
+                            Value::String(format_dm_datetime(&item.created_at)),
+                        ],
+                    )
+                    .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_preview_batch_items(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let rows = conn
+                    .query_rows(
+                        "SELECT * FROM PREVIEW_BATCH_ITEMS WHERE BATCH_ID = ? ORDER BY ITEM_INDEX",
+                        Some(vec![batch_id.to_string()]),
+                    )
+                    .await?;
+                rows.iter().map(map_preview_batch_item_row).collect()
+            }
+        }
+    }
+
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        Some(MigrationDialect::Dm)
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                conn.ensure_schema_migrations_table().await?;
+                let rows = conn
+                    .query_rows("SELECT * FROM SCHEMA_MIGRATIONS ORDER BY VERSION", None)
+                    .await?;
+                Ok(rows.iter().map(map_applied_migration_row).collect())
+            }
+        }
+    }
+
+    /// 网关不提供事务且达梦 DDL 自动提交：逐条执行并跳过已存在的表、列与索引，
+    /// 中途失败的迁移可以直接重跑，全部成功后再登记
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                conn.ensure_schema_migrations_table().await?;
+                let started = std::time::Instant::now();
+                for statement in migration.statements(MigrationDialect::Dm) {
+                    let exists = match SchemaObject::created_by(statement) {
+                        Some(SchemaObject::Column { table, column }) => {
+                            conn.column_exists(table, column).await?
+                        }
+                        Some(SchemaObject::Table(table)) => conn.table_exists(table).await?,
+                        Some(SchemaObject::Index(index)) => conn.index_exists(index).await?,
+                        None => false,
+                    };
+                    if !exists {
+                        conn.execute_update(statement, None).await?;
+                    }
+                }
+                let params = vec![
+                    Value::from(migration.version),
+                    Value::String(migration.name.to_string()),
+                    Value::String(migration.checksum(MigrationDialect::Dm)),
+                    Value::String(format_dm_datetime(&Utc::now())),
+                    Value::from(started.elapsed().as_millis() as i64),
+                ];
+                conn.execute_update_values(
+                    "INSERT INTO SCHEMA_MIGRATIONS (VERSION, NAME, CHECKSUM, APPLIED_AT, \
+                     EXECUTION_MS) VALUES (?, ?, ?, ?, ?)",
+                    params,
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_retention_candidates(
+        &self,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let mut sql = String::from(
+                    "SELECT ID, CREATED_AT, MATTER_ID FROM (\
+                     SELECT PR.ID, PR.CREATED_AT, \
+                     (SELECT REQ.MATTER_ID FROM PREVIEW_REQUESTS REQ \
+                      WHERE REQ.LATEST_PREVIEW_ID = PR.ID LIMIT 1) AS MATTER_ID \
+                     FROM PREVIEW_RECORDS PR \
+                     WHERE PR.LEGAL_HOLD = 0 \
+                     AND PR.STATUS IN ('completed', 'failed', 'cancelled') \
+                     AND PR.CREATED_AT < ? \
+                     AND COALESCE(PR.PURGED_ARTIFACTS, '') NOT LIKE ?) C WHERE 1 = 1",
+                );
+                let mut params = vec![
+                    format_dm_datetime(&query.created_before),
+                    query.artifact.purged_marker_pattern(),
+                ];
+                if let Some(matter_id) = &query.matter_id {
+                    sql.push_str(" AND MATTER_ID = ?");
+                    params.push(matter_id.clone());
+                }
+                if !query.exclude_matters.is_empty() {
+                    let placeholders = vec!["?"; query.exclude_matters.len()].join(", ");
+                    sql.push_str(&format!(
+                        " AND (MATTER_ID IS NULL OR MATTER_ID NOT IN ({}))",
+                        placeholders
+                    ));
+                    params.extend(query.exclude_matters.iter().cloned());
+                }
+                sql.push_str(&format!(" ORDER BY CREATED_AT LIMIT {}", query.limit));
+                let rows = conn.query_rows(&sql, Some(params)).await?;
+                Ok(rows
+                    .iter()
+                    .map(|row| RetentionCandidate {
+                        preview_id: as_str(row.get("ID")).unwrap_or_default(),
+                        matter_id: opt_str(row.get("MATTER_ID")),
+                        created_at: parse_dt(row.get("CREATED_AT")),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// 网关不提供事务：先确认未被保全，再逐条清理，最后标记并写审计；
+    /// 中途失败时不会留下“已清理”标记，下一轮会重新执行
+    async fn purge_preview_artifact(&self, purge: &ArtifactPurge) -> Result<Option<i64>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let preview_id = purge
+                    .preview_id
+                    .clone()
+                    .ok_or_else(|| anyhow!("清理预审产物缺少 preview_id"))?;
+                let rows = conn
+                    .query_rows(
+                        "SELECT LEGAL_HOLD FROM PREVIEW_RECORDS WHERE ID = ?",
+                        Some(vec![preview_id.clone()]),
+                    )
+                    .await?;
+                match rows.first() {
+                    Some(row) if as_i64(row.get("LEGAL_HOLD")).unwrap_or(0) == 0 => {}
+                    _ => return Ok(None),
+                }
+
+                let mut db_rows = 0i64;
+                for statement in dm_artifact_statements(purge.artifact) {
+                    db_rows += conn
+                        .execute_update(statement, Some(vec![preview_id.clone()]))
+                        .await? as i64;
+                }
+                let marked = conn
+                    .execute_update(
+                        "UPDATE PREVIEW_RECORDS SET PURGED_ARTIFACTS = CASE \
+                         WHEN COALESCE(PURGED_ARTIFACTS, '') LIKE ? THEN PURGED_ARTIFACTS \
+                         ELSE COALESCE(PURGED_ARTIFACTS, ',') || ? END \
+                         WHERE ID = ? AND LEGAL_HOLD = 0",
+                        Some(vec![
+                            purge.artifact.purged_marker_pattern(),
+                            format!("{},", purge.artifact.as_str()),
+                            preview_id,
+                        ]),
+                    )
+                    .await?;
+                if marked == 0 {
+                    return Ok(None);
+                }
+                conn.insert_purge_audit(purge, db_rows).await?;
+                Ok(Some(db_rows))
+            }
+        }
+    }
+
+    async fn purge_log_records(&self, before: DateTime<Utc>, purge: &ArtifactPurge) -> Result<i64> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let cutoff = format_dm_datetime(&before);
+                let mut db_rows = 0i64;
+                for statement in [
+                    "DELETE FROM API_STATS WHERE CREATED_AT < ?",
+                    "DELETE FROM USER_LOGIN_RECORDS WHERE CREATED_AT < ?",
+                ] {
+                    db_rows += conn
+                        .execute_update(statement, Some(vec![cutoff.clone()]))
+                        .await? as i64;
+                }
+                if db_rows > 0 {
+                    conn.insert_purge_audit(purge, db_rows).await?;
+                }
+                Ok(db_rows)
+            }
+        }
+    }
+
+    async fn set_preview_legal_hold(&self, update: &LegalHoldUpdate) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let affected = if update.hold {
+                    conn.execute_update_values(
+                        "UPDATE PREVIEW_RECORDS SET LEGAL_HOLD = 1, LEGAL_HOLD_REASON = ?, \
+                         LEGAL_HOLD_BY = ?, LEGAL_HOLD_AT = ? WHERE ID = ?",
+                        vec![
+                            str_option_to_value(&update.reason),
+                            Value::String(update.updated_by.clone()),
+                            Value::String(format_dm_datetime(&Utc::now())),
+                            Value::String(update.preview_id.clone()),
+                        ],
+                    )
+                    .await?
+                } else {
+                    conn.execute_update(
+                        "UPDATE PREVIEW_RECORDS SET LEGAL_HOLD = 0, LEGAL_HOLD_REASON = NULL, \
+                         LEGAL_HOLD_BY = NULL, LEGAL_HOLD_AT = NULL WHERE ID = ?",
+                        Some(vec![update.preview_id.clone()]),
+                    )
+                    .await?
+                };
+                Ok(affected > 0)
+            }
+        }
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let rows = conn
+                    .query_rows(
+                        "SELECT ID, LEGAL_HOLD_REASON, LEGAL_HOLD_BY, LEGAL_HOLD_AT, CREATED_AT \
+                         FROM PREVIEW_RECORDS WHERE LEGAL_HOLD = 1 ORDER BY LEGAL_HOLD_AT DESC",
+                        None,
+                    )
+                    .await?;
+                Ok(rows
+                    .iter()
+                    .map(|row| LegalHoldRecord {
+                        preview_id: as_str(row.get("ID")).unwrap_or_default(),
+                        reason: opt_str(row.get("LEGAL_HOLD_REASON")),
+                        held_by: opt_str(row.get("LEGAL_HOLD_BY")),
+                        held_at: parse_dt_opt(row.get("LEGAL_HOLD_AT")),
+                        created_at: parse_dt(row.get("CREATED_AT")),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    async fn list_purge_audit(&self, filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let mut sql = String::from("SELECT * FROM RETENTION_PURGE_LOG WHERE 1 = 1");
+                let mut params = Vec::new();
+                if let Some(preview_id) = &filter.preview_id {
+                    sql.push_str(" AND PREVIEW_ID = ?");
+                    params.push(preview_id.clone());
+                }
+                if let Some(artifact) = filter.artifact {
+                    sql.push_str(" AND ARTIFACT = ?");
+                    params.push(artifact.as_str().to_string());
+                }
+                sql.push_str(&format!(
+                    " ORDER BY PURGED_AT DESC LIMIT {} OFFSET {}",
+                    filter.limit.unwrap_or(50),
+                    filter.offset.unwrap_or(0)
+                ));
+                let rows = conn.query_rows(&sql, Some(params)).await?;
+                rows.iter().map(map_purge_audit_row).collect()
+            }
+        }
+    }
+
+    async fn save_pii_lookup_value(&self, token: &str, ciphertext: &str) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let params = vec![ciphertext.to_string(), token.to_string()];
+                let updated = conn
+                    .execute_update(
+                        "UPDATE PII_LOOKUP_VALUES SET CIPHERTEXT = ?, UPDATED_AT = CURRENT_TIMESTAMP \
+                         WHERE TOKEN = ?",
+                        Some(params),
+                    )
+                    .await?;
+                if updated == 0 {
+                    conn.execute_update(
+                        "INSERT INTO PII_LOOKUP_VALUES (TOKEN, CIPHERTEXT, UPDATED_AT) \
+                         VALUES (?, ?, CURRENT_TIMESTAMP)",
+                        Some(vec![token.to_string(), ciphertext.to_string()]),
+                    )
+                    .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn get_pii_lookup_values(
+        &self,
+        tokens: &[String],
+    ) -> Result<std::collections::HashMap<String, String>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                if tokens.is_empty() {
+                    return Ok(std::collections::HashMap::new());
+                }
+                let placeholders = vec!["?"; tokens.len()].join(", ");
+                let sql = format!(
+                    "SELECT TOKEN, CIPHERTEXT FROM PII_LOOKUP_VALUES WHERE TOKEN IN ({})",
+                    placeholders
+                );
+                let rows = conn.query_rows(&sql, Some(tokens.to_vec())).await?;
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        Some((as_str(row.get("TOKEN"))?, as_str(row.get("CIPHERTEXT"))?))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    async fn list_pii_rows(
+        &self,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let key = table.key_column().to_ascii_uppercase();
+                let columns: Vec<String> = table
+                    .columns()
+                    .iter()
+                    .map(|column| column.to_ascii_uppercase())
+                    .collect();
+                let mut sql = format!(
+                    "SELECT CAST({key} AS VARCHAR(200)) AS PII_KEY, {} FROM {} WHERE 1 = 1",
+                    columns.join(", "),
+                    table.table_name().to_ascii_uppercase()
+                );
+                let mut params = Vec::new();
+                if let Some(after) = after {
+                    sql.push_str(&format!(" AND {} > ?", key));
+                    params.push(after.to_string());
+                }
+                sql.push_str(&format!(" ORDER BY {} LIMIT {}", key, limit));
+                let rows = conn.query_rows(&sql, Some(params)).await?;
+                Ok(rows
+                    .iter()
+                    .map(|row| PiiRow {
+                        key: as_str(row.get("PII_KEY")).unwrap_or_default(),
+                        values: columns
+                            .iter()
+                            .map(|column| opt_str(row.get(column)))
+                            .collect(),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    async fn update_pii_row(&self, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                if update.changes.is_empty() {
+                    return Ok(false);
+                }
+                let mut params = Vec::new();
+                let assignments: Vec<String> = update
+                    .changes
+                    .iter()
+                    .map(|change| {
+                        params.push(change.value.clone());
+                        format!("{} = ?", change.column.to_ascii_uppercase())
+                    })
+                    .collect();
+                params.push(update.key.clone());
+                let mut sql = format!(
+                    "UPDATE {} SET {} WHERE {} = ?",
+                    table.table_name().to_ascii_uppercase(),
+                    assignments.join(", "),
+                    table.key_column().to_ascii_uppercase()
+                );
+                for change in &update.changes {
+                    sql.push_str(&format!(
+                        " AND {} LIKE ? ESCAPE '\\'",
+                        change.column.to_ascii_uppercase()
+                    ));
+                    params.push(change.like_pattern());
+                }
+                Ok(conn.execute_update(&sql, Some(params)).await? > 0)
+            }
+        }
+    }
+
+    async fn touch_storage_blob(&self, blob: &StorageBlobRecord) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let updated = conn
+                    .execute_update(
+                        "UPDATE STORAGE_BLOBS SET LAST_REFERENCED_AT = ? \
+                         WHERE STORAGE_KEY = ? AND GC_CLAIM_ID IS NULL",
+                        Some(vec![
+                            format_dm_datetime(&blob.last_referenced_at),
+                            blob.storage_key.clone(),
+                        ]),
+                    )
+                    .await?;
+                if updated > 0 {
+                    return Ok(true);
+                }
+                let existing = conn
+                    .query_rows(
+                        "SELECT STORAGE_KEY FROM STORAGE_BLOBS WHERE STORAGE_KEY = ?",
+                        Some(vec![blob.storage_key.clone()]),
+                    )
+                    .await?;
+                if !existing.is_empty() {
+                    return Ok(false);
+                }
+                // 并发插入时主键冲突报错，调用方回退到按预审存放
+                conn.execute_update(
+                    "INSERT INTO STORAGE_BLOBS (STORAGE_KEY, CHECKSUM_SHA256, SIZE_BYTES, \
+                     CREATED_AT, LAST_REFERENCED_AT) VALUES (?, ?, ?, ?, ?)",
+                    Some(vec![
+                        blob.storage_key.clone(),
+                        blob.checksum_sha256.clone(),
+                        blob.size_bytes.to_string(),
+                        format_dm_datetime(&blob.created_at),
+                        format_dm_datetime(&blob.last_referenced_at),
+                    ]),
+                )
+                .await?;
+                Ok(true)
+            }
+        }
+    }
+
+    async fn claim_unreferenced_blobs(
+        &self,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                let unreferenced_before = format_dm_datetime(&query.unreferenced_before);
+                let stale_claim_before = format_dm_datetime(&query.stale_claim_before);
+                let candidates = conn
+                    .query_rows(
+                        &format!(
+                            "SELECT B.STORAGE_KEY FROM STORAGE_BLOBS B \
+                             WHERE B.LAST_REFERENCED_AT < ? \
+                             AND (B.GC_CLAIM_ID IS NULL OR B.GC_CLAIMED_AT < ?) \
+                             AND NOT EXISTS (SELECT 1 FROM PREVIEW_MATERIAL_FILES F \
+                             WHERE F.STORED_ORIGINAL_KEY = B.STORAGE_KEY) \
+                             ORDER BY B.LAST_REFERENCED_AT LIMIT {}",
+                            query.limit
+                        ),
+                        Some(vec![
+                            unreferenced_before.clone(),
+                            stale_claim_before.clone(),
+                        ]),
+                    )
+                    .await?;
+
+                // 网关不提供事务，逐条带条件认领
+                let now = format_dm_datetime(&chrono::Utc::now());
+                for row in &candidates {
+                    let Some(key) = as_str(row.get("STORAGE_KEY")) else {
+                        continue;
+                    };
+                    conn.execute_update(
+                        "UPDATE STORAGE_BLOBS SET GC_CLAIM_ID = ?, GC_CLAIMED_AT = ? \
+                         WHERE STORAGE_KEY = ? AND LAST_REFERENCED_AT < ? \
+                         AND (GC_CLAIM_ID IS NULL OR GC_CLAIMED_AT < ?) \
+                         AND NOT EXISTS (SELECT 1 FROM PREVIEW_MATERIAL_FILES F \
+                         WHERE F.STORED_ORIGINAL_KEY = STORAGE_BLOBS.STORAGE_KEY)",
+                        Some(vec![
+                            query.claim_id.clone(),
+                            now.clone(),
+                            key,
+                            unreferenced_before.clone(),
+                            stale_claim_before.clone(),
+                        ]),
+                    )
+                    .await?;
+                }
+
+                let rows = conn
+                    .query_rows(
+                        "SELECT STORAGE_KEY, CHECKSUM_SHA256, SIZE_BYTES, CREATED_AT, \
+                         LAST_REFERENCED_AT FROM STORAGE_BLOBS WHERE GC_CLAIM_ID = ? \
+                         ORDER BY LAST_REFERENCED_AT",
+                        Some(vec![query.claim_id.clone()]),
+                    )
+                    .await?;
+                Ok(rows
+                    .iter()
+                    .map(|row| StorageBlobRecord {
+                        storage_key: as_str(row.get("STORAGE_KEY")).unwrap_or_default(),
+                        checksum_sha256: as_str(row.get("CHECKSUM_SHA256")).unwrap_or_default(),
+                        size_bytes: as_i64(row.get("SIZE_BYTES")).unwrap_or(0),
+                        created_at: parse_dt(row.get("CREATED_AT")),
+                        last_referenced_at: parse_dt(row.get("LAST_REFERENCED_AT")),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    async fn delete_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<bool> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => Ok(conn
+                .execute_update(
+                    "DELETE FROM STORAGE_BLOBS WHERE STORAGE_KEY = ? AND GC_CLAIM_ID = ?",
+                    Some(vec![storage_key.to_string(), claim_id.to_string()]),
+                )
+                .await?
+                > 0),
+        }
+    }
+
+    async fn release_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<()> {
+        match &self.connection {
+            #[cfg(feature = "dm_go")]
+            DmConnectionType::Go(conn) => {
+                conn.execute_update(
+                    "UPDATE STORAGE_BLOBS SET GC_CLAIM_ID = NULL, GC_CLAIMED_AT = NULL \
+                     WHERE STORAGE_KEY = ? AND GC_CLAIM_ID = ?",
+                    Some(vec![storage_key.to_string(), claim_id.to_string()]),
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dm_go")]
+fn map_material_file_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<MaterialFileRecord> {
+    Ok(MaterialFileRecord {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
+        material_code: as_str(row.get("MATERIAL_CODE")).unwrap_or_default(),
+        attachment_name: opt_str(row.get("ATTACHMENT_NAME")),
+        source_url: opt_str(row.get("SOURCE_URL")),
+        stored_original_key: as_str(row.get("STORED_ORIGINAL_KEY")).unwrap_or_default(),
+        stored_processed_keys: opt_str(row.get("STORED_PROCESSED_KEYS")),
+        mime_type: opt_str(row.get("MIME_TYPE")),
+        size_bytes: as_i64(row.get("SIZE_BYTES")),
+        checksum_sha256: opt_str(row.get("CHECKSUM_SHA256")),
+        ocr_text_key: opt_str(row.get("OCR_TEXT_KEY")),
+        ocr_text_length: as_i64(row.get("OCR_TEXT_LENGTH")),
+        status: as_str(row.get("STATUS")).unwrap_or_else(|| "pending".to_string()),
+        error_message: opt_str(row.get("ERROR_MESSAGE")),
+        created_at: parse_dt(row.get("CREATED_AT")),
+        updated_at: parse_dt(row.get("UPDATED_AT")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_cached_material_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<CachedMaterialRecord> {
+    let status = as_str(row.get("UPLOAD_STATUS")).unwrap_or_else(|| "downloaded".to_string());
+    Ok(CachedMaterialRecord {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
+        material_code: as_str(row.get("MATERIAL_CODE")).unwrap_or_default(),
+        attachment_index: as_i64(row.get("ATTACHMENT_INDEX")).unwrap_or(0) as i32,
+        token: as_str(row.get("TOKEN")).unwrap_or_default(),
+        local_path: as_str(row.get("LOCAL_PATH")).unwrap_or_default(),
+        upload_status: CachedMaterialStatus::from_str(&status),
+        oss_key: opt_str(row.get("OSS_KEY")),
+        last_error: opt_str(row.get("LAST_ERROR")),
+        file_size: as_i64(row.get("FILE_SIZE")),
+        checksum_sha256: opt_str(row.get("CHECKSUM_SHA256")),
+        created_at: parse_dt(row.get("CREATED_AT")),
+        updated_at: parse_dt(row.get("UPDATED_AT")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_outbox_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<OutboxEvent> {
+    Ok(OutboxEvent {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        table_name: as_str(row.get("TABLE_NAME")).unwrap_or_default(),
+        op_type: as_str(row.get("OP_TYPE")).unwrap_or_default(),
+        pk_value: as_str(row.get("PK_VALUE")).unwrap_or_default(),
+        idempotency_key: as_str(row.get("IDEMPOTENCY_KEY")).unwrap_or_default(),
+        payload: as_str(row.get("PAYLOAD")).unwrap_or_default(),
+        created_at: parse_dt(row.get("CREATED_AT")),
+        applied_at: parse_dt_opt(row.get("APPLIED_AT")),
+        retries: as_i64(row.get("RETRIES")).unwrap_or(0) as i32,
+        last_error: opt_str(row.get("LAST_ERROR")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_queued_task_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> QueuedTaskRecord {
+    QueuedTaskRecord {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        queue: as_str(row.get("QUEUE")).unwrap_or_default(),
+        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
+        payload: as_str(row.get("PAYLOAD")).unwrap_or_default(),
+        status: as_str(row.get("STATUS")).unwrap_or_default(),
+        attempts: as_i64(row.get("ATTEMPTS")).unwrap_or(0) as i32,
+        lease_owner: opt_str(row.get("LEASE_OWNER")),
+        lease_token: opt_str(row.get("LEASE_TOKEN")),
+        lease_expires_at: as_i64(row.get("LEASE_EXPIRES_AT")),
+        visible_at: as_i64(row.get("VISIBLE_AT")).unwrap_or(0),
+        last_error: opt_str(row.get("LAST_ERROR")),
+        created_at: parse_dt(row.get("CREATED_AT")),
+        updated_at: parse_dt(row.get("UPDATED_AT")),
+    }
+}
+
+#[cfg(feature = "dm_go")]
+fn map_worker_control_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<WorkerControlRecord> {
+    let state = as_str(row.get("STATE")).unwrap_or_else(|| "active".to_string());
+    Ok(WorkerControlRecord {
+        worker_id: as_str(row.get("WORKER_ID")).unwrap_or_default(),
+        state: state.parse()?,
+        reason: opt_str(row.get("REASON")),
+        updated_by: as_str(row.get("UPDATED_BY")).unwrap_or_default(),
+        updated_at: parse_dt(row.get("UPDATED_AT")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_preview_batch_item_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<PreviewBatchItemRecord> {
+    let status = as_str(row.get("STATUS")).unwrap_or_else(|| "rejected".to_string());
+    Ok(PreviewBatchItemRecord {
+        batch_id: as_str(row.get("BATCH_ID")).unwrap_or_default(),
+        item_index: as_i64(row.get("ITEM_INDEX")).unwrap_or(0) as i32,
+        submitted_by: as_str(row.get("SUBMITTED_BY")).unwrap_or_default(),
+        status: status.parse()?,
+        preview_id: opt_str(row.get("PREVIEW_ID")),
+        third_party_request_id: opt_str(row.get("THIRD_PARTY_REQUEST_ID")),
+        error: opt_str(row.get("ERROR")),
+        created_at: parse_dt(row.get("CREATED_AT")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_dead_letter_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<DeadLetterRecord> {
+    let status = as_str(row.get("STATUS")).unwrap_or_else(|| "dead".to_string());
+    Ok(DeadLetterRecord {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        preview_id: as_str(row.get("PREVIEW_ID")).unwrap_or_default(),
+        queue: as_str(row.get("QUEUE")).unwrap_or_default(),
+        source: as_str(row.get("SOURCE")).unwrap_or_default(),
+        payload: as_str(row.get("PAYLOAD")).unwrap_or_default(),
+        failure_reason: as_str(row.get("FAILURE_REASON")).unwrap_or_default(),
+        attempts: as_i64(row.get("ATTEMPTS")).unwrap_or(0) as i32,
+        last_error: opt_str(row.get("LAST_ERROR")),
+        status: status.parse()?,
+        resolved_by: opt_str(row.get("RESOLVED_BY")),
+        resolved_at: parse_dt_opt(row.get("RESOLVED_AT")),
+        created_at: parse_dt(row.get("CREATED_AT")),
+        updated_at: parse_dt(row.get("UPDATED_AT")),
+    })
+}
+
+#[cfg(feature = "dm_go")]
+fn map_applied_migration_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> AppliedMigration {
+    AppliedMigration {
+        version: as_i64(row.get("VERSION")).unwrap_or(0),
+        name: as_str(row.get("NAME")).unwrap_or_default(),
+        checksum: as_str(row.get("CHECKSUM")).unwrap_or_default(),
+        applied_at: parse_dt(row.get("APPLIED_AT")),
+        execution_ms: as_i64(row.get("EXECUTION_MS")).unwrap_or(0),
+    }
+}
+
+/// 各类产物在数据库中的清理语句，每条只绑定一个预审ID
+#[cfg(feature = "dm_go")]
+fn dm_artifact_statements(artifact: RetentionArtifact) -> &'static [&'static str] {
+    match artifact {
+        RetentionArtifact::RawRequest => &[
+            "DELETE FROM PREVIEW_TASK_PAYLOADS WHERE PREVIEW_ID = ?",
+            "UPDATE PREVIEW_REQUESTS SET USER_INFO_JSON = NULL, AGENT_INFO_JSON = NULL, \
+             SUBJECT_INFO_JSON = NULL, FORM_DATA_JSON = NULL, SCENE_DATA_JSON = NULL, \
+             MATERIAL_DATA_JSON = NULL WHERE LATEST_PREVIEW_ID = ?",
+            "UPDATE PREVIEW_RECORDS SET USER_INFO_JSON = NULL WHERE ID = ?",
+            "UPDATE PREVIEW_MATERIAL_FILES SET STORED_ORIGINAL_KEY = '', SOURCE_URL = NULL \
+             WHERE PREVIEW_ID = ?",
+            "UPDATE CACHED_MATERIALS SET OSS_KEY = NULL WHERE PREVIEW_ID = ?",
+        ],
+        RetentionArtifact::OcrText => &[
+            "UPDATE PREVIEW_RECORDS SET OCR_TEXT = '' WHERE ID = ?",
+            "UPDATE PREVIEW_MATERIAL_FILES SET OCR_TEXT_KEY = NULL, OCR_TEXT_LENGTH = NULL \
+             WHERE PREVIEW_ID = ?",
+        ],
+        RetentionArtifact::PageImages => &[
+            "UPDATE PREVIEW_MATERIAL_FILES SET STORED_PROCESSED_KEYS = NULL WHERE PREVIEW_ID = ?",
+        ],
+        RetentionArtifact::Reports => &[
+            "UPDATE PREVIEW_RECORDS SET EVALUATION_RESULT = NULL, CALLBACK_PAYLOAD = NULL \
+             WHERE ID = ?",
+            "DELETE FROM PREVIEW_RULE_RESULTS WHERE PREVIEW_ID = ?",
+            "DELETE FROM PREVIEW_MATERIAL_RESULTS WHERE PREVIEW_ID = ?",
+        ],
+        RetentionArtifact::Logs => &[],
+    }
+}
+
+#[cfg(feature = "dm_go")]
+fn map_purge_audit_row(
+    row: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<PurgeAuditRecord> {
+    let storage_keys = as_str(row.get("STORAGE_KEYS")).unwrap_or_else(|| "[]".to_string());
+    Ok(PurgeAuditRecord {
+        id: as_str(row.get("ID")).unwrap_or_default(),
+        preview_id: opt_str(row.get("PREVIEW_ID")),
+        matter_id: opt_str(row.get("MATTER_ID")),
+        artifact: as_str(row.get("ARTIFACT")).unwrap_or_default(),
+        storage_keys: serde_json::from_str(&storage_keys)?,
+        db_rows: as_i64(row.get("DB_ROWS")).unwrap_or(0),
+        reason: as_str(row.get("REASON")).unwrap_or_default(),
+        purged_at: parse_dt(row.get("PURGED_AT")),
     })
 }
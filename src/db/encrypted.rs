@@ -0,0 +1,1559 @@
+//! 字段级加密包装层
+//!
+//! 包在具体后端（或故障转移层）之外，写入前加密指定的申请人敏感字段，读取后解密，
+//! 调用方看到的始终是明文。第三方请求ID存放盲索引令牌，等值查询按令牌进行，
+//! 原值的密文存放在 `pii_lookup_values`，读取时批量还原。
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use super::migrations::{Migration, MigrationDialect};
+use super::traits::*;
+use crate::util::config::types::PiiEncryptionConfig;
+use crate::util::crypto::PiiCipher;
+
+/// 明文并发检测取前多少个字符（SQLite 的 LIKE 模式有长度上限）
+const GUARD_PREFIX_CHARS: usize = 200;
+
+pub struct EncryptedDatabase {
+    inner: Arc<dyn Database>,
+    cipher: Arc<PiiCipher>,
+}
+
+impl EncryptedDatabase {
+    pub fn new(inner: Arc<dyn Database>, cipher: Arc<PiiCipher>) -> Self {
+        Self { inner, cipher }
+    }
+
+    pub fn from_config(inner: Arc<dyn Database>, config: &PiiEncryptionConfig) -> Result<Self> {
+        let cipher = PiiCipher::new(&config.key_version, &config.keys, &config.blind_index_key)
+            .context("字段加密配置无效")?;
+        Ok(Self::new(inner, Arc::new(cipher)))
+    }
+
+    /// 加密单个值；已是密文的值（如回放日志中的记录）原样返回
+    fn seal(&self, value: &str) -> Result<String> {
+        if PiiCipher::is_encrypted(value) {
+            return Ok(value.to_string());
+        }
+        self.cipher.encrypt(value)
+    }
+
+    fn encrypt_opt(&self, value: Option<&String>) -> Result<Option<String>> {
+        value.map(|v| self.seal(v)).transpose()
+    }
+
+    fn decrypt_opt(&self, value: Option<String>) -> Result<Option<String>> {
+        value.map(|v| self.cipher.decrypt(&v)).transpose()
+    }
+
+    /// 返回盲索引令牌，并保存原值密文供读取时还原
+    async fn tokenize(&self, value: Option<&String>) -> Result<Option<String>> {
+        match value.filter(|v| !v.is_empty() && !PiiCipher::is_blind_index(v)) {
+            Some(v) => {
+                let token = self.cipher.blind_index(v);
+                self.inner
+                    .save_pii_lookup_value(&token, &self.cipher.encrypt(v)?)
+                    .await?;
+                Ok(Some(token))
+            }
+            None => Ok(value.cloned()),
+        }
+    }
+
+    /// 批量把令牌还原为原值；找不到密文的令牌不在结果中
+    async fn resolve_tokens<'a>(
+        &self,
+        values: impl Iterator<Item = Option<&'a String>>,
+    ) -> Result<HashMap<String, String>> {
+        let tokens: Vec<String> = values
+            .flatten()
+            .filter(|v| PiiCipher::is_blind_index(v))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if tokens.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut resolved = HashMap::new();
+        for (token, ciphertext) in self.inner.get_pii_lookup_values(&tokens).await? {
+            let value = self.cipher.decrypt(&ciphertext)?;
+            resolved.insert(token, value);
+        }
+        Ok(resolved)
+    }
+
+    async fn encrypt_request(
+        &self,
+        request: &PreviewRequestRecord,
+    ) -> Result<PreviewRequestRecord> {
+        let mut request = request.clone();
+        request.third_party_request_id = self
+            .tokenize(request.third_party_request_id.as_ref())
+            .await?;
+        for field in [
+            &mut request.user_info_json,
+            &mut request.agent_info_json,
+            &mut request.subject_info_json,
+            &mut request.form_data_json,
+            &mut request.scene_data_json,
+            &mut request.material_data_json,
+        ] {
+            *field = self.encrypt_opt(field.as_ref())?;
+        }
+        Ok(request)
+    }
+
+    /// 第三方请求号存的是盲索引，模糊搜索匹配不到，另按盲索引精确匹配后合并。
+    /// 两路各取前 offset + limit 条，合并排序后再分页
+    async fn search_preview_requests(
+        &self,
+        filter: &PreviewRequestFilter,
+        search: &str,
+    ) -> Result<Vec<PreviewRequestRecord>> {
+        let window = filter
+            .limit
+            .map(|limit| limit.saturating_add(filter.offset.unwrap_or(0)));
+        let mut fuzzy = filter.clone();
+        fuzzy.limit = window;
+        fuzzy.offset = None;
+        let mut exact = fuzzy.clone();
+        exact.search = None;
+        exact.third_party_request_id = Some(self.cipher.blind_index(search.trim()));
+
+        let mut requests = self.inner.list_preview_requests(&exact).await?;
+        for request in self.inner.list_preview_requests(&fuzzy).await? {
+            if !requests.iter().any(|r| r.id == request.id) {
+                requests.push(request);
+            }
+        }
+        requests.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        let offset = filter.offset.unwrap_or(0) as usize;
+        let limit = filter.limit.map_or(usize::MAX, |limit| limit as usize);
+        Ok(requests.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn decrypt_requests(
+        &self,
+        mut requests: Vec<PreviewRequestRecord>,
+    ) -> Result<Vec<PreviewRequestRecord>> {
+        let resolved = self
+            .resolve_tokens(requests.iter().map(|r| r.third_party_request_id.as_ref()))
+            .await?;
+        for request in &mut requests {
+            if let Some(value) = request
+                .third_party_request_id
+                .as_ref()
+                .and_then(|token| resolved.get(token))
+            {
+                request.third_party_request_id = Some(value.clone());
+            }
+            for field in [
+                &mut request.user_info_json,
+                &mut request.agent_info_json,
+                &mut request.subject_info_json,
+                &mut request.form_data_json,
+                &mut request.scene_data_json,
+                &mut request.material_data_json,
+            ] {
+                *field = self
+                    .decrypt_opt(field.take())
+                    .with_context(|| format!("解密预审请求 {} 失败", request.id))?;
+            }
+        }
+        Ok(requests)
+    }
+
+    async fn encrypt_record(&self, record: &PreviewRecord) -> Result<PreviewRecord> {
+        let mut record = record.clone();
+        record.third_party_request_id = self
+            .tokenize(record.third_party_request_id.as_ref())
+            .await?;
+        record.user_info_json = self.encrypt_opt(record.user_info_json.as_ref())?;
+        record.ocr_text = self.seal(&record.ocr_text)?;
+        record.evaluation_result = self.encrypt_opt(record.evaluation_result.as_ref())?;
+        Ok(record)
+    }
+
+    async fn decrypt_records(&self, mut records: Vec<PreviewRecord>) -> Result<Vec<PreviewRecord>> {
+        let resolved = self
+            .resolve_tokens(records.iter().map(|r| r.third_party_request_id.as_ref()))
+            .await?;
+        for record in &mut records {
+            if let Some(value) = record
+                .third_party_request_id
+                .as_ref()
+                .and_then(|token| resolved.get(token))
+            {
+                record.third_party_request_id = Some(value.clone());
+            }
+            record.user_info_json = self
+                .decrypt_opt(record.user_info_json.take())
+                .with_context(|| format!("解密预审记录 {} 失败", record.id))?;
+            record.ocr_text = self
+                .cipher
+                .decrypt(&record.ocr_text)
+                .with_context(|| format!("解密预审记录 {} 失败", record.id))?;
+            record.evaluation_result = self
+                .decrypt_opt(record.evaluation_result.take())
+                .with_context(|| format!("解密预审记录 {} 失败", record.id))?;
+        }
+        Ok(records)
+    }
+
+    /// 解密队列载荷；加密启用前写入的明文原样返回
+    fn decrypt_payload(&self, payload: &str, queue: &str, id: &str) -> Result<String> {
+        self.cipher
+            .decrypt(payload)
+            .with_context(|| format!("解密{} {} 失败", queue, id))
+    }
+
+    fn decrypt_dead_letter(&self, mut record: DeadLetterRecord) -> Result<DeadLetterRecord> {
+        record.payload = self
+            .cipher
+            .decrypt(&record.payload)
+            .with_context(|| format!("解密死信 {} 失败", record.id))?;
+        Ok(record)
+    }
+
+    /// 重新加密一行；无需改动时返回 None，被并发修改时返回 Some(false)
+    async fn rotate_row(&self, table: PiiTable, row: &PiiRow) -> Result<Option<bool>> {
+        let mut changes = Vec::new();
+        for (column, value) in table.columns().iter().zip(&row.values) {
+            let Some(value) = value.as_deref().filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            if table.blind_index_column() == Some(*column) {
+                if !PiiCipher::is_blind_index(value) {
+                    let token = self.cipher.blind_index(value);
+                    self.inner
+                        .save_pii_lookup_value(&token, &self.cipher.encrypt(value)?)
+                        .await?;
+                    changes.push(PiiColumnUpdate {
+                        column,
+                        value: token,
+                        expected_prefix: guard_prefix(value),
+                    });
+                }
+                continue;
+            }
+            if let Some(rotated) = self.cipher.rewrap(value)? {
+                changes.push(PiiColumnUpdate {
+                    column,
+                    value: rotated,
+                    expected_prefix: guard_prefix(value),
+                });
+            }
+        }
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let update = PiiRowUpdate {
+            key: row.key.clone(),
+            changes,
+        };
+        self.inner.update_pii_row(table, &update).await.map(Some)
+    }
+}
+
+/// 并发写入检测用的前缀：密文取到版本号为止，明文取开头一段
+fn guard_prefix(value: &str) -> String {
+    match PiiCipher::key_version_of(value) {
+        Some(version) => PiiCipher::ciphertext_prefix(version),
+        None => value.chars().take(GUARD_PREFIX_CHARS).collect(),
+    }
+}
+
+#[async_trait]
+impl Database for EncryptedDatabase {
+    // 透明包装：按具体类型识别后端（故障转移状态、监控用户等）时看到的是内层
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+
+    async fn save_preview_request(&self, request: &PreviewRequestRecord) -> Result<()> {
+        let request = self.encrypt_request(request).await?;
+        self.inner.save_preview_request(&request).await
+    }
+
+    async fn get_preview_request(&self, id: &str) -> Result<Option<PreviewRequestRecord>> {
+        match self.inner.get_preview_request(id).await? {
+            Some(request) => Ok(self.decrypt_requests(vec![request]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_preview_request_by_third_party(
+        &self,
+        third_party_request_id: &str,
+    ) -> Result<Option<PreviewRequestRecord>> {
+        let token = self.cipher.blind_index(third_party_request_id);
+        let found = match self
+            .inner
+            .find_preview_request_by_third_party(&token)
+            .await?
+        {
+            Some(request) => Some(request),
+            // 轮换任务尚未处理的历史明文
+            None => {
+                self.inner
+                    .find_preview_request_by_third_party(third_party_request_id)
+                    .await?
+            }
+        };
+        match found {
+            Some(request) => Ok(self.decrypt_requests(vec![request]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_preview_request_latest(
+        &self,
+        request_id: &str,
+        latest_preview_id: Option<&str>,
+        latest_status: Option<PreviewStatus>,
+    ) -> Result<()> {
+        self.inner
+            .update_preview_request_latest(request_id, latest_preview_id, latest_status)
+            .await
+    }
+
+    async fn list_preview_requests(
+        &self,
+        filter: &PreviewRequestFilter,
+    ) -> Result<Vec<PreviewRequestRecord>> {
+        let requests = match &filter.third_party_request_id {
+            Some(third_party_request_id) => {
+                let mut tokenized = filter.clone();
+                tokenized.third_party_request_id =
+                    Some(self.cipher.blind_index(third_party_request_id));
+                let requests = self.inner.list_preview_requests(&tokenized).await?;
+                if requests.is_empty() {
+                    self.inner.list_preview_requests(filter).await?
+                } else {
+                    requests
+                }
+            }
+            None => match filter.search.as_deref().filter(|s| !s.trim().is_empty()) {
+                Some(search) => self.search_preview_requests(filter, search).await?,
+                None => self.inner.list_preview_requests(filter).await?,
+            },
+        };
+        self.decrypt_requests(requests).await
+    }
+
+    async fn save_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        let record = self.encrypt_record(record).await?;
+        self.inner.save_preview_record(&record).await
+    }
+
+    async fn get_preview_record(&self, id: &str) -> Result<Option<PreviewRecord>> {
+        match self.inner.get_preview_record(id).await? {
+            Some(record) => Ok(self.decrypt_records(vec![record]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_preview_status(&self, id: &str, status: PreviewStatus) -> Result<()> {
+        self.inner.update_preview_status(id, status).await
+    }
+
+    async fn update_preview_evaluation_result(
+        &self,
+        id: &str,
+        evaluation_result: &str,
+    ) -> Result<()> {
+        self.inner
+            .update_preview_evaluation_result(id, &self.seal(evaluation_result)?)
+            .await
+    }
+
+    async fn mark_preview_processing(
+        &self,
+        id: &str,
+        worker_id: &str,
+        attempt_id: &str,
+    ) -> Result<()> {
+        self.inner
+            .mark_preview_processing(id, worker_id, attempt_id)
+            .await
+    }
+
+    async fn update_preview_artifacts(
+        &self,
+        id: &str,
+        file_name: &str,
+        preview_url: &str,
+        preview_view_url: Option<&str>,
+        preview_download_url: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_preview_artifacts(
+                id,
+                file_name,
+                preview_url,
+                preview_view_url,
+                preview_download_url,
+            )
+            .await
+    }
+
+    async fn replace_preview_material_results(
+        &self,
+        preview_id: &str,
+        records: &[PreviewMaterialResultRecord],
+    ) -> Result<()> {
+        let mut records = records.to_vec();
+        for record in &mut records {
+            record.attachments_json = self.encrypt_opt(record.attachments_json.as_ref())?;
+            record.summary_json = self.encrypt_opt(record.summary_json.as_ref())?;
+        }
+        self.inner
+            .replace_preview_material_results(preview_id, &records)
+            .await
+    }
+
+    async fn replace_preview_rule_results(
+        &self,
+        preview_id: &str,
+        records: &[PreviewRuleResultRecord],
+    ) -> Result<()> {
+        let mut records = records.to_vec();
+        for record in &mut records {
+            for field in [
+                &mut record.message,
+                &mut record.suggestions_json,
+                &mut record.evidence_json,
+                &mut record.extra_json,
+            ] {
+                *field = self.encrypt_opt(field.as_ref())?;
+            }
+        }
+        self.inner
+            .replace_preview_rule_results(preview_id, &records)
+            .await
+    }
+
+    async fn update_preview_failure_context(&self, update: &PreviewFailureUpdate) -> Result<()> {
+        self.inner.update_preview_failure_context(update).await
+    }
+
+    async fn list_preview_records(&self, filter: &PreviewFilter) -> Result<Vec<PreviewRecord>> {
+        let records = match &filter.third_party_request_id {
+            Some(third_party_request_id) => {
+                let mut tokenized = filter.clone();
+                tokenized.third_party_request_id =
+                    Some(self.cipher.blind_index(third_party_request_id));
+                let records = self.inner.list_preview_records(&tokenized).await?;
+                if records.is_empty() {
+                    self.inner.list_preview_records(filter).await?
+                } else {
+                    records
+                }
+            }
+            None => self.inner.list_preview_records(filter).await?,
+        };
+        self.decrypt_records(records).await
+    }
+
+    async fn check_and_update_preview_dedup(
+        &self,
+        fingerprint: &str,
+        preview_id: &str,
+        meta: &PreviewDedupMeta,
+        limit: i32,
+    ) -> Result<PreviewDedupDecision> {
+        let mut meta = meta.clone();
+        meta.third_party_request_id = meta
+            .third_party_request_id
+            .as_deref()
+            .filter(|value| !value.is_empty())
+            .map(|value| self.cipher.blind_index(value));
+        self.inner
+            .check_and_update_preview_dedup(fingerprint, preview_id, &meta, limit)
+            .await
+    }
+
+    async fn get_preview_status_counts(&self) -> Result<PreviewStatusCounts> {
+        self.inner.get_preview_status_counts().await
+    }
+
+    async fn find_preview_by_third_party_id(
+        &self,
+        third_party_id: &str,
+        user_id: &str,
+    ) -> Result<Option<PreviewRecord>> {
+        let token = self.cipher.blind_index(third_party_id);
+        let found = match self
+            .inner
+            .find_preview_by_third_party_id(&token, user_id)
+            .await?
+        {
+            Some(record) => Some(record),
+            None => {
+                self.inner
+                    .find_preview_by_third_party_id(third_party_id, user_id)
+                    .await?
+            }
+        };
+        match found {
+            Some(record) => Ok(self.decrypt_records(vec![record]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_api_stats(&self, stats: &ApiStats) -> Result<()> {
+        self.inner.save_api_stats(stats).await
+    }
+
+    async fn get_api_stats(&self, filter: &StatsFilter) -> Result<Vec<ApiStats>> {
+        self.inner.get_api_stats(filter).await
+    }
+
+    async fn get_api_summary(&self, filter: &StatsFilter) -> Result<ApiSummary> {
+        self.inner.get_api_summary(filter).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        self.inner.initialize().await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_user_login_record(
+        &self,
+        user_id: &str,
+        user_name: Option<&str>,
+        certificate_type: &str,
+        certificate_number: Option<&str>,
+        phone_number: Option<&str>,
+        email: Option<&str>,
+        organization_name: Option<&str>,
+        organization_code: Option<&str>,
+        login_type: &str,
+        login_time: &str,
+        client_ip: &str,
+        user_agent: &str,
+        referer: &str,
+        cookie_info: &str,
+        raw_data: &str,
+    ) -> Result<()> {
+        let encrypt = |value: Option<&str>| value.map(|v| self.seal(v)).transpose();
+        let user_name = encrypt(user_name)?;
+        let certificate_number = encrypt(certificate_number)?;
+        let phone_number = encrypt(phone_number)?;
+        let email = encrypt(email)?;
+        let raw_data = self.seal(raw_data)?;
+        self.inner
+            .save_user_login_record(
+                user_id,
+                user_name.as_deref(),
+                certificate_type,
+                certificate_number.as_deref(),
+                phone_number.as_deref(),
+                email.as_deref(),
+                organization_name,
+                organization_code,
+                login_type,
+                login_time,
+                client_ip,
+                user_agent,
+                referer,
+                cookie_info,
+                &raw_data,
+            )
+            .await
+    }
+
+    async fn upsert_cached_material_record(&self, record: &CachedMaterialRecord) -> Result<()> {
+        self.inner.upsert_cached_material_record(record).await
+    }
+
+    async fn update_cached_material_status(
+        &self,
+        id: &str,
+        status: CachedMaterialStatus,
+        oss_key: Option<&str>,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_cached_material_status(id, status, oss_key, last_error)
+            .await
+    }
+
+    async fn list_cached_material_records(
+        &self,
+        filter: &CachedMaterialFilter,
+    ) -> Result<Vec<CachedMaterialRecord>> {
+        self.inner.list_cached_material_records(filter).await
+    }
+
+    async fn delete_cached_material_record(&self, id: &str) -> Result<()> {
+        self.inner.delete_cached_material_record(id).await
+    }
+
+    async fn delete_cached_materials_by_preview(&self, preview_id: &str) -> Result<()> {
+        self.inner
+            .delete_cached_materials_by_preview(preview_id)
+            .await
+    }
+
+    async fn save_material_file_record(&self, record: &MaterialFileRecord) -> Result<()> {
+        self.inner.save_material_file_record(record).await
+    }
+
+    async fn update_material_file_status(
+        &self,
+        id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_material_file_status(id, status, error)
+            .await
+    }
+
+    async fn update_material_file_processing(
+        &self,
+        id: &str,
+        processed_keys_json: Option<&str>,
+        ocr_text_key: Option<&str>,
+        ocr_text_length: Option<i64>,
+    ) -> Result<()> {
+        self.inner
+            .update_material_file_processing(id, processed_keys_json, ocr_text_key, ocr_text_length)
+            .await
+    }
+
+    async fn list_material_files(
+        &self,
+        filter: &MaterialFileFilter,
+    ) -> Result<Vec<MaterialFileRecord>> {
+        self.inner.list_material_files(filter).await
+    }
+
+    async fn save_task_payload(&self, preview_id: &str, payload: &str) -> Result<()> {
+        let payload = self.seal(payload)?;
+        self.inner.save_task_payload(preview_id, &payload).await
+    }
+
+    async fn load_task_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        self.decrypt_opt(self.inner.load_task_payload(preview_id).await?)
+    }
+
+    async fn delete_task_payload(&self, preview_id: &str) -> Result<()> {
+        self.inner.delete_task_payload(preview_id).await
+    }
+
+    async fn update_preview_callback_state(&self, update: &PreviewCallbackUpdate) -> Result<()> {
+        self.inner.update_preview_callback_state(update).await
+    }
+
+    async fn list_due_callbacks(&self, limit: u32) -> Result<Vec<PreviewRecord>> {
+        let records = self.inner.list_due_callbacks(limit).await?;
+        self.decrypt_records(records).await
+    }
+
+    async fn enqueue_outbox_event(&self, event: &NewOutboxEvent) -> Result<()> {
+        self.inner.enqueue_outbox_event(event).await
+    }
+
+    async fn fetch_pending_outbox_events(&self, limit: u32) -> Result<Vec<OutboxEvent>> {
+        self.inner.fetch_pending_outbox_events(limit).await
+    }
+
+    async fn mark_outbox_event_applied(&self, event_id: &str) -> Result<()> {
+        self.inner.mark_outbox_event_applied(event_id).await
+    }
+
+    async fn mark_outbox_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        self.inner.mark_outbox_event_failed(event_id, error).await
+    }
+
+    async fn get_matter_rule_config(
+        &self,
+        matter_id: &str,
+    ) -> Result<Option<MatterRuleConfigRecord>> {
+        self.inner.get_matter_rule_config(matter_id).await
+    }
+
+    async fn upsert_matter_rule_config(&self, config: &MatterRuleConfigRecord) -> Result<()> {
+        self.inner.upsert_matter_rule_config(config).await
+    }
+
+    async fn list_matter_rule_configs(
+        &self,
+        status: Option<&str>,
+    ) -> Result<Vec<MatterRuleConfigRecord>> {
+        self.inner.list_matter_rule_configs(status).await
+    }
+
+    async fn find_monitor_user_by_username(&self, username: &str) -> Result<Option<MonitorUser>> {
+        self.inner.find_monitor_user_by_username(username).await
+    }
+
+    async fn get_monitor_user_password_hash(&self, user_id: &str) -> Result<String> {
+        self.inner.get_monitor_user_password_hash(user_id).await
+    }
+
+    async fn create_monitor_session(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        ip: &str,
+        user_agent: &str,
+        created_at: &str,
+        expires_at: &str,
+    ) -> Result<()> {
+        self.inner
+            .create_monitor_session(session_id, user_id, ip, user_agent, created_at, expires_at)
+            .await
+    }
+
+    async fn find_monitor_session_by_id(&self, session_id: &str) -> Result<Option<MonitorSession>> {
+        self.inner.find_monitor_session_by_id(session_id).await
+    }
+
+    async fn update_monitor_login_info(&self, user_id: &str, now: &str) -> Result<()> {
+        self.inner.update_monitor_login_info(user_id, now).await
+    }
+
+    async fn update_monitor_session_activity(&self, session_id: &str, now: &str) -> Result<()> {
+        self.inner
+            .update_monitor_session_activity(session_id, now)
+            .await
+    }
+
+    async fn delete_monitor_session(&self, session_id: &str) -> Result<()> {
+        self.inner.delete_monitor_session(session_id).await
+    }
+
+    async fn cleanup_expired_monitor_sessions(&self, now: &str) -> Result<u64> {
+        self.inner.cleanup_expired_monitor_sessions(now).await
+    }
+
+    async fn get_active_monitor_sessions_count(&self, now: &str) -> Result<i64> {
+        self.inner.get_active_monitor_sessions_count(now).await
+    }
+
+    async fn list_monitor_users(&self) -> Result<Vec<MonitorUser>> {
+        self.inner.list_monitor_users().await
+    }
+
+    async fn create_monitor_user(
+        &self,
+        id: &str,
+        username: &str,
+        password_hash: &str,
+        role: &str,
+        now: &str,
+    ) -> Result<()> {
+        self.inner
+            .create_monitor_user(id, username, password_hash, role, now)
+            .await
+    }
+
+    async fn update_monitor_user_role(&self, user_id: &str, role: &str, now: &str) -> Result<()> {
+        self.inner
+            .update_monitor_user_role(user_id, role, now)
+            .await
+    }
+
+    async fn update_monitor_user_password(
+        &self,
+        user_id: &str,
+        password_hash: &str,
+        now: &str,
+    ) -> Result<()> {
+        self.inner
+            .update_monitor_user_password(user_id, password_hash, now)
+            .await
+    }
+
+    async fn set_monitor_user_active(
+        &self,
+        user_id: &str,
+        is_active: bool,
+        now: &str,
+    ) -> Result<()> {
+        self.inner
+            .set_monitor_user_active(user_id, is_active, now)
+            .await
+    }
+
+    async fn count_active_monitor_admins(&self) -> Result<i64> {
+        self.inner.count_active_monitor_admins().await
+    }
+
+    async fn find_monitor_user_by_id(&self, user_id: &str) -> Result<Option<MonitorUser>> {
+        self.inner.find_monitor_user_by_id(user_id).await
+    }
+
+    async fn enqueue_worker_result(&self, preview_id: &str, payload: &str) -> Result<()> {
+        self.inner
+            .enqueue_worker_result(preview_id, &self.seal(payload)?)
+            .await
+    }
+
+    async fn fetch_pending_worker_results(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<WorkerResultQueueRecord>> {
+        let mut records = self.inner.fetch_pending_worker_results(limit).await?;
+        for record in &mut records {
+            record.payload = self.decrypt_payload(&record.payload, "结果回传", &record.id)?;
+        }
+        Ok(records)
+    }
+
+    async fn update_worker_result_status(
+        &self,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_worker_result_status(id, status, last_error)
+            .await
+    }
+
+    async fn get_worker_result_by_preview_id(
+        &self,
+        preview_id: &str,
+    ) -> Result<Option<WorkerResultQueueRecord>> {
+        let mut record = self
+            .inner
+            .get_worker_result_by_preview_id(preview_id)
+            .await?;
+        if let Some(record) = &mut record {
+            record.payload = self.decrypt_payload(&record.payload, "结果回传", &record.id)?;
+        }
+        Ok(record)
+    }
+
+    async fn enqueue_material_download(&self, preview_id: &str, payload: &str) -> Result<()> {
+        self.inner
+            .enqueue_material_download(preview_id, &self.seal(payload)?)
+            .await
+    }
+
+    async fn fetch_pending_material_downloads(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<MaterialDownloadQueueRecord>> {
+        let mut records = self.inner.fetch_pending_material_downloads(limit).await?;
+        for record in &mut records {
+            record.payload = self.decrypt_payload(&record.payload, "材料下载任务", &record.id)?;
+        }
+        Ok(records)
+    }
+
+    async fn update_material_download_status(
+        &self,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_material_download_status(id, status, last_error)
+            .await
+    }
+
+    async fn update_material_download_payload(&self, id: &str, payload: &str) -> Result<()> {
+        self.inner
+            .update_material_download_payload(id, &self.seal(payload)?)
+            .await
+    }
+
+    async fn cancel_material_downloads(&self, preview_id: &str) -> Result<u64> {
+        self.inner.cancel_material_downloads(preview_id).await
+    }
+
+    async fn latest_material_download_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        self.inner
+            .latest_material_download_payload(preview_id)
+            .await?
+            .map(|payload| self.decrypt_payload(&payload, "材料下载任务", preview_id))
+            .transpose()
+    }
+
+    async fn ensure_task_queue_table(&self, table: &str) -> Result<()> {
+        self.inner.ensure_task_queue_table(table).await
+    }
+
+    async fn enqueue_queued_task(&self, table: &str, task: &NewQueuedTask) -> Result<()> {
+        let mut task = task.clone();
+        task.payload = self.seal(&task.payload)?;
+        self.inner.enqueue_queued_task(table, &task).await
+    }
+
+    async fn claim_queued_tasks(
+        &self,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        let mut tasks = self.inner.claim_queued_tasks(table, claim).await?;
+        for task in &mut tasks {
+            task.payload = self.decrypt_payload(&task.payload, "队列任务", &task.id)?;
+        }
+        Ok(tasks)
+    }
+
+    async fn extend_queued_task_lease(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        self.inner
+            .extend_queued_task_lease(table, task_id, lease_token, lease_expires_at)
+            .await
+    }
+
+    async fn complete_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        self.inner
+            .complete_queued_task(table, task_id, lease_token)
+            .await
+    }
+
+    async fn release_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        self.inner
+            .release_queued_task(table, task_id, lease_token, error, retry_at)
+            .await
+    }
+
+    async fn return_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        self.inner
+            .return_queued_task(table, task_id, lease_token)
+            .await
+    }
+
+    async fn queued_task_stats(&self, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        self.inner.queued_task_stats(table, queue).await
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        let mut tasks = self
+            .inner
+            .list_failed_queued_tasks(table, queue, limit)
+            .await?;
+        for task in &mut tasks {
+            task.payload = self.decrypt_payload(&task.payload, "队列任务", &task.id)?;
+        }
+        Ok(tasks)
+    }
+
+    async fn delete_queued_task(&self, table: &str, task_id: &str) -> Result<bool> {
+        self.inner.delete_queued_task(table, task_id).await
+    }
+
+    async fn cancel_queued_tasks(&self, table: &str, queue: &str, preview_id: &str) -> Result<u64> {
+        self.inner
+            .cancel_queued_tasks(table, queue, preview_id)
+            .await
+    }
+
+    async fn save_dead_letter(&self, record: &DeadLetterRecord) -> Result<()> {
+        let mut record = record.clone();
+        record.payload = self.seal(&record.payload)?;
+        self.inner.save_dead_letter(&record).await
+    }
+
+    async fn list_dead_letters(&self, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        self.inner
+            .list_dead_letters(filter)
+            .await?
+            .into_iter()
+            .map(|record| self.decrypt_dead_letter(record))
+            .collect()
+    }
+
+    async fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetterRecord>> {
+        self.inner
+            .get_dead_letter(id)
+            .await?
+            .map(|record| self.decrypt_dead_letter(record))
+            .transpose()
+    }
+
+    async fn resolve_dead_letter(
+        &self,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        let payload = payload.map(|p| self.seal(p)).transpose()?;
+        self.inner
+            .resolve_dead_letter(id, status, payload.as_deref(), resolved_by)
+            .await
+    }
+
+    async fn save_worker_control(&self, record: &WorkerControlRecord) -> Result<()> {
+        self.inner.save_worker_control(record).await
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        self.inner.list_worker_controls().await
+    }
+
+    async fn reconcile_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        let record = self.encrypt_record(record).await?;
+        self.inner.reconcile_preview_record(&record).await
+    }
+
+    async fn save_preview_batch_items(&self, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        self.inner.save_preview_batch_items(items).await
+    }
+
+    async fn list_preview_batch_items(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        self.inner.list_preview_batch_items(batch_id).await
+    }
+
+    async fn get_download_cache_token(
+        &self,
+        url: &str,
+    ) -> Result<Option<MaterialDownloadCacheEntry>> {
+        self.inner.get_download_cache_token(url).await
+    }
+
+    async fn upsert_download_cache_token(
+        &self,
+        url: &str,
+        token: &str,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        self.inner
+            .upsert_download_cache_token(url, token, ttl_secs)
+            .await
+    }
+
+    async fn create_preview_share_token(
+        &self,
+        preview_id: &str,
+        token: &str,
+        format: &str,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        self.inner
+            .create_preview_share_token(preview_id, token, format, ttl_secs)
+            .await
+    }
+
+    async fn consume_preview_share_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<PreviewShareTokenRecord>> {
+        self.inner.consume_preview_share_token(token).await
+    }
+
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        self.inner.migration_dialect()
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        self.inner.list_applied_migrations().await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        self.inner.apply_migration(migration).await
+    }
+
+    async fn list_retention_candidates(
+        &self,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        self.inner.list_retention_candidates(query).await
+    }
+
+    async fn purge_preview_artifact(&self, purge: &ArtifactPurge) -> Result<Option<i64>> {
+        self.inner.purge_preview_artifact(purge).await
+    }
+
+    async fn purge_log_records(&self, before: DateTime<Utc>, purge: &ArtifactPurge) -> Result<i64> {
+        self.inner.purge_log_records(before, purge).await
+    }
+
+    async fn set_preview_legal_hold(&self, update: &LegalHoldUpdate) -> Result<bool> {
+        self.inner.set_preview_legal_hold(update).await
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        self.inner.list_legal_holds().await
+    }
+
+    async fn list_purge_audit(&self, filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        self.inner.list_purge_audit(filter).await
+    }
+
+    async fn save_pii_lookup_value(&self, token: &str, ciphertext: &str) -> Result<()> {
+        self.inner.save_pii_lookup_value(token, ciphertext).await
+    }
+
+    async fn get_pii_lookup_values(&self, tokens: &[String]) -> Result<HashMap<String, String>> {
+        self.inner.get_pii_lookup_values(tokens).await
+    }
+
+    async fn list_pii_rows(
+        &self,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        self.inner.list_pii_rows(table, after, limit).await
+    }
+
+    async fn update_pii_row(&self, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        self.inner.update_pii_row(table, update).await
+    }
+
+    async fn touch_storage_blob(&self, blob: &StorageBlobRecord) -> Result<bool> {
+        self.inner.touch_storage_blob(blob).await
+    }
+
+    async fn claim_unreferenced_blobs(
+        &self,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        self.inner.claim_unreferenced_blobs(query).await
+    }
+
+    async fn delete_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<bool> {
+        self.inner.delete_storage_blob(storage_key, claim_id).await
+    }
+
+    async fn release_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<()> {
+        self.inner.release_storage_blob(storage_key, claim_id).await
+    }
+
+    async fn rotate_pii_keys(&self, batch_size: u32) -> Result<PiiRotationSummary> {
+        let batch_size = batch_size.max(1);
+        let mut summary = PiiRotationSummary::default();
+        // 查找表放在最后，前面新生成的盲索引密文已是当前版本
+        for table in PiiTable::ALL {
+            let mut after: Option<String> = None;
+            loop {
+                let rows = self
+                    .inner
+                    .list_pii_rows(table, after.as_deref(), batch_size)
+                    .await?;
+                for row in &rows {
+                    summary.scanned += 1;
+                    match self.rotate_row(table, row).await {
+                        Ok(None) => {}
+                        Ok(Some(true)) => summary.updated += 1,
+                        Ok(Some(false)) => summary.skipped += 1,
+                        Err(err) => {
+                            summary.failures += 1;
+                            warn!(
+                                table = table.table_name(),
+                                key = %row.key,
+                                error = %err,
+                                "重新加密敏感字段失败"
+                            );
+                        }
+                    }
+                }
+                match rows.last() {
+                    Some(last) if rows.len() == batch_size as usize => {
+                        after = Some(last.key.clone())
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::util::crypto::{AesEncryption, EncryptionConfig};
+
+    fn key(version: &str) -> EncryptionConfig {
+        EncryptionConfig {
+            encryption_key: AesEncryption::generate_key(),
+            key_version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn sqlite(dir: &tempfile::TempDir) -> Arc<dyn Database> {
+        let sqlite = SqliteDatabase::new(dir.path().join("pii.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.initialize().await.unwrap();
+        MigrationRunner::new(&sqlite).migrate_up().await.unwrap();
+        Arc::new(sqlite)
+    }
+
+    fn record(id: &str, third_party_request_id: &str) -> PreviewRecord {
+        let now = Utc::now();
+        PreviewRecord {
+            id: id.to_string(),
+            user_id: "u1".to_string(),
+            user_info_json: Some(r#"{"certificateNumber":"330102199001011234"}"#.to_string()),
+            file_name: "a.pdf".to_string(),
+            ocr_text: "申请人 张三 330102199001011234".to_string(),
+            theme_id: None,
+            evaluation_result: Some(r#"{"applicant":"330102199001011234"}"#.to_string()),
+            preview_url: String::new(),
+            preview_view_url: None,
+            preview_download_url: None,
+            status: PreviewStatus::Completed,
+            created_at: now,
+            updated_at: now,
+            third_party_request_id: Some(third_party_request_id.to_string()),
+            queued_at: None,
+            processing_started_at: None,
+            retry_count: 0,
+            last_worker_id: None,
+            last_attempt_id: None,
+            failure_reason: None,
+            ocr_stderr_summary: None,
+            failure_context: None,
+            last_error_code: None,
+            slow_attachment_info_json: None,
+            callback_url: None,
+            callback_status: None,
+            callback_attempts: 0,
+            callback_successes: 0,
+            callback_failures: 0,
+            last_callback_at: None,
+            last_callback_status_code: None,
+            last_callback_response: None,
+            last_callback_error: None,
+            callback_payload: None,
+            next_callback_after: None,
+        }
+    }
+
+    async fn raw_record(inner: &Arc<dyn Database>) -> PiiRow {
+        inner
+            .list_pii_rows(PiiTable::PreviewRecords, None, 10)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn stores_ciphertext_and_finds_by_blind_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = sqlite(&dir).await;
+        let bidx = AesEncryption::generate_key();
+        let cipher = PiiCipher::new("v1", &[key("v1")], &bidx).unwrap();
+        let database = EncryptedDatabase::new(inner.clone(), Arc::new(cipher));
+
+        let original = record("p-1", "REQ-001");
+        database.save_preview_record(&original).await.unwrap();
+
+        let raw = raw_record(&inner).await;
+        assert!(PiiCipher::is_blind_index(raw.values[0].as_deref().unwrap()));
+        for value in &raw.values[1..] {
+            let value = value.as_deref().unwrap();
+            assert_eq!(PiiCipher::key_version_of(value), Some("v1"));
+            assert!(!value.contains("330102199001011234"));
+        }
+
+        let found = database
+            .find_preview_by_third_party_id("REQ-001", "u1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.third_party_request_id.as_deref(), Some("REQ-001"));
+        assert_eq!(found.ocr_text, original.ocr_text);
+        assert_eq!(found.user_info_json, original.user_info_json);
+
+        let listed = database
+            .list_preview_records(&PreviewFilter {
+                third_party_request_id: Some("REQ-001".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+
+        // 重放已加密的记录不应二次加密
+        let stored = inner.get_preview_record("p-1").await.unwrap().unwrap();
+        database.reconcile_preview_record(&stored).await.unwrap();
+        let again = database.get_preview_record("p-1").await.unwrap().unwrap();
+        assert_eq!(again.ocr_text, original.ocr_text);
+    }
+
+    #[tokio::test]
+    async fn rotation_backfills_plaintext_and_rewraps_old_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = sqlite(&dir).await;
+        let bidx = AesEncryption::generate_key();
+        let (v1, v2) = (key("v1"), key("v2"));
+
+        // 启用加密前写入的历史明文
+        inner
+            .save_preview_record(&record("p-legacy", "REQ-LEGACY"))
+            .await
+            .unwrap();
+
+        let old = PiiCipher::new("v1", &[v1.clone()], &bidx).unwrap();
+        let database = EncryptedDatabase::new(inner.clone(), Arc::new(old));
+        assert!(database
+            .find_preview_by_third_party_id("REQ-LEGACY", "u1")
+            .await
+            .unwrap()
+            .is_some());
+        let summary = database.rotate_pii_keys(1).await.unwrap();
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.failures, 0);
+        let raw = raw_record(&inner).await;
+        assert!(PiiCipher::is_blind_index(raw.values[0].as_deref().unwrap()));
+        assert_eq!(
+            PiiCipher::key_version_of(raw.values[2].as_deref().unwrap()),
+            Some("v1")
+        );
+
+        let new = PiiCipher::new("v2", &[v1, v2], &bidx).unwrap();
+        let database = EncryptedDatabase::new(inner.clone(), Arc::new(new));
+        let summary = database.rotate_pii_keys(1).await.unwrap();
+        // 记录行与查找表各一行
+        assert_eq!(summary.updated, 2);
+        assert_eq!(database.rotate_pii_keys(1).await.unwrap().updated, 0);
+        let raw = raw_record(&inner).await;
+        assert_eq!(
+            PiiCipher::key_version_of(raw.values[2].as_deref().unwrap()),
+            Some("v2")
+        );
+
+        let found = database
+            .find_preview_by_third_party_id("REQ-LEGACY", "u1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.third_party_request_id.as_deref(), Some("REQ-LEGACY"));
+        assert_eq!(found.ocr_text, "申请人 张三 330102199001011234");
+    }
+
+    #[tokio::test]
+    async fn queue_payloads_and_results_are_stored_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = sqlite(&dir).await;
+        let cipher = PiiCipher::new("v1", &[key("v1")], &AesEncryption::generate_key()).unwrap();
+        let database = EncryptedDatabase::new(inner.clone(), Arc::new(cipher));
+        let pii = "330102199001011234";
+        let payload = format!(r#"{{"certificateNumber":"{}"}}"#, pii);
+        let now = Utc::now();
+
+        let original = record("p-1", "REQ-001");
+        database.save_preview_record(&original).await.unwrap();
+        let saved = database.get_preview_record("p-1").await.unwrap().unwrap();
+        assert_eq!(saved.evaluation_result, original.evaluation_result);
+        database
+            .update_preview_evaluation_result("p-1", &payload)
+            .await
+            .unwrap();
+        database
+            .enqueue_worker_result("p-1", &payload)
+            .await
+            .unwrap();
+        database
+            .enqueue_material_download("p-1", &payload)
+            .await
+            .unwrap();
+        database
+            .replace_preview_material_results(
+                "p-1",
+                &[PreviewMaterialResultRecord {
+                    id: "m-1".to_string(),
+                    preview_id: "p-1".to_string(),
+                    material_code: "id_card".to_string(),
+                    material_name: None,
+                    status: "passed".to_string(),
+                    status_code: 0,
+                    processing_status: None,
+                    issues_count: 0,
+                    warnings_count: 0,
+                    attachments_json: Some(payload.clone()),
+                    summary_json: Some(payload.clone()),
+                    created_at: now,
+                    updated_at: now,
+                }],
+            )
+            .await
+            .unwrap();
+        database
+            .replace_preview_rule_results(
+                "p-1",
+                &[PreviewRuleResultRecord {
+                    id: "r-1".to_string(),
+                    preview_id: "p-1".to_string(),
+                    material_result_id: Some("m-1".to_string()),
+                    material_code: None,
+                    rule_id: None,
+                    rule_code: None,
+                    rule_name: None,
+                    engine: None,
+                    severity: None,
+                    status: None,
+                    message: Some(format!("证件号 {} 与申请人不一致", pii)),
+                    suggestions_json: None,
+                    evidence_json: Some(payload.clone()),
+                    extra_json: None,
+                    created_at: now,
+                    updated_at: now,
+                }],
+            )
+            .await
+            .unwrap();
+        database.ensure_task_queue_table("pii_tasks").await.unwrap();
+        database
+            .enqueue_queued_task(
+                "pii_tasks",
+                &NewQueuedTask {
+                    id: "t-1".to_string(),
+                    queue: "preview".to_string(),
+                    preview_id: "p-1".to_string(),
+                    payload: payload.clone(),
+                    visible_at: 0,
+                },
+            )
+            .await
+            .unwrap();
+
+        for table in PiiTable::ALL {
+            for row in inner.list_pii_rows(table, None, 10).await.unwrap() {
+                for value in row.values.iter().flatten() {
+                    assert!(!value.contains(pii), "{} 存有明文", table.table_name());
+                }
+            }
+        }
+        let pool = inner
+            .as_any()
+            .downcast_ref::<SqliteDatabase>()
+            .unwrap()
+            .pool();
+        let raw_task: String = sqlx::query_scalar("SELECT payload FROM pii_tasks")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert!(!raw_task.contains(pii));
+        let claim = TaskClaimRequest {
+            queue: "preview".to_string(),
+            consumer_id: "c-1".to_string(),
+            lease_token: "l-1".to_string(),
+            now: 1,
+            lease_expires_at: 60,
+            max_attempts: 3,
+            limit: 10,
+        };
+        let claimed = database
+            .claim_queued_tasks("pii_tasks", &claim)
+            .await
+            .unwrap();
+        assert_eq!(claimed[0].payload, payload);
+        assert_eq!(
+            database
+                .latest_material_download_payload("p-1")
+                .await
+                .unwrap(),
+            Some(payload.clone())
+        );
+        let pending = database.fetch_pending_worker_results(10).await.unwrap();
+        assert_eq!(pending[0].payload, payload);
+    }
+
+    fn request(id: &str, third_party_request_id: &str) -> PreviewRequestRecord {
+        let now = Utc::now();
+        PreviewRequestRecord {
+            id: id.to_string(),
+            third_party_request_id: Some(third_party_request_id.to_string()),
+            user_id: "u1".to_string(),
+            user_info_json: None,
+            matter_id: "m1".to_string(),
+            matter_type: String::new(),
+            matter_name: String::new(),
+            channel: String::new(),
+            sequence_no: String::new(),
+            agent_info_json: None,
+            subject_info_json: None,
+            form_data_json: None,
+            scene_data_json: None,
+            material_data_json: None,
+            latest_preview_id: None,
+            latest_status: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn search_finds_requests_by_exact_third_party_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = sqlite(&dir).await;
+        let bidx = AesEncryption::generate_key();
+        let cipher = PiiCipher::new("v1", &[key("v1")], &bidx).unwrap();
+        let database = EncryptedDatabase::new(inner.clone(), Arc::new(cipher));
+
+        database
+            .save_preview_request(&request("r-1", "REQ-001"))
+            .await
+            .unwrap();
+        database
+            .save_preview_request(&request("r-2", "REQ-002"))
+            .await
+            .unwrap();
+
+        let search = |text: &str| PreviewRequestFilter {
+            search: Some(text.to_string()),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let found = database
+            .list_preview_requests(&search("REQ-001"))
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "r-1");
+        assert_eq!(found[0].third_party_request_id.as_deref(), Some("REQ-001"));
+
+        // 请求 ID 仍按模糊匹配
+        let found = database.list_preview_requests(&search("r-")).await.unwrap();
+        assert_eq!(found.len(), 2);
+    }
+}
@@ -7,6 +7,8 @@ use tracing::{error, info, warn};
 
 #[cfg(feature = "dm_go")]
 use super::dm::DmDatabase;
+#[cfg(feature = "postgres")]
+use super::postgres::PostgresDatabase;
 use super::sqlite::SqliteDatabase;
 use super::traits::Database;
 
@@ -16,6 +18,8 @@ pub enum DatabaseType {
     Sqlite,
     #[cfg(feature = "dm_go")]
     Dm,
+    #[cfg(feature = "postgres")]
+    Postgres,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -26,6 +30,9 @@ pub struct DatabaseConfig {
     pub sqlite: Option<SqliteConfig>,
 
     pub dm: Option<DmConfig>,
+
+    #[serde(default)]
+    pub postgres: Option<PostgresConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -46,6 +53,33 @@ pub struct DmConfig {
     pub go_gateway: Option<GoGatewayConfig>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PostgresConfig {
+    pub host: String,
+    #[serde(default = "default_postgres_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub database: String,
+    #[serde(default = "default_postgres_max_connections")]
+    pub max_connections: u32,
+    #[serde(default = "default_postgres_connection_timeout")]
+    pub connection_timeout: u64,
+    /// 非空时作为连接的 search_path，便于多套环境共用一个库
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+fn default_postgres_port() -> u16 {
+    5432
+}
+fn default_postgres_max_connections() -> u32 {
+    10
+}
+fn default_postgres_connection_timeout() -> u64 {
+    30
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GoGatewayConfig {
     #[serde(default = "default_go_gateway_enabled")]
@@ -357,6 +391,23 @@ pub async fn create_database(config: &DatabaseConfig) -> Result<Box<dyn Database
             Ok(Box::new(db))
         }
 
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => {
+            let pg_config = config
+                .postgres
+                .as_ref()
+                .ok_or_else(|| anyhow!("PostgreSQL数据库配置缺失"))?;
+
+            info!(
+                "[card] 连接PostgreSQL数据库: {}:{}/{}",
+                pg_config.host, pg_config.port, pg_config.database
+            );
+            let db = PostgresDatabase::new(pg_config).await?;
+            db.initialize().await?;
+            info!("[ok] PostgreSQL数据库连接和初始化成功");
+            Ok(Box::new(db))
+        }
+
         DatabaseType::Sqlite => {
             let sqlite_config = config
                 .sqlite
@@ -399,6 +450,7 @@ mod tests {
                 path: ":memory:".to_string(),
             }),
             dm: None,
+            postgres: None,
         };
 
         let db = create_database(&config).await.unwrap();
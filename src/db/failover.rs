@@ -1,13 +1,18 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
 use tracing::{error, info, warn};
 
 use super::factory;
+use super::migrations::{Migration, MigrationDialect, MigrationRunner};
+use super::reconcile::{
+    FallbackJournal, JournalCounts, JournalEntity, JournalEntry, JournalStatus,
+};
+use super::sqlite::SqliteDatabase;
 use super::traits::*;
 use crate::util::config::DatabaseFailoverConfig;
 
@@ -26,6 +31,11 @@ pub struct FailoverDatabase {
     last_health_check: Arc<RwLock<DateTime<Utc>>>,
     health_check_lock: Arc<Mutex<()>>,
     state_transition_counter: Arc<AtomicU32>,
+    /// 降级期间的写入日志，主库恢复后据此回放；降级库不是 SQLite 时为空
+    journal: Option<Arc<FallbackJournal>>,
+    replaying: Arc<AtomicBool>,
+    /// 启动时主库不可用而推迟的迁移，恢复后在回放前补跑
+    migrations_deferred: Arc<AtomicBool>,
 }
 
 impl FailoverDatabase {
@@ -36,6 +46,7 @@ impl FailoverDatabase {
                 path: format!("{}/fallback.db", config.local_data_dir),
             }),
             dm: None,
+            postgres: None,
         };
 
         std::fs::create_dir_all(&config.local_data_dir)
@@ -44,6 +55,15 @@ impl FailoverDatabase {
         let fallback = factory::create_database(&fallback_config).await?;
 
         fallback.initialize().await?;
+        MigrationRunner::new(fallback.as_ref()).migrate_up().await?;
+
+        let journal = fallback
+            .as_any()
+            .downcast_ref::<SqliteDatabase>()
+            .map(|db| Arc::new(FallbackJournal::new(db.pool().clone())));
+        if journal.is_none() {
+            warn!("[warn] 降级库不支持写入日志，主库恢复后不会回放降级期间的写入");
+        }
 
         Ok(Self {
             primary,
@@ -53,9 +73,58 @@ impl FailoverDatabase {
             last_health_check: Arc::new(RwLock::new(Utc::now())),
             health_check_lock: Arc::new(Mutex::new(())),
             state_transition_counter: Arc::new(AtomicU32::new(0)),
+            journal,
+            replaying: Arc::new(AtomicBool::new(false)),
+            migrations_deferred: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// 启动时迁移主库；主库不可用且允许降级时改用降级库启动（降级库在创建时已迁移），
+    /// 主库恢复后先补跑迁移再回放写入日志。主库可用但迁移失败时仍拒绝启动
+    pub async fn migrate_on_startup(&self) -> Result<()> {
+        let err = match MigrationRunner::new(self.primary.as_ref())
+            .run_on_startup()
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        if !self.config.fallback_to_local || matches!(self.primary.health_check().await, Ok(true)) {
+            return Err(err);
+        }
+        warn!(
+            "[warn] 主数据库不可用，推迟表结构迁移并使用降级库启动: {:#}",
+            err
+        );
+        self.migrations_deferred.store(true, Ordering::SeqCst);
+        self.try_transition_state(FailoverState::Primary, FailoverState::Fallback)
+            .await;
+        Ok(())
+    }
+
+    pub async fn state_name(&self) -> &'static str {
+        match *self.state.read().await {
+            FailoverState::Primary => "primary",
+            FailoverState::Fallback => "fallback",
+            FailoverState::Recovering => "recovering",
+        }
+    }
+
+    pub async fn journal_counts(&self) -> Result<Option<JournalCounts>> {
+        match &self.journal {
+            Some(journal) => Ok(Some(journal.counts().await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 回放冲突的条目，最近的在前
+    pub async fn journal_conflicts(&self, limit: u32) -> Result<Vec<JournalEntry>> {
+        match &self.journal {
+            Some(journal) => journal.list(JournalStatus::Conflict, limit).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
     async fn try_transition_state(&self, from: FailoverState, to: FailoverState) -> bool {
         let mut state = self.state.write().await;
         if *state == from {
@@ -119,13 +188,7 @@ impl FailoverDatabase {
 
             match operation(db.clone()).await {
                 Ok(result) => {
-                    if state == FailoverState::Recovering {
-                        self.try_transition_state(
-                            FailoverState::Recovering,
-                            FailoverState::Primary,
-                        )
-                        .await;
-                    }
+                    // Recovering 期间仍写降级库，回放完成后由健康检查切回主库
                     self.state_transition_counter.store(0, Ordering::Relaxed);
                     return Ok(result);
                 }
@@ -224,29 +287,151 @@ impl FailoverDatabase {
                 .await
             {
                 let primary = self.primary.clone();
+                let fallback = self.fallback.clone();
+                let journal = self.journal.clone();
+                let replaying = self.replaying.clone();
+                let migrations_deferred = self.migrations_deferred.clone();
                 let state_clone = self.state.clone();
                 let state_transition_counter = self.state_transition_counter.clone();
 
                 tokio::spawn(async move {
-                    match primary.health_check().await {
+                    let recovered = match primary.health_check().await {
                         Ok(true) => {
                             info!("[ok] 主数据库健康检查通过");
+                            // 回放的写入可能用到新列，推迟的迁移必须先完成
+                            if !migrate_deferred(&migrations_deferred, &primary).await {
+                                false
+                            } else {
+                                match journal {
+                                    Some(journal) => {
+                                        replay_journal(&journal, &replaying, &primary, &fallback)
+                                            .await
+                                    }
+                                    None => true,
+                                }
+                            }
                         }
                         Ok(false) | Err(_) => {
                             warn!("[warn] 主数据库仍不健康，保持fallback模式");
-                            let mut state = state_clone.write().await;
-                            *state = FailoverState::Fallback;
-                            state_transition_counter.store(0, Ordering::Relaxed);
+                            false
                         }
+                    };
+
+                    let mut state = state_clone.write().await;
+                    if *state == FailoverState::Recovering {
+                        *state = if recovered {
+                            info!("[ok] 降级期间的写入已回放完毕，切回主数据库");
+                            FailoverState::Primary
+                        } else {
+                            FailoverState::Fallback
+                        };
                     }
+                    state_transition_counter.store(0, Ordering::Relaxed);
                 });
             } else {
                 warn!("[warn] 状态切换失败，跳过健康检查");
             }
+        } else if state == FailoverState::Primary {
+            // 切回主库前后仍在执行的降级写入会留下少量日志，在主库模式下补回放
+            if let Some(journal) = self.journal.clone() {
+                if matches!(journal.counts().await, Ok(counts) if counts.open() > 0) {
+                    let replaying = self.replaying.clone();
+                    let primary = self.primary.clone();
+                    let fallback = self.fallback.clone();
+                    tokio::spawn(async move {
+                        replay_journal(&journal, &replaying, &primary, &fallback).await;
+                    });
+                }
+            }
+        }
+    }
+
+    /// 写操作落到降级库时记入写入日志；`entry` 仅在需要时求值
+    async fn execute_journaled<F, T, E>(&self, entry: E, operation: F) -> Result<T>
+    where
+        F: Fn(
+            Arc<dyn Database>,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>>,
+        E: FnOnce() -> (JournalEntity, String, Option<String>),
+    {
+        let fallback_addr = Arc::as_ptr(&self.fallback) as *const () as usize;
+        let served_by_fallback = AtomicBool::new(false);
+        let result = self
+            .execute_with_failover(|db| {
+                if Arc::as_ptr(&db) as *const () as usize == fallback_addr {
+                    served_by_fallback.store(true, Ordering::Relaxed);
+                }
+                operation(db)
+            })
+            .await?;
+
+        if served_by_fallback.load(Ordering::Relaxed) {
+            if let Some(journal) = &self.journal {
+                let (entity, key, payload) = entry();
+                if let Err(e) = journal.record(entity, &key, payload.as_deref()).await {
+                    error!(
+                        "[fail] 记录降级写入日志失败: {} {}: {}",
+                        entity.as_str(),
+                        key,
+                        e
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// 补跑启动时推迟的主库迁移，返回主库表结构是否已就绪
+async fn migrate_deferred(deferred: &AtomicBool, primary: &Arc<dyn Database>) -> bool {
+    if !deferred.load(Ordering::SeqCst) {
+        return true;
+    }
+    match MigrationRunner::new(primary.as_ref())
+        .run_on_startup()
+        .await
+    {
+        Ok(()) => {
+            info!("[ok] 推迟的主数据库表结构迁移已完成");
+            deferred.store(false, Ordering::SeqCst);
+            true
+        }
+        Err(err) => {
+            error!("[fail] 主数据库表结构迁移失败，保持降级模式: {:#}", err);
+            false
         }
     }
 }
 
+/// 回放降级写入日志，返回是否已全部处理；同一时间只允许一个回放任务
+async fn replay_journal(
+    journal: &FallbackJournal,
+    replaying: &AtomicBool,
+    primary: &Arc<dyn Database>,
+    fallback: &Arc<dyn Database>,
+) -> bool {
+    if replaying.swap(true, Ordering::AcqRel) {
+        return false;
+    }
+    let drained = match journal.drain(primary, fallback).await {
+        Ok(report) => {
+            if report.conflicts > 0 {
+                warn!(
+                    "[warn] 降级写入回放存在 {} 条冲突，已保留主库数据",
+                    report.conflicts
+                );
+            }
+            report.drained
+        }
+        Err(e) => {
+            warn!("[warn] 降级写入回放失败: {}", e);
+            false
+        }
+    };
+    replaying.store(false, Ordering::Release);
+    drained
+}
+
 #[async_trait]
 impl Database for FailoverDatabase {
     fn as_any(&self) -> &dyn std::any::Any {
@@ -254,10 +439,13 @@ impl Database for FailoverDatabase {
     }
 
     async fn save_preview_request(&self, request: &PreviewRequestRecord) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let request = request.clone();
-            Box::pin(async move { db.save_preview_request(&request).await })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRequest, request.id.clone(), None),
+            |db| {
+                let request = request.clone();
+                Box::pin(async move { db.save_preview_request(&request).await })
+            },
+        )
         .await
     }
 
@@ -289,19 +477,22 @@ impl Database for FailoverDatabase {
         latest_preview_id: Option<&str>,
         latest_status: Option<PreviewStatus>,
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let request_id = request_id.to_string();
-            let latest_preview_id = latest_preview_id.map(|s| s.to_string());
-            let latest_status = latest_status.clone();
-            Box::pin(async move {
-                db.update_preview_request_latest(
-                    &request_id,
-                    latest_preview_id.as_deref(),
-                    latest_status,
-                )
-                .await
-            })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRequest, request_id.to_string(), None),
+            |db| {
+                let request_id = request_id.to_string();
+                let latest_preview_id = latest_preview_id.map(|s| s.to_string());
+                let latest_status = latest_status.clone();
+                Box::pin(async move {
+                    db.update_preview_request_latest(
+                        &request_id,
+                        latest_preview_id.as_deref(),
+                        latest_status,
+                    )
+                    .await
+                })
+            },
+        )
         .await
     }
 
@@ -317,10 +508,13 @@ impl Database for FailoverDatabase {
     }
 
     async fn save_preview_record(&self, record: &PreviewRecord) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let record = record.clone();
-            Box::pin(async move { db.save_preview_record(&record).await })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRecord, record.id.clone(), None),
+            |db| {
+                let record = record.clone();
+                Box::pin(async move { db.save_preview_record(&record).await })
+            },
+        )
         .await
     }
 
@@ -333,11 +527,14 @@ impl Database for FailoverDatabase {
     }
 
     async fn update_preview_status(&self, id: &str, status: PreviewStatus) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let id = id.to_string();
-            let status = status.clone();
-            Box::pin(async move { db.update_preview_status(&id, status).await })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRecord, id.to_string(), None),
+            |db| {
+                let id = id.to_string();
+                let status = status.clone();
+                Box::pin(async move { db.update_preview_status(&id, status).await })
+            },
+        )
         .await
     }
 
@@ -346,14 +543,17 @@ impl Database for FailoverDatabase {
         id: &str,
         evaluation_result: &str,
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let id = id.to_string();
-            let evaluation_result = evaluation_result.to_string();
-            Box::pin(async move {
-                db.update_preview_evaluation_result(&id, &evaluation_result)
-                    .await
-            })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRecord, id.to_string(), None),
+            |db| {
+                let id = id.to_string();
+                let evaluation_result = evaluation_result.to_string();
+                Box::pin(async move {
+                    db.update_preview_evaluation_result(&id, &evaluation_result)
+                        .await
+                })
+            },
+        )
         .await
     }
 
@@ -363,15 +563,18 @@ impl Database for FailoverDatabase {
         worker_id: &str,
         attempt_id: &str,
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let id = id.to_string();
-            let worker_id = worker_id.to_string();
-            let attempt_id = attempt_id.to_string();
-            Box::pin(async move {
-                db.mark_preview_processing(&id, &worker_id, &attempt_id)
-                    .await
-            })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRecord, id.to_string(), None),
+            |db| {
+                let id = id.to_string();
+                let worker_id = worker_id.to_string();
+                let attempt_id = attempt_id.to_string();
+                Box::pin(async move {
+                    db.mark_preview_processing(&id, &worker_id, &attempt_id)
+                        .await
+                })
+            },
+        )
         .await
     }
 
@@ -383,23 +586,26 @@ impl Database for FailoverDatabase {
         preview_view_url: Option<&str>,
         preview_download_url: Option<&str>,
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let id = id.to_string();
-            let file_name = file_name.to_string();
-            let preview_url = preview_url.to_string();
-            let preview_view_url = preview_view_url.map(|s| s.to_string());
-            let preview_download_url = preview_download_url.map(|s| s.to_string());
-            Box::pin(async move {
-                db.update_preview_artifacts(
-                    &id,
-                    &file_name,
-                    &preview_url,
-                    preview_view_url.as_deref(),
-                    preview_download_url.as_deref(),
-                )
-                .await
-            })
-        })
+        self.execute_journaled(
+            || (JournalEntity::PreviewRecord, id.to_string(), None),
+            |db| {
+                let id = id.to_string();
+                let file_name = file_name.to_string();
+                let preview_url = preview_url.to_string();
+                let preview_view_url = preview_view_url.map(|s| s.to_string());
+                let preview_download_url = preview_download_url.map(|s| s.to_string());
+                Box::pin(async move {
+                    db.update_preview_artifacts(
+                        &id,
+                        &file_name,
+                        &preview_url,
+                        preview_view_url.as_deref(),
+                        preview_download_url.as_deref(),
+                    )
+                    .await
+                })
+            },
+        )
         .await
     }
 
@@ -408,14 +614,25 @@ impl Database for FailoverDatabase {
         preview_id: &str,
         records: &[PreviewMaterialResultRecord],
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let preview_id = preview_id.to_string();
-            let records = records.to_vec();
-            Box::pin(async move {
-                db.replace_preview_material_results(&preview_id, &records)
-                    .await
-            })
-        })
+        // 先序列化：载荷缺失的日志无法回放，宁可不写
+        let payload = serde_json::to_string(records)?;
+        self.execute_journaled(
+            || {
+                (
+                    JournalEntity::MaterialResults,
+                    preview_id.to_string(),
+                    Some(payload),
+                )
+            },
+            |db| {
+                let preview_id = preview_id.to_string();
+                let records = records.to_vec();
+                Box::pin(async move {
+                    db.replace_preview_material_results(&preview_id, &records)
+                        .await
+                })
+            },
+        )
         .await
     }
 
@@ -424,11 +641,24 @@ impl Database for FailoverDatabase {
         preview_id: &str,
         records: &[PreviewRuleResultRecord],
     ) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let preview_id = preview_id.to_string();
-            let records = records.to_vec();
-            Box::pin(async move { db.replace_preview_rule_results(&preview_id, &records).await })
-        })
+        // 先序列化：载荷缺失的日志无法回放，宁可不写
+        let payload = serde_json::to_string(records)?;
+        self.execute_journaled(
+            || {
+                (
+                    JournalEntity::RuleResults,
+                    preview_id.to_string(),
+                    Some(payload),
+                )
+            },
+            |db| {
+                let preview_id = preview_id.to_string();
+                let records = records.to_vec();
+                Box::pin(
+                    async move { db.replace_preview_rule_results(&preview_id, &records).await },
+                )
+            },
+        )
         .await
     }
 
@@ -661,10 +891,19 @@ impl Database for FailoverDatabase {
     }
 
     async fn update_preview_callback_state(&self, update: &PreviewCallbackUpdate) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let update = update.clone();
-            Box::pin(async move { db.update_preview_callback_state(&update).await })
-        })
+        self.execute_journaled(
+            || {
+                (
+                    JournalEntity::PreviewRecord,
+                    update.preview_id.clone(),
+                    None,
+                )
+            },
+            |db| {
+                let update = update.clone();
+                Box::pin(async move { db.update_preview_callback_state(&update).await })
+            },
+        )
         .await
     }
 
@@ -674,18 +913,36 @@ impl Database for FailoverDatabase {
     }
 
     async fn update_preview_failure_context(&self, update: &PreviewFailureUpdate) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let update = update.clone();
-            Box::pin(async move { db.update_preview_failure_context(&update).await })
-        })
+        self.execute_journaled(
+            || {
+                (
+                    JournalEntity::PreviewRecord,
+                    update.preview_id.clone(),
+                    None,
+                )
+            },
+            |db| {
+                let update = update.clone();
+                Box::pin(async move { db.update_preview_failure_context(&update).await })
+            },
+        )
         .await
     }
 
     async fn enqueue_outbox_event(&self, event: &NewOutboxEvent) -> Result<()> {
-        self.execute_with_failover(|db| {
-            let event = event.clone();
-            Box::pin(async move { db.enqueue_outbox_event(&event).await })
-        })
+        self.execute_journaled(
+            || {
+                (
+                    JournalEntity::OutboxEvent,
+                    event.idempotency_key.clone(),
+                    None,
+                )
+            },
+            |db| {
+                let event = event.clone();
+                Box::pin(async move { db.enqueue_outbox_event(&event).await })
+            },
+        )
         .await
     }
 
@@ -1021,6 +1278,243 @@ impl Database for FailoverDatabase {
         .await
     }
 
+    async fn cancel_material_downloads(&self, preview_id: &str) -> Result<u64> {
+        self.execute_with_failover(|db| {
+            let preview_id = preview_id.to_string();
+            Box::pin(async move { db.cancel_material_downloads(&preview_id).await })
+        })
+        .await
+    }
+
+    async fn latest_material_download_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        self.execute_with_failover(|db| {
+            let preview_id = preview_id.to_string();
+            Box::pin(async move { db.latest_material_download_payload(&preview_id).await })
+        })
+        .await
+    }
+
+    // Database task queue methods
+    async fn ensure_task_queue_table(&self, table: &str) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            Box::pin(async move { db.ensure_task_queue_table(&table).await })
+        })
+        .await
+    }
+
+    async fn enqueue_queued_task(&self, table: &str, task: &NewQueuedTask) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task = task.clone();
+            Box::pin(async move { db.enqueue_queued_task(&table, &task).await })
+        })
+        .await
+    }
+
+    async fn claim_queued_tasks(
+        &self,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let claim = claim.clone();
+            Box::pin(async move { db.claim_queued_tasks(&table, &claim).await })
+        })
+        .await
+    }
+
+    async fn extend_queued_task_lease(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task_id = task_id.to_string();
+            let lease_token = lease_token.to_string();
+            Box::pin(async move {
+                db.extend_queued_task_lease(&table, &task_id, &lease_token, lease_expires_at)
+                    .await
+            })
+        })
+        .await
+    }
+
+    async fn complete_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task_id = task_id.to_string();
+            let lease_token = lease_token.to_string();
+            Box::pin(async move {
+                db.complete_queued_task(&table, &task_id, &lease_token)
+                    .await
+            })
+        })
+        .await
+    }
+
+    async fn release_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task_id = task_id.to_string();
+            let lease_token = lease_token.to_string();
+            let error = error.to_string();
+            Box::pin(async move {
+                db.release_queued_task(&table, &task_id, &lease_token, &error, retry_at)
+                    .await
+            })
+        })
+        .await
+    }
+
+    async fn return_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task_id = task_id.to_string();
+            let lease_token = lease_token.to_string();
+            Box::pin(async move { db.return_queued_task(&table, &task_id, &lease_token).await })
+        })
+        .await
+    }
+
+    async fn queued_task_stats(&self, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let queue = queue.to_string();
+            Box::pin(async move { db.queued_task_stats(&table, &queue).await })
+        })
+        .await
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let queue = queue.to_string();
+            Box::pin(async move { db.list_failed_queued_tasks(&table, &queue, limit).await })
+        })
+        .await
+    }
+
+    async fn delete_queued_task(&self, table: &str, task_id: &str) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let task_id = task_id.to_string();
+            Box::pin(async move { db.delete_queued_task(&table, &task_id).await })
+        })
+        .await
+    }
+
+    async fn cancel_queued_tasks(&self, table: &str, queue: &str, preview_id: &str) -> Result<u64> {
+        self.execute_with_failover(|db| {
+            let table = table.to_string();
+            let queue = queue.to_string();
+            let preview_id = preview_id.to_string();
+            Box::pin(async move { db.cancel_queued_tasks(&table, &queue, &preview_id).await })
+        })
+        .await
+    }
+
+    async fn save_dead_letter(&self, record: &DeadLetterRecord) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let record = record.clone();
+            Box::pin(async move { db.save_dead_letter(&record).await })
+        })
+        .await
+    }
+
+    async fn list_dead_letters(&self, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        self.execute_with_failover(|db| {
+            let filter = filter.clone();
+            Box::pin(async move { db.list_dead_letters(&filter).await })
+        })
+        .await
+    }
+
+    async fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetterRecord>> {
+        self.execute_with_failover(|db| {
+            let id = id.to_string();
+            Box::pin(async move { db.get_dead_letter(&id).await })
+        })
+        .await
+    }
+
+    async fn resolve_dead_letter(
+        &self,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        self.execute_with_failover(|db| {
+            let id = id.to_string();
+            let payload = payload.map(|p| p.to_string());
+            let resolved_by = resolved_by.to_string();
+            Box::pin(async move {
+                db.resolve_dead_letter(&id, status, payload.as_deref(), &resolved_by)
+                    .await
+            })
+        })
+        .await
+    }
+
+    async fn save_worker_control(&self, record: &WorkerControlRecord) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let record = record.clone();
+            Box::pin(async move { db.save_worker_control(&record).await })
+        })
+        .await
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        self.execute_with_failover(|db| Box::pin(async move { db.list_worker_controls().await }))
+            .await
+    }
+
+    async fn save_preview_batch_items(&self, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let items = items.to_vec();
+            Box::pin(async move { db.save_preview_batch_items(&items).await })
+        })
+        .await
+    }
+
+    async fn list_preview_batch_items(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        self.execute_with_failover(|db| {
+            let batch_id = batch_id.to_string();
+            Box::pin(async move { db.list_preview_batch_items(&batch_id).await })
+        })
+        .await
+    }
+
     async fn get_download_cache_token(
         &self,
         url: &str,
@@ -1075,4 +1569,103 @@ impl Database for FailoverDatabase {
         })
         .await
     }
+    // 迁移只作用于主库，降级库在创建时已自行迁移
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        self.primary.migration_dialect()
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        self.primary.list_applied_migrations().await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        self.primary.apply_migration(migration).await
+    }
+
+    // 留存清理与法律保全只作用于主库：降级期间写入的数据在恢复后回放到主库，
+    // 在降级库上清理或保全会在回放时丢失；主库不可用时本轮清理直接失败
+    async fn list_retention_candidates(
+        &self,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        self.primary.list_retention_candidates(query).await
+    }
+
+    async fn purge_preview_artifact(&self, purge: &ArtifactPurge) -> Result<Option<i64>> {
+        self.primary.purge_preview_artifact(purge).await
+    }
+
+    async fn purge_log_records(&self, before: DateTime<Utc>, purge: &ArtifactPurge) -> Result<i64> {
+        self.primary.purge_log_records(before, purge).await
+    }
+
+    async fn set_preview_legal_hold(&self, update: &LegalHoldUpdate) -> Result<bool> {
+        self.primary.set_preview_legal_hold(update).await
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        self.primary.list_legal_holds().await
+    }
+
+    async fn list_purge_audit(&self, filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        self.primary.list_purge_audit(filter).await
+    }
+
+    // 盲索引密文随预审记录写入当前可用的库；轮换扫描只针对主库
+    async fn save_pii_lookup_value(&self, token: &str, ciphertext: &str) -> Result<()> {
+        self.execute_with_failover(|db| {
+            let token = token.to_string();
+            let ciphertext = ciphertext.to_string();
+            Box::pin(async move { db.save_pii_lookup_value(&token, &ciphertext).await })
+        })
+        .await
+    }
+
+    async fn get_pii_lookup_values(
+        &self,
+        tokens: &[String],
+    ) -> Result<std::collections::HashMap<String, String>> {
+        self.execute_with_failover(|db| {
+            let tokens = tokens.to_vec();
+            Box::pin(async move { db.get_pii_lookup_values(&tokens).await })
+        })
+        .await
+    }
+
+    async fn list_pii_rows(
+        &self,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        self.primary.list_pii_rows(table, after, limit).await
+    }
+
+    async fn update_pii_row(&self, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        self.primary.update_pii_row(table, update).await
+    }
+
+    // 备库上的材料记录回收任务看不到，blob 只登记在主库；主库不可用时调用方改用按预审存放
+    async fn touch_storage_blob(&self, blob: &StorageBlobRecord) -> Result<bool> {
+        self.primary.touch_storage_blob(blob).await
+    }
+
+    async fn claim_unreferenced_blobs(
+        &self,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        self.primary.claim_unreferenced_blobs(query).await
+    }
+
+    async fn delete_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<bool> {
+        self.primary
+            .delete_storage_blob(storage_key, claim_id)
+            .await
+    }
+
+    async fn release_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<()> {
+        self.primary
+            .release_storage_blob(storage_key, claim_id)
+            .await
+    }
 }
@@ -0,0 +1,639 @@
+//! 版本化表结构迁移
+//!
+//! 迁移按版本号顺序登记在 [`MIGRATIONS`]，同一版本在各后端语义一致、各自提供方言 SQL。
+//! 执行记录写入 `schema_migrations`（版本、名称、校验和、执行时间），
+//! 已执行迁移的 SQL 被改动时校验和不一致，`migrate verify` 会报告出来。
+//!
+//! 版本 1 是基线：对应各后端 `initialize()` 以 `CREATE TABLE IF NOT EXISTS`
+//! 与补列语句建立的表结构，之后的结构变更一律追加新版本，不再修改基线。
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use super::traits::{AppliedMigration, Database};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationDialect {
+    Sqlite,
+    Postgres,
+    Dm,
+}
+
+impl MigrationDialect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+            Self::Dm => "dm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sqlite: &'static [&'static str],
+    pub postgres: &'static [&'static str],
+    pub dm: &'static [&'static str],
+}
+
+impl Migration {
+    pub fn statements(&self, dialect: MigrationDialect) -> &'static [&'static str] {
+        match dialect {
+            MigrationDialect::Sqlite => self.sqlite,
+            MigrationDialect::Postgres => self.postgres,
+            MigrationDialect::Dm => self.dm,
+        }
+    }
+
+    /// 版本、名称与该方言语句的 SHA-256，语句首尾空白不参与计算
+    pub fn checksum(&self, dialect: MigrationDialect) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.version.to_string().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.name.as_bytes());
+        for statement in self.statements(dialect) {
+            hasher.update(b"\n");
+            hasher.update(statement.trim().as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// 迁移语句新建的对象；后端据此跳过已存在的对象，中途失败的迁移可以直接重跑
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaObject<'a> {
+    Column { table: &'a str, column: &'a str },
+    Table(&'a str),
+    Index(&'a str),
+}
+
+impl<'a> SchemaObject<'a> {
+    /// 识别 `ALTER TABLE .. ADD [COLUMN]`、`CREATE TABLE`、`CREATE [UNIQUE] INDEX`，
+    /// 其他语句返回 `None`
+    pub fn created_by(statement: &'a str) -> Option<Self> {
+        let mut words = statement
+            .split(|c: char| c.is_whitespace() || c == '(')
+            .filter(|w| !w.is_empty());
+        let mut next = || words.next();
+        let is = |word: Option<&str>, expected: &str| {
+            word.is_some_and(|w| w.eq_ignore_ascii_case(expected))
+        };
+        let first = next();
+        if is(first, "ALTER") {
+            if !is(next(), "TABLE") {
+                return None;
+            }
+            let table = next()?;
+            if !is(next(), "ADD") {
+                return None;
+            }
+            let column = match next()? {
+                word if word.eq_ignore_ascii_case("COLUMN") => next()?,
+                word => word,
+            };
+            return Some(Self::Column { table, column });
+        }
+        if !is(first, "CREATE") {
+            return None;
+        }
+        let mut kind = next();
+        if is(kind, "UNIQUE") {
+            kind = next();
+        }
+        let table = is(kind, "TABLE");
+        if !table && !is(kind, "INDEX") {
+            return None;
+        }
+        let mut name = next()?;
+        if name.eq_ignore_ascii_case("IF") {
+            next();
+            next();
+            name = next()?;
+        }
+        Some(if table {
+            Self::Table(name)
+        } else {
+            Self::Index(name)
+        })
+    }
+}
+
+/// 已登记的迁移，版本号严格递增
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        sqlite: &[],
+        postgres: &[],
+        dm: &[],
+    },
+    Migration {
+        version: 2,
+        name: "retention_legal_hold_and_purge_log",
+        sqlite: &[
+            "ALTER TABLE preview_records ADD COLUMN legal_hold INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE preview_records ADD COLUMN legal_hold_reason TEXT",
+            "ALTER TABLE preview_records ADD COLUMN legal_hold_by TEXT",
+            "ALTER TABLE preview_records ADD COLUMN legal_hold_at TEXT",
+            "ALTER TABLE preview_records ADD COLUMN purged_artifacts TEXT",
+            "CREATE INDEX IF NOT EXISTS idx_preview_requests_latest_preview \
+             ON preview_requests(latest_preview_id)",
+            r#"CREATE TABLE IF NOT EXISTS retention_purge_log (
+                id TEXT PRIMARY KEY,
+                preview_id TEXT,
+                matter_id TEXT,
+                artifact TEXT NOT NULL,
+                storage_keys TEXT NOT NULL,
+                db_rows INTEGER NOT NULL DEFAULT 0,
+                reason TEXT NOT NULL,
+                purged_at TEXT NOT NULL
+            )"#,
+            "CREATE INDEX IF NOT EXISTS idx_retention_purge_log_preview \
+             ON retention_purge_log(preview_id)",
+            "CREATE INDEX IF NOT EXISTS idx_retention_purge_log_purged_at \
+             ON retention_purge_log(purged_at)",
+        ],
+        postgres: &[
+            "ALTER TABLE preview_records ADD COLUMN IF NOT EXISTS legal_hold BOOLEAN NOT NULL \
+             DEFAULT FALSE",
+            "ALTER TABLE preview_records ADD COLUMN IF NOT EXISTS legal_hold_reason TEXT",
+            "ALTER TABLE preview_records ADD COLUMN IF NOT EXISTS legal_hold_by TEXT",
+            "ALTER TABLE preview_records ADD COLUMN IF NOT EXISTS legal_hold_at TIMESTAMPTZ",
+            "ALTER TABLE preview_records ADD COLUMN IF NOT EXISTS purged_artifacts TEXT",
+            "CREATE INDEX IF NOT EXISTS idx_preview_requests_latest_preview \
+             ON preview_requests(latest_preview_id)",
+            r#"CREATE TABLE IF NOT EXISTS retention_purge_log (
+                id TEXT PRIMARY KEY,
+                preview_id TEXT,
+                matter_id TEXT,
+                artifact TEXT NOT NULL,
+                storage_keys TEXT NOT NULL,
+                db_rows BIGINT NOT NULL DEFAULT 0,
+                reason TEXT NOT NULL,
+                purged_at TIMESTAMPTZ NOT NULL
+            )"#,
+            "CREATE INDEX IF NOT EXISTS idx_retention_purge_log_preview \
+             ON retention_purge_log(preview_id)",
+            "CREATE INDEX IF NOT EXISTS idx_retention_purge_log_purged_at \
+             ON retention_purge_log(purged_at)",
+        ],
+        dm: &[
+            "ALTER TABLE PREVIEW_RECORDS ADD LEGAL_HOLD INT DEFAULT 0 NOT NULL",
+            "ALTER TABLE PREVIEW_RECORDS ADD LEGAL_HOLD_REASON VARCHAR(1000)",
+            "ALTER TABLE PREVIEW_RECORDS ADD LEGAL_HOLD_BY VARCHAR(100)",
+            "ALTER TABLE PREVIEW_RECORDS ADD LEGAL_HOLD_AT TIMESTAMP",
+            "ALTER TABLE PREVIEW_RECORDS ADD PURGED_ARTIFACTS VARCHAR(200)",
+            "CREATE INDEX IDX_PREVIEW_REQUESTS_LATEST ON PREVIEW_REQUESTS(LATEST_PREVIEW_ID)",
+            r#"CREATE TABLE RETENTION_PURGE_LOG (
+                ID VARCHAR(100) PRIMARY KEY,
+                PREVIEW_ID VARCHAR(100),
+                MATTER_ID VARCHAR(200),
+                ARTIFACT VARCHAR(50) NOT NULL,
+                STORAGE_KEYS CLOB,
+                DB_ROWS BIGINT DEFAULT 0,
+                REASON VARCHAR(200) NOT NULL,
+                PURGED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )"#,
+            "CREATE INDEX IDX_PURGE_LOG_PREVIEW ON RETENTION_PURGE_LOG(PREVIEW_ID)",
+            "CREATE INDEX IDX_PURGE_LOG_PURGED_AT ON RETENTION_PURGE_LOG(PURGED_AT)",
+        ],
+    },
+    Migration {
+        version: 3,
+        name: "pii_lookup_values",
+        sqlite: &[r#"CREATE TABLE IF NOT EXISTS pii_lookup_values (
+                token TEXT PRIMARY KEY,
+                ciphertext TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )"#],
+        postgres: &[r#"CREATE TABLE IF NOT EXISTS pii_lookup_values (
+                token TEXT PRIMARY KEY,
+                ciphertext TEXT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )"#],
+        dm: &[
+            r#"CREATE TABLE PII_LOOKUP_VALUES (
+                TOKEN VARCHAR(100) PRIMARY KEY,
+                CIPHERTEXT VARCHAR(2000) NOT NULL,
+                UPDATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )"#,
+            // 密文远长于原值
+            "ALTER TABLE USER_LOGIN_RECORDS MODIFY USER_NAME VARCHAR(1000)",
+            "ALTER TABLE USER_LOGIN_RECORDS MODIFY CERTIFICATE_NUMBER VARCHAR(1000)",
+            "ALTER TABLE USER_LOGIN_RECORDS MODIFY PHONE_NUMBER VARCHAR(1000)",
+            "ALTER TABLE USER_LOGIN_RECORDS MODIFY EMAIL VARCHAR(1000)",
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "storage_blobs",
+        sqlite: &[
+            r#"CREATE TABLE IF NOT EXISTS storage_blobs (
+                storage_key TEXT PRIMARY KEY,
+                checksum_sha256 TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                last_referenced_at TEXT NOT NULL,
+                gc_claim_id TEXT,
+                gc_claimed_at TEXT
+            )"#,
+            "CREATE INDEX IF NOT EXISTS idx_storage_blobs_last_referenced \
+             ON storage_blobs(last_referenced_at)",
+            "CREATE INDEX IF NOT EXISTS idx_material_files_original_key \
+             ON preview_material_files(stored_original_key)",
+        ],
+        postgres: &[
+            r#"CREATE TABLE IF NOT EXISTS storage_blobs (
+                storage_key TEXT PRIMARY KEY,
+                checksum_sha256 TEXT NOT NULL,
+                size_bytes BIGINT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL,
+                last_referenced_at TIMESTAMPTZ NOT NULL,
+                gc_claim_id TEXT,
+                gc_claimed_at TIMESTAMPTZ
+            )"#,
+            "CREATE INDEX IF NOT EXISTS idx_storage_blobs_last_referenced \
+             ON storage_blobs(last_referenced_at)",
+            "CREATE INDEX IF NOT EXISTS idx_material_files_original_key \
+             ON preview_material_files(stored_original_key)",
+        ],
+        dm: &[
+            r#"CREATE TABLE STORAGE_BLOBS (
+                STORAGE_KEY VARCHAR(500) PRIMARY KEY,
+                CHECKSUM_SHA256 VARCHAR(64) NOT NULL,
+                SIZE_BYTES BIGINT NOT NULL,
+                CREATED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                LAST_REFERENCED_AT TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                GC_CLAIM_ID VARCHAR(100),
+                GC_CLAIMED_AT TIMESTAMP
+            )"#,
+            "CREATE INDEX IDX_STORAGE_BLOBS_LAST_REF ON STORAGE_BLOBS(LAST_REFERENCED_AT)",
+            "CREATE INDEX IDX_MATERIAL_ORIGINAL_KEY ON PREVIEW_MATERIAL_FILES(STORED_ORIGINAL_KEY)",
+        ],
+    },
+    // PostgreSQL 与达梦在基线中已有该表
+    Migration {
+        version: 5,
+        name: "sqlite_material_download_queue",
+        sqlite: &[
+            r#"CREATE TABLE IF NOT EXISTS material_download_queue (
+                id TEXT PRIMARY KEY,
+                preview_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )"#,
+            "CREATE INDEX IF NOT EXISTS idx_mdq_status \
+             ON material_download_queue(status, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_mdq_preview ON material_download_queue(preview_id)",
+        ],
+        postgres: &[],
+        dm: &[],
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// 已执行，但代码中的 SQL 与执行时不一致
+    ChecksumMismatch,
+    /// 库中有记录，当前版本的程序不认识
+    Unknown,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Applied => "applied",
+            Self::Pending => "pending",
+            Self::ChecksumMismatch => "checksum_mismatch",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<String>,
+}
+
+pub struct MigrationRunner<'a> {
+    db: &'a dyn Database,
+    migrations: &'a [Migration],
+}
+
+impl<'a> MigrationRunner<'a> {
+    pub fn new(db: &'a dyn Database) -> Self {
+        Self::with_migrations(db, MIGRATIONS)
+    }
+
+    pub fn with_migrations(db: &'a dyn Database, migrations: &'a [Migration]) -> Self {
+        Self { db, migrations }
+    }
+
+    fn dialect(&self) -> Result<MigrationDialect> {
+        self.db
+            .migration_dialect()
+            .ok_or_else(|| anyhow!("当前数据库后端不支持版本化迁移"))
+    }
+
+    /// 已登记迁移与库中记录合并，按版本升序
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>> {
+        let dialect = self.dialect()?;
+        let mut applied: HashMap<i64, AppliedMigration> = self
+            .db
+            .list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|m| (m.version, m))
+            .collect();
+
+        let mut statuses = Vec::with_capacity(self.migrations.len());
+        for migration in self.migrations {
+            let status = match applied.remove(&migration.version) {
+                Some(record) => MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    state: if record.checksum == migration.checksum(dialect) {
+                        MigrationState::Applied
+                    } else {
+                        MigrationState::ChecksumMismatch
+                    },
+                    applied_at: Some(record.applied_at.to_rfc3339()),
+                },
+                None => MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    state: MigrationState::Pending,
+                    applied_at: None,
+                },
+            };
+            statuses.push(status);
+        }
+        statuses.extend(applied.into_values().map(|record| MigrationStatus {
+            version: record.version,
+            name: record.name,
+            state: MigrationState::Unknown,
+            applied_at: Some(record.applied_at.to_rfc3339()),
+        }));
+        statuses.sort_by_key(|s| s.version);
+        Ok(statuses)
+    }
+
+    /// 返回校验和不一致或不认识的迁移，为空表示库与代码一致（待执行的不算问题）
+    pub async fn verify(&self) -> Result<Vec<MigrationStatus>> {
+        Ok(self
+            .status()
+            .await?
+            .into_iter()
+            .filter(|s| {
+                matches!(
+                    s.state,
+                    MigrationState::ChecksumMismatch | MigrationState::Unknown
+                )
+            })
+            .collect())
+    }
+
+    /// 库中存在比本程序更新的迁移时拒绝继续，避免旧版本程序写坏新表结构
+    pub async fn ensure_not_newer(&self) -> Result<()> {
+        let known = self.migrations.iter().map(|m| m.version).max().unwrap_or(0);
+        let newer: Vec<i64> = self
+            .db
+            .list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|m| m.version)
+            .filter(|v| *v > known)
+            .collect();
+        if newer.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "数据库表结构版本 {:?} 高于本程序支持的最高版本 {}，请升级程序后再启动",
+                newer,
+                known
+            ))
+        }
+    }
+
+    /// 按版本顺序执行待执行的迁移，返回本次执行的版本号；
+    /// 已执行迁移校验和不一致时不执行任何迁移
+    pub async fn migrate_up(&self) -> Result<Vec<i64>> {
+        self.ensure_not_newer().await?;
+        let statuses = self.status().await?;
+        if let Some(bad) = statuses
+            .iter()
+            .find(|s| s.state == MigrationState::ChecksumMismatch)
+        {
+            return Err(anyhow!(
+                "迁移 {} ({}) 的校验和与执行时不一致，请先运行 migrate verify 排查",
+                bad.version,
+                bad.name
+            ));
+        }
+
+        let mut applied = Vec::new();
+        for migration in self.migrations {
+            let pending = statuses
+                .iter()
+                .any(|s| s.version == migration.version && s.state == MigrationState::Pending);
+            if !pending {
+                continue;
+            }
+            let started = Instant::now();
+            self.db.apply_migration(migration).await.map_err(|e| {
+                anyhow!(
+                    "迁移 {} ({}) 执行失败: {}",
+                    migration.version,
+                    migration.name,
+                    e
+                )
+            })?;
+            info!(
+                version = migration.version,
+                name = migration.name,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "[ok] 表结构迁移完成"
+            );
+            applied.push(migration.version);
+        }
+        Ok(applied)
+    }
+
+    /// 启动时调用：拒绝更新的表结构，执行待执行迁移；后端不支持时跳过
+    pub async fn run_on_startup(&self) -> Result<()> {
+        if self.db.migration_dialect().is_none() {
+            info!("ℹ 当前数据库后端未接入版本化迁移，跳过");
+            return Ok(());
+        }
+        for problem in self.verify().await? {
+            if problem.state == MigrationState::ChecksumMismatch {
+                warn!(
+                    version = problem.version,
+                    name = %problem.name,
+                    "[warn] 已执行迁移的校验和与代码不一致"
+                );
+            }
+        }
+        self.migrate_up().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::SqliteDatabase;
+
+    const WITH_NOTES: &[Migration] = &[
+        MIGRATIONS[0],
+        Migration {
+            version: 99,
+            name: "add_preview_notes",
+            sqlite: &["ALTER TABLE preview_records ADD COLUMN notes TEXT"],
+            postgres: &["ALTER TABLE preview_records ADD COLUMN notes TEXT"],
+            dm: &["ALTER TABLE PREVIEW_RECORDS ADD NOTES VARCHAR(500)"],
+        },
+    ];
+
+    async fn sqlite_db() -> (tempfile::TempDir, SqliteDatabase) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("migrations.db");
+        let db = SqliteDatabase::new(path.to_str().unwrap()).await.unwrap();
+        db.initialize().await.unwrap();
+        (dir, db)
+    }
+
+    #[tokio::test]
+    async fn migrate_up_applies_pending_once() {
+        let (_dir, db) = sqlite_db().await;
+        let runner = MigrationRunner::with_migrations(&db, WITH_NOTES);
+
+        assert_eq!(runner.migrate_up().await.unwrap(), vec![1, 99]);
+        assert!(runner.migrate_up().await.unwrap().is_empty());
+
+        let states: Vec<_> = runner
+            .status()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.state)
+            .collect();
+        assert_eq!(states, vec![MigrationState::Applied; 2]);
+        sqlx::query("SELECT notes FROM preview_records")
+            .fetch_all(db.pool())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn older_binary_refuses_newer_schema() {
+        let (_dir, db) = sqlite_db().await;
+        MigrationRunner::with_migrations(&db, WITH_NOTES)
+            .migrate_up()
+            .await
+            .unwrap();
+
+        let old = MigrationRunner::new(&db);
+        assert!(old.ensure_not_newer().await.is_err());
+        assert!(old.migrate_up().await.is_err());
+        let unknown = old.verify().await.unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].state, MigrationState::Unknown);
+    }
+
+    #[tokio::test]
+    async fn edited_migration_is_reported_and_blocks_upgrade() {
+        let (_dir, db) = sqlite_db().await;
+        MigrationRunner::with_migrations(&db, WITH_NOTES)
+            .migrate_up()
+            .await
+            .unwrap();
+
+        let edited: &[Migration] = &[
+            WITH_NOTES[0],
+            Migration {
+                sqlite: &["ALTER TABLE preview_records ADD COLUMN notes TEXT NOT NULL DEFAULT ''"],
+                ..WITH_NOTES[1]
+            },
+        ];
+        let runner = MigrationRunner::with_migrations(&db, edited);
+        let problems = runner.verify().await.unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].state, MigrationState::ChecksumMismatch);
+        assert!(runner.migrate_up().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn column_added_by_failed_run_is_skipped_on_retry() {
+        let (_dir, db) = sqlite_db().await;
+        MigrationRunner::with_migrations(&db, &WITH_NOTES[..1])
+            .migrate_up()
+            .await
+            .unwrap();
+        sqlx::query("ALTER TABLE preview_records ADD COLUMN notes TEXT")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let runner = MigrationRunner::with_migrations(&db, WITH_NOTES);
+        assert_eq!(runner.migrate_up().await.unwrap(), vec![99]);
+    }
+
+    #[test]
+    fn schema_object_parses_created_objects() {
+        assert_eq!(
+            SchemaObject::created_by("ALTER TABLE PREVIEW_RECORDS ADD NOTES VARCHAR(500)"),
+            Some(SchemaObject::Column {
+                table: "PREVIEW_RECORDS",
+                column: "NOTES"
+            })
+        );
+        assert_eq!(
+            SchemaObject::created_by("alter table t add column c TEXT"),
+            Some(SchemaObject::Column {
+                table: "t",
+                column: "c"
+            })
+        );
+        assert_eq!(
+            SchemaObject::created_by("CREATE TABLE IF NOT EXISTS jobs(id TEXT)"),
+            Some(SchemaObject::Table("jobs"))
+        );
+        assert_eq!(
+            SchemaObject::created_by("CREATE UNIQUE INDEX IDX_JOBS ON JOBS(ID)"),
+            Some(SchemaObject::Index("IDX_JOBS"))
+        );
+        assert_eq!(SchemaObject::created_by("UPDATE jobs SET id = 1"), None);
+    }
+
+    #[test]
+    fn checksum_depends_on_dialect_statements() {
+        let m = WITH_NOTES[1];
+        assert_eq!(
+            m.checksum(MigrationDialect::Sqlite),
+            m.checksum(MigrationDialect::Postgres)
+        );
+        assert_ne!(
+            m.checksum(MigrationDialect::Sqlite),
+            m.checksum(MigrationDialect::Dm)
+        );
+    }
+}
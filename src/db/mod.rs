@@ -1,18 +1,25 @@
 
 #[cfg(feature = "dm_go")]
 pub mod dm;
+pub mod encrypted;
 pub mod factory;
 pub mod failover;
+pub mod migrations;
 pub mod models;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod reconcile;
 pub mod sqlite;
 pub mod traits;
 
+pub use encrypted::EncryptedDatabase;
 #[cfg(feature = "dm_go")]
 pub use factory::{
-    create_database, DatabaseConfig, DatabaseType, DmConfig, SmartDatabaseManager, SqliteConfig,
+    create_database, DatabaseConfig, DatabaseType, DmConfig, PostgresConfig, SmartDatabaseManager,
+    SqliteConfig,
 };
 #[cfg(not(feature = "dm_go"))]
-pub use factory::{create_database, DatabaseConfig, DatabaseType, SqliteConfig};
+pub use factory::{create_database, DatabaseConfig, DatabaseType, PostgresConfig, SqliteConfig};
 pub use failover::FailoverDatabase;
 pub use models::*;
 pub use traits::Database;
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::db::traits::{BlobGcQuery, StorageBlobRecord};
+
+pub struct BlobQueries;
+
+impl BlobQueries {
+    pub async fn touch(pool: &PgPool, blob: &StorageBlobRecord) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO storage_blobs
+                (storage_key, checksum_sha256, size_bytes, created_at, last_referenced_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(storage_key) DO UPDATE SET
+                last_referenced_at = excluded.last_referenced_at
+            WHERE storage_blobs.gc_claim_id IS NULL
+            "#,
+        )
+        .bind(&blob.storage_key)
+        .bind(&blob.checksum_sha256)
+        .bind(blob.size_bytes)
+        .bind(blob.created_at)
+        .bind(blob.last_referenced_at)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn claim_unreferenced(
+        pool: &PgPool,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        // 外层条件在并发更新后会被重新判断，子查询只负责限制批量
+        let rows = sqlx::query(
+            r#"
+            UPDATE storage_blobs SET gc_claim_id = $1, gc_claimed_at = $2
+            WHERE last_referenced_at < $3
+              AND (gc_claim_id IS NULL OR gc_claimed_at < $4)
+              AND NOT EXISTS (
+                  SELECT 1 FROM preview_material_files f
+                  WHERE f.stored_original_key = storage_blobs.storage_key
+              )
+              AND storage_key IN (
+                  SELECT b.storage_key FROM storage_blobs b
+                  WHERE b.last_referenced_at < $3
+                    AND (b.gc_claim_id IS NULL OR b.gc_claimed_at < $4)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM preview_material_files f
+                        WHERE f.stored_original_key = b.storage_key
+                    )
+                  ORDER BY b.last_referenced_at
+                  LIMIT $5
+              )
+            RETURNING storage_key, checksum_sha256, size_bytes, created_at, last_referenced_at
+            "#,
+        )
+        .bind(&query.claim_id)
+        .bind(Utc::now())
+        .bind(query.unreferenced_before)
+        .bind(query.stale_claim_before)
+        .bind(query.limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let mut blobs: Vec<_> = rows.iter().map(Self::map_row).collect::<Result<_>>()?;
+        blobs.sort_by_key(|b| b.last_referenced_at);
+        Ok(blobs)
+    }
+
+    pub async fn delete_claimed(pool: &PgPool, storage_key: &str, claim_id: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM storage_blobs WHERE storage_key = $1 AND gc_claim_id = $2")
+                .bind(storage_key)
+                .bind(claim_id)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn release(pool: &PgPool, storage_key: &str, claim_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE storage_blobs SET gc_claim_id = NULL, gc_claimed_at = NULL \
+             WHERE storage_key = $1 AND gc_claim_id = $2",
+        )
+        .bind(storage_key)
+        .bind(claim_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    fn map_row(row: &PgRow) -> Result<StorageBlobRecord> {
+        Ok(StorageBlobRecord {
+            storage_key: row.try_get("storage_key")?,
+            checksum_sha256: row.try_get("checksum_sha256")?,
+            size_bytes: row.try_get("size_bytes")?,
+            created_at: row.try_get("created_at")?,
+            last_referenced_at: row.try_get("last_referenced_at")?,
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use std::time::Duration;
+use tracing::info;
+
+use crate::db::factory::PostgresConfig;
+use crate::db::sqlite::connection::PoolInfo;
+
+pub struct ConnectionManager;
+
+impl ConnectionManager {
+    pub async fn create_pool(config: &PostgresConfig) -> Result<PgPool> {
+        let mut options = PgConnectOptions::new()
+            .host(&config.host)
+            .port(config.port)
+            .username(&config.username)
+            .password(&config.password)
+            .database(&config.database);
+        if let Some(schema) = config.schema.as_deref().filter(|s| !s.is_empty()) {
+            options = options.options([("search_path", schema)]);
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections.max(1))
+            .acquire_timeout(Duration::from_secs(config.connection_timeout.max(1)))
+            .connect_with(options)
+            .await?;
+
+        info!(
+            "Successfully connected to PostgreSQL database: {}:{}/{}",
+            config.host, config.port, config.database
+        );
+        Ok(pool)
+    }
+
+    pub async fn validate_pool(pool: &PgPool) -> Result<()> {
+        sqlx::query("SELECT 1").fetch_one(pool).await?;
+        Ok(())
+    }
+
+    pub fn get_pool_info(pool: &PgPool) -> PoolInfo {
+        let max_connections = pool.size();
+        let idle_connections = pool.num_idle();
+        let active_connections = (max_connections as usize).saturating_sub(idle_connections);
+
+        PoolInfo {
+            max_connections,
+            idle_connections,
+            active_connections,
+        }
+    }
+}
+
+/// 集成测试连接：每次调用建一个独立 schema，测试之间互不影响。
+/// 相关测试标记为 ignore，用 `--ignored` 运行时未设置 `POSTGRES_TEST_URL` 直接失败
+#[cfg(test)]
+pub(crate) async fn test_pool() -> (PgPool, TestSchema) {
+    use std::str::FromStr;
+
+    let url =
+        std::env::var("POSTGRES_TEST_URL").expect("PostgreSQL 集成测试需要设置 POSTGRES_TEST_URL");
+    let schema = format!("crate_test_{}", uuid::Uuid::new_v4().simple());
+
+    let admin = PgPool::connect(&url).await.expect("连接测试库失败");
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&admin)
+        .await
+        .expect("创建测试 schema 失败");
+    admin.close().await;
+
+    let options = PgConnectOptions::from_str(&url)
+        .expect("POSTGRES_TEST_URL 格式错误")
+        .options([("search_path", schema.as_str())]);
+    let pool = PgPoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await
+        .expect("连接测试库失败");
+    (pool, TestSchema { url, name: schema })
+}
+
+/// 测试结束（含失败）时删除 [`test_pool`] 创建的 schema
+#[cfg(test)]
+pub(crate) struct TestSchema {
+    url: String,
+    name: String,
+}
+
+#[cfg(test)]
+impl Drop for TestSchema {
+    fn drop(&mut self) {
+        let url = self.url.clone();
+        let name = std::mem::take(&mut self.name);
+        // 测试运行时正在析构，另起线程与运行时执行清理
+        let dropped = std::thread::spawn(move || -> Result<()> {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?
+                .block_on(async {
+                    let admin = PgPool::connect(&url).await?;
+                    sqlx::query(&format!("DROP SCHEMA IF EXISTS {name} CASCADE"))
+                        .execute(&admin)
+                        .await?;
+                    admin.close().await;
+                    Ok(())
+                })
+        })
+        .join();
+        match dropped {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => eprintln!("删除测试 schema 失败: {err:#}"),
+            Err(_) => eprintln!("删除测试 schema 的线程异常退出"),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+
+use crate::db::traits::{DeadLetterFilter, DeadLetterRecord, DeadLetterStatus};
+
+const DEAD_LETTER_COLUMNS: &str = "id, preview_id, queue, source, payload, failure_reason, \
+    attempts, last_error, status, resolved_by, resolved_at, created_at, updated_at";
+
+pub struct DeadLetterQueries;
+
+impl DeadLetterQueries {
+    /// 同一 id 重复写入时刷新载荷与失败信息，并重新置为 dead
+    pub async fn save(pool: &PgPool, record: &DeadLetterRecord) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO preview_dead_letters ({DEAD_LETTER_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (id) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                failure_reason = EXCLUDED.failure_reason,
+                attempts = EXCLUDED.attempts,
+                last_error = EXCLUDED.last_error,
+                status = EXCLUDED.status,
+                resolved_by = NULL,
+                resolved_at = NULL,
+                updated_at = EXCLUDED.updated_at
+            "#
+        ))
+        .bind(&record.id)
+        .bind(&record.preview_id)
+        .bind(&record.queue)
+        .bind(&record.source)
+        .bind(&record.payload)
+        .bind(&record.failure_reason)
+        .bind(record.attempts)
+        .bind(&record.last_error)
+        .bind(record.status.as_str())
+        .bind(&record.resolved_by)
+        .bind(record.resolved_at)
+        .bind(record.created_at)
+        .bind(record.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(pool: &PgPool, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(format!(
+            "SELECT {DEAD_LETTER_COLUMNS} FROM preview_dead_letters WHERE 1 = 1"
+        ));
+        if let Some(status) = filter.status {
+            builder.push(" AND status = ").push_bind(status.as_str());
+        }
+        if let Some(preview_id) = &filter.preview_id {
+            builder
+                .push(" AND preview_id = ")
+                .push_bind(preview_id.clone());
+        }
+        if let Some(source) = &filter.source {
+            builder.push(" AND source = ").push_bind(source.clone());
+        }
+        builder
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(50) as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset.unwrap_or(0) as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(map_dead_letter).collect()
+    }
+
+    pub async fn get(pool: &PgPool, id: &str) -> Result<Option<DeadLetterRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {DEAD_LETTER_COLUMNS} FROM preview_dead_letters WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(map_dead_letter).transpose()
+    }
+
+    pub async fn resolve(
+        pool: &PgPool,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE preview_dead_letters
+            SET status = $1, payload = COALESCE($2, payload), resolved_by = $3,
+                resolved_at = now(), updated_at = now()
+            WHERE id = $4 AND status = 'dead'
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(payload)
+        .bind(resolved_by)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn map_dead_letter(row: &PgRow) -> Result<DeadLetterRecord> {
+    Ok(DeadLetterRecord {
+        id: row.try_get("id")?,
+        preview_id: row.try_get("preview_id")?,
+        queue: row.try_get("queue")?,
+        source: row.try_get("source")?,
+        payload: row.try_get("payload")?,
+        failure_reason: row.try_get("failure_reason")?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        status: row.try_get::<String, _>("status")?.parse()?,
+        resolved_by: row.try_get("resolved_by")?,
+        resolved_at: row.try_get("resolved_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
@@ -0,0 +1,896 @@
+pub mod blobs;
+pub mod connection;
+pub mod dead_letter;
+pub mod monitor_queries;
+pub mod pii;
+pub mod preview_batch;
+pub mod queries;
+pub mod queues;
+pub mod retention;
+pub mod schema_migrations;
+pub mod schemas;
+pub mod task_queue;
+pub mod tokens;
+pub mod worker_control;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use super::migrations::{Migration, MigrationDialect};
+use super::traits::*;
+use crate::db::factory::PostgresConfig;
+use crate::db::models::{MonitorSession, MonitorUser};
+use crate::db::sqlite::connection::PoolInfo;
+use blobs::BlobQueries;
+use connection::ConnectionManager;
+use dead_letter::DeadLetterQueries;
+use monitor_queries::MonitorQueries;
+use pii::PiiQueries;
+use preview_batch::PreviewBatchQueries;
+use queries::{
+    ApiStatsQueries, CachedMaterialQueries, DedupQueries, HealthQueries, MaterialFileQueries,
+    MaterialResultQueries, MatterRuleConfigQueries, OutboxQueries, PreviewQueries,
+    PreviewRequestQueries, RuleResultQueries, TaskPayloadQueries, UserLoginQueries,
+};
+use queues::{MaterialDownloadQueries, WorkerResultQueries};
+use retention::RetentionQueries;
+use schema_migrations::SchemaMigrationQueries;
+use schemas::SchemaManager;
+use task_queue::TaskQueueQueries;
+use tokens::{DownloadCacheQueries, ShareTokenQueries};
+use worker_control::WorkerControlQueries;
+
+/// PostgreSQL 实现：行锁 + SKIP LOCKED 保证多实例并发安全
+pub struct PostgresDatabase {
+    pool: PgPool,
+}
+
+impl PostgresDatabase {
+    pub async fn new(config: &PostgresConfig) -> Result<Self> {
+        let pool = ConnectionManager::create_pool(config).await?;
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub fn pool_info(&self) -> PoolInfo {
+        ConnectionManager::get_pool_info(&self.pool)
+    }
+}
+
+#[async_trait]
+impl Database for PostgresDatabase {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn save_preview_request(&self, request: &PreviewRequestRecord) -> Result<()> {
+        PreviewRequestQueries::upsert(&self.pool, request).await
+    }
+
+    async fn get_preview_request(&self, id: &str) -> Result<Option<PreviewRequestRecord>> {
+        PreviewRequestQueries::get_by_id(&self.pool, id).await
+    }
+
+    async fn find_preview_request_by_third_party(
+        &self,
+        third_party_request_id: &str,
+    ) -> Result<Option<PreviewRequestRecord>> {
+        PreviewRequestQueries::get_by_third_party(&self.pool, third_party_request_id).await
+    }
+
+    async fn update_preview_request_latest(
+        &self,
+        request_id: &str,
+        latest_preview_id: Option<&str>,
+        latest_status: Option<PreviewStatus>,
+    ) -> Result<()> {
+        PreviewRequestQueries::update_latest_state(
+            &self.pool,
+            request_id,
+            latest_preview_id,
+            latest_status,
+        )
+        .await
+    }
+
+    async fn list_preview_requests(
+        &self,
+        filter: &PreviewRequestFilter,
+    ) -> Result<Vec<PreviewRequestRecord>> {
+        PreviewRequestQueries::list(&self.pool, filter).await
+    }
+
+    async fn save_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        PreviewQueries::save_record(&self.pool, record).await
+    }
+
+    async fn get_preview_record(&self, id: &str) -> Result<Option<PreviewRecord>> {
+        PreviewQueries::get_by_id(&self.pool, id).await
+    }
+
+    async fn reconcile_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        PreviewQueries::replace_record(&self.pool, record).await
+    }
+
+    async fn update_preview_status(&self, id: &str, status: PreviewStatus) -> Result<()> {
+        PreviewQueries::update_status(&self.pool, id, status).await
+    }
+
+    async fn update_preview_evaluation_result(
+        &self,
+        id: &str,
+        evaluation_result: &str,
+    ) -> Result<()> {
+        PreviewQueries::update_evaluation_result(&self.pool, id, evaluation_result).await
+    }
+
+    async fn mark_preview_processing(
+        &self,
+        id: &str,
+        worker_id: &str,
+        attempt_id: &str,
+    ) -> Result<()> {
+        PreviewQueries::mark_processing(&self.pool, id, worker_id, attempt_id).await
+    }
+
+    async fn update_preview_artifacts(
+        &self,
+        id: &str,
+        file_name: &str,
+        preview_url: &str,
+        preview_view_url: Option<&str>,
+        preview_download_url: Option<&str>,
+    ) -> Result<()> {
+        PreviewQueries::update_artifacts(
+            &self.pool,
+            id,
+            file_name,
+            preview_url,
+            preview_view_url,
+            preview_download_url,
+        )
+        .await
+    }
+
+    async fn replace_preview_material_results(
+        &self,
+        preview_id: &str,
+        records: &[PreviewMaterialResultRecord],
+    ) -> Result<()> {
+        MaterialResultQueries::replace(&self.pool, preview_id, records).await
+    }
+
+    async fn replace_preview_rule_results(
+        &self,
+        preview_id: &str,
+        records: &[PreviewRuleResultRecord],
+    ) -> Result<()> {
+        RuleResultQueries::replace(&self.pool, preview_id, records).await
+    }
+
+    async fn update_preview_failure_context(&self, update: &PreviewFailureUpdate) -> Result<()> {
+        PreviewQueries::update_failure_context(&self.pool, update).await
+    }
+
+    async fn list_preview_records(&self, filter: &PreviewFilter) -> Result<Vec<PreviewRecord>> {
+        PreviewQueries::list_with_filter(&self.pool, filter).await
+    }
+
+    async fn check_and_update_preview_dedup(
+        &self,
+        fingerprint: &str,
+        preview_id: &str,
+        meta: &PreviewDedupMeta,
+        limit: i32,
+    ) -> Result<PreviewDedupDecision> {
+        DedupQueries::check_and_update(&self.pool, fingerprint, preview_id, meta, limit).await
+    }
+
+    async fn get_preview_status_counts(&self) -> Result<PreviewStatusCounts> {
+        PreviewQueries::count_by_status(&self.pool).await
+    }
+
+    async fn find_preview_by_third_party_id(
+        &self,
+        third_party_id: &str,
+        user_id: &str,
+    ) -> Result<Option<PreviewRecord>> {
+        PreviewQueries::find_by_third_party_id(&self.pool, third_party_id, user_id).await
+    }
+
+    async fn save_api_stats(&self, stats: &ApiStats) -> Result<()> {
+        ApiStatsQueries::save_stats(&self.pool, stats).await
+    }
+
+    async fn get_api_stats(&self, filter: &StatsFilter) -> Result<Vec<ApiStats>> {
+        ApiStatsQueries::get_stats_with_filter(&self.pool, filter).await
+    }
+
+    async fn get_api_summary(&self, filter: &StatsFilter) -> Result<ApiSummary> {
+        ApiStatsQueries::get_summary(&self.pool, filter).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        HealthQueries::check_health(&self.pool).await
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        SchemaManager::create_all_tables(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn save_user_login_record(
+        &self,
+        user_id: &str,
+        user_name: Option<&str>,
+        certificate_type: &str,
+        certificate_number: Option<&str>,
+        phone_number: Option<&str>,
+        email: Option<&str>,
+        organization_name: Option<&str>,
+        organization_code: Option<&str>,
+        login_type: &str,
+        login_time: &str,
+        client_ip: &str,
+        user_agent: &str,
+        referer: &str,
+        cookie_info: &str,
+        raw_data: &str,
+    ) -> Result<()> {
+        UserLoginQueries::insert(
+            &self.pool,
+            user_id,
+            user_name,
+            certificate_type,
+            certificate_number,
+            phone_number,
+            email,
+            organization_name,
+            organization_code,
+            login_type,
+            login_time,
+            client_ip,
+            user_agent,
+            referer,
+            cookie_info,
+            raw_data,
+        )
+        .await
+    }
+
+    async fn upsert_cached_material_record(&self, record: &CachedMaterialRecord) -> Result<()> {
+        CachedMaterialQueries::upsert(&self.pool, record).await
+    }
+
+    async fn update_cached_material_status(
+        &self,
+        id: &str,
+        status: CachedMaterialStatus,
+        oss_key: Option<&str>,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        CachedMaterialQueries::update_status(&self.pool, id, status, oss_key, last_error).await
+    }
+
+    async fn list_cached_material_records(
+        &self,
+        filter: &CachedMaterialFilter,
+    ) -> Result<Vec<CachedMaterialRecord>> {
+        CachedMaterialQueries::list(&self.pool, filter).await
+    }
+
+    async fn delete_cached_material_record(&self, id: &str) -> Result<()> {
+        CachedMaterialQueries::delete_by_id(&self.pool, id).await
+    }
+
+    async fn delete_cached_materials_by_preview(&self, preview_id: &str) -> Result<()> {
+        CachedMaterialQueries::delete_by_preview(&self.pool, preview_id).await
+    }
+
+    async fn save_material_file_record(&self, record: &MaterialFileRecord) -> Result<()> {
+        MaterialFileQueries::insert(&self.pool, record).await
+    }
+
+    async fn update_material_file_status(
+        &self,
+        id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        MaterialFileQueries::update_status(&self.pool, id, status, error).await
+    }
+
+    async fn update_material_file_processing(
+        &self,
+        id: &str,
+        processed_keys_json: Option<&str>,
+        ocr_text_key: Option<&str>,
+        ocr_text_length: Option<i64>,
+    ) -> Result<()> {
+        MaterialFileQueries::update_processing(
+            &self.pool,
+            id,
+            processed_keys_json,
+            ocr_text_key,
+            ocr_text_length,
+        )
+        .await
+    }
+
+    async fn list_material_files(
+        &self,
+        filter: &MaterialFileFilter,
+    ) -> Result<Vec<MaterialFileRecord>> {
+        MaterialFileQueries::list(&self.pool, filter).await
+    }
+
+    async fn save_task_payload(&self, preview_id: &str, payload: &str) -> Result<()> {
+        TaskPayloadQueries::upsert(&self.pool, preview_id, payload).await
+    }
+
+    async fn load_task_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        TaskPayloadQueries::load(&self.pool, preview_id).await
+    }
+
+    async fn delete_task_payload(&self, preview_id: &str) -> Result<()> {
+        TaskPayloadQueries::delete(&self.pool, preview_id).await
+    }
+
+    async fn update_preview_callback_state(&self, update: &PreviewCallbackUpdate) -> Result<()> {
+        PreviewQueries::update_callback_state(&self.pool, update).await
+    }
+
+    async fn list_due_callbacks(&self, limit: u32) -> Result<Vec<PreviewRecord>> {
+        PreviewQueries::list_due_callbacks(&self.pool, limit).await
+    }
+
+    async fn enqueue_outbox_event(&self, event: &NewOutboxEvent) -> Result<()> {
+        OutboxQueries::insert(&self.pool, event).await
+    }
+
+    async fn fetch_pending_outbox_events(&self, limit: u32) -> Result<Vec<OutboxEvent>> {
+        OutboxQueries::fetch_pending(&self.pool, limit).await
+    }
+
+    async fn mark_outbox_event_applied(&self, event_id: &str) -> Result<()> {
+        OutboxQueries::mark_applied(&self.pool, event_id).await
+    }
+
+    async fn mark_outbox_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        OutboxQueries::mark_failed(&self.pool, event_id, error).await
+    }
+
+    async fn get_matter_rule_config(
+        &self,
+        matter_id: &str,
+    ) -> Result<Option<MatterRuleConfigRecord>> {
+        MatterRuleConfigQueries::get_by_matter_id(&self.pool, matter_id).await
+    }
+
+    async fn upsert_matter_rule_config(&self, config: &MatterRuleConfigRecord) -> Result<()> {
+        MatterRuleConfigQueries::upsert(&self.pool, config).await
+    }
+
+    async fn list_matter_rule_configs(
+        &self,
+        status: Option<&str>,
+    ) -> Result<Vec<MatterRuleConfigRecord>> {
+        MatterRuleConfigQueries::list(&self.pool, status).await
+    }
+
+    async fn find_monitor_user_by_username(&self, username: &str) -> Result<Option<MonitorUser>> {
+        MonitorQueries::find_user_by_username(&self.pool, username).await
+    }
+
+    async fn get_monitor_user_password_hash(&self, user_id: &str) -> Result<String> {
+        MonitorQueries::get_user_password_hash(&self.pool, user_id).await
+    }
+
+    async fn create_monitor_session(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        ip: &str,
+        user_agent: &str,
+        created_at: &str,
+        expires_at: &str,
+    ) -> Result<()> {
+        MonitorQueries::create_session(
+            &self.pool, session_id, user_id, ip, user_agent, created_at, expires_at,
+        )
+        .await
+    }
+
+    async fn find_monitor_session_by_id(&self, session_id: &str) -> Result<Option<MonitorSession>> {
+        MonitorQueries::find_session_by_id(&self.pool, session_id).await
+    }
+
+    async fn update_monitor_login_info(&self, user_id: &str, now: &str) -> Result<()> {
+        MonitorQueries::update_login_info(&self.pool, user_id, now).await
+    }
+
+    async fn update_monitor_session_activity(&self, session_id: &str, now: &str) -> Result<()> {
+        MonitorQueries::update_session_activity(&self.pool, session_id, now).await
+    }
+
+    async fn delete_monitor_session(&self, session_id: &str) -> Result<()> {
+        MonitorQueries::delete_session(&self.pool, session_id).await
+    }
+
+    async fn cleanup_expired_monitor_sessions(&self, now: &str) -> Result<u64> {
+        MonitorQueries::cleanup_expired_sessions(&self.pool, now).await
+    }
+
+    async fn get_active_monitor_sessions_count(&self, now: &str) -> Result<i64> {
+        MonitorQueries::get_active_sessions_count(&self.pool, now).await
+    }
+
+    async fn list_monitor_users(&self) -> Result<Vec<MonitorUser>> {
+        MonitorQueries::list_users(&self.pool).await
+    }
+
+    async fn create_monitor_user(
+        &self,
+        id: &str,
+        username: &str,
+        password_hash: &str,
+        role: &str,
+        now: &str,
+    ) -> Result<()> {
+        MonitorQueries::create_user(&self.pool, id, username, password_hash, role, now).await
+    }
+
+    async fn update_monitor_user_role(&self, user_id: &str, role: &str, now: &str) -> Result<()> {
+        MonitorQueries::update_user_role(&self.pool, user_id, role, now).await
+    }
+
+    async fn update_monitor_user_password(
+        &self,
+        user_id: &str,
+        password_hash: &str,
+        now: &str,
+    ) -> Result<()> {
+        MonitorQueries::update_user_password(&self.pool, user_id, password_hash, now).await
+    }
+
+    async fn set_monitor_user_active(
+        &self,
+        user_id: &str,
+        is_active: bool,
+        now: &str,
+    ) -> Result<()> {
+        MonitorQueries::set_user_active(&self.pool, user_id, is_active, now).await
+    }
+
+    async fn count_active_monitor_admins(&self) -> Result<i64> {
+        MonitorQueries::count_active_admins(&self.pool).await
+    }
+
+    async fn find_monitor_user_by_id(&self, user_id: &str) -> Result<Option<MonitorUser>> {
+        MonitorQueries::find_user_by_id(&self.pool, user_id).await
+    }
+
+    async fn enqueue_worker_result(&self, preview_id: &str, payload: &str) -> Result<()> {
+        WorkerResultQueries::enqueue(&self.pool, preview_id, payload).await
+    }
+
+    async fn fetch_pending_worker_results(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<WorkerResultQueueRecord>> {
+        WorkerResultQueries::fetch_pending(&self.pool, limit).await
+    }
+
+    async fn update_worker_result_status(
+        &self,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        WorkerResultQueries::update_status(&self.pool, id, status, last_error).await
+    }
+
+    async fn get_worker_result_by_preview_id(
+        &self,
+        preview_id: &str,
+    ) -> Result<Option<WorkerResultQueueRecord>> {
+        WorkerResultQueries::get_by_preview_id(&self.pool, preview_id).await
+    }
+
+    async fn enqueue_material_download(&self, preview_id: &str, payload: &str) -> Result<()> {
+        MaterialDownloadQueries::enqueue(&self.pool, preview_id, payload).await
+    }
+
+    async fn fetch_pending_material_downloads(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<MaterialDownloadQueueRecord>> {
+        MaterialDownloadQueries::fetch_pending(&self.pool, limit).await
+    }
+
+    async fn update_material_download_status(
+        &self,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        MaterialDownloadQueries::update_status(&self.pool, id, status, last_error).await
+    }
+
+    async fn update_material_download_payload(&self, id: &str, payload: &str) -> Result<()> {
+        MaterialDownloadQueries::update_payload(&self.pool, id, payload).await
+    }
+
+    async fn cancel_material_downloads(&self, preview_id: &str) -> Result<u64> {
+        MaterialDownloadQueries::cancel_pending(&self.pool, preview_id).await
+    }
+
+    async fn latest_material_download_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        MaterialDownloadQueries::latest_payload(&self.pool, preview_id).await
+    }
+
+    async fn ensure_task_queue_table(&self, table: &str) -> Result<()> {
+        TaskQueueQueries::ensure_table(&self.pool, table).await
+    }
+
+    async fn enqueue_queued_task(&self, table: &str, task: &NewQueuedTask) -> Result<()> {
+        TaskQueueQueries::enqueue(&self.pool, table, task).await
+    }
+
+    async fn claim_queued_tasks(
+        &self,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        TaskQueueQueries::claim(&self.pool, table, claim).await
+    }
+
+    async fn extend_queued_task_lease(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        TaskQueueQueries::extend_lease(&self.pool, table, task_id, lease_token, lease_expires_at)
+            .await
+    }
+
+    async fn complete_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        TaskQueueQueries::complete(&self.pool, table, task_id, lease_token).await
+    }
+
+    async fn release_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        TaskQueueQueries::release(&self.pool, table, task_id, lease_token, error, retry_at).await
+    }
+
+    async fn return_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        TaskQueueQueries::return_task(&self.pool, table, task_id, lease_token).await
+    }
+
+    async fn queued_task_stats(&self, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        TaskQueueQueries::stats(&self.pool, table, queue).await
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        TaskQueueQueries::list_failed(&self.pool, table, queue, limit).await
+    }
+
+    async fn delete_queued_task(&self, table: &str, task_id: &str) -> Result<bool> {
+        TaskQueueQueries::delete(&self.pool, table, task_id).await
+    }
+
+    async fn cancel_queued_tasks(&self, table: &str, queue: &str, preview_id: &str) -> Result<u64> {
+        TaskQueueQueries::cancel_pending(&self.pool, table, queue, preview_id).await
+    }
+
+    async fn save_dead_letter(&self, record: &DeadLetterRecord) -> Result<()> {
+        DeadLetterQueries::save(&self.pool, record).await
+    }
+
+    async fn list_dead_letters(&self, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        DeadLetterQueries::list(&self.pool, filter).await
+    }
+
+    async fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetterRecord>> {
+        DeadLetterQueries::get(&self.pool, id).await
+    }
+
+    async fn resolve_dead_letter(
+        &self,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        DeadLetterQueries::resolve(&self.pool, id, status, payload, resolved_by).await
+    }
+
+    async fn save_worker_control(&self, record: &WorkerControlRecord) -> Result<()> {
+        WorkerControlQueries::save(&self.pool, record).await
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        WorkerControlQueries::list(&self.pool).await
+    }
+
+    async fn save_preview_batch_items(&self, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        PreviewBatchQueries::save_items(&self.pool, items).await
+    }
+
+    async fn list_preview_batch_items(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        PreviewBatchQueries::list_items(&self.pool, batch_id).await
+    }
+
+    async fn get_download_cache_token(
+        &self,
+        url: &str,
+    ) -> Result<Option<MaterialDownloadCacheEntry>> {
+        DownloadCacheQueries::get(&self.pool, url).await
+    }
+
+    async fn upsert_download_cache_token(
+        &self,
+        url: &str,
+        token: &str,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        DownloadCacheQueries::upsert(&self.pool, url, token, ttl_secs).await
+    }
+
+    async fn create_preview_share_token(
+        &self,
+        preview_id: &str,
+        token: &str,
+        format: &str,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        ShareTokenQueries::create(&self.pool, preview_id, token, format, ttl_secs).await
+    }
+
+    async fn consume_preview_share_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<PreviewShareTokenRecord>> {
+        ShareTokenQueries::consume(&self.pool, token).await
+    }
+
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        Some(MigrationDialect::Postgres)
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        SchemaMigrationQueries::list(&self.pool).await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        SchemaMigrationQueries::apply(&self.pool, migration).await
+    }
+
+    async fn list_retention_candidates(
+        &self,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        RetentionQueries::list_candidates(&self.pool, query).await
+    }
+
+    async fn purge_preview_artifact(&self, purge: &ArtifactPurge) -> Result<Option<i64>> {
+        RetentionQueries::purge_preview_artifact(&self.pool, purge).await
+    }
+
+    async fn purge_log_records(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+        purge: &ArtifactPurge,
+    ) -> Result<i64> {
+        RetentionQueries::purge_log_records(&self.pool, before, purge).await
+    }
+
+    async fn set_preview_legal_hold(&self, update: &LegalHoldUpdate) -> Result<bool> {
+        RetentionQueries::set_legal_hold(&self.pool, update).await
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        RetentionQueries::list_legal_holds(&self.pool).await
+    }
+
+    async fn list_purge_audit(&self, filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        RetentionQueries::list_audit(&self.pool, filter).await
+    }
+
+    async fn save_pii_lookup_value(&self, token: &str, ciphertext: &str) -> Result<()> {
+        PiiQueries::save_lookup_value(&self.pool, token, ciphertext).await
+    }
+
+    async fn get_pii_lookup_values(
+        &self,
+        tokens: &[String],
+    ) -> Result<std::collections::HashMap<String, String>> {
+        PiiQueries::get_lookup_values(&self.pool, tokens).await
+    }
+
+    async fn list_pii_rows(
+        &self,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        PiiQueries::list_rows(&self.pool, table, after, limit).await
+    }
+
+    async fn update_pii_row(&self, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        PiiQueries::update_row(&self.pool, table, update).await
+    }
+
+    async fn touch_storage_blob(&self, blob: &StorageBlobRecord) -> Result<bool> {
+        BlobQueries::touch(&self.pool, blob).await
+    }
+
+    async fn claim_unreferenced_blobs(
+        &self,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        BlobQueries::claim_unreferenced(&self.pool, query).await
+    }
+
+    async fn delete_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<bool> {
+        BlobQueries::delete_claimed(&self.pool, storage_key, claim_id).await
+    }
+
+    async fn release_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<()> {
+        BlobQueries::release(&self.pool, storage_key, claim_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connection::TestSchema;
+    use super::*;
+    use chrono::Utc;
+
+    async fn test_db() -> (PostgresDatabase, TestSchema) {
+        let (pool, schema) = connection::test_pool().await;
+        let db = PostgresDatabase { pool };
+        db.initialize().await.expect("初始化表结构失败");
+        (db, schema)
+    }
+
+    fn sample_record(id: &str) -> PreviewRecord {
+        let now = Utc::now();
+        PreviewRecord {
+            id: id.to_string(),
+            user_id: "user-1".to_string(),
+            user_info_json: None,
+            file_name: "demo.pdf".to_string(),
+            ocr_text: String::new(),
+            theme_id: None,
+            evaluation_result: None,
+            preview_url: String::new(),
+            preview_view_url: None,
+            preview_download_url: None,
+            status: PreviewStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            third_party_request_id: Some("tp-1".to_string()),
+            queued_at: None,
+            processing_started_at: None,
+            retry_count: 0,
+            last_worker_id: None,
+            last_attempt_id: None,
+            failure_reason: None,
+            ocr_stderr_summary: None,
+            failure_context: None,
+            last_error_code: None,
+            slow_attachment_info_json: None,
+            callback_url: None,
+            callback_status: None,
+            callback_attempts: 0,
+            callback_successes: 0,
+            callback_failures: 0,
+            last_callback_at: None,
+            last_callback_status_code: None,
+            last_callback_response: None,
+            last_callback_error: None,
+            callback_payload: None,
+            next_callback_after: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn preview_record_round_trip() {
+        let (db, _schema) = test_db().await;
+
+        db.save_preview_record(&sample_record("p-1")).await.unwrap();
+        db.update_preview_status("p-1", PreviewStatus::Completed)
+            .await
+            .unwrap();
+
+        let loaded = db.get_preview_record("p-1").await.unwrap().unwrap();
+        assert_eq!(loaded.status, PreviewStatus::Completed);
+        let found = db
+            .find_preview_by_third_party_id("tp-1", "user-1")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|r| r.id), Some("p-1".to_string()));
+        let counts = db.get_preview_status_counts().await.unwrap();
+        assert_eq!(counts.completed, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn share_token_is_single_use() {
+        let (db, _schema) = test_db().await;
+
+        db.create_preview_share_token("p-1", "tok-1", "pdf", 60)
+            .await
+            .unwrap();
+        let first = db.consume_preview_share_token("tok-1").await.unwrap();
+        assert_eq!(first.map(|t| t.preview_id), Some("p-1".to_string()));
+        assert!(db
+            .consume_preview_share_token("tok-1")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn outbox_events_leave_pending_once_applied() {
+        let (db, _schema) = test_db().await;
+
+        db.enqueue_outbox_event(&NewOutboxEvent {
+            table_name: "preview_records".to_string(),
+            op_type: "upsert".to_string(),
+            pk_value: "p-1".to_string(),
+            idempotency_key: "k-1".to_string(),
+            payload: "{}".to_string(),
+        })
+        .await
+        .unwrap();
+
+        let pending = db.fetch_pending_outbox_events(10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        db.mark_outbox_event_applied(&pending[0].id).await.unwrap();
+        assert!(db.fetch_pending_outbox_events(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn default_admin_is_created() {
+        let (db, _schema) = test_db().await;
+
+        let admin = db.find_monitor_user_by_username("admin").await.unwrap();
+        assert!(admin.is_some_and(|u| u.is_active));
+        assert_eq!(db.count_active_monitor_admins().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::db::models::{MonitorSession, MonitorUser};
+
+const USER_COLUMNS: &str = "id, username, role, last_login_at, login_count, is_active";
+
+pub struct MonitorQueries;
+
+impl MonitorQueries {
+    pub async fn find_user_by_username(
+        pool: &PgPool,
+        username: &str,
+    ) -> Result<Option<MonitorUser>> {
+        let row = sqlx::query(&format!(
+            "SELECT {USER_COLUMNS} FROM monitor_users WHERE username = $1 AND is_active"
+        ))
+        .bind(username)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.as_ref().map(map_user))
+    }
+
+    pub async fn get_user_password_hash(pool: &PgPool, user_id: &str) -> Result<String> {
+        let hash = sqlx::query_scalar("SELECT password_hash FROM monitor_users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+        Ok(hash)
+    }
+
+    pub async fn find_user_by_id(pool: &PgPool, user_id: &str) -> Result<Option<MonitorUser>> {
+        let row = sqlx::query(&format!(
+            "SELECT {USER_COLUMNS} FROM monitor_users WHERE id = $1"
+        ))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.as_ref().map(map_user))
+    }
+
+    pub async fn list_users(pool: &PgPool) -> Result<Vec<MonitorUser>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {USER_COLUMNS} FROM monitor_users ORDER BY created_at ASC"
+        ))
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_user).collect())
+    }
+
+    pub async fn create_session(
+        pool: &PgPool,
+        session_id: &str,
+        user_id: &str,
+        ip: &str,
+        user_agent: &str,
+        created_at: &str,
+        expires_at: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO monitor_sessions (id, user_id, ip_address, user_agent, created_at, \
+             expires_at, last_activity, is_active) VALUES ($1, $2, $3, $4, $5, $6, $5, TRUE)",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(ip)
+        .bind(user_agent)
+        .bind(created_at)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_session_by_id(
+        pool: &PgPool,
+        session_id: &str,
+    ) -> Result<Option<MonitorSession>> {
+        let row = sqlx::query(
+            "SELECT s.id AS session_id, s.expires_at, s.ip_address, u.id, u.username, u.role, \
+             u.last_login_at, u.login_count, u.is_active \
+             FROM monitor_sessions s JOIN monitor_users u ON s.user_id = u.id \
+             WHERE s.id = $1 AND s.is_active",
+        )
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            let user = map_user(&row);
+            MonitorSession {
+                id: row.get("session_id"),
+                user_id: user.id.clone(),
+                user,
+                expires_at: row.get("expires_at"),
+                ip_address: row.get("ip_address"),
+            }
+        }))
+    }
+
+    pub async fn update_login_info(pool: &PgPool, user_id: &str, now: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE monitor_users SET last_login_at = $1, login_count = login_count + 1, \
+             updated_at = $1 WHERE id = $2",
+        )
+        .bind(now)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_session_activity(pool: &PgPool, session_id: &str, now: &str) -> Result<()> {
+        sqlx::query("UPDATE monitor_sessions SET last_activity = $1 WHERE id = $2")
+            .bind(now)
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_session(pool: &PgPool, session_id: &str) -> Result<()> {
+        sqlx::query("UPDATE monitor_sessions SET is_active = FALSE WHERE id = $1")
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn cleanup_expired_sessions(pool: &PgPool, now: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE monitor_sessions SET is_active = FALSE WHERE expires_at < $1 AND is_active",
+        )
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_active_sessions_count(pool: &PgPool, now: &str) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM monitor_sessions WHERE expires_at > $1 AND is_active",
+        )
+        .bind(now)
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    pub async fn create_user(
+        pool: &PgPool,
+        id: &str,
+        username: &str,
+        password_hash: &str,
+        role: &str,
+        now: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO monitor_users (id, username, password_hash, role, login_count, \
+             created_at, updated_at, is_active) VALUES ($1, $2, $3, $4, 0, $5, $5, TRUE)",
+        )
+        .bind(id)
+        .bind(username)
+        .bind(password_hash)
+        .bind(role)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_user_role(
+        pool: &PgPool,
+        user_id: &str,
+        role: &str,
+        now: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE monitor_users SET role = $1, updated_at = $2 WHERE id = $3")
+            .bind(role)
+            .bind(now)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_user_password(
+        pool: &PgPool,
+        user_id: &str,
+        password_hash: &str,
+        now: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE monitor_users SET password_hash = $1, updated_at = $2 WHERE id = $3")
+            .bind(password_hash)
+            .bind(now)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_user_active(
+        pool: &PgPool,
+        user_id: &str,
+        is_active: bool,
+        now: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE monitor_users SET is_active = $1, updated_at = $2 WHERE id = $3")
+            .bind(is_active)
+            .bind(now)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn count_active_admins(pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM monitor_users \
+             WHERE role IN ('super_admin', 'admin') AND is_active",
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+}
+
+fn map_user(row: &PgRow) -> MonitorUser {
+    MonitorUser {
+        id: row.get("id"),
+        username: row.get("username"),
+        role: row.get("role"),
+        last_login_at: row.get("last_login_at"),
+        login_count: row.get("login_count"),
+        is_active: row.get("is_active"),
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+
+use crate::db::traits::{PiiRow, PiiRowUpdate, PiiTable};
+
+pub struct PiiQueries;
+
+impl PiiQueries {
+    pub async fn save_lookup_value(pool: &PgPool, token: &str, ciphertext: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO pii_lookup_values (token, ciphertext, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(token) DO UPDATE SET
+                ciphertext = excluded.ciphertext,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(token)
+        .bind(ciphertext)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_lookup_values(
+        pool: &PgPool,
+        tokens: &[String],
+    ) -> Result<HashMap<String, String>> {
+        if tokens.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT token, ciphertext FROM pii_lookup_values WHERE token = ANY(",
+        );
+        builder.push_bind(tokens).push(")");
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("token")?, row.try_get("ciphertext")?)))
+            .collect()
+    }
+
+    pub async fn list_rows(
+        pool: &PgPool,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        let key = table.key_column();
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT CAST({} AS TEXT) AS pii_key, {} FROM {} WHERE TRUE",
+            key,
+            table.columns().join(", "),
+            table.table_name()
+        ));
+        if let Some(after) = after {
+            builder.push(format!(" AND {} > ", key));
+            if table.numeric_key() {
+                builder.push_bind(after.parse::<i64>()?);
+            } else {
+                builder.push_bind(after.to_string());
+            }
+        }
+        builder
+            .push(format!(" ORDER BY {} LIMIT ", key))
+            .push_bind(limit as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                let values = table
+                    .columns()
+                    .iter()
+                    .map(|column| row.try_get::<Option<String>, _>(*column))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PiiRow {
+                    key: row.try_get("pii_key")?,
+                    values,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn update_row(pool: &PgPool, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        if update.changes.is_empty() {
+            return Ok(false);
+        }
+        let mut builder =
+            QueryBuilder::<Postgres>::new(format!("UPDATE {} SET ", table.table_name()));
+        let mut separated = builder.separated(", ");
+        for change in &update.changes {
+            separated.push(format!("{} = ", change.column));
+            separated.push_bind_unseparated(change.value.clone());
+        }
+        builder.push(format!(" WHERE {} = ", table.key_column()));
+        if table.numeric_key() {
+            builder.push_bind(update.key.parse::<i64>()?);
+        } else {
+            builder.push_bind(update.key.clone());
+        }
+        for change in &update.changes {
+            builder
+                .push(format!(" AND {} LIKE ", change.column))
+                .push_bind(change.like_pattern())
+                .push(" ESCAPE '\\'");
+        }
+        let result = builder.build().execute(pool).await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::db::traits::PreviewBatchItemRecord;
+
+pub struct PreviewBatchQueries;
+
+impl PreviewBatchQueries {
+    pub async fn save_items(pool: &PgPool, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for item in items {
+            sqlx::query(
+                r#"
+                INSERT INTO preview_batch_items (
+                    batch_id, item_index, submitted_by, status, preview_id,
+                    third_party_request_id, error, created_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(&item.batch_id)
+            .bind(item.item_index)
+            .bind(&item.submitted_by)
+            .bind(item.status.as_str())
+            .bind(&item.preview_id)
+            .bind(&item.third_party_request_id)
+            .bind(&item.error)
+            .bind(item.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_items(pool: &PgPool, batch_id: &str) -> Result<Vec<PreviewBatchItemRecord>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, item_index, submitted_by, status, preview_id, \
+             third_party_request_id, error, created_at FROM preview_batch_items \
+             WHERE batch_id = $1 ORDER BY item_index",
+        )
+        .bind(batch_id)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_batch_item).collect()
+    }
+}
+
+fn map_batch_item(row: &PgRow) -> Result<PreviewBatchItemRecord> {
+    Ok(PreviewBatchItemRecord {
+        batch_id: row.try_get("batch_id")?,
+        item_index: row.try_get("item_index")?,
+        submitted_by: row.try_get("submitted_by")?,
+        status: row.try_get::<String, _>("status")?.parse()?,
+        preview_id: row.try_get("preview_id")?,
+        third_party_request_id: row.try_get("third_party_request_id")?,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
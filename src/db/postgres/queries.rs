@@ -0,0 +1,1456 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use std::str::FromStr;
+
+use crate::db::traits::*;
+
+const PREVIEW_REQUEST_COLUMNS: &str = "id, third_party_request_id, user_id, user_info_json, \
+    matter_id, matter_type, matter_name, channel, sequence_no, agent_info_json, \
+    subject_info_json, form_data_json, scene_data_json, material_data_json, latest_preview_id, \
+    latest_status, created_at, updated_at";
+
+const PREVIEW_RECORD_COLUMNS: &str = "id, user_id, user_info_json, file_name, ocr_text, \
+    theme_id, evaluation_result, preview_url, preview_view_url, preview_download_url, status, \
+    created_at, updated_at, third_party_request_id, queued_at, processing_started_at, \
+    retry_count, last_worker_id, last_attempt_id, failure_reason, ocr_stderr_summary, \
+    failure_context, last_error_code, slow_attachment_info_json, callback_url, callback_status, \
+    callback_attempts, callback_successes, callback_failures, last_callback_at, \
+    last_callback_status_code, last_callback_response, last_callback_error, callback_payload, \
+    next_callback_after";
+
+const MATERIAL_FILE_COLUMNS: &str = "id, preview_id, material_code, attachment_name, \
+    source_url, stored_original_key, stored_processed_keys, mime_type, size_bytes, \
+    checksum_sha256, ocr_text_key, ocr_text_length, status, error_message, created_at, updated_at";
+
+const CACHED_MATERIAL_COLUMNS: &str = "id, preview_id, material_code, attachment_index, token, \
+    local_path, upload_status, oss_key, last_error, file_size, checksum_sha256, created_at, \
+    updated_at";
+
+const MATTER_RULE_CONFIG_COLUMNS: &str = "id, matter_id, matter_name, spec_version, mode, \
+    rule_payload, status, description, checksum, updated_by, created_at, updated_at";
+
+const API_STATS_COLUMNS: &str = "id, endpoint, method, client_id, user_id, status_code, \
+    response_time_ms, request_size, response_size, error_message, created_at";
+
+fn parse_status(value: &str) -> Result<PreviewStatus> {
+    PreviewStatus::from_str(value).map_err(|_| anyhow!("unknown preview status: {}", value))
+}
+
+/// `Some(None)` 写入 NULL，`None` 表示不修改该列
+fn push_nullable<'a, T>(
+    builder: &mut QueryBuilder<'a, Postgres>,
+    column: &str,
+    value: &'a Option<Option<T>>,
+) where
+    T: 'a + sqlx::Encode<'a, Postgres> + sqlx::Type<Postgres> + Send + Clone,
+{
+    if let Some(value) = value {
+        builder.push(format!(", {column} = "));
+        match value {
+            Some(v) => builder.push_bind(v.clone()),
+            None => builder.push("NULL"),
+        };
+    }
+}
+
+pub struct PreviewRequestQueries;
+
+impl PreviewRequestQueries {
+    pub async fn upsert(pool: &PgPool, request: &PreviewRequestRecord) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO preview_requests ({PREVIEW_REQUEST_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT (id) DO UPDATE SET
+                third_party_request_id = EXCLUDED.third_party_request_id,
+                user_id = EXCLUDED.user_id,
+                user_info_json = EXCLUDED.user_info_json,
+                matter_id = EXCLUDED.matter_id,
+                matter_type = EXCLUDED.matter_type,
+                matter_name = EXCLUDED.matter_name,
+                channel = EXCLUDED.channel,
+                sequence_no = EXCLUDED.sequence_no,
+                agent_info_json = EXCLUDED.agent_info_json,
+                subject_info_json = EXCLUDED.subject_info_json,
+                form_data_json = EXCLUDED.form_data_json,
+                scene_data_json = EXCLUDED.scene_data_json,
+                material_data_json = EXCLUDED.material_data_json,
+                latest_preview_id = EXCLUDED.latest_preview_id,
+                latest_status = EXCLUDED.latest_status,
+                updated_at = EXCLUDED.updated_at
+            "#
+        ))
+        .bind(&request.id)
+        .bind(&request.third_party_request_id)
+        .bind(&request.user_id)
+        .bind(&request.user_info_json)
+        .bind(&request.matter_id)
+        .bind(&request.matter_type)
+        .bind(&request.matter_name)
+        .bind(&request.channel)
+        .bind(&request.sequence_no)
+        .bind(&request.agent_info_json)
+        .bind(&request.subject_info_json)
+        .bind(&request.form_data_json)
+        .bind(&request.scene_data_json)
+        .bind(&request.material_data_json)
+        .bind(&request.latest_preview_id)
+        .bind(request.latest_status.as_ref().map(|s| s.as_str()))
+        .bind(request.created_at)
+        .bind(request.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_latest_state(
+        pool: &PgPool,
+        request_id: &str,
+        latest_preview_id: Option<&str>,
+        latest_status: Option<PreviewStatus>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE preview_requests
+            SET latest_preview_id = $1, latest_status = $2, updated_at = now()
+            WHERE id = $3
+            "#,
+        )
+        .bind(latest_preview_id)
+        .bind(latest_status.map(|s| s.as_str()))
+        .bind(request_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(pool: &PgPool, id: &str) -> Result<Option<PreviewRequestRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {PREVIEW_REQUEST_COLUMNS} FROM preview_requests WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(Self::row_to_record).transpose()
+    }
+
+    pub async fn get_by_third_party(
+        pool: &PgPool,
+        third_party_id: &str,
+    ) -> Result<Option<PreviewRequestRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {PREVIEW_REQUEST_COLUMNS} FROM preview_requests \
+             WHERE third_party_request_id = $1"
+        ))
+        .bind(third_party_id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(Self::row_to_record).transpose()
+    }
+
+    pub async fn list(
+        pool: &PgPool,
+        filter: &PreviewRequestFilter,
+    ) -> Result<Vec<PreviewRequestRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {PREVIEW_REQUEST_COLUMNS} FROM preview_requests WHERE 1=1"
+        ));
+
+        if let Some(user_id) = &filter.user_id {
+            builder.push(" AND user_id = ").push_bind(user_id);
+        }
+        if let Some(matter_id) = &filter.matter_id {
+            builder.push(" AND matter_id = ").push_bind(matter_id);
+        }
+        if let Some(channel) = &filter.channel {
+            builder.push(" AND channel = ").push_bind(channel);
+        }
+        if let Some(sequence_no) = &filter.sequence_no {
+            builder.push(" AND sequence_no = ").push_bind(sequence_no);
+        }
+        if let Some(third_party) = &filter.third_party_request_id {
+            builder
+                .push(" AND third_party_request_id = ")
+                .push_bind(third_party);
+        }
+        if let Some(status) = &filter.latest_status {
+            builder
+                .push(" AND latest_status = ")
+                .push_bind(status.as_str());
+        }
+        if let Some(start) = filter.created_from {
+            builder.push(" AND created_at >= ").push_bind(start);
+        }
+        if let Some(end) = filter.created_to {
+            builder.push(" AND created_at <= ").push_bind(end);
+        }
+        if let Some(search) = &filter.search {
+            let pattern = format!("%{}%", search);
+            builder
+                .push(" AND (id LIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR third_party_request_id LIKE ")
+                .push_bind(pattern)
+                .push(")");
+        }
+
+        builder.push(" ORDER BY updated_at DESC");
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(limit as i64);
+        }
+        if let Some(offset) = filter.offset {
+            builder.push(" OFFSET ").push_bind(offset as i64);
+        }
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(Self::row_to_record).collect()
+    }
+
+    fn row_to_record(row: &PgRow) -> Result<PreviewRequestRecord> {
+        let latest_status = row
+            .try_get::<Option<String>, _>("latest_status")?
+            .as_deref()
+            .map(parse_status)
+            .transpose()?;
+
+        Ok(PreviewRequestRecord {
+            id: row.try_get("id")?,
+            third_party_request_id: row.try_get("third_party_request_id")?,
+            user_id: row.try_get("user_id")?,
+            user_info_json: row.try_get("user_info_json")?,
+            matter_id: row.try_get("matter_id")?,
+            matter_type: row.try_get("matter_type")?,
+            matter_name: row.try_get("matter_name")?,
+            channel: row.try_get("channel")?,
+            sequence_no: row.try_get("sequence_no")?,
+            agent_info_json: row.try_get("agent_info_json")?,
+            subject_info_json: row.try_get("subject_info_json")?,
+            form_data_json: row.try_get("form_data_json")?,
+            scene_data_json: row.try_get("scene_data_json")?,
+            material_data_json: row.try_get("material_data_json")?,
+            latest_preview_id: row.try_get("latest_preview_id")?,
+            latest_status,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+pub struct PreviewQueries;
+
+impl PreviewQueries {
+    pub async fn save_record(pool: &PgPool, record: &PreviewRecord) -> Result<()> {
+        Self::write_record(pool, record, "").await
+    }
+
+    /// 主键已存在时整条覆盖，保留记录自带的时间戳
+    pub async fn replace_record(pool: &PgPool, record: &PreviewRecord) -> Result<()> {
+        let assignments = PREVIEW_RECORD_COLUMNS
+            .split(',')
+            .map(str::trim)
+            .filter(|column| *column != "id")
+            .map(|column| format!("{column} = EXCLUDED.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::write_record(
+            pool,
+            record,
+            &format!("ON CONFLICT (id) DO UPDATE SET {assignments}"),
+        )
+        .await
+    }
+
+    async fn write_record(pool: &PgPool, record: &PreviewRecord, on_conflict: &str) -> Result<()> {
+        let placeholders = (1..=35)
+            .map(|i| format!("${i}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!(
+            "INSERT INTO preview_records ({PREVIEW_RECORD_COLUMNS}) VALUES ({placeholders}) {on_conflict}"
+        ))
+        .bind(&record.id)
+        .bind(&record.user_id)
+        .bind(&record.user_info_json)
+        .bind(&record.file_name)
+        .bind(&record.ocr_text)
+        .bind(&record.theme_id)
+        .bind(&record.evaluation_result)
+        .bind(&record.preview_url)
+        .bind(&record.preview_view_url)
+        .bind(&record.preview_download_url)
+        .bind(record.status.as_str())
+        .bind(record.created_at)
+        .bind(record.updated_at)
+        .bind(&record.third_party_request_id)
+        .bind(record.queued_at)
+        .bind(record.processing_started_at)
+        .bind(record.retry_count)
+        .bind(&record.last_worker_id)
+        .bind(&record.last_attempt_id)
+        .bind(&record.failure_reason)
+        .bind(&record.ocr_stderr_summary)
+        .bind(&record.failure_context)
+        .bind(&record.last_error_code)
+        .bind(&record.slow_attachment_info_json)
+        .bind(&record.callback_url)
+        .bind(&record.callback_status)
+        .bind(record.callback_attempts)
+        .bind(record.callback_successes)
+        .bind(record.callback_failures)
+        .bind(record.last_callback_at)
+        .bind(record.last_callback_status_code)
+        .bind(&record.last_callback_response)
+        .bind(&record.last_callback_error)
+        .bind(&record.callback_payload)
+        .bind(record.next_callback_after)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(pool: &PgPool, id: &str) -> Result<Option<PreviewRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {PREVIEW_RECORD_COLUMNS} FROM preview_records WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(Self::row_to_record).transpose()
+    }
+
+    pub async fn update_status(pool: &PgPool, id: &str, status: PreviewStatus) -> Result<()> {
+        let sql = match status {
+            PreviewStatus::Pending => {
+                r#"
+                UPDATE preview_records
+                SET status = $1, updated_at = now(), queued_at = NULL, processing_started_at = NULL,
+                    retry_count = 0, last_worker_id = NULL, last_attempt_id = NULL
+                WHERE id = $2
+                "#
+            }
+            PreviewStatus::Queued => {
+                r#"
+                UPDATE preview_records
+                SET status = $1, updated_at = now(), queued_at = now(), processing_started_at = NULL
+                WHERE id = $2
+                "#
+            }
+            PreviewStatus::Processing => {
+                r#"
+                UPDATE preview_records
+                SET status = $1, updated_at = now(), processing_started_at = now(),
+                    retry_count = retry_count + 1
+                WHERE id = $2
+                "#
+            }
+            PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled => {
+                "UPDATE preview_records SET status = $1, updated_at = now() WHERE id = $2"
+            }
+        };
+
+        sqlx::query(sql)
+            .bind(status.as_str())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_processing(
+        pool: &PgPool,
+        id: &str,
+        worker_id: &str,
+        attempt_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE preview_records
+            SET status = 'processing',
+                updated_at = now(),
+                processing_started_at = now(),
+                retry_count = retry_count + 1,
+                last_worker_id = $1,
+                last_attempt_id = $2
+            WHERE id = $3
+            "#,
+        )
+        .bind(worker_id)
+        .bind(attempt_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_evaluation_result(
+        pool: &PgPool,
+        id: &str,
+        evaluation_result: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE preview_records SET evaluation_result = $1, updated_at = now() WHERE id = $2",
+        )
+        .bind(evaluation_result)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_artifacts(
+        pool: &PgPool,
+        id: &str,
+        file_name: &str,
+        preview_url: &str,
+        preview_view_url: Option<&str>,
+        preview_download_url: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE preview_records
+            SET file_name = $1, preview_url = $2, preview_view_url = $3,
+                preview_download_url = $4, updated_at = now()
+            WHERE id = $5
+            "#,
+        )
+        .bind(file_name)
+        .bind(preview_url)
+        .bind(preview_view_url)
+        .bind(preview_download_url)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_with_filter(
+        pool: &PgPool,
+        filter: &PreviewFilter,
+    ) -> Result<Vec<PreviewRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {PREVIEW_RECORD_COLUMNS} FROM preview_records WHERE 1=1"
+        ));
+
+        if let Some(user_id) = &filter.user_id {
+            builder.push(" AND user_id = ").push_bind(user_id);
+        }
+        if let Some(status) = &filter.status {
+            builder.push(" AND status = ").push_bind(status.as_str());
+        }
+        if let Some(theme_id) = &filter.theme_id {
+            builder.push(" AND theme_id = ").push_bind(theme_id);
+        }
+        if let Some(tp_id) = &filter.third_party_request_id {
+            builder
+                .push(" AND third_party_request_id = ")
+                .push_bind(tp_id);
+        }
+        if let Some(worker_id) = &filter.worker_id {
+            builder.push(" AND last_worker_id = ").push_bind(worker_id);
+        }
+        if let Some(start) = filter.start_date {
+            builder.push(" AND created_at >= ").push_bind(start);
+        }
+        if let Some(end) = filter.end_date {
+            builder.push(" AND created_at <= ").push_bind(end);
+        }
+
+        builder.push(" ORDER BY created_at DESC");
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(limit as i64);
+        }
+        if let Some(offset) = filter.offset {
+            builder.push(" OFFSET ").push_bind(offset as i64);
+        }
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(Self::row_to_record).collect()
+    }
+
+    pub async fn count_by_status(pool: &PgPool) -> Result<PreviewStatusCounts> {
+        let rows =
+            sqlx::query("SELECT status, COUNT(*) AS cnt FROM preview_records GROUP BY status")
+                .fetch_all(pool)
+                .await?;
+
+        let mut counts = PreviewStatusCounts::default();
+        for row in rows {
+            let status =
+                parse_status(&row.get::<String, _>("status")).unwrap_or(PreviewStatus::Pending);
+            let add = row.get::<i64, _>("cnt").max(0) as u64;
+            counts.total += add;
+            match status {
+                PreviewStatus::Completed => counts.completed += add,
+                PreviewStatus::Processing => counts.processing += add,
+                PreviewStatus::Failed => counts.failed += add,
+                PreviewStatus::Pending => counts.pending += add,
+                PreviewStatus::Queued => counts.queued += add,
+                PreviewStatus::Cancelled => counts.cancelled += add,
+            }
+        }
+        Ok(counts)
+    }
+
+    pub async fn find_by_third_party_id(
+        pool: &PgPool,
+        third_party_id: &str,
+        user_id: &str,
+    ) -> Result<Option<PreviewRecord>> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {PREVIEW_RECORD_COLUMNS} FROM preview_records
+            WHERE user_id = $1 AND third_party_request_id = $2
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        ))
+        .bind(user_id)
+        .bind(third_party_id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(Self::row_to_record).transpose()
+    }
+
+    pub async fn update_callback_state(
+        pool: &PgPool,
+        update: &PreviewCallbackUpdate,
+    ) -> Result<()> {
+        let mut builder =
+            QueryBuilder::<Postgres>::new("UPDATE preview_records SET updated_at = now()");
+
+        push_nullable(&mut builder, "callback_url", &update.callback_url);
+        push_nullable(&mut builder, "callback_status", &update.callback_status);
+        if let Some(attempts) = update.callback_attempts {
+            builder.push(", callback_attempts = ").push_bind(attempts);
+        }
+        if let Some(successes) = update.callback_successes {
+            builder.push(", callback_successes = ").push_bind(successes);
+        }
+        if let Some(failures) = update.callback_failures {
+            builder.push(", callback_failures = ").push_bind(failures);
+        }
+        push_nullable(&mut builder, "last_callback_at", &update.last_callback_at);
+        push_nullable(
+            &mut builder,
+            "last_callback_status_code",
+            &update.last_callback_status_code,
+        );
+        push_nullable(
+            &mut builder,
+            "last_callback_response",
+            &update.last_callback_response,
+        );
+        push_nullable(
+            &mut builder,
+            "last_callback_error",
+            &update.last_callback_error,
+        );
+        push_nullable(&mut builder, "callback_payload", &update.callback_payload);
+        push_nullable(
+            &mut builder,
+            "next_callback_after",
+            &update.next_callback_after,
+        );
+
+        builder.push(" WHERE id = ").push_bind(&update.preview_id);
+        builder.build().execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn update_failure_context(
+        pool: &PgPool,
+        update: &PreviewFailureUpdate,
+    ) -> Result<()> {
+        let mut builder =
+            QueryBuilder::<Postgres>::new("UPDATE preview_records SET updated_at = now()");
+
+        push_nullable(&mut builder, "failure_reason", &update.failure_reason);
+        push_nullable(&mut builder, "failure_context", &update.failure_context);
+        push_nullable(&mut builder, "last_error_code", &update.last_error_code);
+        push_nullable(
+            &mut builder,
+            "slow_attachment_info_json",
+            &update.slow_attachment_info_json,
+        );
+        push_nullable(
+            &mut builder,
+            "ocr_stderr_summary",
+            &update.ocr_stderr_summary,
+        );
+
+        builder.push(" WHERE id = ").push_bind(&update.preview_id);
+        builder.build().execute(pool).await?;
+        Ok(())
+    }
+
+    pub async fn list_due_callbacks(pool: &PgPool, limit: u32) -> Result<Vec<PreviewRecord>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {PREVIEW_RECORD_COLUMNS} FROM preview_records
+            WHERE callback_url IS NOT NULL
+              AND callback_url <> ''
+              AND callback_payload IS NOT NULL
+              AND callback_status IN ('scheduled', 'retrying')
+              AND (next_callback_after IS NULL OR next_callback_after <= now())
+            ORDER BY COALESCE(next_callback_after, updated_at) ASC
+            LIMIT $1
+            "#
+        ))
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(Self::row_to_record).collect()
+    }
+
+    fn row_to_record(row: &PgRow) -> Result<PreviewRecord> {
+        Ok(PreviewRecord {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            user_info_json: row.try_get("user_info_json")?,
+            file_name: row.try_get("file_name")?,
+            ocr_text: row.try_get("ocr_text")?,
+            theme_id: row.try_get("theme_id")?,
+            evaluation_result: row.try_get("evaluation_result")?,
+            preview_url: row.try_get("preview_url")?,
+            preview_view_url: row.try_get("preview_view_url")?,
+            preview_download_url: row.try_get("preview_download_url")?,
+            status: parse_status(&row.try_get::<String, _>("status")?)
+                .unwrap_or(PreviewStatus::Pending),
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            third_party_request_id: row.try_get("third_party_request_id")?,
+            queued_at: row.try_get("queued_at")?,
+            processing_started_at: row.try_get("processing_started_at")?,
+            retry_count: row.try_get("retry_count")?,
+            last_worker_id: row.try_get("last_worker_id")?,
+            last_attempt_id: row.try_get("last_attempt_id")?,
+            failure_reason: row.try_get("failure_reason")?,
+            ocr_stderr_summary: row.try_get("ocr_stderr_summary")?,
+            failure_context: row.try_get("failure_context")?,
+            last_error_code: row.try_get("last_error_code")?,
+            slow_attachment_info_json: row.try_get("slow_attachment_info_json")?,
+            callback_url: row.try_get("callback_url")?,
+            callback_status: row.try_get("callback_status")?,
+            callback_attempts: row.try_get("callback_attempts")?,
+            callback_successes: row.try_get("callback_successes")?,
+            callback_failures: row.try_get("callback_failures")?,
+            last_callback_at: row.try_get("last_callback_at")?,
+            last_callback_status_code: row.try_get("last_callback_status_code")?,
+            last_callback_response: row.try_get("last_callback_response")?,
+            last_callback_error: row.try_get("last_callback_error")?,
+            callback_payload: row.try_get("callback_payload")?,
+            next_callback_after: row.try_get("next_callback_after")?,
+        })
+    }
+}
+
+pub struct MaterialResultQueries;
+
+impl MaterialResultQueries {
+    /// 同一预审的结果整体替换，在事务内先删后插
+    pub async fn replace(
+        pool: &PgPool,
+        preview_id: &str,
+        records: &[PreviewMaterialResultRecord],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM preview_material_results WHERE preview_id = $1")
+            .bind(preview_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO preview_material_results (
+                    id, preview_id, material_code, material_name, status, status_code,
+                    processing_status, issues_count, warnings_count, attachments_json,
+                    summary_json, schema_version, created_at, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 1, $12, $13)
+                "#,
+            )
+            .bind(&record.id)
+            .bind(preview_id)
+            .bind(&record.material_code)
+            .bind(&record.material_name)
+            .bind(&record.status)
+            .bind(record.status_code)
+            .bind(&record.processing_status)
+            .bind(record.issues_count)
+            .bind(record.warnings_count)
+            .bind(&record.attachments_json)
+            .bind(&record.summary_json)
+            .bind(record.created_at)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+pub struct RuleResultQueries;
+
+impl RuleResultQueries {
+    pub async fn replace(
+        pool: &PgPool,
+        preview_id: &str,
+        records: &[PreviewRuleResultRecord],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM preview_rule_results WHERE preview_id = $1")
+            .bind(preview_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO preview_rule_results (
+                    id, preview_id, material_result_id, material_code, rule_id, rule_code,
+                    rule_name, engine, severity, status, message, suggestions_json,
+                    evidence_json, extra_json, created_at, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                "#,
+            )
+            .bind(&record.id)
+            .bind(preview_id)
+            .bind(&record.material_result_id)
+            .bind(&record.material_code)
+            .bind(&record.rule_id)
+            .bind(&record.rule_code)
+            .bind(&record.rule_name)
+            .bind(&record.engine)
+            .bind(&record.severity)
+            .bind(&record.status)
+            .bind(&record.message)
+            .bind(&record.suggestions_json)
+            .bind(&record.evidence_json)
+            .bind(&record.extra_json)
+            .bind(record.created_at)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+pub struct CachedMaterialQueries;
+
+impl CachedMaterialQueries {
+    pub async fn upsert(pool: &PgPool, record: &CachedMaterialRecord) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO cached_materials ({CACHED_MATERIAL_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (id) DO UPDATE SET
+                preview_id = EXCLUDED.preview_id,
+                material_code = EXCLUDED.material_code,
+                attachment_index = EXCLUDED.attachment_index,
+                token = EXCLUDED.token,
+                local_path = EXCLUDED.local_path,
+                upload_status = EXCLUDED.upload_status,
+                oss_key = EXCLUDED.oss_key,
+                last_error = EXCLUDED.last_error,
+                file_size = EXCLUDED.file_size,
+                checksum_sha256 = EXCLUDED.checksum_sha256,
+                updated_at = EXCLUDED.updated_at
+            "#
+        ))
+        .bind(&record.id)
+        .bind(&record.preview_id)
+        .bind(&record.material_code)
+        .bind(record.attachment_index)
+        .bind(&record.token)
+        .bind(&record.local_path)
+        .bind(record.upload_status.as_str())
+        .bind(&record.oss_key)
+        .bind(&record.last_error)
+        .bind(record.file_size)
+        .bind(&record.checksum_sha256)
+        .bind(record.created_at)
+        .bind(record.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        id: &str,
+        status: CachedMaterialStatus,
+        oss_key: Option<&str>,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE cached_materials
+            SET upload_status = $1, oss_key = $2, last_error = $3, updated_at = now()
+            WHERE id = $4
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(oss_key)
+        .bind(last_error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(
+        pool: &PgPool,
+        filter: &CachedMaterialFilter,
+    ) -> Result<Vec<CachedMaterialRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {CACHED_MATERIAL_COLUMNS} FROM cached_materials WHERE 1=1"
+        ));
+        if let Some(preview_id) = &filter.preview_id {
+            builder.push(" AND preview_id = ").push_bind(preview_id);
+        }
+        if let Some(status) = &filter.status {
+            builder
+                .push(" AND upload_status = ")
+                .push_bind(status.as_str());
+        }
+        builder.push(" ORDER BY updated_at DESC");
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(limit as i64);
+        }
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(CachedMaterialRecord {
+                    id: row.try_get("id")?,
+                    preview_id: row.try_get("preview_id")?,
+                    material_code: row.try_get("material_code")?,
+                    attachment_index: row.try_get("attachment_index")?,
+                    token: row.try_get("token")?,
+                    local_path: row.try_get("local_path")?,
+                    upload_status: CachedMaterialStatus::from_str(
+                        &row.try_get::<String, _>("upload_status")?,
+                    ),
+                    oss_key: row.try_get("oss_key")?,
+                    last_error: row.try_get("last_error")?,
+                    file_size: row.try_get("file_size")?,
+                    checksum_sha256: row.try_get("checksum_sha256")?,
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_by_id(pool: &PgPool, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM cached_materials WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_by_preview(pool: &PgPool, preview_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM cached_materials WHERE preview_id = $1")
+            .bind(preview_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct MaterialFileQueries;
+
+impl MaterialFileQueries {
+    pub async fn insert(pool: &PgPool, rec: &MaterialFileRecord) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO preview_material_files ({MATERIAL_FILE_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#
+        ))
+        .bind(&rec.id)
+        .bind(&rec.preview_id)
+        .bind(&rec.material_code)
+        .bind(&rec.attachment_name)
+        .bind(&rec.source_url)
+        .bind(&rec.stored_original_key)
+        .bind(&rec.stored_processed_keys)
+        .bind(&rec.mime_type)
+        .bind(rec.size_bytes)
+        .bind(&rec.checksum_sha256)
+        .bind(&rec.ocr_text_key)
+        .bind(rec.ocr_text_length)
+        .bind(&rec.status)
+        .bind(&rec.error_message)
+        .bind(rec.created_at)
+        .bind(rec.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE preview_material_files
+            SET status = $1, error_message = $2, updated_at = now()
+            WHERE id = $3
+            "#,
+        )
+        .bind(status)
+        .bind(error.unwrap_or(""))
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_processing(
+        pool: &PgPool,
+        id: &str,
+        processed_keys_json: Option<&str>,
+        ocr_text_key: Option<&str>,
+        ocr_text_length: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE preview_material_files
+            SET stored_processed_keys = COALESCE($1, stored_processed_keys),
+                ocr_text_key = COALESCE($2, ocr_text_key),
+                ocr_text_length = COALESCE($3, ocr_text_length),
+                updated_at = now()
+            WHERE id = $4
+            "#,
+        )
+        .bind(processed_keys_json)
+        .bind(ocr_text_key)
+        .bind(ocr_text_length)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(
+        pool: &PgPool,
+        filter: &MaterialFileFilter,
+    ) -> Result<Vec<MaterialFileRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {MATERIAL_FILE_COLUMNS} FROM preview_material_files WHERE 1=1"
+        ));
+        if let Some(preview_id) = &filter.preview_id {
+            builder.push(" AND preview_id = ").push_bind(preview_id);
+        }
+        if let Some(material_code) = &filter.material_code {
+            builder
+                .push(" AND material_code = ")
+                .push_bind(material_code);
+        }
+        builder.push(" ORDER BY created_at ASC");
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(MaterialFileRecord {
+                    id: row.try_get("id")?,
+                    preview_id: row.try_get("preview_id")?,
+                    material_code: row.try_get("material_code")?,
+                    attachment_name: row.try_get("attachment_name")?,
+                    source_url: row.try_get("source_url")?,
+                    stored_original_key: row.try_get("stored_original_key")?,
+                    stored_processed_keys: row.try_get("stored_processed_keys")?,
+                    mime_type: row.try_get("mime_type")?,
+                    size_bytes: row.try_get("size_bytes")?,
+                    checksum_sha256: row.try_get("checksum_sha256")?,
+                    ocr_text_key: row.try_get("ocr_text_key")?,
+                    ocr_text_length: row.try_get("ocr_text_length")?,
+                    status: row.try_get("status")?,
+                    error_message: row.try_get("error_message")?,
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+pub struct MatterRuleConfigQueries;
+
+impl MatterRuleConfigQueries {
+    pub async fn upsert(pool: &PgPool, config: &MatterRuleConfigRecord) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO matter_rule_configs ({MATTER_RULE_CONFIG_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (matter_id) DO UPDATE SET
+                id = EXCLUDED.id,
+                matter_name = EXCLUDED.matter_name,
+                spec_version = EXCLUDED.spec_version,
+                mode = EXCLUDED.mode,
+                rule_payload = EXCLUDED.rule_payload,
+                status = EXCLUDED.status,
+                description = EXCLUDED.description,
+                checksum = EXCLUDED.checksum,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#
+        ))
+        .bind(&config.id)
+        .bind(&config.matter_id)
+        .bind(&config.matter_name)
+        .bind(&config.spec_version)
+        .bind(&config.mode)
+        .bind(&config.rule_payload)
+        .bind(&config.status)
+        .bind(&config.description)
+        .bind(&config.checksum)
+        .bind(&config.updated_by)
+        .bind(config.created_at)
+        .bind(config.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_matter_id(
+        pool: &PgPool,
+        matter_id: &str,
+    ) -> Result<Option<MatterRuleConfigRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {MATTER_RULE_CONFIG_COLUMNS} FROM matter_rule_configs WHERE matter_id = $1"
+        ))
+        .bind(matter_id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(Self::row_to_record).transpose()
+    }
+
+    pub async fn list(pool: &PgPool, status: Option<&str>) -> Result<Vec<MatterRuleConfigRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {MATTER_RULE_CONFIG_COLUMNS} FROM matter_rule_configs WHERE 1=1"
+        ));
+        if let Some(status) = status {
+            builder.push(" AND status = ").push_bind(status);
+        }
+        builder.push(" ORDER BY updated_at DESC");
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(Self::row_to_record).collect()
+    }
+
+    fn row_to_record(row: &PgRow) -> Result<MatterRuleConfigRecord> {
+        Ok(MatterRuleConfigRecord {
+            id: row.try_get("id")?,
+            matter_id: row.try_get("matter_id")?,
+            matter_name: row.try_get("matter_name")?,
+            spec_version: row.try_get("spec_version")?,
+            mode: row.try_get("mode")?,
+            rule_payload: row.try_get("rule_payload")?,
+            status: row.try_get("status")?,
+            description: row.try_get("description")?,
+            checksum: row.try_get("checksum")?,
+            updated_by: row.try_get("updated_by")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+pub struct TaskPayloadQueries;
+
+impl TaskPayloadQueries {
+    pub async fn upsert(pool: &PgPool, preview_id: &str, payload: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO preview_task_payloads (preview_id, payload, created_at, updated_at)
+            VALUES ($1, $2, now(), now())
+            ON CONFLICT (preview_id) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(preview_id)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load(pool: &PgPool, preview_id: &str) -> Result<Option<String>> {
+        let payload = sqlx::query_scalar::<_, String>(
+            "SELECT payload FROM preview_task_payloads WHERE preview_id = $1",
+        )
+        .bind(preview_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(payload)
+    }
+
+    pub async fn delete(pool: &PgPool, preview_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM preview_task_payloads WHERE preview_id = $1")
+            .bind(preview_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct ApiStatsQueries;
+
+impl ApiStatsQueries {
+    pub async fn save_stats(pool: &PgPool, stats: &ApiStats) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO api_stats ({API_STATS_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#
+        ))
+        .bind(&stats.id)
+        .bind(&stats.endpoint)
+        .bind(&stats.method)
+        .bind(&stats.client_id)
+        .bind(&stats.user_id)
+        .bind(stats.status_code as i32)
+        .bind(stats.response_time_ms as i64)
+        .bind(stats.request_size as i64)
+        .bind(stats.response_size as i64)
+        .bind(&stats.error_message)
+        .bind(stats.created_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_stats_with_filter(
+        pool: &PgPool,
+        filter: &StatsFilter,
+    ) -> Result<Vec<ApiStats>> {
+        let mut builder = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {API_STATS_COLUMNS} FROM api_stats WHERE 1=1"
+        ));
+        Self::push_filter(&mut builder, filter);
+        builder.push(" ORDER BY created_at DESC");
+        if let Some(limit) = filter.limit {
+            builder.push(" LIMIT ").push_bind(limit as i64);
+        }
+        if let Some(offset) = filter.offset {
+            builder.push(" OFFSET ").push_bind(offset as i64);
+        }
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(ApiStats {
+                    id: row.try_get("id")?,
+                    endpoint: row.try_get("endpoint")?,
+                    method: row.try_get("method")?,
+                    client_id: row.try_get("client_id")?,
+                    user_id: row.try_get("user_id")?,
+                    status_code: row.try_get::<i32, _>("status_code")? as u16,
+                    response_time_ms: row.try_get::<i64, _>("response_time_ms")? as u32,
+                    request_size: row.try_get::<i64, _>("request_size")? as u32,
+                    response_size: row.try_get::<i64, _>("response_size")? as u32,
+                    error_message: row.try_get("error_message")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_summary(pool: &PgPool, filter: &StatsFilter) -> Result<ApiSummary> {
+        // SUM(BIGINT) 在 PostgreSQL 中为 NUMERIC，统一转回 BIGINT 读取
+        let mut builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT
+                COUNT(*) AS total_calls,
+                COUNT(*) FILTER (WHERE status_code >= 200 AND status_code < 300) AS success_calls,
+                COUNT(*) FILTER (WHERE status_code >= 400) AS failed_calls,
+                AVG(response_time_ms)::DOUBLE PRECISION AS avg_response_time_ms,
+                COALESCE(SUM(request_size), 0)::BIGINT AS total_request_size,
+                COALESCE(SUM(response_size), 0)::BIGINT AS total_response_size
+            FROM api_stats WHERE 1=1
+            "#,
+        );
+        Self::push_filter(&mut builder, filter);
+
+        let row = builder.build().fetch_one(pool).await?;
+        Ok(ApiSummary {
+            total_calls: row.get::<i64, _>("total_calls") as u64,
+            success_calls: row.get::<i64, _>("success_calls") as u64,
+            failed_calls: row.get::<i64, _>("failed_calls") as u64,
+            avg_response_time_ms: row
+                .get::<Option<f64>, _>("avg_response_time_ms")
+                .unwrap_or(0.0),
+            total_request_size: row.get::<i64, _>("total_request_size") as u64,
+            total_response_size: row.get::<i64, _>("total_response_size") as u64,
+        })
+    }
+
+    fn push_filter(builder: &mut QueryBuilder<'_, Postgres>, filter: &StatsFilter) {
+        if let Some(endpoint) = &filter.endpoint {
+            builder.push(" AND endpoint = ").push_bind(endpoint.clone());
+        }
+        if let Some(client_id) = &filter.client_id {
+            builder
+                .push(" AND client_id = ")
+                .push_bind(client_id.clone());
+        }
+        if let Some(user_id) = &filter.user_id {
+            builder.push(" AND user_id = ").push_bind(user_id.clone());
+        }
+        if let Some(start_date) = filter.start_date {
+            builder.push(" AND created_at >= ").push_bind(start_date);
+        }
+        if let Some(end_date) = filter.end_date {
+            builder.push(" AND created_at <= ").push_bind(end_date);
+        }
+    }
+}
+
+pub struct OutboxQueries;
+
+impl OutboxQueries {
+    pub async fn insert(pool: &PgPool, event: &NewOutboxEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO db_outbox (
+                table_name, op_type, pk_value, idempotency_key, payload,
+                created_at, applied_at, retries, last_error
+            ) VALUES ($1, $2, $3, $4, $5, now(), NULL, 0, NULL)
+            ON CONFLICT (idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(&event.table_name)
+        .bind(&event.op_type)
+        .bind(&event.pk_value)
+        .bind(&event.idempotency_key)
+        .bind(&event.payload)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn fetch_pending(pool: &PgPool, limit: u32) -> Result<Vec<OutboxEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, table_name, op_type, pk_value, idempotency_key, payload,
+                   created_at, applied_at, retries, last_error
+            FROM db_outbox
+            WHERE applied_at IS NULL
+            ORDER BY created_at ASC, id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(OutboxEvent {
+                    id: row.try_get::<i64, _>("id")?.to_string(),
+                    table_name: row.try_get("table_name")?,
+                    op_type: row.try_get("op_type")?,
+                    pk_value: row.try_get("pk_value")?,
+                    idempotency_key: row.try_get("idempotency_key")?,
+                    payload: row.try_get("payload")?,
+                    created_at: row.try_get("created_at")?,
+                    applied_at: row.try_get("applied_at")?,
+                    retries: row.try_get("retries")?,
+                    last_error: row.try_get("last_error")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn mark_applied(pool: &PgPool, event_id: &str) -> Result<()> {
+        sqlx::query("UPDATE db_outbox SET applied_at = now(), last_error = NULL WHERE id = $1")
+            .bind(Self::parse_id(event_id)?)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_failed(pool: &PgPool, event_id: &str, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE db_outbox
+            SET retries = retries + 1, last_error = $1, applied_at = NULL
+            WHERE id = $2
+            "#,
+        )
+        .bind(error)
+        .bind(Self::parse_id(event_id)?)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    fn parse_id(event_id: &str) -> Result<i64> {
+        event_id
+            .parse::<i64>()
+            .map_err(|e| anyhow!("无效的Outbox事件ID: {}", e))
+    }
+}
+
+pub struct DedupQueries;
+
+impl DedupQueries {
+    /// 首次出现的指纹直接放行；重复次数达到上限且已有预审时复用该预审。
+    /// 行锁保证并发提交同一指纹时计数不丢失
+    pub async fn check_and_update(
+        pool: &PgPool,
+        fingerprint: &str,
+        preview_id: &str,
+        meta: &PreviewDedupMeta,
+        limit: i32,
+    ) -> Result<PreviewDedupDecision> {
+        let mut tx = pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO preview_dedup (
+                fingerprint, first_preview_id, last_preview_id, user_id, matter_id,
+                third_party_request_id, payload_hash, repeat_count, last_seen_at, created_at
+            ) VALUES ($1, $2, $2, $3, $4, $5, $6, 1, now(), now())
+            ON CONFLICT (fingerprint) DO NOTHING
+            "#,
+        )
+        .bind(fingerprint)
+        .bind(preview_id)
+        .bind(&meta.user_id)
+        .bind(&meta.matter_id)
+        .bind(&meta.third_party_request_id)
+        .bind(&meta.payload_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        if inserted.rows_affected() > 0 {
+            tx.commit().await?;
+            return Ok(PreviewDedupDecision::Allowed { repeat_count: 1 });
+        }
+
+        let row = sqlx::query(
+            "SELECT last_preview_id, repeat_count FROM preview_dedup \
+             WHERE fingerprint = $1 FOR UPDATE",
+        )
+        .bind(fingerprint)
+        .fetch_one(&mut *tx)
+        .await?;
+        let last_preview_id: String = row
+            .try_get::<Option<String>, _>("last_preview_id")?
+            .unwrap_or_default();
+        let new_count = row.try_get::<i32, _>("repeat_count")?.saturating_add(1);
+        let reuse = new_count >= limit && !last_preview_id.is_empty();
+        let target_preview_id = if reuse {
+            last_preview_id
+        } else {
+            preview_id.to_string()
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE preview_dedup
+            SET last_preview_id = $1, repeat_count = $2, last_seen_at = now(),
+                payload_hash = $3, third_party_request_id = $4, user_id = $5, matter_id = $6
+            WHERE fingerprint = $7
+            "#,
+        )
+        .bind(&target_preview_id)
+        .bind(new_count)
+        .bind(&meta.payload_hash)
+        .bind(&meta.third_party_request_id)
+        .bind(&meta.user_id)
+        .bind(&meta.matter_id)
+        .bind(fingerprint)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(if reuse {
+            PreviewDedupDecision::ReuseExisting {
+                preview_id: target_preview_id,
+                repeat_count: new_count,
+            }
+        } else {
+            PreviewDedupDecision::Allowed {
+                repeat_count: new_count,
+            }
+        })
+    }
+}
+
+pub struct UserLoginQueries;
+
+impl UserLoginQueries {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert(
+        pool: &PgPool,
+        user_id: &str,
+        user_name: Option<&str>,
+        certificate_type: &str,
+        certificate_number: Option<&str>,
+        phone_number: Option<&str>,
+        email: Option<&str>,
+        organization_name: Option<&str>,
+        organization_code: Option<&str>,
+        login_type: &str,
+        login_time: &str,
+        client_ip: &str,
+        user_agent: &str,
+        referer: &str,
+        cookie_info: &str,
+        raw_data: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_login_records (
+                user_id, user_name, certificate_type, certificate_number,
+                phone_number, email, organization_name, organization_code,
+                login_type, login_time, client_ip, user_agent, referer,
+                cookie_info, raw_data, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#,
+        )
+        .bind(user_id)
+        .bind(user_name.unwrap_or(""))
+        .bind(certificate_type)
+        .bind(certificate_number.unwrap_or(""))
+        .bind(phone_number.unwrap_or(""))
+        .bind(email.unwrap_or(""))
+        .bind(organization_name.unwrap_or(""))
+        .bind(organization_code.unwrap_or(""))
+        .bind(login_type)
+        .bind(login_time)
+        .bind(client_ip)
+        .bind(user_agent)
+        .bind(referer)
+        .bind(cookie_info)
+        .bind(raw_data)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct HealthQueries;
+
+impl HealthQueries {
+    pub async fn check_health(pool: &PgPool) -> Result<bool> {
+        sqlx::query("SELECT 1")
+            .fetch_one(pool)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.into())
+    }
+}
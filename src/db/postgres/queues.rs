@@ -0,0 +1,193 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::db::traits::{MaterialDownloadQueueRecord, WorkerResultQueueRecord};
+
+const QUEUE_COLUMNS: &str =
+    "id, preview_id, payload, status, attempts, last_error, created_at, updated_at";
+
+/// worker 结果回传队列，同一预审只保留最新一条
+pub struct WorkerResultQueries;
+
+impl WorkerResultQueries {
+    pub async fn enqueue(pool: &PgPool, preview_id: &str, payload: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_results_queue (id, preview_id, payload, status, attempts)
+            VALUES ($1, $2, $3, 'pending', 0)
+            ON CONFLICT (preview_id) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                status = 'pending',
+                attempts = 0,
+                last_error = NULL,
+                updated_at = now()
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(preview_id)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn fetch_pending(pool: &PgPool, limit: u32) -> Result<Vec<WorkerResultQueueRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {QUEUE_COLUMNS} FROM worker_results_queue WHERE status = 'pending' \
+             ORDER BY created_at ASC LIMIT $1"
+        ))
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_worker_result).collect()
+    }
+
+    pub async fn get_by_preview_id(
+        pool: &PgPool,
+        preview_id: &str,
+    ) -> Result<Option<WorkerResultQueueRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {QUEUE_COLUMNS} FROM worker_results_queue WHERE preview_id = $1"
+        ))
+        .bind(preview_id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(map_worker_result).transpose()
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        update_queue_status(pool, "worker_results_queue", id, status, last_error).await
+    }
+}
+
+pub struct MaterialDownloadQueries;
+
+impl MaterialDownloadQueries {
+    pub async fn enqueue(pool: &PgPool, preview_id: &str, payload: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO material_download_queue (id, preview_id, payload, status, attempts) \
+             VALUES ($1, $2, $3, 'pending', 0)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(preview_id)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn fetch_pending(
+        pool: &PgPool,
+        limit: u32,
+    ) -> Result<Vec<MaterialDownloadQueueRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {QUEUE_COLUMNS} FROM material_download_queue WHERE status = 'pending' \
+             ORDER BY created_at ASC LIMIT $1"
+        ))
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(MaterialDownloadQueueRecord {
+                    id: row.try_get("id")?,
+                    preview_id: row.try_get("preview_id")?,
+                    payload: row.try_get("payload")?,
+                    status: row.try_get("status")?,
+                    attempts: row.try_get("attempts")?,
+                    last_error: row.try_get("last_error")?,
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        update_queue_status(pool, "material_download_queue", id, status, last_error).await
+    }
+
+    pub async fn update_payload(pool: &PgPool, id: &str, payload: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE material_download_queue SET payload = $1, updated_at = now() WHERE id = $2",
+        )
+        .bind(payload)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn cancel_pending(pool: &PgPool, preview_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE material_download_queue SET status = 'cancelled', updated_at = now() \
+             WHERE preview_id = $1 AND status = 'pending'",
+        )
+        .bind(preview_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn latest_payload(pool: &PgPool, preview_id: &str) -> Result<Option<String>> {
+        let payload = sqlx::query_scalar(
+            "SELECT payload FROM material_download_queue WHERE preview_id = $1 \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(preview_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(payload)
+    }
+}
+
+/// 带错误信息时累计尝试次数，与达梦实现一致
+async fn update_queue_status(
+    pool: &PgPool,
+    table: &str,
+    id: &str,
+    status: &str,
+    last_error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(&format!(
+        r#"
+        UPDATE {table}
+        SET status = $1,
+            last_error = COALESCE($2, last_error),
+            attempts = attempts + CASE WHEN $2 IS NULL THEN 0 ELSE 1 END,
+            updated_at = now()
+        WHERE id = $3
+        "#
+    ))
+    .bind(status)
+    .bind(last_error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn map_worker_result(row: &PgRow) -> Result<WorkerResultQueueRecord> {
+    Ok(WorkerResultQueueRecord {
+        id: row.try_get("id")?,
+        preview_id: row.try_get("preview_id")?,
+        payload: row.try_get("payload")?,
+        status: row.try_get("status")?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
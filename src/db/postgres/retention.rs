@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+
+use crate::db::traits::{
+    ArtifactPurge, LegalHoldRecord, LegalHoldUpdate, PurgeAuditFilter, PurgeAuditRecord,
+    RetentionArtifact, RetentionCandidate, RetentionCandidateQuery,
+};
+
+/// 各类产物在数据库中的清理语句，每条只绑定一个预审ID
+fn artifact_statements(artifact: RetentionArtifact) -> &'static [&'static str] {
+    match artifact {
+        RetentionArtifact::RawRequest => &[
+            "DELETE FROM preview_task_payloads WHERE preview_id = $1",
+            "UPDATE preview_requests SET user_info_json = NULL, agent_info_json = NULL, \
+             subject_info_json = NULL, form_data_json = NULL, scene_data_json = NULL, \
+             material_data_json = NULL WHERE latest_preview_id = $1",
+            "UPDATE preview_records SET user_info_json = NULL WHERE id = $1",
+            "UPDATE preview_material_files SET stored_original_key = '', source_url = NULL \
+             WHERE preview_id = $1",
+            "UPDATE cached_materials SET oss_key = NULL WHERE preview_id = $1",
+        ],
+        RetentionArtifact::OcrText => &[
+            "UPDATE preview_records SET ocr_text = '' WHERE id = $1",
+            "UPDATE preview_material_files SET ocr_text_key = NULL, ocr_text_length = NULL \
+             WHERE preview_id = $1",
+        ],
+        RetentionArtifact::PageImages => &[
+            "UPDATE preview_material_files SET stored_processed_keys = NULL WHERE preview_id = $1",
+        ],
+        RetentionArtifact::Reports => &[
+            "UPDATE preview_records SET evaluation_result = NULL, callback_payload = NULL \
+             WHERE id = $1",
+            "DELETE FROM preview_rule_results WHERE preview_id = $1",
+            "DELETE FROM preview_material_results WHERE preview_id = $1",
+        ],
+        RetentionArtifact::Logs => &[],
+    }
+}
+
+pub struct RetentionQueries;
+
+impl RetentionQueries {
+    pub async fn list_candidates(
+        pool: &PgPool,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, created_at, matter_id FROM (\
+             SELECT pr.id, pr.created_at, \
+             (SELECT req.matter_id FROM preview_requests req \
+              WHERE req.latest_preview_id = pr.id LIMIT 1) AS matter_id \
+             FROM preview_records pr \
+             WHERE NOT pr.legal_hold \
+             AND pr.status IN ('completed', 'failed', 'cancelled') \
+             AND pr.created_at < ",
+        );
+        builder
+            .push_bind(query.created_before)
+            .push(" AND COALESCE(pr.purged_artifacts, '') NOT LIKE ")
+            .push_bind(query.artifact.purged_marker_pattern())
+            .push(") c WHERE TRUE");
+
+        if let Some(matter_id) = &query.matter_id {
+            builder.push(" AND matter_id = ").push_bind(matter_id);
+        }
+        if !query.exclude_matters.is_empty() {
+            builder
+                .push(" AND (matter_id IS NULL OR NOT (matter_id = ANY(")
+                .push_bind(&query.exclude_matters)
+                .push(")))");
+        }
+        builder
+            .push(" ORDER BY created_at LIMIT ")
+            .push_bind(query.limit as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(RetentionCandidate {
+                    preview_id: row.try_get("id")?,
+                    matter_id: row.try_get("matter_id")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// 行锁下先标记再清理，法律保全中的预审不会被改动
+    pub async fn purge_preview_artifact(
+        pool: &PgPool,
+        purge: &ArtifactPurge,
+    ) -> Result<Option<i64>> {
+        let preview_id = purge
+            .preview_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("清理预审产物缺少 preview_id"))?;
+
+        let mut tx = pool.begin().await?;
+        let marked = sqlx::query(
+            r#"
+            UPDATE preview_records
+            SET purged_artifacts = CASE
+                WHEN COALESCE(purged_artifacts, '') LIKE $1 THEN purged_artifacts
+                ELSE COALESCE(purged_artifacts, ',') || $2
+            END
+            WHERE id = $3 AND NOT legal_hold
+            "#,
+        )
+        .bind(purge.artifact.purged_marker_pattern())
+        .bind(format!("{},", purge.artifact.as_str()))
+        .bind(preview_id)
+        .execute(&mut *tx)
+        .await?;
+        if marked.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let mut db_rows = 0i64;
+        for statement in artifact_statements(purge.artifact) {
+            let result = sqlx::query(statement)
+                .bind(preview_id)
+                .execute(&mut *tx)
+                .await?;
+            db_rows += result.rows_affected() as i64;
+        }
+        insert_audit(&mut tx, purge, db_rows).await?;
+        tx.commit().await?;
+        Ok(Some(db_rows))
+    }
+
+    pub async fn purge_log_records(
+        pool: &PgPool,
+        before: DateTime<Utc>,
+        purge: &ArtifactPurge,
+    ) -> Result<i64> {
+        let mut tx = pool.begin().await?;
+        let mut db_rows = 0i64;
+        for statement in [
+            "DELETE FROM api_stats WHERE created_at < $1",
+            "DELETE FROM user_login_records WHERE created_at < $1",
+        ] {
+            let result = sqlx::query(statement)
+                .bind(before)
+                .execute(&mut *tx)
+                .await?;
+            db_rows += result.rows_affected() as i64;
+        }
+        if db_rows > 0 {
+            insert_audit(&mut tx, purge, db_rows).await?;
+        }
+        tx.commit().await?;
+        Ok(db_rows)
+    }
+
+    pub async fn set_legal_hold(pool: &PgPool, update: &LegalHoldUpdate) -> Result<bool> {
+        let result = if update.hold {
+            sqlx::query(
+                "UPDATE preview_records SET legal_hold = TRUE, legal_hold_reason = $1, \
+                 legal_hold_by = $2, legal_hold_at = now() WHERE id = $3",
+            )
+            .bind(&update.reason)
+            .bind(&update.updated_by)
+            .bind(&update.preview_id)
+            .execute(pool)
+            .await?
+        } else {
+            sqlx::query(
+                "UPDATE preview_records SET legal_hold = FALSE, legal_hold_reason = NULL, \
+                 legal_hold_by = NULL, legal_hold_at = NULL WHERE id = $1",
+            )
+            .bind(&update.preview_id)
+            .execute(pool)
+            .await?
+        };
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_legal_holds(pool: &PgPool) -> Result<Vec<LegalHoldRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, legal_hold_reason, legal_hold_by, legal_hold_at, created_at \
+             FROM preview_records WHERE legal_hold ORDER BY legal_hold_at DESC",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(LegalHoldRecord {
+                    preview_id: row.try_get("id")?,
+                    reason: row.try_get("legal_hold_reason")?,
+                    held_by: row.try_get("legal_hold_by")?,
+                    held_at: row.try_get("legal_hold_at")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn list_audit(
+        pool: &PgPool,
+        filter: &PurgeAuditFilter,
+    ) -> Result<Vec<PurgeAuditRecord>> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, preview_id, matter_id, artifact, storage_keys, db_rows, reason, \
+             purged_at FROM retention_purge_log WHERE TRUE",
+        );
+        if let Some(preview_id) = &filter.preview_id {
+            builder.push(" AND preview_id = ").push_bind(preview_id);
+        }
+        if let Some(artifact) = filter.artifact {
+            builder
+                .push(" AND artifact = ")
+                .push_bind(artifact.as_str());
+        }
+        builder
+            .push(" ORDER BY purged_at DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(50) as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset.unwrap_or(0) as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(map_audit).collect()
+    }
+}
+
+async fn insert_audit(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    purge: &ArtifactPurge,
+    db_rows: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO retention_purge_log (
+            id, preview_id, matter_id, artifact, storage_keys, db_rows, reason, purged_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, now())
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&purge.preview_id)
+    .bind(&purge.matter_id)
+    .bind(purge.artifact.as_str())
+    .bind(serde_json::to_string(&purge.storage_keys)?)
+    .bind(db_rows)
+    .bind(&purge.reason)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn map_audit(row: &PgRow) -> Result<PurgeAuditRecord> {
+    Ok(PurgeAuditRecord {
+        id: row.try_get("id")?,
+        preview_id: row.try_get("preview_id")?,
+        matter_id: row.try_get("matter_id")?,
+        artifact: row.try_get("artifact")?,
+        storage_keys: serde_json::from_str(&row.try_get::<String, _>("storage_keys")?)?,
+        db_rows: row.try_get("db_rows")?,
+        reason: row.try_get("reason")?,
+        purged_at: row.try_get("purged_at")?,
+    })
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::time::Instant;
+
+use crate::db::migrations::{Migration, MigrationDialect};
+use crate::db::traits::AppliedMigration;
+
+pub struct SchemaMigrationQueries;
+
+impl SchemaMigrationQueries {
+    pub async fn ensure_table(pool: &PgPool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                execution_ms BIGINT NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<AppliedMigration>> {
+        Self::ensure_table(pool).await?;
+        let rows = sqlx::query(
+            "SELECT version, name, checksum, applied_at, execution_ms FROM schema_migrations \
+             ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_applied_migration).collect()
+    }
+
+    /// PostgreSQL 的 DDL 可以回滚，语句与登记在同一事务内
+    pub async fn apply(pool: &PgPool, migration: &Migration) -> Result<()> {
+        Self::ensure_table(pool).await?;
+        let started = Instant::now();
+        let mut tx = pool.begin().await?;
+        for statement in migration.statements(MigrationDialect::Postgres) {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, execution_ms) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum(MigrationDialect::Postgres))
+        .bind(started.elapsed().as_millis() as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn map_applied_migration(row: &PgRow) -> Result<AppliedMigration> {
+    Ok(AppliedMigration {
+        version: row.try_get("version")?,
+        name: row.try_get("name")?,
+        checksum: row.try_get("checksum")?,
+        applied_at: row.try_get("applied_at")?,
+        execution_ms: row.try_get("execution_ms")?,
+    })
+}
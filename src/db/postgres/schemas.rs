@@ -0,0 +1,584 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+use crate::api::monitor_auth::DEFAULT_MONITOR_ADMIN_PASSWORD;
+
+/// 表结构与 SQLite 保持一致；业务时间用 TIMESTAMPTZ，监控账户相关时间沿用调用方传入的字符串
+pub struct SchemaManager;
+
+impl SchemaManager {
+    fn default_monitor_admin_password_hash() -> String {
+        #[cfg(feature = "monitoring")]
+        {
+            use bcrypt::{hash, DEFAULT_COST};
+            hash(DEFAULT_MONITOR_ADMIN_PASSWORD, DEFAULT_COST)
+                .unwrap_or_else(|_| DEFAULT_MONITOR_ADMIN_PASSWORD.to_string())
+        }
+        #[cfg(not(feature = "monitoring"))]
+        {
+            DEFAULT_MONITOR_ADMIN_PASSWORD.to_string()
+        }
+    }
+
+    pub async fn create_all_tables(pool: &PgPool) -> Result<()> {
+        Self::create_preview_requests_table(pool).await?;
+        Self::create_preview_records_table(pool).await?;
+        Self::create_preview_results_tables(pool).await?;
+        Self::create_preview_task_payloads_table(pool).await?;
+        Self::create_api_stats_table(pool).await?;
+        Self::create_preview_material_files_table(pool).await?;
+        Self::create_cached_materials_table(pool).await?;
+        Self::create_matter_rule_configs_table(pool).await?;
+        Self::create_monitor_tables(pool).await?;
+        Self::create_user_login_records_table(pool).await?;
+        Self::create_db_outbox_table(pool).await?;
+        Self::create_work_queue_tables(pool).await?;
+        Self::create_share_and_dedup_tables(pool).await?;
+        Self::create_dead_letters_table(pool).await?;
+        Self::create_worker_controls_table(pool).await?;
+        Self::create_preview_batch_items_table(pool).await?;
+        Ok(())
+    }
+
+    async fn execute_all(pool: &PgPool, statements: &[&str]) -> Result<()> {
+        for statement in statements {
+            sqlx::query(statement).execute(pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn create_preview_requests_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_requests (
+                    id TEXT PRIMARY KEY,
+                    third_party_request_id TEXT UNIQUE,
+                    user_id TEXT NOT NULL,
+                    user_info_json TEXT,
+                    matter_id TEXT NOT NULL,
+                    matter_type TEXT NOT NULL,
+                    matter_name TEXT NOT NULL,
+                    channel TEXT NOT NULL,
+                    sequence_no TEXT NOT NULL,
+                    agent_info_json TEXT,
+                    subject_info_json TEXT,
+                    form_data_json TEXT,
+                    scene_data_json TEXT,
+                    material_data_json TEXT,
+                    latest_preview_id TEXT,
+                    latest_status TEXT,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_preview_requests_user_id ON preview_requests(user_id)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_requests_matter ON preview_requests(matter_id)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_requests_created_at ON preview_requests(created_at)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_preview_records_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_records (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    user_info_json TEXT,
+                    file_name TEXT NOT NULL,
+                    ocr_text TEXT NOT NULL,
+                    theme_id TEXT,
+                    evaluation_result TEXT,
+                    preview_url TEXT NOT NULL,
+                    preview_view_url TEXT,
+                    preview_download_url TEXT,
+                    status TEXT NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL,
+                    third_party_request_id TEXT,
+                    queued_at TIMESTAMPTZ,
+                    processing_started_at TIMESTAMPTZ,
+                    retry_count INTEGER NOT NULL DEFAULT 0,
+                    last_worker_id TEXT,
+                    last_attempt_id TEXT,
+                    failure_reason TEXT,
+                    ocr_stderr_summary TEXT,
+                    failure_context TEXT,
+                    last_error_code TEXT,
+                    slow_attachment_info_json TEXT,
+                    callback_url TEXT,
+                    callback_status TEXT,
+                    callback_attempts INTEGER NOT NULL DEFAULT 0,
+                    callback_successes INTEGER NOT NULL DEFAULT 0,
+                    callback_failures INTEGER NOT NULL DEFAULT 0,
+                    last_callback_at TIMESTAMPTZ,
+                    last_callback_status_code INTEGER,
+                    last_callback_response TEXT,
+                    last_callback_error TEXT,
+                    callback_payload TEXT,
+                    next_callback_after TIMESTAMPTZ
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_user_id ON preview_records(user_id)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_third_party_id ON preview_records(third_party_request_id)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_status_retry ON preview_records(status, retry_count)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_created_at ON preview_records(created_at)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_worker ON preview_records(last_worker_id)",
+                "CREATE INDEX IF NOT EXISTS idx_preview_records_callback_status ON preview_records(callback_status, next_callback_after)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_preview_results_tables(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_material_results (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    material_code TEXT NOT NULL,
+                    material_name TEXT,
+                    status TEXT NOT NULL,
+                    status_code INTEGER NOT NULL,
+                    processing_status TEXT,
+                    issues_count INTEGER NOT NULL DEFAULT 0,
+                    warnings_count INTEGER NOT NULL DEFAULT 0,
+                    attachments_json TEXT,
+                    summary_json TEXT,
+                    schema_version INTEGER NOT NULL DEFAULT 1,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_material_results_preview ON preview_material_results(preview_id, material_code)",
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_rule_results (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    material_result_id TEXT,
+                    material_code TEXT,
+                    rule_id TEXT,
+                    rule_code TEXT,
+                    rule_name TEXT,
+                    engine TEXT,
+                    severity TEXT,
+                    status TEXT,
+                    message TEXT,
+                    suggestions_json TEXT,
+                    evidence_json TEXT,
+                    extra_json TEXT,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_rule_results_preview ON preview_rule_results(preview_id)",
+                "CREATE INDEX IF NOT EXISTS idx_rule_results_material ON preview_rule_results(material_result_id)",
+                "CREATE INDEX IF NOT EXISTS idx_rule_results_code ON preview_rule_results(rule_code)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_preview_task_payloads_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_task_payloads (
+                    preview_id TEXT PRIMARY KEY,
+                    payload TEXT NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_task_payloads_updated_at ON preview_task_payloads(updated_at)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_api_stats_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS api_stats (
+                    id TEXT PRIMARY KEY,
+                    endpoint TEXT NOT NULL,
+                    method TEXT NOT NULL,
+                    client_id TEXT,
+                    user_id TEXT,
+                    status_code INTEGER NOT NULL,
+                    response_time_ms BIGINT NOT NULL,
+                    request_size BIGINT NOT NULL,
+                    response_size BIGINT NOT NULL,
+                    error_message TEXT,
+                    created_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_api_stats_created_at ON api_stats(created_at)",
+                "CREATE INDEX IF NOT EXISTS idx_api_stats_endpoint ON api_stats(endpoint)",
+                "CREATE INDEX IF NOT EXISTS idx_api_stats_client_id ON api_stats(client_id)",
+                "CREATE INDEX IF NOT EXISTS idx_api_stats_user_id ON api_stats(user_id)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_preview_material_files_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_material_files (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    material_code TEXT NOT NULL,
+                    attachment_name TEXT,
+                    source_url TEXT,
+                    stored_original_key TEXT NOT NULL,
+                    stored_processed_keys TEXT,
+                    mime_type TEXT,
+                    size_bytes BIGINT,
+                    checksum_sha256 TEXT,
+                    ocr_text_key TEXT,
+                    ocr_text_length BIGINT,
+                    status TEXT NOT NULL,
+                    error_message TEXT,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_material_files_preview ON preview_material_files(preview_id)",
+                "CREATE INDEX IF NOT EXISTS idx_material_files_material_code ON preview_material_files(material_code)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_cached_materials_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS cached_materials (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    material_code TEXT NOT NULL,
+                    attachment_index INTEGER NOT NULL DEFAULT 0,
+                    token TEXT NOT NULL,
+                    local_path TEXT NOT NULL,
+                    upload_status TEXT NOT NULL,
+                    oss_key TEXT,
+                    last_error TEXT,
+                    file_size BIGINT,
+                    checksum_sha256 TEXT,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_cached_materials_preview ON cached_materials(preview_id)",
+                "CREATE INDEX IF NOT EXISTS idx_cached_materials_status ON cached_materials(upload_status, preview_id)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_matter_rule_configs_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS matter_rule_configs (
+                    id TEXT PRIMARY KEY,
+                    matter_id TEXT NOT NULL UNIQUE,
+                    matter_name TEXT,
+                    spec_version TEXT NOT NULL,
+                    mode TEXT NOT NULL DEFAULT 'presentOnly',
+                    rule_payload TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'active',
+                    description TEXT,
+                    checksum TEXT,
+                    updated_by TEXT,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_matter_rule_configs_status ON matter_rule_configs(status)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_monitor_tables(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS monitor_users (
+                    id TEXT PRIMARY KEY,
+                    username TEXT NOT NULL UNIQUE,
+                    password_hash TEXT NOT NULL,
+                    role TEXT NOT NULL DEFAULT 'readonly',
+                    last_login_at TEXT,
+                    login_count BIGINT NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    is_active BOOLEAN NOT NULL DEFAULT TRUE
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_monitor_users_role ON monitor_users(role)",
+                r#"
+                CREATE TABLE IF NOT EXISTS monitor_sessions (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    ip_address TEXT,
+                    user_agent TEXT,
+                    created_at TEXT NOT NULL,
+                    expires_at TEXT NOT NULL,
+                    last_activity TEXT NOT NULL,
+                    is_active BOOLEAN NOT NULL DEFAULT TRUE
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_monitor_sessions_user_id ON monitor_sessions(user_id)",
+                "CREATE INDEX IF NOT EXISTS idx_monitor_sessions_expires_at ON monitor_sessions(expires_at)",
+            ],
+        )
+        .await?;
+        Self::init_default_admin_user(pool).await
+    }
+
+    async fn init_default_admin_user(pool: &PgPool) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO monitor_users (
+                id, username, password_hash, role, login_count, created_at, updated_at, is_active
+            ) VALUES ($1, 'admin', $2, 'admin', 0, $3, $3, TRUE)
+            ON CONFLICT (username) DO NOTHING
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(Self::default_monitor_admin_password_hash())
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        if inserted.rows_affected() > 0 {
+            tracing::info!(
+                "[ok] 默认监控管理员账户已创建: admin/{}",
+                DEFAULT_MONITOR_ADMIN_PASSWORD
+            );
+            tracing::warn!("[warn]  请在生产环境中修改默认密码！");
+        }
+        Ok(())
+    }
+
+    async fn create_user_login_records_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS user_login_records (
+                    id BIGSERIAL PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    user_name TEXT,
+                    certificate_type TEXT,
+                    certificate_number TEXT,
+                    phone_number TEXT,
+                    email TEXT,
+                    organization_name TEXT,
+                    organization_code TEXT,
+                    login_type TEXT NOT NULL,
+                    login_time TEXT NOT NULL,
+                    client_ip TEXT NOT NULL,
+                    user_agent TEXT NOT NULL,
+                    referer TEXT,
+                    cookie_info TEXT,
+                    raw_data TEXT,
+                    created_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_user_login_records_user_id ON user_login_records(user_id)",
+                "CREATE INDEX IF NOT EXISTS idx_user_login_records_created_at ON user_login_records(created_at)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_db_outbox_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS db_outbox (
+                    id BIGSERIAL PRIMARY KEY,
+                    table_name TEXT NOT NULL,
+                    op_type TEXT NOT NULL,
+                    pk_value TEXT NOT NULL,
+                    idempotency_key TEXT NOT NULL UNIQUE,
+                    payload TEXT NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    applied_at TIMESTAMPTZ,
+                    retries INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_db_outbox_pending ON db_outbox(applied_at, created_at)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_work_queue_tables(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS worker_results_queue (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL UNIQUE,
+                    payload TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT,
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_wrq_status ON worker_results_queue(status, created_at)",
+                r#"
+                CREATE TABLE IF NOT EXISTS material_download_queue (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT,
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_mdq_status ON material_download_queue(status, created_at)",
+                "CREATE INDEX IF NOT EXISTS idx_mdq_preview ON material_download_queue(preview_id)",
+                r#"
+                CREATE TABLE IF NOT EXISTS material_download_cache (
+                    url TEXT PRIMARY KEY,
+                    token TEXT NOT NULL,
+                    expires_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_mdc_expires_at ON material_download_cache(expires_at)",
+            ],
+        )
+        .await
+    }
+
+    async fn create_share_and_dedup_tables(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_share_tokens (
+                    token TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    format TEXT NOT NULL DEFAULT 'pdf',
+                    expires_at TIMESTAMPTZ NOT NULL,
+                    used_at TIMESTAMPTZ,
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_pst_preview_id ON preview_share_tokens(preview_id)",
+                "CREATE INDEX IF NOT EXISTS idx_pst_expires_at ON preview_share_tokens(expires_at)",
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_dedup (
+                    fingerprint TEXT PRIMARY KEY,
+                    first_preview_id TEXT,
+                    last_preview_id TEXT,
+                    user_id TEXT,
+                    matter_id TEXT,
+                    third_party_request_id TEXT,
+                    payload_hash TEXT,
+                    repeat_count INTEGER NOT NULL DEFAULT 1,
+                    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_preview_dedup_last_seen ON preview_dedup(last_seen_at)",
+            ],
+        )
+        .await
+    }
+
+    pub(crate) async fn create_dead_letters_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[
+                r#"
+                CREATE TABLE IF NOT EXISTS preview_dead_letters (
+                    id TEXT PRIMARY KEY,
+                    preview_id TEXT NOT NULL,
+                    queue TEXT NOT NULL,
+                    source TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    failure_reason TEXT NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT,
+                    status TEXT NOT NULL DEFAULT 'dead',
+                    resolved_by TEXT,
+                    resolved_at TIMESTAMPTZ,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL
+                )
+                "#,
+                "CREATE INDEX IF NOT EXISTS idx_dead_letters_status ON preview_dead_letters(status, created_at)",
+                "CREATE INDEX IF NOT EXISTS idx_dead_letters_preview ON preview_dead_letters(preview_id)",
+            ],
+        )
+        .await
+    }
+
+    pub(crate) async fn create_worker_controls_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[r#"
+            CREATE TABLE IF NOT EXISTS worker_controls (
+                worker_id TEXT PRIMARY KEY,
+                state TEXT NOT NULL DEFAULT 'active',
+                reason TEXT,
+                updated_by TEXT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )
+            "#],
+        )
+        .await
+    }
+
+    pub(crate) async fn create_preview_batch_items_table(pool: &PgPool) -> Result<()> {
+        Self::execute_all(
+            pool,
+            &[r#"
+            CREATE TABLE IF NOT EXISTS preview_batch_items (
+                batch_id TEXT NOT NULL,
+                item_index INTEGER NOT NULL,
+                submitted_by TEXT NOT NULL,
+                status TEXT NOT NULL,
+                preview_id TEXT,
+                third_party_request_id TEXT,
+                error TEXT,
+                created_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (batch_id, item_index)
+            )
+            "#],
+        )
+        .await
+    }
+}
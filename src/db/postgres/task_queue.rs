@@ -0,0 +1,410 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::db::traits::{
+    shard_queue_key_pattern, validate_queue_table_name, NewQueuedTask, QueuedTaskRecord,
+    QueuedTaskStats, TaskClaimRequest,
+};
+
+const TASK_COLUMNS: &str = "id, queue, preview_id, payload, status, attempts, lease_owner, \
+    lease_token, lease_expires_at, visible_at, last_error, created_at, updated_at";
+
+pub struct TaskQueueQueries;
+
+impl TaskQueueQueries {
+    pub async fn ensure_table(pool: &PgPool, table: &str) -> Result<()> {
+        validate_queue_table_name(table)?;
+        sqlx::query(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                id TEXT PRIMARY KEY,
+                queue TEXT NOT NULL,
+                preview_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                lease_owner TEXT,
+                lease_token TEXT,
+                lease_expires_at BIGINT,
+                visible_at BIGINT NOT NULL,
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )
+            "#
+        ))
+        .execute(pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_{table}_preview ON {table}(queue, preview_id)"
+        ))
+        .execute(pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_{table}_claim ON {table}(queue, status, visible_at)"
+        ))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 同一预审已在队列中时刷新载荷并重置为待处理；正在被领取的任务保持不变
+    pub async fn enqueue(pool: &PgPool, table: &str, task: &NewQueuedTask) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO {table} (id, queue, preview_id, payload, status, attempts, visible_at)
+            VALUES ($1, $2, $3, $4, 'pending', 0, $5)
+            ON CONFLICT (queue, preview_id) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                status = 'pending',
+                attempts = 0,
+                visible_at = EXCLUDED.visible_at,
+                last_error = NULL,
+                updated_at = now()
+            WHERE {table}.status <> 'leased'
+            "#
+        ))
+        .bind(&task.id)
+        .bind(&task.queue)
+        .bind(&task.preview_id)
+        .bind(&task.payload)
+        .bind(task.visible_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn claim(
+        pool: &PgPool,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'failed', last_error = COALESCE(last_error, '租约过期且已达最大尝试次数'),
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = now()
+            WHERE queue = $1 AND status = 'leased' AND lease_expires_at <= $2 AND attempts >= $3
+            "#
+        ))
+        .bind(&claim.queue)
+        .bind(claim.now)
+        .bind(claim.max_attempts)
+        .execute(pool)
+        .await?;
+
+        // SKIP LOCKED 让并发消费者跳过彼此正在领取的行，而不是排队等待
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'leased', attempts = attempts + 1, lease_owner = $1, lease_token = $2,
+                lease_expires_at = $3, updated_at = now()
+            WHERE id IN (
+                SELECT id FROM {table}
+                WHERE queue = $4
+                  AND ((status = 'pending' AND visible_at <= $5)
+                    OR (status = 'leased' AND lease_expires_at <= $5))
+                ORDER BY visible_at ASC, created_at ASC
+                LIMIT $6
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {TASK_COLUMNS}
+            "#
+        ))
+        .bind(&claim.consumer_id)
+        .bind(&claim.lease_token)
+        .bind(claim.lease_expires_at)
+        .bind(&claim.queue)
+        .bind(claim.now)
+        .bind(claim.limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let mut tasks: Vec<QueuedTaskRecord> = rows.iter().map(map_task).collect::<Result<_>>()?;
+        tasks.sort_by_key(|task| task.visible_at);
+        Ok(tasks)
+    }
+
+    pub async fn extend_lease(
+        pool: &PgPool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET lease_expires_at = $1, updated_at = now()
+            WHERE id = $2 AND lease_token = $3 AND status = 'leased'
+            "#
+        ))
+        .bind(lease_expires_at)
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn complete(
+        pool: &PgPool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {table} WHERE id = $1 AND lease_token = $2"
+        ))
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn release(
+        pool: &PgPool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        let status = if retry_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = $1, visible_at = COALESCE($2, visible_at), last_error = $3,
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = now()
+            WHERE id = $4 AND lease_token = $5
+            "#
+        ))
+        .bind(status)
+        .bind(retry_at)
+        .bind(error)
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn return_task(
+        pool: &PgPool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'pending', attempts = GREATEST(attempts - 1, 0),
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = now()
+            WHERE id = $1 AND lease_token = $2
+            "#
+        ))
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn stats(pool: &PgPool, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        let rows = sqlx::query(&format!(
+            "SELECT status, COUNT(*) AS total FROM {table} WHERE queue = $1 GROUP BY status"
+        ))
+        .bind(queue)
+        .fetch_all(pool)
+        .await?;
+
+        let mut stats = QueuedTaskStats::default();
+        for row in rows {
+            let total = row.get::<i64, _>("total").max(0) as u64;
+            match row.get::<String, _>("status").as_str() {
+                "pending" => stats.pending = total,
+                "leased" => stats.leased = total,
+                "failed" => stats.failed = total,
+                _ => {}
+            }
+        }
+        Ok(stats)
+    }
+
+    pub async fn list_failed(
+        pool: &PgPool,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {TASK_COLUMNS} FROM {table}
+            WHERE queue = $1 AND status = 'failed'
+            ORDER BY updated_at ASC
+            LIMIT $2
+            "#
+        ))
+        .bind(queue)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_task).collect()
+    }
+
+    pub async fn delete(pool: &PgPool, table: &str, task_id: &str) -> Result<bool> {
+        let result = sqlx::query(&format!("DELETE FROM {table} WHERE id = $1"))
+            .bind(task_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn cancel_pending(
+        pool: &PgPool,
+        table: &str,
+        queue: &str,
+        preview_id: &str,
+    ) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+            DELETE FROM {table}
+            WHERE queue = $1 AND status = 'pending'
+              AND (preview_id = $2 OR preview_id LIKE $3 ESCAPE '\')
+            "#
+        ))
+        .bind(queue)
+        .bind(preview_id)
+        .bind(shard_queue_key_pattern(preview_id))
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn map_task(row: &PgRow) -> Result<QueuedTaskRecord> {
+    Ok(QueuedTaskRecord {
+        id: row.try_get("id")?,
+        queue: row.try_get("queue")?,
+        preview_id: row.try_get("preview_id")?,
+        payload: row.try_get("payload")?,
+        status: row.try_get("status")?,
+        attempts: row.try_get("attempts")?,
+        lease_owner: row.try_get("lease_owner")?,
+        lease_token: row.try_get("lease_token")?,
+        lease_expires_at: row.try_get("lease_expires_at")?,
+        visible_at: row.try_get("visible_at")?,
+        last_error: row.try_get("last_error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::postgres::connection::test_pool;
+
+    const TABLE: &str = "preview_queue";
+
+    fn new_task(preview_id: &str) -> NewQueuedTask {
+        NewQueuedTask {
+            id: format!("task-{}", preview_id),
+            queue: "preview".to_string(),
+            preview_id: preview_id.to_string(),
+            payload: "{}".to_string(),
+            visible_at: 100,
+        }
+    }
+
+    fn claim(consumer: &str, now: i64, limit: u32) -> TaskClaimRequest {
+        TaskClaimRequest {
+            queue: "preview".to_string(),
+            consumer_id: consumer.to_string(),
+            lease_token: format!("lease-{}-{}", consumer, now),
+            now,
+            lease_expires_at: now + 60,
+            max_attempts: 2,
+            limit,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn concurrent_claims_never_share_a_task() {
+        let (pool, _schema) = test_pool().await;
+        TaskQueueQueries::ensure_table(&pool, TABLE).await.unwrap();
+        for i in 0..20 {
+            TaskQueueQueries::enqueue(&pool, TABLE, &new_task(&format!("p{i}")))
+                .await
+                .unwrap();
+        }
+
+        let handles = (0..4).map(|c| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                TaskQueueQueries::claim(&pool, TABLE, &claim(&format!("c{c}"), 100, 10))
+                    .await
+                    .unwrap()
+            })
+        });
+        let mut claimed: Vec<String> = Vec::new();
+        for handle in handles.collect::<Vec<_>>() {
+            claimed.extend(handle.await.unwrap().into_iter().map(|t| t.id));
+        }
+        let total = claimed.len();
+        claimed.sort();
+        claimed.dedup();
+        assert_eq!(claimed.len(), total);
+        assert_eq!(total, 20);
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 POSTGRES_TEST_URL"]
+    async fn expired_lease_is_reclaimed_then_failed() {
+        let (pool, _schema) = test_pool().await;
+        TaskQueueQueries::ensure_table(&pool, TABLE).await.unwrap();
+        TaskQueueQueries::enqueue(&pool, TABLE, &new_task("p1"))
+            .await
+            .unwrap();
+
+        let first = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 100, 5))
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert!(TaskQueueQueries::claim(&pool, TABLE, &claim("b", 120, 5))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let reclaimed = TaskQueueQueries::claim(&pool, TABLE, &claim("b", 161, 5))
+            .await
+            .unwrap();
+        assert_eq!(reclaimed[0].attempts, 2);
+        let stale = first[0].lease_token.as_deref().unwrap();
+        assert!(!TaskQueueQueries::complete(&pool, TABLE, "task-p1", stale)
+            .await
+            .unwrap());
+
+        assert!(TaskQueueQueries::claim(&pool, TABLE, &claim("c", 300, 5))
+            .await
+            .unwrap()
+            .is_empty());
+        let stats = TaskQueueQueries::stats(&pool, TABLE, "preview")
+            .await
+            .unwrap();
+        assert_eq!(stats.failed, 1);
+    }
+}
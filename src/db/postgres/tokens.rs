@@ -0,0 +1,94 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::db::traits::{MaterialDownloadCacheEntry, PreviewShareTokenRecord};
+
+pub struct DownloadCacheQueries;
+
+impl DownloadCacheQueries {
+    pub async fn get(pool: &PgPool, url: &str) -> Result<Option<MaterialDownloadCacheEntry>> {
+        let row = sqlx::query(
+            "SELECT url, token, expires_at FROM material_download_cache \
+             WHERE url = $1 AND expires_at > now()",
+        )
+        .bind(url)
+        .fetch_optional(pool)
+        .await?;
+        row.map(|row| {
+            Ok(MaterialDownloadCacheEntry {
+                url: row.try_get("url")?,
+                token: row.try_get("token")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn upsert(pool: &PgPool, url: &str, token: &str, ttl_secs: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO material_download_cache (url, token, expires_at, updated_at)
+            VALUES ($1, $2, now() + make_interval(secs => $3), now())
+            ON CONFLICT (url) DO UPDATE SET
+                token = EXCLUDED.token,
+                expires_at = EXCLUDED.expires_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(url)
+        .bind(token)
+        .bind(ttl_secs as f64)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct ShareTokenQueries;
+
+impl ShareTokenQueries {
+    pub async fn create(
+        pool: &PgPool,
+        preview_id: &str,
+        token: &str,
+        format: &str,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO preview_share_tokens (token, preview_id, format, expires_at) \
+             VALUES ($1, $2, $3, now() + make_interval(secs => $4))",
+        )
+        .bind(token)
+        .bind(preview_id)
+        .bind(format)
+        .bind(ttl_secs as f64)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 单条 UPDATE ... RETURNING 完成核销，同一令牌并发使用时只有一个请求成功
+    pub async fn consume(pool: &PgPool, token: &str) -> Result<Option<PreviewShareTokenRecord>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE preview_share_tokens
+            SET used_at = now()
+            WHERE token = $1 AND used_at IS NULL AND expires_at > now()
+            RETURNING token, preview_id, format, expires_at, used_at
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+        row.map(|row| {
+            Ok(PreviewShareTokenRecord {
+                token: row.try_get("token")?,
+                preview_id: row.try_get("preview_id")?,
+                format: row.try_get("format")?,
+                expires_at: row.try_get("expires_at")?,
+                used_at: row.try_get("used_at")?,
+            })
+        })
+        .transpose()
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::db::traits::WorkerControlRecord;
+
+pub struct WorkerControlQueries;
+
+impl WorkerControlQueries {
+    pub async fn save(pool: &PgPool, record: &WorkerControlRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_controls (worker_id, state, reason, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (worker_id) DO UPDATE SET
+                state = EXCLUDED.state,
+                reason = EXCLUDED.reason,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&record.worker_id)
+        .bind(record.state.as_str())
+        .bind(&record.reason)
+        .bind(&record.updated_by)
+        .bind(record.updated_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<WorkerControlRecord>> {
+        let rows = sqlx::query(
+            "SELECT worker_id, state, reason, updated_by, updated_at FROM worker_controls \
+             ORDER BY worker_id",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_worker_control).collect()
+    }
+}
+
+fn map_worker_control(row: &PgRow) -> Result<WorkerControlRecord> {
+    Ok(WorkerControlRecord {
+        worker_id: row.try_get("worker_id")?,
+        state: row.try_get::<String, _>("state")?.parse()?,
+        reason: row.try_get("reason")?,
+        updated_by: row.try_get("updated_by")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
@@ -0,0 +1,465 @@
+//! 降级写入回放
+//!
+//! 主库不可用期间写入本地降级库的预审、结果、回调状态与 outbox 事件记入 `failover_journal`。
+//! 主库恢复后按实体回放：预审与请求记录读取降级库的最新快照整条写回，
+//! 状态不回退、`updated_at` 不早于主库时才覆盖，否则记为冲突保留主库数据。
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use super::sqlite::failover_journal::FailoverJournalQueries;
+use super::traits::{
+    Database, PreviewMaterialResultRecord, PreviewRuleResultRecord, PreviewStatus,
+};
+
+/// 每轮回放读取的条目上限
+const REPLAY_BATCH: u32 = 200;
+/// 单条回放失败达到该次数后转为冲突，不再阻塞切回主库
+const MAX_REPLAY_ATTEMPTS: i32 = 5;
+/// 一次恢复最多回放的轮数，回放期间仍可能有新的降级写入
+const MAX_REPLAY_ROUNDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalEntity {
+    PreviewRequest,
+    PreviewRecord,
+    MaterialResults,
+    RuleResults,
+    OutboxEvent,
+}
+
+impl JournalEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreviewRequest => "preview_request",
+            Self::PreviewRecord => "preview_record",
+            Self::MaterialResults => "material_results",
+            Self::RuleResults => "rule_results",
+            Self::OutboxEvent => "outbox_event",
+        }
+    }
+
+    /// 回放顺序：先请求与预审记录，结果依赖预审是否冲突，outbox 最后
+    fn replay_order(&self) -> u8 {
+        match self {
+            Self::PreviewRequest => 0,
+            Self::PreviewRecord => 1,
+            Self::MaterialResults => 2,
+            Self::RuleResults => 3,
+            Self::OutboxEvent => 4,
+        }
+    }
+}
+
+impl FromStr for JournalEntity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "preview_request" => Ok(Self::PreviewRequest),
+            "preview_record" => Ok(Self::PreviewRecord),
+            "material_results" => Ok(Self::MaterialResults),
+            "rule_results" => Ok(Self::RuleResults),
+            "outbox_event" => Ok(Self::OutboxEvent),
+            other => Err(anyhow!("未知的降级日志实体: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalStatus {
+    Pending,
+    Replayed,
+    Conflict,
+    Failed,
+}
+
+impl JournalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Replayed => "replayed",
+            Self::Conflict => "conflict",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for JournalStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "replayed" => Ok(Self::Replayed),
+            "conflict" => Ok(Self::Conflict),
+            "failed" => Ok(Self::Failed),
+            other => Err(anyhow!("未知的降级日志状态: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub entity: JournalEntity,
+    pub entity_key: String,
+    /// 没有读取接口的实体（结果列表）直接记录写入内容
+    #[serde(skip)]
+    pub payload: Option<String>,
+    pub status: JournalStatus,
+    /// 每次重新写入递增，回放结果只对读取时的版本生效
+    pub revision: i64,
+    pub attempts: i32,
+    pub detail: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    pub replayed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct JournalCounts {
+    pub pending: u64,
+    pub failed: u64,
+    pub conflict: u64,
+    pub replayed: u64,
+}
+
+impl JournalCounts {
+    pub fn open(&self) -> u64 {
+        self.pending + self.failed
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    /// 回放结束时没有待处理条目，可以切回主库
+    pub drained: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Resolution {
+    Apply,
+    Conflict(String),
+}
+
+enum Outcome {
+    Replayed(Option<String>),
+    Conflict(String),
+}
+
+/// 终态之间不分先后，由 `updated_at` 决定
+fn status_rank(status: Option<&PreviewStatus>) -> u8 {
+    match status {
+        None | Some(PreviewStatus::Pending) => 0,
+        Some(PreviewStatus::Queued) => 1,
+        Some(PreviewStatus::Processing) => 2,
+        Some(PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled) => 3,
+    }
+}
+
+fn resolve(
+    fallback: (Option<&PreviewStatus>, DateTime<Utc>),
+    primary: Option<(Option<&PreviewStatus>, DateTime<Utc>)>,
+) -> Resolution {
+    let Some((primary_status, primary_updated)) = primary else {
+        return Resolution::Apply;
+    };
+    let (fallback_status, fallback_updated) = fallback;
+    let describe = |status: Option<&PreviewStatus>| status.map_or("-", |s| s.as_str());
+
+    if status_rank(fallback_status) < status_rank(primary_status) {
+        return Resolution::Conflict(format!(
+            "主库状态 {} 领先于降级库 {}",
+            describe(primary_status),
+            describe(fallback_status)
+        ));
+    }
+    if fallback_updated < primary_updated {
+        return Resolution::Conflict(format!(
+            "主库更新于 {}，晚于降级库 {}",
+            primary_updated.to_rfc3339(),
+            fallback_updated.to_rfc3339()
+        ));
+    }
+    Resolution::Apply
+}
+
+/// 降级写入日志，存放在降级库自身
+pub struct FallbackJournal {
+    pool: SqlitePool,
+}
+
+impl FallbackJournal {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(
+        &self,
+        entity: JournalEntity,
+        entity_key: &str,
+        payload: Option<&str>,
+    ) -> Result<()> {
+        FailoverJournalQueries::record(&self.pool, entity, entity_key, payload).await
+    }
+
+    pub async fn counts(&self) -> Result<JournalCounts> {
+        FailoverJournalQueries::counts(&self.pool).await
+    }
+
+    pub async fn list(&self, status: JournalStatus, limit: u32) -> Result<Vec<JournalEntry>> {
+        FailoverJournalQueries::list_by_status(&self.pool, status, limit).await
+    }
+
+    /// 反复回放直到没有待处理条目或达到轮数上限
+    pub async fn drain(
+        &self,
+        primary: &Arc<dyn Database>,
+        fallback: &Arc<dyn Database>,
+    ) -> Result<ReplayReport> {
+        let mut report = ReplayReport::default();
+        for _ in 0..MAX_REPLAY_ROUNDS {
+            let mut entries = FailoverJournalQueries::list_open(&self.pool, REPLAY_BATCH).await?;
+            if entries.is_empty() {
+                report.drained = true;
+                break;
+            }
+            entries.sort_by_key(|entry| (entry.entity.replay_order(), entry.id));
+            let failed_before = report.failed;
+            self.replay_round(&entries, primary, fallback, &mut report)
+                .await?;
+            if report.failed > failed_before {
+                // 主库仍有错误，留待下一次健康检查，避免短时间内耗尽重试次数
+                break;
+            }
+        }
+        if !report.drained {
+            report.drained = self.counts().await?.open() == 0;
+        }
+        info!(
+            replayed = report.replayed,
+            conflicts = report.conflicts,
+            failed = report.failed,
+            drained = report.drained,
+            "[failover] 降级写入回放完成"
+        );
+        Ok(report)
+    }
+
+    async fn replay_round(
+        &self,
+        entries: &[JournalEntry],
+        primary: &Arc<dyn Database>,
+        fallback: &Arc<dyn Database>,
+        report: &mut ReplayReport,
+    ) -> Result<()> {
+        for entry in entries {
+            let outcome = self.replay_entry(entry, primary, fallback).await;
+
+            let (status, detail) = match outcome {
+                Ok(Outcome::Replayed(detail)) => {
+                    report.replayed += 1;
+                    (JournalStatus::Replayed, detail)
+                }
+                Ok(Outcome::Conflict(detail)) => {
+                    warn!(
+                        entity = entry.entity.as_str(),
+                        key = %entry.entity_key,
+                        detail = %detail,
+                        "[failover] 回放冲突，保留主库数据"
+                    );
+                    report.conflicts += 1;
+                    (JournalStatus::Conflict, Some(detail))
+                }
+                Err(err) if entry.attempts + 1 >= MAX_REPLAY_ATTEMPTS => {
+                    warn!(
+                        entity = entry.entity.as_str(),
+                        key = %entry.entity_key,
+                        error = %err,
+                        "[failover] 回放多次失败，转为冲突"
+                    );
+                    report.conflicts += 1;
+                    (
+                        JournalStatus::Conflict,
+                        Some(format!("回放失败 {} 次: {}", MAX_REPLAY_ATTEMPTS, err)),
+                    )
+                }
+                Err(err) => {
+                    report.failed += 1;
+                    (JournalStatus::Failed, Some(err.to_string()))
+                }
+            };
+
+            FailoverJournalQueries::resolve(
+                &self.pool,
+                entry.id,
+                entry.revision,
+                status,
+                detail.as_deref(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn replay_entry(
+        &self,
+        entry: &JournalEntry,
+        primary: &Arc<dyn Database>,
+        fallback: &Arc<dyn Database>,
+    ) -> Result<Outcome> {
+        let key = entry.entity_key.as_str();
+        match entry.entity {
+            JournalEntity::PreviewRequest => {
+                let Some(request) = fallback.get_preview_request(key).await? else {
+                    return Ok(Outcome::Conflict(
+                        "降级库缺少该请求记录，降级期间的更新未保留".to_string(),
+                    ));
+                };
+                let current = primary.get_preview_request(key).await?;
+                match resolve(
+                    (request.latest_status.as_ref(), request.updated_at),
+                    current
+                        .as_ref()
+                        .map(|current| (current.latest_status.as_ref(), current.updated_at)),
+                ) {
+                    Resolution::Apply => {
+                        primary.save_preview_request(&request).await?;
+                        Ok(Outcome::Replayed(None))
+                    }
+                    Resolution::Conflict(detail) => Ok(Outcome::Conflict(detail)),
+                }
+            }
+            JournalEntity::PreviewRecord => {
+                let Some(record) = fallback.get_preview_record(key).await? else {
+                    return Ok(Outcome::Conflict(
+                        "降级库缺少该预审记录，降级期间的更新未保留".to_string(),
+                    ));
+                };
+                let current = primary.get_preview_record(key).await?;
+                match resolve(
+                    (Some(&record.status), record.updated_at),
+                    current
+                        .as_ref()
+                        .map(|current| (Some(&current.status), current.updated_at)),
+                ) {
+                    Resolution::Apply => {
+                        primary.reconcile_preview_record(&record).await?;
+                        Ok(Outcome::Replayed(None))
+                    }
+                    Resolution::Conflict(detail) => Ok(Outcome::Conflict(detail)),
+                }
+            }
+            JournalEntity::MaterialResults | JournalEntity::RuleResults
+                if FailoverJournalQueries::status_of(
+                    &self.pool,
+                    JournalEntity::PreviewRecord,
+                    key,
+                )
+                .await?
+                    == Some(JournalStatus::Conflict) =>
+            {
+                Ok(Outcome::Conflict(
+                    "预审记录冲突，未覆盖主库结果".to_string(),
+                ))
+            }
+            // 没有载荷的日志回放成空列表会清空主库结果，转人工处理
+            JournalEntity::MaterialResults | JournalEntity::RuleResults
+                if entry.payload.is_none() =>
+            {
+                Ok(Outcome::Conflict("日志缺少结果载荷，未回放".to_string()))
+            }
+            JournalEntity::MaterialResults => {
+                let records: Vec<PreviewMaterialResultRecord> =
+                    serde_json::from_str(entry.payload.as_deref().unwrap_or_default())?;
+                primary
+                    .replace_preview_material_results(key, &records)
+                    .await?;
+                Ok(Outcome::Replayed(None))
+            }
+            JournalEntity::RuleResults => {
+                let records: Vec<PreviewRuleResultRecord> =
+                    serde_json::from_str(entry.payload.as_deref().unwrap_or_default())?;
+                primary.replace_preview_rule_results(key, &records).await?;
+                Ok(Outcome::Replayed(None))
+            }
+            JournalEntity::OutboxEvent => {
+                match FailoverJournalQueries::outbox_event(&self.pool, key).await? {
+                    None => Ok(Outcome::Replayed(Some("降级库无此事件".to_string()))),
+                    Some((_, true)) => {
+                        Ok(Outcome::Replayed(Some("事件已在降级期间处理".to_string())))
+                    }
+                    Some((event, false)) => {
+                        // 主库按 idempotency_key 去重，重复回放不会产生两条事件
+                        primary.enqueue_outbox_event(&event).await?;
+                        FailoverJournalQueries::mark_outbox_handed_over(&self.pool, key).await?;
+                        Ok(Outcome::Replayed(None))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn fallback_applies_when_not_behind_primary() {
+        let now = Utc::now();
+        assert_eq!(
+            resolve((Some(&PreviewStatus::Queued), now), None),
+            Resolution::Apply
+        );
+        assert_eq!(
+            resolve(
+                (Some(&PreviewStatus::Completed), now),
+                Some((
+                    Some(&PreviewStatus::Processing),
+                    now - Duration::seconds(30)
+                ))
+            ),
+            Resolution::Apply
+        );
+        // 终态之间按更新时间取新
+        assert_eq!(
+            resolve(
+                (Some(&PreviewStatus::Failed), now),
+                Some((Some(&PreviewStatus::Completed), now - Duration::seconds(1)))
+            ),
+            Resolution::Apply
+        );
+    }
+
+    #[test]
+    fn status_regression_or_stale_snapshot_is_conflict() {
+        let now = Utc::now();
+        assert!(matches!(
+            resolve(
+                (Some(&PreviewStatus::Processing), now),
+                Some((Some(&PreviewStatus::Completed), now - Duration::seconds(30)))
+            ),
+            Resolution::Conflict(_)
+        ));
+        assert!(matches!(
+            resolve(
+                (Some(&PreviewStatus::Completed), now - Duration::seconds(30)),
+                Some((Some(&PreviewStatus::Completed), now))
+            ),
+            Resolution::Conflict(_)
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use crate::db::traits::{BlobGcQuery, StorageBlobRecord};
+
+pub struct BlobQueries;
+
+impl BlobQueries {
+    pub async fn touch(pool: &SqlitePool, blob: &StorageBlobRecord) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO storage_blobs
+                (storage_key, checksum_sha256, size_bytes, created_at, last_referenced_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(storage_key) DO UPDATE SET
+                last_referenced_at = excluded.last_referenced_at
+            WHERE storage_blobs.gc_claim_id IS NULL
+            "#,
+        )
+        .bind(&blob.storage_key)
+        .bind(&blob.checksum_sha256)
+        .bind(blob.size_bytes)
+        .bind(blob.created_at.to_rfc3339())
+        .bind(blob.last_referenced_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn claim_unreferenced(
+        pool: &SqlitePool,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        let unreferenced_before = query.unreferenced_before.to_rfc3339();
+        let stale_claim_before = query.stale_claim_before.to_rfc3339();
+        // 外层重复判断条件，子查询只负责限制批量
+        sqlx::query(
+            r#"
+            UPDATE storage_blobs SET gc_claim_id = ?, gc_claimed_at = ?
+            WHERE last_referenced_at < ?
+              AND (gc_claim_id IS NULL OR gc_claimed_at < ?)
+              AND NOT EXISTS (
+                  SELECT 1 FROM preview_material_files f
+                  WHERE f.stored_original_key = storage_blobs.storage_key
+              )
+              AND storage_key IN (
+                  SELECT b.storage_key FROM storage_blobs b
+                  WHERE b.last_referenced_at < ?
+                    AND (b.gc_claim_id IS NULL OR b.gc_claimed_at < ?)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM preview_material_files f
+                        WHERE f.stored_original_key = b.storage_key
+                    )
+                  ORDER BY b.last_referenced_at
+                  LIMIT ?
+              )
+            "#,
+        )
+        .bind(&query.claim_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&unreferenced_before)
+        .bind(&stale_claim_before)
+        .bind(&unreferenced_before)
+        .bind(&stale_claim_before)
+        .bind(query.limit as i64)
+        .execute(pool)
+        .await?;
+
+        let rows = sqlx::query(
+            "SELECT storage_key, checksum_sha256, size_bytes, created_at, last_referenced_at \
+             FROM storage_blobs WHERE gc_claim_id = ? ORDER BY last_referenced_at",
+        )
+        .bind(&query.claim_id)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(Self::map_row).collect()
+    }
+
+    pub async fn delete_claimed(
+        pool: &SqlitePool,
+        storage_key: &str,
+        claim_id: &str,
+    ) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM storage_blobs WHERE storage_key = ? AND gc_claim_id = ?")
+                .bind(storage_key)
+                .bind(claim_id)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn release(pool: &SqlitePool, storage_key: &str, claim_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE storage_blobs SET gc_claim_id = NULL, gc_claimed_at = NULL \
+             WHERE storage_key = ? AND gc_claim_id = ?",
+        )
+        .bind(storage_key)
+        .bind(claim_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    fn map_row(row: &SqliteRow) -> Result<StorageBlobRecord> {
+        Ok(StorageBlobRecord {
+            storage_key: row.try_get("storage_key")?,
+            checksum_sha256: row.try_get("checksum_sha256")?,
+            size_bytes: row.try_get("size_bytes")?,
+            created_at: parse_time(row.try_get("created_at")?)?,
+            last_referenced_at: parse_time(row.try_get("last_referenced_at")?)?,
+        })
+    }
+}
+
+fn parse_time(value: String) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::db::traits::{Database, MaterialFileRecord};
+
+    fn blob(key: &str, referenced_at: DateTime<Utc>) -> StorageBlobRecord {
+        StorageBlobRecord {
+            storage_key: key.to_string(),
+            checksum_sha256: "ab".repeat(32),
+            size_bytes: 3,
+            created_at: referenced_at,
+            last_referenced_at: referenced_at,
+        }
+    }
+
+    fn gc_query(claim_id: &str, now: DateTime<Utc>) -> BlobGcQuery {
+        BlobGcQuery {
+            claim_id: claim_id.to_string(),
+            unreferenced_before: now - chrono::Duration::hours(1),
+            stale_claim_before: now - chrono::Duration::hours(1),
+            limit: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn claims_only_unreferenced_blobs_past_grace() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqliteDatabase::new(dir.path().join("blobs.db").to_str().unwrap())
+            .await
+            .unwrap();
+        db.initialize().await.unwrap();
+        MigrationRunner::new(&db).migrate_up().await.unwrap();
+        let pool = db.pool();
+
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(2);
+        for (key, at) in [("blobs/a", old), ("blobs/b", old), ("blobs/c", now)] {
+            assert!(BlobQueries::touch(pool, &blob(key, at)).await.unwrap());
+        }
+        db.save_material_file_record(&MaterialFileRecord {
+            id: "f1".to_string(),
+            preview_id: "p1".to_string(),
+            material_code: "m1".to_string(),
+            attachment_name: None,
+            source_url: None,
+            stored_original_key: "blobs/b".to_string(),
+            stored_processed_keys: None,
+            mime_type: None,
+            size_bytes: Some(3),
+            checksum_sha256: None,
+            ocr_text_key: None,
+            ocr_text_length: None,
+            status: "downloaded".to_string(),
+            error_message: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+
+        let claimed = BlobQueries::claim_unreferenced(pool, &gc_query("gc-1", now))
+            .await
+            .unwrap();
+        let keys: Vec<_> = claimed.iter().map(|b| b.storage_key.as_str()).collect();
+        assert_eq!(keys, vec!["blobs/a"]);
+
+        // 已认领的 blob 不再接受登记，也不能被另一轮回收重复认领
+        let touched = BlobQueries::touch(pool, &blob("blobs/a", now)).await;
+        assert!(!touched.unwrap());
+        let again = BlobQueries::claim_unreferenced(pool, &gc_query("gc-2", now)).await;
+        assert!(again.unwrap().is_empty());
+
+        let stolen = BlobQueries::delete_claimed(pool, "blobs/a", "gc-2").await;
+        assert!(!stolen.unwrap());
+        let deleted = BlobQueries::delete_claimed(pool, "blobs/a", "gc-1").await;
+        assert!(deleted.unwrap());
+        let recreated = BlobQueries::touch(pool, &blob("blobs/a", now)).await;
+        assert!(recreated.unwrap());
+    }
+}
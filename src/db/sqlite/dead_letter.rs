@@ -0,0 +1,212 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::db::traits::{DeadLetterFilter, DeadLetterRecord, DeadLetterStatus};
+
+const DEAD_LETTER_COLUMNS: &str = "id, preview_id, queue, source, payload, failure_reason, \
+    attempts, last_error, status, resolved_by, resolved_at, created_at, updated_at";
+
+pub struct DeadLetterQueries;
+
+impl DeadLetterQueries {
+    /// 同一 id 重复写入时刷新载荷与失败信息，并重新置为 dead
+    pub async fn save(pool: &SqlitePool, record: &DeadLetterRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO preview_dead_letters (
+                id, preview_id, queue, source, payload, failure_reason, attempts,
+                last_error, status, resolved_by, resolved_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                payload = excluded.payload,
+                failure_reason = excluded.failure_reason,
+                attempts = excluded.attempts,
+                last_error = excluded.last_error,
+                status = excluded.status,
+                resolved_by = NULL,
+                resolved_at = NULL,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&record.id)
+        .bind(&record.preview_id)
+        .bind(&record.queue)
+        .bind(&record.source)
+        .bind(&record.payload)
+        .bind(&record.failure_reason)
+        .bind(record.attempts)
+        .bind(&record.last_error)
+        .bind(record.status.as_str())
+        .bind(&record.resolved_by)
+        .bind(record.resolved_at.map(|t| t.to_rfc3339()))
+        .bind(record.created_at.to_rfc3339())
+        .bind(record.updated_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(
+        pool: &SqlitePool,
+        filter: &DeadLetterFilter,
+    ) -> Result<Vec<DeadLetterRecord>> {
+        let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+            "SELECT {DEAD_LETTER_COLUMNS} FROM preview_dead_letters WHERE 1 = 1"
+        ));
+        if let Some(status) = filter.status {
+            builder.push(" AND status = ").push_bind(status.as_str());
+        }
+        if let Some(preview_id) = &filter.preview_id {
+            builder
+                .push(" AND preview_id = ")
+                .push_bind(preview_id.clone());
+        }
+        if let Some(source) = &filter.source {
+            builder.push(" AND source = ").push_bind(source.clone());
+        }
+        builder
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(50) as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset.unwrap_or(0) as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(map_dead_letter).collect()
+    }
+
+    pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<DeadLetterRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {DEAD_LETTER_COLUMNS} FROM preview_dead_letters WHERE id = ?"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        row.as_ref().map(map_dead_letter).transpose()
+    }
+
+    pub async fn resolve(
+        pool: &SqlitePool,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE preview_dead_letters
+            SET status = ?, payload = COALESCE(?, payload), resolved_by = ?,
+                resolved_at = ?, updated_at = ?
+            WHERE id = ? AND status = 'dead'
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(payload)
+        .bind(resolved_by)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn map_dead_letter(row: &SqliteRow) -> Result<DeadLetterRecord> {
+    let parse = |value: String| -> Result<DateTime<Utc>> {
+        Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+    };
+    Ok(DeadLetterRecord {
+        id: row.get("id"),
+        preview_id: row.get("preview_id"),
+        queue: row.get("queue"),
+        source: row.get("source"),
+        payload: row.get("payload"),
+        failure_reason: row.get("failure_reason"),
+        attempts: row.get::<i64, _>("attempts") as i32,
+        last_error: row.get("last_error"),
+        status: row.get::<String, _>("status").parse()?,
+        resolved_by: row.get("resolved_by"),
+        resolved_at: row
+            .get::<Option<String>, _>("resolved_at")
+            .map(parse)
+            .transpose()?,
+        created_at: parse(row.get("created_at"))?,
+        updated_at: parse(row.get("updated_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::schemas::SchemaManager;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn letter(id: &str) -> DeadLetterRecord {
+        let now = Utc::now();
+        DeadLetterRecord {
+            id: id.to_string(),
+            preview_id: format!("preview-{}", id),
+            queue: "preview".to_string(),
+            source: "local".to_string(),
+            payload: "{}".to_string(),
+            failure_reason: "max_attempts_exceeded".to_string(),
+            attempts: 3,
+            last_error: Some("boom".to_string()),
+            status: DeadLetterStatus::Dead,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_only_applies_once() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SchemaManager::create_dead_letters_table(&pool)
+            .await
+            .unwrap();
+        DeadLetterQueries::save(&pool, &letter("d1")).await.unwrap();
+        DeadLetterQueries::save(&pool, &letter("d2")).await.unwrap();
+
+        let resolved = DeadLetterQueries::resolve(
+            &pool,
+            "d1",
+            DeadLetterStatus::Requeued,
+            Some("{\"edited\":true}"),
+            "admin",
+        )
+        .await
+        .unwrap();
+        assert!(resolved);
+        assert!(!DeadLetterQueries::resolve(
+            &pool,
+            "d1",
+            DeadLetterStatus::Discarded,
+            None,
+            "admin"
+        )
+        .await
+        .unwrap());
+
+        let stored = DeadLetterQueries::get(&pool, "d1").await.unwrap().unwrap();
+        assert_eq!(stored.status, DeadLetterStatus::Requeued);
+        assert_eq!(stored.payload, "{\"edited\":true}");
+        assert_eq!(stored.resolved_by.as_deref(), Some("admin"));
+
+        let filter = DeadLetterFilter {
+            status: Some(DeadLetterStatus::Dead),
+            ..DeadLetterFilter::default()
+        };
+        let pending = DeadLetterQueries::list(&pool, &filter).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "d2");
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use crate::db::reconcile::{JournalCounts, JournalEntity, JournalEntry, JournalStatus};
+use crate::db::traits::NewOutboxEvent;
+
+pub struct FailoverJournalQueries;
+
+impl FailoverJournalQueries {
+    /// 同一实体重复写入只保留一条，重置为 pending 并递增 revision
+    pub async fn record(
+        pool: &SqlitePool,
+        entity: JournalEntity,
+        entity_key: &str,
+        payload: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO failover_journal (
+                entity, entity_key, payload, status, revision, attempts, detail,
+                recorded_at, replayed_at
+            ) VALUES (?, ?, ?, 'pending', 1, 0, NULL, ?, NULL)
+            ON CONFLICT(entity, entity_key) DO UPDATE SET
+                payload = excluded.payload,
+                status = 'pending',
+                revision = failover_journal.revision + 1,
+                attempts = 0,
+                detail = NULL,
+                recorded_at = excluded.recorded_at,
+                replayed_at = NULL
+            "#,
+        )
+        .bind(entity.as_str())
+        .bind(entity_key)
+        .bind(payload)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 待回放（pending/failed）的条目，按写入顺序
+    pub async fn list_open(pool: &SqlitePool, limit: u32) -> Result<Vec<JournalEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM failover_journal WHERE status IN ('pending', 'failed') \
+             ORDER BY id LIMIT ?",
+        )
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_journal_entry).collect()
+    }
+
+    pub async fn list_by_status(
+        pool: &SqlitePool,
+        status: JournalStatus,
+        limit: u32,
+    ) -> Result<Vec<JournalEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM failover_journal WHERE status = ? ORDER BY recorded_at DESC LIMIT ?",
+        )
+        .bind(status.as_str())
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_journal_entry).collect()
+    }
+
+    pub async fn status_of(
+        pool: &SqlitePool,
+        entity: JournalEntity,
+        entity_key: &str,
+    ) -> Result<Option<JournalStatus>> {
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM failover_journal WHERE entity = ? AND entity_key = ?",
+        )
+        .bind(entity.as_str())
+        .bind(entity_key)
+        .fetch_optional(pool)
+        .await?;
+        status.map(|status| status.parse()).transpose()
+    }
+
+    /// 回放期间条目被重新写入（revision 变化）时不更新，留待下一轮
+    pub async fn resolve(
+        pool: &SqlitePool,
+        id: i64,
+        revision: i64,
+        status: JournalStatus,
+        detail: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE failover_journal SET
+                status = ?,
+                detail = ?,
+                attempts = attempts + 1,
+                replayed_at = ?
+            WHERE id = ? AND revision = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(detail)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(revision)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn counts(pool: &SqlitePool) -> Result<JournalCounts> {
+        let rows =
+            sqlx::query("SELECT status, COUNT(*) AS cnt FROM failover_journal GROUP BY status")
+                .fetch_all(pool)
+                .await?;
+        let mut counts = JournalCounts::default();
+        for row in rows {
+            let count = row.get::<i64, _>("cnt") as u64;
+            match row.get::<String, _>("status").parse()? {
+                JournalStatus::Pending => counts.pending = count,
+                JournalStatus::Failed => counts.failed = count,
+                JournalStatus::Conflict => counts.conflict = count,
+                JournalStatus::Replayed => counts.replayed = count,
+            }
+        }
+        Ok(counts)
+    }
+
+    /// 降级库中的 outbox 事件及其是否已处理
+    pub async fn outbox_event(
+        pool: &SqlitePool,
+        idempotency_key: &str,
+    ) -> Result<Option<(NewOutboxEvent, bool)>> {
+        let row = sqlx::query(
+            "SELECT table_name, op_type, pk_value, idempotency_key, payload, applied_at \
+             FROM db_outbox WHERE idempotency_key = ?",
+        )
+        .bind(idempotency_key)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(|row| {
+            let applied = row.get::<Option<String>, _>("applied_at").is_some();
+            (
+                NewOutboxEvent {
+                    table_name: row.get("table_name"),
+                    op_type: row.get("op_type"),
+                    pk_value: row.get("pk_value"),
+                    idempotency_key: row.get("idempotency_key"),
+                    payload: row.get("payload"),
+                },
+                applied,
+            )
+        }))
+    }
+
+    /// 事件已转交主库，避免再次降级时由降级库重复处理
+    pub async fn mark_outbox_handed_over(pool: &SqlitePool, idempotency_key: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE db_outbox SET applied_at = ?, last_error = '已转交主库' \
+             WHERE idempotency_key = ? AND applied_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(idempotency_key)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn parse_time(value: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|value| Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc)))
+        .transpose()
+}
+
+fn map_journal_entry(row: &SqliteRow) -> Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get("id"),
+        entity: row.get::<String, _>("entity").parse()?,
+        entity_key: row.get("entity_key"),
+        payload: row.get("payload"),
+        status: row.get::<String, _>("status").parse()?,
+        revision: row.get("revision"),
+        attempts: row.get("attempts"),
+        detail: row.get("detail"),
+        recorded_at: parse_time(Some(row.get("recorded_at")))?.unwrap_or_else(Utc::now),
+        replayed_at: parse_time(row.get("replayed_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::schemas::SchemaManager;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SchemaManager::create_failover_journal_table(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn rewrite_during_replay_keeps_entry_open() {
+        let pool = pool().await;
+        FailoverJournalQueries::record(&pool, JournalEntity::PreviewRecord, "p1", None)
+            .await
+            .unwrap();
+        FailoverJournalQueries::record(&pool, JournalEntity::PreviewRecord, "p1", None)
+            .await
+            .unwrap();
+
+        let open = FailoverJournalQueries::list_open(&pool, 10).await.unwrap();
+        assert_eq!(open.len(), 1);
+        let entry = &open[0];
+        assert_eq!(entry.revision, 2);
+
+        // 读取后又有新的写入，旧 revision 的回放结果不能覆盖
+        FailoverJournalQueries::record(&pool, JournalEntity::PreviewRecord, "p1", None)
+            .await
+            .unwrap();
+        assert!(!FailoverJournalQueries::resolve(
+            &pool,
+            entry.id,
+            entry.revision,
+            JournalStatus::Replayed,
+            None
+        )
+        .await
+        .unwrap());
+        assert_eq!(
+            FailoverJournalQueries::counts(&pool).await.unwrap().pending,
+            1
+        );
+
+        let entry = &FailoverJournalQueries::list_open(&pool, 10).await.unwrap()[0];
+        assert!(FailoverJournalQueries::resolve(
+            &pool,
+            entry.id,
+            entry.revision,
+            JournalStatus::Conflict,
+            Some("主库状态领先")
+        )
+        .await
+        .unwrap());
+        let counts = FailoverJournalQueries::counts(&pool).await.unwrap();
+        assert_eq!((counts.pending, counts.conflict), (0, 1));
+        assert!(FailoverJournalQueries::list_open(&pool, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,8 +1,18 @@
 
+pub mod blobs;
 pub mod connection;
+pub mod dead_letter;
+pub mod failover_journal;
 pub mod monitor_queries;
+pub mod pii;
+pub mod preview_batch;
 pub mod queries;
+pub mod queues;
+pub mod retention;
+pub mod schema_migrations;
 pub mod schemas;
+pub mod task_queue;
+pub mod worker_control;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -10,16 +20,26 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
+use super::migrations::{Migration, MigrationDialect};
 use super::traits::*;
 use crate::db::models::{MonitorSession, MonitorUser};
+use blobs::BlobQueries;
 use connection::ConnectionManager;
+use dead_letter::DeadLetterQueries;
 use monitor_queries::MonitorQueries;
+use pii::PiiQueries;
+use preview_batch::PreviewBatchQueries;
 use queries::{
     ApiStatsQueries, CachedMaterialQueries, HealthQueries, MaterialFileQueries,
     MaterialResultQueries, MatterRuleConfigQueries, OutboxQueries, PreviewQueries,
     PreviewRequestQueries, RuleResultQueries, TaskPayloadQueries,
 };
+use queues::MaterialDownloadQueries;
+use retention::RetentionQueries;
+use schema_migrations::SchemaMigrationQueries;
 use schemas::SchemaManager;
+use task_queue::TaskQueueQueries;
+use worker_control::WorkerControlQueries;
 
 pub struct SqliteDatabase {
     pool: SqlitePool,
@@ -91,6 +111,10 @@ impl Database for SqliteDatabase {
         PreviewQueries::get_by_id(&self.pool, id).await
     }
 
+    async fn reconcile_preview_record(&self, record: &PreviewRecord) -> Result<()> {
+        PreviewQueries::replace_record(&self.pool, record).await
+    }
+
     async fn update_preview_status(&self, id: &str, status: PreviewStatus) -> Result<()> {
         PreviewQueries::update_status(&self.pool, id, status).await
     }
@@ -562,37 +586,154 @@ impl Database for SqliteDatabase {
         Ok(())
     }
 
-    // Material Download Queue (SQLite not supported)
-    async fn enqueue_material_download(&self, _preview_id: &str, _payload: &str) -> Result<()> {
-        Err(anyhow::anyhow!(
-            "SQLite backend does not support material download queue"
-        ))
+    async fn enqueue_material_download(&self, preview_id: &str, payload: &str) -> Result<()> {
+        MaterialDownloadQueries::enqueue(&self.pool, preview_id, payload).await
     }
 
     async fn fetch_pending_material_downloads(
         &self,
-        _limit: u32,
+        limit: u32,
     ) -> Result<Vec<MaterialDownloadQueueRecord>> {
-        Err(anyhow::anyhow!(
-            "SQLite backend does not support material download queue"
-        ))
+        MaterialDownloadQueries::fetch_pending(&self.pool, limit).await
     }
 
     async fn update_material_download_status(
         &self,
-        _id: &str,
-        _status: &str,
-        _last_error: Option<&str>,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
     ) -> Result<()> {
-        Err(anyhow::anyhow!(
-            "SQLite backend does not support material download queue"
-        ))
+        MaterialDownloadQueries::update_status(&self.pool, id, status, last_error).await
+    }
+
+    async fn update_material_download_payload(&self, id: &str, payload: &str) -> Result<()> {
+        MaterialDownloadQueries::update_payload(&self.pool, id, payload).await
+    }
+
+    async fn cancel_material_downloads(&self, preview_id: &str) -> Result<u64> {
+        MaterialDownloadQueries::cancel_pending(&self.pool, preview_id).await
+    }
+
+    async fn latest_material_download_payload(&self, preview_id: &str) -> Result<Option<String>> {
+        MaterialDownloadQueries::latest_payload(&self.pool, preview_id).await
+    }
+
+    async fn ensure_task_queue_table(&self, table: &str) -> Result<()> {
+        TaskQueueQueries::ensure_table(&self.pool, table).await
+    }
+
+    async fn enqueue_queued_task(&self, table: &str, task: &NewQueuedTask) -> Result<()> {
+        TaskQueueQueries::enqueue(&self.pool, table, task).await
+    }
+
+    async fn claim_queued_tasks(
+        &self,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        TaskQueueQueries::claim(&self.pool, table, claim).await
+    }
+
+    async fn extend_queued_task_lease(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        TaskQueueQueries::extend_lease(&self.pool, table, task_id, lease_token, lease_expires_at)
+            .await
+    }
+
+    async fn complete_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        TaskQueueQueries::complete(&self.pool, table, task_id, lease_token).await
     }
 
-    async fn update_material_download_payload(&self, _id: &str, _payload: &str) -> Result<()> {
-        Err(anyhow::anyhow!(
-            "SQLite backend does not support material download queue"
-        ))
+    async fn release_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        TaskQueueQueries::release(&self.pool, table, task_id, lease_token, error, retry_at).await
+    }
+
+    async fn return_queued_task(
+        &self,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        TaskQueueQueries::return_task(&self.pool, table, task_id, lease_token).await
+    }
+
+    async fn queued_task_stats(&self, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        TaskQueueQueries::stats(&self.pool, table, queue).await
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        TaskQueueQueries::list_failed(&self.pool, table, queue, limit).await
+    }
+
+    async fn delete_queued_task(&self, table: &str, task_id: &str) -> Result<bool> {
+        TaskQueueQueries::delete(&self.pool, table, task_id).await
+    }
+
+    async fn cancel_queued_tasks(&self, table: &str, queue: &str, preview_id: &str) -> Result<u64> {
+        TaskQueueQueries::cancel_pending(&self.pool, table, queue, preview_id).await
+    }
+
+    async fn save_dead_letter(&self, record: &DeadLetterRecord) -> Result<()> {
+        DeadLetterQueries::save(&self.pool, record).await
+    }
+
+    async fn list_dead_letters(&self, filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        DeadLetterQueries::list(&self.pool, filter).await
+    }
+
+    async fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetterRecord>> {
+        DeadLetterQueries::get(&self.pool, id).await
+    }
+
+    async fn resolve_dead_letter(
+        &self,
+        id: &str,
+        status: DeadLetterStatus,
+        payload: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool> {
+        DeadLetterQueries::resolve(&self.pool, id, status, payload, resolved_by).await
+    }
+
+    async fn save_worker_control(&self, record: &WorkerControlRecord) -> Result<()> {
+        WorkerControlQueries::save(&self.pool, record).await
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        WorkerControlQueries::list(&self.pool).await
+    }
+
+    async fn save_preview_batch_items(&self, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        PreviewBatchQueries::save_items(&self.pool, items).await
+    }
+
+    async fn list_preview_batch_items(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        PreviewBatchQueries::list_items(&self.pool, batch_id).await
     }
 
     async fn get_download_cache_token(
@@ -610,4 +751,86 @@ impl Database for SqliteDatabase {
     ) -> Result<()> {
         Ok(())
     }
+
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        Some(MigrationDialect::Sqlite)
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        SchemaMigrationQueries::list(&self.pool).await
+    }
+
+    async fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        SchemaMigrationQueries::apply(&self.pool, migration).await
+    }
+
+    async fn list_retention_candidates(
+        &self,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        RetentionQueries::list_candidates(&self.pool, query).await
+    }
+
+    async fn purge_preview_artifact(&self, purge: &ArtifactPurge) -> Result<Option<i64>> {
+        RetentionQueries::purge_preview_artifact(&self.pool, purge).await
+    }
+
+    async fn purge_log_records(&self, before: DateTime<Utc>, purge: &ArtifactPurge) -> Result<i64> {
+        RetentionQueries::purge_log_records(&self.pool, before, purge).await
+    }
+
+    async fn set_preview_legal_hold(&self, update: &LegalHoldUpdate) -> Result<bool> {
+        RetentionQueries::set_legal_hold(&self.pool, update).await
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        RetentionQueries::list_legal_holds(&self.pool).await
+    }
+
+    async fn list_purge_audit(&self, filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        RetentionQueries::list_audit(&self.pool, filter).await
+    }
+
+    async fn save_pii_lookup_value(&self, token: &str, ciphertext: &str) -> Result<()> {
+        PiiQueries::save_lookup_value(&self.pool, token, ciphertext).await
+    }
+
+    async fn get_pii_lookup_values(
+        &self,
+        tokens: &[String],
+    ) -> Result<std::collections::HashMap<String, String>> {
+        PiiQueries::get_lookup_values(&self.pool, tokens).await
+    }
+
+    async fn list_pii_rows(
+        &self,
+        table: PiiTable,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        PiiQueries::list_rows(&self.pool, table, after, limit).await
+    }
+
+    async fn update_pii_row(&self, table: PiiTable, update: &PiiRowUpdate) -> Result<bool> {
+        PiiQueries::update_row(&self.pool, table, update).await
+    }
+
+    async fn touch_storage_blob(&self, blob: &StorageBlobRecord) -> Result<bool> {
+        BlobQueries::touch(&self.pool, blob).await
+    }
+
+    async fn claim_unreferenced_blobs(
+        &self,
+        query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        BlobQueries::claim_unreferenced(&self.pool, query).await
+    }
+
+    async fn delete_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<bool> {
+        BlobQueries::delete_claimed(&self.pool, storage_key, claim_id).await
+    }
+
+    async fn release_storage_blob(&self, storage_key: &str, claim_id: &str) -> Result<()> {
+        BlobQueries::release(&self.pool, storage_key, claim_id).await
+    }
 }
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use crate::db::traits::PreviewBatchItemRecord;
+
+pub struct PreviewBatchQueries;
+
+impl PreviewBatchQueries {
+    pub async fn save_items(pool: &SqlitePool, items: &[PreviewBatchItemRecord]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for item in items {
+            sqlx::query(
+                r#"
+                INSERT INTO preview_batch_items (
+                    batch_id, item_index, submitted_by, status, preview_id,
+                    third_party_request_id, error, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&item.batch_id)
+            .bind(item.item_index)
+            .bind(&item.submitted_by)
+            .bind(item.status.as_str())
+            .bind(&item.preview_id)
+            .bind(&item.third_party_request_id)
+            .bind(&item.error)
+            .bind(item.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_items(
+        pool: &SqlitePool,
+        batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, item_index, submitted_by, status, preview_id, \
+             third_party_request_id, error, created_at FROM preview_batch_items \
+             WHERE batch_id = ? ORDER BY item_index",
+        )
+        .bind(batch_id)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_batch_item).collect()
+    }
+}
+
+fn map_batch_item(row: &SqliteRow) -> Result<PreviewBatchItemRecord> {
+    Ok(PreviewBatchItemRecord {
+        batch_id: row.get("batch_id"),
+        item_index: row.get("item_index"),
+        submitted_by: row.get("submitted_by"),
+        status: row.get::<String, _>("status").parse()?,
+        preview_id: row.get("preview_id"),
+        third_party_request_id: row.get("third_party_request_id"),
+        error: row.get("error"),
+        created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?
+            .with_timezone(&Utc),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::schemas::SchemaManager;
+    use crate::db::traits::BatchItemStatus;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn item(batch_id: &str, index: i32, status: BatchItemStatus) -> PreviewBatchItemRecord {
+        PreviewBatchItemRecord {
+            batch_id: batch_id.to_string(),
+            item_index: index,
+            submitted_by: "client-a".to_string(),
+            status,
+            preview_id: (status != BatchItemStatus::Rejected).then(|| format!("p{index}")),
+            third_party_request_id: Some(format!("req-{index}")),
+            error: (status == BatchItemStatus::Rejected).then(|| "userId 不能为空".to_string()),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn items_are_listed_per_batch_in_order() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SchemaManager::create_preview_batch_items_table(&pool)
+            .await
+            .unwrap();
+
+        PreviewBatchQueries::save_items(
+            &pool,
+            &[
+                item("b1", 1, BatchItemStatus::Rejected),
+                item("b1", 0, BatchItemStatus::Accepted),
+                item("b2", 0, BatchItemStatus::Duplicate),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let items = PreviewBatchQueries::list_items(&pool, "b1").await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].status, BatchItemStatus::Accepted);
+        assert_eq!(items[0].preview_id.as_deref(), Some("p0"));
+        assert_eq!(items[1].status, BatchItemStatus::Rejected);
+        assert!(items[1].preview_id.is_none());
+        assert!(PreviewBatchQueries::list_items(&pool, "missing")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -250,6 +250,7 @@ impl PreviewRequestQueries {
             "processing" => Ok(PreviewStatus::Processing),
             "completed" => Ok(PreviewStatus::Completed),
             "failed" => Ok(PreviewStatus::Failed),
+            "cancelled" => Ok(PreviewStatus::Cancelled),
             other => Err(anyhow!("unknown preview status: {}", other)),
         }
     }
@@ -428,6 +429,15 @@ pub struct PreviewQueries;
 
 impl PreviewQueries {
     pub async fn save_record(pool: &SqlitePool, record: &PreviewRecord) -> Result<()> {
+        Self::write_record(pool, record, "INSERT").await
+    }
+
+    /// 主键已存在时整条替换，保留记录自带的时间戳
+    pub async fn replace_record(pool: &SqlitePool, record: &PreviewRecord) -> Result<()> {
+        Self::write_record(pool, record, "INSERT OR REPLACE").await
+    }
+
+    async fn write_record(pool: &SqlitePool, record: &PreviewRecord, verb: &str) -> Result<()> {
         let status_str = Self::status_to_string(&record.status);
 
         let queued_at = record.queued_at.as_ref().map(|dt| dt.to_rfc3339());
@@ -441,9 +451,9 @@ impl PreviewQueries {
             .as_ref()
             .map(|dt| dt.to_rfc3339());
 
-        sqlx::query(
+        let sql = format!(
             r#"
-            INSERT INTO preview_records (
+            {verb} INTO preview_records (
                 id, user_id, user_info_json, file_name, ocr_text, theme_id,
                 evaluation_result, preview_url, preview_view_url, preview_download_url, status,
                 created_at, updated_at, third_party_request_id,
@@ -454,45 +464,46 @@ impl PreviewQueries {
                 last_callback_at, last_callback_status_code, last_callback_response,
                 last_callback_error, callback_payload, next_callback_after
             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&record.id)
-        .bind(&record.user_id)
-        .bind(&record.user_info_json)
-        .bind(&record.file_name)
-        .bind(&record.ocr_text)
-        .bind(&record.theme_id)
-        .bind(&record.evaluation_result)
-        .bind(&record.preview_url)
-        .bind(record.preview_view_url.as_deref())
-        .bind(record.preview_download_url.as_deref())
-        .bind(status_str)
-        .bind(record.created_at.to_rfc3339())
-        .bind(record.updated_at.to_rfc3339())
-        .bind(&record.third_party_request_id)
-        .bind(queued_at)
-        .bind(processing_started_at)
-        .bind(record.retry_count)
-        .bind(&record.last_worker_id)
-        .bind(&record.last_attempt_id)
-        .bind(&record.failure_reason)
-        .bind(&record.ocr_stderr_summary)
-        .bind(&record.failure_context)
-        .bind(&record.last_error_code)
-        .bind(&record.slow_attachment_info_json)
-        .bind(&record.callback_url)
-        .bind(&record.callback_status)
-        .bind(record.callback_attempts)
-        .bind(record.callback_successes)
-        .bind(record.callback_failures)
-        .bind(last_callback_at)
-        .bind(&record.last_callback_status_code)
-        .bind(&record.last_callback_response)
-        .bind(&record.last_callback_error)
-        .bind(&record.callback_payload)
-        .bind(next_callback_after)
-        .execute(pool)
-        .await?;
+            "#
+        );
+        sqlx::query(&sql)
+            .bind(&record.id)
+            .bind(&record.user_id)
+            .bind(&record.user_info_json)
+            .bind(&record.file_name)
+            .bind(&record.ocr_text)
+            .bind(&record.theme_id)
+            .bind(&record.evaluation_result)
+            .bind(&record.preview_url)
+            .bind(record.preview_view_url.as_deref())
+            .bind(record.preview_download_url.as_deref())
+            .bind(status_str)
+            .bind(record.created_at.to_rfc3339())
+            .bind(record.updated_at.to_rfc3339())
+            .bind(&record.third_party_request_id)
+            .bind(queued_at)
+            .bind(processing_started_at)
+            .bind(record.retry_count)
+            .bind(&record.last_worker_id)
+            .bind(&record.last_attempt_id)
+            .bind(&record.failure_reason)
+            .bind(&record.ocr_stderr_summary)
+            .bind(&record.failure_context)
+            .bind(&record.last_error_code)
+            .bind(&record.slow_attachment_info_json)
+            .bind(&record.callback_url)
+            .bind(&record.callback_status)
+            .bind(record.callback_attempts)
+            .bind(record.callback_successes)
+            .bind(record.callback_failures)
+            .bind(last_callback_at)
+            .bind(&record.last_callback_status_code)
+            .bind(&record.last_callback_response)
+            .bind(&record.last_callback_error)
+            .bind(&record.callback_payload)
+            .bind(next_callback_after)
+            .execute(pool)
+            .await?;
 
         Ok(())
     }
@@ -573,7 +584,7 @@ impl PreviewQueries {
                 .execute(pool)
                 .await?;
             }
-            PreviewStatus::Completed | PreviewStatus::Failed => {
+            PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled => {
                 sqlx::query(
                     r#"
                     UPDATE preview_records 
@@ -726,6 +737,7 @@ impl PreviewQueries {
                 PreviewStatus::Failed => counts.failed += add,
                 PreviewStatus::Pending => counts.pending += add,
                 PreviewStatus::Queued => counts.queued += add,
+                PreviewStatus::Cancelled => counts.cancelled += add,
             }
         }
 
@@ -791,6 +803,11 @@ impl PreviewQueries {
             bindings.push(tp_id.clone());
         }
 
+        if let Some(worker_id) = &filter.worker_id {
+            query.push_str(" AND last_worker_id = ?");
+            bindings.push(worker_id.clone());
+        }
+
         if let Some(start) = filter.start_date {
             query.push_str(" AND created_at >= ?");
             bindings.push(start.to_rfc3339());
@@ -1130,6 +1147,7 @@ impl PreviewQueries {
             PreviewStatus::Processing => "processing",
             PreviewStatus::Completed => "completed",
             PreviewStatus::Failed => "failed",
+            PreviewStatus::Cancelled => "cancelled",
         }
     }
 
@@ -1140,6 +1158,7 @@ impl PreviewQueries {
             "processing" => PreviewStatus::Processing,
             "completed" => PreviewStatus::Completed,
             "failed" => PreviewStatus::Failed,
+            "cancelled" => PreviewStatus::Cancelled,
             _ => PreviewStatus::Pending,
         }
     }
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::traits::MaterialDownloadQueueRecord;
+
+const QUEUE_COLUMNS: &str =
+    "id, preview_id, payload, status, attempts, last_error, created_at, updated_at";
+
+/// 材料下载队列；时间按 RFC 3339 保存，同一秒内按 rowid 保持入队顺序
+pub struct MaterialDownloadQueries;
+
+impl MaterialDownloadQueries {
+    pub async fn enqueue(pool: &SqlitePool, preview_id: &str, payload: &str) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO material_download_queue \
+             (id, preview_id, payload, status, attempts, created_at, updated_at) \
+             VALUES (?, ?, ?, 'pending', 0, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(preview_id)
+        .bind(payload)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn fetch_pending(
+        pool: &SqlitePool,
+        limit: u32,
+    ) -> Result<Vec<MaterialDownloadQueueRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {QUEUE_COLUMNS} FROM material_download_queue WHERE status = 'pending' \
+             ORDER BY created_at ASC, rowid ASC LIMIT ?"
+        ))
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_row).collect()
+    }
+
+    /// 带错误信息时累计尝试次数，与 PostgreSQL、达梦实现一致
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: &str,
+        status: &str,
+        last_error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE material_download_queue
+            SET status = ?,
+                last_error = COALESCE(?, last_error),
+                attempts = attempts + CASE WHEN ? IS NULL THEN 0 ELSE 1 END,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status)
+        .bind(last_error)
+        .bind(last_error)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_payload(pool: &SqlitePool, id: &str, payload: &str) -> Result<()> {
+        sqlx::query("UPDATE material_download_queue SET payload = ?, updated_at = ? WHERE id = ?")
+            .bind(payload)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn cancel_pending(pool: &SqlitePool, preview_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE material_download_queue SET status = 'cancelled', updated_at = ? \
+             WHERE preview_id = ? AND status = 'pending'",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(preview_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn latest_payload(pool: &SqlitePool, preview_id: &str) -> Result<Option<String>> {
+        let payload = sqlx::query_scalar(
+            "SELECT payload FROM material_download_queue WHERE preview_id = ? \
+             ORDER BY created_at DESC, rowid DESC LIMIT 1",
+        )
+        .bind(preview_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(payload)
+    }
+}
+
+fn map_row(row: &SqliteRow) -> Result<MaterialDownloadQueueRecord> {
+    Ok(MaterialDownloadQueueRecord {
+        id: row.try_get("id")?,
+        preview_id: row.try_get("preview_id")?,
+        payload: row.try_get("payload")?,
+        status: row.try_get("status")?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        created_at: parse_time(row.try_get("created_at")?)?,
+        updated_at: parse_time(row.try_get("updated_at")?)?,
+    })
+}
+
+fn parse_time(value: String) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::db::traits::Database;
+
+    #[tokio::test]
+    async fn download_queue_supports_cancel_and_redownload() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqliteDatabase::new(dir.path().join("queue.db").to_str().unwrap())
+            .await
+            .unwrap();
+        db.initialize().await.unwrap();
+        MigrationRunner::new(&db).migrate_up().await.unwrap();
+
+        db.enqueue_material_download("p1", r#"{"v":1}"#)
+            .await
+            .unwrap();
+        db.enqueue_material_download("p1", r#"{"v":2}"#)
+            .await
+            .unwrap();
+        let pending = db.fetch_pending_material_downloads(10).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].payload, r#"{"v":1}"#);
+
+        db.update_material_download_status(&pending[0].id, "failed", Some("timeout"))
+            .await
+            .unwrap();
+        assert_eq!(db.cancel_material_downloads("p1").await.unwrap(), 1);
+        assert!(db
+            .fetch_pending_material_downloads(10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let latest = db.latest_material_download_payload("p1").await.unwrap();
+        assert_eq!(latest.as_deref(), Some(r#"{"v":2}"#));
+        let missing = db.latest_material_download_payload("p2").await.unwrap();
+        assert!(missing.is_none());
+    }
+}
@@ -0,0 +1,481 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::db::traits::{
+    ArtifactPurge, LegalHoldRecord, LegalHoldUpdate, PurgeAuditFilter, PurgeAuditRecord,
+    RetentionArtifact, RetentionCandidate, RetentionCandidateQuery,
+};
+
+/// 各类产物在数据库中的清理语句，每条只绑定一个预审ID
+fn artifact_statements(artifact: RetentionArtifact) -> &'static [&'static str] {
+    match artifact {
+        RetentionArtifact::RawRequest => &[
+            "DELETE FROM preview_task_payloads WHERE preview_id = ?",
+            "UPDATE preview_requests SET user_info_json = NULL, agent_info_json = NULL, \
+             subject_info_json = NULL, form_data_json = NULL, scene_data_json = NULL, \
+             material_data_json = NULL WHERE latest_preview_id = ?",
+            "UPDATE preview_records SET user_info_json = NULL WHERE id = ?",
+            "UPDATE preview_material_files SET stored_original_key = '', source_url = NULL \
+             WHERE preview_id = ?",
+            "UPDATE cached_materials SET oss_key = NULL WHERE preview_id = ?",
+        ],
+        RetentionArtifact::OcrText => &[
+            "UPDATE preview_records SET ocr_text = '' WHERE id = ?",
+            "UPDATE preview_material_files SET ocr_text_key = NULL, ocr_text_length = NULL \
+             WHERE preview_id = ?",
+        ],
+        RetentionArtifact::PageImages => &[
+            "UPDATE preview_material_files SET stored_processed_keys = NULL WHERE preview_id = ?",
+        ],
+        RetentionArtifact::Reports => &[
+            "UPDATE preview_records SET evaluation_result = NULL, callback_payload = NULL \
+             WHERE id = ?",
+            "DELETE FROM preview_rule_results WHERE preview_id = ?",
+            "DELETE FROM preview_material_results WHERE preview_id = ?",
+        ],
+        RetentionArtifact::Logs => &[],
+    }
+}
+
+pub struct RetentionQueries;
+
+impl RetentionQueries {
+    pub async fn list_candidates(
+        pool: &SqlitePool,
+        query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "SELECT id, created_at, matter_id FROM (\
+             SELECT pr.id, pr.created_at, \
+             (SELECT req.matter_id FROM preview_requests req \
+              WHERE req.latest_preview_id = pr.id LIMIT 1) AS matter_id \
+             FROM preview_records pr \
+             WHERE pr.legal_hold = 0 \
+             AND pr.status IN ('completed', 'failed', 'cancelled') \
+             AND pr.created_at < ",
+        );
+        builder
+            .push_bind(query.created_before.to_rfc3339())
+            .push(" AND COALESCE(pr.purged_artifacts, '') NOT LIKE ")
+            .push_bind(query.artifact.purged_marker_pattern())
+            .push(") c WHERE 1=1");
+
+        if let Some(matter_id) = &query.matter_id {
+            builder.push(" AND matter_id = ").push_bind(matter_id);
+        }
+        if !query.exclude_matters.is_empty() {
+            builder.push(" AND (matter_id IS NULL OR matter_id NOT IN (");
+            let mut separated = builder.separated(", ");
+            for matter_id in &query.exclude_matters {
+                separated.push_bind(matter_id);
+            }
+            builder.push("))");
+        }
+        builder
+            .push(" ORDER BY created_at LIMIT ")
+            .push_bind(query.limit as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(RetentionCandidate {
+                    preview_id: row.get("id"),
+                    matter_id: row.get("matter_id"),
+                    created_at: parse_datetime(&row.get::<String, _>("created_at"))?,
+                })
+            })
+            .collect()
+    }
+
+    /// 标记、清理与审计在同一事务内；先标记，法律保全中的预审不会被改动
+    pub async fn purge_preview_artifact(
+        pool: &SqlitePool,
+        purge: &ArtifactPurge,
+    ) -> Result<Option<i64>> {
+        let preview_id = purge
+            .preview_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("清理预审产物缺少 preview_id"))?;
+        let artifact = purge.artifact.as_str();
+
+        let mut tx = pool.begin().await?;
+        let marked = sqlx::query(
+            r#"
+            UPDATE preview_records
+            SET purged_artifacts = CASE
+                WHEN COALESCE(purged_artifacts, '') LIKE ? THEN purged_artifacts
+                ELSE COALESCE(purged_artifacts, ',') || ?
+            END
+            WHERE id = ? AND legal_hold = 0
+            "#,
+        )
+        .bind(purge.artifact.purged_marker_pattern())
+        .bind(format!("{},", artifact))
+        .bind(preview_id)
+        .execute(&mut *tx)
+        .await?;
+        if marked.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let mut db_rows = 0i64;
+        for statement in artifact_statements(purge.artifact) {
+            let result = sqlx::query(statement)
+                .bind(preview_id)
+                .execute(&mut *tx)
+                .await?;
+            db_rows += result.rows_affected() as i64;
+        }
+        insert_audit(&mut tx, purge, db_rows).await?;
+        tx.commit().await?;
+        Ok(Some(db_rows))
+    }
+
+    pub async fn purge_log_records(
+        pool: &SqlitePool,
+        before: DateTime<Utc>,
+        purge: &ArtifactPurge,
+    ) -> Result<i64> {
+        let cutoff = before.to_rfc3339();
+        let mut tx = pool.begin().await?;
+        let mut db_rows = 0i64;
+        for statement in [
+            "DELETE FROM api_stats WHERE created_at < ?",
+            "DELETE FROM user_login_records WHERE created_at < ?",
+        ] {
+            let result = sqlx::query(statement)
+                .bind(&cutoff)
+                .execute(&mut *tx)
+                .await?;
+            db_rows += result.rows_affected() as i64;
+        }
+        if db_rows > 0 {
+            insert_audit(&mut tx, purge, db_rows).await?;
+        }
+        tx.commit().await?;
+        Ok(db_rows)
+    }
+
+    pub async fn set_legal_hold(pool: &SqlitePool, update: &LegalHoldUpdate) -> Result<bool> {
+        let result = if update.hold {
+            sqlx::query(
+                "UPDATE preview_records SET legal_hold = 1, legal_hold_reason = ?, \
+                 legal_hold_by = ?, legal_hold_at = ? WHERE id = ?",
+            )
+            .bind(&update.reason)
+            .bind(&update.updated_by)
+            .bind(Utc::now().to_rfc3339())
+            .bind(&update.preview_id)
+            .execute(pool)
+            .await?
+        } else {
+            sqlx::query(
+                "UPDATE preview_records SET legal_hold = 0, legal_hold_reason = NULL, \
+                 legal_hold_by = NULL, legal_hold_at = NULL WHERE id = ?",
+            )
+            .bind(&update.preview_id)
+            .execute(pool)
+            .await?
+        };
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_legal_holds(pool: &SqlitePool) -> Result<Vec<LegalHoldRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, legal_hold_reason, legal_hold_by, legal_hold_at, created_at \
+             FROM preview_records WHERE legal_hold = 1 ORDER BY legal_hold_at DESC",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(LegalHoldRecord {
+                    preview_id: row.get("id"),
+                    reason: row.get("legal_hold_reason"),
+                    held_by: row.get("legal_hold_by"),
+                    held_at: row
+                        .get::<Option<String>, _>("legal_hold_at")
+                        .map(|value| parse_datetime(&value))
+                        .transpose()?,
+                    created_at: parse_datetime(&row.get::<String, _>("created_at"))?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn list_audit(
+        pool: &SqlitePool,
+        filter: &PurgeAuditFilter,
+    ) -> Result<Vec<PurgeAuditRecord>> {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "SELECT id, preview_id, matter_id, artifact, storage_keys, db_rows, reason, \
+             purged_at FROM retention_purge_log WHERE 1=1",
+        );
+        if let Some(preview_id) = &filter.preview_id {
+            builder.push(" AND preview_id = ").push_bind(preview_id);
+        }
+        if let Some(artifact) = filter.artifact {
+            builder
+                .push(" AND artifact = ")
+                .push_bind(artifact.as_str());
+        }
+        builder
+            .push(" ORDER BY purged_at DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(50) as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset.unwrap_or(0) as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+        rows.iter().map(map_audit).collect()
+    }
+}
+
+async fn insert_audit(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    purge: &ArtifactPurge,
+    db_rows: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO retention_purge_log (
+            id, preview_id, matter_id, artifact, storage_keys, db_rows, reason, purged_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&purge.preview_id)
+    .bind(&purge.matter_id)
+    .bind(purge.artifact.as_str())
+    .bind(serde_json::to_string(&purge.storage_keys)?)
+    .bind(db_rows)
+    .bind(&purge.reason)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// 记录创建时写入 RFC3339，部分旧数据为 `CURRENT_TIMESTAMP` 格式
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    Ok(NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")?.and_utc())
+}
+
+fn map_audit(row: &SqliteRow) -> Result<PurgeAuditRecord> {
+    Ok(PurgeAuditRecord {
+        id: row.get("id"),
+        preview_id: row.get("preview_id"),
+        matter_id: row.get("matter_id"),
+        artifact: row.get("artifact"),
+        storage_keys: serde_json::from_str(&row.get::<String, _>("storage_keys"))?,
+        db_rows: row.get("db_rows"),
+        reason: row.get("reason"),
+        purged_at: parse_datetime(&row.get::<String, _>("purged_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::db::traits::{Database, PreviewRecord, PreviewStatus};
+
+    async fn migrated_db() -> (tempfile::TempDir, SqliteDatabase) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("retention.db");
+        let db = SqliteDatabase::new(path.to_str().unwrap()).await.unwrap();
+        db.initialize().await.unwrap();
+        MigrationRunner::new(&db).migrate_up().await.unwrap();
+        (dir, db)
+    }
+
+    fn record(id: &str, status: PreviewStatus, age_days: i64) -> PreviewRecord {
+        let created_at = Utc::now() - chrono::Duration::days(age_days);
+        PreviewRecord {
+            id: id.to_string(),
+            user_id: "u1".to_string(),
+            user_info_json: Some("{\"name\":\"张三\"}".to_string()),
+            file_name: "a.pdf".to_string(),
+            ocr_text: "识别文本".to_string(),
+            theme_id: None,
+            evaluation_result: Some("{}".to_string()),
+            preview_url: String::new(),
+            preview_view_url: None,
+            preview_download_url: None,
+            status,
+            created_at,
+            updated_at: created_at,
+            third_party_request_id: None,
+            queued_at: None,
+            processing_started_at: None,
+            retry_count: 0,
+            last_worker_id: None,
+            last_attempt_id: None,
+            failure_reason: None,
+            ocr_stderr_summary: None,
+            failure_context: None,
+            last_error_code: None,
+            slow_attachment_info_json: None,
+            callback_url: None,
+            callback_status: None,
+            callback_attempts: 0,
+            callback_successes: 0,
+            callback_failures: 0,
+            last_callback_at: None,
+            last_callback_status_code: None,
+            last_callback_response: None,
+            last_callback_error: None,
+            callback_payload: None,
+            next_callback_after: None,
+        }
+    }
+
+    fn query(artifact: RetentionArtifact) -> RetentionCandidateQuery {
+        RetentionCandidateQuery {
+            artifact,
+            created_before: Utc::now() - chrono::Duration::days(30),
+            matter_id: None,
+            exclude_matters: Vec::new(),
+            limit: 10,
+        }
+    }
+
+    fn purge(preview_id: &str, artifact: RetentionArtifact) -> ArtifactPurge {
+        ArtifactPurge {
+            preview_id: Some(preview_id.to_string()),
+            matter_id: None,
+            artifact,
+            storage_keys: vec![format!("uploads/{}/m1/ocr/a.txt", preview_id)],
+            reason: "retention".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn candidates_skip_recent_unfinished_held_and_purged() {
+        let (_dir, db) = migrated_db().await;
+        for rec in [
+            record("old", PreviewStatus::Completed, 60),
+            record("recent", PreviewStatus::Completed, 1),
+            record("running", PreviewStatus::Processing, 60),
+            record("held", PreviewStatus::Completed, 60),
+        ] {
+            db.save_preview_record(&rec).await.unwrap();
+        }
+        let hold = LegalHoldUpdate {
+            preview_id: "held".to_string(),
+            hold: true,
+            reason: Some("诉讼保全".to_string()),
+            updated_by: "admin".to_string(),
+        };
+        assert!(RetentionQueries::set_legal_hold(db.pool(), &hold)
+            .await
+            .unwrap());
+
+        let ids = |candidates: Vec<RetentionCandidate>| -> Vec<String> {
+            candidates.into_iter().map(|c| c.preview_id).collect()
+        };
+        let ocr = query(RetentionArtifact::OcrText);
+        assert_eq!(
+            ids(RetentionQueries::list_candidates(db.pool(), &ocr)
+                .await
+                .unwrap()),
+            vec!["old"]
+        );
+
+        let purged = RetentionQueries::purge_preview_artifact(
+            db.pool(),
+            &purge("old", RetentionArtifact::OcrText),
+        )
+        .await
+        .unwrap();
+        assert_eq!(purged, Some(1));
+        assert!(RetentionQueries::list_candidates(db.pool(), &ocr)
+            .await
+            .unwrap()
+            .is_empty());
+        // 其他类别不受影响
+        assert_eq!(
+            ids(
+                RetentionQueries::list_candidates(db.pool(), &query(RetentionArtifact::Reports))
+                    .await
+                    .unwrap()
+            ),
+            vec!["old"]
+        );
+
+        let stored = db.get_preview_record("old").await.unwrap().unwrap();
+        assert!(stored.ocr_text.is_empty());
+        assert_eq!(stored.evaluation_result.as_deref(), Some("{}"));
+    }
+
+    #[tokio::test]
+    async fn legal_hold_blocks_purge_and_audit_records_keys() {
+        let (_dir, db) = migrated_db().await;
+        db.save_preview_record(&record("p1", PreviewStatus::Completed, 60))
+            .await
+            .unwrap();
+        let mut hold = LegalHoldUpdate {
+            preview_id: "p1".to_string(),
+            hold: true,
+            reason: None,
+            updated_by: "admin".to_string(),
+        };
+        RetentionQueries::set_legal_hold(db.pool(), &hold)
+            .await
+            .unwrap();
+        assert_eq!(
+            RetentionQueries::list_legal_holds(db.pool())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let reports = purge("p1", RetentionArtifact::Reports);
+        assert_eq!(
+            RetentionQueries::purge_preview_artifact(db.pool(), &reports)
+                .await
+                .unwrap(),
+            None
+        );
+
+        hold.hold = false;
+        RetentionQueries::set_legal_hold(db.pool(), &hold)
+            .await
+            .unwrap();
+        assert!(
+            RetentionQueries::purge_preview_artifact(db.pool(), &reports)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        // 重复清理不会重复写入标记
+        RetentionQueries::purge_preview_artifact(db.pool(), &reports)
+            .await
+            .unwrap();
+        let marker: String =
+            sqlx::query_scalar("SELECT purged_artifacts FROM preview_records WHERE id = 'p1'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(marker, ",reports,");
+
+        let audit = RetentionQueries::list_audit(
+            db.pool(),
+            &PurgeAuditFilter {
+                preview_id: Some("p1".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(audit.len(), 2);
+        assert_eq!(audit[0].artifact, "reports");
+        assert_eq!(audit[0].storage_keys, reports.storage_keys);
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::time::Instant;
+
+use crate::db::migrations::{Migration, MigrationDialect, SchemaObject};
+use crate::db::traits::AppliedMigration;
+
+pub struct SchemaMigrationQueries;
+
+impl SchemaMigrationQueries {
+    pub async fn ensure_table(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at TEXT NOT NULL,
+                execution_ms INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<AppliedMigration>> {
+        Self::ensure_table(pool).await?;
+        let rows = sqlx::query(
+            "SELECT version, name, checksum, applied_at, execution_ms FROM schema_migrations \
+             ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_applied_migration).collect()
+    }
+
+    /// 语句与登记在同一事务内，失败时整体回滚；
+    /// SQLite 不支持 `ADD COLUMN IF NOT EXISTS`，已存在的列跳过
+    pub async fn apply(pool: &SqlitePool, migration: &Migration) -> Result<()> {
+        Self::ensure_table(pool).await?;
+        let started = Instant::now();
+        let mut tx = pool.begin().await?;
+        for statement in migration.statements(MigrationDialect::Sqlite) {
+            if let Some(SchemaObject::Column { table, column }) =
+                SchemaObject::created_by(statement)
+            {
+                let exists: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ? COLLATE NOCASE",
+                )
+                .bind(table)
+                .bind(column)
+                .fetch_one(&mut *tx)
+                .await?;
+                if exists > 0 {
+                    continue;
+                }
+            }
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at, execution_ms) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum(MigrationDialect::Sqlite))
+        .bind(Utc::now().to_rfc3339())
+        .bind(started.elapsed().as_millis() as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn map_applied_migration(row: &SqliteRow) -> Result<AppliedMigration> {
+    Ok(AppliedMigration {
+        version: row.get("version"),
+        name: row.get("name"),
+        checksum: row.get("checksum"),
+        applied_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("applied_at"))?
+            .with_timezone(&Utc),
+        execution_ms: row.get("execution_ms"),
+    })
+}
@@ -36,6 +36,116 @@ impl SchemaManager {
         Self::create_user_login_records_table(pool).await?;
         Self::create_db_outbox_table(pool).await?;
         Self::create_worker_results_queue_table(pool).await?;
+        Self::create_dead_letters_table(pool).await?;
+        Self::create_worker_controls_table(pool).await?;
+        Self::create_preview_batch_items_table(pool).await?;
+        Self::create_failover_journal_table(pool).await?;
+        Ok(())
+    }
+
+    /// 降级期间的写入日志，仅在本地降级库中使用
+    pub(crate) async fn create_failover_journal_table(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS failover_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity TEXT NOT NULL,
+                entity_key TEXT NOT NULL,
+                payload TEXT,
+                status TEXT NOT NULL DEFAULT 'pending',
+                revision INTEGER NOT NULL DEFAULT 1,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                detail TEXT,
+                recorded_at TEXT NOT NULL,
+                replayed_at TEXT,
+                UNIQUE (entity, entity_key)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_preview_batch_items_table(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS preview_batch_items (
+                batch_id TEXT NOT NULL,
+                item_index INTEGER NOT NULL,
+                submitted_by TEXT NOT NULL,
+                status TEXT NOT NULL,
+                preview_id TEXT,
+                third_party_request_id TEXT,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (batch_id, item_index)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_worker_controls_table(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS worker_controls (
+                worker_id TEXT PRIMARY KEY,
+                state TEXT NOT NULL DEFAULT 'active',
+                reason TEXT,
+                updated_by TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_dead_letters_table(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS preview_dead_letters (
+                id TEXT PRIMARY KEY,
+                preview_id TEXT NOT NULL,
+                queue TEXT NOT NULL,
+                source TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                failure_reason TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                status TEXT NOT NULL DEFAULT 'dead',
+                resolved_by TEXT,
+                resolved_at TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_dead_letters_status
+            ON preview_dead_letters(status, created_at)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_dead_letters_preview
+            ON preview_dead_letters(preview_id)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         Ok(())
     }
 
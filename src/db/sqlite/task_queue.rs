@@ -0,0 +1,520 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+use crate::db::traits::{
+    shard_queue_key_pattern, validate_queue_table_name, NewQueuedTask, QueuedTaskRecord,
+    QueuedTaskStats, TaskClaimRequest,
+};
+
+const TASK_COLUMNS: &str = "id, queue, preview_id, payload, status, attempts, lease_owner, \
+    lease_token, lease_expires_at, visible_at, last_error, created_at, updated_at";
+
+pub struct TaskQueueQueries;
+
+impl TaskQueueQueries {
+    pub async fn ensure_table(pool: &SqlitePool, table: &str) -> Result<()> {
+        validate_queue_table_name(table)?;
+        sqlx::query(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                id TEXT PRIMARY KEY,
+                queue TEXT NOT NULL,
+                preview_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                lease_owner TEXT,
+                lease_token TEXT,
+                lease_expires_at INTEGER,
+                visible_at INTEGER NOT NULL,
+                last_error TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            "#
+        ))
+        .execute(pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_{table}_preview ON {table}(queue, preview_id)"
+        ))
+        .execute(pool)
+        .await?;
+
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_{table}_claim ON {table}(queue, status, visible_at)"
+        ))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 同一预审已在队列中时刷新载荷并重置为待处理；正在被领取的任务保持不变
+    pub async fn enqueue(pool: &SqlitePool, table: &str, task: &NewQueuedTask) -> Result<()> {
+        let mut conn = pool.acquire().await?;
+        match Self::upsert(&mut conn, table, task).await {
+            // 连接在建表前已载入 schema 时看不到唯一索引；读一次表让 SQLite 刷新 schema 后重试
+            Err(err)
+                if err
+                    .to_string()
+                    .contains("ON CONFLICT clause does not match") =>
+            {
+                sqlx::query(&format!("SELECT 1 FROM {table} LIMIT 1"))
+                    .fetch_optional(&mut *conn)
+                    .await?;
+                Self::upsert(&mut conn, table, task).await
+            }
+            result => result,
+        }
+    }
+
+    async fn upsert(conn: &mut SqliteConnection, table: &str, task: &NewQueuedTask) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO {table} (id, queue, preview_id, payload, status, attempts, visible_at)
+            VALUES (?, ?, ?, ?, 'pending', 0, ?)
+            ON CONFLICT(queue, preview_id) DO UPDATE SET
+                payload = excluded.payload,
+                status = 'pending',
+                attempts = 0,
+                visible_at = excluded.visible_at,
+                last_error = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE {table}.status <> 'leased'
+            "#
+        ))
+        .bind(&task.id)
+        .bind(&task.queue)
+        .bind(&task.preview_id)
+        .bind(&task.payload)
+        .bind(task.visible_at)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn claim(
+        pool: &SqlitePool,
+        table: &str,
+        claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'failed', last_error = COALESCE(last_error, '租约过期且已达最大尝试次数'),
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE queue = ? AND status = 'leased' AND lease_expires_at <= ? AND attempts >= ?
+            "#
+        ))
+        .bind(&claim.queue)
+        .bind(claim.now)
+        .bind(claim.max_attempts)
+        .execute(pool)
+        .await?;
+
+        // 单条 UPDATE 在 SQLite 中原子执行，多个消费者不会领取到同一任务
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'leased', attempts = attempts + 1, lease_owner = ?, lease_token = ?,
+                lease_expires_at = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id IN (
+                SELECT id FROM {table}
+                WHERE queue = ?
+                  AND ((status = 'pending' AND visible_at <= ?)
+                    OR (status = 'leased' AND lease_expires_at <= ?))
+                ORDER BY visible_at ASC, created_at ASC
+                LIMIT ?
+            )
+            RETURNING {TASK_COLUMNS}
+            "#
+        ))
+        .bind(&claim.consumer_id)
+        .bind(&claim.lease_token)
+        .bind(claim.lease_expires_at)
+        .bind(&claim.queue)
+        .bind(claim.now)
+        .bind(claim.now)
+        .bind(claim.limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let mut tasks: Vec<QueuedTaskRecord> = rows.iter().map(map_task).collect();
+        tasks.sort_by_key(|task| task.visible_at);
+        Ok(tasks)
+    }
+
+    pub async fn extend_lease(
+        pool: &SqlitePool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        lease_expires_at: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET lease_expires_at = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND lease_token = ? AND status = 'leased'
+            "#
+        ))
+        .bind(lease_expires_at)
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn complete(
+        pool: &SqlitePool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {table} WHERE id = ? AND lease_token = ?"
+        ))
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn release(
+        pool: &SqlitePool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<bool> {
+        let status = if retry_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = ?, visible_at = COALESCE(?, visible_at), last_error = ?,
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND lease_token = ?
+            "#
+        ))
+        .bind(status)
+        .bind(retry_at)
+        .bind(error)
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn return_task(
+        pool: &SqlitePool,
+        table: &str,
+        task_id: &str,
+        lease_token: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET status = 'pending', attempts = MAX(attempts - 1, 0),
+                lease_owner = NULL, lease_token = NULL, lease_expires_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND lease_token = ?
+            "#
+        ))
+        .bind(task_id)
+        .bind(lease_token)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn stats(pool: &SqlitePool, table: &str, queue: &str) -> Result<QueuedTaskStats> {
+        let rows = sqlx::query(&format!(
+            "SELECT status, COUNT(*) AS total FROM {table} WHERE queue = ? GROUP BY status"
+        ))
+        .bind(queue)
+        .fetch_all(pool)
+        .await?;
+
+        let mut stats = QueuedTaskStats::default();
+        for row in rows {
+            let total = row.get::<i64, _>("total").max(0) as u64;
+            match row.get::<String, _>("status").as_str() {
+                "pending" => stats.pending = total,
+                "leased" => stats.leased = total,
+                "failed" => stats.failed = total,
+                _ => {}
+            }
+        }
+        Ok(stats)
+    }
+
+    pub async fn list_failed(
+        pool: &SqlitePool,
+        table: &str,
+        queue: &str,
+        limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {TASK_COLUMNS} FROM {table}
+            WHERE queue = ? AND status = 'failed'
+            ORDER BY updated_at ASC
+            LIMIT ?
+            "#
+        ))
+        .bind(queue)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.iter().map(map_task).collect())
+    }
+
+    pub async fn delete(pool: &SqlitePool, table: &str, task_id: &str) -> Result<bool> {
+        let result = sqlx::query(&format!("DELETE FROM {table} WHERE id = ?"))
+            .bind(task_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn cancel_pending(
+        pool: &SqlitePool,
+        table: &str,
+        queue: &str,
+        preview_id: &str,
+    ) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+            DELETE FROM {table}
+            WHERE queue = ? AND status = 'pending'
+              AND (preview_id = ? OR preview_id LIKE ? ESCAPE '\')
+            "#
+        ))
+        .bind(queue)
+        .bind(preview_id)
+        .bind(shard_queue_key_pattern(preview_id))
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn map_task(row: &SqliteRow) -> QueuedTaskRecord {
+    QueuedTaskRecord {
+        id: row.get("id"),
+        queue: row.get("queue"),
+        preview_id: row.get("preview_id"),
+        payload: row.get("payload"),
+        status: row.get("status"),
+        attempts: row.get::<i64, _>("attempts") as i32,
+        lease_owner: row.get("lease_owner"),
+        lease_token: row.get("lease_token"),
+        lease_expires_at: row.get("lease_expires_at"),
+        visible_at: row.get("visible_at"),
+        last_error: row.get("last_error"),
+        created_at: parse_time(row.get("created_at")),
+        updated_at: parse_time(row.get("updated_at")),
+    }
+}
+
+fn parse_time(value: Option<String>) -> DateTime<Utc> {
+    value
+        .and_then(|s| {
+            NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S%.f"))
+                .ok()
+        })
+        .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const TABLE: &str = "preview_queue";
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        TaskQueueQueries::ensure_table(&pool, TABLE).await.unwrap();
+        pool
+    }
+
+    fn new_task(preview_id: &str) -> NewQueuedTask {
+        NewQueuedTask {
+            id: format!("task-{}", preview_id),
+            queue: "preview".to_string(),
+            preview_id: preview_id.to_string(),
+            payload: "{}".to_string(),
+            visible_at: 100,
+        }
+    }
+
+    fn claim(consumer: &str, now: i64, limit: u32) -> TaskClaimRequest {
+        TaskClaimRequest {
+            queue: "preview".to_string(),
+            consumer_id: consumer.to_string(),
+            lease_token: format!("lease-{}-{}", consumer, now),
+            now,
+            lease_expires_at: now + 60,
+            max_attempts: 2,
+            limit,
+        }
+    }
+
+    #[tokio::test]
+    async fn lease_is_exclusive_until_expiry() {
+        let pool = pool().await;
+        TaskQueueQueries::enqueue(&pool, TABLE, &new_task("p1"))
+            .await
+            .unwrap();
+
+        let first = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 100, 5))
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].attempts, 1);
+
+        let second = TaskQueueQueries::claim(&pool, TABLE, &claim("b", 120, 5))
+            .await
+            .unwrap();
+        assert!(second.is_empty());
+
+        // 租约过期后可被其他消费者领取，原持有者的令牌随之失效
+        let reclaimed = TaskQueueQueries::claim(&pool, TABLE, &claim("b", 161, 5))
+            .await
+            .unwrap();
+        assert_eq!(reclaimed.len(), 1);
+        assert_eq!(reclaimed[0].attempts, 2);
+        let stale = first[0].lease_token.as_deref().unwrap();
+        assert!(!TaskQueueQueries::complete(&pool, TABLE, "task-p1", stale)
+            .await
+            .unwrap());
+
+        // 达到最大尝试次数后再次过期即标记为失败
+        let exhausted = TaskQueueQueries::claim(&pool, TABLE, &claim("c", 300, 5))
+            .await
+            .unwrap();
+        assert!(exhausted.is_empty());
+        let stats = TaskQueueQueries::stats(&pool, TABLE, "preview")
+            .await
+            .unwrap();
+        assert_eq!(stats.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn release_schedules_retry_and_complete_removes() {
+        let pool = pool().await;
+        TaskQueueQueries::enqueue(&pool, TABLE, &new_task("p2"))
+            .await
+            .unwrap();
+        let task = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 100, 1))
+            .await
+            .unwrap()
+            .remove(0);
+        let token = task.lease_token.clone().unwrap();
+        assert!(
+            TaskQueueQueries::release(&pool, TABLE, &task.id, &token, "boom", Some(200))
+                .await
+                .unwrap()
+        );
+
+        assert!(TaskQueueQueries::claim(&pool, TABLE, &claim("a", 150, 1))
+            .await
+            .unwrap()
+            .is_empty());
+        let retried = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 200, 1))
+            .await
+            .unwrap();
+        assert_eq!(retried[0].last_error.as_deref(), Some("boom"));
+
+        let token = retried[0].lease_token.clone().unwrap();
+        assert!(TaskQueueQueries::complete(&pool, TABLE, &task.id, &token)
+            .await
+            .unwrap());
+        let stats = TaskQueueQueries::stats(&pool, TABLE, "preview")
+            .await
+            .unwrap();
+        assert_eq!(stats.pending + stats.leased + stats.failed, 0);
+    }
+
+    #[tokio::test]
+    async fn returned_task_is_visible_again_without_counting_an_attempt() {
+        let pool = pool().await;
+        TaskQueueQueries::enqueue(&pool, TABLE, &new_task("p5"))
+            .await
+            .unwrap();
+        let task = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 100, 1))
+            .await
+            .unwrap()
+            .remove(0);
+        let token = task.lease_token.clone().unwrap();
+        assert!(
+            TaskQueueQueries::return_task(&pool, TABLE, &task.id, &token)
+                .await
+                .unwrap()
+        );
+
+        let reclaimed = TaskQueueQueries::claim(&pool, TABLE, &claim("b", 101, 1))
+            .await
+            .unwrap();
+        assert_eq!(reclaimed.len(), 1);
+        assert_eq!(reclaimed[0].attempts, 1);
+        assert!(
+            !TaskQueueQueries::return_task(&pool, TABLE, &task.id, &token)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_removes_only_unclaimed_tasks() {
+        let pool = pool().await;
+        for preview_id in ["p3", "p4"] {
+            TaskQueueQueries::enqueue(&pool, TABLE, &new_task(preview_id))
+                .await
+                .unwrap();
+        }
+        let claimed = TaskQueueQueries::claim(&pool, TABLE, &claim("a", 100, 1))
+            .await
+            .unwrap();
+        let leased = claimed[0].preview_id.clone();
+        let pending = if leased == "p3" { "p4" } else { "p3" };
+
+        assert_eq!(
+            TaskQueueQueries::cancel_pending(&pool, TABLE, "preview", &leased)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            TaskQueueQueries::cancel_pending(&pool, TABLE, "preview", pending)
+                .await
+                .unwrap(),
+            1
+        );
+        let stats = TaskQueueQueries::stats(&pool, TABLE, "preview")
+            .await
+            .unwrap();
+        assert_eq!((stats.pending, stats.leased), (0, 1));
+    }
+}
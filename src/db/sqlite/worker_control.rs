@@ -0,0 +1,99 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use crate::db::traits::WorkerControlRecord;
+
+pub struct WorkerControlQueries;
+
+impl WorkerControlQueries {
+    pub async fn save(pool: &SqlitePool, record: &WorkerControlRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_controls (worker_id, state, reason, updated_by, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(worker_id) DO UPDATE SET
+                state = excluded.state,
+                reason = excluded.reason,
+                updated_by = excluded.updated_by,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&record.worker_id)
+        .bind(record.state.as_str())
+        .bind(&record.reason)
+        .bind(&record.updated_by)
+        .bind(record.updated_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<WorkerControlRecord>> {
+        let rows = sqlx::query(
+            "SELECT worker_id, state, reason, updated_by, updated_at FROM worker_controls \
+             ORDER BY worker_id",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.iter().map(map_worker_control).collect()
+    }
+}
+
+fn map_worker_control(row: &SqliteRow) -> Result<WorkerControlRecord> {
+    Ok(WorkerControlRecord {
+        worker_id: row.get("worker_id"),
+        state: row.get::<String, _>("state").parse()?,
+        reason: row.get("reason"),
+        updated_by: row.get("updated_by"),
+        updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?
+            .with_timezone(&Utc),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::schemas::SchemaManager;
+    use crate::db::traits::WorkerControlState;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn control(worker_id: &str, state: WorkerControlState) -> WorkerControlRecord {
+        WorkerControlRecord {
+            worker_id: worker_id.to_string(),
+            state,
+            reason: Some("维护".to_string()),
+            updated_by: "admin".to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn save_overwrites_previous_state() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SchemaManager::create_worker_controls_table(&pool)
+            .await
+            .unwrap();
+
+        WorkerControlQueries::save(&pool, &control("w1", WorkerControlState::Draining))
+            .await
+            .unwrap();
+        WorkerControlQueries::save(&pool, &control("w2", WorkerControlState::Revoked))
+            .await
+            .unwrap();
+        WorkerControlQueries::save(&pool, &control("w1", WorkerControlState::Drained))
+            .await
+            .unwrap();
+
+        let records = WorkerControlQueries::list(&pool).await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].worker_id, "w1");
+        assert_eq!(records[0].state, WorkerControlState::Drained);
+        assert_eq!(records[1].state, WorkerControlState::Revoked);
+    }
+}
@@ -2,7 +2,9 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{fmt, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use super::migrations::{Migration, MigrationDialect};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PreviewRequestRecord {
@@ -140,6 +142,7 @@ pub enum PreviewStatus {
     Processing,
     Completed,
     Failed,
+    Cancelled,
 }
 
 impl fmt::Display for PreviewStatus {
@@ -150,6 +153,7 @@ impl fmt::Display for PreviewStatus {
             PreviewStatus::Processing => write!(f, "processing"),
             PreviewStatus::Completed => write!(f, "completed"),
             PreviewStatus::Failed => write!(f, "failed"),
+            PreviewStatus::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -162,8 +166,16 @@ impl PreviewStatus {
             PreviewStatus::Processing => "processing",
             PreviewStatus::Completed => "completed",
             PreviewStatus::Failed => "failed",
+            PreviewStatus::Cancelled => "cancelled",
         }
     }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            PreviewStatus::Completed | PreviewStatus::Failed | PreviewStatus::Cancelled
+        )
+    }
 }
 
 impl FromStr for PreviewStatus {
@@ -176,6 +188,7 @@ impl FromStr for PreviewStatus {
             "processing" => Ok(PreviewStatus::Processing),
             "completed" => Ok(PreviewStatus::Completed),
             "failed" => Ok(PreviewStatus::Failed),
+            "cancelled" => Ok(PreviewStatus::Cancelled),
             _ => Err(()),
         }
     }
@@ -202,6 +215,8 @@ pub struct PreviewFilter {
     pub status: Option<PreviewStatus>,
     pub theme_id: Option<String>,
     pub third_party_request_id: Option<String>,
+    /// 按最近一次执行的 worker 过滤
+    pub worker_id: Option<String>,
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
     pub limit: Option<u32>,
@@ -216,6 +231,7 @@ pub struct PreviewStatusCounts {
     pub failed: u64,
     pub pending: u64,
     pub queued: u64,
+    pub cancelled: u64,
 }
 
 impl PreviewStatusCounts {
@@ -227,6 +243,7 @@ impl PreviewStatusCounts {
             PreviewStatus::Failed => self.failed += 1,
             PreviewStatus::Pending => self.pending += 1,
             PreviewStatus::Queued => self.queued += 1,
+            PreviewStatus::Cancelled => self.cancelled += 1,
         }
     }
 }
@@ -575,6 +592,159 @@ pub trait Database: Send + Sync {
     ) -> Result<()>;
     async fn update_material_download_payload(&self, id: &str, payload: &str) -> Result<()>;
 
+    /// 将预审尚未处理的下载任务标记为 cancelled，返回受影响条数
+    async fn cancel_material_downloads(&self, _preview_id: &str) -> Result<u64> {
+        Err(anyhow!("cancel_material_downloads not implemented"))
+    }
+
+    /// 最近一次入队的下载载荷，重新执行并要求重新下载时使用
+    async fn latest_material_download_payload(&self, _preview_id: &str) -> Result<Option<String>> {
+        Err(anyhow!("latest_material_download_payload not implemented"))
+    }
+
+    // 数据库任务队列：按租约领取，租约过期的任务可被其他消费者重新领取
+    async fn ensure_task_queue_table(&self, _table: &str) -> Result<()> {
+        Err(anyhow!("ensure_task_queue_table not implemented"))
+    }
+
+    async fn enqueue_queued_task(&self, _table: &str, _task: &NewQueuedTask) -> Result<()> {
+        Err(anyhow!("enqueue_queued_task not implemented"))
+    }
+
+    /// 领取可见的待处理任务及租约已过期的任务，`attempts` 加一；
+    /// 租约过期且已达最大尝试次数的任务直接标记为 failed
+    async fn claim_queued_tasks(
+        &self,
+        _table: &str,
+        _claim: &TaskClaimRequest,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        Err(anyhow!("claim_queued_tasks not implemented"))
+    }
+
+    /// 续租，返回 false 表示租约已丢失（过期后被他人领取或已结束）
+    async fn extend_queued_task_lease(
+        &self,
+        _table: &str,
+        _task_id: &str,
+        _lease_token: &str,
+        _lease_expires_at: i64,
+    ) -> Result<bool> {
+        Err(anyhow!("extend_queued_task_lease not implemented"))
+    }
+
+    /// 处理成功后删除任务
+    async fn complete_queued_task(
+        &self,
+        _table: &str,
+        _task_id: &str,
+        _lease_token: &str,
+    ) -> Result<bool> {
+        Err(anyhow!("complete_queued_task not implemented"))
+    }
+
+    /// 处理失败后释放租约：`retry_at` 为 None 时标记为 failed，否则在该时刻重新可见
+    async fn release_queued_task(
+        &self,
+        _table: &str,
+        _task_id: &str,
+        _lease_token: &str,
+        _error: &str,
+        _retry_at: Option<i64>,
+    ) -> Result<bool> {
+        Err(anyhow!("release_queued_task not implemented"))
+    }
+
+    /// 处理器未开始执行就交还任务：立即重新可见，并撤销本次领取计入的尝试次数
+    async fn return_queued_task(
+        &self,
+        _table: &str,
+        _task_id: &str,
+        _lease_token: &str,
+    ) -> Result<bool> {
+        Err(anyhow!("return_queued_task not implemented"))
+    }
+
+    async fn queued_task_stats(&self, _table: &str, _queue: &str) -> Result<QueuedTaskStats> {
+        Err(anyhow!("queued_task_stats not implemented"))
+    }
+
+    async fn list_failed_queued_tasks(
+        &self,
+        _table: &str,
+        _queue: &str,
+        _limit: u32,
+    ) -> Result<Vec<QueuedTaskRecord>> {
+        Err(anyhow!("list_failed_queued_tasks not implemented"))
+    }
+
+    async fn delete_queued_task(&self, _table: &str, _task_id: &str) -> Result<bool> {
+        Err(anyhow!("delete_queued_task not implemented"))
+    }
+
+    /// 删除预审（含其 PDF 分片）尚未被领取的排队任务，已领取的任务由执行方根据取消标记中止
+    async fn cancel_queued_tasks(
+        &self,
+        _table: &str,
+        _queue: &str,
+        _preview_id: &str,
+    ) -> Result<u64> {
+        Err(anyhow!("cancel_queued_tasks not implemented"))
+    }
+
+    // 死信：永久失败的预审任务，保留载荷供检查、修改后重新入队或丢弃
+    /// 按 id 写入，已存在时刷新载荷与失败信息
+    async fn save_dead_letter(&self, _record: &DeadLetterRecord) -> Result<()> {
+        Err(anyhow!("save_dead_letter not implemented"))
+    }
+
+    async fn list_dead_letters(&self, _filter: &DeadLetterFilter) -> Result<Vec<DeadLetterRecord>> {
+        Err(anyhow!("list_dead_letters not implemented"))
+    }
+
+    async fn get_dead_letter(&self, _id: &str) -> Result<Option<DeadLetterRecord>> {
+        Err(anyhow!("get_dead_letter not implemented"))
+    }
+
+    /// 将 `dead` 状态的死信标记为 requeued/discarded，`payload` 非空时一并更新；
+    /// 返回 false 表示死信不存在或已被处理
+    async fn resolve_dead_letter(
+        &self,
+        _id: &str,
+        _status: DeadLetterStatus,
+        _payload: Option<&str>,
+        _resolved_by: &str,
+    ) -> Result<bool> {
+        Err(anyhow!("resolve_dead_letter not implemented"))
+    }
+
+    // worker 运维状态：停止接单、排空、吊销，主节点重启后恢复
+    /// 按 worker_id 写入，已存在时覆盖
+    async fn save_worker_control(&self, _record: &WorkerControlRecord) -> Result<()> {
+        Err(anyhow!("save_worker_control not implemented"))
+    }
+
+    async fn list_worker_controls(&self) -> Result<Vec<WorkerControlRecord>> {
+        Err(anyhow!("list_worker_controls not implemented"))
+    }
+
+    /// 故障恢复回放：整条覆盖预审记录（不存在则插入），保留记录自带的时间戳
+    async fn reconcile_preview_record(&self, _record: &PreviewRecord) -> Result<()> {
+        Err(anyhow!("reconcile_preview_record not implemented"))
+    }
+
+    // 批量提交：记录每一项的受理结果，预审的最新状态仍以 preview_records 为准
+    async fn save_preview_batch_items(&self, _items: &[PreviewBatchItemRecord]) -> Result<()> {
+        Err(anyhow!("save_preview_batch_items not implemented"))
+    }
+
+    /// 按 item_index 升序返回
+    async fn list_preview_batch_items(
+        &self,
+        _batch_id: &str,
+    ) -> Result<Vec<PreviewBatchItemRecord>> {
+        Err(anyhow!("list_preview_batch_items not implemented"))
+    }
+
     async fn get_download_cache_token(
         &self,
         url: &str,
@@ -602,6 +772,115 @@ pub trait Database: Send + Sync {
     ) -> Result<Option<PreviewShareTokenRecord>> {
         Err(anyhow!("consume_preview_share_token not implemented"))
     }
+
+    // 版本化迁移：返回 None 表示该后端未接入，启动时跳过
+    fn migration_dialect(&self) -> Option<MigrationDialect> {
+        None
+    }
+
+    /// 按版本升序返回 `schema_migrations` 中的记录，表不存在时先建表
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        Err(anyhow!("list_applied_migrations not implemented"))
+    }
+
+    /// 执行该迁移在本后端方言下的语句并写入 `schema_migrations`
+    async fn apply_migration(&self, _migration: &Migration) -> Result<()> {
+        Err(anyhow!("apply_migration not implemented"))
+    }
+
+    // 留存清理：候选只含已结束、未被法律保全且该类产物尚未清理的预审，按创建时间升序
+    async fn list_retention_candidates(
+        &self,
+        _query: &RetentionCandidateQuery,
+    ) -> Result<Vec<RetentionCandidate>> {
+        Err(anyhow!("list_retention_candidates not implemented"))
+    }
+
+    /// 删除/清空该类产物的数据库行、标记已清理并写入审计，返回受影响行数；
+    /// 预审不存在或已被法律保全时不做任何修改，返回 None
+    async fn purge_preview_artifact(&self, _purge: &ArtifactPurge) -> Result<Option<i64>> {
+        Err(anyhow!("purge_preview_artifact not implemented"))
+    }
+
+    /// 删除早于 `before` 的接口调用统计与登录记录并写入审计
+    async fn purge_log_records(
+        &self,
+        _before: DateTime<Utc>,
+        _purge: &ArtifactPurge,
+    ) -> Result<i64> {
+        Err(anyhow!("purge_log_records not implemented"))
+    }
+
+    /// 设置或解除法律保全，预审不存在时返回 false
+    async fn set_preview_legal_hold(&self, _update: &LegalHoldUpdate) -> Result<bool> {
+        Err(anyhow!("set_preview_legal_hold not implemented"))
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHoldRecord>> {
+        Err(anyhow!("list_legal_holds not implemented"))
+    }
+
+    /// 按清理时间倒序返回
+    async fn list_purge_audit(&self, _filter: &PurgeAuditFilter) -> Result<Vec<PurgeAuditRecord>> {
+        Err(anyhow!("list_purge_audit not implemented"))
+    }
+
+    // 字段级加密：盲索引令牌对应的密文，以及密钥轮换时的逐行扫描
+    /// 按令牌写入，已存在时覆盖密文
+    async fn save_pii_lookup_value(&self, _token: &str, _ciphertext: &str) -> Result<()> {
+        Err(anyhow!("save_pii_lookup_value not implemented"))
+    }
+
+    /// 返回令牌到密文的映射，不存在的令牌不出现在结果中
+    async fn get_pii_lookup_values(&self, _tokens: &[String]) -> Result<HashMap<String, String>> {
+        Err(anyhow!("get_pii_lookup_values not implemented"))
+    }
+
+    /// 按主键升序返回 `after` 之后的行，列顺序与 `PiiTable::columns` 一致
+    async fn list_pii_rows(
+        &self,
+        _table: PiiTable,
+        _after: Option<&str>,
+        _limit: u32,
+    ) -> Result<Vec<PiiRow>> {
+        Err(anyhow!("list_pii_rows not implemented"))
+    }
+
+    /// 把旧版本主密钥的密文与历史明文重新加密；仅字段加密包装层实现
+    async fn rotate_pii_keys(&self, _batch_size: u32) -> Result<PiiRotationSummary> {
+        Err(anyhow!("字段加密未启用"))
+    }
+
+    /// 各列仅在当前值仍以 `expected_prefix` 开头时才更新，否则整行不变并返回 false，
+    /// 避免覆盖扫描之后的并发写入
+    async fn update_pii_row(&self, _table: PiiTable, _update: &PiiRowUpdate) -> Result<bool> {
+        Err(anyhow!("update_pii_row not implemented"))
+    }
+
+    // 内容寻址存储：blob 的引用数取自 `preview_material_files.stored_original_key`
+    /// 登记 blob 或刷新最近引用时间；已被回收任务认领时不做修改并返回 false
+    async fn touch_storage_blob(&self, _blob: &StorageBlobRecord) -> Result<bool> {
+        Err(anyhow!("touch_storage_blob not implemented"))
+    }
+
+    /// 以 `claim_id` 认领一批无引用且超过宽限期的 blob，按最近引用时间升序；
+    /// 认领超时的视为回收中断，可被再次认领
+    async fn claim_unreferenced_blobs(
+        &self,
+        _query: &BlobGcQuery,
+    ) -> Result<Vec<StorageBlobRecord>> {
+        Err(anyhow!("claim_unreferenced_blobs not implemented"))
+    }
+
+    /// 删除仍由 `claim_id` 持有的 blob 记录，认领已被接管时返回 false
+    async fn delete_storage_blob(&self, _storage_key: &str, _claim_id: &str) -> Result<bool> {
+        Err(anyhow!("delete_storage_blob not implemented"))
+    }
+
+    /// 放弃认领，blob 恢复可用
+    async fn release_storage_blob(&self, _storage_key: &str, _claim_id: &str) -> Result<()> {
+        Err(anyhow!("release_storage_blob not implemented"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -803,6 +1082,252 @@ pub struct MaterialDownloadQueueRecord {
     pub updated_at: DateTime<Utc>,
 }
 
+/// 数据库任务队列中的任务；时间字段为 Unix 秒，便于跨数据库比较
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTaskRecord {
+    pub id: String,
+    pub queue: String,
+    pub preview_id: String,
+    pub payload: String,
+    pub status: String, // pending | leased | failed
+    pub attempts: i32,
+    pub lease_owner: Option<String>,
+    pub lease_token: Option<String>,
+    pub lease_expires_at: Option<i64>,
+    pub visible_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// PDF 分片的队列键标记，见 [`shard_queue_key`]
+const SHARD_KEY_MARKER: &str = "#shard-";
+
+/// PDF 分片在数据库队列中的键。所属预审执行期间其任务处于租约中，
+/// 分片使用独立的键才不会被同一预审的去重规则吞掉；所属预审 ID 保留在载荷中
+pub fn shard_queue_key(preview_id: &str, job_id: &str, index: usize) -> String {
+    format!("{}{}{}-{}", preview_id, SHARD_KEY_MARKER, job_id, index)
+}
+
+/// 匹配某预审全部分片键的 `LIKE ... ESCAPE '\'` 模式
+pub fn shard_queue_key_pattern(preview_id: &str) -> String {
+    like_prefix_pattern(&format!("{}{}", preview_id, SHARD_KEY_MARKER))
+}
+
+/// 前缀匹配的 LIKE 模式，以 `\` 转义通配符
+fn like_prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[derive(Debug, Clone)]
+pub struct NewQueuedTask {
+    pub id: String,
+    pub queue: String,
+    /// 队列键：普通任务为预审 ID，PDF 分片为 [`shard_queue_key`]
+    pub preview_id: String,
+    pub payload: String,
+    pub visible_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskClaimRequest {
+    pub queue: String,
+    pub consumer_id: String,
+    pub lease_token: String,
+    pub now: i64,
+    pub lease_expires_at: i64,
+    pub max_attempts: i32,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueuedTaskStats {
+    pub pending: u64,
+    pub leased: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeadLetterStatus {
+    Dead,
+    Requeued,
+    Discarded,
+}
+
+impl DeadLetterStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dead => "dead",
+            Self::Requeued => "requeued",
+            Self::Discarded => "discarded",
+        }
+    }
+}
+
+impl FromStr for DeadLetterStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dead" => Ok(Self::Dead),
+            "requeued" => Ok(Self::Requeued),
+            "discarded" => Ok(Self::Discarded),
+            other => Err(anyhow!("未知的死信状态: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterRecord {
+    pub id: String,
+    pub preview_id: String,
+    pub queue: String,
+    /// 产生死信的队列驱动：local | nats | database
+    pub source: String,
+    pub payload: String,
+    pub failure_reason: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub status: DeadLetterStatus,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkerControlState {
+    #[default]
+    Active,
+    /// 停止接收新任务，执行中的任务照常完成
+    Cordoned,
+    /// 停止接单，执行中的任务完成后通知 worker 退出
+    Draining,
+    /// 已通知退出，重新上线后仍保持停止接单
+    Drained,
+    /// 凭证失效，worker 的所有内部请求被拒绝
+    Revoked,
+}
+
+impl WorkerControlState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Cordoned => "cordoned",
+            Self::Draining => "draining",
+            Self::Drained => "drained",
+            Self::Revoked => "revoked",
+        }
+    }
+
+    pub fn accepts_jobs(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+}
+
+impl FromStr for WorkerControlState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "active" => Ok(Self::Active),
+            "cordoned" => Ok(Self::Cordoned),
+            "draining" => Ok(Self::Draining),
+            "drained" => Ok(Self::Drained),
+            "revoked" => Ok(Self::Revoked),
+            other => Err(anyhow!("未知的 worker 状态: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerControlRecord {
+    pub worker_id: String,
+    pub state: WorkerControlState,
+    pub reason: Option<String>,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchItemStatus {
+    /// 已入队
+    Accepted,
+    /// 重复提交，折叠到已有预审
+    Duplicate,
+    /// 解析、校验或入队失败
+    Rejected,
+}
+
+impl BatchItemStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Duplicate => "duplicate",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl FromStr for BatchItemStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "accepted" => Ok(Self::Accepted),
+            "duplicate" => Ok(Self::Duplicate),
+            "rejected" => Ok(Self::Rejected),
+            other => Err(anyhow!("未知的批量提交状态: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewBatchItemRecord {
+    pub batch_id: String,
+    pub item_index: i32,
+    /// 提交方：第三方客户端ID或会话用户ID，查询批次时校验
+    pub submitted_by: String,
+    pub status: BatchItemStatus,
+    pub preview_id: Option<String>,
+    pub third_party_request_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeadLetterFilter {
+    pub status: Option<DeadLetterStatus>,
+    pub preview_id: Option<String>,
+    pub source: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 队列表名会直接拼进 SQL，只允许字母、数字与下划线
+pub fn validate_queue_table_name(table: &str) -> Result<()> {
+    let valid = !table.is_empty()
+        && table.len() <= 64
+        && table.starts_with(|c: char| c.is_ascii_alphabetic())
+        && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("非法的任务队列表名: {}", table))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PreviewDedupMeta {
     pub user_id: String,
@@ -822,3 +1347,289 @@ pub enum PreviewDedupDecision {
     },
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: DateTime<Utc>,
+    pub execution_ms: i64,
+}
+
+/// 可按保留期清理的产物类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionArtifact {
+    /// 原始请求：任务载荷、请求摘要中的表单与材料数据、原始附件
+    RawRequest,
+    /// OCR 识别文本
+    OcrText,
+    /// 转换后的页面图片与文本框侧车文件
+    PageImages,
+    /// 评估结果与 HTML/PDF 报告
+    Reports,
+    /// 接口调用统计与登录记录，不区分预审
+    Logs,
+}
+
+impl RetentionArtifact {
+    pub const PER_PREVIEW: [RetentionArtifact; 4] = [
+        Self::RawRequest,
+        Self::OcrText,
+        Self::PageImages,
+        Self::Reports,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RawRequest => "raw_request",
+            Self::OcrText => "ocr_text",
+            Self::PageImages => "page_images",
+            Self::Reports => "reports",
+            Self::Logs => "logs",
+        }
+    }
+
+    /// `purged_artifacts` 列中的标记，形如 `,ocr_text,reports,`
+    pub fn purged_marker_pattern(&self) -> String {
+        format!("%,{},%", self.as_str())
+    }
+}
+
+impl FromStr for RetentionArtifact {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "raw_request" => Ok(Self::RawRequest),
+            "ocr_text" => Ok(Self::OcrText),
+            "page_images" => Ok(Self::PageImages),
+            "reports" => Ok(Self::Reports),
+            "logs" => Ok(Self::Logs),
+            other => Err(anyhow!("未知的产物类别: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionCandidateQuery {
+    pub artifact: RetentionArtifact,
+    pub created_before: DateTime<Utc>,
+    /// 只查该事项；事项取自以该预审为最新一次的预审请求
+    pub matter_id: Option<String>,
+    /// 排除有单独保留策略的事项
+    pub exclude_matters: Vec<String>,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionCandidate {
+    pub preview_id: String,
+    pub matter_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtifactPurge {
+    pub preview_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub artifact: RetentionArtifact,
+    /// 已从存储中删除的 key，原样写入审计
+    pub storage_keys: Vec<String>,
+    /// `retention` 表示后台按保留期清理，手动触发时为 `manual:<操作人>`
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeAuditRecord {
+    pub id: String,
+    pub preview_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub artifact: String,
+    pub storage_keys: Vec<String>,
+    pub db_rows: i64,
+    pub reason: String,
+    pub purged_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PurgeAuditFilter {
+    pub preview_id: Option<String>,
+    pub artifact: Option<RetentionArtifact>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LegalHoldUpdate {
+    pub preview_id: String,
+    pub hold: bool,
+    pub reason: Option<String>,
+    pub updated_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHoldRecord {
+    pub preview_id: String,
+    pub reason: Option<String>,
+    pub held_by: Option<String>,
+    pub held_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 存放敏感字段的表；轮换任务按此逐表扫描
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiTable {
+    PreviewRequests,
+    PreviewRecords,
+    TaskPayloads,
+    DeadLetters,
+    LoginRecords,
+    MaterialResults,
+    RuleResults,
+    WorkerResults,
+    MaterialDownloads,
+    LookupValues,
+}
+
+impl PiiTable {
+    pub const ALL: [PiiTable; 10] = [
+        PiiTable::PreviewRequests,
+        PiiTable::PreviewRecords,
+        PiiTable::TaskPayloads,
+        PiiTable::DeadLetters,
+        PiiTable::LoginRecords,
+        PiiTable::MaterialResults,
+        PiiTable::RuleResults,
+        PiiTable::WorkerResults,
+        PiiTable::MaterialDownloads,
+        PiiTable::LookupValues,
+    ];
+
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            PiiTable::PreviewRequests => "preview_requests",
+            PiiTable::PreviewRecords => "preview_records",
+            PiiTable::TaskPayloads => "preview_task_payloads",
+            PiiTable::DeadLetters => "preview_dead_letters",
+            PiiTable::LoginRecords => "user_login_records",
+            PiiTable::MaterialResults => "preview_material_results",
+            PiiTable::RuleResults => "preview_rule_results",
+            PiiTable::WorkerResults => "worker_results_queue",
+            PiiTable::MaterialDownloads => "material_download_queue",
+            PiiTable::LookupValues => "pii_lookup_values",
+        }
+    }
+
+    pub fn key_column(&self) -> &'static str {
+        match self {
+            PiiTable::TaskPayloads => "preview_id",
+            PiiTable::LookupValues => "token",
+            _ => "id",
+        }
+    }
+
+    /// 主键为自增整数，游标需按数值比较
+    pub fn numeric_key(&self) -> bool {
+        matches!(self, PiiTable::LoginRecords)
+    }
+
+    /// 加密列；以 `blind_index_column` 开头的表，该列存放盲索引令牌
+    pub fn columns(&self) -> &'static [&'static str] {
+        match self {
+            PiiTable::PreviewRequests => &[
+                "third_party_request_id",
+                "user_info_json",
+                "agent_info_json",
+                "subject_info_json",
+                "form_data_json",
+                "scene_data_json",
+                "material_data_json",
+            ],
+            PiiTable::PreviewRecords => &[
+                "third_party_request_id",
+                "user_info_json",
+                "ocr_text",
+                "evaluation_result",
+            ],
+            PiiTable::TaskPayloads
+            | PiiTable::DeadLetters
+            | PiiTable::WorkerResults
+            | PiiTable::MaterialDownloads => &["payload"],
+            PiiTable::LoginRecords => &[
+                "user_name",
+                "certificate_number",
+                "phone_number",
+                "email",
+                "raw_data",
+            ],
+            PiiTable::MaterialResults => &["attachments_json", "summary_json"],
+            PiiTable::RuleResults => {
+                &["message", "suggestions_json", "evidence_json", "extra_json"]
+            }
+            PiiTable::LookupValues => &["ciphertext"],
+        }
+    }
+
+    pub fn blind_index_column(&self) -> Option<&'static str> {
+        match self {
+            PiiTable::PreviewRequests | PiiTable::PreviewRecords => Some("third_party_request_id"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PiiRow {
+    pub key: String,
+    pub values: Vec<Option<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PiiRotationSummary {
+    pub scanned: u64,
+    pub updated: u64,
+    /// 扫描后被并发修改、本轮未更新的行
+    pub skipped: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PiiRowUpdate {
+    pub key: String,
+    pub changes: Vec<PiiColumnUpdate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PiiColumnUpdate {
+    pub column: &'static str,
+    pub value: String,
+    pub expected_prefix: String,
+}
+
+impl PiiColumnUpdate {
+    /// `LIKE ... ESCAPE '\'` 使用的模式
+    pub fn like_pattern(&self) -> String {
+        like_prefix_pattern(&self.expected_prefix)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageBlobRecord {
+    pub storage_key: String,
+    pub checksum_sha256: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_referenced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlobGcQuery {
+    pub claim_id: String,
+    /// 最近引用时间早于该时刻的 blob 才可回收
+    pub unreferenced_before: DateTime<Utc>,
+    /// 认领时间早于该时刻的视为回收中断
+    pub stale_claim_before: DateTime<Utc>,
+    pub limit: u32,
+}
@@ -65,6 +65,7 @@ async fn main() -> anyhow::Result<()> {
 
     match args.next().as_deref() {
         Some("worker") | Some("--worker") => server::start_worker().await,
+        Some("migrate") => server::run_migrations(args.next().as_deref().unwrap_or("status")).await,
         Some("health-check") | Some("--health-check") => {
             let report = server::check_system_health().await?;
             println!(
@@ -49,6 +49,9 @@ pub struct MaterialEvaluationResult {
     pub display_summary: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_detail: Option<String>,
+    /// 从材料图片中还原出的表格结构
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<ocr_conn::table::TableGrid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -186,6 +189,7 @@ impl MaterialEvaluationResult {
             },
             display_summary: None,
             display_detail: None,
+            tables: Vec::new(),
         }
     }
 }
@@ -1,11 +1,44 @@
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 use crate::util::WebResult;
+use axum::extract::multipart::Field;
 use axum::extract::Multipart;
 use ocr_conn::ocr::{OcrEngineOptions, GLOBAL_POOL};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
+use tokio::io::AsyncWriteExt;
 use tracing::warn;
 
+/// 上传落盘的临时文件，离开作用域时删除
+struct SpooledUpload(PathBuf);
+
+impl Drop for SpooledUpload {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// 将上传字段分块写入临时文件，超过 `limit` 字节时返回 None
+async fn spool_field(
+    field: &mut Field<'_>,
+    path: &Path,
+    limit: u64,
+) -> anyhow::Result<Option<u64>> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut written = 0u64;
+    while let Some(chunk) = field.chunk().await? {
+        written += chunk.len() as u64;
+        if written > limit {
+            return Ok(None);
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(Some(written))
+}
+
 fn estimate_pdf_pages_quick(data: &[u8]) -> Option<usize> {
     if data.len() < 8 {
         return None;
@@ -35,20 +68,49 @@ pub async fn upload(mut multipart: Multipart) -> anyhow::Result<WebResult> {
         OcrEngineOptions::default()
     };
     GLOBAL_POOL.set_options_if_empty(engine_opts);
-    while let Some(field) = multipart.next_field().await? {
+    let limits = &crate::CONFIG.download_limits;
+    while let Some(mut field) = multipart.next_field().await? {
         let file = PathBuf::from(field.file_name().unwrap_or_default());
-        let bytes = field.bytes().await?;
-        if file
+        let is_pdf = file
+            .extension()
+            .is_some_and(|ext| ext.to_string_lossy().eq("pdf"));
+        let limit_mb = if is_pdf {
+            limits.max_pdf_mb
+        } else {
+            limits.max_file_mb
+        };
+        let ext = file
             .extension()
-            .is_some_and(|ext| ext.to_string_lossy().eq("pdf"))
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "bin".to_string());
+        let spooled = SpooledUpload(ocr_conn::CURRENT_DIR.join("images").join(format!(
+            "upload-{}.{}",
+            uuid::Uuid::new_v4(),
+            ext
+        )));
+        if spool_field(&mut field, &spooled.0, limit_mb * 1024 * 1024)
+            .await?
+            .is_none()
         {
-            let limits = &crate::CONFIG.download_limits;
-            let size_ok = (bytes.len() as u64) <= limits.max_pdf_mb * 1024 * 1024;
+            let msg = if is_pdf {
+                format!(
+                    "文件超限: 大小<= {}MB 且页数<= {}",
+                    limits.max_pdf_mb, limits.pdf_max_pages
+                )
+            } else {
+                format!("文件超限: 大小<= {}MB", limits.max_file_mb)
+            };
+            warn!("上传文件超限已拒绝处理: {}", msg);
+            return Ok(WebResult::err_with_code(422, msg));
+        }
+        if is_pdf {
+            // PDF 渲染需要完整字节，此时大小已受 max_pdf_mb 约束
+            let bytes = tokio::fs::read(&spooled.0).await?;
             let pages_ok = match estimate_pdf_pages_quick(&bytes) {
                 Some(p) => (p as u32) <= limits.pdf_max_pages,
                 None => true,
             };
-            if !size_ok || !pages_ok {
+            if !pages_ok {
                 let msg = format!(
                     "文件超限: 大小<= {}MB 且页数<= {}",
                     limits.max_pdf_mb, limits.pdf_max_pages
@@ -92,7 +154,7 @@ pub async fn upload(mut multipart: Multipart) -> anyhow::Result<WebResult> {
                 }
             };
             let ocr_started = Instant::now();
-            let contents_result = handle.ocr_and_parse(bytes.to_vec().into());
+            let contents_result = handle.ocr_and_parse(spooled.0.as_path().into());
             let duration = ocr_started.elapsed();
             METRICS_COLLECTOR.record_ocr_invocation(contents_result.is_ok(), duration);
             let Ok(contents) = contents_result else {
@@ -5,13 +5,14 @@ use crate::model::evaluation::{
 };
 use crate::model::{Goto, PreviewInfo};
 use crate::storage::Storage;
+use crate::util::http_range::{self, RangeRequest};
 use crate::util::material_cache;
 use crate::util::report::pdf::PdfGenerator;
 use crate::util::rules::MatterRuleDefinition;
 use crate::util::zen::evaluation::PreviewEvaluator;
 use crate::util::WebResult;
 use axum::body::Body;
-use axum::http::{header, StatusCode};
+use axum::http::header;
 use axum::response::Response;
 use chrono::Local;
 use ocr_conn::CURRENT_DIR;
@@ -19,13 +20,12 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
-use std::io::Cursor;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::task;
-use tokio_util::io::ReaderStream;
 use tracing::{debug, info, warn};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -37,6 +37,9 @@ pub struct PreviewBody {
     pub rule_definition: Option<serde_json::Value>,
     #[serde(skip)]
     pub parsed_rule_definition: Option<Arc<MatterRuleDefinition>>,
+    /// 由任务载荷设置，不接受请求方传入
+    #[serde(skip)]
+    pub force_reocr: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -116,6 +119,7 @@ impl ProductionPreviewRequest {
             },
             rule_definition: None,
             parsed_rule_definition: None,
+            force_reocr: false,
         }
     }
 }
@@ -391,13 +395,17 @@ impl PreviewBody {
             database.clone(),
         );
         evaluator.set_embedded_rule_definition(embedded_rule_definition);
+        evaluator.set_force_reocr(self.force_reocr);
         let evaluation_attempt = evaluator.evaluate_complete().await;
 
         let (evaluation_result, html) = match evaluation_attempt {
             Ok(result) => {
+                crate::util::preview_progress::report(&request_id);
                 let html = crate::util::report::PreviewReportGenerator::generate_html(&result);
                 (Some(result), html)
             }
+            // 取消不生成兜底报告，交由调用方保留 cancelled 状态
+            Err(e) if crate::util::preview_cancel::is_cancellation(&e) => return Err(e),
             Err(e) => {
                 tracing::error!("预审评估失败: {}", e);
                 let fallback = self.build_fallback_evaluation(&e.to_string());
@@ -532,6 +540,7 @@ impl PreviewBody {
             },
             display_summary: Some("系统暂时无法生成报告".to_string()),
             display_detail: Some("请稍后重试或联系运维人员".to_string()),
+            tables: Vec::new(),
         };
 
         fallback.material_results.push(material);
@@ -771,11 +780,12 @@ impl PreviewBody {
         (out, resolved_tokens, missing_tokens)
     }
 
-    pub async fn download(goto: Goto) -> anyhow::Result<Response> {
-        Self::download_local(goto).await
+    pub async fn download(goto: Goto, range: Option<&str>) -> anyhow::Result<Response> {
+        Self::download_local(goto, range).await
     }
 
-    pub async fn download_local(goto: Goto) -> anyhow::Result<Response> {
+    /// range 为请求的 `Range` 头，文件按需分段流式返回
+    pub async fn download_local(goto: Goto, range: Option<&str>) -> anyhow::Result<Response> {
         // Basic extension whitelist
         let allowed_exts = ["pdf", "html", "jpg", "jpeg", "png", "txt"];
         let path = Path::new(&goto.goto);
@@ -817,12 +827,20 @@ impl PreviewBody {
             return Err(anyhow::anyhow!("下载路径不被允许"));
         }
 
-        let buf = fs::read(&req_abs).await?;
+        let mut file = fs::File::open(&req_abs).await?;
+        let size = file.metadata().await?.len();
+        let range = RangeRequest::parse(range, size);
+        if range == RangeRequest::Unsatisfiable {
+            return Ok(range.response(size).body(Body::empty())?);
+        }
+        let span = range.as_range().unwrap_or(0..size);
+        file.seek(SeekFrom::Start(span.start)).await?;
+
         let content_type = mime_guess::from_path(&goto.goto)
             .first_or_octet_stream()
             .to_string();
-        let response = Response::builder()
-            .status(StatusCode::OK)
+        let response = range
+            .response(size)
             .header(header::CONTENT_TYPE, content_type)
             .header(
                 header::CONTENT_DISPOSITION,
@@ -836,7 +854,7 @@ impl PreviewBody {
                     )
                 ),
             )
-            .body(Body::from_stream(ReaderStream::new(Cursor::new(buf))))?;
+            .body(http_range::stream_body(file.take(span.end - span.start)))?;
         info!("Download file: {}", req_abs.display());
         Ok(response)
     }
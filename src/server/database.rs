@@ -38,6 +38,21 @@ impl DatabaseInitializer {
         url
     }
     pub async fn create_from_config(config: &Config) -> Result<Arc<dyn db::Database>> {
+        let database = Self::create_backend(config).await?;
+        if !config.pii_encryption.enabled {
+            return Ok(database);
+        }
+
+        // 加密层放在故障转移之外，降级日志中保存的同样是密文
+        let encrypted = db::EncryptedDatabase::from_config(database, &config.pii_encryption)?;
+        info!(
+            "[lock] 敏感字段加密已启用，当前密钥版本: {}",
+            config.pii_encryption.key_version
+        );
+        Ok(Arc::new(encrypted))
+    }
+
+    async fn create_backend(config: &Config) -> Result<Arc<dyn db::Database>> {
         info!("[cabinet] 初始化数据库连接...");
 
         if let Some(database_config) = &config.database {
@@ -79,6 +94,7 @@ impl DatabaseInitializer {
                             health_check_interval: 60,
                         }),
                     }),
+                    postgres: None,
                 }
             }
             #[cfg(not(feature = "dm_go"))]
@@ -90,6 +106,7 @@ impl DatabaseInitializer {
                         path: "runtime/data/ocr_fallback.db".to_string(),
                     }),
                     dm: None,
+                    postgres: None,
                 }
             }
         } else {
@@ -104,6 +121,7 @@ impl DatabaseInitializer {
                     path: "data/ocr.db".to_string(),
                 }),
                 dm: None,
+                postgres: None,
             }
         };
 
@@ -131,6 +149,7 @@ impl DatabaseInitializer {
                             path: "data/ocr.db".to_string(),
                         }),
                         dm: None,
+                        postgres: None,
                     };
 
                     match db::factory::create_database(&sqlite_config).await {
@@ -190,6 +209,7 @@ impl DatabaseInitializer {
                             path: sqlite_config.path.clone(),
                         }),
                         dm: None,
+                        postgres: None,
                     }
                 }
                 #[cfg(feature = "dm_go")]
@@ -222,6 +242,30 @@ impl DatabaseInitializer {
                             connection_timeout: dm_config.connection_timeout,
                             go_gateway: gw,
                         }),
+                        postgres: None,
+                    }
+                }
+                #[cfg(feature = "postgres")]
+                "postgres" => {
+                    info!("[card] PostgreSQL数据库模式");
+                    let pg_config = database_config
+                        .postgres
+                        .as_ref()
+                        .ok_or_else(|| anyhow::anyhow!("PostgreSQL数据库配置缺失"))?;
+                    db::factory::DatabaseConfig {
+                        db_type: db::factory::DatabaseType::Postgres,
+                        sqlite: None,
+                        dm: None,
+                        postgres: Some(db::factory::PostgresConfig {
+                            host: pg_config.host.clone(),
+                            port: pg_config.port,
+                            username: pg_config.username.clone(),
+                            password: pg_config.password.clone(),
+                            database: pg_config.database.clone(),
+                            max_connections: pg_config.max_connections,
+                            connection_timeout: pg_config.connection_timeout,
+                            schema: pg_config.schema.clone(),
+                        }),
                     }
                 }
                 "smart" => {
@@ -257,6 +301,7 @@ impl DatabaseInitializer {
                                     connection_timeout: dm_config.connection_timeout,
                                     go_gateway: gw,
                                 }),
+                                postgres: None,
                             }
                         } else {
                             warn!("[warn] 智能模式启用但缺少达梦数据库配置，使用SQLite");
@@ -270,6 +315,7 @@ impl DatabaseInitializer {
                                     path: sqlite_config.path.clone(),
                                 }),
                                 dm: None,
+                                postgres: None,
                             }
                         }
                     }
@@ -286,6 +332,7 @@ impl DatabaseInitializer {
                                 path: sqlite_config.path.clone(),
                             }),
                             dm: None,
+                            postgres: None,
                         }
                     }
                 }
@@ -304,6 +351,7 @@ impl DatabaseInitializer {
                             path: sqlite_config.path.clone(),
                         }),
                         dm: None,
+                        postgres: None,
                     }
                 }
             };
@@ -323,6 +371,7 @@ impl DatabaseInitializer {
                             db_type: db::factory::DatabaseType::Sqlite,
                             sqlite: Some(sqlite_config.clone()),
                             dm: None,
+                            postgres: None,
                         };
 
                         match db::factory::create_database(&fallback_config).await {
@@ -345,6 +394,7 @@ impl DatabaseInitializer {
                                 path: "runtime/data/ocr_fallback.db".to_string(),
                             }),
                             dm: None,
+                            postgres: None,
                         };
 
                         match db::factory::create_database(&default_sqlite_config).await {
@@ -381,6 +431,15 @@ impl DatabaseInitializer {
     pub async fn initialize_schema(database: &Arc<dyn db::Database>) -> Result<()> {
         info!("[clipboard] 检查数据库表结构...");
 
+        // 故障转移模式下主库不可用时推迟迁移，使用降级库启动
+        match database.as_any().downcast_ref::<db::FailoverDatabase>() {
+            Some(failover) => failover.migrate_on_startup().await?,
+            None => {
+                db::migrations::MigrationRunner::new(database.as_ref())
+                    .run_on_startup()
+                    .await?
+            }
+        }
 
         info!("[ok] 数据库表结构检查完成");
         Ok(())
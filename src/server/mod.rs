@@ -21,23 +21,31 @@ pub use storage::{StorageHealth, StorageInitializer, StorageStats};
 
 use crate::api::{LocalPreviewTaskHandler, RemotePreviewTaskHandler};
 use crate::build_info;
+use crate::db::migrations::MigrationRunner;
+use crate::db::Database;
 use crate::util::adaptive_limiter;
-use crate::util::config::types::DeploymentRole;
+use crate::util::config::types::{DeploymentRole, TaskQueueDriver};
 use crate::util::config::Config;
 use crate::util::dynamic_worker::DynamicWorkerConfig;
 use crate::util::material_cache;
 use crate::util::material_cache_manager;
 use crate::util::service_watchdog;
-use crate::util::system_info::{self, init_start_time};
+use crate::util::system_info::init_start_time;
 use crate::util::task_queue::{
     initialize_task_queue, start_queue_worker, PreviewTaskHandler, TaskQueue,
 };
 use crate::util::task_recovery;
+use crate::util::task_routing::WorkerCapabilities;
 use crate::util::worker;
+use crate::util::worker_fleet;
 use crate::AppState;
 use anyhow::{anyhow, Context, Result};
 use num_cpus;
-use ocr_conn::ocr::{configure_pool_capacity, OcrEngineOptions, GLOBAL_POOL};
+use ocr_conn::ocr::{
+    configure_pool_capacity, configure_pool_tuning, recommend_pool_size, spawn_pool_reaper,
+    OcrEngineOptions, PoolTuning, GLOBAL_POOL,
+};
+use ocr_conn::preprocess::{self, PreprocessOptions, PreprocessStep};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tokio::time::{timeout, Duration};
@@ -84,6 +92,7 @@ impl ServerBootstrap {
         init_start_time();
 
         apply_ocr_pool_config_for_role("master", &self.config);
+        apply_ocr_preprocess_config(&self.config);
 
         crate::util::processing::optimized_pipeline::OPTIMIZED_PIPELINE.configure(&self.config);
 
@@ -93,6 +102,11 @@ impl ServerBootstrap {
         service_watchdog::spawn_master_watchdog(&app_state);
         adaptive_limiter::spawn_for_master(&app_state);
         material_cache_manager::spawn_material_cache_manager(&app_state);
+        crate::util::ocr_cache::spawn_maintenance(&app_state);
+        crate::util::retention::spawn_purger(&app_state);
+        crate::util::blob_store::spawn_collector(&app_state);
+        crate::util::pii_rotation::spawn_startup_rotation(&app_state);
+        crate::util::search::spawn_indexer(&app_state);
 
         let processor =
             crate::util::worker::result_processor::ResultProcessor::new(app_state.clone());
@@ -178,6 +192,7 @@ impl ServerBootstrap {
         let task_queue = initialize_task_queue(
             self.config.distributed.enabled,
             &self.config.task_queue,
+            database.clone(),
             task_handler.clone(),
         )
         .await
@@ -230,6 +245,7 @@ impl ServerBootstrap {
 
         crate::util::callbacks::initialize(&app_state);
         crate::util::outbox::initialize(&app_state);
+        crate::util::pdf_shard::initialize(&app_state);
 
         if matches!(
             self.config.deployment.role,
@@ -239,6 +255,12 @@ impl ServerBootstrap {
                 &app_state,
                 &self.config.master.processing_watchdog,
             );
+
+            match worker_fleet::load(&app_state.database).await {
+                Ok(count) if count > 0 => info!("[ok] 已载入 {} 个 worker 运维状态", count),
+                Ok(_) => {}
+                Err(e) => warn!("[warn] 载入 worker 运维状态失败: {:#}", e),
+            }
         }
 
         info!("[ok] 应用状态创建完成");
@@ -252,6 +274,7 @@ impl ServerBootstrap {
         handler: Arc<dyn PreviewTaskHandler>,
     ) -> Result<()> {
         use crate::util::dynamic_worker::{init_dynamic_worker_manager, DynamicWorkerManager};
+        use crate::util::lane_scheduler::LanePolicy;
         use crate::util::task_queue::{NatsTaskQueue, NatsTaskQueueConsumer};
 
         let queue_arc: Arc<NatsTaskQueue> = {
@@ -263,11 +286,13 @@ impl ServerBootstrap {
         };
         let consumer_factory = {
             let queue_clone = Arc::clone(&queue_arc);
+            let policy = LanePolicy::from_global_config();
             move || -> Result<NatsTaskQueueConsumer> {
                 Ok(NatsTaskQueueConsumer::new(
                     queue_clone.queue_name(),
                     queue_clone.jetstream_context(),
                     queue_clone.get_config().clone(),
+                    policy.clone(),
                 ))
             }
         };
@@ -485,6 +510,62 @@ pub async fn check_system_health() -> Result<SystemHealthReport> {
     bootstrap.health_check().await
 }
 
+/// `ocr-server migrate <status|up|verify>`：只连接数据库，不启动服务
+pub async fn run_migrations(action: &str) -> Result<()> {
+    let bootstrap = ServerBootstrap::new().await?;
+    let database = DatabaseInitializer::create_from_config(&bootstrap.config).await?;
+    let runner = MigrationRunner::new(database.as_ref());
+    let dialect = database
+        .migration_dialect()
+        .ok_or_else(|| anyhow!("当前数据库后端不支持版本化迁移"))?;
+    println!("数据库: {}", dialect.as_str());
+
+    match action {
+        "status" => {
+            for status in runner.status().await? {
+                println!(
+                    "{:>4}  {:<32} {:<18} {}",
+                    status.version,
+                    status.name,
+                    status.state.as_str(),
+                    status.applied_at.as_deref().unwrap_or("-")
+                );
+            }
+            Ok(())
+        }
+        "up" => {
+            let applied = runner.migrate_up().await?;
+            if applied.is_empty() {
+                println!("表结构已是最新");
+            } else {
+                println!("已执行迁移: {:?}", applied);
+            }
+            Ok(())
+        }
+        "verify" => {
+            let problems = runner.verify().await?;
+            for problem in &problems {
+                println!(
+                    "{:>4}  {:<32} {}",
+                    problem.version,
+                    problem.name,
+                    problem.state.as_str()
+                );
+            }
+            if problems.is_empty() {
+                println!("校验通过");
+                Ok(())
+            } else {
+                Err(anyhow!("{} 个迁移与代码不一致", problems.len()))
+            }
+        }
+        other => Err(anyhow!(
+            "未知的 migrate 子命令: {}，可用: status | up | verify",
+            other
+        )),
+    }
+}
+
 pub async fn start_worker() -> Result<()> {
     info!("=== OCR Worker 启动 ===");
     info!("版本信息: {}", build_info::summary());
@@ -514,6 +595,7 @@ pub async fn start_worker() -> Result<()> {
     adaptive_limiter::spawn_for_worker(&config);
 
     apply_ocr_pool_config_for_role("worker", &config);
+    apply_ocr_preprocess_config(&config);
 
     crate::util::processing::optimized_pipeline::OPTIMIZED_PIPELINE.configure(&config);
 
@@ -538,26 +620,67 @@ pub async fn start_worker() -> Result<()> {
 
     worker::log_worker_startup("worker");
     worker::spawn_heartbeat_task(worker_settings.heartbeat_interval_secs.unwrap_or(30));
+    worker::spawn_progress_forwarder();
 
     let handler = Arc::new(RemotePreviewTaskHandler::new(proxy_client));
 
-    start_queue_worker(&config.task_queue, handler).await
+    // 数据库队列由 worker 直接读写队列表，其余驱动无需数据库连接
+    let database = match config.task_queue.driver {
+        TaskQueueDriver::Database => Some(
+            DatabaseInitializer::create_from_config(&config)
+                .await
+                .context("初始化数据库队列连接失败")?,
+        ),
+        _ => None,
+    };
+
+    let pool = worker_settings
+        .capabilities
+        .as_ref()
+        .and_then(|capabilities| WorkerCapabilities::from_config(capabilities).pool);
+
+    // 排空完成后主节点通知退出；未确认的消息与租约到期的任务会重新投递
+    tokio::select! {
+        result = start_queue_worker(&config.task_queue, pool, database, handler) => result,
+        _ = worker::shutdown_requested() => {
+            info!("[ok] worker 已排空，退出任务消费");
+            Ok(())
+        }
+    }
 }
 
 fn apply_ocr_pool_config_for_role(role: &str, config: &Config) {
-    let requested = config.ocr_pool.max_engines.max(1);
-    let normalized = requested.clamp(1, 128);
+    let cfg = &config.ocr_pool;
+    let non_zero = |value: u64| (value > 0).then_some(value);
+    configure_pool_tuning(PoolTuning {
+        engine_memory_mb: cfg.engine_memory_mb.max(64),
+        max_engine_rss_mb: non_zero(cfg.max_engine_rss_mb),
+        max_requests_per_engine: non_zero(cfg.max_requests_per_engine),
+        idle_timeout_secs: non_zero(cfg.idle_timeout_secs),
+        min_idle_engines: cfg.min_idle_engines,
+        reap_interval_secs: cfg.reap_interval_secs.max(1),
+    });
+
+    let recommendation = recommend_pool_size(cfg.engine_memory_mb.max(64));
+    let normalized = if cfg.max_engines == 0 {
+        recommendation.recommended
+    } else {
+        cfg.max_engines.clamp(1, 128)
+    };
     configure_pool_capacity(normalized);
-
-    let recommendation = compute_ocr_pool_recommendation();
+    spawn_pool_reaper();
 
     info!(
         role = role,
         configured = normalized,
+        auto = cfg.max_engines == 0,
         recommended = recommendation.recommended,
         cpu_based = recommendation.cpu_based,
         memory_based = recommendation.memory_based,
-        available_memory_mb = recommendation.available_memory_mb,
+        available_memory_mb = ?recommendation.available_memory_mb,
+        max_engine_rss_mb = cfg.max_engine_rss_mb,
+        max_requests_per_engine = cfg.max_requests_per_engine,
+        idle_timeout_secs = cfg.idle_timeout_secs,
         "[config] OCR 引擎池容量已设置"
     );
 
@@ -578,27 +701,32 @@ fn apply_ocr_pool_config_for_role(role: &str, config: &Config) {
     }
 }
 
-struct OcrPoolRecommendation {
-    recommended: usize,
-    cpu_based: usize,
-    memory_based: usize,
-    available_memory_mb: u64,
-}
-
-fn compute_ocr_pool_recommendation() -> OcrPoolRecommendation {
-    let physical_cores = num_cpus::get_physical().max(1);
-    let cpu_based = std::cmp::max(1, physical_cores / 3);
-
-    let memory = system_info::get_memory_usage();
-    let available_mb = memory.total_mb.saturating_sub(memory.used_mb);
-    let memory_based = std::cmp::max(1, (available_mb / 512) as usize);
-
-    let recommended = cpu_based.min(memory_based).clamp(1, 32);
-
-    OcrPoolRecommendation {
-        recommended,
-        cpu_based,
-        memory_based,
-        available_memory_mb: available_mb,
+fn apply_ocr_preprocess_config(config: &Config) {
+    let cfg = &config.ocr_preprocess;
+    let mut steps = Vec::with_capacity(cfg.steps.len());
+    for raw in &cfg.steps {
+        match PreprocessStep::parse(raw) {
+            Some(step) if !steps.contains(&step) => steps.push(step),
+            Some(_) => warn!(step = %raw, "OCR 预处理步骤重复配置，已忽略"),
+            None => warn!(step = %raw, "未知的 OCR 预处理步骤，已忽略"),
+        }
     }
+
+    let options = PreprocessOptions {
+        enabled: cfg.enabled,
+        steps,
+        contrast_boost: cfg.contrast_boost,
+        max_deskew_degrees: cfg.max_deskew_degrees,
+        min_deskew_degrees: cfg.min_deskew_degrees,
+        min_document_area_ratio: cfg.min_document_area_ratio.clamp(0.0, 1.0),
+        analysis_max_dimension: cfg.analysis_max_dimension.clamp(200, 4000),
+        ocr_orientation: cfg.ocr_orientation,
+    };
+    info!(
+        enabled = options.enabled,
+        steps = ?options.steps.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        ocr_orientation = options.ocr_orientation,
+        "[config] OCR 预处理流水线已设置"
+    );
+    preprocess::configure(options);
 }
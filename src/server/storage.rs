@@ -1,5 +1,8 @@
 
 use crate::storage;
+use crate::storage::factory::{
+    LocalConfig, OssConfig, S3Config, StorageConfig, StorageType, TieredConfig,
+};
 use crate::util::config::Config;
 use anyhow::Result;
 use std::sync::Arc;
@@ -11,49 +14,7 @@ impl StorageInitializer {
     pub async fn create_from_config(config: &Config) -> Result<Arc<dyn storage::Storage>> {
         info!("[storage] 初始化存储系统...");
 
-        let storage_config = if config.oss.access_key.is_empty() {
-            info!("使用本地存储系统");
-            storage::factory::StorageConfig {
-                storage_type: storage::factory::StorageType::Local,
-                local: Some(storage::factory::LocalConfig {
-                    base_path: "data/storage".to_string(),
-                    base_url: format!("{}/files", config.base_url().trim_end_matches('/')),
-                }),
-                oss: None,
-            }
-        } else {
-            info!("使用 OSS 存储系统: {}", config.oss.server_url);
-
-            let endpoint = if config.oss.server_url.contains("hzggcloud.xc.com") {
-                info!("[building] 专有云OSS：使用完整HTTP URL作为endpoint");
-                config.oss.server_url.clone()
-            } else {
-                config
-                    .oss
-                    .server_url
-                    .trim_start_matches("http://")
-                    .trim_start_matches("https://")
-                    .to_string()
-            };
-            info!("[tool] OpenDAL端点配置: {}", endpoint);
-
-            storage::factory::StorageConfig {
-                storage_type: storage::factory::StorageType::Oss,
-                local: None,
-                oss: Some(storage::factory::OssConfig {
-                    bucket: config.oss.bucket.clone(),
-                    endpoint: endpoint,
-                    access_key_id: config.oss.access_key.clone(),
-                    access_key_secret: config.oss.access_key_secret.clone(),
-                    root: Some(config.oss.root.clone()),
-                    public_endpoint: Some(format!(
-                        "{}.{}",
-                        config.oss.bucket,
-                        config.oss.server_url.trim_end_matches('/')
-                    )),
-                }),
-            }
-        };
+        let storage_config = Self::build_storage_config(config)?;
 
         let storage = storage::factory::create_storage(&storage_config).await?;
 
@@ -62,7 +23,7 @@ impl StorageInitializer {
             let failover_storage = storage::FailoverStorage::new(
                 Arc::from(storage),
                 config.failover.storage.clone(),
-                config.host.clone(),
+                Self::files_base_url(config),
             )
             .await?;
             Ok(Arc::new(failover_storage) as Arc<dyn storage::Storage>)
@@ -72,6 +33,127 @@ impl StorageInitializer {
         }
     }
 
+    fn build_storage_config(config: &Config) -> Result<StorageConfig> {
+        let backend = &config.storage_backend;
+        let mut storage_config = StorageConfig {
+            storage_type: StorageType::Local,
+            local: None,
+            oss: None,
+            s3: None,
+            tiered: None,
+        };
+
+        match backend.backend.as_str() {
+            "local" => {
+                info!("使用本地存储系统");
+                storage_config.local = Some(Self::local_config(config, &backend.local_path));
+            }
+            "auto" if config.oss.access_key.is_empty() => {
+                info!("使用本地存储系统");
+                storage_config.local = Some(Self::local_config(config, &backend.local_path));
+            }
+            "auto" | "oss" => {
+                info!("使用 OSS 存储系统: {}", config.oss.server_url);
+                storage_config.storage_type = StorageType::Oss;
+                storage_config.oss = Some(Self::oss_config(config));
+            }
+            "s3" => {
+                info!("使用 S3 兼容存储系统: {}", backend.s3.endpoint);
+                storage_config.storage_type = StorageType::S3;
+                storage_config.s3 = Some(Self::s3_config(config));
+            }
+            "tiered" => {
+                let tiered = &backend.tiered;
+                let cold = match tiered.cold.as_str() {
+                    "oss" => {
+                        storage_config.oss = Some(Self::oss_config(config));
+                        StorageType::Oss
+                    }
+                    "s3" => {
+                        storage_config.s3 = Some(Self::s3_config(config));
+                        StorageType::S3
+                    }
+                    other => {
+                        return Err(anyhow::anyhow!("分层存储冷层仅支持 oss 或 s3: {}", other))
+                    }
+                };
+                info!(
+                    "使用分层存储系统: 热层 {}, 冷层 {}",
+                    tiered.hot_path, tiered.cold
+                );
+                storage_config.storage_type = StorageType::Tiered;
+                storage_config.local = Some(Self::local_config(config, &tiered.hot_path));
+                storage_config.tiered = Some(TieredConfig {
+                    cold,
+                    migrate_after_secs: tiered.migrate_after_hours * 3600,
+                    scan_interval_secs: tiered.scan_interval_secs,
+                    batch_size: tiered.batch_size,
+                });
+            }
+            other => return Err(anyhow::anyhow!("不支持的存储后端: {}", other)),
+        }
+
+        Ok(storage_config)
+    }
+
+    fn local_config(config: &Config, base_path: &str) -> LocalConfig {
+        LocalConfig {
+            base_path: base_path.to_string(),
+            base_url: Self::files_base_url(config),
+            signing_secret: Some(storage::signing::shared_secret().to_string()),
+        }
+    }
+
+    /// 本地存储签发链接的前缀，主存储与故障转移兜底共用
+    fn files_base_url(config: &Config) -> String {
+        format!(
+            "{}/api/storage/files",
+            config.base_url().trim_end_matches('/')
+        )
+    }
+
+    fn oss_config(config: &Config) -> OssConfig {
+        let endpoint = if config.oss.server_url.contains("hzggcloud.xc.com") {
+            info!("[building] 专有云OSS：使用完整HTTP URL作为endpoint");
+            config.oss.server_url.clone()
+        } else {
+            config
+                .oss
+                .server_url
+                .trim_start_matches("http://")
+                .trim_start_matches("https://")
+                .to_string()
+        };
+        info!("[tool] OpenDAL端点配置: {}", endpoint);
+
+        OssConfig {
+            bucket: config.oss.bucket.clone(),
+            endpoint,
+            access_key_id: config.oss.access_key.clone(),
+            access_key_secret: config.oss.access_key_secret.clone(),
+            root: Some(config.oss.root.clone()),
+            public_endpoint: Some(format!(
+                "{}.{}",
+                config.oss.bucket,
+                config.oss.server_url.trim_end_matches('/')
+            )),
+        }
+    }
+
+    fn s3_config(config: &Config) -> S3Config {
+        let s3 = &config.storage_backend.s3;
+        S3Config {
+            bucket: s3.bucket.clone(),
+            endpoint: s3.endpoint.clone(),
+            region: Some(s3.region.clone()),
+            access_key_id: s3.access_key_id.clone(),
+            secret_access_key: s3.secret_access_key.clone(),
+            root: Some(s3.root.clone()),
+            public_endpoint: s3.public_endpoint.clone(),
+            virtual_host_style: s3.virtual_host_style,
+        }
+    }
+
     pub async fn validate_connection(storage: &Arc<dyn storage::Storage>) -> Result<()> {
         info!("[search] 验证存储系统连接...");
 
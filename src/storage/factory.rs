@@ -1,9 +1,15 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::local::LocalStorage;
 use super::oss::OssConfig as InternalOssConfig;
 use super::oss::OssStorage;
+use super::s3::S3Config as InternalS3Config;
+use super::s3::S3Storage;
+use super::signing::UrlSigner;
+use super::tiered::{TierPolicy, TieredStorage};
 use super::traits::Storage;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -11,6 +17,8 @@ use super::traits::Storage;
 pub enum StorageType {
     Local,
     Oss,
+    S3,
+    Tiered,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -21,12 +29,22 @@ pub struct StorageConfig {
     pub local: Option<LocalConfig>,
 
     pub oss: Option<OssConfig>,
+
+    #[serde(default)]
+    pub s3: Option<S3Config>,
+
+    /// 热层使用 local 配置，冷层使用 oss 或 s3 配置
+    #[serde(default)]
+    pub tiered: Option<TieredConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LocalConfig {
     pub base_path: String,
     pub base_url: String,
+    /// 预签名链接使用的 HMAC 密钥，未配置时无法生成预签名链接
+    #[serde(default)]
+    pub signing_secret: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -39,43 +57,137 @@ pub struct OssConfig {
     pub public_endpoint: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct S3Config {
+    pub bucket: String,
+    pub endpoint: String,
+    #[serde(default)]
+    pub region: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub root: Option<String>,
+    pub public_endpoint: Option<String>,
+    #[serde(default)]
+    pub virtual_host_style: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TieredConfig {
+    pub cold: StorageType,
+    pub migrate_after_secs: u64,
+    /// 0 表示不启动后台迁移
+    pub scan_interval_secs: u64,
+    pub batch_size: usize,
+}
+
 pub async fn create_storage(config: &StorageConfig) -> Result<Box<dyn Storage>> {
     match config.storage_type {
-        StorageType::Local => {
-            let local_config = config
-                .local
-                .as_ref()
-                .ok_or_else(|| anyhow::anyhow!("Local storage configuration missing"))?;
+        StorageType::Local => Ok(Box::new(create_local(config)?)),
 
-            let storage = LocalStorage::new(&local_config.base_path, &local_config.base_url)?;
+        StorageType::Oss => Ok(Box::new(create_oss(config)?)),
 
-            tracing::info!("Local storage initialized at: {}", local_config.base_path);
-            Ok(Box::new(storage))
-        }
+        StorageType::S3 => Ok(Box::new(create_s3(config)?)),
 
-        StorageType::Oss => {
-            let oss_config = config
-                .oss
+        StorageType::Tiered => {
+            let tiered_config = config
+                .tiered
                 .as_ref()
-                .ok_or_else(|| anyhow::anyhow!("OSS configuration missing"))?;
-
-            let internal_config = InternalOssConfig {
-                bucket: oss_config.bucket.clone(),
-                endpoint: oss_config.endpoint.clone(),
-                access_key_id: oss_config.access_key_id.clone(),
-                access_key_secret: oss_config.access_key_secret.clone(),
-                root: oss_config.root.clone(),
-                public_endpoint: oss_config.public_endpoint.clone(),
+                .ok_or_else(|| anyhow::anyhow!("Tiered storage configuration missing"))?;
+
+            let hot = create_local(config)?;
+            let cold: Arc<dyn Storage> = match tiered_config.cold {
+                StorageType::Oss => Arc::new(create_oss(config)?),
+                StorageType::S3 => Arc::new(create_s3(config)?),
+                ref other => {
+                    return Err(anyhow::anyhow!(
+                        "Unsupported cold tier for tiered storage: {:?}",
+                        other
+                    ))
+                }
             };
 
-            let storage = OssStorage::new(internal_config)?;
-
-            tracing::info!("OSS storage initialized for bucket: {}", oss_config.bucket);
+            let storage = TieredStorage::new(
+                hot,
+                cold,
+                TierPolicy {
+                    migrate_after: Duration::from_secs(tiered_config.migrate_after_secs),
+                    scan_interval: Duration::from_secs(tiered_config.scan_interval_secs),
+                    batch_size: tiered_config.batch_size,
+                },
+            );
+            storage.spawn_migrator();
+
+            tracing::info!(
+                "Tiered storage initialized: cold tier {:?}, migrate after {}s",
+                tiered_config.cold,
+                tiered_config.migrate_after_secs
+            );
             Ok(Box::new(storage))
         }
     }
 }
 
+fn create_local(config: &StorageConfig) -> Result<LocalStorage> {
+    let local_config = config
+        .local
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Local storage configuration missing"))?;
+
+    let mut storage = LocalStorage::new(&local_config.base_path, &local_config.base_url)?;
+    if let Some(secret) = &local_config.signing_secret {
+        storage = storage.with_signer(UrlSigner::new(secret)?);
+    }
+
+    tracing::info!("Local storage initialized at: {}", local_config.base_path);
+    Ok(storage)
+}
+
+fn create_oss(config: &StorageConfig) -> Result<OssStorage> {
+    let oss_config = config
+        .oss
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("OSS configuration missing"))?;
+
+    let internal_config = InternalOssConfig {
+        bucket: oss_config.bucket.clone(),
+        endpoint: oss_config.endpoint.clone(),
+        access_key_id: oss_config.access_key_id.clone(),
+        access_key_secret: oss_config.access_key_secret.clone(),
+        root: oss_config.root.clone(),
+        public_endpoint: oss_config.public_endpoint.clone(),
+    };
+
+    let storage = OssStorage::new(internal_config)?;
+
+    tracing::info!("OSS storage initialized for bucket: {}", oss_config.bucket);
+    Ok(storage)
+}
+
+fn create_s3(config: &StorageConfig) -> Result<S3Storage> {
+    let s3_config = config
+        .s3
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("S3 configuration missing"))?;
+
+    let storage = S3Storage::new(InternalS3Config {
+        bucket: s3_config.bucket.clone(),
+        endpoint: s3_config.endpoint.clone(),
+        region: s3_config.region.clone(),
+        access_key_id: s3_config.access_key_id.clone(),
+        secret_access_key: s3_config.secret_access_key.clone(),
+        root: s3_config.root.clone(),
+        public_endpoint: s3_config.public_endpoint.clone(),
+        virtual_host_style: s3_config.virtual_host_style,
+    })?;
+
+    tracing::info!(
+        "S3 storage initialized for bucket: {} ({})",
+        s3_config.bucket,
+        s3_config.endpoint
+    );
+    Ok(storage)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,11 +199,48 @@ mod tests {
             local: Some(LocalConfig {
                 base_path: "/tmp/test-storage".to_string(),
                 base_url: "http://localhost/files".to_string(),
+                signing_secret: None,
             }),
             oss: None,
+            s3: None,
+            tiered: None,
         };
 
         let storage = create_storage(&config).await.unwrap();
         assert!(storage.health_check().await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_tiered_storage_requires_object_cold_tier() {
+        let config = StorageConfig {
+            storage_type: StorageType::Tiered,
+            local: Some(LocalConfig {
+                base_path: "/tmp/test-storage-hot".to_string(),
+                base_url: "http://localhost/files".to_string(),
+                signing_secret: None,
+            }),
+            oss: None,
+            s3: Some(S3Config {
+                bucket: "previews".to_string(),
+                endpoint: "http://127.0.0.1:9000".to_string(),
+                region: None,
+                access_key_id: "minio".to_string(),
+                secret_access_key: "minio-secret".to_string(),
+                root: None,
+                public_endpoint: None,
+                virtual_host_style: false,
+            }),
+            tiered: Some(TieredConfig {
+                cold: StorageType::Local,
+                migrate_after_secs: 3600,
+                scan_interval_secs: 0,
+                batch_size: 10,
+            }),
+        };
+        assert!(create_storage(&config).await.is_err());
+
+        let mut config = config;
+        config.tiered.as_mut().unwrap().cold = StorageType::S3;
+        assert!(create_storage(&config).await.is_ok());
+    }
 }
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
@@ -9,7 +10,7 @@ use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
 use super::factory;
-use super::traits::{FileMetadata, Storage};
+use super::traits::{FileMetadata, Storage, StorageReader};
 use crate::util::config::StorageFailoverConfig;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -47,8 +48,11 @@ impl FailoverStorage {
             local: Some(factory::LocalConfig {
                 base_path: config.local_fallback_dir.clone(),
                 base_url,
+                signing_secret: Some(super::signing::shared_secret().to_string()),
             }),
             oss: None,
+            s3: None,
+            tiered: None,
         };
 
         let fallback = Arc::from(factory::create_storage(&fallback_config).await?);
@@ -381,6 +385,60 @@ impl Storage for FailoverStorage {
         let storage = self.get_active_storage().await;
         storage.health_check().await
     }
+
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        self.execute_with_failover(|storage| {
+            let key = key.to_string();
+            let range = range.clone();
+            Box::pin(async move { storage.get_reader(&key, range).await })
+        })
+        .await
+    }
+
+    /// 上传流只能消费一次，先落盘到回退目录，每次重试重新打开
+    async fn put_reader(&self, key: &str, mut reader: StorageReader) -> Result<u64> {
+        let spool_path = PathBuf::from(&self.config.local_fallback_dir)
+            .join(".spool")
+            .join(uuid::Uuid::new_v4().to_string());
+        if let Some(parent) = spool_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("创建上传暂存目录失败")?;
+        }
+        let mut spool = tokio::fs::File::create(&spool_path)
+            .await
+            .context("创建上传暂存文件失败")?;
+        let spooled = tokio::io::copy(&mut reader, &mut spool).await;
+        drop(spool);
+
+        let result = match spooled {
+            Ok(_) => {
+                let key_clone = key.to_string();
+                let path_clone = spool_path.clone();
+                self.execute_with_failover(|storage| {
+                    let key = key_clone.clone();
+                    let path = path_clone.clone();
+                    Box::pin(async move {
+                        let file = tokio::fs::File::open(&path).await?;
+                        storage.put_reader(&key, Box::new(file)).await
+                    })
+                })
+                .await
+            }
+            Err(e) => Err(e).context("写入上传暂存文件失败"),
+        };
+        let _ = tokio::fs::remove_file(&spool_path).await;
+
+        if result.is_ok() {
+            self.record_pending_sync(key).await;
+        }
+
+        result
+    }
 }
 
 async fn load_pending_sync(path: &Path) -> Result<Vec<PendingSyncFile>> {
@@ -1,15 +1,21 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::Utc;
+use std::io::SeekFrom;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use urlencoding::encode;
 
-use super::traits::{FileMetadata, Storage};
+use super::signing::UrlSigner;
+use super::traits::{FileMetadata, Storage, StorageReader};
 
 pub struct LocalStorage {
     base_path: PathBuf,
     base_url: String,
+    signer: Option<UrlSigner>,
 }
 
 impl LocalStorage {
@@ -21,13 +27,50 @@ impl LocalStorage {
         Ok(Self {
             base_path,
             base_url: base_url.trim_end_matches('/').to_string(),
+            signer: None,
         })
     }
 
+    /// 预签名链接需经文件代理校验，base_url 应指向 `/api/storage/files`
+    pub fn with_signer(mut self, signer: UrlSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
     fn get_full_path(&self, key: &str) -> PathBuf {
         self.base_path.join(key.trim_start_matches('/'))
     }
 
+    /// 递归列出全部文件及其修改时间，供分层存储挑选迁移对象
+    pub async fn list_with_mtime(&self) -> Result<Vec<(String, chrono::DateTime<Utc>)>> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.base_path.clone()];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).context("Failed to read directory"),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    pending.push(entry.path());
+                } else if file_type.is_file() {
+                    let modified = entry.metadata().await?.modified()?;
+                    if let Ok(relative) = entry.path().strip_prefix(&self.base_path) {
+                        files.push((
+                            relative.to_string_lossy().replace('\\', "/"),
+                            chrono::DateTime::<Utc>::from(modified),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
     async fn ensure_parent_dir(&self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
@@ -127,8 +170,18 @@ impl Storage for LocalStorage {
         Ok(format!("{}/{}", self.base_url, key.trim_start_matches('/')))
     }
 
-    async fn get_presigned_url(&self, key: &str, _expires: Duration) -> Result<String> {
-        self.get_public_url(key).await
+    async fn get_presigned_url(&self, key: &str, expires: Duration) -> Result<String> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("本地存储未配置链接签名密钥"))?;
+        let key = key.trim_start_matches('/');
+        Ok(format!(
+            "{}/{}?{}",
+            self.base_url,
+            encode(key),
+            signer.query(key, expires)
+        ))
     }
 
     async fn get_metadata(&self, key: &str) -> Result<FileMetadata> {
@@ -157,6 +210,47 @@ impl Storage for LocalStorage {
             .try_exists()
             .context("Failed to check base directory")
     }
+
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        let path = self.get_full_path(key);
+
+        let mut file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => Err(e).context("Failed to open file")?,
+        };
+        match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start))
+                    .await
+                    .context("Failed to seek file")?;
+                Ok(Some(Box::new(
+                    file.take(range.end.saturating_sub(range.start)),
+                )))
+            }
+            None => Ok(Some(Box::new(file))),
+        }
+    }
+
+    async fn put_reader(&self, key: &str, mut reader: StorageReader) -> Result<u64> {
+        let path = self.get_full_path(key);
+
+        self.ensure_parent_dir(&path).await?;
+
+        let mut file = fs::File::create(&path)
+            .await
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        let written = tokio::io::copy(&mut reader, &mut file)
+            .await
+            .with_context(|| format!("Failed to write file: {}", path.display()))?;
+        file.flush().await?;
+
+        Ok(written)
+    }
 }
 
 #[cfg(test)]
@@ -184,4 +278,68 @@ mod tests {
         storage.delete(key).await.unwrap();
         assert!(!storage.exists(key).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn presigned_url_is_signed_and_expiring() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage =
+            LocalStorage::new(temp_dir.path(), "http://localhost/api/storage/files").unwrap();
+        assert!(storage
+            .get_presigned_url("a.txt", Duration::from_secs(60))
+            .await
+            .is_err());
+
+        let signer = UrlSigner::new("secret").unwrap();
+        let storage = storage.with_signer(signer.clone());
+        let url = storage
+            .get_presigned_url("/reports/a b.html", Duration::from_secs(60))
+            .await
+            .unwrap();
+        let parsed = url::Url::parse(&url).unwrap();
+        assert_eq!(parsed.path(), "/api/storage/files/reports%2Fa%20b.html");
+
+        let params: std::collections::HashMap<_, _> = parsed.query_pairs().collect();
+        let expires: i64 = params["expires"].parse().unwrap();
+        let now = Utc::now().timestamp();
+        assert!(expires > now && expires <= now + 60);
+        assert!(signer
+            .verify("reports/a b.html", expires, &params["signature"], now)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn streams_ranges_and_writes_from_reader() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = LocalStorage::new(temp_dir.path(), "http://localhost/files").unwrap();
+
+        let body: Vec<u8> = (0..=255u8).cycle().take(100_000).collect();
+        let written = storage
+            .put_reader(
+                "big/report.pdf",
+                Box::new(std::io::Cursor::new(body.clone())),
+            )
+            .await
+            .unwrap();
+        assert_eq!(written, body.len() as u64);
+
+        let mut reader = storage
+            .get_reader("big/report.pdf", Some(1000..1010))
+            .await
+            .unwrap()
+            .unwrap();
+        let mut chunk = Vec::new();
+        reader.read_to_end(&mut chunk).await.unwrap();
+        assert_eq!(chunk, &body[1000..1010]);
+
+        let mut full = Vec::new();
+        let mut reader = storage
+            .get_reader("big/report.pdf", None)
+            .await
+            .unwrap()
+            .unwrap();
+        reader.read_to_end(&mut full).await.unwrap();
+        assert_eq!(full, body);
+
+        assert!(storage.get_reader("missing", None).await.unwrap().is_none());
+    }
 }
@@ -3,7 +3,12 @@ pub mod factory;
 pub mod failover;
 pub mod local;
 pub mod oss;
+pub mod s3;
+pub mod signing;
+mod streaming;
+pub mod tiered;
 pub mod traits;
 
 pub use failover::FailoverStorage;
-pub use traits::Storage;
+pub use tiered::TieredStorage;
+pub use traits::{Storage, StorageReader};
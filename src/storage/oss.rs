@@ -1,10 +1,12 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use opendal::{services::Oss as OssService, Operator};
+use std::ops::Range;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use super::traits::{FileMetadata, Storage};
+use super::streaming;
+use super::traits::{FileMetadata, Storage, StorageReader};
 
 pub struct OssStorage {
     operator: Operator,
@@ -223,8 +225,14 @@ impl Storage for OssStorage {
         }
     }
 
-    async fn get_presigned_url(&self, key: &str, _expires: Duration) -> Result<String> {
-        self.get_public_url(key).await
+    async fn get_presigned_url(&self, key: &str, expires: Duration) -> Result<String> {
+        let request = self
+            .operator
+            .presign_read(key.trim_start_matches('/'), expires)
+            .await
+            .with_context(|| format!("Failed to presign OSS object: {}", key))?;
+        debug!("OSS预签名URL生成成功: key={}, expires={:?}", key, expires);
+        Ok(request.uri().to_string())
     }
 
     async fn get_metadata(&self, key: &str) -> Result<FileMetadata> {
@@ -322,6 +330,28 @@ impl Storage for OssStorage {
             }
         }
     }
+
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        streaming::read_object(&self.operator, key, range).await
+    }
+
+    async fn put_reader(&self, key: &str, reader: StorageReader) -> Result<u64> {
+        let start = Instant::now();
+        let written = streaming::write_object(&self.operator, key, reader)
+            .await
+            .with_context(|| format!("Failed to stream to OSS: {}", key))?;
+        info!(
+            "[ok] OSS流式写入成功: key={}, size={}字节, 用时: {:?}",
+            key,
+            written,
+            start.elapsed()
+        );
+        Ok(written)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,268 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use opendal::{services::S3 as S3Service, Operator};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info};
+
+use super::streaming;
+use super::traits::{FileMetadata, Storage, StorageReader};
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// S3 兼容对象存储，适用于 MinIO、Ceph RGW 及华为 OBS 的 S3 接口
+pub struct S3Storage {
+    operator: Operator,
+    bucket: String,
+    endpoint: String,
+    public_endpoint: Option<String>,
+    virtual_host_style: bool,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config) -> Result<Self> {
+        info!(
+            "[tool] 配置S3服务: endpoint={}, bucket={}, virtual_host_style={}",
+            config.endpoint, config.bucket, config.virtual_host_style
+        );
+
+        let region = config
+            .region
+            .filter(|r| !r.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_REGION.to_string());
+
+        // 不读取本机 ~/.aws 配置，凭证只取自配置文件
+        let mut builder = S3Service::default()
+            .root(&config.root.unwrap_or_default())
+            .bucket(&config.bucket)
+            .endpoint(&config.endpoint)
+            .region(&region)
+            .access_key_id(&config.access_key_id)
+            .secret_access_key(&config.secret_access_key)
+            .disable_config_load();
+        if config.virtual_host_style {
+            builder = builder.enable_virtual_host_style();
+        }
+
+        let operator = Operator::new(builder)?.finish();
+
+        Ok(Self {
+            operator,
+            bucket: config.bucket,
+            endpoint: config.endpoint,
+            public_endpoint: config.public_endpoint,
+            virtual_host_style: config.virtual_host_style,
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let start = Instant::now();
+        self.operator
+            .write(key, data.to_vec())
+            .await
+            .map_err(|e| {
+                error!(
+                    "[fail] S3写入失败: key={}, kind={:?}, error={}",
+                    key,
+                    e.kind(),
+                    e
+                );
+                e
+            })
+            .context("Failed to write to S3")?;
+        info!(
+            "[ok] S3写入成功: key={}, size={}字节, 用时: {:?}",
+            key,
+            data.len(),
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.operator.read(key).await {
+            Ok(data) => Ok(Some(data.to_vec())),
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => {
+                debug!("[download] S3读取: key不存在: {}", key);
+                Ok(None)
+            }
+            Err(e) => {
+                error!(
+                    "[fail] S3读取失败: key={}, kind={:?}, error={}",
+                    key,
+                    e.kind(),
+                    e
+                );
+                Err(e).context("Failed to read from S3")?
+            }
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.operator
+            .delete(key)
+            .await
+            .context("Failed to delete from S3")
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        match self.operator.stat(key).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).context("Failed to check existence in S3")?,
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let entries = self
+            .operator
+            .list(prefix)
+            .await
+            .context("Failed to list S3 objects")?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| entry.path().to_string())
+            .collect())
+    }
+
+    async fn get_public_url(&self, key: &str) -> Result<String> {
+        let key = key.trim_start_matches('/');
+        if let Some(endpoint) = &self.public_endpoint {
+            return Ok(format!("{}/{}", endpoint.trim_end_matches('/'), key));
+        }
+
+        let endpoint = self.endpoint.trim_end_matches('/');
+        if self.virtual_host_style {
+            let (scheme, host) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+            Ok(format!("{}://{}.{}/{}", scheme, self.bucket, host, key))
+        } else {
+            Ok(format!("{}/{}/{}", endpoint, self.bucket, key))
+        }
+    }
+
+    async fn get_presigned_url(&self, key: &str, expires: Duration) -> Result<String> {
+        let request = self
+            .operator
+            .presign_read(key.trim_start_matches('/'), expires)
+            .await
+            .with_context(|| format!("Failed to presign S3 object: {}", key))?;
+        Ok(request.uri().to_string())
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<FileMetadata> {
+        let metadata = self
+            .operator
+            .stat(key)
+            .await
+            .context("Failed to get metadata from S3")?;
+
+        Ok(FileMetadata {
+            size: metadata.content_length(),
+            content_type: metadata.content_type().map(|s| s.to_string()),
+            last_modified: metadata.last_modified().unwrap_or_else(chrono::Utc::now),
+            etag: metadata.etag().map(|s| s.to_string()),
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        match self.operator.check().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                error!(
+                    "[fail] S3健康检查失败: bucket={}, endpoint={}, kind={:?}, error={}",
+                    self.bucket,
+                    self.endpoint,
+                    e.kind(),
+                    e
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        streaming::read_object(&self.operator, key, range).await
+    }
+
+    async fn put_reader(&self, key: &str, reader: StorageReader) -> Result<u64> {
+        let start = Instant::now();
+        let written = streaming::write_object(&self.operator, key, reader)
+            .await
+            .with_context(|| format!("Failed to stream to S3: {}", key))?;
+        info!(
+            "[ok] S3流式写入成功: key={}, size={}字节, 用时: {:?}",
+            key,
+            written,
+            start.elapsed()
+        );
+        Ok(written)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub bucket: String,
+    pub endpoint: String,
+    pub region: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub root: Option<String>,
+    pub public_endpoint: Option<String>,
+    pub virtual_host_style: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(virtual_host_style: bool) -> S3Config {
+        S3Config {
+            bucket: "previews".to_string(),
+            endpoint: "http://127.0.0.1:9000".to_string(),
+            region: None,
+            access_key_id: "minio".to_string(),
+            secret_access_key: "minio-secret".to_string(),
+            root: Some("/ocr".to_string()),
+            public_endpoint: None,
+            virtual_host_style,
+        }
+    }
+
+    #[tokio::test]
+    async fn public_url_follows_addressing_style() {
+        let path_style = S3Storage::new(config(false)).unwrap();
+        assert_eq!(
+            path_style.get_public_url("/reports/a.html").await.unwrap(),
+            "http://127.0.0.1:9000/previews/reports/a.html"
+        );
+
+        let mut obs = config(true);
+        obs.endpoint = "https://obs.cn-north-4.myhuaweicloud.com".to_string();
+        let virtual_host = S3Storage::new(obs).unwrap();
+        assert_eq!(
+            virtual_host.get_public_url("reports/a.html").await.unwrap(),
+            "https://previews.obs.cn-north-4.myhuaweicloud.com/reports/a.html"
+        );
+    }
+
+    #[tokio::test]
+    async fn presigned_url_carries_signature_and_expiry() {
+        let storage = S3Storage::new(config(false)).unwrap();
+        let url = storage
+            .get_presigned_url("reports/a.html", Duration::from_secs(300))
+            .await
+            .unwrap();
+
+        assert!(url.contains("/previews/ocr/reports/a.html?"));
+        assert!(url.contains("X-Amz-Expires=300"));
+        assert!(url.contains("X-Amz-Signature="));
+    }
+}
@@ -0,0 +1,220 @@
+//! 存储文件链接签名
+//!
+//! 签名串为 `{key}\n{expires}`，HMAC-SHA256 后十六进制编码，链接形如
+//! `{base_url}/api/storage/files/{key}?expires={unix}&signature={hex}`，由文件代理校验。
+//! 落库的结果只保存不带签名的代理地址，返回给调用方时再签发。
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use hmac::digest::{Key, KeyInit};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use urlencoding::{decode, encode};
+
+use crate::CONFIG;
+
+const PROXY_PATH: &str = "/api/storage/files/";
+
+static SHARED_SECRET: Lazy<String> = Lazy::new(|| {
+    let secret = CONFIG.storage_signing.secret.trim();
+    if secret.is_empty() {
+        warn!("未配置 storage_signing.secret，使用随机密钥，已签发链接在重启后失效");
+        crate::util::crypto::AesEncryption::generate_key()
+    } else {
+        secret.to_string()
+    }
+});
+
+// SHARED_SECRET 为空时已替换为随机密钥，这里无需再校验
+static SIGNER: Lazy<UrlSigner> = Lazy::new(|| UrlSigner::from_secret(&SHARED_SECRET));
+
+#[derive(Clone)]
+pub struct UrlSigner {
+    mac: Hmac<Sha256>,
+}
+
+impl UrlSigner {
+    pub fn new(secret: &str) -> Result<Self> {
+        if secret.trim().is_empty() {
+            bail!("链接签名密钥不能为空");
+        }
+        Ok(Self::from_secret(secret))
+    }
+
+    fn from_secret(secret: &str) -> Self {
+        Self {
+            mac: keyed_mac(secret.as_bytes()),
+        }
+    }
+
+    pub fn sign(&self, key: &str, expires_at: i64) -> String {
+        hex::encode(self.mac(key, expires_at).finalize().into_bytes())
+    }
+
+    /// 生成 `expires=..&signature=..` 查询串
+    pub fn query(&self, key: &str, ttl: Duration) -> String {
+        let expires_at = Utc::now().timestamp() + ttl.as_secs() as i64;
+        format!(
+            "expires={}&signature={}",
+            expires_at,
+            self.sign(key, expires_at)
+        )
+    }
+
+    pub fn verify(&self, key: &str, expires_at: i64, signature: &str, now: i64) -> Result<()> {
+        let signature = hex::decode(signature).map_err(|_| anyhow!("链接签名无效"))?;
+        self.mac(key, expires_at)
+            .verify_slice(&signature)
+            .map_err(|_| anyhow!("链接签名无效"))?;
+        if expires_at < now {
+            bail!("链接已过期");
+        }
+        Ok(())
+    }
+
+    fn mac(&self, key: &str, expires_at: i64) -> Hmac<Sha256> {
+        let mut mac = self.mac.clone();
+        mac.update(key.trim_start_matches('/').as_bytes());
+        mac.update(b"\n");
+        mac.update(expires_at.to_string().as_bytes());
+        mac
+    }
+}
+
+/// 按 RFC 2104 将密钥规整为一个分组：超过分组长度先做摘要，不足补零，因此构造不会失败
+fn keyed_mac(secret: &[u8]) -> Hmac<Sha256> {
+    let mut block = Key::<Hmac<Sha256>>::default();
+    if secret.len() > block.len() {
+        let digest = Sha256::digest(secret);
+        block[..digest.len()].copy_from_slice(&digest);
+    } else {
+        block[..secret.len()].copy_from_slice(secret);
+    }
+    <Hmac<Sha256> as KeyInit>::new(&block)
+}
+
+/// 当前进程使用的签名密钥
+pub fn shared_secret() -> &'static str {
+    &SHARED_SECRET
+}
+
+pub fn signer() -> &'static UrlSigner {
+    &SIGNER
+}
+
+/// 经文件代理访问的地址，不带签名，用于落库
+pub fn proxy_url(storage_key: &str) -> String {
+    format!(
+        "{}{}{}",
+        CONFIG.base_url().trim_end_matches('/'),
+        PROXY_PATH,
+        encode(storage_key.trim_start_matches('/'))
+    )
+}
+
+/// 经文件代理访问的限时链接，有效期取 `storage_signing.link_ttl_secs`
+pub fn signed_proxy_url(storage_key: &str) -> String {
+    let key = storage_key.trim_start_matches('/');
+    format!(
+        "{}?{}",
+        proxy_url(key),
+        signer().query(
+            key,
+            Duration::from_secs(CONFIG.storage_signing.link_ttl_secs)
+        )
+    )
+}
+
+/// 为指向本服务文件代理的链接重新签发；旧签名一并替换。
+/// 只签 `owned` 中的存储键，其余链接返回 `None`
+pub fn resign_proxy_url(url: &str, owned: &HashSet<String>) -> Option<String> {
+    let key = proxy_key(url, CONFIG.base_url().trim_end_matches('/'))?;
+    owned.contains(&key).then(|| signed_proxy_url(&key))
+}
+
+fn proxy_key(url: &str, base_url: &str) -> Option<String> {
+    let path = url.strip_prefix(base_url).unwrap_or(url);
+    let encoded = path.strip_prefix(PROXY_PATH)?;
+    let encoded = encoded.split(['?', '#']).next().unwrap_or_default();
+    let key = decode(encoded).ok()?;
+    let key = key.trim_start_matches('/');
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// 校验文件代理请求携带的签名；未签名请求仅在 `allow_unsigned` 时放行
+pub fn verify_request(
+    storage_key: &str,
+    expires: Option<i64>,
+    signature: Option<&str>,
+) -> Result<()> {
+    match (expires, signature) {
+        (Some(expires), Some(signature)) => {
+            signer().verify(storage_key, expires, signature, Utc::now().timestamp())
+        }
+        _ if CONFIG.storage_signing.allow_unsigned => Ok(()),
+        _ => bail!("缺少链接签名"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_binds_key_and_expiry() {
+        let signer = UrlSigner::new("test-secret").unwrap();
+        let sig = signer.sign("reports/a.html", 1_000);
+
+        assert!(signer.verify("reports/a.html", 1_000, &sig, 900).is_ok());
+        assert!(signer.verify("/reports/a.html", 1_000, &sig, 900).is_ok());
+        assert!(signer.verify("reports/b.html", 1_000, &sig, 900).is_err());
+        assert!(signer.verify("reports/a.html", 2_000, &sig, 900).is_err());
+        assert!(signer.verify("reports/a.html", 1_000, "zz", 900).is_err());
+
+        let expired = signer.verify("reports/a.html", 1_000, &sig, 1_001);
+        assert_eq!(expired.unwrap_err().to_string(), "链接已过期");
+
+        let other = UrlSigner::new("other-secret").unwrap();
+        assert!(other.verify("reports/a.html", 1_000, &sig, 900).is_err());
+        assert!(UrlSigner::new("  ").is_err());
+    }
+
+    #[test]
+    fn keyed_mac_matches_variable_length_keys() {
+        for secret in ["k", &"x".repeat(64), &"long-secret".repeat(20)] {
+            let mut expected = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).unwrap();
+            let mut actual = keyed_mac(secret.as_bytes());
+            expected.update(b"payload");
+            actual.update(b"payload");
+            assert_eq!(
+                expected.finalize().into_bytes(),
+                actual.finalize().into_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn proxy_key_accepts_only_local_proxy_links() {
+        let base = "https://preview.example.com";
+        assert_eq!(
+            proxy_key(
+                "https://preview.example.com/api/storage/files/previews%2Fp1%2Fa.jpg?expires=1&signature=ab",
+                base
+            )
+            .as_deref(),
+            Some("previews/p1/a.jpg")
+        );
+        assert_eq!(
+            proxy_key("/api/storage/files/uploads%2Fp1%2Fm1.pdf", base).as_deref(),
+            Some("uploads/p1/m1.pdf")
+        );
+        assert!(proxy_key("https://other.example.com/api/storage/files/a.jpg", base).is_none());
+        assert!(proxy_key("https://preview.example.com/api/storage/files/", base).is_none());
+        assert!(proxy_key("data:image/png;base64,AAAA", base).is_none());
+    }
+}
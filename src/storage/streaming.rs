@@ -0,0 +1,79 @@
+//! OSS 与 S3 共用的 opendal 流式读写
+
+use anyhow::{Context, Result};
+use opendal::Operator;
+use std::ops::Range;
+use tokio::io::AsyncReadExt;
+use tokio_util::io::StreamReader;
+
+use super::traits::StorageReader;
+
+/// 每次从调用方读取的字节数
+const READ_CHUNK_SIZE: u64 = 1024 * 1024;
+/// 分片上传的分片大小，OSS 与 S3 都要求非末尾分片不小于 5MB
+const WRITE_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+pub(crate) async fn read_object(
+    operator: &Operator,
+    key: &str,
+    range: Option<Range<u64>>,
+) -> Result<Option<StorageReader>> {
+    let size = match operator.stat(key).await {
+        Ok(metadata) => metadata.content_length(),
+        Err(e) if e.kind() == opendal::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context("Failed to stat object"),
+    };
+
+    let range = range.unwrap_or(0..size);
+    let stream = operator
+        .reader(key)
+        .await
+        .context("Failed to open object reader")?
+        .into_bytes_stream(range)
+        .await
+        .context("Failed to open object stream")?;
+
+    Ok(Some(Box::new(StreamReader::new(Box::pin(stream)))))
+}
+
+pub(crate) async fn write_object(
+    operator: &Operator,
+    key: &str,
+    mut reader: StorageReader,
+) -> Result<u64> {
+    let mut writer = operator
+        .writer_with(key)
+        .chunk(WRITE_CHUNK_SIZE)
+        .await
+        .context("Failed to open object writer")?;
+
+    let mut written = 0u64;
+    loop {
+        let mut chunk = Vec::with_capacity(READ_CHUNK_SIZE as usize);
+        let read = match (&mut reader)
+            .take(READ_CHUNK_SIZE)
+            .read_to_end(&mut chunk)
+            .await
+        {
+            Ok(read) => read,
+            Err(e) => {
+                let _ = writer.abort().await;
+                return Err(e).context("Failed to read upload stream");
+            }
+        };
+        if read == 0 {
+            break;
+        }
+        if let Err(e) = writer.write(chunk).await {
+            let _ = writer.abort().await;
+            return Err(e).context("Failed to write object chunk");
+        }
+        written += read as u64;
+    }
+
+    writer
+        .close()
+        .await
+        .context("Failed to finish object upload")?;
+    Ok(written)
+}
@@ -0,0 +1,256 @@
+//! 分层存储
+//!
+//! 写入只落本地热层；读取先查热层再查冷层。后台迁移任务把超过
+//! `migrate_after` 未修改的热层文件复制到冷层后再删除，迁移期间被改写的文件留到下一轮。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::local::LocalStorage;
+use super::traits::{FileMetadata, Storage, StorageReader};
+
+#[derive(Debug, Clone)]
+pub struct TierPolicy {
+    pub migrate_after: Duration,
+    /// 0 表示不启动后台迁移
+    pub scan_interval: Duration,
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TierMigrationSummary {
+    pub candidates: usize,
+    pub migrated: usize,
+    pub skipped: usize,
+    pub failures: usize,
+}
+
+#[derive(Clone)]
+pub struct TieredStorage {
+    hot: Arc<LocalStorage>,
+    cold: Arc<dyn Storage>,
+    policy: TierPolicy,
+}
+
+impl TieredStorage {
+    pub fn new(hot: LocalStorage, cold: Arc<dyn Storage>, policy: TierPolicy) -> Self {
+        Self {
+            hot: Arc::new(hot),
+            cold,
+            policy,
+        }
+    }
+
+    /// 按 scan_interval 周期迁移，scan_interval 为 0 时不启动
+    pub fn spawn_migrator(&self) {
+        if self.policy.scan_interval.is_zero() {
+            return;
+        }
+        let storage = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(storage.policy.scan_interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match storage.migrate_once().await {
+                    Ok(summary) if summary.candidates > 0 => info!(
+                        candidates = summary.candidates,
+                        migrated = summary.migrated,
+                        skipped = summary.skipped,
+                        failures = summary.failures,
+                        "分层存储迁移完成"
+                    ),
+                    Ok(_) => {}
+                    Err(e) => warn!(error = %e, "分层存储迁移失败"),
+                }
+            }
+        });
+    }
+
+    /// 迁移一批最久未修改的热层文件
+    pub async fn migrate_once(&self) -> Result<TierMigrationSummary> {
+        let cutoff = Utc::now() - chrono::Duration::from_std(self.policy.migrate_after)?;
+        let mut candidates: Vec<_> = self
+            .hot
+            .list_with_mtime()
+            .await?
+            .into_iter()
+            .filter(|(_, modified)| *modified <= cutoff)
+            .collect();
+        candidates.sort_by_key(|(_, modified)| *modified);
+        candidates.truncate(self.policy.batch_size.max(1));
+
+        let mut summary = TierMigrationSummary {
+            candidates: candidates.len(),
+            ..Default::default()
+        };
+        for (key, modified) in candidates {
+            match self.demote(&key, modified).await {
+                Ok(true) => summary.migrated += 1,
+                Ok(false) => summary.skipped += 1,
+                Err(e) => {
+                    warn!(key = %key, error = %e, "迁移到冷层失败");
+                    summary.failures += 1;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    async fn demote(&self, key: &str, modified: DateTime<Utc>) -> Result<bool> {
+        let Some(reader) = self.hot.get_reader(key, None).await? else {
+            return Ok(false);
+        };
+        self.cold.put_reader(key, reader).await?;
+
+        if !self.hot.exists(key).await? {
+            // 上传期间被删除，冷层副本随之清理
+            self.cold.delete(key).await?;
+            return Ok(false);
+        }
+        if self.hot.get_metadata(key).await?.last_modified != modified {
+            return Ok(false);
+        }
+        self.hot.delete(key).await?;
+        Ok(true)
+    }
+
+    async fn tier_of(&self, key: &str) -> Result<&dyn Storage> {
+        if self.hot.exists(key).await? {
+            Ok(self.hot.as_ref())
+        } else {
+            Ok(self.cold.as_ref())
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for TieredStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.hot.put(key, data).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        if let Some(data) = self.hot.get(key).await? {
+            return Ok(Some(data));
+        }
+        self.cold.get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.hot.delete(key).await?;
+        self.cold.delete(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.hot.exists(key).await? || self.cold.exists(key).await?)
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys: BTreeSet<String> = self.hot.list(prefix).await?.into_iter().collect();
+        keys.extend(self.cold.list(prefix).await?);
+        Ok(keys.into_iter().collect())
+    }
+
+    async fn get_public_url(&self, key: &str) -> Result<String> {
+        self.tier_of(key).await?.get_public_url(key).await
+    }
+
+    async fn get_presigned_url(&self, key: &str, expires: Duration) -> Result<String> {
+        self.tier_of(key)
+            .await?
+            .get_presigned_url(key, expires)
+            .await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<FileMetadata> {
+        self.tier_of(key).await?.get_metadata(key).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(self.hot.health_check().await? && self.cold.health_check().await?)
+    }
+
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        if let Some(reader) = self.hot.get_reader(key, range.clone()).await? {
+            return Ok(Some(reader));
+        }
+        self.cold.get_reader(key, range).await
+    }
+
+    async fn put_reader(&self, key: &str, reader: StorageReader) -> Result<u64> {
+        self.hot.put_reader(key, reader).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tiered(dir: &TempDir, migrate_after: Duration) -> (TieredStorage, Arc<LocalStorage>) {
+        let hot = LocalStorage::new(dir.path().join("hot"), "http://localhost/hot").unwrap();
+        let cold =
+            Arc::new(LocalStorage::new(dir.path().join("cold"), "http://localhost/cold").unwrap());
+        let policy = TierPolicy {
+            migrate_after,
+            scan_interval: Duration::ZERO,
+            batch_size: 10,
+        };
+        (TieredStorage::new(hot, cold.clone(), policy), cold)
+    }
+
+    #[tokio::test]
+    async fn migrates_old_objects_and_reads_across_tiers() {
+        let dir = TempDir::new().unwrap();
+        let (storage, cold) = tiered(&dir, Duration::ZERO);
+
+        storage.put("reports/a.html", b"a").await.unwrap();
+        storage.put("uploads/p1/b.pdf", b"b").await.unwrap();
+        assert!(!cold.exists("reports/a.html").await.unwrap());
+
+        let summary = storage.migrate_once().await.unwrap();
+        assert_eq!(summary.migrated, 2);
+        assert_eq!(summary.failures, 0);
+        assert!(!storage.hot.exists("uploads/p1/b.pdf").await.unwrap());
+        assert_eq!(cold.get("uploads/p1/b.pdf").await.unwrap().unwrap(), b"b");
+
+        assert_eq!(storage.get("reports/a.html").await.unwrap().unwrap(), b"a");
+        assert_eq!(
+            storage.get_public_url("reports/a.html").await.unwrap(),
+            "http://localhost/cold/reports/a.html"
+        );
+
+        storage.put("reports/a.html", b"a2").await.unwrap();
+        assert_eq!(storage.get("reports/a.html").await.unwrap().unwrap(), b"a2");
+        assert_eq!(
+            storage.list("reports").await.unwrap(),
+            vec!["reports/a.html"]
+        );
+
+        storage.delete("reports/a.html").await.unwrap();
+        assert!(!storage.exists("reports/a.html").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn keeps_recent_objects_hot() {
+        let dir = TempDir::new().unwrap();
+        let (storage, cold) = tiered(&dir, Duration::from_secs(3600));
+
+        storage.put("reports/new.html", b"new").await.unwrap();
+        let summary = storage.migrate_once().await.unwrap();
+        assert_eq!(summary.candidates, 0);
+        assert!(storage.hot.exists("reports/new.html").await.unwrap());
+        assert!(!cold.exists("reports/new.html").await.unwrap());
+    }
+}
@@ -1,6 +1,11 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::io::Cursor;
+use std::ops::Range;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+pub type StorageReader = Box<dyn AsyncRead + Send + Unpin>;
 
 #[async_trait]
 pub trait Storage: Send + Sync {
@@ -21,6 +26,33 @@ pub trait Storage: Send + Sync {
     async fn get_metadata(&self, key: &str) -> Result<FileMetadata>;
 
     async fn health_check(&self) -> Result<bool>;
+
+    /// 流式读取；range 为半开区间，调用方需先按对象大小裁剪。默认实现整体读入内存
+    async fn get_reader(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<StorageReader>> {
+        let Some(data) = self.get(key).await? else {
+            return Ok(None);
+        };
+        let data = match range {
+            Some(range) => {
+                let end = (range.end as usize).min(data.len());
+                data[(range.start as usize).min(end)..end].to_vec()
+            }
+            None => data,
+        };
+        Ok(Some(Box::new(Cursor::new(data))))
+    }
+
+    /// 流式写入，返回写入字节数。默认实现整体读入内存后调用 put
+    async fn put_reader(&self, key: &str, mut reader: StorageReader) -> Result<u64> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        self.put(key, &data).await?;
+        Ok(data.len() as u64)
+    }
 }
 
 #[derive(Debug, Clone)]
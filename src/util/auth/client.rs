@@ -287,6 +287,8 @@ mod tests {
             source_type: "direct_api".to_string(),
             enabled: true,
             permissions: vec![],
+            queue_priority: None,
+            queue_weight: 1,
         };
 
         assert!(ThirdPartyAuthService::validate_client_config(&client).is_ok());
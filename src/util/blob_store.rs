@@ -0,0 +1,311 @@
+//! 材料原件的内容寻址存储
+//!
+//! 开启 `storage_dedup.enabled` 后，原件按 SHA-256 存放在 `blobs/sha256/{前两位}/{摘要}.{扩展名}`，
+//! 不同预审的材料文件记录通过 `stored_original_key` 引用同一对象。`storage_blobs` 登记每个 blob，
+//! 引用数即指向它的材料记录数；留存清理清空 `stored_original_key` 即释放引用，不直接删除 blob。
+//!
+//! 回收只认领无引用且最近引用早于宽限期的 blob，认领后先删对象再删记录。上传前的登记会刷新
+//! 最近引用时间，并拒绝已被认领的 blob（此时退回按预审存放），登记到写入材料记录之间的窗口
+//! 由宽限期保护。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::db::traits::{BlobGcQuery, StorageBlobRecord};
+use crate::db::Database;
+use crate::storage::Storage;
+use crate::util::config::types::{DeploymentRole, StorageDedupConfig};
+use crate::{AppState, CONFIG};
+
+pub const BLOB_PREFIX: &str = "blobs/sha256/";
+
+/// 认领后超过该时长仍未完成的回收视为中断，可被下一轮重新认领
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(3600);
+
+static COLLECTOR_TASK: OnceCell<()> = OnceCell::new();
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BlobGcSummary {
+    pub claimed: usize,
+    pub deleted: usize,
+    pub freed_bytes: i64,
+    pub failures: usize,
+}
+
+/// 摘要须为 64 位十六进制；扩展名只保留字母数字，为空时用 bin
+pub fn blob_key(checksum: &str, extension: &str) -> Option<String> {
+    if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let checksum = checksum.to_ascii_lowercase();
+    let mut ext: String = extension
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if ext.is_empty() {
+        ext = "bin".to_string();
+    }
+    Some(format!(
+        "{}{}/{}.{}",
+        BLOB_PREFIX,
+        &checksum[..2],
+        checksum,
+        ext
+    ))
+}
+
+/// 登记并写入 blob，返回其存储 key；blob 正被回收时返回 None
+pub async fn put_blob(
+    database: &dyn Database,
+    storage: &dyn Storage,
+    checksum: &str,
+    extension: &str,
+    data: &[u8],
+) -> Result<Option<String>> {
+    let key = blob_key(checksum, extension)
+        .ok_or_else(|| anyhow!("无效的 SHA-256 摘要: {}", checksum))?;
+    let now = Utc::now();
+    let registered = database
+        .touch_storage_blob(&StorageBlobRecord {
+            storage_key: key.clone(),
+            checksum_sha256: checksum.to_ascii_lowercase(),
+            size_bytes: data.len() as i64,
+            created_at: now,
+            last_referenced_at: now,
+        })
+        .await?;
+    if !registered {
+        return Ok(None);
+    }
+
+    // 大小不符说明上次写入中断，重新写入
+    let reusable =
+        storage.exists(&key).await? && storage.get_metadata(&key).await?.size == data.len() as u64;
+    if reusable {
+        debug!(storage_key = %key, "复用已存在的 blob");
+    } else {
+        storage.put(&key, data).await?;
+    }
+    Ok(Some(key))
+}
+
+/// 保存材料原件并返回实际使用的存储 key。去重开启时存为 blob，
+/// 数据库不可用、blob 正被回收或登记失败时退回 `fallback_key`
+pub async fn store_original(
+    database: Option<&dyn Database>,
+    storage: &dyn Storage,
+    fallback_key: &str,
+    checksum: &str,
+    extension: &str,
+    data: &[u8],
+) -> Result<String> {
+    if let (true, Some(database)) = (CONFIG.storage_dedup.enabled, database) {
+        match put_blob(database, storage, checksum, extension, data).await {
+            Ok(Some(key)) => return Ok(key),
+            Ok(None) => debug!(checksum = %checksum, "blob 正在回收，按预审路径保存"),
+            Err(err) => {
+                warn!(checksum = %checksum, error = %err, "内容寻址存储失败，按预审路径保存")
+            }
+        }
+    }
+    storage.put(fallback_key, data).await?;
+    Ok(fallback_key.to_string())
+}
+
+/// 回收一批无引用的 blob。对象删除失败时放弃认领，留到下一轮
+pub async fn collect_garbage(
+    database: &dyn Database,
+    storage: &dyn Storage,
+    config: &StorageDedupConfig,
+) -> Result<BlobGcSummary> {
+    let now = Utc::now();
+    let query = BlobGcQuery {
+        claim_id: Uuid::new_v4().to_string(),
+        unreferenced_before: now - chrono::Duration::seconds(config.gc_grace_secs as i64),
+        stale_claim_before: now - chrono::Duration::from_std(CLAIM_TIMEOUT)?,
+        limit: config.gc_batch_size.max(1),
+    };
+    let blobs = database.claim_unreferenced_blobs(&query).await?;
+
+    let mut summary = BlobGcSummary {
+        claimed: blobs.len(),
+        ..Default::default()
+    };
+    for blob in blobs {
+        if let Err(err) = storage.delete(&blob.storage_key).await {
+            warn!(storage_key = %blob.storage_key, error = %err, "删除 blob 失败");
+            summary.failures += 1;
+            if let Err(err) = database
+                .release_storage_blob(&blob.storage_key, &query.claim_id)
+                .await
+            {
+                warn!(storage_key = %blob.storage_key, error = %err, "放弃 blob 认领失败");
+            }
+            continue;
+        }
+        match database
+            .delete_storage_blob(&blob.storage_key, &query.claim_id)
+            .await
+        {
+            Ok(true) => {
+                summary.deleted += 1;
+                summary.freed_bytes += blob.size_bytes;
+            }
+            // 认领已超时被接管，记录由接管的一轮删除
+            Ok(false) => {}
+            Err(err) => {
+                warn!(storage_key = %blob.storage_key, error = %err, "删除 blob 记录失败");
+                summary.failures += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// 由 master 定期回收；关闭去重后已有的 blob 仍会随引用释放被回收
+pub fn spawn_collector(app_state: &AppState) {
+    let config = app_state.config.storage_dedup.clone();
+    if config.gc_interval_secs == 0 || app_state.config.deployment.role == DeploymentRole::Worker {
+        return;
+    }
+    if COLLECTOR_TASK.set(()).is_err() {
+        return;
+    }
+
+    let database = Arc::clone(&app_state.database);
+    let storage = Arc::clone(&app_state.storage);
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(config.gc_interval_secs.max(60)));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match collect_garbage(database.as_ref(), storage.as_ref(), &config).await {
+                Ok(summary) if summary.claimed > 0 => info!(
+                    claimed = summary.claimed,
+                    deleted = summary.deleted,
+                    freed_bytes = summary.freed_bytes,
+                    failures = summary.failures,
+                    "blob 回收完成"
+                ),
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "blob 回收失败"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::db::traits::MaterialFileRecord;
+    use crate::storage::local::LocalStorage;
+    use sha2::{Digest, Sha256};
+
+    fn material_file(id: &str, preview_id: &str, key: &str) -> MaterialFileRecord {
+        let now = Utc::now();
+        MaterialFileRecord {
+            id: id.to_string(),
+            preview_id: preview_id.to_string(),
+            material_code: "m1".to_string(),
+            attachment_name: None,
+            source_url: None,
+            stored_original_key: key.to_string(),
+            stored_processed_keys: None,
+            mime_type: Some("application/pdf".to_string()),
+            size_bytes: None,
+            checksum_sha256: None,
+            ocr_text_key: None,
+            ocr_text_length: None,
+            status: "downloaded".to_string(),
+            error_message: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn blob_key_is_derived_from_checksum() {
+        let checksum = "AB".repeat(32);
+        assert_eq!(
+            blob_key(&checksum, ".PDF").unwrap(),
+            format!("blobs/sha256/ab/{}.pdf", "ab".repeat(32))
+        );
+        assert!(blob_key(&checksum, "").unwrap().ends_with(".bin"));
+        assert!(blob_key("abc", "pdf").is_none());
+        assert!(blob_key(&"zz".repeat(32), "pdf").is_none());
+    }
+
+    #[tokio::test]
+    async fn shared_blob_is_collected_after_last_reference_is_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqliteDatabase::new(dir.path().join("blobs.db").to_str().unwrap())
+            .await
+            .unwrap();
+        db.initialize().await.unwrap();
+        MigrationRunner::new(&db).migrate_up().await.unwrap();
+        let storage = LocalStorage::new(dir.path().join("storage"), "http://localhost").unwrap();
+
+        let data = b"%PDF-1.4 same attachment";
+        let checksum = hex::encode(Sha256::digest(data));
+        let first = put_blob(&db, &storage, &checksum, "pdf", data)
+            .await
+            .unwrap()
+            .unwrap();
+        let second = put_blob(&db, &storage, &checksum, "pdf", data)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(storage.list_with_mtime().await.unwrap().len(), 1);
+
+        db.save_material_file_record(&material_file("f1", "p1", &first))
+            .await
+            .unwrap();
+        db.save_material_file_record(&material_file("f2", "p2", &first))
+            .await
+            .unwrap();
+
+        let config = StorageDedupConfig {
+            gc_grace_secs: 0,
+            ..Default::default()
+        };
+        let summary = collect_garbage(&db, &storage, &config).await.unwrap();
+        assert_eq!(summary.claimed, 0);
+
+        // 留存清理清空原件 key 即释放引用；仍有一条引用时不回收
+        sqlx::query("UPDATE preview_material_files SET stored_original_key = '' WHERE id = 'f1'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let summary = collect_garbage(&db, &storage, &config).await.unwrap();
+        assert_eq!(summary.claimed, 0);
+        assert!(storage.exists(&first).await.unwrap());
+
+        sqlx::query("UPDATE preview_material_files SET stored_original_key = '' WHERE id = 'f2'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let summary = collect_garbage(&db, &storage, &config).await.unwrap();
+        assert_eq!(summary.deleted, 1);
+        assert_eq!(summary.freed_bytes, data.len() as i64);
+        assert!(!storage.exists(&first).await.unwrap());
+
+        // 回收后再次提交相同附件会重新写入
+        let again = put_blob(&db, &storage, &checksum, "pdf", data)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(storage.get(&again).await.unwrap().unwrap(), data);
+    }
+}
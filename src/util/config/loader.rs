@@ -366,6 +366,25 @@ impl ConfigLoader {
             return Err(anyhow::anyhow!("无效的日志级别: {}", config.logging.level));
         }
 
+        // 检索索引独立于业务库以明文保存 OCR 文本与申请人信息，会绕过字段加密
+        if config.search.enabled && config.pii_encryption.enabled {
+            return Err(anyhow::anyhow!(
+                "search.enabled 与 pii_encryption.enabled 不能同时开启：检索索引会以明文保存 OCR 文本与申请人信息"
+            ));
+        }
+
+        // 随机密钥只在本进程有效，其他节点签发的链接会被拒绝
+        let multi_node = config.distributed.enabled
+            || matches!(
+                config.deployment.role,
+                super::types::DeploymentRole::Master | super::types::DeploymentRole::Worker
+            );
+        if multi_node && config.storage_signing.secret.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "多节点部署必须配置 storage_signing.secret，且各节点保持一致"
+            ));
+        }
+
         let public_base = config
             .public_base_url
             .as_ref()
@@ -582,6 +601,8 @@ impl ConfigWriter {
                     source_type: "direct_api".to_string(),
                     enabled: false,
                     permissions: vec!["preview".to_string(), "query".to_string()],
+                    queue_priority: None,
+                    queue_weight: 1,
                 }],
                 signature: SignatureConfig {
                     required: true,
@@ -633,6 +654,14 @@ impl ConfigWriter {
             },
             ocr_tuning: super::types::OcrTuningConfig::default(),
             ocr_pool: super::types::OcrPoolConfig::default(),
+            ocr_preprocess: super::types::OcrPreprocessConfig::default(),
+            ocr_cache: super::types::OcrCacheConfig::default(),
+            retention: super::types::RetentionConfig::default(),
+            pii_encryption: super::types::PiiEncryptionConfig::default(),
+            search: super::types::SearchConfig::default(),
+            storage_signing: super::types::StorageSigningConfig::default(),
+            storage_backend: super::types::StorageBackendConfig::default(),
+            storage_dedup: super::types::StorageDedupConfig::default(),
             ocr_engine: None,
             task_queue: super::types::TaskQueueConfig::default(),
             worker_proxy: super::types::WorkerProxyConfig::default(),
@@ -81,6 +81,22 @@ pub struct Config {
     #[serde(default)]
     pub ocr_pool: OcrPoolConfig,
     #[serde(default)]
+    pub ocr_preprocess: OcrPreprocessConfig,
+    #[serde(default)]
+    pub ocr_cache: OcrCacheConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub pii_encryption: PiiEncryptionConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub storage_signing: StorageSigningConfig,
+    #[serde(default)]
+    pub storage_backend: StorageBackendConfig,
+    #[serde(default)]
+    pub storage_dedup: StorageDedupConfig,
+    #[serde(default)]
     pub task_queue: TaskQueueConfig,
     #[serde(default)]
     pub worker_proxy: WorkerProxyConfig,
@@ -236,6 +252,18 @@ pub struct WorkerCapabilitiesConfig {
     pub max_concurrent: u32,
     #[serde(default = "default_worker_file_size")]
     pub max_file_size_mb: u32,
+    /// 所属任务池，只消费路由到该池的任务；为空时消费默认主题/队列
+    #[serde(default)]
+    pub pool: Option<String>,
+    /// 可处理的附件格式（扩展名），为空时使用 PDF 与常见图片格式
+    #[serde(default)]
+    pub formats: Vec<String>,
+    /// 资源标签，如 gpu-free、large-memory
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 只接收这些事项的任务，为空表示不限
+    #[serde(default)]
+    pub matters: Vec<String>,
 }
 
 impl Default for WorkerCapabilitiesConfig {
@@ -243,6 +271,10 @@ impl Default for WorkerCapabilitiesConfig {
         Self {
             max_concurrent: default_worker_concurrency(),
             max_file_size_mb: default_worker_file_size(),
+            pool: None,
+            formats: Vec::new(),
+            tags: Vec::new(),
+            matters: Vec::new(),
         }
     }
 }
@@ -311,6 +343,12 @@ pub struct TaskQueueConfig {
     pub nats: Option<NatsQueueConfig>,
     #[serde(default)]
     pub database: Option<DatabaseQueueConfig>,
+    #[serde(default)]
+    pub lanes: PriorityLanesConfig,
+    #[serde(default)]
+    pub routing: TaskRoutingConfig,
+    #[serde(default)]
+    pub pdf_sharding: PdfShardingConfig,
 }
 
 impl Default for TaskQueueConfig {
@@ -320,6 +358,177 @@ impl Default for TaskQueueConfig {
             local: LocalQueueConfig::default(),
             nats: None,
             database: None,
+            lanes: PriorityLanesConfig::default(),
+            routing: TaskRoutingConfig::default(),
+            pdf_sharding: PdfShardingConfig::default(),
+        }
+    }
+}
+
+/// 大 PDF 按页段拆成子任务，经任务队列分发给多个 worker 并行识别
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfShardingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 页数达到该值才分片
+    #[serde(default = "default_shard_min_pages")]
+    pub min_pages: u32,
+    #[serde(default = "default_shard_pages_per_shard")]
+    pub pages_per_shard: u32,
+    /// 单个分片的最多执行次数，用尽后整份附件识别失败
+    #[serde(default = "default_shard_max_attempts")]
+    pub max_attempts: u32,
+    /// 分片被领取后超过该时长未回报即视为丢失，重新开放领取
+    #[serde(default = "default_shard_timeout_secs")]
+    pub shard_timeout_secs: u64,
+}
+
+fn default_shard_min_pages() -> u32 {
+    40
+}
+
+fn default_shard_pages_per_shard() -> u32 {
+    10
+}
+
+fn default_shard_max_attempts() -> u32 {
+    2
+}
+
+fn default_shard_timeout_secs() -> u64 {
+    600
+}
+
+impl Default for PdfShardingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_pages: default_shard_min_pages(),
+            pages_per_shard: default_shard_pages_per_shard(),
+            max_attempts: default_shard_max_attempts(),
+            shard_timeout_secs: default_shard_timeout_secs(),
+        }
+    }
+}
+
+/// 按 worker 心跳上报的能力把任务路由到对应任务池，仅 nats/database 驱动生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRoutingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 没有在线 worker 能处理任务时的处理方式
+    #[serde(default)]
+    pub unmatched: UnmatchedTaskPolicy,
+    /// 暂缓任务重新匹配的间隔
+    #[serde(default = "default_routing_hold_check_secs")]
+    pub hold_check_secs: u64,
+    /// 事项要求 worker 具备的标签，键为事项 ID
+    #[serde(default)]
+    pub matter_tags: HashMap<String, Vec<String>>,
+}
+
+fn default_routing_hold_check_secs() -> u64 {
+    30
+}
+
+impl Default for TaskRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unmatched: UnmatchedTaskPolicy::default(),
+            hold_check_secs: default_routing_hold_check_secs(),
+            matter_tags: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnmatchedTaskPolicy {
+    /// 暂缓入队，等待具备能力的 worker 上线
+    #[default]
+    Hold,
+    /// 直接判定失败
+    Reject,
+}
+
+/// 预审任务优先级通道
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum QueuePriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl QueuePriority {
+    pub const ALL: [QueuePriority; 3] = [
+        QueuePriority::High,
+        QueuePriority::Normal,
+        QueuePriority::Low,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueuePriority::High => "high",
+            QueuePriority::Normal => "normal",
+            QueuePriority::Low => "low",
+        }
+    }
+}
+
+/// 优先级通道之间按权重轮询出队，权重为 0 的通道仅在其他通道为空时出队
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityLanesConfig {
+    #[serde(default = "default_lane_high_weight")]
+    pub high_weight: u32,
+    #[serde(default = "default_lane_normal_weight")]
+    pub normal_weight: u32,
+    #[serde(default = "default_lane_low_weight")]
+    pub low_weight: u32,
+    /// 门户等交互式请求（无第三方客户端身份）使用的通道
+    #[serde(default = "default_interactive_priority")]
+    pub interactive_priority: QueuePriority,
+    /// 第三方客户端未配置 `queue_priority` 时使用的通道
+    #[serde(default)]
+    pub third_party_priority: QueuePriority,
+}
+
+fn default_lane_high_weight() -> u32 {
+    8
+}
+
+fn default_lane_normal_weight() -> u32 {
+    4
+}
+
+fn default_lane_low_weight() -> u32 {
+    1
+}
+
+fn default_interactive_priority() -> QueuePriority {
+    QueuePriority::High
+}
+
+impl Default for PriorityLanesConfig {
+    fn default() -> Self {
+        Self {
+            high_weight: default_lane_high_weight(),
+            normal_weight: default_lane_normal_weight(),
+            low_weight: default_lane_low_weight(),
+            interactive_priority: default_interactive_priority(),
+            third_party_priority: QueuePriority::Normal,
+        }
+    }
+}
+
+impl PriorityLanesConfig {
+    pub fn weight(&self, priority: QueuePriority) -> u32 {
+        match priority {
+            QueuePriority::High => self.high_weight,
+            QueuePriority::Normal => self.normal_weight,
+            QueuePriority::Low => self.low_weight,
         }
     }
 }
@@ -328,16 +537,51 @@ impl Default for TaskQueueConfig {
 pub struct LocalQueueConfig {
     #[serde(default = "default_local_channel_capacity")]
     pub channel_capacity: usize,
+    /// 并发消费的任务数
+    #[serde(default = "default_local_concurrency")]
+    pub concurrency: usize,
+    /// 单个任务最多执行次数（含首次）
+    #[serde(default = "default_local_max_attempts")]
+    pub max_attempts: u32,
+    /// 重试基础延迟，按尝试次数指数增长，上限 60 秒
+    #[serde(default = "default_local_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// 启动时从任务 payload 日志重放未完成的任务
+    #[serde(default = "default_true")]
+    pub replay_on_start: bool,
+    #[serde(default = "default_local_replay_limit")]
+    pub replay_limit: u32,
 }
 
 fn default_local_channel_capacity() -> usize {
     128
 }
 
+fn default_local_concurrency() -> usize {
+    2
+}
+
+fn default_local_max_attempts() -> u32 {
+    3
+}
+
+fn default_local_retry_backoff_ms() -> u64 {
+    2_000
+}
+
+fn default_local_replay_limit() -> u32 {
+    500
+}
+
 impl Default for LocalQueueConfig {
     fn default() -> Self {
         Self {
             channel_capacity: default_local_channel_capacity(),
+            concurrency: default_local_concurrency(),
+            max_attempts: default_local_max_attempts(),
+            retry_backoff_ms: default_local_retry_backoff_ms(),
+            replay_on_start: true,
+            replay_limit: default_local_replay_limit(),
         }
     }
 }
@@ -464,6 +708,21 @@ pub struct DatabaseQueueConfig {
     pub table_name: String,
     #[serde(default = "default_db_queue_poll_interval_ms")]
     pub poll_interval_ms: u64,
+    /// 租约（可见性超时）时长，处理期间按三分之一周期续租
+    #[serde(default = "default_db_queue_lease_secs")]
+    pub lease_secs: u64,
+    /// 超过该尝试次数后任务标记为 failed，不再投递
+    #[serde(default = "default_db_queue_max_attempts")]
+    pub max_attempts: u32,
+    /// 失败重试延迟为 `retry_backoff_secs × 已尝试次数`
+    #[serde(default = "default_db_queue_retry_backoff_secs")]
+    pub retry_backoff_secs: u64,
+    /// 单个消费者同时处理的任务数
+    #[serde(default = "default_db_queue_max_inflight")]
+    pub max_inflight: usize,
+    /// 主节点进程内同时启动消费者
+    #[serde(default = "default_true")]
+    pub inline_worker: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -517,6 +776,22 @@ fn default_db_queue_poll_interval_ms() -> u64 {
     1_000
 }
 
+fn default_db_queue_lease_secs() -> u64 {
+    120
+}
+
+fn default_db_queue_max_attempts() -> u32 {
+    5
+}
+
+fn default_db_queue_retry_backoff_secs() -> u64 {
+    30
+}
+
+fn default_db_queue_max_inflight() -> usize {
+    4
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterNodeConfig {
     #[serde(default = "default_material_cache_dir")]
@@ -849,6 +1124,11 @@ impl Default for DatabaseQueueConfig {
             enabled: false,
             table_name: default_db_queue_table(),
             poll_interval_ms: default_db_queue_poll_interval_ms(),
+            lease_secs: default_db_queue_lease_secs(),
+            max_attempts: default_db_queue_max_attempts(),
+            retry_backoff_secs: default_db_queue_retry_backoff_secs(),
+            max_inflight: default_db_queue_max_inflight(),
+            inline_worker: true,
         }
     }
 }
@@ -1018,6 +1298,9 @@ pub struct DatabaseConfig {
     #[serde(default)]
     pub sqlite: Option<SqliteConfig>,
 
+    #[serde(default)]
+    pub postgres: Option<PostgresConfig>,
+
     #[serde(default)]
     pub go_gateway: Option<GoGatewayConfig>,
 }
@@ -1057,6 +1340,28 @@ pub struct SqliteConfig {
     pub path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostgresConfig {
+    pub host: String,
+    #[serde(default = "default_postgres_port")]
+    pub port: u16,
+    pub database: String,
+    pub username: String,
+    pub password: String,
+
+    #[serde(default = "default_dm_max_connections")]
+    pub max_connections: u32,
+    #[serde(default = "default_dm_connection_timeout")]
+    pub connection_timeout: u64,
+
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+fn default_postgres_port() -> u16 {
+    5432
+}
+
 fn default_dm_max_connections() -> u32 {
     10
 }
@@ -1298,6 +1603,16 @@ pub struct ThirdPartyClient {
     pub enabled: bool,
     #[serde(default)]
     pub permissions: Vec<String>,
+    /// 该客户端预审任务所在的优先级通道，未配置时使用 `task_queue.lanes.third_party_priority`
+    #[serde(default)]
+    pub queue_priority: Option<QueuePriority>,
+    /// 同一通道内与其他客户端公平出队的权重
+    #[serde(default = "default_client_queue_weight")]
+    pub queue_weight: u32,
+}
+
+fn default_client_queue_weight() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1661,20 +1976,471 @@ impl Default for OcrTuningConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OcrPoolConfig {
-    #[serde(default = "default_ocr_pool_max_engines")]
+    /// 0 表示按 CPU 核数与可用内存自动确定
+    #[serde(default)]
     pub max_engines: usize,
+    /// 自动确定容量时每个引擎预留的内存
+    #[serde(default = "default_ocr_engine_memory_mb")]
+    pub engine_memory_mb: u64,
+    /// 单个引擎 RSS 超过该值后退役，0 表示不限制
+    #[serde(default = "default_ocr_max_engine_rss_mb")]
+    pub max_engine_rss_mb: u64,
+    /// 单个引擎处理该数量请求后退役，0 表示不限制
+    #[serde(default = "default_ocr_max_requests_per_engine")]
+    pub max_requests_per_engine: u64,
+    /// 空闲超过该时长的引擎被回收，0 表示不回收
+    #[serde(default = "default_ocr_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    #[serde(default = "default_ocr_min_idle_engines")]
+    pub min_idle_engines: usize,
+    #[serde(default = "default_ocr_reap_interval_secs")]
+    pub reap_interval_secs: u64,
 }
 
 impl Default for OcrPoolConfig {
     fn default() -> Self {
         Self {
-            max_engines: default_ocr_pool_max_engines(),
+            max_engines: 0,
+            engine_memory_mb: default_ocr_engine_memory_mb(),
+            max_engine_rss_mb: default_ocr_max_engine_rss_mb(),
+            max_requests_per_engine: default_ocr_max_requests_per_engine(),
+            idle_timeout_secs: default_ocr_idle_timeout_secs(),
+            min_idle_engines: default_ocr_min_idle_engines(),
+            reap_interval_secs: default_ocr_reap_interval_secs(),
+        }
+    }
+}
+
+const fn default_ocr_engine_memory_mb() -> u64 {
+    512
+}
+
+const fn default_ocr_max_engine_rss_mb() -> u64 {
+    2048
+}
+
+const fn default_ocr_max_requests_per_engine() -> u64 {
+    5000
+}
+
+const fn default_ocr_idle_timeout_secs() -> u64 {
+    600
+}
+
+const fn default_ocr_min_idle_engines() -> usize {
+    1
+}
+
+const fn default_ocr_reap_interval_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrPreprocessConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 严格按顺序执行的步骤：exif_orientation | orientation | perspective | deskew | contrast；
+    /// 默认仅 EXIF 方向 + 对比度，纠偏等几何步骤需显式配置
+    #[serde(default = "default_preprocess_steps")]
+    pub steps: Vec<String>,
+    #[serde(default = "default_contrast_boost")]
+    pub contrast_boost: f32,
+    #[serde(default = "default_max_deskew_degrees")]
+    pub max_deskew_degrees: f32,
+    #[serde(default = "default_min_deskew_degrees")]
+    pub min_deskew_degrees: f32,
+    #[serde(default = "default_min_document_area_ratio")]
+    pub min_document_area_ratio: f32,
+    #[serde(default = "default_analysis_max_dimension")]
+    pub analysis_max_dimension: u32,
+    /// 识别前用 OCR 对四个方向的缩略图打分（每张图额外 1~4 次缩略图识别）
+    #[serde(default)]
+    pub ocr_orientation: bool,
+}
+
+fn default_preprocess_steps() -> Vec<String> {
+    ocr_conn::preprocess::PreprocessOptions::default()
+        .steps
+        .iter()
+        .map(|step| step.as_str().to_string())
+        .collect()
+}
+fn default_contrast_boost() -> f32 {
+    12.0
+}
+fn default_max_deskew_degrees() -> f32 {
+    15.0
+}
+fn default_min_deskew_degrees() -> f32 {
+    0.3
+}
+fn default_min_document_area_ratio() -> f32 {
+    0.25
+}
+fn default_analysis_max_dimension() -> u32 {
+    1000
+}
+
+impl Default for OcrPreprocessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            steps: default_preprocess_steps(),
+            contrast_boost: default_contrast_boost(),
+            max_deskew_degrees: default_max_deskew_degrees(),
+            min_deskew_degrees: default_min_deskew_degrees(),
+            min_document_area_ratio: default_min_document_area_ratio(),
+            analysis_max_dimension: default_analysis_max_dimension(),
+            ocr_orientation: false,
         }
     }
 }
 
-const fn default_ocr_pool_max_engines() -> usize {
-    6
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrCacheConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 引擎/模型版本标识，升级 PaddleOCR 模型后修改即可使旧缓存失效
+    #[serde(default = "default_ocr_cache_model_version")]
+    pub model_version: String,
+    #[serde(default = "default_ocr_cache_ttl_hours")]
+    pub ttl_hours: u64,
+    #[serde(default = "default_ocr_cache_max_size_mb")]
+    pub max_size_mb: u64,
+    #[serde(default = "default_ocr_cache_max_entries")]
+    pub max_entries: usize,
+    #[serde(default = "default_ocr_cache_max_entry_kb")]
+    pub max_entry_kb: u64,
+    #[serde(default = "default_ocr_cache_maintenance_interval_secs")]
+    pub maintenance_interval_secs: u64,
+}
+
+fn default_ocr_cache_model_version() -> String {
+    "paddleocr-json-v1".to_string()
+}
+fn default_ocr_cache_ttl_hours() -> u64 {
+    24 * 30
+}
+fn default_ocr_cache_max_size_mb() -> u64 {
+    1024
+}
+fn default_ocr_cache_max_entries() -> usize {
+    200_000
+}
+fn default_ocr_cache_max_entry_kb() -> u64 {
+    512
+}
+fn default_ocr_cache_maintenance_interval_secs() -> u64 {
+    3600
+}
+
+impl Default for OcrCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model_version: default_ocr_cache_model_version(),
+            ttl_hours: default_ocr_cache_ttl_hours(),
+            max_size_mb: default_ocr_cache_max_size_mb(),
+            max_entries: default_ocr_cache_max_entries(),
+            max_entry_kb: default_ocr_cache_max_entry_kb(),
+            maintenance_interval_secs: default_ocr_cache_maintenance_interval_secs(),
+        }
+    }
+}
+
+/// 预审产物留存策略：天数为空表示沿用上一级（顶层为空即永久保留），0 表示永久保留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_retention_interval_secs")]
+    pub interval_secs: u64,
+    /// 每类产物每轮最多处理的预审数
+    #[serde(default = "default_retention_batch_size")]
+    pub batch_size: u32,
+    #[serde(default)]
+    pub default: RetentionPolicy,
+    /// 按 matter_id 覆盖；日志不区分事项，其中的 log_days 不生效
+    #[serde(default)]
+    pub matters: HashMap<String, RetentionPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub raw_request_days: Option<u32>,
+    #[serde(default)]
+    pub ocr_text_days: Option<u32>,
+    #[serde(default)]
+    pub page_image_days: Option<u32>,
+    #[serde(default)]
+    pub report_days: Option<u32>,
+    #[serde(default)]
+    pub log_days: Option<u32>,
+}
+
+fn default_retention_interval_secs() -> u64 {
+    3600
+}
+fn default_retention_batch_size() -> u32 {
+    200
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_retention_interval_secs(),
+            batch_size: default_retention_batch_size(),
+            default: RetentionPolicy::default(),
+            matters: HashMap::new(),
+        }
+    }
+}
+
+/// 申请人敏感字段的落库加密；keys 中保留旧版本主密钥直到轮换完成
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiEncryptionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 新写入数据使用的主密钥版本
+    #[serde(default = "default_pii_key_version")]
+    pub key_version: String,
+    #[serde(default)]
+    pub keys: Vec<crate::util::crypto::EncryptionConfig>,
+    /// 盲索引 HMAC 密钥（十六进制），启用后不可更换
+    #[serde(default)]
+    pub blind_index_key: String,
+    /// 启动后在后台把旧版本密文与历史明文重新加密
+    #[serde(default = "default_true")]
+    pub rotate_on_startup: bool,
+    #[serde(default = "default_pii_rotation_batch_size")]
+    pub rotation_batch_size: u32,
+}
+
+fn default_pii_key_version() -> String {
+    "v1".to_string()
+}
+fn default_pii_rotation_batch_size() -> u32 {
+    200
+}
+
+impl Default for PiiEncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_version: default_pii_key_version(),
+            keys: Vec::new(),
+            blind_index_key: String::new(),
+            rotate_on_startup: true,
+            rotation_batch_size: default_pii_rotation_batch_size(),
+        }
+    }
+}
+
+/// 预审全文检索；索引中证件号、手机号已脱敏，但 OCR 文本为明文，
+/// 因此不能与 `pii_encryption` 同时开启
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 索引后端，目前支持 sqlite_fts
+    #[serde(default = "default_search_backend")]
+    pub backend: String,
+    #[serde(default = "default_search_sqlite_path")]
+    pub sqlite_path: String,
+    /// 主节点启动后补建索引中缺失的已结束预审
+    #[serde(default = "default_true")]
+    pub backfill_on_startup: bool,
+    #[serde(default = "default_search_max_results")]
+    pub max_results: u32,
+}
+
+fn default_search_backend() -> String {
+    "sqlite_fts".to_string()
+}
+fn default_search_sqlite_path() -> String {
+    "runtime/data/search.db".to_string()
+}
+fn default_search_max_results() -> u32 {
+    100
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: default_search_backend(),
+            sqlite_path: default_search_sqlite_path(),
+            backfill_on_startup: true,
+            max_results: default_search_max_results(),
+        }
+    }
+}
+
+/// 存储文件链接签名；报告、材料链接与本地存储预签名链接均按此签发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageSigningConfig {
+    /// HMAC 密钥，多节点部署（distributed 或 master/worker）必填且各节点一致；
+    /// 单机留空则每次启动随机生成
+    #[serde(default)]
+    pub secret: String,
+    /// 报告与材料链接有效期（秒）
+    #[serde(default = "default_storage_link_ttl_secs")]
+    pub link_ttl_secs: u64,
+    /// 仍允许访问未签名的旧链接，仅用于升级过渡
+    #[serde(default)]
+    pub allow_unsigned: bool,
+}
+
+fn default_storage_link_ttl_secs() -> u64 {
+    7 * 24 * 3600
+}
+
+impl Default for StorageSigningConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(),
+            link_ttl_secs: default_storage_link_ttl_secs(),
+            allow_unsigned: false,
+        }
+    }
+}
+
+/// 存储后端选择：auto 时配置了 zhzwdt-oss 则用 OSS，否则用本地存储
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageBackendConfig {
+    /// auto、local、oss、s3 或 tiered
+    #[serde(default = "default_storage_backend")]
+    pub backend: String,
+    #[serde(default = "default_storage_local_path")]
+    pub local_path: String,
+    #[serde(default)]
+    pub s3: S3StorageConfig,
+    #[serde(default)]
+    pub tiered: TieredStorageConfig,
+}
+
+fn default_storage_backend() -> String {
+    "auto".to_string()
+}
+fn default_storage_local_path() -> String {
+    "data/storage".to_string()
+}
+
+impl Default for StorageBackendConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_storage_backend(),
+            local_path: default_storage_local_path(),
+            s3: S3StorageConfig::default(),
+            tiered: TieredStorageConfig::default(),
+        }
+    }
+}
+
+/// S3 兼容对象存储（MinIO、Ceph RGW、华为 OBS 等）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct S3StorageConfig {
+    #[serde(default)]
+    pub endpoint: String,
+    /// MinIO 等不校验区域的服务可保持默认值
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub access_key_id: String,
+    #[serde(default)]
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub root: String,
+    /// 使用 `{bucket}.{endpoint}` 形式访问，华为 OBS 需开启
+    #[serde(default)]
+    pub virtual_host_style: bool,
+    #[serde(default)]
+    pub public_endpoint: Option<String>,
+}
+
+/// 分层存储：新写入对象放在本地热层，超过 migrate_after_hours 未修改的迁移到冷层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieredStorageConfig {
+    /// 冷层后端，oss 或 s3
+    #[serde(default = "default_tiered_cold")]
+    pub cold: String,
+    #[serde(default = "default_tiered_hot_path")]
+    pub hot_path: String,
+    #[serde(default = "default_tiered_migrate_after_hours")]
+    pub migrate_after_hours: u64,
+    /// 迁移扫描间隔（秒），0 表示不自动迁移
+    #[serde(default = "default_tiered_scan_interval_secs")]
+    pub scan_interval_secs: u64,
+    #[serde(default = "default_tiered_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_tiered_cold() -> String {
+    "s3".to_string()
+}
+fn default_tiered_hot_path() -> String {
+    "data/storage/hot".to_string()
+}
+fn default_tiered_migrate_after_hours() -> u64 {
+    72
+}
+fn default_tiered_scan_interval_secs() -> u64 {
+    3600
+}
+fn default_tiered_batch_size() -> usize {
+    200
+}
+
+impl Default for TieredStorageConfig {
+    fn default() -> Self {
+        Self {
+            cold: default_tiered_cold(),
+            hot_path: default_tiered_hot_path(),
+            migrate_after_hours: default_tiered_migrate_after_hours(),
+            scan_interval_secs: default_tiered_scan_interval_secs(),
+            batch_size: default_tiered_batch_size(),
+        }
+    }
+}
+
+/// 材料原件按 SHA-256 内容寻址存放，重复提交的相同附件只存一份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageDedupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 回收扫描间隔（秒），0 表示不回收；关闭去重后仍会回收已有的 blob
+    #[serde(default = "default_dedup_gc_interval_secs")]
+    pub gc_interval_secs: u64,
+    /// 无引用的 blob 至少保留的时长，须长于一次附件从上传到写入材料记录的耗时
+    #[serde(default = "default_dedup_gc_grace_secs")]
+    pub gc_grace_secs: u64,
+    #[serde(default = "default_dedup_gc_batch_size")]
+    pub gc_batch_size: u32,
+}
+
+fn default_dedup_gc_interval_secs() -> u64 {
+    3600
+}
+fn default_dedup_gc_grace_secs() -> u64 {
+    24 * 3600
+}
+fn default_dedup_gc_batch_size() -> u32 {
+    200
+}
+
+impl Default for StorageDedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gc_interval_secs: default_dedup_gc_interval_secs(),
+            gc_grace_secs: default_dedup_gc_grace_secs(),
+            gc_batch_size: default_dedup_gc_batch_size(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +8,7 @@ use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EncryptionConfig {
     pub encryption_key: String,
     pub key_version: String,
@@ -132,6 +133,10 @@ impl AesEncryption {
         Ok(plaintext)
     }
 
+    pub fn key_hex(&self) -> &str {
+        &self.config.encryption_key
+    }
+
     pub fn generate_key() -> String {
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -0,0 +1,233 @@
+//! 字段级信封加密
+//!
+//! 每个值使用随机数据密钥（DEK）加密，DEK 再由按版本管理的主密钥（KEK）包裹，
+//! 存储格式为 `pii:{kek_version}|{wrapped_dek}|{ciphertext}`。轮换主密钥时只需重新包裹 DEK。
+//! 不带前缀的值视为加密启用前写入的明文，读取时原样返回。
+//!
+//! 盲索引使用独立的 HMAC 密钥，与主密钥版本无关，更换后已有索引会失效。
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::aes::{AesEncryption, EncryptionConfig};
+
+const CIPHERTEXT_PREFIX: &str = "pii:";
+const BLIND_INDEX_PREFIX: &str = "bidx:";
+/// 盲索引保留的 HMAC 字节数
+const BLIND_INDEX_BYTES: usize = 16;
+
+pub struct PiiCipher {
+    current_version: String,
+    keys: HashMap<String, AesEncryption>,
+    blind_index_key: Vec<u8>,
+}
+
+impl PiiCipher {
+    pub fn new(
+        current_version: &str,
+        keys: &[EncryptionConfig],
+        blind_index_key: &str,
+    ) -> Result<Self> {
+        let mut keyring = HashMap::new();
+        for key in keys {
+            if key.key_version.contains('|') || key.key_version.is_empty() {
+                return Err(anyhow!("无效的密钥版本: {:?}", key.key_version));
+            }
+            keyring.insert(key.key_version.clone(), AesEncryption::new(key.clone())?);
+        }
+        if !keyring.contains_key(current_version) {
+            return Err(anyhow!("未配置当前密钥版本 {}", current_version));
+        }
+
+        let blind_index_key =
+            hex::decode(blind_index_key).map_err(|e| anyhow!("无效的盲索引密钥: {}", e))?;
+        if blind_index_key.len() < 32 {
+            return Err(anyhow!("盲索引密钥至少需要32字节"));
+        }
+
+        Ok(Self {
+            current_version: current_version.to_string(),
+            keys: keyring,
+            blind_index_key,
+        })
+    }
+
+    pub fn current_version(&self) -> &str {
+        &self.current_version
+    }
+
+    /// 某版本密文的固定前缀
+    pub fn ciphertext_prefix(version: &str) -> String {
+        format!("{}{}|", CIPHERTEXT_PREFIX, version)
+    }
+
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(CIPHERTEXT_PREFIX)
+    }
+
+    pub fn is_blind_index(value: &str) -> bool {
+        value.starts_with(BLIND_INDEX_PREFIX)
+    }
+
+    /// 返回密文使用的主密钥版本，明文返回 None
+    pub fn key_version_of(value: &str) -> Option<&str> {
+        value
+            .strip_prefix(CIPHERTEXT_PREFIX)
+            .and_then(|rest| rest.split('|').next())
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        if plaintext.is_empty() {
+            return Ok(plaintext.to_string());
+        }
+
+        let dek = AesEncryption::new(EncryptionConfig {
+            encryption_key: AesEncryption::generate_key(),
+            key_version: "dek".to_string(),
+            ..Default::default()
+        })?;
+        let data = dek.encrypt(plaintext)?;
+        let data = data.strip_prefix("dek|").unwrap_or(&data);
+        let wrapped = self.kek(&self.current_version)?.encrypt(dek.key_hex())?;
+
+        Ok(format!("{}{}|{}", CIPHERTEXT_PREFIX, wrapped, data))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some((version, wrapped, data)) = Self::split(value)? else {
+            return Ok(value.to_string());
+        };
+
+        let dek_hex = self
+            .kek(version)?
+            .decrypt(&format!("{}|{}", version, wrapped))?;
+        let dek = AesEncryption::new(EncryptionConfig {
+            encryption_key: dek_hex,
+            key_version: "dek".to_string(),
+            ..Default::default()
+        })?;
+        dek.decrypt(&format!("dek|{}", data))
+    }
+
+    /// 用当前主密钥重新包裹 DEK；明文会被加密。已是当前版本时返回 None
+    pub fn rewrap(&self, value: &str) -> Result<Option<String>> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let Some((version, wrapped, data)) = Self::split(value)? else {
+            return self.encrypt(value).map(Some);
+        };
+        if version == self.current_version {
+            return Ok(None);
+        }
+
+        let dek_hex = self
+            .kek(version)?
+            .decrypt(&format!("{}|{}", version, wrapped))?;
+        let wrapped = self.kek(&self.current_version)?.encrypt(&dek_hex)?;
+        Ok(Some(format!("{}{}|{}", CIPHERTEXT_PREFIX, wrapped, data)))
+    }
+
+    /// 确定性的查询令牌，用于加密字段的等值查询
+    pub fn blind_index(&self, value: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.blind_index_key).expect("HMAC 接受任意长度密钥");
+        mac.update(value.trim().as_bytes());
+        let digest = mac.finalize().into_bytes();
+        format!(
+            "{}{}",
+            BLIND_INDEX_PREFIX,
+            hex::encode(&digest[..BLIND_INDEX_BYTES])
+        )
+    }
+
+    fn kek(&self, version: &str) -> Result<&AesEncryption> {
+        self.keys
+            .get(version)
+            .ok_or_else(|| anyhow!("未配置密钥版本 {}", version))
+    }
+
+    fn split(value: &str) -> Result<Option<(&str, &str, &str)>> {
+        let Some(rest) = value.strip_prefix(CIPHERTEXT_PREFIX) else {
+            return Ok(None);
+        };
+        let mut parts = rest.splitn(3, '|');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(version), Some(wrapped), Some(data)) => {
+                general_purpose::STANDARD
+                    .decode(wrapped)
+                    .map_err(|e| anyhow!("解码包裹密钥失败: {}", e))?;
+                Ok(Some((version, wrapped, data)))
+            }
+            _ => Err(anyhow!("无效的字段密文格式")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(version: &str) -> EncryptionConfig {
+        EncryptionConfig {
+            encryption_key: AesEncryption::generate_key(),
+            key_version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trip_and_rotation_rewraps_only_the_data_key() {
+        let bidx = AesEncryption::generate_key();
+        let (v1, v2) = (key("v1"), key("v2"));
+        let old = PiiCipher::new("v1", &[v1.clone()], &bidx).unwrap();
+        let new = PiiCipher::new("v2", &[v1, v2], &bidx).unwrap();
+
+        let id_number = "330102199001011234";
+        let encrypted = old.encrypt(id_number).unwrap();
+        assert!(!encrypted.contains(id_number));
+        assert_eq!(PiiCipher::key_version_of(&encrypted), Some("v1"));
+        assert_eq!(new.decrypt(&encrypted).unwrap(), id_number);
+
+        let rotated = new.rewrap(&encrypted).unwrap().unwrap();
+        assert_eq!(PiiCipher::key_version_of(&rotated), Some("v2"));
+        assert_eq!(
+            rotated.rsplit('|').next(),
+            encrypted.rsplit('|').next(),
+            "数据密文不应重新计算"
+        );
+        assert_eq!(new.decrypt(&rotated).unwrap(), id_number);
+        assert!(new.rewrap(&rotated).unwrap().is_none());
+        assert!(old.decrypt(&rotated).is_err());
+    }
+
+    #[test]
+    fn legacy_plaintext_passes_through_and_blind_index_is_stable() {
+        let bidx = AesEncryption::generate_key();
+        let cipher = PiiCipher::new("v1", &[key("v1")], &bidx).unwrap();
+
+        assert_eq!(cipher.decrypt("明文").unwrap(), "明文");
+        assert!(PiiCipher::is_encrypted(
+            &cipher.rewrap("明文").unwrap().unwrap()
+        ));
+        assert_eq!(cipher.encrypt("").unwrap(), "");
+
+        let token = cipher.blind_index("REQ-001");
+        assert!(PiiCipher::is_blind_index(&token));
+        assert_eq!(token, cipher.blind_index(" REQ-001 "));
+        assert_ne!(token, cipher.blind_index("REQ-002"));
+        let other = PiiCipher::new("v1", &[key("v1")], &AesEncryption::generate_key()).unwrap();
+        assert_ne!(token, other.blind_index("REQ-001"));
+    }
+
+    #[test]
+    fn rejects_missing_current_key() {
+        let bidx = AesEncryption::generate_key();
+        assert!(PiiCipher::new("v2", &[key("v1")], &bidx).is_err());
+        assert!(PiiCipher::new("v1", &[key("v1")], "abcd").is_err());
+    }
+}
@@ -1,4 +1,5 @@
-
 pub mod aes;
+pub mod envelope;
 
 pub use aes::{AesEncryption, EncryptionConfig};
+pub use envelope::PiiCipher;
@@ -0,0 +1,395 @@
+//! 死信：重试耗尽或载荷无法解析的预审任务统一落库，供监控端查看、修改后重新入队或丢弃
+//!
+//! - 本地队列与数据库队列由消费者直接写入数据库
+//! - NATS worker 通常不连数据库，先将死信发布到 `{subject}.dead`，由主节点消费落库
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_nats::jetstream::{self, consumer, stream, AckKind};
+use chrono::Utc;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::db::traits::{DeadLetterRecord, DeadLetterStatus};
+use crate::db::{Database, PreviewStatus};
+use crate::util::config::types::NatsQueueConfig;
+use crate::util::task_queue::{PreviewTask, TaskQueue};
+use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
+
+pub const SOURCE_LOCAL: &str = "local";
+pub const SOURCE_NATS: &str = "nats";
+pub const SOURCE_DATABASE: &str = "database";
+
+pub const REASON_MAX_ATTEMPTS: &str = "max_attempts_exceeded";
+pub const REASON_INVALID_PAYLOAD: &str = "invalid_payload";
+
+/// 载荷无法解析且取不到 preview_id 时使用的占位 id 前缀
+pub const PLACEHOLDER_PREVIEW_PREFIX: &str = "invalid-payload-";
+
+/// 死信流保留时长，主节点长时间离线时避免无限堆积
+const NATS_DLQ_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// 为空时自动生成；数据库队列使用原任务 id，重复写入不会产生多条记录
+    #[serde(default)]
+    pub id: Option<String>,
+    pub preview_id: String,
+    pub queue: String,
+    pub source: String,
+    pub payload: String,
+    pub failure_reason: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+impl DeadLetter {
+    pub fn from_task(
+        task: &PreviewTask,
+        queue: &str,
+        source: &str,
+        attempts: i32,
+        last_error: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            preview_id: task.preview_id.clone(),
+            queue: queue.to_string(),
+            source: source.to_string(),
+            payload: serde_json::to_string(task).unwrap_or_default(),
+            failure_reason: REASON_MAX_ATTEMPTS.to_string(),
+            attempts,
+            last_error: Some(last_error.into()),
+        }
+    }
+
+    /// 载荷无法解析为预审任务时，尽量从原始 JSON 中取出 preview_id，取不到则生成占位 id
+    pub fn invalid_payload(
+        payload: &[u8],
+        queue: &str,
+        source: &str,
+        error: impl Into<String>,
+    ) -> Self {
+        let preview_id = serde_json::from_slice::<serde_json::Value>(payload)
+            .ok()
+            .and_then(|value| {
+                value
+                    .get("preview_id")
+                    .and_then(|id| id.as_str())
+                    .map(|id| id.trim().to_string())
+            })
+            .unwrap_or_default();
+        Self {
+            id: None,
+            preview_id,
+            queue: queue.to_string(),
+            source: source.to_string(),
+            payload: String::from_utf8_lossy(payload).into_owned(),
+            failure_reason: REASON_INVALID_PAYLOAD.to_string(),
+            attempts: 1,
+            last_error: Some(error.into()),
+        }
+    }
+
+    fn into_record(self) -> DeadLetterRecord {
+        let now = Utc::now();
+        let id = self.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        // 兼容旧版 worker 发布的空 preview_id，保证死信可定位、可重新入队
+        let preview_id = if self.preview_id.trim().is_empty() {
+            format!("{}{}", PLACEHOLDER_PREVIEW_PREFIX, id)
+        } else {
+            self.preview_id
+        };
+        DeadLetterRecord {
+            id,
+            preview_id,
+            queue: self.queue,
+            source: self.source,
+            payload: self.payload,
+            failure_reason: self.failure_reason,
+            attempts: self.attempts,
+            last_error: self.last_error,
+            status: DeadLetterStatus::Dead,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+pub async fn record(database: &Arc<dyn Database>, letter: DeadLetter) -> Result<String> {
+    let record = letter.into_record();
+    database
+        .save_dead_letter(&record)
+        .await
+        .context("写入死信失败")?;
+    METRICS_COLLECTOR.record_queue_dead_letter(&record.queue, &record.source);
+    warn!(
+        dead_letter_id = %record.id,
+        preview_id = %record.preview_id,
+        source = %record.source,
+        reason = %record.failure_reason,
+        attempts = record.attempts,
+        "预审任务已转入死信"
+    );
+    Ok(record.id)
+}
+
+/// 重新入队死信；`edited_payload` 非空时替换原载荷，但不允许修改 preview_id，
+/// 占位 id 的死信除外，其载荷须修改为有效任务后才能入队
+pub async fn requeue(
+    database: &Arc<dyn Database>,
+    task_queue: &Arc<dyn TaskQueue>,
+    id: &str,
+    edited_payload: Option<serde_json::Value>,
+    operator: &str,
+) -> Result<DeadLetterRecord> {
+    let record = database
+        .get_dead_letter(id)
+        .await?
+        .ok_or_else(|| anyhow!("死信不存在: {}", id))?;
+    if record.status != DeadLetterStatus::Dead {
+        return Err(anyhow!("死信已处理，当前状态: {}", record.status.as_str()));
+    }
+
+    let edited = edited_payload
+        .map(|value| serde_json::to_string(&value))
+        .transpose()
+        .context("序列化修改后的载荷失败")?;
+    let payload = edited.as_deref().unwrap_or(&record.payload);
+    let task: PreviewTask = serde_json::from_str(payload).context("载荷不是有效的预审任务")?;
+    let placeholder = record.preview_id.starts_with(PLACEHOLDER_PREVIEW_PREFIX);
+    if placeholder && task.preview_id.trim().is_empty() {
+        return Err(anyhow!("载荷缺少 preview_id"));
+    }
+    if !placeholder && task.preview_id != record.preview_id {
+        return Err(anyhow!(
+            "载荷中的 preview_id 与死信不一致: {} != {}",
+            task.preview_id,
+            record.preview_id
+        ));
+    }
+
+    // 先抢占状态，避免并发请求重复入队
+    if !database
+        .resolve_dead_letter(id, DeadLetterStatus::Requeued, edited.as_deref(), operator)
+        .await?
+    {
+        return Err(anyhow!("死信已被其他请求处理"));
+    }
+
+    if let Err(err) = database.save_task_payload(&task.preview_id, payload).await {
+        warn!(preview_id = %task.preview_id, error = %err, "重新入队时保存任务payload失败");
+    }
+    let request_key = Some(task.third_party_request_id.trim())
+        .filter(|key| !key.is_empty())
+        .unwrap_or(&task.preview_id)
+        .to_string();
+    let preview_id = task.preview_id.clone();
+
+    if let Err(err) = task_queue.enqueue(task).await {
+        // 入队失败时恢复为 dead，便于稍后再次处理
+        let mut restored = record.clone();
+        restored.updated_at = Utc::now();
+        if let Err(restore_err) = database.save_dead_letter(&restored).await {
+            error!(dead_letter_id = %id, error = %restore_err, "恢复死信状态失败");
+        }
+        return Err(err.context("死信重新入队失败"));
+    }
+
+    let status = PreviewStatus::Queued;
+    if let Err(err) = database
+        .update_preview_status(&preview_id, status.clone())
+        .await
+    {
+        warn!(preview_id = %preview_id, error = %err, "重新入队后更新预审状态失败");
+    } else {
+        // 索引中仍是失败状态，随重新入队刷新
+        crate::util::search::schedule_index(Arc::clone(database), preview_id.clone());
+        if let Err(err) = database
+            .update_preview_request_latest(&request_key, Some(&preview_id), Some(status))
+            .await
+        {
+            warn!(preview_id = %preview_id, error = %err, "同步预审请求状态失败");
+        }
+    }
+
+    info!(
+        dead_letter_id = %id,
+        preview_id = %preview_id,
+        operator = %operator,
+        edited = edited.is_some(),
+        "死信已重新入队"
+    );
+    database
+        .get_dead_letter(id)
+        .await?
+        .ok_or_else(|| anyhow!("死信不存在: {}", id))
+}
+
+pub async fn discard(database: &Arc<dyn Database>, id: &str, operator: &str) -> Result<bool> {
+    let discarded = database
+        .resolve_dead_letter(id, DeadLetterStatus::Discarded, None, operator)
+        .await?;
+    if discarded {
+        info!(dead_letter_id = %id, operator = %operator, "死信已丢弃");
+    }
+    Ok(discarded)
+}
+
+fn nats_dlq_stream(config: &NatsQueueConfig) -> String {
+    format!("{}_DLQ", config.stream)
+}
+
+fn nats_dlq_subject(config: &NatsQueueConfig) -> String {
+    format!("{}.dead", config.subject)
+}
+
+fn build_dlq_stream_config(config: &NatsQueueConfig) -> stream::Config {
+    stream::Config {
+        name: nats_dlq_stream(config),
+        subjects: vec![nats_dlq_subject(config)],
+        retention: stream::RetentionPolicy::WorkQueue,
+        max_age: NATS_DLQ_MAX_AGE,
+        ..Default::default()
+    }
+}
+
+/// worker 侧：将死信发布到死信流
+pub async fn publish_nats(
+    context: &jetstream::Context,
+    config: &NatsQueueConfig,
+    letter: &DeadLetter,
+) -> Result<()> {
+    context
+        .get_or_create_stream(build_dlq_stream_config(config))
+        .await
+        .context("创建/获取死信流失败")?;
+    let payload = serde_json::to_vec(letter).context("序列化死信失败")?;
+    context
+        .publish(nats_dlq_subject(config), payload.into())
+        .await
+        .context("发布死信失败")?
+        .await
+        .context("等待死信确认失败")?;
+    METRICS_COLLECTOR.record_queue_dead_letter(&letter.queue, &letter.source);
+    Ok(())
+}
+
+/// 主节点侧：持续消费死信流并落库，落库失败的消息等待重投
+pub async fn run_nats_ingester(
+    context: jetstream::Context,
+    config: NatsQueueConfig,
+    database: Arc<dyn Database>,
+) -> Result<()> {
+    let durable = format!("{}-dlq-ingest", config.durable_consumer);
+    loop {
+        let stream = context
+            .get_or_create_stream(build_dlq_stream_config(&config))
+            .await
+            .context("创建/获取死信流失败")?;
+        let consumer = stream
+            .get_or_create_consumer(
+                &durable,
+                consumer::pull::Config {
+                    durable_name: Some(durable.clone()),
+                    ack_policy: consumer::AckPolicy::Explicit,
+                    ack_wait: Duration::from_millis(config.ack_wait_ms),
+                    ..Default::default()
+                },
+            )
+            .await
+            .with_context(|| format!("创建/获取死信消费者 [{}] 失败", durable))?;
+        let mut messages = consumer
+            .stream()
+            .expires(Duration::from_millis(config.pull_wait_ms))
+            .messages()
+            .await
+            .context("获取死信消息流失败")?;
+
+        info!(stream = %nats_dlq_stream(&config), consumer = %durable, "死信落库消费者已启动");
+        while let Some(item) = messages.next().await {
+            let message = match item {
+                Ok(message) => message,
+                Err(err) => {
+                    warn!("拉取死信消息失败: {:#}", err);
+                    break;
+                }
+            };
+            let letter = match serde_json::from_slice::<DeadLetter>(&message.payload) {
+                Ok(letter) => letter,
+                Err(err) => {
+                    error!("死信消息格式错误，丢弃: {:#}", err);
+                    let _ = message.ack_with(AckKind::Term).await;
+                    continue;
+                }
+            };
+            // 计数已在 worker 发布时记录，这里直接落库
+            let record = letter.into_record();
+            match database.save_dead_letter(&record).await {
+                Ok(()) => {
+                    info!(
+                        dead_letter_id = %record.id,
+                        preview_id = %record.preview_id,
+                        "NATS 死信已落库"
+                    );
+                    if let Err(err) = message.ack().await {
+                        warn!(preview_id = %record.preview_id, error = %err, "死信 ACK 失败");
+                    }
+                }
+                Err(err) => {
+                    warn!(preview_id = %record.preview_id, error = %err, "死信落库失败，等待重投");
+                    let _ = message
+                        .ack_with(AckKind::Nak(Some(Duration::from_secs(30))))
+                        .await;
+                }
+            }
+        }
+
+        sleep(Duration::from_millis(config.pull_wait_ms.max(500))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::preview::PreviewBody;
+
+    #[test]
+    fn dead_letter_keeps_task_payload() {
+        let task = PreviewTask::new(PreviewBody::default(), "p1".to_string(), "r1".to_string());
+        let letter = DeadLetter::from_task(&task, "preview", SOURCE_LOCAL, 3, "boom");
+        let record = letter.into_record();
+
+        assert_eq!(record.status, DeadLetterStatus::Dead);
+        assert_eq!(record.failure_reason, REASON_MAX_ATTEMPTS);
+        let restored: PreviewTask = serde_json::from_str(&record.payload).unwrap();
+        assert_eq!(restored.preview_id, "p1");
+        assert_eq!(restored.third_party_request_id, "r1");
+    }
+
+    #[test]
+    fn invalid_payload_gets_a_usable_preview_id() {
+        let letter = DeadLetter::invalid_payload(
+            br#"{"preview_id":"p9","preview":42}"#,
+            "preview",
+            SOURCE_NATS,
+            "bad body",
+        );
+        assert_eq!(letter.preview_id, "p9");
+        assert_eq!(letter.into_record().failure_reason, REASON_INVALID_PAYLOAD);
+
+        let record = DeadLetter::invalid_payload(b"not json", "preview", SOURCE_NATS, "bad json")
+            .into_record();
+        assert_eq!(
+            record.preview_id,
+            format!("{}{}", PLACEHOLDER_PREVIEW_PREFIX, record.id)
+        );
+        assert_eq!(record.payload, "not json");
+    }
+}
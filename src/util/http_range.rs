@@ -0,0 +1,134 @@
+//! HTTP Range 请求处理
+//!
+//! 只支持单段 `bytes=` 范围；多段或格式错误的请求按 RFC 9110 忽略，返回完整内容。
+
+use std::ops::Range;
+
+use axum::body::Body;
+use axum::http::{header, response::Builder, Response, StatusCode};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeRequest {
+    Full,
+    /// 半开区间，已按内容长度裁剪
+    Partial(Range<u64>),
+    Unsatisfiable,
+}
+
+impl RangeRequest {
+    pub fn parse(value: Option<&str>, size: u64) -> Self {
+        let Some(spec) = value.and_then(|v| v.trim().strip_prefix("bytes=")) else {
+            return Self::Full;
+        };
+        if spec.contains(',') {
+            return Self::Full;
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+
+        let (start, end) = match (start.trim(), end.trim()) {
+            ("", "") => return Self::Full,
+            ("", suffix) => match suffix.parse::<u64>() {
+                Ok(0) => return Self::Unsatisfiable,
+                Ok(len) => (size.saturating_sub(len), size),
+                Err(_) => return Self::Full,
+            },
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Self::Full;
+                };
+                let end = if end.is_empty() {
+                    size
+                } else {
+                    match end.parse::<u64>() {
+                        Ok(end) if end >= start => end.saturating_add(1).min(size),
+                        _ => return Self::Full,
+                    }
+                };
+                (start, end)
+            }
+        };
+
+        if start >= size {
+            return Self::Unsatisfiable;
+        }
+        Self::Partial(start..end)
+    }
+
+    /// 交给存储层的读取范围
+    pub fn as_range(&self) -> Option<Range<u64>> {
+        match self {
+            Self::Partial(range) => Some(range.clone()),
+            _ => None,
+        }
+    }
+
+    /// 带状态码、`Content-Length`、`Accept-Ranges` 与 `Content-Range` 的响应
+    pub fn response(&self, size: u64) -> Builder {
+        let builder = Response::builder().header(header::ACCEPT_RANGES, "bytes");
+        match self {
+            Self::Full => builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, size),
+            Self::Partial(range) => builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_LENGTH, range.end - range.start)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end - 1, size),
+                ),
+            Self::Unsatisfiable => builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", size)),
+        }
+    }
+}
+
+pub fn stream_body<R>(reader: R) -> Body
+where
+    R: AsyncRead + Send + 'static,
+{
+    Body::from_stream(ReaderStream::new(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_byte_ranges() {
+        let parse = |v| RangeRequest::parse(Some(v), 1000);
+
+        assert_eq!(parse("bytes=0-99"), RangeRequest::Partial(0..100));
+        assert_eq!(parse("bytes=900-"), RangeRequest::Partial(900..1000));
+        assert_eq!(parse("bytes=-100"), RangeRequest::Partial(900..1000));
+        assert_eq!(parse("bytes=-5000"), RangeRequest::Partial(0..1000));
+        assert_eq!(parse("bytes=990-2000"), RangeRequest::Partial(990..1000));
+        assert_eq!(parse("bytes=1000-"), RangeRequest::Unsatisfiable);
+        assert_eq!(parse("bytes=-0"), RangeRequest::Unsatisfiable);
+
+        assert_eq!(parse("bytes=0-1,5-9"), RangeRequest::Full);
+        assert_eq!(parse("bytes=9-1"), RangeRequest::Full);
+        assert_eq!(parse("items=0-1"), RangeRequest::Full);
+        assert_eq!(RangeRequest::parse(None, 1000), RangeRequest::Full);
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=0-"), 0),
+            RangeRequest::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn partial_response_sets_content_range() {
+        let response = RangeRequest::Partial(10..20)
+            .response(100)
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "10");
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 10-19/100");
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+    }
+}
@@ -0,0 +1,410 @@
+//! 预审任务的优先级通道与按第三方客户端的加权公平调度
+//!
+//! 通道之间、同一通道内的客户端之间均使用平滑加权轮询：每次出队为候选项累加权重，
+//! 选出累计值最大者并扣除本轮总权重。单个客户端的批量导入只占用其权重对应的份额。
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::util::config::types::{PriorityLanesConfig, QueuePriority, ThirdPartyClient};
+
+/// 无第三方客户端身份的任务（门户提交、补偿重放等）归入的公平分组
+pub const INTERACTIVE_CLIENT: &str = "interactive";
+
+/// 通道权重与客户端权重，进程启动时由配置构建
+#[derive(Debug, Clone)]
+pub struct LanePolicy {
+    lanes: PriorityLanesConfig,
+    clients: HashMap<String, ClientLane>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClientLane {
+    priority: Option<QueuePriority>,
+    weight: u32,
+}
+
+impl Default for LanePolicy {
+    fn default() -> Self {
+        Self::new(PriorityLanesConfig::default(), &[])
+    }
+}
+
+impl LanePolicy {
+    pub fn new(lanes: PriorityLanesConfig, clients: &[ThirdPartyClient]) -> Self {
+        let clients = clients
+            .iter()
+            .map(|client| {
+                (
+                    client.client_id.clone(),
+                    ClientLane {
+                        priority: client.queue_priority,
+                        weight: client.queue_weight,
+                    },
+                )
+            })
+            .collect();
+        Self { lanes, clients }
+    }
+
+    pub fn from_global_config() -> Self {
+        Self::new(
+            crate::CONFIG.task_queue.lanes.clone(),
+            &crate::CONFIG.third_party_access.clients,
+        )
+    }
+
+    /// 无客户端身份视为交互式请求
+    pub fn resolve_priority(&self, client_id: Option<&str>) -> QueuePriority {
+        match client_id {
+            None => self.lanes.interactive_priority,
+            Some(id) => self
+                .clients
+                .get(id)
+                .and_then(|client| client.priority)
+                .unwrap_or(self.lanes.third_party_priority),
+        }
+    }
+
+    pub fn is_known_client(&self, client_id: &str) -> bool {
+        self.clients.contains_key(client_id)
+    }
+
+    pub fn lane_weight(&self, priority: QueuePriority) -> u32 {
+        self.lanes.weight(priority)
+    }
+
+    pub fn client_weight(&self, client_key: &str) -> u32 {
+        self.clients
+            .get(client_key)
+            .map(|client| client.weight)
+            .unwrap_or(1)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LaneDepth {
+    pub lane: QueuePriority,
+    pub depth: u64,
+    /// 有排队任务的客户端数；NATS 通道无法得知时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clients: Option<usize>,
+}
+
+/// 权重为 0 时按 1 参与累加，但仅在没有其他候选项时才可能被选中
+#[derive(Debug, Default, Clone, Copy)]
+struct Credit {
+    current: i64,
+}
+
+/// 在候选项中执行一轮平滑加权轮询，返回选中项的下标
+fn smooth_pick(candidates: &mut [(u32, &mut Credit)]) -> Option<usize> {
+    if candidates.is_empty() {
+        return None;
+    }
+    let positive = candidates.iter().any(|(weight, _)| *weight > 0);
+    let mut total = 0i64;
+    let mut picked = 0usize;
+    let mut best = i64::MIN;
+    for (index, (weight, credit)) in candidates.iter_mut().enumerate() {
+        if positive && *weight == 0 {
+            continue;
+        }
+        let weight = i64::from((*weight).max(1));
+        credit.current += weight;
+        total += weight;
+        if credit.current > best {
+            best = credit.current;
+            picked = index;
+        }
+    }
+    candidates[picked].1.current -= total;
+    Some(picked)
+}
+
+struct ClientQueue<T> {
+    credit: Credit,
+    items: VecDeque<T>,
+}
+
+struct Lane<T> {
+    priority: QueuePriority,
+    credit: Credit,
+    clients: BTreeMap<String, ClientQueue<T>>,
+    len: usize,
+}
+
+impl<T> Lane<T> {
+    fn pop(&mut self, policy: &LanePolicy) -> Option<T> {
+        let mut candidates: Vec<(u32, &mut Credit)> = Vec::with_capacity(self.clients.len());
+        let mut keys = Vec::with_capacity(self.clients.len());
+        for (key, queue) in self.clients.iter_mut() {
+            candidates.push((policy.client_weight(key), &mut queue.credit));
+            keys.push(key.clone());
+        }
+        let index = smooth_pick(&mut candidates)?;
+        let key = &keys[index];
+
+        let queue = self.clients.get_mut(key)?;
+        let item = queue.items.pop_front();
+        // 清空后移除，避免空闲客户端积累额度
+        if queue.items.is_empty() {
+            self.clients.remove(key);
+        }
+        if item.is_some() {
+            self.len -= 1;
+        }
+        item
+    }
+}
+
+/// 内存中的多通道公平队列；不做并发控制，由调用方加锁
+pub struct FairScheduler<T> {
+    policy: LanePolicy,
+    lanes: Vec<Lane<T>>,
+    len: usize,
+}
+
+impl<T> FairScheduler<T> {
+    pub fn new(policy: LanePolicy) -> Self {
+        let lanes = QueuePriority::ALL
+            .iter()
+            .map(|priority| Lane {
+                priority: *priority,
+                credit: Credit::default(),
+                clients: BTreeMap::new(),
+                len: 0,
+            })
+            .collect();
+        Self {
+            policy,
+            lanes,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, priority: QueuePriority, client_key: &str, item: T) -> Result<()> {
+        let lane = self
+            .lanes
+            .iter_mut()
+            .find(|lane| lane.priority == priority)
+            .ok_or_else(|| anyhow!("优先级通道 {} 不存在", priority.as_str()))?;
+        lane.clients
+            .entry(client_key.to_string())
+            .or_insert_with(|| ClientQueue {
+                credit: Credit::default(),
+                items: VecDeque::new(),
+            })
+            .items
+            .push_back(item);
+        lane.len += 1;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let policy = &self.policy;
+        let mut candidates: Vec<(u32, &mut Credit)> = Vec::with_capacity(self.lanes.len());
+        let mut indexes = Vec::with_capacity(self.lanes.len());
+        for (index, lane) in self.lanes.iter_mut().enumerate() {
+            if lane.len > 0 {
+                candidates.push((policy.lane_weight(lane.priority), &mut lane.credit));
+                indexes.push(index);
+            }
+        }
+        let picked = indexes[smooth_pick(&mut candidates)?];
+
+        let lane = &mut self.lanes[picked];
+        let item = lane.pop(policy);
+        if lane.len == 0 {
+            lane.credit = Credit::default();
+        }
+        if item.is_some() {
+            self.len -= 1;
+        }
+        item
+    }
+
+    /// 移除不满足条件的排队项，返回移除数量；不影响各通道与客户端的累计额度
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) -> usize {
+        let mut removed = 0;
+        for lane in self.lanes.iter_mut() {
+            lane.clients.retain(|_, queue| {
+                let before = queue.items.len();
+                queue.items.retain(&mut keep);
+                let dropped = before - queue.items.len();
+                lane.len -= dropped;
+                removed += dropped;
+                !queue.items.is_empty()
+            });
+            if lane.len == 0 {
+                lane.credit = Credit::default();
+            }
+        }
+        self.len -= removed;
+        removed
+    }
+
+    /// 按通道、客户端顺序遍历排队项，不改变出队顺序
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.lanes
+            .iter()
+            .flat_map(|lane| lane.clients.values())
+            .flat_map(|queue| queue.items.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn depths(&self) -> Vec<LaneDepth> {
+        self.lanes
+            .iter()
+            .map(|lane| LaneDepth {
+                lane: lane.priority,
+                depth: lane.len as u64,
+                clients: Some(lane.clients.len()),
+            })
+            .collect()
+    }
+}
+
+/// 仅在通道之间轮询，用于只能按通道批量拉取的驱动（NATS）
+pub struct LaneRotation {
+    policy: LanePolicy,
+    credits: [Credit; 3],
+}
+
+impl LaneRotation {
+    pub fn new(policy: LanePolicy) -> Self {
+        Self {
+            policy,
+            credits: [Credit::default(); 3],
+        }
+    }
+
+    /// 本轮的通道尝试顺序：轮询选中的通道在前，其余按优先级依次兜底
+    pub fn next_order(&mut self) -> [QueuePriority; 3] {
+        let policy = &self.policy;
+        let mut candidates: Vec<(u32, &mut Credit)> = QueuePriority::ALL
+            .iter()
+            .zip(self.credits.iter_mut())
+            .map(|(priority, credit)| (policy.lane_weight(*priority), credit))
+            .collect();
+        let first = QueuePriority::ALL[smooth_pick(&mut candidates).unwrap_or(0)];
+
+        let mut order = [first; 3];
+        let mut slot = 1;
+        for priority in QueuePriority::ALL {
+            if priority != first {
+                order[slot] = priority;
+                slot += 1;
+            }
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(id: &str, priority: Option<QueuePriority>, weight: u32) -> ThirdPartyClient {
+        ThirdPartyClient {
+            client_id: id.to_string(),
+            secret_key: String::new(),
+            name: id.to_string(),
+            source_type: "direct_api".to_string(),
+            enabled: true,
+            permissions: vec![],
+            queue_priority: priority,
+            queue_weight: weight,
+        }
+    }
+
+    #[test]
+    fn resolves_priority_from_client_config() {
+        let policy = LanePolicy::new(
+            PriorityLanesConfig::default(),
+            &[client("batch", Some(QueuePriority::Low), 1)],
+        );
+        assert_eq!(policy.resolve_priority(None), QueuePriority::High);
+        assert_eq!(policy.resolve_priority(Some("batch")), QueuePriority::Low);
+        assert_eq!(
+            policy.resolve_priority(Some("other")),
+            QueuePriority::Normal
+        );
+    }
+
+    #[test]
+    fn lanes_share_by_weight_without_starvation() {
+        let mut scheduler = FairScheduler::new(LanePolicy::default());
+        for i in 0..40 {
+            scheduler
+                .push(QueuePriority::High, INTERACTIVE_CLIENT, ("high", i))
+                .unwrap();
+            scheduler
+                .push(QueuePriority::Low, "batch", ("low", i))
+                .unwrap();
+        }
+        let first: Vec<_> = (0..9).filter_map(|_| scheduler.pop()).collect();
+        let low = first.iter().filter(|(lane, _)| *lane == "low").count();
+        assert_eq!(low, 1, "8:1 权重下每 9 个任务出队 1 个低优先级任务");
+        assert_eq!(scheduler.len(), 71);
+    }
+
+    #[test]
+    fn clients_in_same_lane_alternate() {
+        let policy = LanePolicy::new(
+            PriorityLanesConfig::default(),
+            &[client("bulk", None, 1), client("portal-proxy", None, 1)],
+        );
+        let mut scheduler = FairScheduler::new(policy);
+        for i in 0..10 {
+            scheduler
+                .push(QueuePriority::Normal, "bulk", format!("bulk-{}", i))
+                .unwrap();
+        }
+        scheduler
+            .push(QueuePriority::Normal, "portal-proxy", "proxy-0".to_string())
+            .unwrap();
+
+        let order: Vec<_> = (0..3).filter_map(|_| scheduler.pop()).collect();
+        assert!(order[..2].contains(&"proxy-0".to_string()));
+        assert_eq!(order.iter().filter(|id| id.starts_with("bulk")).count(), 2);
+    }
+
+    #[test]
+    fn retain_drops_items_and_empty_clients() {
+        let mut scheduler = FairScheduler::new(LanePolicy::default());
+        scheduler
+            .push(QueuePriority::High, INTERACTIVE_CLIENT, "a")
+            .unwrap();
+        scheduler.push(QueuePriority::Normal, "bulk", "b").unwrap();
+        scheduler.push(QueuePriority::Normal, "bulk", "c").unwrap();
+
+        assert_eq!(scheduler.retain(|item| *item != "b" && *item != "a"), 2);
+        assert_eq!(scheduler.len(), 1);
+        let depths = scheduler.depths();
+        assert_eq!(depths[0].depth, 0);
+        assert_eq!(depths[0].clients, Some(0));
+        assert_eq!(scheduler.pop(), Some("c"));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn rotation_prefers_heavier_lane() {
+        let mut rotation = LaneRotation::new(LanePolicy::default());
+        let firsts: Vec<_> = (0..13).map(|_| rotation.next_order()[0]).collect();
+        let count = |p| firsts.iter().filter(|lane| **lane == p).count();
+        assert_eq!(count(QueuePriority::High), 8);
+        assert_eq!(count(QueuePriority::Normal), 4);
+        assert_eq!(count(QueuePriority::Low), 1);
+    }
+}
@@ -2,7 +2,9 @@ use crate::api::preview::{parse_flexible_json_to_preview_body, persist_preview_r
 use crate::db::Database;
 use crate::model::preview::PreviewBody;
 use crate::util::material_cache;
-use crate::util::task_queue::{PreviewTask, TaskQueue};
+use crate::util::preview_cancel;
+use crate::util::preview_progress;
+use crate::util::task_queue::{PreviewTask, TaskQueue, TaskScheduling};
 use crate::util::zen::downloader::download_file_content;
 use anyhow::{Context, Result};
 use futures::stream::{self, StreamExt};
@@ -100,6 +102,14 @@ impl MaterialDownloaderService {
                     let task_id = task.id.clone();
                     let preview_id = task.preview_id.clone();
 
+                    if preview_cancel::is_cancelled(&preview_id) {
+                        tracing::info!(preview_id = %preview_id, "预审已取消，跳过材料下载");
+                        let _ = db
+                            .update_material_download_status(&task_id, "cancelled", None)
+                            .await;
+                        return;
+                    }
+
                     if task.attempts >= max_attempts as i32 {
                         let reason =
                             format!("Max attempts reached ({}), not retrying", task.attempts);
@@ -187,18 +197,28 @@ async fn process_single_task_inner(
         .or_else(|_| parse_flexible_json_to_preview_body(task.payload.as_bytes()))
         .context("Failed to parse preview body")?;
 
+    let scheduling = TaskScheduling::extract(&task.payload).unwrap_or_default();
     let preview_id = task.preview_id.clone();
 
     let third_party_request_id = preview_body.preview.request_id.clone();
 
     let mut failed: Vec<String> = Vec::new();
+    let download_total: usize = preview_body
+        .preview
+        .material_data
+        .iter()
+        .map(|material| material.attachment_list.len())
+        .sum();
+    let mut download_done = 0;
 
     for material in preview_body.preview.material_data.iter_mut() {
         for (attachment_index, attachment) in material.attachment_list.iter_mut().enumerate() {
+            download_done += 1;
             let url = &attachment.attach_url;
             if url.starts_with(material_cache::WORKER_CACHE_SCHEME) {
                 continue;
             }
+            preview_progress::downloading(&preview_id, download_done, download_total);
 
             tracing::info!(preview_id = %preview_id, url = %url, "Downloading attachment");
 
@@ -257,6 +277,7 @@ async fn process_single_task_inner(
     if !failed.is_empty() {
         let payload = serde_json::to_string(&preview_body)
             .context("serialize preview_body after partial downloads")?;
+        let payload = scheduling.embed(payload.as_bytes()).unwrap_or(payload);
         let _ = database
             .update_material_download_payload(&task.id, &payload)
             .await;
@@ -274,13 +295,20 @@ async fn process_single_task_inner(
     )
     .await?;
 
-    let task = PreviewTask {
+    let task = PreviewTask::new(
         preview_body,
-        preview_id: preview_id.clone(),
-        third_party_request_id: third_party_request_id.clone(),
-    };
+        preview_id.clone(),
+        third_party_request_id.clone(),
+    )
+    .with_scheduling(scheduling);
 
+    let payload = serde_json::to_string(&task).context("序列化预审任务失败")?;
+    database
+        .save_task_payload(&preview_id, &payload)
+        .await
+        .context("保存任务payload失败")?;
     task_queue.enqueue(task).await?;
+    preview_progress::queued(&preview_id);
 
     Ok(())
 }
@@ -216,7 +216,8 @@ pub async fn store_material_with_token(
     Ok(file_path)
 }
 
-pub async fn read_material(token: &str) -> Result<Vec<u8>> {
+/// 令牌对应的缓存文件路径；令牌过期时移除缓存并返回错误
+async fn live_material_path(token: &str) -> Result<PathBuf> {
     let cache = ensure_initialized()?;
     let (path, expires_at) = {
         let guard = cache.read().await;
@@ -233,12 +234,26 @@ pub async fn read_material(token: &str) -> Result<Vec<u8>> {
         remove_entry_locked(&mut guard, token);
         return Err(anyhow!("材料令牌已过期"));
     }
+    Ok(path)
+}
 
+pub async fn read_material(token: &str) -> Result<Vec<u8>> {
+    let path = live_material_path(token).await?;
     tokio::fs::read(&path)
         .await
         .with_context(|| format!("读取缓存材料失败: {}", path.display()))
 }
 
+/// 打开缓存材料供流式读取，返回文件及其大小
+pub async fn open_material(token: &str) -> Result<(tokio::fs::File, u64)> {
+    let path = live_material_path(token).await?;
+    let file = tokio::fs::File::open(&path)
+        .await
+        .with_context(|| format!("读取缓存材料失败: {}", path.display()))?;
+    let size = file.metadata().await?.len();
+    Ok((file, size))
+}
+
 pub async fn get_material_path(token: &str) -> Option<PathBuf> {
     let cache = ensure_initialized().ok()?;
     let guard = cache.read().await;
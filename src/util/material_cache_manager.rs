@@ -99,8 +99,8 @@ async fn upload_single(
         .await?;
 
     let path = Path::new(&record.local_path);
-    let data = match tokio::fs::read(path).await {
-        Ok(data) => data,
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
         Err(e) if e.kind() == ErrorKind::NotFound => {
             if let Some(key) = record.oss_key.as_deref() {
                 match storage.get(key).await {
@@ -181,7 +181,7 @@ async fn upload_single(
         .unwrap_or_else(|| format!("{}_{}.bin", record.material_code, record.attachment_index));
     let oss_key = format!("cache/{}/{}", record.preview_id, file_name);
 
-    if let Err(err) = storage.put(&oss_key, &data).await {
+    if let Err(err) = storage.put_reader(&oss_key, Box::new(file)).await {
         let message = truncate_error(&err);
         database
             .update_cached_material_status(
@@ -8,30 +8,44 @@ use std::fmt::Display;
 pub mod adaptive_limiter;
 pub mod api_stats;
 pub mod auth;
+pub mod blob_store;
 pub mod callbacks;
 pub mod config;
 pub mod converter;
 pub mod crypto;
+pub mod dead_letter;
 pub mod dynamic_worker;
 pub mod extract;
 pub mod http_client;
+pub mod http_range;
+pub mod lane_scheduler;
 pub mod log;
 pub mod logging;
 pub mod material;
 pub mod material_cache;
 pub mod material_cache_manager;
 pub mod middleware;
+pub mod ocr_cache;
+pub mod ocr_export;
 pub mod outbox;
+pub mod pdf_shard;
 pub mod permit_tracker;
+pub mod pii_rotation;
+pub mod preview_cancel;
+pub mod preview_progress;
 pub mod processing;
 pub mod report;
+pub mod retention;
 pub mod rules;
+pub mod search;
 pub mod service_watchdog;
 pub mod system_info;
 pub mod task_queue;
 pub mod task_recovery;
+pub mod task_routing;
 pub mod tracing;
 pub mod worker;
+pub mod worker_fleet;
 pub mod zen;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
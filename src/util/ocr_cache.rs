@@ -0,0 +1,320 @@
+//! 基于内容哈希的 OCR 结果缓存
+//!
+//! 申请人在不同预审中反复提交同一张身份证、营业执照，逐张重新识别浪费 OCR 引擎。
+//! 缓存键为 `SHA-256(送入引擎的图片字节) + 引擎/模型版本 + 预处理参数 + 渲染 DPI`，
+//! 结果以 JSON 形式存放在存储后端 `ocr-cache/` 前缀下，可在多个预审、多个节点间共享。
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ocr_conn::ocr::ContentData;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+use crate::storage::Storage;
+use crate::util::config::types::{DeploymentRole, OcrCacheConfig};
+use crate::AppState;
+
+pub const OCR_CACHE_PREFIX: &str = "ocr-cache/";
+const ENTRY_FORMAT_VERSION: u32 = 1;
+
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static STORES: AtomicU64 = AtomicU64::new(0);
+static STORE_FAILURES: AtomicU64 = AtomicU64::new(0);
+static EVICTIONS: AtomicU64 = AtomicU64::new(0);
+static MAINTENANCE_TASK: OnceCell<()> = OnceCell::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    format_version: u32,
+    fingerprint: String,
+    render_dpi: Option<u32>,
+    created_at: DateTime<Utc>,
+    contents: Vec<ContentData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrCacheStats {
+    pub enabled: bool,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub stores: u64,
+    pub store_failures: u64,
+    pub evictions: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OcrCachePurgeSummary {
+    pub scanned: usize,
+    pub removed: usize,
+    pub removed_bytes: u64,
+    pub remaining: usize,
+    pub remaining_bytes: u64,
+}
+
+fn config() -> &'static OcrCacheConfig {
+    &crate::CONFIG.ocr_cache
+}
+
+pub fn is_enabled() -> bool {
+    config().enabled
+}
+
+/// 引擎与预处理参数指纹，任一变化都会使旧缓存自然失效
+fn engine_fingerprint() -> String {
+    let preprocess = serde_json::to_string(&ocr_conn::preprocess::current_options())
+        .unwrap_or_default();
+    let binary = crate::CONFIG
+        .ocr_engine
+        .as_ref()
+        .and_then(|cfg| cfg.binary.clone())
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(config().model_version.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(binary.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(preprocess.as_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
+/// 计算缓存键：图片内容、引擎指纹和渲染 DPI 共同决定
+pub fn cache_key(image_bytes: &[u8], render_dpi: Option<u32>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(image_bytes);
+    hasher.update(engine_fingerprint().as_bytes());
+    hasher.update(render_dpi.unwrap_or(0).to_le_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn storage_key(key: &str) -> String {
+    format!("{}{}.json", OCR_CACHE_PREFIX, key)
+}
+
+/// 查询缓存；命中后校验指纹与 TTL，过期或格式不兼容的条目顺手删除
+pub async fn lookup(
+    storage: &Arc<dyn Storage>,
+    image_bytes: &[u8],
+    render_dpi: Option<u32>,
+) -> Option<Vec<ContentData>> {
+    if !is_enabled() {
+        return None;
+    }
+    let key = cache_key(image_bytes, render_dpi);
+    let object_key = storage_key(&key);
+
+    let bytes = match storage.get(&object_key).await {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => {
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Err(err) => {
+            debug!(key = %object_key, error = %err, "读取OCR缓存失败，按未命中处理");
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+    };
+
+    let entry = match serde_json::from_slice::<CacheEntry>(&bytes) {
+        Ok(entry)
+            if entry.format_version == ENTRY_FORMAT_VERSION
+                && entry.fingerprint == engine_fingerprint()
+                && entry.render_dpi == render_dpi =>
+        {
+            entry
+        }
+        _ => {
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            let _ = storage.delete(&object_key).await;
+            return None;
+        }
+    };
+
+    let ttl = chrono::Duration::hours(config().ttl_hours as i64);
+    if Utc::now() - entry.created_at > ttl {
+        MISSES.fetch_add(1, Ordering::Relaxed);
+        EVICTIONS.fetch_add(1, Ordering::Relaxed);
+        let _ = storage.delete(&object_key).await;
+        return None;
+    }
+
+    HITS.fetch_add(1, Ordering::Relaxed);
+    debug!(
+        key = %object_key,
+        blocks = entry.contents.len(),
+        "OCR缓存命中"
+    );
+    Some(entry.contents)
+}
+
+pub async fn store(
+    storage: &Arc<dyn Storage>,
+    image_bytes: &[u8],
+    render_dpi: Option<u32>,
+    contents: &[ContentData],
+) {
+    if !is_enabled() {
+        return;
+    }
+    let key = cache_key(image_bytes, render_dpi);
+    let entry = CacheEntry {
+        format_version: ENTRY_FORMAT_VERSION,
+        fingerprint: engine_fingerprint(),
+        render_dpi,
+        created_at: Utc::now(),
+        contents: contents.to_vec(),
+    };
+    let payload = match serde_json::to_vec(&entry) {
+        Ok(payload) => payload,
+        Err(err) => {
+            STORE_FAILURES.fetch_add(1, Ordering::Relaxed);
+            warn!(error = %err, "序列化OCR缓存失败");
+            return;
+        }
+    };
+    if payload.len() as u64 > config().max_entry_kb * 1024 {
+        debug!(size = payload.len(), "OCR结果过大，跳过缓存");
+        return;
+    }
+    match storage.put(&storage_key(&key), &payload).await {
+        Ok(_) => {
+            STORES.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(err) => {
+            STORE_FAILURES.fetch_add(1, Ordering::Relaxed);
+            warn!(error = %err, "写入OCR缓存失败");
+        }
+    }
+}
+
+pub fn stats() -> OcrCacheStats {
+    let hits = HITS.load(Ordering::Relaxed);
+    let misses = MISSES.load(Ordering::Relaxed);
+    let total = hits + misses;
+    OcrCacheStats {
+        enabled: is_enabled(),
+        hits,
+        misses,
+        hit_rate: if total > 0 {
+            hits as f64 / total as f64
+        } else {
+            0.0
+        },
+        stores: STORES.load(Ordering::Relaxed),
+        store_failures: STORE_FAILURES.load(Ordering::Relaxed),
+        evictions: EVICTIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// 清理缓存：`older_than` 为空时清空全部；否则删除早于该时长的条目，
+/// 并在总量超过 `max_size_mb` / `max_entries` 时按最后修改时间淘汰最旧的条目。
+pub async fn purge(
+    storage: &Arc<dyn Storage>,
+    older_than: Option<Duration>,
+) -> Result<OcrCachePurgeSummary> {
+    let keys = storage.list(OCR_CACHE_PREFIX).await?;
+    let mut summary = OcrCachePurgeSummary {
+        scanned: keys.len(),
+        ..Default::default()
+    };
+
+    let mut live = Vec::with_capacity(keys.len());
+    let cutoff = older_than.map(|age| {
+        Utc::now() - chrono::Duration::from_std(age).unwrap_or(chrono::Duration::zero())
+    });
+    for key in keys {
+        let metadata = match storage.get_metadata(&key).await {
+            Ok(meta) => meta,
+            Err(err) => {
+                debug!(key = %key, error = %err, "读取OCR缓存元数据失败，跳过");
+                continue;
+            }
+        };
+        let expired = match cutoff {
+            None => true,
+            Some(cutoff) => metadata.last_modified < cutoff,
+        };
+        if expired {
+            if storage.delete(&key).await.is_ok() {
+                summary.removed += 1;
+                summary.removed_bytes += metadata.size;
+            }
+        } else {
+            live.push((key, metadata.size, metadata.last_modified));
+        }
+    }
+
+    let max_bytes = config().max_size_mb * 1024 * 1024;
+    let max_entries = config().max_entries;
+    let mut total_bytes: u64 = live.iter().map(|(_, size, _)| *size).sum();
+    if total_bytes > max_bytes || live.len() > max_entries {
+        live.sort_by_key(|(_, _, modified)| *modified);
+        let mut remaining = live.len();
+        let mut evict = Vec::new();
+        for (key, size, _) in live.iter() {
+            if total_bytes <= max_bytes && remaining <= max_entries {
+                break;
+            }
+            evict.push(key.clone());
+            total_bytes = total_bytes.saturating_sub(*size);
+            remaining -= 1;
+            summary.removed_bytes += *size;
+        }
+        for key in &evict {
+            if let Err(err) = storage.delete(key).await {
+                warn!(key = %key, error = %err, "淘汰OCR缓存失败");
+            }
+        }
+        summary.removed += evict.len();
+        let evicted: HashSet<String> = evict.into_iter().collect();
+        live.retain(|(key, _, _)| !evicted.contains(key));
+    }
+
+    summary.remaining = live.len();
+    summary.remaining_bytes = live.iter().map(|(_, size, _)| *size).sum();
+    EVICTIONS.fetch_add(summary.removed as u64, Ordering::Relaxed);
+    Ok(summary)
+}
+
+/// 由 master 定期执行 TTL 清理与容量淘汰
+pub fn spawn_maintenance(app_state: &AppState) {
+    if !is_enabled() || app_state.config.deployment.role == DeploymentRole::Worker {
+        return;
+    }
+    if MAINTENANCE_TASK.set(()).is_err() {
+        return;
+    }
+
+    let storage = Arc::clone(&app_state.storage);
+    let interval_secs = config().maintenance_interval_secs.max(60);
+    let ttl = Duration::from_secs(config().ttl_hours * 3600);
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(interval_secs));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match purge(&storage, Some(ttl)).await {
+                Ok(summary) if summary.removed > 0 => {
+                    info!(
+                        removed = summary.removed,
+                        removed_bytes = summary.removed_bytes,
+                        remaining = summary.remaining,
+                        "OCR缓存后台清理完成"
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "OCR缓存后台清理失败"),
+            }
+        }
+    });
+}
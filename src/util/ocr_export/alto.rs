@@ -0,0 +1,93 @@
+//! ALTO v4 输出，坐标单位为像素；每页一个 TextBlock，每个文本框一条 TextLine
+
+use std::fmt::Write as _;
+
+use ocr_conn::table::BoundingBox;
+
+use super::{escape_xml, ExportPage};
+
+pub fn render_alto(pages: &[ExportPage]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<alto xmlns=\"http://www.loc.gov/standards/alto/ns-v4#\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.loc.gov/standards/alto/ns-v4# \
+         http://www.loc.gov/alto/v4/alto-4-2.xsd\">\n",
+    );
+    out.push_str("<Description>\n<MeasurementUnit>pixel</MeasurementUnit>\n");
+    out.push_str("</Description>\n<Layout>\n");
+
+    for (page_no, page) in pages.iter().enumerate() {
+        let page_id = page_no + 1;
+        let (w, h) = (page.boxes.width, page.boxes.height);
+        let _ = writeln!(
+            out,
+            "<Page ID=\"page_{}\" PHYSICAL_IMG_NR=\"{}\" WIDTH=\"{}\" HEIGHT=\"{}\">",
+            page_id, page_id, w, h
+        );
+        let _ = writeln!(
+            out,
+            "<PrintSpace HPOS=\"0\" VPOS=\"0\" WIDTH=\"{}\" HEIGHT=\"{}\">",
+            w, h
+        );
+
+        let lines: Vec<(BoundingBox, &str, f64)> = page
+            .boxes
+            .contents
+            .iter()
+            .filter(|item| !item.text.trim().is_empty())
+            .map(|item| {
+                (
+                    BoundingBox::from_rect(&item.rect),
+                    item.text.trim(),
+                    item.score,
+                )
+            })
+            .collect();
+        if let Some(first) = lines.first() {
+            let block = lines
+                .iter()
+                .skip(1)
+                .fold(first.0, |acc, (b, _, _)| BoundingBox {
+                    left: acc.left.min(b.left),
+                    top: acc.top.min(b.top),
+                    right: acc.right.max(b.right),
+                    bottom: acc.bottom.max(b.bottom),
+                });
+            let _ = writeln!(
+                out,
+                "<TextBlock ID=\"block_{}\" {}>",
+                page_id,
+                position(&block)
+            );
+            for (line_no, (bbox, text, score)) in lines.iter().enumerate() {
+                let pos = position(bbox);
+                let _ = writeln!(
+                    out,
+                    "<TextLine ID=\"line_{0}_{1}\" {2}><String ID=\"string_{0}_{1}\" CONTENT=\"{3}\" WC=\"{4:.2}\" {2}/></TextLine>",
+                    page_id,
+                    line_no + 1,
+                    pos,
+                    escape_xml(text),
+                    score.clamp(0.0, 1.0)
+                );
+            }
+            out.push_str("</TextBlock>\n");
+        }
+        out.push_str("</PrintSpace>\n</Page>\n");
+    }
+
+    out.push_str("</Layout>\n</alto>\n");
+    out
+}
+
+fn position(b: &BoundingBox) -> String {
+    format!(
+        "HPOS=\"{}\" VPOS=\"{}\" WIDTH=\"{}\" HEIGHT=\"{}\"",
+        b.left,
+        b.top,
+        b.right.saturating_sub(b.left),
+        b.bottom.saturating_sub(b.top)
+    )
+}
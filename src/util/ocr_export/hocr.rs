@@ -0,0 +1,65 @@
+//! hOCR 1.2 输出，每个文本框对应一行（`ocr_line`）及其唯一的词（`ocrx_word`）
+
+use std::fmt::Write as _;
+
+use ocr_conn::table::BoundingBox;
+
+use super::{escape_xml, ExportPage};
+
+pub fn render_hocr(title: &str, pages: &[ExportPage]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \
+         \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">\n",
+    );
+    out.push_str(
+        "<html xmlns=\"http://www.w3.org/1999/xhtml\" xml:lang=\"zh\" lang=\"zh\">\n<head>\n",
+    );
+    let _ = writeln!(out, "<title>{}</title>", escape_xml(title));
+    out.push_str("<meta http-equiv=\"Content-Type\" content=\"text/html;charset=utf-8\"/>\n");
+    out.push_str("<meta name=\"ocr-system\" content=\"PaddleOCR-json\"/>\n");
+    out.push_str("<meta name=\"ocr-capabilities\" content=\"ocr_page ocr_line ocrx_word\"/>\n");
+    out.push_str("</head>\n<body>\n");
+
+    for (page_no, page) in pages.iter().enumerate() {
+        let page_id = page_no + 1;
+        let _ = writeln!(
+            out,
+            "<div class=\"ocr_page\" id=\"page_{}\" title=\"image &quot;{}&quot;; bbox 0 0 {} {}; ppageno {}\" data-material=\"{}\">",
+            page_id,
+            escape_xml(&page.image_key),
+            page.boxes.width,
+            page.boxes.height,
+            page_no,
+            escape_xml(&page.material_code)
+        );
+        for (line_no, item) in page.boxes.contents.iter().enumerate() {
+            let text = item.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let bbox = BoundingBox::from_rect(&item.rect);
+            let title = format!(
+                "bbox {} {} {} {}; x_wconf {}",
+                bbox.left,
+                bbox.top,
+                bbox.right,
+                bbox.bottom,
+                (item.score * 100.0).round().clamp(0.0, 100.0) as u32
+            );
+            let _ = writeln!(
+                out,
+                "<span class=\"ocr_line\" id=\"line_{0}_{1}\" title=\"{2}\"><span class=\"ocrx_word\" id=\"word_{0}_{1}\" title=\"{2}\">{3}</span></span>",
+                page_id,
+                line_no + 1,
+                title,
+                escape_xml(text)
+            );
+        }
+        out.push_str("</div>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
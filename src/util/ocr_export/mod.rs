@@ -0,0 +1,201 @@
+//! OCR 结果导出：可检索 PDF（原图 + 不可见文字层）、hOCR、ALTO XML
+//!
+//! 转换后的页面图片存放在 `uploads/{preview_id}/{material}/converted/` 下，
+//! 每张图片旁边有一份同名 `.ocr.json` 记录文本框坐标，导出时按页读取二者拼装。
+
+pub mod alto;
+pub mod hocr;
+pub mod pdf;
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ocr_conn::ocr::ContentData;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::storage::Storage;
+
+const SIDECAR_SUFFIX: &str = ".ocr.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Pdf,
+    Hocr,
+    Alto,
+}
+
+impl ExportFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "pdf" => Some(Self::Pdf),
+            "hocr" | "html" => Some(Self::Hocr),
+            "alto" | "xml" => Some(Self::Alto),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Pdf => "application/pdf",
+            Self::Hocr => "text/html; charset=utf-8",
+            Self::Alto => "application/xml; charset=utf-8",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Hocr => "hocr.html",
+            Self::Alto => "alto.xml",
+        }
+    }
+}
+
+/// 页面图片旁的文本框记录，坐标以该图片像素为准
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageBoxes {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub dpi: Option<u32>,
+    pub contents: Vec<ContentData>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportPage {
+    pub material_code: String,
+    pub image_key: String,
+    pub image: Vec<u8>,
+    pub boxes: PageBoxes,
+}
+
+pub fn converted_prefix(preview_id: &str, material_code: &str) -> String {
+    format!("uploads/{}/{}/converted/", preview_id, material_code)
+}
+
+pub fn sidecar_key(image_key: &str) -> String {
+    format!("{}{}", image_key, SIDECAR_SUFFIX)
+}
+
+/// 记录页面图片对应的文本框；图片字节须与送入引擎的一致（预处理后的版本）
+pub async fn store_page_boxes(
+    storage: &Arc<dyn Storage>,
+    image_key: &str,
+    image_bytes: &[u8],
+    dpi: Option<u32>,
+    contents: &[ContentData],
+) {
+    let (width, height) = match image_dimensions(image_bytes) {
+        Ok(dims) => dims,
+        Err(err) => {
+            debug!(key = %image_key, error = %err, "无法读取页面尺寸，跳过文本框记录");
+            return;
+        }
+    };
+    let boxes = PageBoxes {
+        width,
+        height,
+        dpi,
+        contents: contents.to_vec(),
+    };
+    let payload = match serde_json::to_vec(&boxes) {
+        Ok(payload) => payload,
+        Err(err) => {
+            warn!(key = %image_key, error = %err, "序列化页面文本框失败");
+            return;
+        }
+    };
+    if let Err(err) = storage.put(&sidecar_key(image_key), &payload).await {
+        warn!(key = %image_key, error = %err, "保存页面文本框失败");
+    }
+}
+
+/// 读取某材料全部带文本框记录的页面，按页码排序
+pub async fn collect_material_pages(
+    storage: &Arc<dyn Storage>,
+    preview_id: &str,
+    material_code: &str,
+) -> Result<Vec<ExportPage>> {
+    let keys = storage
+        .list(&converted_prefix(preview_id, material_code))
+        .await?;
+    let mut image_keys: Vec<String> = keys
+        .iter()
+        .filter(|key| !key.ends_with(SIDECAR_SUFFIX))
+        .filter(|key| keys.contains(&sidecar_key(key)))
+        .cloned()
+        .collect();
+    image_keys.sort_by_key(|key| page_sort_key(key));
+
+    let mut pages = Vec::with_capacity(image_keys.len());
+    for image_key in image_keys {
+        let Some(sidecar) = storage.get(&sidecar_key(&image_key)).await? else {
+            continue;
+        };
+        let boxes: PageBoxes = match serde_json::from_slice(&sidecar) {
+            Ok(boxes) => boxes,
+            Err(err) => {
+                warn!(key = %image_key, error = %err, "页面文本框记录损坏，跳过");
+                continue;
+            }
+        };
+        let Some(image) = storage.get(&image_key).await? else {
+            continue;
+        };
+        pages.push(ExportPage {
+            material_code: material_code.to_string(),
+            image_key,
+            image,
+            boxes,
+        });
+    }
+    Ok(pages)
+}
+
+/// 按格式渲染导出文件
+pub fn render(format: ExportFormat, title: &str, pages: &[ExportPage]) -> Result<Vec<u8>> {
+    if pages.is_empty() {
+        return Err(anyhow!("没有可导出的OCR页面"));
+    }
+    match format {
+        ExportFormat::Pdf => pdf::render_searchable_pdf(pages),
+        ExportFormat::Hocr => Ok(hocr::render_hocr(title, pages).into_bytes()),
+        ExportFormat::Alto => Ok(alto::render_alto(pages).into_bytes()),
+    }
+}
+
+fn image_dimensions(bytes: &[u8]) -> Result<(u32, u32)> {
+    Ok(image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_dimensions()?)
+}
+
+/// `page-N.<ext>` 按 N 排序，其余文件名排在后面
+fn page_sort_key(key: &str) -> (u32, String) {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    let number = name
+        .strip_prefix("page-")
+        .and_then(|rest| rest.split('.').next())
+        .and_then(|n| n.parse::<u32>().ok())
+        .unwrap_or(u32::MAX);
+    (number, name.to_string())
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,262 @@
+//! 可检索 PDF：每页铺满原始页面图片，其上叠加渲染模式 3（不可见）的文字层。
+//!
+//! 文字使用不嵌入字形的 Type0/Identity-H 字体，字符按 UTF-16 编码作为 CID，
+//! 再通过恒等 ToUnicode 映射回 Unicode，阅读器即可检索、复制。
+
+use std::fmt::Write as _;
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use image::codecs::jpeg::{JpegDecoder, JpegEncoder};
+use image::{ColorType, ImageDecoder};
+use ocr_conn::table::BoundingBox;
+
+use super::ExportPage;
+
+/// 页面记录缺少 DPI 时按此换算页面尺寸
+const DEFAULT_DPI: u32 = 150;
+const REENCODE_JPEG_QUALITY: u8 = 90;
+/// 字体缺省字宽（千分之一字号），用于计算水平缩放让文字宽度贴合文本框
+const GLYPH_WIDTH: f64 = 500.0;
+
+const TO_UNICODE_CMAP: &str = "/CIDInit /ProcSet findresource begin\n\
+12 dict begin\n\
+begincmap\n\
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+/CMapName /Adobe-Identity-UCS def\n\
+/CMapType 2 def\n\
+1 begincodespacerange\n\
+<0000> <FFFF>\n\
+endcodespacerange\n\
+1 beginbfrange\n\
+<0000> <FFFF> <0000>\n\
+endbfrange\n\
+endcmap\n\
+CMapName currentdict /CMap defineresource pop\n\
+end\n\
+end\n";
+
+struct PdfWriter {
+    buf: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n");
+        Self {
+            buf,
+            offsets: Vec::new(),
+        }
+    }
+
+    /// 对象编号从 1 开始按写入顺序分配，调用方需按编号顺序写入
+    fn object(&mut self, body: &str) {
+        self.offsets.push(self.buf.len());
+        let id = self.offsets.len();
+        self.buf
+            .extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", id, body).as_bytes());
+    }
+
+    fn stream(&mut self, dict: &str, data: &[u8]) {
+        self.offsets.push(self.buf.len());
+        let id = self.offsets.len();
+        self.buf.extend_from_slice(
+            format!(
+                "{} 0 obj\n<< {} /Length {} >>\nstream\n",
+                id,
+                dict,
+                data.len()
+            )
+            .as_bytes(),
+        );
+        self.buf.extend_from_slice(data);
+        self.buf.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self, root: usize) -> Vec<u8> {
+        let xref_offset = self.buf.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            let _ = writeln!(xref, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            root,
+            xref_offset
+        );
+        self.buf.extend_from_slice(xref.as_bytes());
+        self.buf
+    }
+}
+
+pub fn render_searchable_pdf(pages: &[ExportPage]) -> Result<Vec<u8>> {
+    // 固定对象：1 目录，2 页面树，3 Type0 字体，4 CID 字体，5 字体描述，6 ToUnicode
+    // 每页 3 个对象：页面、图片、内容流
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 7 + i * 3).collect();
+
+    let mut writer = PdfWriter::new();
+    writer.object("<< /Type /Catalog /Pages 2 0 R >>");
+    let kids = page_ids
+        .iter()
+        .map(|id| format!("{} 0 R", id))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writer.object(&format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids,
+        pages.len()
+    ));
+    writer.object(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /GlyphLessFont /Encoding /Identity-H \
+         /DescendantFonts [4 0 R] /ToUnicode 6 0 R >>",
+    );
+    writer.object(&format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /GlyphLessFont \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /CIDToGIDMap /Identity /FontDescriptor 5 0 R /DW {} >>",
+        GLYPH_WIDTH
+    ));
+    writer.object(
+        "<< /Type /FontDescriptor /FontName /GlyphLessFont /Flags 5 /FontBBox [0 0 500 1000] \
+         /ItalicAngle 0 /Ascent 1000 /Descent 0 /CapHeight 1000 /StemV 80 >>",
+    );
+    writer.stream("", TO_UNICODE_CMAP.as_bytes());
+
+    for (index, page) in pages.iter().enumerate() {
+        let page_id = page_ids[index];
+        let (jpeg, width, height, color_space) = jpeg_for_pdf(&page.image)
+            .map_err(|e| anyhow!("页面图片无法嵌入PDF ({}): {}", page.image_key, e))?;
+        let scale = 72.0 / page.boxes.dpi.unwrap_or(DEFAULT_DPI).max(1) as f64;
+        let page_w = width as f64 * scale;
+        let page_h = height as f64 * scale;
+        // 文本框坐标按记录尺寸换算到实际图片尺寸
+        let box_scale_x = width as f64 / page.boxes.width.max(1) as f64;
+        let box_scale_y = height as f64 / page.boxes.height.max(1) as f64;
+
+        writer.object(&format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /XObject << /Im0 {} 0 R >> /Font << /F1 3 0 R >> >> \
+             /Contents {} 0 R >>",
+            page_w,
+            page_h,
+            page_id + 1,
+            page_id + 2
+        ));
+        writer.stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} \
+                 /BitsPerComponent 8 /Filter /DCTDecode",
+                width, height, color_space
+            ),
+            &jpeg,
+        );
+
+        let mut content = format!(
+            "q\n{:.2} 0 0 {:.2} 0 0 cm\n/Im0 Do\nQ\nBT\n3 Tr\n",
+            page_w, page_h
+        );
+        for item in &page.boxes.contents {
+            let units: Vec<u16> = item.text.trim().encode_utf16().collect();
+            if units.is_empty() {
+                continue;
+            }
+            let bbox = BoundingBox::from_rect(&item.rect);
+            let left = bbox.left as f64 * box_scale_x * scale;
+            let right = bbox.right as f64 * box_scale_x * scale;
+            let top = bbox.top as f64 * box_scale_y * scale;
+            let bottom = bbox.bottom as f64 * box_scale_y * scale;
+            let font_size = (bottom - top).max(1.0);
+            let natural_width = units.len() as f64 * font_size * GLYPH_WIDTH / 1000.0;
+            let horizontal_scale = ((right - left).max(1.0) / natural_width * 100.0).max(1.0);
+            let hex: String = units.iter().map(|u| format!("{:04X}", u)).collect();
+            let _ = writeln!(
+                content,
+                "/F1 {:.2} Tf {:.2} Tz 1 0 0 1 {:.2} {:.2} Tm <{}> Tj",
+                font_size,
+                horizontal_scale,
+                left,
+                page_h - bottom,
+                hex
+            );
+        }
+        content.push_str("ET\n");
+        writer.stream("", content.as_bytes());
+    }
+
+    Ok(writer.finish(1))
+}
+
+/// 返回可直接以 DCTDecode 嵌入的 JPEG 数据、尺寸与颜色空间；
+/// 非 JPEG 或非灰度/RGB 的图片重新编码。
+fn jpeg_for_pdf(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32, &'static str)> {
+    if let Ok(decoder) = JpegDecoder::new(Cursor::new(bytes)) {
+        let (width, height) = decoder.dimensions();
+        match decoder.color_type() {
+            ColorType::L8 => return Ok((bytes.to_vec(), width, height, "DeviceGray")),
+            ColorType::Rgb8 => return Ok((bytes.to_vec(), width, height, "DeviceRGB")),
+            _ => {}
+        }
+    }
+    let rgb = image::load_from_memory(bytes)?.to_rgb8();
+    let (width, height) = rgb.dimensions();
+    let mut encoded = Vec::new();
+    JpegEncoder::new_with_quality(&mut encoded, REENCODE_JPEG_QUALITY).encode_image(&rgb)?;
+    Ok((encoded, width, height, "DeviceRGB"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::ocr_export::PageBoxes;
+    use ocr_conn::ocr::ContentData;
+
+    fn sample_page() -> ExportPage {
+        let img = image::RgbImage::from_pixel(40, 20, image::Rgb([255, 255, 255]));
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg).encode_image(&img).unwrap();
+        ExportPage {
+            material_code: "M1".to_string(),
+            image_key: "uploads/p/M1/converted/page-1.jpg".to_string(),
+            image: jpeg,
+            boxes: PageBoxes {
+                width: 40,
+                height: 20,
+                dpi: Some(72),
+                contents: vec![ContentData {
+                    rect: [[2, 2], [30, 2], [30, 12], [2, 12]],
+                    score: 0.98,
+                    text: "京A1".to_string(),
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn writes_text_layer_and_valid_xref() {
+        let pdf = render_searchable_pdf(&[sample_page()]).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.5"));
+        assert!(text.contains("3 Tr"));
+        assert!(text.contains("<4EAC00410031> Tj"));
+
+        // 二进制图片数据会使有损转换后的偏移失真，xref 需按字节解析
+        let tail_at = pdf.windows(10).rposition(|w| w == b"startxref\n").unwrap();
+        let tail = std::str::from_utf8(&pdf[tail_at + 10..]).unwrap();
+        let xref_at: usize = tail.lines().next().unwrap().parse().unwrap();
+        let xref = std::str::from_utf8(&pdf[xref_at..]).unwrap();
+        let entries: Vec<usize> = xref
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 9);
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(pdf[*offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+}
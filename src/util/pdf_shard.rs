@@ -0,0 +1,745 @@
+//! 大 PDF 分片：主节点把附件按页段拆成子任务经任务队列分发，发起分片的节点
+//! （执行该预审的 worker 或主节点）空闲时也领取分片，全部完成后按页序合并
+//!
+//! 分片登记只保存在主节点内存中；主节点重启后查询失败，预审按普通失败重试。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::model::preview::{Attachment, MaterialValue, PreviewBody};
+use crate::storage::Storage;
+use crate::util::config::types::{PdfShardingConfig, QueuePriority};
+use crate::util::material_cache::WORKER_CACHE_SCHEME;
+use crate::util::preview_cancel;
+use crate::util::preview_progress;
+use crate::util::processing::optimized_pipeline::OPTIMIZED_PIPELINE;
+use crate::util::task_queue::{PreviewTask, TaskQueue};
+use crate::util::worker::{self, WorkerJobActivityGuard, WorkerProxyClient};
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 发起节点异常退出时遗留的登记在此之后清理
+const JOB_TTL: Duration = Duration::from_secs(6 * 3600);
+
+static JOBS: Lazy<Mutex<HashMap<String, ShardJob>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static QUEUE: OnceCell<Arc<dyn TaskQueue>> = OnceCell::new();
+
+/// 主节点启动时调用，分片任务经同一任务队列分发
+pub fn initialize(app_state: &AppState) {
+    let _ = QUEUE.set(Arc::clone(&app_state.task_queue));
+}
+
+/// 随任务载荷下发的页段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageShard {
+    pub job_id: String,
+    pub index: usize,
+    pub start_page: u32,
+    pub end_page: u32,
+    pub material_code: String,
+    pub attach_name: String,
+    /// 主节点材料缓存地址（worker-cache://）
+    pub attach_url: String,
+    #[serde(default)]
+    pub force_reocr: bool,
+}
+
+impl PageShard {
+    fn page_count(&self) -> u32 {
+        self.end_page + 1 - self.start_page
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardJobRequest {
+    pub preview_id: String,
+    pub matter_id: String,
+    pub material_code: String,
+    pub attach_name: String,
+    pub attach_url: String,
+    pub page_count: u32,
+    #[serde(default)]
+    pub force_reocr: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShardState {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardStatus {
+    pub index: usize,
+    pub start_page: u32,
+    pub end_page: u32,
+    pub state: ShardState,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 全部分片完成后 `pages` 按页序给出合并后的逐页文本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardJobStatus {
+    pub shards: Vec<ShardStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<Result<Vec<String>>> for ShardReport {
+    fn from(outcome: Result<Vec<String>>) -> Self {
+        match outcome {
+            Ok(pages) => Self {
+                pages: Some(pages),
+                error: None,
+            },
+            Err(err) => Self {
+                pages: None,
+                error: Some(format!("{:#}", err)),
+            },
+        }
+    }
+}
+
+struct ShardJob {
+    request: ShardJobRequest,
+    config: PdfShardingConfig,
+    slots: Vec<ShardSlot>,
+    opened_at: Instant,
+}
+
+struct ShardSlot {
+    shard: PageShard,
+    state: ShardState,
+    attempts: u32,
+    worker: Option<String>,
+    claimed_at: Option<Instant>,
+    error: Option<String>,
+    pages: Option<Vec<String>>,
+}
+
+impl ShardSlot {
+    /// 领取后超时未回报的分片重新开放领取，不额外计入失败次数
+    fn expire(&mut self, timeout: Duration) {
+        if self.state == ShardState::Running
+            && self.claimed_at.map_or(true, |at| at.elapsed() >= timeout)
+        {
+            warn!(
+                job_id = %self.shard.job_id,
+                shard = self.shard.index,
+                worker = self.worker.as_deref().unwrap_or(""),
+                "PDF 分片执行超时，重新开放领取"
+            );
+            self.state = ShardState::Pending;
+            self.worker = None;
+            self.claimed_at = None;
+        }
+    }
+
+    fn status(&self) -> ShardStatus {
+        ShardStatus {
+            index: self.shard.index,
+            start_page: self.shard.start_page,
+            end_page: self.shard.end_page,
+            state: self.state,
+            attempts: self.attempts,
+            worker: self.worker.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+impl ShardJob {
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.shard_timeout_secs.max(1))
+    }
+}
+
+/// 按固定页数切分 `1..=page_count`
+pub fn plan_ranges(page_count: u32, pages_per_shard: u32) -> Vec<(u32, u32)> {
+    let size = pages_per_shard.max(1);
+    let mut ranges = Vec::new();
+    let mut start = 1;
+    while start <= page_count {
+        let end = (start + size - 1).min(page_count);
+        ranges.push((start, end));
+        start = end + 1;
+    }
+    ranges
+}
+
+/// 主节点登记分片并投递子任务；不满足分片条件时返回 None
+pub async fn open_job(
+    request: ShardJobRequest,
+    config: &PdfShardingConfig,
+) -> Option<Vec<PageShard>> {
+    if !config.enabled
+        || request.page_count < config.min_pages
+        || !request.attach_url.starts_with(WORKER_CACHE_SCHEME)
+    {
+        return None;
+    }
+    let ranges = plan_ranges(request.page_count, config.pages_per_shard);
+    if ranges.len() < 2 {
+        return None;
+    }
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let shards: Vec<PageShard> = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start_page, end_page))| PageShard {
+            job_id: job_id.clone(),
+            index,
+            start_page,
+            end_page,
+            material_code: request.material_code.clone(),
+            attach_name: request.attach_name.clone(),
+            attach_url: request.attach_url.clone(),
+            force_reocr: request.force_reocr,
+        })
+        .collect();
+
+    {
+        let mut jobs = JOBS.lock();
+        jobs.retain(|_, job| job.opened_at.elapsed() < JOB_TTL);
+        jobs.insert(
+            job_id.clone(),
+            ShardJob {
+                request: request.clone(),
+                config: config.clone(),
+                slots: shards
+                    .iter()
+                    .map(|shard| ShardSlot {
+                        shard: shard.clone(),
+                        state: ShardState::Pending,
+                        attempts: 0,
+                        worker: None,
+                        claimed_at: None,
+                        error: None,
+                        pages: None,
+                    })
+                    .collect(),
+                opened_at: Instant::now(),
+            },
+        );
+    }
+
+    info!(
+        preview_id = %request.preview_id,
+        material_code = %request.material_code,
+        job_id = %job_id,
+        pages = request.page_count,
+        shards = shards.len(),
+        "PDF 分片已登记"
+    );
+    for shard in &shards {
+        publish(&request, shard.clone()).await;
+    }
+    Some(shards)
+}
+
+/// 队列未初始化或入队失败时不投递，分片由发起节点处理
+async fn publish(request: &ShardJobRequest, shard: PageShard) {
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+    let index = shard.index;
+    if let Err(err) = queue.enqueue(shard_task(request, shard)).await {
+        warn!(
+            preview_id = %request.preview_id,
+            shard = index,
+            error = %err,
+            "PDF 分片入队失败，由发起节点处理"
+        );
+    }
+}
+
+fn shard_task(request: &ShardJobRequest, shard: PageShard) -> PreviewTask {
+    let mut body = PreviewBody::default();
+    body.preview.matter_id = request.matter_id.clone();
+    body.preview.request_id = request.preview_id.clone();
+    // 只带分片所属附件，供能力路由判断格式与大小
+    body.preview.material_data = vec![MaterialValue {
+        code: shard.material_code.clone(),
+        attachment_list: vec![Attachment {
+            attach_name: shard.attach_name.clone(),
+            attach_url: shard.attach_url.clone(),
+            ..Attachment::default()
+        }],
+        ..MaterialValue::default()
+    }];
+
+    let mut task = PreviewTask::new(body, request.preview_id.clone(), String::new());
+    // 所属预审已在执行，分片走高优先级通道
+    task.priority = QueuePriority::High;
+    task.force_reocr = shard.force_reocr;
+    task.shard = Some(shard);
+    task
+}
+
+/// 领取分片；已被领取、已结束或登记已关闭时返回 false
+pub fn claim(job_id: &str, index: usize, worker: &str) -> bool {
+    let mut jobs = JOBS.lock();
+    let Some(job) = jobs.get_mut(job_id) else {
+        return false;
+    };
+    let timeout = job.timeout();
+    let Some(slot) = job.slots.get_mut(index) else {
+        return false;
+    };
+    slot.expire(timeout);
+    if slot.state != ShardState::Pending {
+        return false;
+    }
+    slot.state = ShardState::Running;
+    slot.attempts += 1;
+    slot.worker = Some(worker.to_string());
+    slot.claimed_at = Some(Instant::now());
+    true
+}
+
+/// 记录分片结果；失败且未用尽次数时重新投递
+pub async fn report(job_id: &str, index: usize, worker: &str, report: ShardReport) -> Result<()> {
+    let retry = {
+        let mut jobs = JOBS.lock();
+        let job = jobs
+            .get_mut(job_id)
+            .ok_or_else(|| anyhow!("PDF 分片登记不存在: {}", job_id))?;
+        let max_attempts = job.config.max_attempts.max(1);
+        let slot = job
+            .slots
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("PDF 分片序号越界: {}", index))?;
+        if slot.state == ShardState::Done {
+            return Ok(());
+        }
+
+        let expected = slot.shard.page_count() as usize;
+        match report.pages {
+            Some(pages) if report.error.is_none() && pages.len() == expected => {
+                slot.state = ShardState::Done;
+                slot.pages = Some(pages);
+                slot.error = None;
+                slot.worker = None;
+                slot.claimed_at = None;
+                None
+            }
+            // 超时后已被重新领取的分片，旧执行者的失败不影响当前执行
+            _ if slot.worker.as_deref() != Some(worker) => None,
+            pages => {
+                let error = report.error.unwrap_or_else(|| {
+                    format!(
+                        "返回 {} 页，应为 {} 页",
+                        pages.map_or(0, |pages| pages.len()),
+                        expected
+                    )
+                });
+                warn!(
+                    preview_id = %job.request.preview_id,
+                    job_id = %job_id,
+                    shard = index,
+                    worker = %worker,
+                    attempts = slot.attempts,
+                    error = %error,
+                    "PDF 分片识别失败"
+                );
+                slot.worker = None;
+                slot.claimed_at = None;
+                slot.error = Some(error);
+                if slot.attempts >= max_attempts {
+                    slot.state = ShardState::Failed;
+                    None
+                } else {
+                    slot.state = ShardState::Pending;
+                    Some((job.request.clone(), slot.shard.clone()))
+                }
+            }
+        }
+    };
+
+    if let Some((request, shard)) = retry {
+        publish(&request, shard).await;
+    }
+    Ok(())
+}
+
+pub fn status(job_id: &str) -> Result<ShardJobStatus> {
+    let mut jobs = JOBS.lock();
+    let job = jobs
+        .get_mut(job_id)
+        .ok_or_else(|| anyhow!("PDF 分片登记不存在: {}", job_id))?;
+    let timeout = job.timeout();
+    for slot in job.slots.iter_mut() {
+        slot.expire(timeout);
+    }
+
+    let pages = job
+        .slots
+        .iter()
+        .all(|slot| slot.state == ShardState::Done)
+        .then(|| {
+            job.slots
+                .iter()
+                .flat_map(|slot| slot.pages.iter().flatten().cloned())
+                .collect()
+        });
+    Ok(ShardJobStatus {
+        shards: job.slots.iter().map(ShardSlot::status).collect(),
+        pages,
+    })
+}
+
+pub fn close(job_id: &str) {
+    JOBS.lock().remove(job_id);
+}
+
+/// 分片登记的访问入口：主节点直接读写内存，worker 经主节点内部接口
+enum ShardBackend {
+    Local,
+    Remote(Arc<WorkerProxyClient>),
+}
+
+impl ShardBackend {
+    fn current() -> Self {
+        worker::client().map_or(Self::Local, Self::Remote)
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Self::Local => "master",
+            Self::Remote(client) => client.worker_id(),
+        }
+    }
+
+    async fn open(&self, request: ShardJobRequest) -> Result<Option<Vec<PageShard>>> {
+        match self {
+            Self::Local => Ok(open_job(request, &crate::CONFIG.task_queue.pdf_sharding).await),
+            Self::Remote(client) => client.open_shard_job(&request).await,
+        }
+    }
+
+    async fn claim(&self, job_id: &str, index: usize) -> Result<bool> {
+        match self {
+            Self::Local => Ok(claim(job_id, index, self.label())),
+            Self::Remote(client) => client.claim_shard(job_id, index).await,
+        }
+    }
+
+    async fn report(&self, job_id: &str, index: usize, outcome: ShardReport) -> Result<()> {
+        match self {
+            Self::Local => report(job_id, index, self.label(), outcome).await,
+            Self::Remote(client) => client.report_shard(job_id, index, &outcome).await,
+        }
+    }
+
+    async fn status(&self, job_id: &str) -> Result<ShardJobStatus> {
+        match self {
+            Self::Local => status(job_id),
+            Self::Remote(client) => client.shard_status(job_id).await,
+        }
+    }
+
+    async fn close(&self, job_id: &str) {
+        match self {
+            Self::Local => close(job_id),
+            Self::Remote(client) => {
+                if let Err(err) = client.close_shard_job(job_id).await {
+                    warn!(job_id = %job_id, error = %err, "关闭 PDF 分片登记失败");
+                }
+            }
+        }
+    }
+}
+
+/// 评估附件时调用：满足分片条件时分发页段并等待合并结果，返回 None 表示由调用方整份识别
+pub async fn process_sharded(
+    request: ShardJobRequest,
+    pdf_path: &Path,
+    storage: Option<Arc<dyn Storage>>,
+) -> Result<Option<Vec<String>>> {
+    if !request.attach_url.starts_with(WORKER_CACHE_SCHEME) {
+        return Ok(None);
+    }
+    let backend = ShardBackend::current();
+    let shards = match backend.open(request.clone()).await {
+        Ok(Some(shards)) => shards,
+        Ok(None) => return Ok(None),
+        Err(err) => {
+            warn!(
+                preview_id = %request.preview_id,
+                material_code = %request.material_code,
+                error = %err,
+                "发起 PDF 分片失败，整份识别"
+            );
+            return Ok(None);
+        }
+    };
+
+    let job_id = shards[0].job_id.clone();
+    let result = coordinate(&backend, &request, &shards, pdf_path, storage).await;
+    backend.close(&job_id).await;
+    result.map(Some)
+}
+
+async fn coordinate(
+    backend: &ShardBackend,
+    request: &ShardJobRequest,
+    shards: &[PageShard],
+    pdf_path: &Path,
+    storage: Option<Arc<dyn Storage>>,
+) -> Result<Vec<String>> {
+    let job_id = &shards[0].job_id;
+    let mut reported_pages = 0;
+    loop {
+        preview_cancel::check(&request.preview_id)?;
+        let status = backend.status(job_id).await?;
+        if let Some(pages) = status.pages {
+            return Ok(pages);
+        }
+        if let Some(failed) = status
+            .shards
+            .iter()
+            .find(|shard| shard.state == ShardState::Failed)
+        {
+            return Err(anyhow!(
+                "第 {}-{} 页分片识别失败（已执行 {} 次）: {}",
+                failed.start_page,
+                failed.end_page,
+                failed.attempts,
+                failed.error.as_deref().unwrap_or("未知错误")
+            ));
+        }
+
+        let done_pages: u32 = status
+            .shards
+            .iter()
+            .filter(|shard| shard.state == ShardState::Done)
+            .map(|shard| shard.end_page + 1 - shard.start_page)
+            .sum();
+        if done_pages != reported_pages {
+            reported_pages = done_pages;
+            preview_progress::ocr_page(&request.preview_id, done_pages, request.page_count);
+        }
+
+        // 空闲时自己领取尚未开始的分片，其他节点都在忙时也不会干等
+        let mut worked = false;
+        for pending in status
+            .shards
+            .iter()
+            .filter(|shard| shard.state == ShardState::Pending)
+        {
+            if !backend.claim(job_id, pending.index).await? {
+                continue;
+            }
+            let shard = &shards[pending.index];
+            let outcome = run_range(&request.preview_id, shard, pdf_path, storage.clone()).await;
+            if matches!(&outcome, Err(err) if preview_cancel::is_cancellation(err)) {
+                return outcome;
+            }
+            backend.report(job_id, shard.index, outcome.into()).await?;
+            worked = true;
+            break;
+        }
+        if !worked {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+async fn run_range(
+    preview_id: &str,
+    shard: &PageShard,
+    pdf_path: &Path,
+    storage: Option<Arc<dyn Storage>>,
+) -> Result<Vec<String>> {
+    OPTIMIZED_PIPELINE
+        .process_pdf_range(
+            pdf_path.to_path_buf(),
+            preview_id.to_string(),
+            shard.material_code.clone(),
+            storage,
+            shard.force_reocr,
+            shard.start_page,
+            shard.end_page,
+        )
+        .await
+}
+
+/// 队列消费者收到分片任务时调用；识别失败记入分片登记并由登记决定是否重新投递，
+/// 只有访问登记失败时返回错误交给队列重试
+pub async fn handle_shard_task(
+    preview_id: &str,
+    shard: PageShard,
+    storage: Option<Arc<dyn Storage>>,
+) -> Result<()> {
+    let backend = ShardBackend::current();
+    if !backend.claim(&shard.job_id, shard.index).await? {
+        debug!(
+            preview_id = %preview_id,
+            job_id = %shard.job_id,
+            shard = shard.index,
+            "PDF 分片已被领取或已结束，跳过"
+        );
+        return Ok(());
+    }
+
+    // 计入心跳上报的执行中任务，排空 worker 时等待分片完成
+    let _activity = WorkerJobActivityGuard::new(format!("{}#shard-{}", preview_id, shard.index));
+    let started = Instant::now();
+    let outcome = match worker::fetch_material_path(
+        &shard.attach_url,
+        Some(preview_id),
+        Some(&shard.material_code),
+        Some(&shard.attach_name),
+    )
+    .await
+    {
+        Some(Ok(path)) => run_range(preview_id, &shard, &path, storage).await,
+        Some(Err(err)) => Err(err),
+        None => Err(anyhow!("分片附件不在材料缓存中: {}", shard.attach_url)),
+    };
+    if outcome.is_ok() {
+        info!(
+            preview_id = %preview_id,
+            job_id = %shard.job_id,
+            shard = shard.index,
+            page_start = shard.start_page,
+            page_end = shard.end_page,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "PDF 分片识别完成"
+        );
+    }
+    backend
+        .report(&shard.job_id, shard.index, outcome.into())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(preview_id: &str, page_count: u32) -> ShardJobRequest {
+        ShardJobRequest {
+            preview_id: preview_id.to_string(),
+            matter_id: "matter".to_string(),
+            material_code: "M001".to_string(),
+            attach_name: "big.pdf".to_string(),
+            attach_url: format!("{}token", WORKER_CACHE_SCHEME),
+            page_count,
+            force_reocr: false,
+        }
+    }
+
+    fn config() -> PdfShardingConfig {
+        PdfShardingConfig {
+            enabled: true,
+            min_pages: 20,
+            pages_per_shard: 10,
+            max_attempts: 2,
+            shard_timeout_secs: 600,
+        }
+    }
+
+    fn pages(shard: &PageShard) -> ShardReport {
+        let outcome: Result<Vec<String>> = Ok((shard.start_page..=shard.end_page)
+            .map(|page| format!("p{}", page))
+            .collect());
+        outcome.into()
+    }
+
+    #[test]
+    fn plan_ranges_covers_every_page_once() {
+        assert_eq!(plan_ranges(25, 10), vec![(1, 10), (11, 20), (21, 25)]);
+        assert_eq!(plan_ranges(10, 10), vec![(1, 10)]);
+        assert_eq!(plan_ranges(3, 0), vec![(1, 1), (2, 2), (3, 3)]);
+        assert!(plan_ranges(0, 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn small_or_uncached_pdfs_are_not_sharded() {
+        assert!(open_job(request("shard-small", 19), &config())
+            .await
+            .is_none());
+
+        let mut uncached = request("shard-uncached", 100);
+        uncached.attach_url = "https://example.com/big.pdf".to_string();
+        assert!(open_job(uncached, &config()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn results_merge_in_page_order() {
+        let shards = open_job(request("shard-merge", 25), &config())
+            .await
+            .unwrap();
+        let job_id = shards[0].job_id.clone();
+
+        // 后面的分片先完成
+        for shard in shards.iter().rev() {
+            assert!(claim(&job_id, shard.index, "w1"));
+            assert!(!claim(&job_id, shard.index, "w2"));
+            report(&job_id, shard.index, "w1", pages(shard))
+                .await
+                .unwrap();
+        }
+
+        let merged = status(&job_id).unwrap().pages.unwrap();
+        assert_eq!(merged.len(), 25);
+        assert_eq!(merged[0], "p1");
+        assert_eq!(merged[24], "p25");
+        close(&job_id);
+        assert!(status(&job_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_shard_is_retried_then_marked_failed() {
+        let shards = open_job(request("shard-fail", 20), &config())
+            .await
+            .unwrap();
+        let job_id = shards[0].job_id.clone();
+        let failure = || ShardReport {
+            pages: None,
+            error: Some("OCR识别失败".to_string()),
+        };
+
+        assert!(claim(&job_id, 0, "w1"));
+        report(&job_id, 0, "w1", failure()).await.unwrap();
+        assert_eq!(
+            status(&job_id).unwrap().shards[0].state,
+            ShardState::Pending
+        );
+
+        // 不完整的结果按失败处理；次数用尽后不再开放领取
+        assert!(claim(&job_id, 0, "w2"));
+        let mut partial = pages(&shards[0]);
+        partial.pages.as_mut().unwrap().pop();
+        report(&job_id, 0, "w2", partial).await.unwrap();
+        let shard = &status(&job_id).unwrap().shards[0];
+        assert_eq!(shard.state, ShardState::Failed);
+        assert_eq!(shard.attempts, 2);
+        assert!(!claim(&job_id, 0, "w3"));
+        close(&job_id);
+    }
+}
@@ -0,0 +1,42 @@
+//! 敏感字段密钥轮换
+//!
+//! 主节点启动后在后台执行一次：把加密启用前的历史明文补加密，
+//! 并用当前主密钥重新包裹旧版本密文的数据密钥。轮换可重复执行，中断后下次启动继续。
+
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use tracing::{info, warn};
+
+use crate::util::config::types::DeploymentRole;
+use crate::AppState;
+
+static ROTATION_TASK: OnceCell<()> = OnceCell::new();
+
+pub fn spawn_startup_rotation(app_state: &AppState) {
+    let config = &app_state.config.pii_encryption;
+    if !config.enabled
+        || !config.rotate_on_startup
+        || app_state.config.deployment.role == DeploymentRole::Worker
+    {
+        return;
+    }
+    if ROTATION_TASK.set(()).is_err() {
+        return;
+    }
+
+    let database = Arc::clone(&app_state.database);
+    let batch_size = config.rotation_batch_size;
+    tokio::spawn(async move {
+        match database.rotate_pii_keys(batch_size).await {
+            Ok(summary) => info!(
+                scanned = summary.scanned,
+                updated = summary.updated,
+                skipped = summary.skipped,
+                failures = summary.failures,
+                "敏感字段密钥轮换完成"
+            ),
+            Err(err) => warn!(error = %err, "敏感字段密钥轮换失败"),
+        }
+    });
+}
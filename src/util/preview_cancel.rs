@@ -0,0 +1,178 @@
+//! 预审取消登记：执行中的任务持有取消令牌，被取消的 preview_id 保留一段时间，
+//! 供出队、材料下载、评估循环与 worker 心跳查询
+//!
+//! 登记只存在于进程内存中；进程重启后以数据库中的 cancelled 状态为准。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// 取消标记的保留时长，超过后任务早已出队或被清理
+const TOMBSTONE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| Mutex::new(Registry::default()));
+
+#[derive(Default)]
+struct Registry {
+    running: HashMap<String, (u64, CancellationToken)>,
+    cancelled: HashMap<String, Instant>,
+    next_id: u64,
+}
+
+impl Registry {
+    fn prune(&mut self) {
+        self.cancelled
+            .retain(|_, cancelled_at| cancelled_at.elapsed() < TOMBSTONE_TTL);
+    }
+}
+
+/// 评估因取消而中止时返回的错误，用于与普通失败区分
+#[derive(Debug, Clone)]
+pub struct PreviewCancelled {
+    pub preview_id: String,
+}
+
+impl fmt::Display for PreviewCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "预审任务已取消: {}", self.preview_id)
+    }
+}
+
+impl std::error::Error for PreviewCancelled {}
+
+/// 标记取消；返回 true 表示本进程内有正在执行的任务收到了信号
+pub fn cancel(preview_id: &str) -> bool {
+    let mut registry = REGISTRY.lock();
+    registry.prune();
+    registry
+        .cancelled
+        .insert(preview_id.to_string(), Instant::now());
+    match registry.running.get(preview_id) {
+        Some((_, token)) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 重新执行前清除取消标记
+pub fn clear(preview_id: &str) {
+    REGISTRY.lock().cancelled.remove(preview_id);
+}
+
+pub fn is_cancelled(preview_id: &str) -> bool {
+    REGISTRY.lock().cancelled.contains_key(preview_id)
+}
+
+/// 评估循环在附件、页之间调用
+pub fn check(preview_id: &str) -> anyhow::Result<()> {
+    if is_cancelled(preview_id) {
+        return Err(PreviewCancelled {
+            preview_id: preview_id.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+pub fn is_cancellation(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<PreviewCancelled>())
+}
+
+/// 筛出已取消的任务，主节点通过心跳应答转告 worker
+pub fn cancelled_among(preview_ids: &[String]) -> Vec<String> {
+    let registry = REGISTRY.lock();
+    preview_ids
+        .iter()
+        .filter(|id| registry.cancelled.contains_key(id.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// 登记执行中的任务；若已被取消，返回的令牌即处于取消状态
+pub fn track(preview_id: &str) -> RunningPreview {
+    let mut registry = REGISTRY.lock();
+    let token = CancellationToken::new();
+    if registry.cancelled.contains_key(preview_id) {
+        token.cancel();
+    }
+    registry.next_id += 1;
+    let id = registry.next_id;
+    registry
+        .running
+        .insert(preview_id.to_string(), (id, token.clone()));
+    RunningPreview {
+        preview_id: preview_id.to_string(),
+        id,
+        token,
+    }
+}
+
+/// 执行期间持有，释放时注销令牌
+pub struct RunningPreview {
+    preview_id: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl RunningPreview {
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+}
+
+impl Drop for RunningPreview {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock();
+        // 同一预审被重新执行时，只注销自己登记的令牌
+        if registry
+            .running
+            .get(&self.preview_id)
+            .is_some_and(|(id, _)| *id == self.id)
+        {
+            registry.running.remove(&self.preview_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn cancel_signals_running_task() {
+        let running = track("cancel-running");
+        assert!(!running.is_cancelled());
+        assert!(cancel("cancel-running"));
+        assert!(running.is_cancelled());
+
+        let err = check("cancel-running").context("附件处理失败").unwrap_err();
+        assert!(is_cancellation(&err));
+
+        clear("cancel-running");
+        assert!(check("cancel-running").is_ok());
+    }
+
+    #[test]
+    fn cancel_before_start_is_remembered() {
+        assert!(!cancel("cancel-queued"));
+        let running = track("cancel-queued");
+        assert!(running.is_cancelled());
+        assert_eq!(
+            cancelled_among(&["cancel-queued".to_string(), "other".to_string()]),
+            vec!["cancel-queued".to_string()]
+        );
+        drop(running);
+        clear("cancel-queued");
+    }
+}
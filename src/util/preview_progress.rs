@@ -0,0 +1,407 @@
+//! 预审进度推送：入队、附件下载、OCR 分页、规则评估、报告生成、回调等阶段发布进度事件，
+//! 供 SSE 订阅者实时展示
+//!
+//! 每个预审一条广播通道，并保留最近一条事件，订阅者接入时先收到当前进度。
+//! worker 进程内发布的事件经转发器上报主节点，再由主节点广播。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+/// 单个订阅者可积压的事件数，落后过多时跳过中间事件
+const CHANNEL_CAPACITY: usize = 64;
+/// 结束后的通道保留时长，便于晚到的订阅者拿到最终状态
+const FINISHED_TTL: Duration = Duration::from_secs(10 * 60);
+/// 长时间没有新事件的通道视为遗留，一并清理
+const IDLE_TTL: Duration = Duration::from_secs(6 * 3600);
+
+static HUB: Lazy<Mutex<HashMap<String, Channel>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static FORWARDER: OnceCell<mpsc::UnboundedSender<ProgressEvent>> = OnceCell::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    Queued,
+    Downloading,
+    Ocr,
+    Evaluating,
+    Report,
+    Callback,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl ProgressStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Downloading => "downloading",
+            Self::Ocr => "ocr",
+            Self::Evaluating => "evaluating",
+            Self::Report => "report",
+            Self::Callback => "callback",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+
+    /// 入队与下载标志一次新的执行（含重新执行）
+    fn starts_run(&self) -> bool {
+        matches!(self, Self::Queued | Self::Downloading)
+    }
+
+    pub fn from_status(status: &str) -> Option<Self> {
+        match status {
+            "queued" => Some(Self::Queued),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressEvent {
+    pub preview_id: String,
+    /// 主节点按预审递增的序号，作为 SSE 事件 id
+    #[serde(default)]
+    pub seq: u64,
+    pub stage: ProgressStage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_code: Option<String>,
+    /// 从 1 开始的材料序号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_total: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 整体进度 0-100，同一次执行内不回退
+    #[serde(default)]
+    pub percent: u8,
+    pub timestamp: String,
+}
+
+impl ProgressEvent {
+    fn new(preview_id: &str, stage: ProgressStage) -> Self {
+        Self {
+            preview_id: preview_id.to_string(),
+            seq: 0,
+            stage,
+            current: None,
+            total: None,
+            material_code: None,
+            material_index: None,
+            material_total: None,
+            message: None,
+            percent: 0,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn step(mut self, current: u32, total: u32) -> Self {
+        self.current = Some(current);
+        self.total = Some(total);
+        self
+    }
+
+    fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+struct MaterialPosition {
+    code: String,
+    index: u32,
+    total: u32,
+}
+
+struct Channel {
+    sender: broadcast::Sender<ProgressEvent>,
+    last: Option<ProgressEvent>,
+    material: Option<MaterialPosition>,
+    seq: u64,
+    percent: u8,
+    finished_at: Option<Instant>,
+    touched_at: Instant,
+}
+
+impl Channel {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            last: None,
+            material: None,
+            seq: 0,
+            percent: 0,
+            finished_at: None,
+            touched_at: Instant::now(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.finished_at
+            .is_some_and(|finished| finished.elapsed() >= FINISHED_TTL)
+            || self.touched_at.elapsed() >= IDLE_TTL
+    }
+
+    /// 补全材料位置与整体进度，返回 None 表示事件已过期（如取消后迟到的分页事件）
+    fn apply(&mut self, mut event: ProgressEvent) -> Option<ProgressEvent> {
+        if self.finished_at.is_some() {
+            if event.stage.starts_run() {
+                self.material = None;
+                self.percent = 0;
+                self.finished_at = None;
+            } else if event.stage != ProgressStage::Callback {
+                return None;
+            }
+        }
+
+        if event.material_index.is_none() {
+            if let Some(position) = &self.material {
+                if matches!(event.stage, ProgressStage::Ocr | ProgressStage::Evaluating) {
+                    event.material_code = Some(position.code.clone());
+                    event.material_index = Some(position.index);
+                    event.material_total = Some(position.total);
+                }
+            }
+        }
+
+        let percent = match event.stage {
+            // 转发来的事件已由 worker 计算进度
+            _ if event.percent > 0 => event.percent,
+            ProgressStage::Completed => 100,
+            ProgressStage::Failed | ProgressStage::Cancelled => self.percent,
+            // 结束后才提交的回调不再推进进度
+            ProgressStage::Callback if self.finished_at.is_some() => self.percent,
+            stage => estimate_percent(stage, &event),
+        };
+        self.percent = self.percent.max(percent.min(100));
+        self.seq += 1;
+        event.seq = self.seq;
+        event.percent = self.percent;
+
+        self.touched_at = Instant::now();
+        if event.stage.is_terminal() {
+            self.finished_at = Some(Instant::now());
+        }
+        self.last = Some(event.clone());
+        Some(event)
+    }
+}
+
+/// 各阶段占整体进度的区间：入队 2%，下载至 15%，OCR 至 80%，之后依次是评估、报告、回调
+fn estimate_percent(stage: ProgressStage, event: &ProgressEvent) -> u8 {
+    let fraction = match (event.current, event.total) {
+        (Some(current), Some(total)) if total > 0 => current.min(total) as f64 / total as f64,
+        _ => 0.0,
+    };
+    let material = match (event.material_index, event.material_total) {
+        (Some(index), Some(total)) if total > 0 => Some((index.saturating_sub(1), total)),
+        _ => None,
+    };
+    let value = match stage {
+        ProgressStage::Queued => 2.0,
+        ProgressStage::Downloading => 2.0 + 13.0 * fraction,
+        ProgressStage::Ocr => {
+            let done = match material {
+                Some((index, total)) => (index as f64 + fraction) / total as f64,
+                None => fraction,
+            };
+            15.0 + 65.0 * done
+        }
+        ProgressStage::Evaluating => match material {
+            Some((index, total)) => 15.0 + 65.0 * (index + 1) as f64 / total as f64,
+            None => 80.0,
+        },
+        ProgressStage::Report => 88.0,
+        ProgressStage::Callback => 95.0,
+        ProgressStage::Completed | ProgressStage::Failed | ProgressStage::Cancelled => 100.0,
+    };
+    value.round() as u8
+}
+
+fn publish(event: ProgressEvent) {
+    let forwarded = {
+        let mut hub = HUB.lock();
+        if !hub.contains_key(&event.preview_id) {
+            hub.retain(|_, channel| !channel.expired());
+        }
+        let channel = hub
+            .entry(event.preview_id.clone())
+            .or_insert_with(Channel::new);
+        let Some(event) = channel.apply(event) else {
+            return;
+        };
+        // 没有订阅者时发送失败，忽略即可
+        let _ = channel.sender.send(event.clone());
+        event
+    };
+
+    if let Some(forwarder) = FORWARDER.get() {
+        let _ = forwarder.send(forwarded);
+    }
+}
+
+/// worker 进程调用：之后发布的事件同时交给转发器上报主节点
+pub fn set_forwarder(sender: mpsc::UnboundedSender<ProgressEvent>) -> bool {
+    FORWARDER.set(sender).is_ok()
+}
+
+/// 主节点接收 worker 上报的事件
+pub fn relay(event: ProgressEvent) {
+    // 终态以主节点写入的状态为准，忽略 worker 上报的终态
+    if event.stage.is_terminal() {
+        return;
+    }
+    let mut hub = HUB.lock();
+    let channel = hub
+        .entry(event.preview_id.clone())
+        .or_insert_with(Channel::new);
+    if let Some(event) = channel.apply(event) {
+        let _ = channel.sender.send(event);
+    }
+}
+
+/// 返回最近一条事件与后续事件的接收端
+pub fn subscribe(preview_id: &str) -> (Option<ProgressEvent>, broadcast::Receiver<ProgressEvent>) {
+    let mut hub = HUB.lock();
+    let channel = hub
+        .entry(preview_id.to_string())
+        .or_insert_with(Channel::new);
+    (channel.last.clone(), channel.sender.subscribe())
+}
+
+pub fn queued(preview_id: &str) {
+    publish(ProgressEvent::new(preview_id, ProgressStage::Queued).message("已进入处理队列"));
+}
+
+pub fn downloading(preview_id: &str, current: usize, total: usize) {
+    publish(
+        ProgressEvent::new(preview_id, ProgressStage::Downloading)
+            .step(current as u32, total as u32)
+            .message(format!("正在下载附件 {}/{}", current, total)),
+    );
+}
+
+/// 评估开始处理某份材料，后续 OCR 与评估事件都归属这份材料
+pub fn material(preview_id: &str, index: usize, total: usize, code: &str) {
+    let mut hub = HUB.lock();
+    let channel = hub
+        .entry(preview_id.to_string())
+        .or_insert_with(Channel::new);
+    channel.material = Some(MaterialPosition {
+        code: code.to_string(),
+        index: index as u32 + 1,
+        total: total as u32,
+    });
+}
+
+pub fn ocr_page(preview_id: &str, page: u32, pages: u32) {
+    publish(
+        ProgressEvent::new(preview_id, ProgressStage::Ocr)
+            .step(page, pages)
+            .message(format!("正在识别第 {}/{} 页", page, pages)),
+    );
+}
+
+pub fn evaluating(preview_id: &str) {
+    publish(ProgressEvent::new(preview_id, ProgressStage::Evaluating).message("正在评估规则"));
+}
+
+pub fn report(preview_id: &str) {
+    publish(ProgressEvent::new(preview_id, ProgressStage::Report).message("正在生成报告"));
+}
+
+pub fn callback(preview_id: &str) {
+    publish(ProgressEvent::new(preview_id, ProgressStage::Callback).message("已提交结果回调"));
+}
+
+/// 按预审状态发布事件，非入队或终态的状态忽略
+pub fn status(preview_id: &str, status: &str) {
+    match ProgressStage::from_status(status) {
+        Some(ProgressStage::Queued) => queued(preview_id),
+        Some(stage) => publish(ProgressEvent::new(preview_id, stage).message(match stage {
+            ProgressStage::Completed => "预审完成",
+            ProgressStage::Cancelled => "预审已取消",
+            _ => "预审失败",
+        })),
+        None => {}
+    }
+}
+
+/// 没有进度记录时（如主节点重启后），由数据库中的状态构造一条事件
+pub fn snapshot_from_status(preview_id: &str, status: &str) -> Option<ProgressEvent> {
+    let stage = ProgressStage::from_status(status)?;
+    let mut event = ProgressEvent::new(preview_id, stage);
+    event.percent = match stage {
+        ProgressStage::Completed => 100,
+        _ => estimate_percent(stage, &event),
+    };
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_follows_materials_and_never_goes_back() {
+        let id = "progress-materials";
+        let (_, mut rx) = subscribe(id);
+
+        queued(id);
+        material(id, 0, 2, "A");
+        ocr_page(id, 2, 2);
+        material(id, 1, 2, "B");
+        ocr_page(id, 1, 4);
+        evaluating(id);
+
+        let events: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        let percents: Vec<u8> = events.iter().map(|e| e.percent).collect();
+        assert_eq!(percents, vec![2, 48, 56, 80]);
+        assert_eq!(events[2].material_code.as_deref(), Some("B"));
+        assert_eq!(events[2].material_index, Some(2));
+        assert!(events.windows(2).all(|w| w[0].seq < w[1].seq));
+    }
+
+    #[test]
+    fn finished_preview_drops_stale_events_until_rerun() {
+        let id = "progress-rerun";
+        ocr_page(id, 1, 2);
+        status(id, "cancelled");
+        ocr_page(id, 2, 2);
+
+        let (last, _) = subscribe(id);
+        let last = last.unwrap();
+        assert_eq!(last.stage, ProgressStage::Cancelled);
+        assert_eq!(last.percent, 48);
+
+        callback(id);
+        assert_eq!(subscribe(id).0.unwrap().stage, ProgressStage::Callback);
+
+        queued(id);
+        let last = subscribe(id).0.unwrap();
+        assert_eq!(last.stage, ProgressStage::Queued);
+        assert_eq!(last.percent, 2);
+    }
+}
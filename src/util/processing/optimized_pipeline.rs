@@ -1,8 +1,13 @@
 //!
 
 use crate::util::logging::standards::events;
+use crate::util::ocr_cache;
+use crate::util::ocr_export;
+use crate::util::preview_cancel;
+use crate::util::preview_progress;
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 use anyhow::Result;
+use ocr_conn::ocr::ContentData;
 use serde_json::json;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -14,6 +19,8 @@ use std::time::Instant;
 use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, error, info, warn};
 
+const COMPRESSED_RENDER_DPI: u32 = 120;
+
 pub struct OptimizedPdfOcrPipeline {
     pdf_semaphore: Arc<Semaphore>,
     convert_semaphore: Arc<Semaphore>,
@@ -237,6 +244,7 @@ impl OptimizedPdfOcrPipeline {
         request_id: String,
         material_code: String,
         storage: Option<Arc<dyn crate::storage::Storage>>,
+        bypass_cache: bool,
     ) -> Result<Vec<String>> {
         let start_time = std::time::Instant::now();
         let cfg = self.config.read().unwrap().clone();
@@ -274,8 +282,14 @@ impl OptimizedPdfOcrPipeline {
             batch_total = batches.len()
         );
 
+        let total_pages = batches
+            .last()
+            .and_then(|batch| batch.page_ranges.last())
+            .map(|(_, end)| *end)
+            .unwrap_or(0);
         let mut all_ocr_results = Vec::new();
         for (batch_index, batch) in batches.into_iter().enumerate() {
+            preview_cancel::check(&request_id)?;
             debug!(
                 target: "processing.pipeline",
                 event = events::PIPELINE_STAGE,
@@ -285,10 +299,11 @@ impl OptimizedPdfOcrPipeline {
             );
 
             let batch_results = self
-                .process_batch_optimized(batch, request_id.clone(), storage.clone())
+                .process_batch_optimized(batch, request_id.clone(), storage.clone(), bypass_cache)
                 .await?;
 
             all_ocr_results.extend(batch_results);
+            preview_progress::ocr_page(&request_id, all_ocr_results.len() as u32, total_pages);
 
             if batch_index % 2 == 0 {
                 self.memory_monitor
@@ -327,6 +342,69 @@ impl OptimizedPdfOcrPipeline {
         Ok(all_ocr_results)
     }
 
+    /// 识别 `[start_page, end_page]` 页段，供 PDF 分片使用；不上报进度，
+    /// 任一页识别失败即返回错误，保证结果与页号一一对应
+    pub async fn process_pdf_range(
+        &self,
+        pdf_path: PathBuf,
+        request_id: String,
+        material_code: String,
+        storage: Option<Arc<dyn crate::storage::Storage>>,
+        bypass_cache: bool,
+        start_page: u32,
+        end_page: u32,
+    ) -> Result<Vec<String>> {
+        let start_time = std::time::Instant::now();
+        let cfg = self.config.read().unwrap().clone();
+        self.validate_pdf_input(&pdf_path).await?;
+        self.adaptive_tuning().await;
+
+        let batch_size = cfg.batch_size.max(1);
+        let mut results = Vec::with_capacity((end_page + 1).saturating_sub(start_page) as usize);
+        let mut current_page = start_page.max(1);
+        while current_page <= end_page {
+            preview_cancel::check(&request_id)?;
+            let batch_end = (current_page + batch_size - 1).min(end_page);
+            // 同一附件的多个分片可能在同一节点并行，批次号取起始页避免临时文件重名
+            let batch = ProcessingBatch {
+                material_code: material_code.clone(),
+                page_ranges: vec![(current_page, batch_end)],
+                pdf_path: pdf_path.clone(),
+                batch_id: current_page as usize,
+            };
+            let batch_results = self
+                .process_batch_optimized(batch, request_id.clone(), storage.clone(), bypass_cache)
+                .await?;
+            let expected = (batch_end - current_page + 1) as usize;
+            if batch_results.len() != expected {
+                return Err(anyhow::anyhow!(
+                    "第 {}-{} 页识别不完整: {}/{}",
+                    current_page,
+                    batch_end,
+                    batch_results.len(),
+                    expected
+                ));
+            }
+            results.extend(batch_results);
+            current_page = batch_end + 1;
+        }
+
+        self.performance_tracker.record_processing(
+            results.len() as u32,
+            start_time.elapsed().as_millis() as u64,
+        );
+        info!(
+            target: "processing.pipeline",
+            event = events::PIPELINE_STAGE,
+            stage = "range_complete",
+            material_code = %material_code,
+            page_start = start_page,
+            page_end = end_page,
+            duration_ms = start_time.elapsed().as_millis() as u64
+        );
+        Ok(results)
+    }
+
     async fn validate_pdf_input(&self, pdf_path: &PathBuf) -> Result<()> {
         let cfg = self.config.read().unwrap().clone();
         let metadata = tokio::fs::metadata(pdf_path).await?;
@@ -399,6 +477,7 @@ impl OptimizedPdfOcrPipeline {
         batch: ProcessingBatch,
         request_id: String,
         storage: Option<Arc<dyn crate::storage::Storage>>,
+        bypass_cache: bool,
     ) -> Result<Vec<String>> {
         let batch_start = std::time::Instant::now();
         let _convert_permit = self.convert_semaphore.acquire().await?;
@@ -418,27 +497,59 @@ impl OptimizedPdfOcrPipeline {
 
         drop(_convert_permit);
 
+        let render_dpi = if self.config.read().unwrap().enable_compression {
+            Some(COMPRESSED_RENDER_DPI)
+        } else {
+            None
+        };
+        let first_page = batch.page_ranges[0].0;
         let mut ocr_tasks = Vec::new();
         for (index, image_path) in image_paths.iter().enumerate() {
             let image_path = image_path.clone();
             let request_id = request_id.clone();
+            let material_code = batch.material_code.clone();
+            let page_no = first_page + index as u32;
             let storage = storage.clone();
             let semaphore = self.ocr_semaphore.clone();
             let upload_semaphore = self.upload_semaphore.clone();
 
             let task = tokio::spawn(async move {
                 let _ocr_permit = semaphore.acquire().await?;
+                if let Err(err) = preview_cancel::check(&request_id) {
+                    let _ = tokio::fs::remove_file(&image_path).await;
+                    return Err(err);
+                }
 
-                let ocr_result = Self::process_single_image_ocr(&image_path).await?;
+                let contents = Self::process_single_image_ocr(
+                    &image_path,
+                    storage.as_ref(),
+                    render_dpi,
+                    bypass_cache,
+                )
+                .await?;
 
                 if let Some(storage) = storage {
                     let _upload_permit = upload_semaphore.acquire().await?;
-                    Self::upload_image_to_oss(&image_path, &request_id, index, storage).await?;
+                    Self::upload_page_image(
+                        &image_path,
+                        &request_id,
+                        &material_code,
+                        page_no,
+                        render_dpi,
+                        &contents,
+                        storage,
+                    )
+                    .await?;
                 }
 
                 let _ = tokio::fs::remove_file(&image_path).await;
 
-                Ok::<String, anyhow::Error>(ocr_result)
+                let text = contents
+                    .into_iter()
+                    .map(|content| content.text)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok::<String, anyhow::Error>(text)
             });
 
             ocr_tasks.push(task);
@@ -501,7 +612,7 @@ impl OptimizedPdfOcrPipeline {
         let mut render_builder = RenderOptionsBuilder::default();
         if cfg.enable_compression {
             render_builder
-                .resolution(DPI::Uniform(120))
+                .resolution(DPI::Uniform(COMPRESSED_RENDER_DPI))
                 .greyscale(true)
                 .pdftocairo(true);
         }
@@ -568,45 +679,71 @@ impl OptimizedPdfOcrPipeline {
         Ok(image_paths)
     }
 
-    async fn process_single_image_ocr(image_path: &PathBuf) -> Result<String> {
+    async fn process_single_image_ocr(
+        image_path: &PathBuf,
+        storage: Option<&Arc<dyn crate::storage::Storage>>,
+        render_dpi: Option<u32>,
+        bypass_cache: bool,
+    ) -> Result<Vec<ContentData>> {
         use ocr_conn::ocr::GLOBAL_POOL;
 
-        let mut handle = GLOBAL_POOL
-            .acquire()
-            .await
-            .map_err(|e| anyhow::anyhow!("获取OCR引擎失败: {}", e))?;
-
-        let ocr_started = Instant::now();
-        let ocr_result = handle.ocr_and_parse(image_path.clone().into());
-        let duration = ocr_started.elapsed();
-        METRICS_COLLECTOR.record_ocr_invocation(ocr_result.is_ok(), duration);
-        let contents = ocr_result.map_err(|e| anyhow::anyhow!("OCR识别失败: {}", e))?;
+        // 引擎预处理会原地改写图片，缓存键取识别前的渲染结果
+        let image_bytes = match storage {
+            Some(_) => tokio::fs::read(image_path).await.ok(),
+            None => None,
+        };
+        let cached = match (storage, image_bytes.as_deref()) {
+            (Some(storage), Some(bytes)) if !bypass_cache => {
+                ocr_cache::lookup(storage, bytes, render_dpi).await
+            }
+            _ => None,
+        };
 
-        let text = contents
-            .into_iter()
-            .map(|content| content.text)
-            .collect::<Vec<_>>()
-            .join("\n");
+        let contents = match cached {
+            Some(contents) => contents,
+            None => {
+                let mut handle = GLOBAL_POOL
+                    .acquire()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("获取OCR引擎失败: {}", e))?;
+
+                let ocr_started = Instant::now();
+                let ocr_result = handle.ocr_and_parse(image_path.clone().into());
+                let duration = ocr_started.elapsed();
+                METRICS_COLLECTOR.record_ocr_invocation(ocr_result.is_ok(), duration);
+                drop(handle);
+                let contents = ocr_result.map_err(|e| anyhow::anyhow!("OCR识别失败: {}", e))?;
+                if let (Some(storage), Some(bytes)) = (storage, image_bytes.as_deref()) {
+                    ocr_cache::store(storage, bytes, render_dpi, &contents).await;
+                }
+                contents
+            }
+        };
 
-        Ok(text)
+        Ok(contents)
     }
 
-    async fn upload_image_to_oss(
+    /// 与逐页识别路径使用相同的 `converted/page-N.jpg` 布局，并记录文本框供导出使用
+    async fn upload_page_image(
         image_path: &PathBuf,
         request_id: &str,
-        index: usize,
+        material_code: &str,
+        page_no: u32,
+        render_dpi: Option<u32>,
+        contents: &[ContentData],
         storage: Arc<dyn crate::storage::Storage>,
     ) -> Result<()> {
         let image_content = tokio::fs::read(image_path).await?;
 
         let oss_key = format!(
-            "materials/{}/images/{}_{}.jpg",
-            request_id,
-            image_path.file_stem().unwrap().to_str().unwrap(),
-            index
+            "{}page-{}.jpg",
+            ocr_export::converted_prefix(request_id, material_code),
+            page_no
         );
 
         storage.put(&oss_key, &image_content).await?;
+        ocr_export::store_page_boxes(&storage, &oss_key, &image_content, render_dpi, contents)
+            .await;
         debug!(
             target: "processing.pipeline",
             event = events::PIPELINE_STAGE,
@@ -75,6 +75,9 @@ impl PreviewReportGenerator {
             );
         }
 
+        // 2.6. 签发文件链接；归档的 HTML/PDF 是快照，链接在 link_ttl_secs 后过期，下载接口会重新渲染
+        crate::api::worker_proxy::sign_preview_links(database, preview_id, &mut result).await;
+
         // 3. Generate HTML
         let html_content = Self::generate_html(&result);
 
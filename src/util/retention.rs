@@ -0,0 +1,569 @@
+//! 预审产物留存清理
+//!
+//! 按产物类别（原始请求、OCR 文本、页面图片、报告、日志）与事项配置保留天数，
+//! 由主节点定期清理到期产物：先删除存储中的文件，再在数据库中清空对应内容、
+//! 标记该类已清理并写入审计。存储删除成功而数据库失败时，该预审下一轮仍是候选，
+//! 删除是幂等的，会自然补齐。法律保全中的预审不会成为候选，数据库清理时也会再次校验。
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+use crate::api::worker_proxy::sanitize_for_fs;
+use crate::db::traits::{
+    ArtifactPurge, CachedMaterialFilter, MaterialFileFilter, RetentionArtifact, RetentionCandidate,
+    RetentionCandidateQuery,
+};
+use crate::db::Database;
+use crate::storage::Storage;
+use crate::util::config::types::{DeploymentRole, RetentionConfig, RetentionPolicy};
+use crate::AppState;
+
+pub const REASON_RETENTION: &str = "retention";
+
+static PURGER_TASK: OnceCell<()> = OnceCell::new();
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionRunSummary {
+    /// 清理的（预审, 产物类别）组合数
+    pub purged: usize,
+    pub storage_keys: usize,
+    pub db_rows: i64,
+    /// 清理前被设为法律保全或已删除的预审
+    pub skipped: usize,
+    pub failures: usize,
+}
+
+fn policy_days(policy: &RetentionPolicy, artifact: RetentionArtifact) -> Option<u32> {
+    match artifact {
+        RetentionArtifact::RawRequest => policy.raw_request_days,
+        RetentionArtifact::OcrText => policy.ocr_text_days,
+        RetentionArtifact::PageImages => policy.page_image_days,
+        RetentionArtifact::Reports => policy.report_days,
+        RetentionArtifact::Logs => policy.log_days,
+    }
+}
+
+/// 该类产物在该事项下的保留天数；None 表示永久保留
+pub fn retention_days(
+    config: &RetentionConfig,
+    artifact: RetentionArtifact,
+    matter_id: Option<&str>,
+) -> Option<u32> {
+    matter_id
+        .filter(|_| artifact != RetentionArtifact::Logs)
+        .and_then(|matter_id| config.matters.get(matter_id))
+        .and_then(|policy| policy_days(policy, artifact))
+        .or_else(|| policy_days(&config.default, artifact))
+        .filter(|days| *days > 0)
+}
+
+/// 按存储路径约定判断 key 属于该预审的哪类产物
+pub fn classify_key(preview_id: &str, key: &str) -> Option<RetentionArtifact> {
+    let key = key.replace('\\', "/");
+    let key = key.trim_start_matches('/');
+
+    if let Some(rest) = key.strip_prefix(&format!("uploads/{}/", preview_id)) {
+        return match rest.split('/').nth(1) {
+            Some("original") => Some(RetentionArtifact::RawRequest),
+            Some("ocr") => Some(RetentionArtifact::OcrText),
+            Some("converted") => Some(RetentionArtifact::PageImages),
+            _ => None,
+        };
+    }
+    let safe_id = sanitize_for_fs(preview_id);
+    if let Some(rest) = key.strip_prefix(&format!("previews/{}/materials/", safe_id)) {
+        return if rest.split('/').nth(1) == Some("preview") && rest.matches('/').count() >= 2 {
+            Some(RetentionArtifact::PageImages)
+        } else {
+            Some(RetentionArtifact::RawRequest)
+        };
+    }
+    if key.starts_with(&format!("cache/{}/", preview_id)) {
+        return Some(RetentionArtifact::RawRequest);
+    }
+    let reports = [
+        format!("previews/{}.html", preview_id),
+        format!("previews/{}.pdf", preview_id),
+        format!("previews/{0}/{0}_report.html", preview_id),
+        format!("previews/{0}/{0}_report.pdf", preview_id),
+    ];
+    if reports.iter().any(|report| report == key) {
+        return Some(RetentionArtifact::Reports);
+    }
+    None
+}
+
+/// 列出该预审某类产物的存储 key。本地存储的 list 不递归，按材料编码逐个目录列出；
+/// 对象存储按前缀递归列出，两者结果合并去重
+async fn collect_storage_keys(
+    database: &Arc<dyn Database>,
+    storage: &Arc<dyn Storage>,
+    preview_id: &str,
+    artifact: RetentionArtifact,
+) -> Result<Vec<String>> {
+    let safe_id = sanitize_for_fs(preview_id);
+    let mut prefixes = Vec::new();
+
+    if artifact == RetentionArtifact::Reports {
+        prefixes.push(format!("previews/{}/", preview_id));
+        let mut keys = BTreeSet::new();
+        for key in [
+            format!("previews/{}.html", preview_id),
+            format!("previews/{}.pdf", preview_id),
+        ] {
+            if storage.exists(&key).await? {
+                keys.insert(key);
+            }
+        }
+        for key in storage.list(&prefixes[0]).await? {
+            if classify_key(preview_id, &key) == Some(artifact) {
+                keys.insert(key);
+            }
+        }
+        return Ok(keys.into_iter().collect());
+    }
+
+    let mut codes = BTreeSet::new();
+    match database
+        .list_material_files(&MaterialFileFilter {
+            preview_id: Some(preview_id.to_string()),
+            material_code: None,
+        })
+        .await
+    {
+        Ok(files) => codes.extend(files.into_iter().map(|f| f.material_code)),
+        Err(err) => debug!(preview_id = %preview_id, error = %err, "读取材料文件记录失败"),
+    }
+    if let Ok(cached) = database
+        .list_cached_material_records(&CachedMaterialFilter {
+            preview_id: Some(preview_id.to_string()),
+            ..Default::default()
+        })
+        .await
+    {
+        codes.extend(cached.into_iter().map(|c| c.material_code));
+    }
+
+    prefixes.push(format!("uploads/{}/", preview_id));
+    prefixes.push(format!("previews/{}/materials/", safe_id));
+    if artifact == RetentionArtifact::RawRequest {
+        prefixes.push(format!("cache/{}/", preview_id));
+    }
+    for code in &codes {
+        let safe_code = sanitize_for_fs(code);
+        match artifact {
+            RetentionArtifact::RawRequest => {
+                prefixes.push(format!("uploads/{}/{}/original/", preview_id, code));
+                prefixes.push(format!("previews/{}/materials/{}/", safe_id, safe_code));
+            }
+            RetentionArtifact::OcrText => {
+                prefixes.push(format!("uploads/{}/{}/ocr/", preview_id, code));
+            }
+            RetentionArtifact::PageImages => {
+                prefixes.push(format!("uploads/{}/{}/converted/", preview_id, code));
+                prefixes.push(format!(
+                    "previews/{}/materials/{}/preview/",
+                    safe_id, safe_code
+                ));
+            }
+            RetentionArtifact::Reports | RetentionArtifact::Logs => {}
+        }
+    }
+
+    let mut keys = BTreeSet::new();
+    for prefix in &prefixes {
+        for key in storage.list(prefix).await? {
+            if classify_key(preview_id, &key) == Some(artifact) {
+                keys.insert(key);
+            }
+        }
+    }
+    Ok(keys.into_iter().collect())
+}
+
+/// 清理单个预审的某类产物，返回删除的存储 key 数与数据库行数；被保全时返回 None
+async fn purge_candidate(
+    database: &Arc<dyn Database>,
+    storage: &Arc<dyn Storage>,
+    candidate: &RetentionCandidate,
+    artifact: RetentionArtifact,
+    reason: &str,
+) -> Result<Option<(usize, i64)>> {
+    let keys = collect_storage_keys(database, storage, &candidate.preview_id, artifact).await?;
+    for key in &keys {
+        storage.delete(key).await?;
+    }
+    let purge = ArtifactPurge {
+        preview_id: Some(candidate.preview_id.clone()),
+        matter_id: candidate.matter_id.clone(),
+        artifact,
+        storage_keys: keys,
+        reason: reason.to_string(),
+    };
+    Ok(database
+        .purge_preview_artifact(&purge)
+        .await?
+        .map(|db_rows| (purge.storage_keys.len(), db_rows)))
+}
+
+/// 按配置执行一轮清理。每类产物分别处理默认策略与各事项的覆盖策略，
+/// 默认策略排除有覆盖值的事项，避免按较短的默认期限误删
+pub async fn run_once(
+    database: &Arc<dyn Database>,
+    storage: &Arc<dyn Storage>,
+    config: &RetentionConfig,
+    reason: &str,
+) -> Result<RetentionRunSummary> {
+    let mut summary = RetentionRunSummary::default();
+    let batch_size = config.batch_size.max(1);
+
+    for artifact in RetentionArtifact::PER_PREVIEW {
+        let overridden: Vec<String> = config
+            .matters
+            .iter()
+            .filter(|(_, policy)| policy_days(policy, artifact).is_some())
+            .map(|(matter_id, _)| matter_id.clone())
+            .collect();
+
+        let mut scopes = vec![(
+            None,
+            overridden.clone(),
+            retention_days(config, artifact, None),
+        )];
+        scopes.extend(overridden.iter().map(|matter_id| {
+            (
+                Some(matter_id.clone()),
+                Vec::new(),
+                retention_days(config, artifact, Some(matter_id)),
+            )
+        }));
+
+        for (matter_id, exclude_matters, days) in scopes {
+            let Some(days) = days else {
+                continue;
+            };
+            let query = RetentionCandidateQuery {
+                artifact,
+                created_before: Utc::now() - chrono::Duration::days(days as i64),
+                matter_id,
+                exclude_matters,
+                limit: batch_size,
+            };
+            loop {
+                let candidates = database.list_retention_candidates(&query).await?;
+                // 删除存储前再确认一次保全状态，缩小列出候选与删除之间的竞争窗口
+                let held: BTreeSet<String> = database
+                    .list_legal_holds()
+                    .await?
+                    .into_iter()
+                    .map(|hold| hold.preview_id)
+                    .collect();
+                let mut progressed = false;
+                for candidate in &candidates {
+                    if held.contains(&candidate.preview_id) {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    match purge_candidate(database, storage, candidate, artifact, reason).await {
+                        Ok(Some((keys, db_rows))) => {
+                            progressed = true;
+                            summary.purged += 1;
+                            summary.storage_keys += keys;
+                            summary.db_rows += db_rows;
+                            // 检索索引中的 OCR 文本与评估结果随之更新
+                            if matches!(
+                                artifact,
+                                RetentionArtifact::OcrText | RetentionArtifact::Reports
+                            ) {
+                                crate::util::search::schedule_index(
+                                    Arc::clone(database),
+                                    candidate.preview_id.clone(),
+                                );
+                            }
+                        }
+                        Ok(None) => summary.skipped += 1,
+                        Err(err) => {
+                            summary.failures += 1;
+                            warn!(
+                                preview_id = %candidate.preview_id,
+                                artifact = artifact.as_str(),
+                                error = %err,
+                                "清理预审产物失败，下一轮重试"
+                            );
+                        }
+                    }
+                }
+                if candidates.len() < batch_size as usize || !progressed {
+                    break;
+                }
+            }
+        }
+    }
+
+    if let Some(days) = retention_days(config, RetentionArtifact::Logs, None) {
+        let purge = ArtifactPurge {
+            preview_id: None,
+            matter_id: None,
+            artifact: RetentionArtifact::Logs,
+            storage_keys: Vec::new(),
+            reason: reason.to_string(),
+        };
+        let before = Utc::now() - chrono::Duration::days(days as i64);
+        match database.purge_log_records(before, &purge).await {
+            Ok(rows) if rows > 0 => {
+                summary.purged += 1;
+                summary.db_rows += rows;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                summary.failures += 1;
+                warn!(error = %err, "清理日志记录失败");
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// 由 master 定期按保留期清理
+pub fn spawn_purger(app_state: &AppState) {
+    let config = app_state.config.retention.clone();
+    if !config.enabled || app_state.config.deployment.role == DeploymentRole::Worker {
+        return;
+    }
+    if PURGER_TASK.set(()).is_err() {
+        return;
+    }
+
+    let database = Arc::clone(&app_state.database);
+    let storage = Arc::clone(&app_state.storage);
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(config.interval_secs.max(60)));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match run_once(&database, &storage, &config, REASON_RETENTION).await {
+                Ok(summary) if summary.purged > 0 || summary.failures > 0 => {
+                    info!(
+                        purged = summary.purged,
+                        storage_keys = summary.storage_keys,
+                        db_rows = summary.db_rows,
+                        failures = summary.failures,
+                        "留存清理完成"
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "留存清理失败"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MigrationRunner;
+    use crate::db::sqlite::SqliteDatabase;
+    use crate::db::traits::{
+        LegalHoldUpdate, MaterialFileRecord, PreviewRecord, PreviewStatus, PurgeAuditFilter,
+    };
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn keys_are_classified_by_path_convention() {
+        let id = "p-1";
+        let cases = [
+            (
+                "uploads/p-1/m1/original/a-0.pdf",
+                Some(RetentionArtifact::RawRequest),
+            ),
+            ("uploads/p-1/m1/ocr/x.txt", Some(RetentionArtifact::OcrText)),
+            (
+                "uploads/p-1/m1/converted/page-1.jpg",
+                Some(RetentionArtifact::PageImages),
+            ),
+            (
+                "previews/p-1/materials/m1/a.pdf",
+                Some(RetentionArtifact::RawRequest),
+            ),
+            (
+                "previews/p-1/materials/m1/preview/a-preview.jpg",
+                Some(RetentionArtifact::PageImages),
+            ),
+            ("cache/p-1/m1_0.bin", Some(RetentionArtifact::RawRequest)),
+            ("previews/p-1.pdf", Some(RetentionArtifact::Reports)),
+            (
+                "previews/p-1/p-1_report.html",
+                Some(RetentionArtifact::Reports),
+            ),
+            ("uploads/p-10/m1/ocr/x.txt", None),
+            ("ocr-cache/abc.json", None),
+        ];
+        for (key, expected) in cases {
+            assert_eq!(classify_key(id, key), expected, "{}", key);
+        }
+    }
+
+    #[test]
+    fn matter_override_falls_back_to_default_and_zero_keeps_forever() {
+        let mut config = RetentionConfig::default();
+        config.default.ocr_text_days = Some(30);
+        config.default.report_days = Some(365);
+        config.matters.insert(
+            "m-keep".to_string(),
+            RetentionPolicy {
+                report_days: Some(0),
+                ocr_text_days: Some(7),
+                log_days: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let days = |artifact, matter| retention_days(&config, artifact, matter);
+        assert_eq!(days(RetentionArtifact::OcrText, Some("m-keep")), Some(7));
+        assert_eq!(days(RetentionArtifact::OcrText, Some("other")), Some(30));
+        assert_eq!(days(RetentionArtifact::Reports, Some("m-keep")), None);
+        assert_eq!(days(RetentionArtifact::RawRequest, None), None);
+        // 日志不区分事项
+        assert_eq!(days(RetentionArtifact::Logs, Some("m-keep")), None);
+    }
+
+    fn old_record(id: &str) -> PreviewRecord {
+        let created_at = Utc::now() - chrono::Duration::days(60);
+        PreviewRecord {
+            id: id.to_string(),
+            user_id: "u1".to_string(),
+            user_info_json: None,
+            file_name: "a.pdf".to_string(),
+            ocr_text: "识别文本".to_string(),
+            theme_id: None,
+            evaluation_result: Some("{}".to_string()),
+            preview_url: String::new(),
+            preview_view_url: None,
+            preview_download_url: None,
+            status: PreviewStatus::Completed,
+            created_at,
+            updated_at: created_at,
+            third_party_request_id: None,
+            queued_at: None,
+            processing_started_at: None,
+            retry_count: 0,
+            last_worker_id: None,
+            last_attempt_id: None,
+            failure_reason: None,
+            ocr_stderr_summary: None,
+            failure_context: None,
+            last_error_code: None,
+            slow_attachment_info_json: None,
+            callback_url: None,
+            callback_status: None,
+            callback_attempts: 0,
+            callback_successes: 0,
+            callback_failures: 0,
+            last_callback_at: None,
+            last_callback_status_code: None,
+            last_callback_response: None,
+            last_callback_error: None,
+            callback_payload: None,
+            next_callback_after: None,
+        }
+    }
+
+    fn material_file(preview_id: &str) -> MaterialFileRecord {
+        MaterialFileRecord {
+            id: format!("{}-f1", preview_id),
+            preview_id: preview_id.to_string(),
+            material_code: "m1".to_string(),
+            attachment_name: None,
+            source_url: None,
+            stored_original_key: format!("uploads/{}/m1/original/a-0.pdf", preview_id),
+            stored_processed_keys: None,
+            mime_type: None,
+            size_bytes: None,
+            checksum_sha256: None,
+            ocr_text_key: Some(format!("uploads/{}/m1/ocr/t.txt", preview_id)),
+            ocr_text_length: Some(4),
+            status: "completed".to_string(),
+            error_message: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_once_purges_expired_artifacts_and_honours_legal_hold() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite = SqliteDatabase::new(dir.path().join("r.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.initialize().await.unwrap();
+        MigrationRunner::new(&sqlite).migrate_up().await.unwrap();
+        let database: Arc<dyn Database> = Arc::new(sqlite);
+        let storage: Arc<dyn Storage> =
+            Arc::new(LocalStorage::new(dir.path().join("files"), "http://localhost").unwrap());
+
+        for id in ["p-old", "p-held"] {
+            database.save_preview_record(&old_record(id)).await.unwrap();
+            database
+                .save_material_file_record(&material_file(id))
+                .await
+                .unwrap();
+            for key in [
+                format!("uploads/{}/m1/original/a-0.pdf", id),
+                format!("uploads/{}/m1/ocr/t.txt", id),
+                format!("uploads/{}/m1/converted/page-1.jpg", id),
+            ] {
+                storage.put(&key, b"x").await.unwrap();
+            }
+        }
+        database
+            .set_preview_legal_hold(&LegalHoldUpdate {
+                preview_id: "p-held".to_string(),
+                hold: true,
+                reason: Some("复议中".to_string()),
+                updated_by: "admin".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let mut config = RetentionConfig::default();
+        config.default.ocr_text_days = Some(30);
+        let summary = run_once(&database, &storage, &config, REASON_RETENTION)
+            .await
+            .unwrap();
+        assert_eq!(summary.purged, 1);
+        assert_eq!(summary.storage_keys, 1);
+        assert_eq!(summary.failures, 0);
+
+        assert!(!storage.exists("uploads/p-old/m1/ocr/t.txt").await.unwrap());
+        assert!(storage
+            .exists("uploads/p-old/m1/converted/page-1.jpg")
+            .await
+            .unwrap());
+        assert!(storage.exists("uploads/p-held/m1/ocr/t.txt").await.unwrap());
+        let record = database.get_preview_record("p-old").await.unwrap().unwrap();
+        assert!(record.ocr_text.is_empty());
+
+        let audit = database
+            .list_purge_audit(&PurgeAuditFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].preview_id.as_deref(), Some("p-old"));
+        assert_eq!(audit[0].storage_keys, vec!["uploads/p-old/m1/ocr/t.txt"]);
+
+        // 第二轮没有新的到期产物
+        let again = run_once(&database, &storage, &config, REASON_RETENTION)
+            .await
+            .unwrap();
+        assert_eq!(again.purged, 0);
+    }
+}
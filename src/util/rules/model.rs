@@ -214,6 +214,9 @@ pub struct MaterialRepeat {
     pub case_list: String,
     pub case_key_field: String,
     pub ocr_key_field: String,
+    /// 材料表格中对应列的表头候选（如“号牌号码”），为空时按 `ocr_key_field` 匹配
+    #[serde(default)]
+    pub table_headers: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,396 @@
+//! 预审全文检索
+//!
+//! 预审结束（完成、失败或取消）后，把 OCR 文本、评估结果中的申请人/代理人信息和事项写入检索索引，
+//! 供监控台按关键字、事项、状态、时间范围查找预审。证件号与手机号在入索引前脱敏，
+//! 查询词按同样规则脱敏后再匹配，因此按完整证件号仍能查到。
+//!
+//! 索引实现通过 [`SearchIndex`] 接入，目前提供基于 SQLite FTS5 的本地索引，
+//! 独立于业务数据库，达梦、PostgreSQL 后端同样可用。
+
+pub mod sqlite_fts;
+
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::db::traits::{PreviewFilter, PreviewRecord, PreviewRequestRecord, PreviewStatus};
+use crate::db::Database;
+use crate::util::config::types::{DeploymentRole, SearchConfig};
+use crate::AppState;
+
+pub use sqlite_fts::SqliteFtsIndex;
+
+/// 索引返回的摘要中高亮片段的起止标记，输出前替换为 `<mark>`
+pub(crate) const HIGHLIGHT_START: char = '\u{E000}';
+pub(crate) const HIGHLIGHT_END: char = '\u{E001}';
+
+const BACKFILL_BATCH: u32 = 200;
+
+static INDEX: OnceCell<Arc<dyn SearchIndex>> = OnceCell::new();
+static INDEXER_TASK: OnceCell<()> = OnceCell::new();
+
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    pub preview_id: String,
+    pub user_id: String,
+    pub third_party_request_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub matter_name: Option<String>,
+    pub status: PreviewStatus,
+    pub applicant_name: Option<String>,
+    /// 已脱敏
+    pub applicant_id: Option<String>,
+    pub agent_name: Option<String>,
+    /// 预审 OCR 文本与各材料识别内容，已脱敏
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// 空白分隔的关键字，全部命中才返回
+    pub text: Option<String>,
+    /// 事项ID精确匹配或事项名称包含
+    pub matter: Option<String>,
+    pub status: Option<PreviewStatus>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub preview_id: String,
+    pub third_party_request_id: Option<String>,
+    pub user_id: String,
+    pub matter_id: Option<String>,
+    pub matter_name: Option<String>,
+    pub status: String,
+    pub applicant_name: Option<String>,
+    pub applicant_id: Option<String>,
+    pub agent_name: Option<String>,
+    /// 命中位置附近的文本，已做 HTML 转义，命中词包在 `<mark>` 中
+    pub snippet: Option<String>,
+    /// 相关度，越小越相关；无关键字时为空
+    pub score: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchResults {
+    pub total: u64,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackfillSummary {
+    pub scanned: u64,
+    pub indexed: u64,
+    pub failures: u64,
+}
+
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    fn backend(&self) -> &'static str;
+
+    /// 写入或整体替换一个预审的索引文档
+    async fn upsert(&self, document: &SearchDocument) -> Result<()>;
+
+    async fn remove(&self, preview_id: &str) -> Result<()>;
+
+    async fn contains(&self, preview_id: &str) -> Result<bool>;
+
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults>;
+}
+
+pub async fn create_index(config: &SearchConfig) -> Result<Arc<dyn SearchIndex>> {
+    match config.backend.as_str() {
+        "sqlite_fts" => Ok(Arc::new(SqliteFtsIndex::open(&config.sqlite_path).await?)),
+        other => Err(anyhow!("不支持的全文检索后端: {}", other)),
+    }
+}
+
+/// 已初始化的检索索引；未启用或非主节点时为空
+pub fn index() -> Option<&'static Arc<dyn SearchIndex>> {
+    INDEX.get()
+}
+
+/// 把证件号、手机号替换为保留首尾的掩码
+pub fn mask_sensitive(text: &str) -> String {
+    static ID_RE: OnceLock<Regex> = OnceLock::new();
+    static PHONE_RE: OnceLock<Regex> = OnceLock::new();
+    let id_re = ID_RE.get_or_init(|| {
+        Regex::new(r"\d{6}(?:18|19|20)\d{2}(?:0[1-9]|1[0-2])(?:0[1-9]|[12]\d|3[01])\d{3}[\dXx]")
+            .expect("证件号正则")
+    });
+    let phone_re = PHONE_RE.get_or_init(|| Regex::new(r"1[3-9]\d{9}").expect("手机号正则"));
+
+    let masked = id_re.replace_all(text, |caps: &regex::Captures| mask_chars(&caps[0], 6, 4));
+    phone_re
+        .replace_all(&masked, |caps: &regex::Captures| mask_chars(&caps[0], 3, 4))
+        .into_owned()
+}
+
+fn mask_chars(value: &str, prefix: usize, suffix: usize) -> String {
+    let len = value.chars().count();
+    value
+        .chars()
+        .enumerate()
+        .map(|(idx, ch)| {
+            if idx < prefix || idx >= len.saturating_sub(suffix) {
+                ch
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+/// 证件号字段：大陆身份证与正文同样脱敏，其他证件保留首尾各两位
+fn mask_identifier(value: &str) -> String {
+    let masked = mask_sensitive(value);
+    if masked != value {
+        return masked;
+    }
+    mask_chars(value, 2, 2)
+}
+
+/// 把索引返回的摘要转为可直接嵌入页面的 HTML
+pub(crate) fn render_snippet(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(HIGHLIGHT_START, "<mark>")
+        .replace(HIGHLIGHT_END, "</mark>")
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// 由预审记录（及其请求）生成索引文档；评估结果缺失时只索引 OCR 文本
+pub fn build_document(
+    record: &PreviewRecord,
+    request: Option<&PreviewRequestRecord>,
+) -> SearchDocument {
+    let evaluation = record
+        .evaluation_result
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<Value>(raw).ok());
+    let basic = evaluation.as_ref().and_then(|e| e.get("basic_info"));
+    let basic_str = |key: &str| non_empty(basic.and_then(|b| b.get(key)).and_then(Value::as_str));
+    let user_info = record
+        .user_info_json
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<Value>(raw).ok());
+    let user_str = |key: &str| {
+        non_empty(
+            user_info
+                .as_ref()
+                .and_then(|u| u.get(key))
+                .and_then(Value::as_str),
+        )
+    };
+
+    let mut content = record.ocr_text.clone();
+    if let Some(materials) = evaluation
+        .as_ref()
+        .and_then(|e| e.get("material_results"))
+        .and_then(Value::as_array)
+    {
+        for material in materials {
+            for key in ["material_name", "ocr_content"] {
+                if let Some(text) = non_empty(material.get(key).and_then(Value::as_str)) {
+                    content.push('\n');
+                    content.push_str(&text);
+                }
+            }
+        }
+    }
+
+    let applicant_id = basic_str("applicant_certificate_number")
+        .or_else(|| user_str("certificate_number"))
+        .or_else(|| basic_str("applicant_id"))
+        .map(|id| mask_identifier(&id));
+
+    SearchDocument {
+        preview_id: record.id.clone(),
+        user_id: record.user_id.clone(),
+        third_party_request_id: record.third_party_request_id.clone(),
+        matter_id: request
+            .and_then(|r| non_empty(Some(r.matter_id.as_str())))
+            .or_else(|| basic_str("matter_id")),
+        matter_name: request
+            .and_then(|r| non_empty(Some(r.matter_name.as_str())))
+            .or_else(|| basic_str("matter_name")),
+        status: record.status.clone(),
+        applicant_name: basic_str("applicant_name").or_else(|| user_str("user_name")),
+        applicant_id,
+        agent_name: basic_str("agent_name"),
+        content: mask_sensitive(&content),
+        created_at: record.created_at,
+        updated_at: record.updated_at,
+    }
+}
+
+async fn load_document(
+    database: &Arc<dyn Database>,
+    preview_id: &str,
+) -> Result<Option<SearchDocument>> {
+    let Some(record) = database.get_preview_record(preview_id).await? else {
+        return Ok(None);
+    };
+    let request = match record.third_party_request_id.as_deref() {
+        Some(tp_id) => database
+            .find_preview_request_by_third_party(tp_id)
+            .await
+            .unwrap_or_else(|err| {
+                warn!(preview_id = %preview_id, error = %err, "检索索引查询预审请求失败");
+                None
+            }),
+        None => None,
+    };
+    Ok(Some(build_document(&record, request.as_ref())))
+}
+
+/// 按数据库中的当前内容重建单个预审的索引；记录不存在时从索引删除
+pub async fn index_preview(
+    index: &Arc<dyn SearchIndex>,
+    database: &Arc<dyn Database>,
+    preview_id: &str,
+) -> Result<()> {
+    match load_document(database, preview_id).await? {
+        Some(document) => index.upsert(&document).await,
+        None => index.remove(preview_id).await,
+    }
+}
+
+/// 在后台刷新单个预审的索引，检索未启用时不做任何事
+pub fn schedule_index(database: Arc<dyn Database>, preview_id: String) {
+    let Some(index) = index() else {
+        return;
+    };
+    let index = Arc::clone(index);
+    tokio::spawn(async move {
+        if let Err(err) = index_preview(&index, &database, &preview_id).await {
+            warn!(preview_id = %preview_id, error = %err, "更新检索索引失败");
+        }
+    });
+}
+
+/// 为已结束的预审补建索引；`force` 时全部重建，否则跳过已在索引中的预审
+pub async fn backfill(
+    index: &Arc<dyn SearchIndex>,
+    database: &Arc<dyn Database>,
+    force: bool,
+) -> Result<BackfillSummary> {
+    let mut summary = BackfillSummary::default();
+    for status in [
+        PreviewStatus::Completed,
+        PreviewStatus::Failed,
+        PreviewStatus::Cancelled,
+    ] {
+        let mut offset = 0;
+        loop {
+            let records = database
+                .list_preview_records(&PreviewFilter {
+                    status: Some(status.clone()),
+                    limit: Some(BACKFILL_BATCH),
+                    offset: Some(offset),
+                    ..Default::default()
+                })
+                .await
+                .context("列出待索引预审失败")?;
+            for record in &records {
+                summary.scanned += 1;
+                if !force && index.contains(&record.id).await.unwrap_or(false) {
+                    continue;
+                }
+                let result = index_preview(index, database, &record.id).await;
+                match result {
+                    Ok(()) => summary.indexed += 1,
+                    Err(err) => {
+                        summary.failures += 1;
+                        warn!(preview_id = %record.id, error = %err, "补建检索索引失败");
+                    }
+                }
+            }
+            if records.len() < BACKFILL_BATCH as usize {
+                break;
+            }
+            offset += BACKFILL_BATCH;
+        }
+    }
+    Ok(summary)
+}
+
+/// 主节点启动时打开索引，并按配置在后台补建缺失的索引
+pub fn spawn_indexer(app_state: &AppState) {
+    let config = app_state.config.search.clone();
+    if !config.enabled || app_state.config.deployment.role == DeploymentRole::Worker {
+        return;
+    }
+    if INDEXER_TASK.set(()).is_err() {
+        return;
+    }
+
+    let database = Arc::clone(&app_state.database);
+    tokio::spawn(async move {
+        let index = match create_index(&config).await {
+            Ok(index) => index,
+            Err(err) => {
+                warn!(error = %err, "初始化全文检索索引失败，检索不可用");
+                return;
+            }
+        };
+        let index = Arc::clone(INDEX.get_or_init(|| index));
+        info!(backend = index.backend(), "全文检索索引已就绪");
+
+        if config.backfill_on_startup {
+            match backfill(&index, &database, false).await {
+                Ok(summary) if summary.indexed > 0 || summary.failures > 0 => info!(
+                    scanned = summary.scanned,
+                    indexed = summary.indexed,
+                    failures = summary.failures,
+                    "检索索引补建完成"
+                ),
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "检索索引补建失败"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_id_numbers_and_phones() {
+        let text = "身份证号 33010219900101123X 电话 13812345678 编号 2024010112";
+        assert_eq!(
+            mask_sensitive(text),
+            "身份证号 330102********123X 电话 138****5678 编号 2024010112"
+        );
+    }
+
+    #[test]
+    fn snippet_is_escaped_before_highlighting() {
+        let raw = format!("<b>{}张三{}</b>", HIGHLIGHT_START, HIGHLIGHT_END);
+        assert_eq!(render_snippet(&raw), "&lt;b&gt;<mark>张三</mark>&lt;/b&gt;");
+    }
+}
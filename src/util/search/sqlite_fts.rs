@@ -0,0 +1,543 @@
+//! 基于 SQLite FTS5 的检索索引
+//!
+//! 使用 trigram 分词，中文无需分词即可做子串匹配。三个字符以下的关键字无法走 trigram 索引，
+//! 改用 LIKE 扫描，摘要在内存中生成。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{sqlite::SqliteRow, QueryBuilder, Row, Sqlite, SqlitePool};
+
+use super::{
+    mask_sensitive, render_snippet, SearchDocument, SearchHit, SearchIndex, SearchQuery,
+    SearchResults, HIGHLIGHT_END, HIGHLIGHT_START,
+};
+use crate::db::sqlite::connection::ConnectionManager;
+
+/// 摘要长度（字符）
+const SNIPPET_CHARS: usize = 48;
+/// trigram 索引可用的最短关键字
+const MIN_INDEXED_TERM_CHARS: usize = 3;
+/// 全文列，顺序与 bm25 权重一一对应
+const TEXT_COLUMNS: [&str; 5] = [
+    "applicant_name",
+    "applicant_id",
+    "agent_name",
+    "matter_name",
+    "content",
+];
+
+enum TextMatch {
+    None,
+    Fts(String),
+    Like(Vec<String>),
+}
+
+pub struct SqliteFtsIndex {
+    pool: SqlitePool,
+}
+
+impl SqliteFtsIndex {
+    pub async fn open(path: &str) -> Result<Self> {
+        let pool = ConnectionManager::create_pool(path).await?;
+        let index = Self { pool };
+        index.ensure_schema().await?;
+        Ok(index)
+    }
+
+    async fn ensure_schema(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS search_documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                preview_id TEXT NOT NULL UNIQUE,
+                user_id TEXT NOT NULL,
+                third_party_request_id TEXT,
+                matter_id TEXT,
+                matter_name TEXT,
+                status TEXT NOT NULL,
+                applicant_name TEXT,
+                applicant_id TEXT,
+                agent_name TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_search_documents_created_at ON search_documents(created_at)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS search_fts USING fts5(
+                applicant_name, applicant_id, agent_name, matter_name, content,
+                tokenize = 'trigram'
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn text_match(text: Option<&str>) -> TextMatch {
+        let terms: Vec<String> = text
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(mask_sensitive)
+            .collect();
+        if terms.is_empty() {
+            TextMatch::None
+        } else if terms
+            .iter()
+            .all(|t| t.chars().count() >= MIN_INDEXED_TERM_CHARS)
+        {
+            // 每个词按短语匹配，避免用户输入被解析为 FTS5 语法
+            let expression = terms
+                .iter()
+                .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            TextMatch::Fts(expression)
+        } else {
+            TextMatch::Like(terms)
+        }
+    }
+
+    fn push_from_where(builder: &mut QueryBuilder<Sqlite>, text: &TextMatch, query: &SearchQuery) {
+        match text {
+            TextMatch::None => {
+                builder.push(" FROM search_documents d WHERE 1 = 1");
+            }
+            TextMatch::Fts(expression) => {
+                builder.push(
+                    " FROM search_documents d JOIN search_fts ON search_fts.rowid = d.id \
+                     WHERE search_fts MATCH ",
+                );
+                builder.push_bind(expression.clone());
+            }
+            TextMatch::Like(terms) => {
+                builder.push(
+                    " FROM search_documents d JOIN search_fts ON search_fts.rowid = d.id WHERE 1 = 1",
+                );
+                for term in terms {
+                    let pattern = like_pattern(term);
+                    builder.push(" AND (");
+                    for (idx, column) in TEXT_COLUMNS.iter().enumerate() {
+                        if idx > 0 {
+                            builder.push(" OR ");
+                        }
+                        builder
+                            .push(format!("search_fts.{} LIKE ", column))
+                            .push_bind(pattern.clone())
+                            .push(" ESCAPE '\\'");
+                    }
+                    builder.push(")");
+                }
+            }
+        }
+
+        if let Some(matter) = query.matter.as_deref().filter(|m| !m.trim().is_empty()) {
+            let matter = matter.trim();
+            builder
+                .push(" AND (d.matter_id = ")
+                .push_bind(matter.to_string())
+                .push(" OR d.matter_name LIKE ")
+                .push_bind(like_pattern(matter))
+                .push(" ESCAPE '\\')");
+        }
+        if let Some(status) = &query.status {
+            builder.push(" AND d.status = ").push_bind(status.as_str());
+        }
+        if let Some(start) = query.start_date {
+            builder.push(" AND d.created_at >= ").push_bind(start);
+        }
+        if let Some(end) = query.end_date {
+            builder.push(" AND d.created_at <= ").push_bind(end);
+        }
+    }
+
+    fn row_to_hit(row: &SqliteRow, terms: Option<&[String]>) -> Result<SearchHit> {
+        let snippet = match terms {
+            Some(terms) => row
+                .try_get::<Option<String>, _>("content")?
+                .and_then(|content| like_snippet(&content, terms)),
+            None => row.try_get::<Option<String>, _>("snippet")?,
+        };
+        Ok(SearchHit {
+            preview_id: row.try_get("preview_id")?,
+            third_party_request_id: row.try_get("third_party_request_id")?,
+            user_id: row.try_get("user_id")?,
+            matter_id: row.try_get("matter_id")?,
+            matter_name: row.try_get("matter_name")?,
+            status: row.try_get("status")?,
+            applicant_name: row.try_get("applicant_name")?,
+            applicant_id: row.try_get("applicant_id")?,
+            agent_name: row.try_get("agent_name")?,
+            snippet: snippet
+                .filter(|s| s.contains(HIGHLIGHT_START))
+                .map(|s| render_snippet(&s)),
+            score: row.try_get("score")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl SearchIndex for SqliteFtsIndex {
+    fn backend(&self) -> &'static str {
+        "sqlite_fts"
+    }
+
+    async fn upsert(&self, document: &SearchDocument) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let existing: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM search_documents WHERE preview_id = ?")
+                .bind(&document.preview_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        let rowid = match existing {
+            Some(id) => {
+                sqlx::query("DELETE FROM search_fts WHERE rowid = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(
+                    r#"
+                    UPDATE search_documents SET
+                        user_id = ?, third_party_request_id = ?, matter_id = ?, matter_name = ?,
+                        status = ?, applicant_name = ?, applicant_id = ?, agent_name = ?,
+                        created_at = ?, updated_at = ?
+                    WHERE id = ?
+                    "#,
+                )
+                .bind(&document.user_id)
+                .bind(&document.third_party_request_id)
+                .bind(&document.matter_id)
+                .bind(&document.matter_name)
+                .bind(document.status.as_str())
+                .bind(&document.applicant_name)
+                .bind(&document.applicant_id)
+                .bind(&document.agent_name)
+                .bind(document.created_at)
+                .bind(document.updated_at)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                id
+            }
+            None => sqlx::query(
+                r#"
+                INSERT INTO search_documents (
+                    preview_id, user_id, third_party_request_id, matter_id, matter_name,
+                    status, applicant_name, applicant_id, agent_name, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&document.preview_id)
+            .bind(&document.user_id)
+            .bind(&document.third_party_request_id)
+            .bind(&document.matter_id)
+            .bind(&document.matter_name)
+            .bind(document.status.as_str())
+            .bind(&document.applicant_name)
+            .bind(&document.applicant_id)
+            .bind(&document.agent_name)
+            .bind(document.created_at)
+            .bind(document.updated_at)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid(),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO search_fts (rowid, applicant_name, applicant_id, agent_name, matter_name, content)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(rowid)
+        .bind(&document.applicant_name)
+        .bind(&document.applicant_id)
+        .bind(&document.agent_name)
+        .bind(&document.matter_name)
+        .bind(&document.content)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove(&self, preview_id: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM search_fts WHERE rowid IN (SELECT id FROM search_documents WHERE preview_id = ?)",
+        )
+        .bind(preview_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM search_documents WHERE preview_id = ?")
+            .bind(preview_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn contains(&self, preview_id: &str) -> Result<bool> {
+        let found: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM search_documents WHERE preview_id = ?")
+                .bind(preview_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(found.is_some())
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults> {
+        let text = Self::text_match(query.text.as_deref());
+
+        let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*)");
+        Self::push_from_where(&mut count, &text, query);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        // trigram 下每个字符一个词元，snippet 的词元数即字符数
+        let extra = match &text {
+            TextMatch::None => "NULL AS snippet, NULL AS score, NULL AS content".to_string(),
+            TextMatch::Fts(_) => format!(
+                "snippet(search_fts, -1, char(57344), char(57345), '…', {}) AS snippet, \
+                 bm25(search_fts, 10.0, 10.0, 5.0, 2.0, 1.0) AS score, NULL AS content",
+                SNIPPET_CHARS
+            ),
+            TextMatch::Like(_) => {
+                "NULL AS snippet, NULL AS score, search_fts.content AS content".to_string()
+            }
+        };
+        let mut select = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT d.preview_id, d.third_party_request_id, d.user_id, d.matter_id, d.matter_name, \
+             d.status, d.applicant_name, d.applicant_id, d.agent_name, d.created_at, d.updated_at, {}",
+            extra
+        ));
+        Self::push_from_where(&mut select, &text, query);
+        select.push(match text {
+            TextMatch::Fts(_) => " ORDER BY score, d.created_at DESC",
+            _ => " ORDER BY d.created_at DESC",
+        });
+        select
+            .push(" LIMIT ")
+            .push_bind(query.limit.max(1) as i64)
+            .push(" OFFSET ")
+            .push_bind(query.offset as i64);
+
+        let rows = select.build().fetch_all(&self.pool).await?;
+        let terms = match &text {
+            TextMatch::Like(terms) => Some(terms.as_slice()),
+            _ => None,
+        };
+        let hits = rows
+            .iter()
+            .map(|row| Self::row_to_hit(row, terms))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SearchResults {
+            total: total.max(0) as u64,
+            hits,
+        })
+    }
+}
+
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// 在内容中截取首个命中附近的文本并标记所有命中，与 FTS5 snippet 的输出格式一致
+fn like_snippet(content: &str, terms: &[String]) -> Option<String> {
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let terms: Vec<Vec<char>> = terms
+        .iter()
+        .map(|t| t.to_lowercase().chars().collect())
+        .collect();
+    let matches_at = |pos: usize| {
+        terms
+            .iter()
+            .find(|t| !t.is_empty() && lower[pos..].starts_with(t))
+            .map(Vec::len)
+    };
+
+    let first = (0..lower.len()).find(|&pos| matches_at(pos).is_some())?;
+    let start = first.saturating_sub(SNIPPET_CHARS / 4);
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut pos = start;
+    while pos < end {
+        match matches_at(pos) {
+            Some(len) => {
+                let stop = (pos + len).min(chars.len());
+                snippet.push(HIGHLIGHT_START);
+                snippet.extend(&chars[pos..stop]);
+                snippet.push(HIGHLIGHT_END);
+                pos = stop;
+            }
+            None => {
+                snippet.push(chars[pos]);
+                pos += 1;
+            }
+        }
+    }
+    if pos < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::traits::PreviewStatus;
+    use chrono::{Duration, Utc};
+
+    fn document(preview_id: &str, applicant: &str, content: &str) -> SearchDocument {
+        SearchDocument {
+            preview_id: preview_id.to_string(),
+            user_id: "u1".to_string(),
+            third_party_request_id: Some(format!("REQ-{}", preview_id)),
+            matter_id: Some("m-001".to_string()),
+            matter_name: Some("食品经营许可".to_string()),
+            status: PreviewStatus::Completed,
+            applicant_name: Some(applicant.to_string()),
+            applicant_id: Some(mask_sensitive("330102199001011234")),
+            agent_name: None,
+            content: mask_sensitive(content),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    async fn open_index(dir: &tempfile::TempDir) -> SqliteFtsIndex {
+        SqliteFtsIndex::open(dir.path().join("search.db").to_str().unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn text(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: Some(text.to_string()),
+            limit: 10,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn matches_chinese_substrings_with_highlight() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = open_index(&dir).await;
+        index
+            .upsert(&document(
+                "p-1",
+                "张三",
+                "营业执照 统一社会信用代码 经营范围：<b>预包装食品</b>销售",
+            ))
+            .await
+            .unwrap();
+        index
+            .upsert(&document("p-2", "李四", "身份证 正面 住址 杭州市西湖区"))
+            .await
+            .unwrap();
+
+        let results = index.search(&text("预包装食品")).await.unwrap();
+        assert_eq!(results.total, 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.preview_id, "p-1");
+        let snippet = hit.snippet.as_deref().unwrap();
+        assert!(snippet.contains("<mark>预包装食品</mark>"), "{}", snippet);
+        assert!(snippet.contains("&lt;b&gt;"), "{}", snippet);
+
+        // 两个字的姓名走 LIKE
+        let results = index.search(&text("李四")).await.unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].preview_id, "p-2");
+
+        // 多个关键字同时命中
+        assert_eq!(index.search(&text("身份证 西湖")).await.unwrap().total, 1);
+        assert_eq!(index.search(&text("身份证 销售")).await.unwrap().total, 0);
+
+        // 用户输入的 FTS 语法按字面匹配
+        assert_eq!(index.search(&text("\"OR\" NEAR(")).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn id_numbers_are_masked_but_still_searchable() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = open_index(&dir).await;
+        index
+            .upsert(&document("p-1", "张三", "公民身份号码 330102199001011234"))
+            .await
+            .unwrap();
+
+        let results = index.search(&text("330102199001011234")).await.unwrap();
+        assert_eq!(results.total, 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.applicant_id.as_deref(), Some("330102********1234"));
+        assert!(!hit.snippet.as_deref().unwrap().contains("19900101"));
+    }
+
+    #[tokio::test]
+    async fn filters_and_reindexing() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = open_index(&dir).await;
+        let mut old = document("p-old", "王五", "申请书");
+        old.created_at = Utc::now() - Duration::days(10);
+        old.status = PreviewStatus::Failed;
+        index.upsert(&old).await.unwrap();
+        index
+            .upsert(&document("p-new", "赵六", "申请书"))
+            .await
+            .unwrap();
+
+        let query = SearchQuery {
+            matter: Some("食品经营".to_string()),
+            start_date: Some(Utc::now() - Duration::days(1)),
+            limit: 10,
+            ..Default::default()
+        };
+        let results = index.search(&query).await.unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].preview_id, "p-new");
+
+        let query = SearchQuery {
+            status: Some(PreviewStatus::Failed),
+            ..text("申请书")
+        };
+        assert_eq!(
+            index.search(&query).await.unwrap().hits[0].preview_id,
+            "p-old"
+        );
+
+        // 重新索引替换旧内容
+        old.content = "补正材料".to_string();
+        index.upsert(&old).await.unwrap();
+        assert_eq!(index.search(&text("申请书")).await.unwrap().total, 1);
+        assert_eq!(index.search(&text("补正材料")).await.unwrap().total, 1);
+
+        index.remove("p-old").await.unwrap();
+        assert!(!index.contains("p-old").await.unwrap());
+        assert_eq!(index.search(&text("补正材料")).await.unwrap().total, 0);
+    }
+}
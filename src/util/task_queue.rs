@@ -8,17 +8,28 @@ use async_nats::jetstream::{self, consumer, stream, AckKind};
 use async_nats::ConnectOptions;
 use async_trait::async_trait;
 use futures::StreamExt;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{Notify, RwLock, Semaphore};
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+use crate::db::traits::{
+    shard_queue_key, validate_queue_table_name, NewQueuedTask, QueuedTaskRecord, QueuedTaskStats,
+    TaskClaimRequest,
+};
+use crate::db::{Database, PreviewFilter, PreviewStatus};
 use crate::model::preview::PreviewBody;
 use crate::util::config::types::{
-    LocalQueueConfig, NatsQueueConfig, TaskQueueConfig, TaskQueueDriver,
+    DatabaseQueueConfig, LocalQueueConfig, NatsQueueConfig, QueuePriority, TaskQueueConfig,
+    TaskQueueDriver,
 };
+use crate::util::dead_letter::{self, DeadLetter};
+use crate::util::lane_scheduler::{self, FairScheduler, LaneDepth, LanePolicy, LaneRotation};
 use crate::util::logging::standards::events;
+use crate::util::pdf_shard::PageShard;
+use crate::util::task_routing::{self, Routed, TaskRouter};
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 
 pub const PREVIEW_QUEUE_NAME: &str = "preview";
@@ -28,6 +39,21 @@ pub struct PreviewTask {
     pub preview_body: PreviewBody,
     pub preview_id: String,
     pub third_party_request_id: String,
+    /// 旧版本入队的载荷没有该字段，按 normal 处理
+    #[serde(default)]
+    pub priority: QueuePriority,
+    /// 提交任务的第三方客户端；为空表示交互式请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// 重新执行时要求忽略 OCR 缓存
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_reocr: bool,
+    /// 入队时按 worker 能力选定的任务池；为空投递到默认主题/队列
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// 大 PDF 的页段子任务；为空表示完整的预审任务
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<PageShard>,
 }
 
 impl PreviewTask {
@@ -40,8 +66,92 @@ impl PreviewTask {
             preview_body,
             preview_id,
             third_party_request_id,
+            priority: QueuePriority::default(),
+            client_id: None,
+            force_reocr: false,
+            route: None,
+            shard: None,
+        }
+    }
+
+    pub fn with_scheduling(mut self, scheduling: TaskScheduling) -> Self {
+        self.priority = scheduling.priority;
+        self.client_id = scheduling.client_id;
+        self.force_reocr = scheduling.force_reocr;
+        self
+    }
+
+    /// 同一通道内公平出队的分组键
+    pub fn fair_key(&self) -> &str {
+        self.client_id
+            .as_deref()
+            .unwrap_or(lane_scheduler::INTERACTIVE_CLIENT)
+    }
+}
+
+/// 提交时确定的调度信息与执行选项；材料下载队列保存的是原始请求体，
+/// 由服务端写入保留字段随载荷传递，覆盖请求方自带的同名字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskScheduling {
+    pub priority: QueuePriority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_reocr: bool,
+}
+
+impl TaskScheduling {
+    const PAYLOAD_KEY: &'static str = "__queueScheduling";
+
+    /// 未在配置中登记的客户端标识（如关闭第三方鉴权时的 open_access）按交互式请求处理
+    pub fn resolve(policy: &LanePolicy, client_id: Option<&str>) -> Self {
+        let client_id = client_id.filter(|id| policy.is_known_client(id));
+        Self {
+            priority: policy.resolve_priority(client_id),
+            client_id: client_id.map(str::to_string),
+            force_reocr: false,
         }
     }
+
+    /// 载荷不是 JSON 对象时返回 None
+    pub fn embed(&self, payload: &[u8]) -> Option<String> {
+        let mut value: serde_json::Value = serde_json::from_slice(payload).ok()?;
+        let object = value.as_object_mut()?;
+        object.insert(
+            Self::PAYLOAD_KEY.to_string(),
+            serde_json::to_value(self).ok()?,
+        );
+        serde_json::to_string(&value).ok()
+    }
+
+    pub fn extract(payload: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+        serde_json::from_value(value.get(Self::PAYLOAD_KEY)?.clone()).ok()
+    }
+}
+
+/// 处理器未开始执行就交还任务（如 worker 凭证被主节点拒绝）：
+/// 队列让任务立即可被其他消费者领取，不计入尝试次数
+#[derive(Debug, Clone)]
+pub struct TaskReturned {
+    pub preview_id: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for TaskReturned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "预审任务已交还队列: {} ({})",
+            self.preview_id, self.reason
+        )
+    }
+}
+
+impl std::error::Error for TaskReturned {}
+
+fn is_returned(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<TaskReturned>().is_some()
 }
 
 #[async_trait]
@@ -53,13 +163,40 @@ pub trait PreviewTaskHandler: Send + Sync {
 pub trait TaskQueue: Send + Sync + Any {
     async fn enqueue(&self, task: PreviewTask) -> Result<()>;
 
+    /// 移除尚未被消费者取走的任务，返回移除数量；无法按任务删除的驱动返回 0
+    async fn cancel(&self, _preview_id: &str) -> Result<u64> {
+        Ok(0)
+    }
+
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
 pub async fn initialize_task_queue(
     distributed_enabled: bool,
     config: &TaskQueueConfig,
+    database: Arc<dyn Database>,
+    handler: Arc<dyn PreviewTaskHandler>,
+) -> Result<Arc<dyn TaskQueue>> {
+    build_task_queue(distributed_enabled, config, database, handler, true).await
+}
+
+/// 仅用于投递的队列：不启动内联消费者、死信落库消费者与路由暂挂循环，
+/// 供运维工具在服务进程之外重新入队
+pub async fn initialize_producer_task_queue(
+    distributed_enabled: bool,
+    config: &TaskQueueConfig,
+    database: Arc<dyn Database>,
+    handler: Arc<dyn PreviewTaskHandler>,
+) -> Result<Arc<dyn TaskQueue>> {
+    build_task_queue(distributed_enabled, config, database, handler, false).await
+}
+
+async fn build_task_queue(
+    distributed_enabled: bool,
+    config: &TaskQueueConfig,
+    database: Arc<dyn Database>,
     handler: Arc<dyn PreviewTaskHandler>,
+    background: bool,
 ) -> Result<Arc<dyn TaskQueue>> {
     if !distributed_enabled {
         return Ok(Arc::new(DirectTaskQueue::new(handler)));
@@ -70,6 +207,8 @@ pub async fn initialize_task_queue(
         TaskQueueDriver::Local => Ok(create_local_queue(
             PREVIEW_QUEUE_NAME,
             &config.local,
+            LanePolicy::from_global_config(),
+            database,
             handler,
         )),
         TaskQueueDriver::Nats => {
@@ -79,14 +218,37 @@ pub async fn initialize_task_queue(
                 .cloned()
                 .ok_or_else(|| anyhow!("缺少 NATS 队列配置"))?;
 
-            let queue_impl =
-                Arc::new(NatsTaskQueue::connect(PREVIEW_QUEUE_NAME, &nats_config).await?);
+            let inline_worker = background && nats_config.inline_worker;
+            let router = create_router(config, inline_worker, &database);
+            let mut queue_impl = NatsTaskQueue::connect(PREVIEW_QUEUE_NAME, &nats_config).await?;
+            if let Some(router) = router.as_ref() {
+                queue_impl = queue_impl.with_router(Arc::clone(router));
+            }
+            let queue_impl = Arc::new(queue_impl);
+
+            if background {
+                let ingest_context = queue_impl.jetstream_context();
+                let ingest_config = nats_config.clone();
+                let ingest_database = database.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = dead_letter::run_nats_ingester(
+                        ingest_context,
+                        ingest_config,
+                        ingest_database,
+                    )
+                    .await
+                    {
+                        error!("NATS 死信落库消费者退出: {:#}", err);
+                    }
+                });
+            }
 
-            if nats_config.inline_worker {
+            if inline_worker {
                 let consumer = NatsTaskQueueConsumer::new(
                     PREVIEW_QUEUE_NAME,
                     queue_impl.jetstream_context(),
                     nats_config.clone(),
+                    LanePolicy::from_global_config(),
                 );
                 let handler_clone = Arc::clone(&handler);
                 tokio::spawn(async move {
@@ -97,95 +259,452 @@ pub async fn initialize_task_queue(
             }
 
             let queue: Arc<dyn TaskQueue> = queue_impl;
+            if let Some(router) = router.filter(|_| background) {
+                router.spawn_hold_loop(Arc::clone(&queue));
+            }
             Ok(queue)
         }
-        TaskQueueDriver::Database => Err(anyhow!(
-            "数据库任务队列驱动尚未实现，请修改配置或使用 NATS/Local 驱动"
-        )),
+        TaskQueueDriver::Database => {
+            let db_config = config
+                .database
+                .as_ref()
+                .cloned()
+                .ok_or_else(|| anyhow!("缺少数据库队列配置"))?;
+
+            let inline_worker = background && db_config.inline_worker;
+            let router = create_router(config, inline_worker, &database);
+            let mut queue_impl =
+                DatabaseTaskQueue::new(PREVIEW_QUEUE_NAME, database.clone(), db_config.clone())
+                    .await?;
+            if let Some(router) = router.as_ref() {
+                queue_impl = queue_impl.with_router(Arc::clone(router));
+            }
+            let queue_impl = Arc::new(queue_impl);
+
+            if inline_worker {
+                let consumer =
+                    DatabaseTaskQueueConsumer::new(PREVIEW_QUEUE_NAME, database, db_config);
+                let handler_clone = Arc::clone(&handler);
+                tokio::spawn(async move {
+                    if let Err(err) = consumer.run(handler_clone).await {
+                        error!("内联数据库队列消费失败: {:#}", err);
+                    }
+                });
+            }
+
+            let queue: Arc<dyn TaskQueue> = queue_impl;
+            if let Some(router) = router.filter(|_| background) {
+                router.spawn_hold_loop(Arc::clone(&queue));
+            }
+            Ok(queue)
+        }
+    }
+}
+
+/// 内联消费者按本节点 deployment.worker.capabilities（未配置时取默认值）登记能力
+fn create_router(
+    config: &TaskQueueConfig,
+    inline_worker: bool,
+    database: &Arc<dyn Database>,
+) -> Option<Arc<TaskRouter>> {
+    if !config.routing.enabled {
+        return None;
+    }
+    if inline_worker {
+        let capabilities = crate::CONFIG
+            .deployment
+            .worker
+            .as_ref()
+            .and_then(|worker| worker.capabilities.as_ref())
+            .map(task_routing::WorkerCapabilities::from_config)
+            .unwrap_or_default();
+        task_routing::register_inline_worker(capabilities);
     }
+    info!(unmatched = ?config.routing.unmatched, "已启用按 worker 能力路由任务");
+    Some(Arc::new(TaskRouter::new(
+        config.routing.clone(),
+        Arc::clone(database),
+    )))
 }
 
 fn create_local_queue(
     queue_name: &'static str,
     local_config: &LocalQueueConfig,
+    policy: LanePolicy,
+    database: Arc<dyn Database>,
     handler: Arc<dyn PreviewTaskHandler>,
 ) -> Arc<dyn TaskQueue> {
-    let capacity = local_config.channel_capacity.max(16);
-    Arc::new(LocalTaskQueue::new(queue_name, handler, capacity))
+    let queue = Arc::new(LocalTaskQueue::new(
+        queue_name,
+        handler,
+        Some(database),
+        local_config,
+        policy,
+    ));
+    if local_config.replay_on_start {
+        let replay_queue = Arc::clone(&queue);
+        let limit = local_config.replay_limit.max(1);
+        tokio::spawn(async move {
+            match replay_queue.replay_journal(limit).await {
+                Ok(0) => debug!("本地任务队列无待重放任务"),
+                Ok(count) => info!(count, "本地任务队列已重放未完成任务"),
+                Err(err) => error!("本地任务队列重放失败: {:#}", err),
+            }
+        });
+    }
+    queue
+}
+
+struct LocalJob {
+    task: PreviewTask,
+    attempt: u32,
+}
+
+#[derive(Clone, Copy)]
+struct LocalRetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl LocalRetryPolicy {
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+    fn delay_after(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.backoff.saturating_mul(factor).min(Self::MAX_BACKOFF)
+    }
+}
+
+/// 本地优先级通道：按通道权重与客户端公平出队，总容量满时入队等待
+struct LocalLanes {
+    scheduler: Mutex<FairScheduler<LocalJob>>,
+    ready: Notify,
+    capacity: Semaphore,
+    closed: AtomicBool,
+}
+
+impl LocalLanes {
+    fn new(policy: LanePolicy, capacity: usize) -> Self {
+        Self {
+            scheduler: Mutex::new(FairScheduler::new(policy)),
+            ready: Notify::new(),
+            capacity: Semaphore::new(capacity),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    async fn push(&self, job: LocalJob) -> Result<()> {
+        let permit = self
+            .capacity
+            .acquire()
+            .await
+            .map_err(|_| anyhow!("本地队列已关闭"))?;
+        let priority = job.task.priority;
+        let client_key = job.task.fair_key().to_string();
+        self.scheduler.lock().push(priority, &client_key, job)?;
+        permit.forget();
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// 关闭后仍先取完已排队的任务
+    async fn pop(&self) -> Option<LocalJob> {
+        loop {
+            let notified = self.ready.notified();
+            if let Some(job) = self.scheduler.lock().pop() {
+                self.capacity.add_permits(1);
+                return Some(job);
+            }
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    fn remove(&self, preview_id: &str) -> usize {
+        let removed = self
+            .scheduler
+            .lock()
+            .retain(|job| job.task.preview_id != preview_id);
+        self.capacity.add_permits(removed);
+        removed
+    }
+
+    fn depths(&self) -> Vec<LaneDepth> {
+        self.scheduler.lock().depths()
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.capacity.close();
+        self.ready.notify_waiters();
+    }
 }
 
+/// 进程内任务队列：多个消费者共享优先级通道；提交方入队前写入的任务 payload
+/// 即预写日志，执行成功后删除，重启时据此重放 queued/processing 任务
 pub struct LocalTaskQueue {
-    sender: mpsc::Sender<PreviewTask>,
+    lanes: Arc<LocalLanes>,
     #[allow(dead_code)]
     handler: Arc<dyn PreviewTaskHandler>,
     queue_name: &'static str,
     pending_tasks: Arc<AtomicU64>,
+    journal: Option<Arc<dyn Database>>,
 }
 
 impl LocalTaskQueue {
     pub fn new(
         queue_name: &'static str,
         handler: Arc<dyn PreviewTaskHandler>,
-        capacity: usize,
+        journal: Option<Arc<dyn Database>>,
+        config: &LocalQueueConfig,
+        policy: LanePolicy,
     ) -> Self {
-        let (sender, mut receiver) = mpsc::channel(capacity);
-        let worker_handler = handler.clone();
+        let lanes = Arc::new(LocalLanes::new(policy, config.channel_capacity.max(16)));
         let pending_tasks = Arc::new(AtomicU64::new(0));
-        let pending_for_loop = Arc::clone(&pending_tasks);
-        let queue_name_for_loop = queue_name;
-        tokio::spawn(async move {
-            while let Some(task) = receiver.recv().await {
-                let result = worker_handler.handle_preview_task(task).await;
-                let success = result.is_ok();
-                if let Err(err) = result {
-                    error!("本地预审任务执行失败: {:?}", err);
-                }
-                let previous = pending_for_loop
-                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
-                        current.checked_sub(1)
-                    })
-                    .unwrap_or(0);
-                let depth = previous.saturating_sub(1);
-                METRICS_COLLECTOR.record_queue_dequeue(queue_name_for_loop, success, Some(depth));
-                METRICS_COLLECTOR.record_worker_inflight("local", depth);
-            }
-            METRICS_COLLECTOR.record_queue_depth(queue_name_for_loop, 0);
-            METRICS_COLLECTOR.record_worker_inflight("local", 0);
-        });
+        let retry = LocalRetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            backoff: Duration::from_millis(config.retry_backoff_ms),
+        };
+
+        for _ in 0..config.concurrency.max(1) {
+            let consumer = LocalConsumer {
+                queue_name,
+                lanes: Arc::clone(&lanes),
+                handler: Arc::clone(&handler),
+                journal: journal.clone(),
+                pending_tasks: Arc::clone(&pending_tasks),
+                policy: retry,
+            };
+            tokio::spawn(consumer.run());
+        }
+
         METRICS_COLLECTOR.record_queue_depth(queue_name, 0);
         METRICS_COLLECTOR.record_worker_inflight("local", 0);
         Self {
-            sender,
+            lanes,
             handler,
             queue_name,
             pending_tasks,
+            journal,
         }
     }
-}
 
-#[async_trait]
-impl TaskQueue for LocalTaskQueue {
-    async fn enqueue(&self, task: PreviewTask) -> Result<()> {
+    pub fn pending(&self) -> u64 {
+        self.pending_tasks.load(Ordering::SeqCst)
+    }
+
+    /// 各通道中等待出队的任务数，不含执行中与等待重试的任务
+    pub fn lane_depths(&self) -> Vec<LaneDepth> {
+        self.lanes.depths()
+    }
+
+    /// 重放日志中尚未完成的任务：queued 状态，以及由本节点处理中断的 processing 状态
+    pub async fn replay_journal(&self, limit: u32) -> Result<usize> {
+        let Some(database) = self.journal.as_ref() else {
+            return Ok(0);
+        };
+
+        let mut candidates = Vec::new();
+        for status in [PreviewStatus::Queued, PreviewStatus::Processing] {
+            let mut filter = PreviewFilter::default();
+            filter.status = Some(status);
+            filter.limit = Some(limit);
+            candidates.extend(database.list_preview_records(&filter).await?);
+        }
+        candidates.retain(|record| {
+            record.status != PreviewStatus::Processing
+                || record
+                    .last_worker_id
+                    .as_deref()
+                    .map_or(true, |id| id.eq_ignore_ascii_case("master"))
+        });
+        candidates.sort_by_key(|record| record.queued_at.unwrap_or(record.created_at));
+        candidates.truncate(limit as usize);
+
+        let mut replayed = 0;
+        for record in candidates {
+            let payload = match database.load_task_payload(&record.id).await {
+                Ok(Some(payload)) => payload,
+                Ok(None) => {
+                    warn!(preview_id = %record.id, "任务缺少 payload 日志，无法重放");
+                    continue;
+                }
+                Err(err) => {
+                    warn!(preview_id = %record.id, error = %err, "读取任务 payload 日志失败");
+                    continue;
+                }
+            };
+            let task = match serde_json::from_str::<PreviewTask>(&payload) {
+                Ok(task) => task,
+                Err(err) => {
+                    warn!(preview_id = %record.id, error = %err, "任务 payload 日志损坏，跳过重放");
+                    continue;
+                }
+            };
+            self.dispatch(LocalJob { task, attempt: 1 }).await?;
+            METRICS_COLLECTOR.record_queue_retry(self.queue_name);
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+
+    async fn dispatch(&self, job: LocalJob) -> Result<()> {
         let new_depth = self.pending_tasks.fetch_add(1, Ordering::SeqCst) + 1;
         METRICS_COLLECTOR.record_queue_enqueue(self.queue_name, Some(new_depth));
         METRICS_COLLECTOR.record_worker_inflight("local", new_depth);
 
-        match self.sender.send(task).await {
+        match self.lanes.push(job).await {
             Ok(()) => Ok(()),
             Err(e) => {
-                let previous = self
-                    .pending_tasks
-                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
-                        current.checked_sub(1)
-                    })
-                    .unwrap_or(0);
-                let depth = previous.saturating_sub(1);
+                let depth = decrement(&self.pending_tasks);
                 METRICS_COLLECTOR.record_queue_depth(self.queue_name, depth);
                 METRICS_COLLECTOR.record_worker_inflight("local", depth);
                 Err(anyhow!("发送预审任务失败: {}", e))
             }
         }
     }
+}
+
+impl Drop for LocalTaskQueue {
+    fn drop(&mut self) {
+        self.lanes.close();
+    }
+}
+
+fn decrement(counter: &AtomicU64) -> u64 {
+    counter
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+            current.checked_sub(1)
+        })
+        .unwrap_or(0)
+        .saturating_sub(1)
+}
+
+struct LocalConsumer {
+    queue_name: &'static str,
+    lanes: Arc<LocalLanes>,
+    handler: Arc<dyn PreviewTaskHandler>,
+    journal: Option<Arc<dyn Database>>,
+    pending_tasks: Arc<AtomicU64>,
+    policy: LocalRetryPolicy,
+}
+
+impl LocalConsumer {
+    async fn run(self) {
+        while let Some(job) = self.lanes.pop().await {
+            self.handle(job).await;
+        }
+        METRICS_COLLECTOR.record_queue_depth(self.queue_name, 0);
+        METRICS_COLLECTOR.record_worker_inflight("local", 0);
+    }
+
+    async fn handle(&self, job: LocalJob) {
+        let preview_id = job.task.preview_id.clone();
+        let is_shard = job.task.shard.is_some();
+        let err = match self.handler.handle_preview_task(job.task.clone()).await {
+            Ok(()) => {
+                if let Some(journal) = self.journal.as_ref().filter(|_| !is_shard) {
+                    if let Err(err) = journal.delete_task_payload(&preview_id).await {
+                        warn!(preview_id = %preview_id, error = %err, "删除任务 payload 日志失败");
+                    }
+                }
+                self.finish(true);
+                return;
+            }
+            Err(err) => err,
+        };
+
+        if job.attempt >= self.policy.max_attempts {
+            error!(
+                preview_id = %preview_id,
+                attempts = job.attempt,
+                "本地预审任务执行失败且已达最大尝试次数: {:?}",
+                err
+            );
+            if let Some(journal) = &self.journal {
+                let letter = DeadLetter::from_task(
+                    &job.task,
+                    self.queue_name,
+                    dead_letter::SOURCE_LOCAL,
+                    job.attempt as i32,
+                    format!("{:#}", err),
+                );
+                // 载荷已随死信保存，删除日志避免重放再次执行；死信重新入队时会重写日志
+                match dead_letter::record(journal, letter).await {
+                    Ok(_) if !is_shard => {
+                        if let Err(err) = journal.delete_task_payload(&preview_id).await {
+                            warn!(preview_id = %preview_id, error = %err, "删除任务 payload 日志失败");
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(dl_err) => {
+                        warn!(preview_id = %preview_id, error = %dl_err, "本地任务转入死信失败");
+                    }
+                }
+            }
+            self.finish(false);
+            return;
+        }
+
+        let delay = self.policy.delay_after(job.attempt);
+        warn!(
+            preview_id = %preview_id,
+            attempt = job.attempt,
+            retry_in_ms = delay.as_millis() as u64,
+            error = %err,
+            "本地预审任务执行失败，将延迟重试"
+        );
+        METRICS_COLLECTOR.record_queue_retry(self.queue_name);
+
+        // 延迟期间不占用消费槽位，任务仍计入待处理数；弱引用不阻止队列关闭
+        let lanes = Arc::downgrade(&self.lanes);
+        let pending_tasks = Arc::clone(&self.pending_tasks);
+        let queue_name = self.queue_name;
+        let next = LocalJob {
+            task: job.task,
+            attempt: job.attempt + 1,
+        };
+        tokio::spawn(async move {
+            sleep(delay).await;
+            let delivered = match lanes.upgrade() {
+                Some(lanes) => lanes.push(next).await.is_ok(),
+                None => false,
+            };
+            if !delivered {
+                warn!(preview_id = %preview_id, "本地队列已关闭，放弃重试");
+                let depth = decrement(&pending_tasks);
+                METRICS_COLLECTOR.record_queue_dequeue(queue_name, false, Some(depth));
+                METRICS_COLLECTOR.record_worker_inflight("local", depth);
+            }
+        });
+    }
+
+    fn finish(&self, success: bool) {
+        let depth = decrement(&self.pending_tasks);
+        METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, success, Some(depth));
+        METRICS_COLLECTOR.record_worker_inflight("local", depth);
+    }
+}
+
+#[async_trait]
+impl TaskQueue for LocalTaskQueue {
+    async fn enqueue(&self, task: PreviewTask) -> Result<()> {
+        self.dispatch(LocalJob { task, attempt: 1 }).await
+    }
+
+    /// 等待重试的任务不在通道中，重新执行前由处理器根据取消标记跳过；
+    /// payload 日志保留，供重新执行复用
+    async fn cancel(&self, preview_id: &str) -> Result<u64> {
+        let removed = self.lanes.remove(preview_id);
+        for _ in 0..removed {
+            let depth = decrement(&self.pending_tasks);
+            METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, false, Some(depth));
+            METRICS_COLLECTOR.record_worker_inflight("local", depth);
+        }
+        Ok(removed as u64)
+    }
 
     fn as_any(&self) -> &dyn std::any::Any {
         self
@@ -195,12 +714,12 @@ impl TaskQueue for LocalTaskQueue {
 #[derive(Clone)]
 pub struct NatsTaskQueue {
     context: Arc<RwLock<jetstream::Context>>,
-    subject: String,
     stream: String,
     queue_name: &'static str,
     config: NatsQueueConfig,
     reconnecting: Arc<AtomicBool>,
     healthy: Arc<AtomicBool>,
+    router: Option<Arc<TaskRouter>>,
 }
 
 #[derive(Debug, Clone)]
@@ -235,23 +754,18 @@ impl NatsTaskQueue {
 
                     let context = jetstream::new(client);
 
-                    context
-                        .get_or_create_stream(build_stream_config(config))
-                        .await
-                        .with_context(|| {
-                            format!("创建/获取 JetStream Stream [{}] 失败", config.stream)
-                        })?;
+                    ensure_stream(&context, config).await?;
 
                     info!("[ok] JetStream Stream [{}] 就绪", config.stream);
 
                     let queue = Self {
                         context: Arc::new(RwLock::new(context)),
-                        subject: config.subject.clone(),
                         stream: config.stream.clone(),
                         queue_name,
                         config: config.clone(),
                         reconnecting: Arc::new(AtomicBool::new(false)),
                         healthy: Arc::new(AtomicBool::new(true)),
+                        router: None,
                     };
 
                     queue.start_health_monitor();
@@ -338,6 +852,15 @@ impl NatsTaskQueue {
         self.queue_name
     }
 
+    pub fn with_router(mut self, router: Arc<TaskRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    pub fn router(&self) -> Option<&Arc<TaskRouter>> {
+        self.router.as_ref()
+    }
+
     pub async fn get_queue_depth(&self) -> Result<u64> {
         let context = self.context.read().await;
         let mut stream = context
@@ -350,6 +873,40 @@ impl NatsTaskQueue {
         Ok(info.state.messages)
     }
 
+    /// 各通道消费者上尚未投递的消息数；尚无 worker 创建消费者的通道不在结果中
+    pub async fn lane_depths(&self) -> Result<Vec<LaneDepth>> {
+        let context = self.context.read().await;
+        let stream = context
+            .get_stream(&self.stream)
+            .await
+            .context("获取Stream失败")?;
+
+        let mut depths = Vec::with_capacity(QueuePriority::ALL.len());
+        for priority in QueuePriority::ALL {
+            let durable = lane_durable(&self.config, None, priority);
+            let mut consumer = match stream
+                .get_consumer::<consumer::pull::Config>(&durable)
+                .await
+            {
+                Ok(consumer) => consumer,
+                Err(err) => {
+                    debug!(consumer = %durable, error = %err, "获取通道消费者失败");
+                    continue;
+                }
+            };
+            let info = consumer
+                .info()
+                .await
+                .with_context(|| format!("获取消费者 [{}] 信息失败", durable))?;
+            depths.push(LaneDepth {
+                lane: priority,
+                depth: info.num_pending,
+                clients: None,
+            });
+        }
+        Ok(depths)
+    }
+
     pub async fn stream_metrics(&self) -> Result<QueueStreamMetrics> {
         let context = self.context.read().await;
         let mut stream = context
@@ -447,10 +1004,7 @@ impl NatsTaskQueue {
                 Ok(Ok(client)) => {
                     let new_context = jetstream::new(client);
 
-                    match new_context
-                        .get_or_create_stream(build_stream_config(&self.config))
-                        .await
-                    {
+                    match ensure_stream(&new_context, &self.config).await {
                         Ok(_) => {
                             let mut context_guard = self.context.write().await;
                             *context_guard = new_context;
@@ -509,12 +1063,18 @@ impl NatsTaskQueue {
 
 #[async_trait]
 impl TaskQueue for NatsTaskQueue {
-    async fn enqueue(&self, task: PreviewTask) -> Result<()> {
+    async fn enqueue(&self, mut task: PreviewTask) -> Result<()> {
+        if let Some(router) = self.router.as_ref() {
+            if router.route(&mut task).await? == Routed::Held {
+                return Ok(());
+            }
+        }
         let payload = serde_json::to_vec(&task).context("序列化预审任务失败")?;
+        let subject = lane_subject(&self.config, task.route.as_deref(), task.priority);
 
         let context = self.context.read().await;
         let ack = context
-            .publish(self.subject.clone(), payload.into())
+            .publish(subject, payload.into())
             .await
             .context("发布预审任务消息失败")?;
 
@@ -523,6 +1083,13 @@ impl TaskQueue for NatsTaskQueue {
         Ok(())
     }
 
+    async fn cancel(&self, preview_id: &str) -> Result<u64> {
+        Ok(self
+            .router
+            .as_ref()
+            .map_or(0, |router| router.cancel(preview_id)))
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -532,6 +1099,15 @@ pub struct NatsTaskQueueConsumer {
     queue_name: &'static str,
     context: jetstream::Context,
     config: NatsQueueConfig,
+    policy: LanePolicy,
+    pool: Option<String>,
+}
+
+/// 只解析公平分组需要的字段
+#[derive(Deserialize)]
+struct TaskClientEnvelope {
+    #[serde(default)]
+    client_id: Option<String>,
 }
 
 impl NatsTaskQueueConsumer {
@@ -539,17 +1115,72 @@ impl NatsTaskQueueConsumer {
         queue_name: &'static str,
         context: jetstream::Context,
         config: NatsQueueConfig,
+        policy: LanePolicy,
     ) -> Self {
         Self {
             queue_name,
             context,
             config,
+            policy,
+            pool: None,
         }
     }
 
-    pub async fn connect(queue_name: &'static str, config: NatsQueueConfig) -> Result<Self> {
+    /// 只消费路由到指定任务池的任务
+    pub fn with_pool(mut self, pool: Option<String>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    pub async fn connect(
+        queue_name: &'static str,
+        config: NatsQueueConfig,
+        policy: LanePolicy,
+    ) -> Result<Self> {
         let queue = NatsTaskQueue::connect(queue_name, &config).await?;
-        Ok(Self::new(queue_name, queue.jetstream_context(), config))
+        Ok(Self::new(
+            queue_name,
+            queue.jetstream_context(),
+            config,
+            policy,
+        ))
+    }
+
+    /// 死信发布失败时仍终止消息：该消息已不会再被投递，内容保留在日志中
+    /// 重新发布到原 subject 后确认原消息，投递次数从头计算；发布失败时退回 NAK
+    async fn return_message(&self, message: &jetstream::Message) {
+        let republished = match self
+            .context
+            .publish(message.subject.clone(), message.payload.clone())
+            .await
+        {
+            Ok(ack) => ack.await.map(|_| ()).map_err(anyhow::Error::from),
+            Err(err) => Err(err.into()),
+        };
+        let result = match republished {
+            Ok(()) => message.ack().await,
+            Err(err) => {
+                warn!("重新发布交还的任务失败，改为 NAK: {:#}", err);
+                message.ack_with(AckKind::Nak(None)).await
+            }
+        };
+        if let Err(err) = result {
+            warn!("确认交还的任务消息失败: {:#}", err);
+        }
+    }
+
+    async fn dead_letter_and_term(&self, message: &jetstream::Message, letter: &DeadLetter) {
+        if let Err(err) = dead_letter::publish_nats(&self.context, &self.config, letter).await {
+            error!(
+                preview_id = %letter.preview_id,
+                payload = %letter.payload,
+                error = %err,
+                "发布死信失败"
+            );
+        }
+        if let Err(term_err) = message.ack_with(AckKind::Term).await {
+            warn!("终止消息失败: {:#}", term_err);
+        }
     }
 
     pub async fn run(self, handler: Arc<dyn PreviewTaskHandler>) -> Result<()> {
@@ -557,164 +1188,58 @@ impl NatsTaskQueueConsumer {
             stream = %self.config.stream,
             subject = %self.config.subject,
             durable_consumer = %self.config.durable_consumer,
+            pool = ?self.pool,
             ack_wait_ms = self.config.ack_wait_ms,
             max_batch = self.config.max_batch,
             pull_wait_ms = self.config.pull_wait_ms,
             "启动 NATS 任务队列消费者"
         );
 
+        let mut rotation = LaneRotation::new(self.policy.clone());
+        // 已拉取未处理的消息跨批次保留在同一调度器中，客户端公平不限于单批
+        let mut pending = FairScheduler::new(self.policy.clone());
         loop {
             info!(
                 stream = %self.config.stream,
                 durable_consumer = %self.config.durable_consumer,
                 "准备连接 JetStream stream/consumer"
             );
-            let stream = self
-                .context
-                .get_or_create_stream(build_stream_config(&self.config))
-                .await
-                .with_context(|| {
-                    format!("创建/获取 JetStream Stream [{}] 失败", self.config.stream)
-                })?;
-
-            let consumer = stream
-                .get_or_create_consumer(
-                    &self.config.durable_consumer,
-                    build_consumer_config(&self.config),
-                )
-                .await
-                .with_context(|| {
-                    format!("创建/获取消费者 [{}] 失败", self.config.durable_consumer)
-                })?;
+            let consumers = self.bind_lane_consumers().await?;
 
-            let inflight = Arc::new(AtomicU64::new(0));
+            let inflight = AtomicU64::new(0);
             METRICS_COLLECTOR.record_worker_inflight(&self.config.durable_consumer, 0);
 
-            let mut messages = consumer
-                .stream()
-                .max_messages_per_batch(self.config.max_batch)
-                .expires(Duration::from_millis(self.config.pull_wait_ms))
-                .messages()
-                .await
-                .context("获取 JetStream 消息流失败")?;
-
-            while let Some(item) = messages.next().await {
-                match item {
-                    Ok(message) => {
-                        let inflight_now = inflight.fetch_add(1, Ordering::SeqCst) + 1;
-                        METRICS_COLLECTOR
-                            .record_worker_inflight(&self.config.durable_consumer, inflight_now);
-
-                        let payload = message.payload.clone();
-                        let mut success = false;
-                        let mut retry = false;
-
-                        match serde_json::from_slice::<PreviewTask>(&payload) {
-                            Ok(task) => {
-                                let preview_id = task.preview_id.clone();
-                                let message_info = message.info();
-                                let delivered = message_info
-                                    .as_ref()
-                                    .map(|info| info.delivered)
-                                    .unwrap_or(0);
-                                let pending =
-                                    message_info.as_ref().map(|info| info.pending).unwrap_or(0);
-                                let stream_sequence = message_info
-                                    .as_ref()
-                                    .map(|info| info.stream_sequence)
-                                    .unwrap_or(0);
-                                let consumer_sequence = message_info
-                                    .as_ref()
-                                    .map(|info| info.consumer_sequence)
-                                    .unwrap_or(0);
-                                tracing::info!(
-                                    target: "queue.consumer",
-                                    event = events::QUEUE_DEQUEUE,
-                                    preview_id = %preview_id,
-                                    stream = %self.config.stream,
-                                    consumer = %self.config.durable_consumer,
-                                    delivered_attempts = delivered,
-                                    pending,
-                                    stream_sequence,
-                                    consumer_sequence
-                                );
-                                tracing::debug!(
-                                    preview_id = %preview_id,
-                                    stream = %self.config.stream,
-                                    consumer = %self.config.durable_consumer,
-                                    ack_wait_ms = self.config.ack_wait_ms,
-                                    "收到预审任务消息"
-                                );
-                                match handler.handle_preview_task(task).await {
-                                    Ok(_) => {
-                                        success = true;
-                                        debug!(
-                                            preview_id = %preview_id,
-                                            stream = %self.config.stream,
-                                            consumer = %self.config.durable_consumer,
-                                            "预审任务处理完成，准备 ACK"
-                                        );
-                                        if let Err(err) = message.ack().await {
-                                            warn!(
-                                                preview_id = %preview_id,
-                                                error = %err,
-                                                "预审任务完成但 ACK 失败"
-                                            );
-                                            success = false;
-                                            retry = true;
-                                        } else {
-                                            debug!(preview_id = %preview_id, "NATS 消息 ACK 成功");
-                                        }
-                                    }
-                                    Err(err) => {
-                                        error!(
-                                            preview_id = %preview_id,
-                                            error = %err,
-                                            "预审任务处理失败，将重试"
-                                        );
-                                        retry = true;
-                                        if let Err(nak_err) =
-                                            message.ack_with(AckKind::Nak(None)).await
-                                        {
-                                            warn!("发送 NAK 失败: {:#}", nak_err);
-                                        } else {
-                                            warn!(
-                                                preview_id = %preview_id,
-                                                "已发送 NAK，等待 JetStream 重试"
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                            Err(err) => {
-                                error!("无法解析任务消息，将终止该消息: {:#}", err);
-                                if let Err(term_err) = message.ack_with(AckKind::Term).await {
-                                    warn!("终止消息失败: {:#}", term_err);
-                                } else {
-                                    warn!("因解析失败终止消息，防止重复投递");
-                                }
-                            }
-                        }
-
-                        METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, success, None);
-                        if retry {
-                            METRICS_COLLECTOR.record_queue_retry(self.queue_name);
-                        }
-
-                        let previous = inflight
-                            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
-                                current.checked_sub(1)
-                            })
-                            .unwrap_or(0);
-                        let remaining = previous.saturating_sub(1);
-                        METRICS_COLLECTOR
-                            .record_worker_inflight(&self.config.durable_consumer, remaining);
-                    }
-                    Err(err) => {
+            loop {
+                // 主节点要求停止接单时不再拉取，未拉取的消息留给其他 worker
+                let paused = crate::util::worker::intake_paused();
+                if !paused && pending.len() <= self.config.max_batch / 2 {
+                    if let Err(err) = self
+                        .fetch_into(&consumers, &mut rotation, &mut pending)
+                        .await
+                    {
                         warn!("从 NATS 拉取消息失败: {:#}", err);
                         break;
                     }
                 }
+
+                match pending.pop() {
+                    Some(message) => {
+                        let mut waiting: Vec<&jetstream::Message> = pending.iter().collect();
+                        waiting.push(&message);
+                        with_progress_acks(
+                            self.process_message(&message, &handler, &inflight),
+                            &waiting,
+                            self.progress_interval(),
+                        )
+                        .await
+                    }
+                    None if paused => {
+                        sleep(Duration::from_millis(self.config.pull_wait_ms.max(500))).await;
+                    }
+                    None => {
+                        sleep(Duration::from_millis(self.config.pull_wait_ms.max(100))).await;
+                    }
+                }
             }
 
             METRICS_COLLECTOR.record_worker_inflight(&self.config.durable_consumer, 0);
@@ -728,9 +1253,683 @@ impl NatsTaskQueueConsumer {
             sleep(Duration::from_millis(self.config.pull_wait_ms.max(500))).await;
         }
     }
-}
 
-pub struct DirectTaskQueue {
+    async fn bind_lane_consumers(&self) -> Result<Vec<(QueuePriority, consumer::PullConsumer)>> {
+        let stream = ensure_stream(&self.context, &self.config).await?;
+        let mut consumers = Vec::with_capacity(QueuePriority::ALL.len());
+        for priority in QueuePriority::ALL {
+            let pool = self.pool.as_deref();
+            let durable = lane_durable(&self.config, pool, priority);
+            let consumer = stream
+                .get_or_create_consumer(
+                    &durable,
+                    build_consumer_config(&self.config, pool, priority),
+                )
+                .await
+                .with_context(|| format!("创建/获取消费者 [{}] 失败", durable))?;
+            consumers.push((priority, consumer));
+        }
+        Ok(consumers)
+    }
+
+    /// 按通道轮询顺序拉取第一批非空消息补入待处理调度器，缓冲最多 `max_batch` 条；
+    /// 加权轮询保证缓冲中的消息在有限轮次内出队
+    async fn fetch_into(
+        &self,
+        consumers: &[(QueuePriority, consumer::PullConsumer)],
+        rotation: &mut LaneRotation,
+        pending: &mut FairScheduler<jetstream::Message>,
+    ) -> Result<()> {
+        let wanted = self.config.max_batch.max(1).saturating_sub(pending.len());
+        if wanted == 0 {
+            return Ok(());
+        }
+        for priority in rotation.next_order() {
+            let Some((_, consumer)) = consumers.iter().find(|(lane, _)| *lane == priority) else {
+                continue;
+            };
+            let mut batch = consumer
+                .fetch()
+                .max_messages(wanted)
+                .messages()
+                .await
+                .with_context(|| format!("拉取 {} 通道消息失败", priority.as_str()))?;
+
+            let mut fetched = 0usize;
+            while let Some(item) = batch.next().await {
+                let message = item.map_err(|err| anyhow!("读取 JetStream 消息失败: {}", err))?;
+                let client_key = serde_json::from_slice::<TaskClientEnvelope>(&message.payload)
+                    .ok()
+                    .and_then(|envelope| envelope.client_id)
+                    .unwrap_or_else(|| lane_scheduler::INTERACTIVE_CLIENT.to_string());
+                pending.push(priority, &client_key, message)?;
+                fetched += 1;
+            }
+            if fetched > 0 {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// 处理中确认的发送周期，取 ack_wait 的一半
+    fn progress_interval(&self) -> Duration {
+        Duration::from_millis((self.config.ack_wait_ms / 2).max(1000))
+    }
+
+    async fn process_message(
+        &self,
+        message: &jetstream::Message,
+        handler: &Arc<dyn PreviewTaskHandler>,
+        inflight: &AtomicU64,
+    ) {
+        let inflight_now = inflight.fetch_add(1, Ordering::SeqCst) + 1;
+        METRICS_COLLECTOR.record_worker_inflight(&self.config.durable_consumer, inflight_now);
+
+        let payload = message.payload.clone();
+        let mut success = false;
+        let mut retry = false;
+
+        match serde_json::from_slice::<PreviewTask>(&payload) {
+            Ok(task) => {
+                let preview_id = task.preview_id.clone();
+                let priority = task.priority;
+                let message_info = message.info();
+                let delivered = message_info
+                    .as_ref()
+                    .map(|info| info.delivered)
+                    .unwrap_or(0);
+                let pending = message_info.as_ref().map(|info| info.pending).unwrap_or(0);
+                let stream_sequence = message_info
+                    .as_ref()
+                    .map(|info| info.stream_sequence)
+                    .unwrap_or(0);
+                let consumer_sequence = message_info
+                    .as_ref()
+                    .map(|info| info.consumer_sequence)
+                    .unwrap_or(0);
+                tracing::info!(
+                    target: "queue.consumer",
+                    event = events::QUEUE_DEQUEUE,
+                    preview_id = %preview_id,
+                    stream = %self.config.stream,
+                    consumer = %self.config.durable_consumer,
+                    lane = priority.as_str(),
+                    client_id = task.client_id.as_deref().unwrap_or(""),
+                    delivered_attempts = delivered,
+                    pending,
+                    stream_sequence,
+                    consumer_sequence
+                );
+                tracing::debug!(
+                    preview_id = %preview_id,
+                    stream = %self.config.stream,
+                    consumer = %self.config.durable_consumer,
+                    ack_wait_ms = self.config.ack_wait_ms,
+                    "收到预审任务消息"
+                );
+                match handler.handle_preview_task(task).await {
+                    Ok(_) => {
+                        success = true;
+                        debug!(
+                            preview_id = %preview_id,
+                            stream = %self.config.stream,
+                            consumer = %self.config.durable_consumer,
+                            "预审任务处理完成，准备 ACK"
+                        );
+                        if let Err(err) = message.ack().await {
+                            warn!(
+                                preview_id = %preview_id,
+                                error = %err,
+                                "预审任务完成但 ACK 失败"
+                            );
+                            success = false;
+                            retry = true;
+                        } else {
+                            debug!(preview_id = %preview_id, "NATS 消息 ACK 成功");
+                        }
+                    }
+                    Err(err) if is_returned(&err) => {
+                        warn!(preview_id = %preview_id, error = %err, "任务交还队列");
+                        self.return_message(message).await;
+                    }
+                    Err(err)
+                        if self.config.max_deliver > 0
+                            && delivered >= i64::from(self.config.max_deliver) =>
+                    {
+                        error!(
+                            preview_id = %preview_id,
+                            delivered_attempts = delivered,
+                            error = %err,
+                            "预审任务已达最大投递次数，转入死信"
+                        );
+                        let letter = DeadLetter {
+                            id: None,
+                            preview_id: preview_id.clone(),
+                            queue: self.queue_name.to_string(),
+                            source: dead_letter::SOURCE_NATS.to_string(),
+                            payload: String::from_utf8_lossy(&payload).into_owned(),
+                            failure_reason: dead_letter::REASON_MAX_ATTEMPTS.to_string(),
+                            attempts: delivered as i32,
+                            last_error: Some(format!("{:#}", err)),
+                        };
+                        self.dead_letter_and_term(message, &letter).await;
+                    }
+                    Err(err) => {
+                        error!(
+                            preview_id = %preview_id,
+                            error = %err,
+                            "预审任务处理失败，将重试"
+                        );
+                        retry = true;
+                        if let Err(nak_err) = message.ack_with(AckKind::Nak(None)).await {
+                            warn!("发送 NAK 失败: {:#}", nak_err);
+                        } else {
+                            warn!(
+                                preview_id = %preview_id,
+                                "已发送 NAK，等待 JetStream 重试"
+                            );
+                        }
+                    }
+                }
+            }
+            Err(err) => {
+                error!("无法解析任务消息，转入死信并终止该消息: {:#}", err);
+                let letter = DeadLetter::invalid_payload(
+                    &payload,
+                    self.queue_name,
+                    dead_letter::SOURCE_NATS,
+                    format!("{:#}", err),
+                );
+                self.dead_letter_and_term(message, &letter).await;
+            }
+        }
+
+        METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, success, None);
+        if retry {
+            METRICS_COLLECTOR.record_queue_retry(self.queue_name);
+        }
+
+        let remaining = decrement(inflight);
+        METRICS_COLLECTOR.record_worker_inflight(&self.config.durable_consumer, remaining);
+    }
+}
+
+/// `work` 运行期间周期性地为已拉取的消息发送处理中确认，立即发送一次；
+/// 缓冲中等待的消息不会因超过 ack_wait 被重投
+async fn with_progress_acks<T>(
+    work: impl std::future::Future<Output = T>,
+    messages: &[&jetstream::Message],
+    interval: Duration,
+) -> T {
+    tokio::pin!(work);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            output = &mut work => return output,
+            _ = ticker.tick() => {
+                for message in messages {
+                    if let Err(err) = message.ack_with(AckKind::Progress).await {
+                        debug!("发送处理进度失败: {:#}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+const DEAD_LETTER_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+fn unix_now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// 基于数据库表的任务队列，任务持久化后由消费者按租约领取
+pub struct DatabaseTaskQueue {
+    database: Arc<dyn Database>,
+    queue_name: &'static str,
+    table: String,
+    router: Option<Arc<TaskRouter>>,
+}
+
+impl DatabaseTaskQueue {
+    pub async fn new(
+        queue_name: &'static str,
+        database: Arc<dyn Database>,
+        config: DatabaseQueueConfig,
+    ) -> Result<Self> {
+        validate_queue_table_name(&config.table_name)?;
+        database
+            .ensure_task_queue_table(&config.table_name)
+            .await
+            .with_context(|| format!("初始化任务队列表 [{}] 失败", config.table_name))?;
+        info!(
+            table = %config.table_name,
+            lease_secs = config.lease_secs,
+            max_attempts = config.max_attempts,
+            "数据库任务队列已就绪"
+        );
+        Ok(Self {
+            database,
+            queue_name,
+            table: config.table_name,
+            router: None,
+        })
+    }
+
+    pub fn with_router(mut self, router: Arc<TaskRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    pub fn router(&self) -> Option<&Arc<TaskRouter>> {
+        self.router.as_ref()
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table
+    }
+
+    pub async fn stats(&self) -> Result<QueuedTaskStats> {
+        self.database
+            .queued_task_stats(&self.table, self.queue_name)
+            .await
+    }
+}
+
+#[async_trait]
+impl TaskQueue for DatabaseTaskQueue {
+    async fn enqueue(&self, mut task: PreviewTask) -> Result<()> {
+        if let Some(router) = self.router.as_ref() {
+            if router.route(&mut task).await? == Routed::Held {
+                return Ok(());
+            }
+        }
+        let payload = serde_json::to_string(&task).context("序列化预审任务失败")?;
+        let queue_key = match task.shard.as_ref() {
+            Some(shard) => shard_queue_key(&task.preview_id, &shard.job_id, shard.index),
+            None => task.preview_id.clone(),
+        };
+        let record = NewQueuedTask {
+            id: uuid::Uuid::new_v4().to_string(),
+            queue: pool_queue_name(self.queue_name, task.route.as_deref()),
+            preview_id: queue_key,
+            payload,
+            visible_at: unix_now(),
+        };
+        self.database
+            .enqueue_queued_task(&self.table, &record)
+            .await
+            .context("写入数据库任务队列失败")?;
+
+        let depth = self
+            .stats()
+            .await
+            .ok()
+            .map(|stats| stats.pending + stats.leased);
+        METRICS_COLLECTOR.record_queue_enqueue(self.queue_name, depth);
+        Ok(())
+    }
+
+    async fn cancel(&self, preview_id: &str) -> Result<u64> {
+        let mut removed = self
+            .database
+            .cancel_queued_tasks(&self.table, self.queue_name, preview_id)
+            .await?;
+        if let Some(router) = self.router.as_ref() {
+            removed += router.cancel(preview_id);
+            for pool in router.known_pools() {
+                let queue = pool_queue_name(self.queue_name, Some(&pool));
+                removed += self
+                    .database
+                    .cancel_queued_tasks(&self.table, &queue, preview_id)
+                    .await?;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 数据库队列消费者：轮询领取任务，处理期间定期续租；
+/// 进程崩溃时租约到期，任务会被其他消费者重新领取
+pub struct DatabaseTaskQueueConsumer {
+    queue_name: &'static str,
+    /// 领取任务的队列名，任务池消费者为 `{queue_name}.{pool}`
+    claim_queue: String,
+    database: Arc<dyn Database>,
+    config: DatabaseQueueConfig,
+    consumer_id: String,
+}
+
+impl DatabaseTaskQueueConsumer {
+    pub fn new(
+        queue_name: &'static str,
+        database: Arc<dyn Database>,
+        config: DatabaseQueueConfig,
+    ) -> Self {
+        let consumer_id = format!(
+            "{}-{}",
+            std::process::id(),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        Self {
+            queue_name,
+            claim_queue: queue_name.to_string(),
+            database,
+            config,
+            consumer_id,
+        }
+    }
+
+    /// 只领取路由到指定任务池的任务
+    pub fn with_pool(mut self, pool: Option<String>) -> Self {
+        self.claim_queue = pool_queue_name(self.queue_name, pool.as_deref());
+        self
+    }
+
+    pub async fn run(self, handler: Arc<dyn PreviewTaskHandler>) -> Result<()> {
+        validate_queue_table_name(&self.config.table_name)?;
+        self.database
+            .ensure_task_queue_table(&self.config.table_name)
+            .await
+            .with_context(|| format!("初始化任务队列表 [{}] 失败", self.config.table_name))?;
+
+        let max_inflight = self.config.max_inflight.max(1);
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms.max(100));
+        info!(
+            table = %self.config.table_name,
+            queue = %self.claim_queue,
+            consumer = %self.consumer_id,
+            max_inflight,
+            poll_interval_ms = poll_interval.as_millis() as u64,
+            "启动数据库任务队列消费者"
+        );
+
+        let semaphore = Arc::new(Semaphore::new(max_inflight));
+        let inflight = Arc::new(AtomicU64::new(0));
+        METRICS_COLLECTOR.record_worker_inflight(&self.consumer_id, 0);
+        let mut last_sweep: Option<std::time::Instant> = None;
+
+        loop {
+            if last_sweep.map_or(true, |at| at.elapsed() >= DEAD_LETTER_SWEEP_INTERVAL) {
+                last_sweep = Some(std::time::Instant::now());
+                self.move_failed_to_dead_letters().await;
+            }
+
+            let available = semaphore.available_permits();
+            if available == 0 || crate::util::worker::intake_paused() {
+                sleep(poll_interval).await;
+                continue;
+            }
+
+            let now = unix_now();
+            let claim = TaskClaimRequest {
+                queue: self.claim_queue.clone(),
+                consumer_id: self.consumer_id.clone(),
+                lease_token: uuid::Uuid::new_v4().to_string(),
+                now,
+                lease_expires_at: now + self.config.lease_secs.max(1) as i64,
+                max_attempts: self.config.max_attempts.max(1) as i32,
+                limit: available as u32,
+            };
+            let tasks = match self
+                .database
+                .claim_queued_tasks(&self.config.table_name, &claim)
+                .await
+            {
+                Ok(tasks) => tasks,
+                Err(err) => {
+                    warn!(consumer = %self.consumer_id, error = %err, "领取数据库队列任务失败");
+                    sleep(poll_interval).await;
+                    continue;
+                }
+            };
+
+            if tasks.is_empty() {
+                sleep(poll_interval).await;
+                continue;
+            }
+
+            for record in tasks {
+                let permit = Arc::clone(&semaphore)
+                    .acquire_owned()
+                    .await
+                    .context("数据库队列并发信号量已关闭")?;
+                let inflight_now = inflight.fetch_add(1, Ordering::SeqCst) + 1;
+                METRICS_COLLECTOR.record_worker_inflight(&self.consumer_id, inflight_now);
+
+                let worker = TaskLeaseWorker {
+                    queue_name: self.queue_name,
+                    database: Arc::clone(&self.database),
+                    config: self.config.clone(),
+                    consumer_id: self.consumer_id.clone(),
+                };
+                let handler = Arc::clone(&handler);
+                let inflight = Arc::clone(&inflight);
+                tokio::spawn(async move {
+                    worker.process(record, handler).await;
+                    let previous = inflight
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                            current.checked_sub(1)
+                        })
+                        .unwrap_or(0);
+                    METRICS_COLLECTOR
+                        .record_worker_inflight(&worker.consumer_id, previous.saturating_sub(1));
+                    drop(permit);
+                });
+            }
+        }
+    }
+}
+
+impl DatabaseTaskQueueConsumer {
+    /// 将 failed 状态的任务（含租约过期耗尽的任务）转入死信后从队列表删除
+    async fn move_failed_to_dead_letters(&self) {
+        let table = self.config.table_name.as_str();
+        let failed = match self
+            .database
+            .list_failed_queued_tasks(table, &self.claim_queue, 100)
+            .await
+        {
+            Ok(failed) => failed,
+            Err(err) => {
+                warn!(table = %table, error = %err, "查询失败任务失败");
+                return;
+            }
+        };
+
+        for task in failed {
+            // 分片的队列键不是预审 ID，死信按载荷中的所属预审记录
+            let parsed = serde_json::from_str::<PreviewTask>(&task.payload).ok();
+            let failure_reason = if parsed.is_some() {
+                dead_letter::REASON_MAX_ATTEMPTS
+            } else {
+                dead_letter::REASON_INVALID_PAYLOAD
+            };
+            let letter = DeadLetter {
+                id: Some(task.id.clone()),
+                preview_id: parsed.map_or_else(|| task.preview_id.clone(), |t| t.preview_id),
+                queue: task.queue.clone(),
+                source: dead_letter::SOURCE_DATABASE.to_string(),
+                payload: task.payload.clone(),
+                failure_reason: failure_reason.to_string(),
+                attempts: task.attempts,
+                last_error: task.last_error.clone(),
+            };
+            if let Err(err) = dead_letter::record(&self.database, letter).await {
+                warn!(task_id = %task.id, error = %err, "失败任务转入死信失败");
+                continue;
+            }
+            if let Err(err) = self.database.delete_queued_task(table, &task.id).await {
+                warn!(task_id = %task.id, error = %err, "删除已转入死信的任务失败");
+            }
+        }
+    }
+}
+
+struct TaskLeaseWorker {
+    queue_name: &'static str,
+    database: Arc<dyn Database>,
+    config: DatabaseQueueConfig,
+    consumer_id: String,
+}
+
+impl TaskLeaseWorker {
+    async fn process(&self, record: QueuedTaskRecord, handler: Arc<dyn PreviewTaskHandler>) {
+        let table = self.config.table_name.as_str();
+        let lease_token = record.lease_token.clone().unwrap_or_default();
+
+        let task = match serde_json::from_str::<PreviewTask>(&record.payload) {
+            Ok(task) => task,
+            Err(err) => {
+                error!(task_id = %record.id, error = %err, "无法解析数据库队列任务，标记为失败");
+                let reason = format!("payload 解析失败: {}", err);
+                if let Err(err) = self
+                    .database
+                    .release_queued_task(table, &record.id, &lease_token, &reason, None)
+                    .await
+                {
+                    warn!(task_id = %record.id, error = %err, "标记任务失败时出错");
+                }
+                METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, false, None);
+                return;
+            }
+        };
+
+        tracing::info!(
+            target: "queue.consumer",
+            event = events::QUEUE_DEQUEUE,
+            preview_id = %record.preview_id,
+            table = %table,
+            consumer = %self.consumer_id,
+            delivered_attempts = record.attempts
+        );
+
+        let renewal = self.spawn_lease_renewal(&record.id, &lease_token);
+        let result = handler.handle_preview_task(task).await;
+        renewal.abort();
+
+        let success = match result {
+            Ok(()) => match self
+                .database
+                .complete_queued_task(table, &record.id, &lease_token)
+                .await
+            {
+                Ok(true) => true,
+                Ok(false) => {
+                    warn!(
+                        preview_id = %record.preview_id,
+                        task_id = %record.id,
+                        "任务已处理完成，但租约已失效，任务可能被重复执行"
+                    );
+                    true
+                }
+                Err(err) => {
+                    warn!(preview_id = %record.preview_id, error = %err, "确认任务完成失败");
+                    false
+                }
+            },
+            Err(err) if is_returned(&err) => {
+                warn!(preview_id = %record.preview_id, error = %err, "任务交还队列");
+                if let Err(return_err) = self
+                    .database
+                    .return_queued_task(table, &record.id, &lease_token)
+                    .await
+                {
+                    warn!(
+                        preview_id = %record.preview_id,
+                        error = %return_err,
+                        "交还任务失败，等待租约过期后重新投递"
+                    );
+                }
+                false
+            }
+            Err(err) => {
+                let exhausted = record.attempts >= self.config.max_attempts.max(1) as i32;
+                let retry_at = (!exhausted).then(|| {
+                    unix_now()
+                        + self.config.retry_backoff_secs as i64 * record.attempts.max(1) as i64
+                });
+                if exhausted {
+                    error!(
+                        preview_id = %record.preview_id,
+                        attempts = record.attempts,
+                        error = %err,
+                        "预审任务处理失败且已达最大尝试次数，标记为失败"
+                    );
+                } else {
+                    warn!(
+                        preview_id = %record.preview_id,
+                        attempts = record.attempts,
+                        error = %err,
+                        "预审任务处理失败，将延迟重试"
+                    );
+                    METRICS_COLLECTOR.record_queue_retry(self.queue_name);
+                }
+                if let Err(release_err) = self
+                    .database
+                    .release_queued_task(
+                        table,
+                        &record.id,
+                        &lease_token,
+                        &format!("{:#}", err),
+                        retry_at,
+                    )
+                    .await
+                {
+                    warn!(
+                        preview_id = %record.preview_id,
+                        error = %release_err,
+                        "释放任务租约失败，等待租约过期后重新投递"
+                    );
+                }
+                false
+            }
+        };
+
+        METRICS_COLLECTOR.record_queue_dequeue(self.queue_name, success, None);
+    }
+
+    fn spawn_lease_renewal(&self, task_id: &str, lease_token: &str) -> tokio::task::JoinHandle<()> {
+        let database = Arc::clone(&self.database);
+        let table = self.config.table_name.clone();
+        let lease_secs = self.config.lease_secs.max(1);
+        let task_id = task_id.to_string();
+        let lease_token = lease_token.to_string();
+        tokio::spawn(async move {
+            let interval = Duration::from_secs((lease_secs / 3).max(1));
+            loop {
+                sleep(interval).await;
+                let expires_at = unix_now() + lease_secs as i64;
+                match database
+                    .extend_queued_task_lease(&table, &task_id, &lease_token, expires_at)
+                    .await
+                {
+                    Ok(true) => debug!(task_id = %task_id, "任务租约已续期"),
+                    Ok(false) => {
+                        warn!(task_id = %task_id, "任务租约已丢失，停止续租");
+                        break;
+                    }
+                    Err(err) => warn!(task_id = %task_id, error = %err, "任务续租失败"),
+                }
+            }
+        })
+    }
+}
+
+fn pool_queue_name(queue_name: &str, pool: Option<&str>) -> String {
+    match pool {
+        Some(pool) => format!("{}.{}", queue_name, pool),
+        None => queue_name.to_string(),
+    }
+}
+
+pub struct DirectTaskQueue {
     handler: Arc<dyn PreviewTaskHandler>,
 }
 
@@ -757,10 +1956,38 @@ impl TaskQueue for DirectTaskQueue {
     }
 }
 
+/// normal 通道沿用原主题与消费者名称，已有部署和旧版本 worker 无需迁移；
+/// 任务池使用 `{subject}.pool.{pool}` 子主题
+fn lane_subject(config: &NatsQueueConfig, pool: Option<&str>, priority: QueuePriority) -> String {
+    let base = match pool {
+        Some(pool) => format!("{}.pool.{}", config.subject, pool),
+        None => config.subject.clone(),
+    };
+    match priority {
+        QueuePriority::Normal => base,
+        other => format!("{}.{}", base, other.as_str()),
+    }
+}
+
+fn lane_durable(config: &NatsQueueConfig, pool: Option<&str>, priority: QueuePriority) -> String {
+    let base = match pool {
+        Some(pool) => format!("{}-{}", config.durable_consumer, pool),
+        None => config.durable_consumer.clone(),
+    };
+    match priority {
+        QueuePriority::Normal => base,
+        other => format!("{}-{}", base, other.as_str()),
+    }
+}
+
 fn build_stream_config(config: &NatsQueueConfig) -> stream::Config {
     let mut stream_config = stream::Config::default();
     stream_config.name = config.stream.clone();
-    stream_config.subjects = vec![config.subject.clone()];
+    stream_config.subjects = QueuePriority::ALL
+        .iter()
+        .map(|priority| lane_subject(config, None, *priority))
+        .chain(std::iter::once(format!("{}.pool.>", config.subject)))
+        .collect();
     stream_config.retention = stream::RetentionPolicy::WorkQueue;
     let max_messages = config.max_messages.unwrap_or(-1);
     stream_config.max_messages = max_messages;
@@ -772,22 +1999,66 @@ fn build_stream_config(config: &NatsQueueConfig) -> stream::Config {
     stream_config
 }
 
-fn build_consumer_config(config: &NatsQueueConfig) -> consumer::pull::Config {
+/// 获取或创建任务流；旧版本创建的流只有 normal 主题，补齐各通道主题
+async fn ensure_stream(
+    context: &jetstream::Context,
+    config: &NatsQueueConfig,
+) -> Result<stream::Stream> {
+    let stream_config = build_stream_config(config);
+    let mut stream = context
+        .get_or_create_stream(stream_config.clone())
+        .await
+        .with_context(|| format!("创建/获取 JetStream Stream [{}] 失败", config.stream))?;
+
+    let existing = &stream.cached_info().config.subjects;
+    let missing: Vec<String> = stream_config
+        .subjects
+        .iter()
+        .filter(|subject| !existing.contains(subject))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        let mut updated = stream.cached_info().config.clone();
+        updated.subjects.extend(missing.iter().cloned());
+        context
+            .update_stream(&updated)
+            .await
+            .with_context(|| format!("为 Stream [{}] 添加通道主题失败", config.stream))?;
+        info!(stream = %config.stream, subjects = ?missing, "已为任务流添加通道与任务池主题");
+        stream = context
+            .get_stream(&config.stream)
+            .await
+            .context("获取Stream失败")?;
+    }
+    Ok(stream)
+}
+
+fn build_consumer_config(
+    config: &NatsQueueConfig,
+    pool: Option<&str>,
+    priority: QueuePriority,
+) -> consumer::pull::Config {
     consumer::pull::Config {
-        durable_name: Some(config.durable_consumer.clone()),
+        durable_name: Some(lane_durable(config, pool, priority)),
         ack_policy: consumer::AckPolicy::Explicit,
         ack_wait: Duration::from_millis(config.ack_wait_ms),
         max_deliver: config.max_deliver as i64,
-        filter_subject: config.subject.clone(),
+        filter_subject: lane_subject(config, pool, priority),
         max_batch: config.max_batch as i64,
         ..Default::default()
     }
 }
 
+/// `pool` 为 worker 声明的任务池，为空时消费默认主题/队列
 pub async fn start_queue_worker(
     config: &TaskQueueConfig,
+    pool: Option<String>,
+    database: Option<Arc<dyn Database>>,
     handler: Arc<dyn PreviewTaskHandler>,
 ) -> Result<()> {
+    if let Some(pool) = pool.as_deref() {
+        task_routing::validate_pool_name(pool)?;
+    }
     match config.driver.clone() {
         TaskQueueDriver::Local => {
             info!("当前配置使用本地任务队列，无需独立 worker，任务在主节点内处理");
@@ -799,9 +2070,323 @@ pub async fn start_queue_worker(
                 .as_ref()
                 .cloned()
                 .ok_or_else(|| anyhow!("缺少 NATS 队列配置"))?;
-            let consumer = NatsTaskQueueConsumer::connect(PREVIEW_QUEUE_NAME, nats_config).await?;
+            let consumer = NatsTaskQueueConsumer::connect(
+                PREVIEW_QUEUE_NAME,
+                nats_config,
+                LanePolicy::from_global_config(),
+            )
+            .await?
+            .with_pool(pool);
             consumer.run(handler).await
         }
-        TaskQueueDriver::Database => Err(anyhow!("数据库任务队列驱动尚未实现，无法启动 worker")),
+        TaskQueueDriver::Database => {
+            let db_config = config
+                .database
+                .as_ref()
+                .cloned()
+                .ok_or_else(|| anyhow!("缺少数据库队列配置"))?;
+            let database = database.ok_or_else(|| anyhow!("数据库队列 worker 缺少数据库连接"))?;
+            DatabaseTaskQueueConsumer::new(PREVIEW_QUEUE_NAME, database, db_config)
+                .with_pool(pool)
+                .run(handler)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    /// 每个任务前 `failures` 次失败，之后成功；记录执行次数与最大并发
+    struct FlakyHandler {
+        failures: u32,
+        delay: Duration,
+        attempts: Mutex<HashMap<String, u32>>,
+        running: AtomicU64,
+        peak: AtomicU64,
+        done: mpsc::UnboundedSender<String>,
+    }
+
+    #[async_trait]
+    impl PreviewTaskHandler for FlakyHandler {
+        async fn handle_preview_task(&self, task: PreviewTask) -> Result<()> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            sleep(self.delay).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            let attempt = {
+                let mut attempts = self.attempts.lock().unwrap();
+                let entry = attempts.entry(task.preview_id.clone()).or_insert(0);
+                *entry += 1;
+                *entry
+            };
+            if attempt <= self.failures {
+                return Err(anyhow!("模拟失败"));
+            }
+            let _ = self.done.send(task.preview_id);
+            Ok(())
+        }
+    }
+
+    fn task(id: &str) -> PreviewTask {
+        PreviewTask::new(PreviewBody::default(), id.to_string(), id.to_string())
+    }
+
+    fn local_config(concurrency: usize, max_attempts: u32) -> LocalQueueConfig {
+        LocalQueueConfig {
+            concurrency,
+            max_attempts,
+            retry_backoff_ms: 10,
+            ..LocalQueueConfig::default()
+        }
+    }
+
+    fn handler(
+        failures: u32,
+        delay_ms: u64,
+    ) -> (Arc<FlakyHandler>, mpsc::UnboundedReceiver<String>) {
+        let (done, rx) = mpsc::unbounded_channel();
+        let handler = Arc::new(FlakyHandler {
+            failures,
+            delay: Duration::from_millis(delay_ms),
+            attempts: Mutex::new(HashMap::new()),
+            running: AtomicU64::new(0),
+            peak: AtomicU64::new(0),
+            done,
+        });
+        (handler, rx)
+    }
+
+    #[tokio::test]
+    async fn local_queue_runs_tasks_concurrently() {
+        let (handler, mut done) = handler(0, 50);
+        let queue = LocalTaskQueue::new(
+            "test",
+            handler.clone(),
+            None,
+            &local_config(3, 1),
+            LanePolicy::default(),
+        );
+        for id in ["a", "b", "c"] {
+            queue.enqueue(task(id)).await.unwrap();
+        }
+        for _ in 0..3 {
+            tokio::time::timeout(Duration::from_secs(5), done.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        assert!(handler.peak.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn local_queue_retries_until_success() {
+        let (handler, mut done) = handler(2, 1);
+        let queue = LocalTaskQueue::new(
+            "test",
+            handler.clone(),
+            None,
+            &local_config(1, 3),
+            LanePolicy::default(),
+        );
+        queue.enqueue(task("p1")).await.unwrap();
+
+        let finished = tokio::time::timeout(Duration::from_secs(5), done.recv())
+            .await
+            .unwrap();
+        assert_eq!(finished.as_deref(), Some("p1"));
+        assert_eq!(handler.attempts.lock().unwrap()["p1"], 3);
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(queue.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn dead_lettered_local_task_is_not_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::sqlite::SqliteDatabase::new(dir.path().join("q.db").to_str().unwrap())
+            .await
+            .unwrap();
+        db.initialize().await.unwrap();
+        let database: Arc<dyn Database> = Arc::new(db);
+        let payload = serde_json::to_string(&task("p1")).unwrap();
+        database.save_task_payload("p1", &payload).await.unwrap();
+
+        let (handler, _done) = handler(u32::MAX, 1);
+        let queue = LocalTaskQueue::new(
+            "test",
+            handler.clone(),
+            Some(Arc::clone(&database)),
+            &local_config(1, 2),
+            LanePolicy::default(),
+        );
+        queue.enqueue(task("p1")).await.unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while queue.pending() > 0 && std::time::Instant::now() < deadline {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(queue.pending(), 0);
+        assert_eq!(handler.attempts.lock().unwrap()["p1"], 2);
+        assert!(database.load_task_payload("p1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn local_queue_serves_high_lane_first() {
+        let (handler, mut done) = handler(0, 20);
+        let queue = LocalTaskQueue::new(
+            "test",
+            handler.clone(),
+            None,
+            &local_config(1, 1),
+            LanePolicy::default(),
+        );
+        for i in 0..3 {
+            let mut low = task(&format!("low-{}", i));
+            low.priority = QueuePriority::Low;
+            queue.enqueue(low).await.unwrap();
+        }
+        let mut high = task("high");
+        high.priority = QueuePriority::High;
+        queue.enqueue(high).await.unwrap();
+
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            let id = tokio::time::timeout(Duration::from_secs(5), done.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            order.push(id);
+        }
+        // 消费者可能在 high 入队前已取走 low-0，但不会再让其他低优先级任务插队
+        assert!(order[..2].contains(&"high".to_string()));
+    }
+
+    #[test]
+    fn scheduling_overrides_client_supplied_field() {
+        let scheduling = TaskScheduling {
+            priority: QueuePriority::Low,
+            client_id: Some("batch".to_string()),
+            force_reocr: true,
+        };
+        let body = br#"{"userId":"u1","__queueScheduling":{"priority":"high"}}"#;
+        let payload = scheduling.embed(body).unwrap();
+        let restored = TaskScheduling::extract(&payload).unwrap();
+        assert_eq!(restored.priority, QueuePriority::Low);
+        assert_eq!(restored.client_id.as_deref(), Some("batch"));
+        assert!(restored.force_reocr);
+        assert!(TaskScheduling::extract(r#"{"userId":"u1"}"#).is_none());
+    }
+
+    #[test]
+    fn retry_delay_grows_and_caps() {
+        let policy = LocalRetryPolicy {
+            max_attempts: 10,
+            backoff: Duration::from_secs(2),
+        };
+        assert_eq!(policy.delay_after(1), Duration::from_secs(2));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(8));
+        assert_eq!(policy.delay_after(30), LocalRetryPolicy::MAX_BACKOFF);
+    }
+
+    #[test]
+    fn pool_subjects_and_durables_keep_default_names() {
+        let config = NatsQueueConfig {
+            subject: "ocr.preview".to_string(),
+            durable_consumer: "workers".to_string(),
+            ..NatsQueueConfig::default()
+        };
+        assert_eq!(
+            lane_subject(&config, None, QueuePriority::Normal),
+            "ocr.preview"
+        );
+        assert_eq!(
+            lane_subject(&config, Some("office"), QueuePriority::High),
+            "ocr.preview.pool.office.high"
+        );
+        assert_eq!(
+            lane_durable(&config, Some("office"), QueuePriority::Normal),
+            "workers-office"
+        );
+        assert_eq!(pool_queue_name("preview", Some("office")), "preview.office");
+        assert!(build_stream_config(&config)
+            .subjects
+            .contains(&"ocr.preview.pool.>".to_string()));
+    }
+
+    fn shard(job_id: &str, index: usize) -> PageShard {
+        PageShard {
+            job_id: job_id.to_string(),
+            index,
+            start_page: index as u32 * 10 + 1,
+            end_page: index as u32 * 10 + 10,
+            material_code: "m1".to_string(),
+            attach_name: "a.pdf".to_string(),
+            attach_url: "worker-cache://a.pdf".to_string(),
+            force_reocr: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn database_queue_fans_out_shards_while_parent_is_leased() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::sqlite::SqliteDatabase::new(dir.path().join("q.db").to_str().unwrap())
+            .await
+            .unwrap();
+        db.initialize().await.unwrap();
+        let database: Arc<dyn Database> = Arc::new(db);
+        let config = DatabaseQueueConfig::default();
+        let table = config.table_name.clone();
+        let queue = DatabaseTaskQueue::new("preview", Arc::clone(&database), config)
+            .await
+            .unwrap();
+        let claim = |consumer: &str| TaskClaimRequest {
+            queue: "preview".to_string(),
+            consumer_id: consumer.to_string(),
+            lease_token: format!("lease-{}", consumer),
+            now: unix_now(),
+            lease_expires_at: unix_now() + 60,
+            max_attempts: 3,
+            limit: 10,
+        };
+
+        queue.enqueue(task("p1")).await.unwrap();
+        let parent = database
+            .claim_queued_tasks(&table, &claim("initiator"))
+            .await
+            .unwrap();
+        assert_eq!(parent.len(), 1);
+
+        for index in 0..2 {
+            let mut sub = task("p1");
+            sub.shard = Some(shard("job-1", index));
+            queue.enqueue(sub).await.unwrap();
+        }
+        // 取消只移除尚未领取的分片，执行中的所属预审不受影响
+        assert_eq!(queue.cancel("p1").await.unwrap(), 2);
+
+        for index in 0..2 {
+            let mut sub = task("p1");
+            sub.shard = Some(shard("job-1", index));
+            queue.enqueue(sub).await.unwrap();
+        }
+        let shards = database
+            .claim_queued_tasks(&table, &claim("worker"))
+            .await
+            .unwrap();
+        assert_eq!(shards.len(), 2);
+        for record in &shards {
+            let task: PreviewTask = serde_json::from_str(&record.payload).unwrap();
+            assert_eq!(task.preview_id, "p1");
+            let shard = task.shard.unwrap();
+            assert_eq!(
+                record.preview_id,
+                shard_queue_key("p1", &shard.job_id, shard.index)
+            );
+        }
     }
 }
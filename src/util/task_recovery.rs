@@ -9,6 +9,7 @@ use tracing::{debug, error, info, warn};
 use crate::api::worker_proxy;
 use crate::db::{PreviewFilter, PreviewStatus};
 use crate::util::config::types::ProcessingWatchdogConfig;
+use crate::util::search;
 use crate::util::task_queue::{PreviewTask, TaskQueue, PREVIEW_QUEUE_NAME};
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 use crate::AppState;
@@ -119,6 +120,7 @@ async fn check_processing_timeouts(
                 .await
             {
                 Ok(_) => {
+                    search::schedule_index(Arc::clone(database), record.id.clone());
                     if let Err(err) = database
                         .update_preview_request_latest(&request_key, Some(&record.id), Some(status))
                         .await
@@ -157,6 +159,7 @@ async fn check_processing_timeouts(
                             .await
                         {
                             Ok(_) => {
+                                search::schedule_index(Arc::clone(database), record.id.clone());
                                 if let Err(update_err) = database
                                     .update_preview_request_latest(
                                         &request_key,
@@ -220,6 +223,7 @@ async fn check_processing_timeouts(
                         .await
                     {
                         Ok(_) => {
+                            search::schedule_index(Arc::clone(database), record.id.clone());
                             if let Err(update_err) = database
                                 .update_preview_request_latest(
                                     &request_key,
@@ -252,6 +256,7 @@ async fn check_processing_timeouts(
                     .await
                 {
                     Ok(_) => {
+                        search::schedule_index(Arc::clone(database), record.id.clone());
                         if let Err(update_err) = database
                             .update_preview_request_latest(
                                 &request_key,
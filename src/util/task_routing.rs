@@ -0,0 +1,539 @@
+//! 按 worker 能力路由预审任务：worker 通过心跳上报能力，主节点入队时
+//! 根据任务的附件格式、文件大小与事项挑选能处理它的任务池
+//!
+//! 能力登记只存在于进程内存中，主节点重启后等待 worker 下一次心跳重新上报。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::db::traits::PreviewFailureUpdate;
+use crate::db::{Database, PreviewFilter, PreviewStatus};
+use crate::util::config::types::{
+    TaskRoutingConfig, UnmatchedTaskPolicy, WorkerCapabilitiesConfig,
+};
+use crate::util::material_cache::{self, WORKER_CACHE_SCHEME};
+use crate::util::task_queue::{PreviewTask, TaskQueue};
+use crate::util::worker_fleet;
+
+/// 暂缓任务写入预审记录的错误码
+pub const NO_CAPABLE_WORKER: &str = "NO_CAPABLE_WORKER";
+
+/// 未声明 formats 的 worker 默认可处理的格式；DOCX 等需要转换的格式须显式声明
+pub const DEFAULT_FORMATS: &[&str] = &[
+    "pdf", "jpg", "jpeg", "png", "bmp", "gif", "tif", "tiff", "webp",
+];
+
+const MIN_WORKER_TTL: Duration = Duration::from_secs(30);
+const WORKER_TTL_FACTOR: u32 = 3;
+const INLINE_WORKER_ID: &str = "master";
+
+static WORKERS: Lazy<Mutex<HashMap<String, WorkerEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct WorkerEntry {
+    capabilities: WorkerCapabilities,
+    last_seen: Instant,
+    /// 为空表示常驻（主节点内联消费者）
+    ttl: Option<Duration>,
+}
+
+impl WorkerEntry {
+    fn is_live(&self) -> bool {
+        self.ttl.map_or(true, |ttl| self.last_seen.elapsed() <= ttl)
+    }
+}
+
+/// worker 随心跳上报的能力
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkerCapabilities {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    pub max_file_size_mb: u32,
+    #[serde(default)]
+    pub formats: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub matters: Vec<String>,
+}
+
+impl Default for WorkerCapabilities {
+    fn default() -> Self {
+        Self::from_config(&WorkerCapabilitiesConfig::default())
+    }
+}
+
+impl WorkerCapabilities {
+    pub fn from_config(config: &WorkerCapabilitiesConfig) -> Self {
+        let formats = if config.formats.is_empty() {
+            DEFAULT_FORMATS.iter().map(|f| f.to_string()).collect()
+        } else {
+            config.formats.iter().map(|f| normalize_format(f)).collect()
+        };
+        Self {
+            pool: config
+                .pool
+                .as_deref()
+                .map(str::trim)
+                .filter(|pool| !pool.is_empty())
+                .map(str::to_string),
+            max_file_size_mb: config.max_file_size_mb,
+            formats,
+            tags: config.tags.clone(),
+            matters: config.matters.clone(),
+        }
+    }
+
+    /// 判断能否处理任务，不能时返回原因
+    pub fn accepts(&self, requirements: &TaskRequirements) -> Result<(), String> {
+        if !self.matters.is_empty() && !self.matters.contains(&requirements.matter_id) {
+            return Err(format!("事项 {} 不在允许列表中", requirements.matter_id));
+        }
+        if let Some(format) = requirements
+            .formats
+            .iter()
+            .find(|format| !self.formats.iter().any(|f| normalize_format(f) == **format))
+        {
+            return Err(format!("不支持 {} 格式", format));
+        }
+        let limit = u64::from(self.max_file_size_mb) * 1024 * 1024;
+        if self.max_file_size_mb > 0 && requirements.max_file_bytes > limit {
+            return Err(format!(
+                "附件 {} 字节超过 {} MB 上限",
+                requirements.max_file_bytes, self.max_file_size_mb
+            ));
+        }
+        if let Some(tag) = requirements
+            .tags
+            .iter()
+            .find(|tag| !self.tags.contains(tag))
+        {
+            return Err(format!("缺少标签 {}", tag));
+        }
+        Ok(())
+    }
+}
+
+/// 任务对 worker 的要求
+#[derive(Debug, Clone, Default)]
+pub struct TaskRequirements {
+    pub formats: BTreeSet<String>,
+    pub max_file_bytes: u64,
+    pub matter_id: String,
+    pub tags: Vec<String>,
+}
+
+impl TaskRequirements {
+    /// 大小只能从主节点材料缓存中的附件得到，其余附件按 0 处理
+    pub async fn collect(task: &PreviewTask, config: &TaskRoutingConfig) -> Self {
+        let preview = &task.preview_body.preview;
+        let mut requirements = Self {
+            matter_id: preview.matter_id.clone(),
+            tags: config
+                .matter_tags
+                .get(&preview.matter_id)
+                .cloned()
+                .unwrap_or_default(),
+            ..Self::default()
+        };
+
+        for attachment in preview
+            .material_data
+            .iter()
+            .flat_map(|material| material.attachment_list.iter())
+        {
+            if let Some(format) = attachment_format(&attachment.attach_name)
+                .or_else(|| attachment_format(url_path(&attachment.attach_url)))
+            {
+                requirements.formats.insert(format);
+            }
+            if let Some(token) = attachment.attach_url.strip_prefix(WORKER_CACHE_SCHEME) {
+                if let Some(path) = material_cache::get_material_path(token).await {
+                    if let Ok(meta) = tokio::fs::metadata(&path).await {
+                        requirements.max_file_bytes = requirements.max_file_bytes.max(meta.len());
+                    }
+                }
+            }
+        }
+        requirements
+    }
+}
+
+fn normalize_format(format: &str) -> String {
+    format.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+fn url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+fn attachment_format(name: &str) -> Option<String> {
+    let file_name = name.rsplit(['/', '\\']).next()?;
+    let (_, ext) = file_name.rsplit_once('.')?;
+    let ext = normalize_format(ext);
+    (!ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then_some(ext)
+}
+
+/// 池名会拼进 NATS 主题与队列名，只允许字母、数字、`-` 与 `_`
+pub fn validate_pool_name(pool: &str) -> Result<()> {
+    if pool.is_empty()
+        || !pool
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "任务池名称 [{}] 非法，只允许字母、数字、- 与 _",
+            pool
+        ));
+    }
+    Ok(())
+}
+
+/// 登记心跳上报的能力；存活期为心跳间隔的 3 倍且不少于 30 秒
+pub fn record_worker(worker_id: &str, capabilities: WorkerCapabilities, interval_secs: u64) {
+    let ttl = (Duration::from_secs(interval_secs) * WORKER_TTL_FACTOR).max(MIN_WORKER_TTL);
+    WORKERS.lock().insert(
+        worker_id.to_string(),
+        WorkerEntry {
+            capabilities,
+            last_seen: Instant::now(),
+            ttl: Some(ttl),
+        },
+    );
+}
+
+/// 主节点内联消费者始终消费默认任务池
+pub fn register_inline_worker(capabilities: WorkerCapabilities) {
+    WORKERS.lock().insert(
+        INLINE_WORKER_ID.to_string(),
+        WorkerEntry {
+            capabilities: WorkerCapabilities {
+                pool: None,
+                ..capabilities
+            },
+            last_seen: Instant::now(),
+            ttl: None,
+        },
+    );
+}
+
+/// 当前存活 worker 的能力，用于监控展示
+pub fn live_workers() -> Vec<(String, WorkerCapabilities)> {
+    let mut workers = WORKERS.lock();
+    workers.retain(|_, entry| entry.is_live());
+    let mut live: Vec<_> = workers
+        .iter()
+        .map(|(id, entry)| (id.clone(), entry.capabilities.clone()))
+        .collect();
+    live.sort_by(|a, b| a.0.cmp(&b.0));
+    live
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteDecision {
+    /// 投递到指定任务池，None 为默认池
+    Pool(Option<String>),
+    Hold(String),
+    Reject(String),
+}
+
+/// 优先默认池；否则选匹配 worker 最多的池
+pub fn decide(
+    requirements: &TaskRequirements,
+    workers: &[WorkerCapabilities],
+    unmatched: UnmatchedTaskPolicy,
+) -> RouteDecision {
+    let mut pools: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+    let mut reasons = BTreeSet::new();
+    for worker in workers {
+        match worker.accepts(requirements) {
+            Ok(()) => *pools.entry(worker.pool.as_deref()).or_default() += 1,
+            Err(reason) => {
+                reasons.insert(reason);
+            }
+        }
+    }
+
+    if pools.contains_key(&None) {
+        return RouteDecision::Pool(None);
+    }
+    if let Some((&pool, _)) = pools.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0))) {
+        return RouteDecision::Pool(pool.map(str::to_string));
+    }
+
+    let reason = if reasons.is_empty() {
+        "没有在线的 worker".to_string()
+    } else {
+        format!(
+            "没有能处理该任务的 worker: {}",
+            reasons.into_iter().collect::<Vec<_>>().join("; ")
+        )
+    };
+    match unmatched {
+        UnmatchedTaskPolicy::Hold => RouteDecision::Hold(reason),
+        UnmatchedTaskPolicy::Reject => RouteDecision::Reject(reason),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Routed {
+    Publish,
+    Held,
+}
+
+/// 由 NATS/数据库队列在入队前调用，负责选池与暂缓任务的保管
+pub struct TaskRouter {
+    config: TaskRoutingConfig,
+    database: Arc<dyn Database>,
+    held: Mutex<HashMap<String, PreviewTask>>,
+    /// 曾经投递过的任务池，取消任务时逐个清理
+    pools: Mutex<BTreeSet<String>>,
+}
+
+impl TaskRouter {
+    pub fn new(config: TaskRoutingConfig, database: Arc<dyn Database>) -> Self {
+        Self {
+            config,
+            database,
+            held: Mutex::new(HashMap::new()),
+            pools: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// 设置 `task.route`；返回 Held 时不投递，完整任务由路由器保管，稍后重新入队
+    pub async fn route(&self, task: &mut PreviewTask) -> Result<Routed> {
+        let requirements = TaskRequirements::collect(task, &self.config).await;
+        let workers: Vec<_> = live_workers()
+            .into_iter()
+            .filter(|(id, _)| worker_fleet::accepts_jobs(id))
+            .map(|(_, caps)| caps)
+            .collect();
+
+        let decision = decide(&requirements, &workers, self.config.unmatched);
+        // 分片任务不暂缓也不改动预审状态，没有可用 worker 时由发起分片的节点自行处理
+        if task.shard.is_some() {
+            return Ok(match decision {
+                RouteDecision::Pool(pool) => {
+                    if let Some(pool) = pool.as_ref() {
+                        self.pools.lock().insert(pool.clone());
+                    }
+                    task.route = pool;
+                    Routed::Publish
+                }
+                RouteDecision::Hold(reason) | RouteDecision::Reject(reason) => {
+                    debug!(preview_id = %task.preview_id, reason = %reason, "分片任务暂无可用 worker，不投递");
+                    Routed::Held
+                }
+            });
+        }
+
+        match decision {
+            RouteDecision::Pool(pool) => {
+                if let Some(pool) = pool.as_ref() {
+                    self.pools.lock().insert(pool.clone());
+                }
+                debug!(preview_id = %task.preview_id, pool = ?pool, "预审任务已路由");
+                task.route = pool;
+                if self.held.lock().remove(&task.preview_id).is_some() {
+                    info!(preview_id = %task.preview_id, pool = ?task.route, "暂缓任务已找到可用 worker");
+                    self.mark(&task.preview_id, None).await;
+                }
+                Ok(Routed::Publish)
+            }
+            RouteDecision::Hold(reason) => {
+                let newly_held = self
+                    .held
+                    .lock()
+                    .insert(task.preview_id.clone(), task.clone())
+                    .is_none();
+                if newly_held {
+                    warn!(preview_id = %task.preview_id, reason = %reason, "暂无可用 worker，任务暂缓入队");
+                    self.mark(&task.preview_id, Some(reason)).await;
+                }
+                Ok(Routed::Held)
+            }
+            RouteDecision::Reject(reason) => {
+                self.held.lock().remove(&task.preview_id);
+                self.mark(&task.preview_id, Some(reason.clone())).await;
+                Err(anyhow!(reason))
+            }
+        }
+    }
+
+    /// 移除暂缓中的任务，返回移除数量
+    pub fn cancel(&self, preview_id: &str) -> u64 {
+        u64::from(self.held.lock().remove(preview_id).is_some())
+    }
+
+    pub fn held_count(&self) -> usize {
+        self.held.lock().len()
+    }
+
+    pub fn known_pools(&self) -> Vec<String> {
+        let mut pools = self.pools.lock().clone();
+        pools.extend(live_workers().into_iter().filter_map(|(_, caps)| caps.pool));
+        pools.into_iter().collect()
+    }
+
+    async fn mark(&self, preview_id: &str, reason: Option<String>) {
+        let code = reason.as_ref().map(|_| NO_CAPABLE_WORKER.to_string());
+        let update = PreviewFailureUpdate {
+            preview_id: preview_id.to_string(),
+            failure_reason: Some(reason),
+            last_error_code: Some(code),
+            ..PreviewFailureUpdate::default()
+        };
+        if let Err(err) = self.database.update_preview_failure_context(&update).await {
+            warn!(preview_id = %preview_id, error = %err, "更新任务路由状态失败");
+        }
+    }
+
+    /// 主节点重启后找回仍在暂缓的任务
+    async fn restore_held(&self) -> Result<usize> {
+        let mut filter = PreviewFilter::default();
+        filter.status = Some(PreviewStatus::Queued);
+        filter.limit = Some(500);
+
+        let mut restored = 0;
+        for record in self.database.list_preview_records(&filter).await? {
+            if record.last_error_code.as_deref() != Some(NO_CAPABLE_WORKER)
+                || self.held.lock().contains_key(&record.id)
+            {
+                continue;
+            }
+            let Some(payload) = self.database.load_task_payload(&record.id).await? else {
+                continue;
+            };
+            match serde_json::from_str::<PreviewTask>(&payload) {
+                Ok(task) => {
+                    self.held.lock().insert(record.id.clone(), task);
+                    restored += 1;
+                }
+                Err(err) => warn!(preview_id = %record.id, error = %err, "解析暂缓任务失败"),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// 定期把暂缓任务重新入队，首轮先从数据库找回重启前的暂缓任务
+    pub fn spawn_hold_loop(self: &Arc<Self>, queue: Arc<dyn TaskQueue>) {
+        let router = Arc::clone(self);
+        let period = Duration::from_secs(self.config.hold_check_secs.max(5));
+        tokio::spawn(async move {
+            let mut restored = false;
+            loop {
+                tokio::time::sleep(period).await;
+                if !restored {
+                    restored = true;
+                    match router.restore_held().await {
+                        Ok(0) => {}
+                        Ok(count) => info!(count, "已找回暂缓中的预审任务"),
+                        Err(err) => warn!(error = %err, "找回暂缓任务失败"),
+                    }
+                }
+
+                let held: Vec<PreviewTask> = router.held.lock().values().cloned().collect();
+                for task in held {
+                    let preview_id = task.preview_id.clone();
+                    if let Err(err) = queue.enqueue(task).await {
+                        warn!(preview_id = %preview_id, error = %err, "暂缓任务重新入队失败");
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker(pool: Option<&str>, formats: &[&str], max_mb: u32) -> WorkerCapabilities {
+        WorkerCapabilities::from_config(&WorkerCapabilitiesConfig {
+            pool: pool.map(str::to_string),
+            formats: formats.iter().map(|f| f.to_string()).collect(),
+            max_file_size_mb: max_mb,
+            ..WorkerCapabilitiesConfig::default()
+        })
+    }
+
+    fn requirements(formats: &[&str], bytes: u64) -> TaskRequirements {
+        TaskRequirements {
+            formats: formats.iter().map(|f| f.to_string()).collect(),
+            max_file_bytes: bytes,
+            matter_id: "M1".to_string(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn capabilities_check_format_size_matter_and_tags() {
+        let mut caps = worker(None, &[], 10);
+        assert!(caps.accepts(&requirements(&["pdf", "png"], 1024)).is_ok());
+        assert!(caps.accepts(&requirements(&["docx"], 0)).is_err());
+        assert!(caps
+            .accepts(&requirements(&["pdf"], 11 * 1024 * 1024))
+            .is_err());
+
+        caps.matters = vec!["M2".to_string()];
+        assert!(caps.accepts(&requirements(&["pdf"], 0)).is_err());
+
+        caps.matters.clear();
+        let mut tagged = requirements(&["pdf"], 0);
+        tagged.tags = vec!["large-memory".to_string()];
+        assert!(caps.accepts(&tagged).is_err());
+        caps.tags = vec!["large-memory".to_string()];
+        assert!(caps.accepts(&tagged).is_ok());
+    }
+
+    #[test]
+    fn decide_prefers_default_pool_then_holds_or_rejects() {
+        let workers = vec![
+            worker(None, &[], 10),
+            worker(Some("office"), &[".PDF", "docx"], 10),
+            worker(Some("big"), &[], 500),
+        ];
+        let hold = UnmatchedTaskPolicy::Hold;
+
+        assert_eq!(
+            decide(&requirements(&["pdf"], 0), &workers, hold),
+            RouteDecision::Pool(None)
+        );
+        assert_eq!(
+            decide(&requirements(&["docx"], 0), &workers, hold),
+            RouteDecision::Pool(Some("office".to_string()))
+        );
+        assert_eq!(
+            decide(&requirements(&["pdf"], 100 * 1024 * 1024), &workers, hold),
+            RouteDecision::Pool(Some("big".to_string()))
+        );
+        assert!(matches!(
+            decide(&requirements(&["dwg"], 0), &workers, hold),
+            RouteDecision::Hold(_)
+        ));
+        assert!(matches!(
+            decide(&requirements(&["pdf"], 0), &[], UnmatchedTaskPolicy::Reject),
+            RouteDecision::Reject(_)
+        ));
+    }
+
+    #[test]
+    fn attachment_format_reads_name_or_url() {
+        assert_eq!(attachment_format("营业执照.PDF").as_deref(), Some("pdf"));
+        assert_eq!(
+            attachment_format(url_path("https://x/a/b.docx?sig=1.2")).as_deref(),
+            Some("docx")
+        );
+        assert_eq!(attachment_format("无扩展名"), None);
+        assert!(validate_pool_name("gpu_free-1").is_ok());
+        assert!(validate_pool_name("a.b").is_err());
+    }
+}
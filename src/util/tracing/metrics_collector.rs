@@ -538,6 +538,13 @@ impl MetricsCollector {
         self.record_counter("queue_retry_total", 1, labels);
     }
 
+    pub fn record_queue_dead_letter(&self, queue: &str, source: &str) {
+        let mut labels = HashMap::new();
+        labels.insert("queue".to_string(), queue.to_string());
+        labels.insert("source".to_string(), source.to_string());
+        self.record_counter("queue_dead_letter_total", 1, labels);
+    }
+
     pub fn record_worker_inflight(&self, worker: &str, inflight: u64) {
         if !self.config.enable_detailed_metrics {
             return;
@@ -1,6 +1,7 @@
 pub mod result_processor;
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -8,16 +9,21 @@ use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use once_cell::sync::{Lazy, OnceCell};
 use parking_lot::Mutex;
-use serde::Serialize;
-use tracing::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
 
 use crate::util::logging::standards::events;
 use crate::util::tracing::metrics_collector::METRICS_COLLECTOR;
 
 use crate::model::evaluation::PreviewEvaluationResult;
 use crate::util::material_cache::{self, WORKER_CACHE_SCHEME};
+use crate::util::pdf_shard::{PageShard, ShardJobRequest, ShardJobStatus, ShardReport};
+use crate::util::preview_cancel;
+use crate::util::preview_progress::{self, ProgressEvent};
+use crate::util::task_routing::WorkerCapabilities;
 use crate::util::{system_info, WebResult};
 use ocr_conn::{ocr, pdf_page_count};
+use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "reqwest")]
 use reqwest::{Client, StatusCode};
@@ -29,6 +35,33 @@ struct WorkerContext {
 
 static WORKER_CONTEXT: OnceCell<WorkerContext> = OnceCell::new();
 
+/// 主节点要求停止接单（cordon/drain）时置位，队列消费循环据此暂停拉取
+static INTAKE_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// 主节点以 401/403 拒绝本 worker 的凭证（如已吊销）
+#[derive(Debug, Clone)]
+pub struct WorkerRejected {
+    pub status: u16,
+}
+
+impl std::fmt::Display for WorkerRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "主节点拒绝 worker 凭证: status={}", self.status)
+    }
+}
+
+impl std::error::Error for WorkerRejected {}
+
+/// 凭证被拒后暂停接单，凭证恢复后由心跳应答重新放开
+fn pause_intake_on_rejection(worker_id: &str, status: u16) {
+    if !INTAKE_PAUSED.swap(true, Ordering::Relaxed) {
+        error!(worker_id = %worker_id, status, "主节点拒绝本 worker 凭证，暂停接单");
+    }
+}
+
+/// 排空完成后主节点要求 worker 退出
+static SHUTDOWN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+
 static WORKER_ACTIVITY: Lazy<Mutex<WorkerActivityState>> =
     Lazy::new(|| Mutex::new(WorkerActivityState::default()));
 
@@ -146,6 +179,14 @@ pub fn client() -> Option<Arc<WorkerProxyClient>> {
     WORKER_CONTEXT.get().map(|ctx| Arc::clone(&ctx.client))
 }
 
+pub fn intake_paused() -> bool {
+    INTAKE_PAUSED.load(Ordering::Relaxed)
+}
+
+pub async fn shutdown_requested() {
+    SHUTDOWN.cancelled().await
+}
+
 pub fn spawn_heartbeat_task(interval_seconds: u64) {
     let ctx = match WORKER_CONTEXT.get() {
         Some(ctx) => ctx,
@@ -158,6 +199,13 @@ pub fn spawn_heartbeat_task(interval_seconds: u64) {
     let client = Arc::clone(&ctx.client);
     let worker_id = ctx.worker_id.clone();
     let heartbeat_interval = interval_seconds.max(5);
+    let capabilities = crate::CONFIG
+        .deployment
+        .worker
+        .as_ref()
+        .and_then(|worker| worker.capabilities.as_ref())
+        .map(WorkerCapabilities::from_config)
+        .unwrap_or_default();
 
     tokio::spawn(async move {
         const HEARTBEAT_FAILURE_THRESHOLD: u32 = 5;
@@ -180,14 +228,36 @@ pub fn spawn_heartbeat_task(interval_seconds: u64) {
                 interval_secs: Some(heartbeat_interval as u64),
                 last_job_started_at: activity.last_job_started_at,
                 last_job_finished_at: activity.last_job_finished_at,
+                capabilities: Some(capabilities.clone()),
             };
 
             let send_started = Instant::now();
             match client.send_heartbeat(&payload).await {
-                Ok(_) => {
+                Ok(ack) => {
                     let elapsed = send_started.elapsed();
                     METRICS_COLLECTOR.record_worker_heartbeat_success(&worker_id, elapsed);
 
+                    for preview_id in &ack.cancelled {
+                        if preview_cancel::cancel(preview_id) {
+                            info!(worker_id = %worker_id, preview_id = %preview_id, "收到主节点取消通知");
+                        }
+                    }
+
+                    let was_paused = INTAKE_PAUSED.swap(ack.intake_paused, Ordering::Relaxed);
+                    if was_paused != ack.intake_paused {
+                        info!(
+                            worker_id = %worker_id,
+                            intake_paused = ack.intake_paused,
+                            "主节点调整接单状态"
+                        );
+                    }
+
+                    if ack.shutdown {
+                        info!(worker_id = %worker_id, "排空完成，主节点要求退出");
+                        SHUTDOWN.cancel();
+                        return;
+                    }
+
                     if consecutive_failures > 0 {
                         info!(
                             worker_id = %worker_id,
@@ -202,6 +272,9 @@ pub fn spawn_heartbeat_task(interval_seconds: u64) {
                     failure_alert_emitted = false;
                 }
                 Err(err) => {
+                    if let Some(rejected) = err.downcast_ref::<WorkerRejected>() {
+                        pause_intake_on_rejection(&worker_id, rejected.status);
+                    }
                     let elapsed = send_started.elapsed();
                     consecutive_failures = consecutive_failures.saturating_add(1);
                     let failure_reason = err.to_string();
@@ -242,6 +315,41 @@ pub fn spawn_heartbeat_task(interval_seconds: u64) {
     });
 }
 
+/// 把本进程发布的预审进度上报主节点；同一预审积压的事件只上报最新一条
+pub fn spawn_progress_forwarder() {
+    let Some(ctx) = WORKER_CONTEXT.get() else {
+        warn!("尝试启动进度上报任务，但上下文未初始化");
+        return;
+    };
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    if !preview_progress::set_forwarder(sender) {
+        return;
+    }
+
+    let client = Arc::clone(&ctx.client);
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let mut latest: Vec<ProgressEvent> = vec![event];
+            while let Ok(next) = receiver.try_recv() {
+                match latest
+                    .iter_mut()
+                    .find(|pending| pending.preview_id == next.preview_id)
+                {
+                    Some(pending) => *pending = next,
+                    None => latest.push(next),
+                }
+            }
+
+            for event in latest {
+                if let Err(err) = client.report_progress(&event).await {
+                    debug!(preview_id = %event.preview_id, error = %err, "上报预审进度失败");
+                }
+            }
+        }
+    });
+}
+
 pub async fn fetch_material_path(
     url: &str,
     preview_id: Option<&str>,
@@ -566,7 +674,10 @@ impl WorkerProxyClient {
         }
     }
 
-    pub async fn send_heartbeat(&self, payload: &WorkerHeartbeatPayload) -> Result<()> {
+    pub async fn send_heartbeat(
+        &self,
+        payload: &WorkerHeartbeatPayload,
+    ) -> Result<WorkerHeartbeatAck> {
         #[cfg(not(feature = "reqwest"))]
         {
             let _ = payload;
@@ -583,7 +694,21 @@ impl WorkerProxyClient {
                 .context("发送 worker 心跳失败")?;
 
             match response.status() {
-                StatusCode::OK => Ok(()),
+                StatusCode::OK => {
+                    // 旧版本主节点的应答没有 cancelled 字段
+                    let body: serde_json::Value = response.json().await.unwrap_or_default();
+                    Ok(body
+                        .get("data")
+                        .cloned()
+                        .and_then(|data| serde_json::from_value(data).ok())
+                        .unwrap_or_default())
+                }
+                status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                    Err(WorkerRejected {
+                        status: status.as_u16(),
+                    }
+                    .into())
+                }
                 status => {
                     let body = response
                         .text()
@@ -599,6 +724,33 @@ impl WorkerProxyClient {
         }
     }
 
+    pub async fn report_progress(&self, event: &ProgressEvent) -> Result<()> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = event;
+            return Err(anyhow!("reqwest 功能未启用，无法上报进度"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let response = self
+                .build_request(
+                    reqwest::Method::POST,
+                    &format!("/internal/worker/previews/{}/progress", event.preview_id),
+                )
+                .json(event)
+                .send()
+                .await
+                .context("上报进度请求失败")?;
+
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(anyhow!("上报进度失败: status={}", response.status()))
+            }
+        }
+    }
+
     pub async fn notify_job_started(&self, preview_id: &str, attempt_id: &str) -> Result<()> {
         #[cfg(not(feature = "reqwest"))]
         {
@@ -622,6 +774,18 @@ impl WorkerProxyClient {
 
             if response.status().is_success() {
                 Ok(())
+            } else if response.status() == StatusCode::CONFLICT {
+                Err(preview_cancel::PreviewCancelled {
+                    preview_id: preview_id.to_string(),
+                }
+                .into())
+            } else if matches!(
+                response.status(),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+            ) {
+                let status = response.status().as_u16();
+                pause_intake_on_rejection(&self.worker_id, status);
+                Err(WorkerRejected { status }.into())
             } else {
                 let status = response.status();
                 let body = response
@@ -632,6 +796,126 @@ impl WorkerProxyClient {
             }
         }
     }
+
+    pub async fn open_shard_job(
+        &self,
+        request: &ShardJobRequest,
+    ) -> Result<Option<Vec<PageShard>>> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = request;
+            return Err(anyhow!("reqwest 功能未启用，无法发起 PDF 分片"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let builder = self
+                .build_request(reqwest::Method::POST, "/internal/worker/shards")
+                .json(request);
+            self.shard_call(builder, "发起 PDF 分片").await
+        }
+    }
+
+    pub async fn shard_status(&self, job_id: &str) -> Result<ShardJobStatus> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = job_id;
+            return Err(anyhow!("reqwest 功能未启用，无法查询 PDF 分片"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let builder = self.build_request(
+                reqwest::Method::GET,
+                &format!("/internal/worker/shards/{}", job_id),
+            );
+            self.shard_call(builder, "查询 PDF 分片").await
+        }
+    }
+
+    pub async fn claim_shard(&self, job_id: &str, index: usize) -> Result<bool> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = (job_id, index);
+            return Err(anyhow!("reqwest 功能未启用，无法领取 PDF 分片"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let builder = self.build_request(
+                reqwest::Method::POST,
+                &format!("/internal/worker/shards/{}/{}/claim", job_id, index),
+            );
+            self.shard_call(builder, "领取 PDF 分片").await
+        }
+    }
+
+    pub async fn report_shard(
+        &self,
+        job_id: &str,
+        index: usize,
+        report: &ShardReport,
+    ) -> Result<()> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = (job_id, index, report);
+            return Err(anyhow!("reqwest 功能未启用，无法上报 PDF 分片结果"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let builder = self
+                .build_request(
+                    reqwest::Method::POST,
+                    &format!("/internal/worker/shards/{}/{}/result", job_id, index),
+                )
+                .json(report);
+            self.shard_call(builder, "上报 PDF 分片结果").await
+        }
+    }
+
+    pub async fn close_shard_job(&self, job_id: &str) -> Result<()> {
+        #[cfg(not(feature = "reqwest"))]
+        {
+            let _ = job_id;
+            return Err(anyhow!("reqwest 功能未启用，无法关闭 PDF 分片"));
+        }
+
+        #[cfg(feature = "reqwest")]
+        {
+            let builder = self.build_request(
+                reqwest::Method::DELETE,
+                &format!("/internal/worker/shards/{}", job_id),
+            );
+            self.shard_call(builder, "关闭 PDF 分片").await
+        }
+    }
+
+    /// 分片接口统一返回 WebResult，结果在 data 中
+    #[cfg(feature = "reqwest")]
+    async fn shard_call<T: serde::de::DeserializeOwned>(
+        &self,
+        builder: reqwest::RequestBuilder,
+        action: &str,
+    ) -> Result<T> {
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("{}请求失败", action))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no-body>".to_string());
+            return Err(anyhow!("{}失败: status={} body={}", action, status, body));
+        }
+        let body: WebResult = response
+            .json()
+            .await
+            .with_context(|| format!("解析{}应答失败", action))?;
+        serde_json::from_value(body.data).with_context(|| format!("解析{}应答失败", action))
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -679,6 +963,17 @@ pub struct WorkerResultMetricsPayload {
     pub pages: Option<u32>,
 }
 
+/// 心跳应答，携带主节点上已取消的执行中任务
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkerHeartbeatAck {
+    #[serde(default)]
+    pub cancelled: Vec<String>,
+    #[serde(default)]
+    pub intake_paused: bool,
+    #[serde(default)]
+    pub shutdown: bool,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct WorkerHeartbeatPayload {
     pub worker_id: String,
@@ -695,6 +990,8 @@ pub struct WorkerHeartbeatPayload {
     pub last_job_started_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_job_finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<WorkerCapabilities>,
 }
 
 #[derive(Debug, Serialize, Clone, Default)]
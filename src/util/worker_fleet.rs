@@ -0,0 +1,220 @@
+//! worker 运维状态：停止接单（cordon）、排空（drain）与吊销凭证
+//!
+//! 状态写入数据库，主节点启动时载入内存；鉴权与心跳应答只读内存副本。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tracing::info;
+
+use crate::db::traits::{WorkerControlRecord, WorkerControlState};
+use crate::db::Database;
+
+static CONTROLS: Lazy<RwLock<HashMap<String, Control>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+struct Control {
+    record: WorkerControlRecord,
+    /// 已在心跳应答中通知 worker 停止接单；排空须在此之后才算完成
+    pause_delivered: bool,
+}
+
+/// 心跳应答中下发给 worker 的指令
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeartbeatDirective {
+    pub intake_paused: bool,
+    pub shutdown: bool,
+}
+
+pub async fn load(database: &Arc<dyn Database>) -> Result<usize> {
+    let records = database.list_worker_controls().await?;
+    let mut controls = CONTROLS.write();
+    controls.clear();
+    for record in records {
+        if record.state == WorkerControlState::Active {
+            continue;
+        }
+        controls.insert(
+            record.worker_id.clone(),
+            Control {
+                record,
+                pause_delivered: false,
+            },
+        );
+    }
+    Ok(controls.len())
+}
+
+pub fn state(worker_id: &str) -> WorkerControlState {
+    CONTROLS
+        .read()
+        .get(worker_id)
+        .map(|control| control.record.state)
+        .unwrap_or_default()
+}
+
+pub fn record(worker_id: &str) -> Option<WorkerControlRecord> {
+    CONTROLS
+        .read()
+        .get(worker_id)
+        .map(|control| control.record.clone())
+}
+
+pub fn is_revoked(worker_id: &str) -> bool {
+    state(worker_id) == WorkerControlState::Revoked
+}
+
+pub fn accepts_jobs(worker_id: &str) -> bool {
+    state(worker_id).accepts_jobs()
+}
+
+/// 管理员变更状态；吊销只能由 `allow_unrevoke` 的调用方解除
+pub async fn set_state(
+    database: &Arc<dyn Database>,
+    worker_id: &str,
+    target: WorkerControlState,
+    reason: Option<String>,
+    operator: &str,
+    allow_unrevoke: bool,
+) -> Result<WorkerControlRecord> {
+    let current = state(worker_id);
+    if current == WorkerControlState::Revoked
+        && target != WorkerControlState::Revoked
+        && !allow_unrevoke
+    {
+        return Err(anyhow!("worker {} 凭证已吊销，需超级管理员恢复", worker_id));
+    }
+    if target == WorkerControlState::Draining && current == WorkerControlState::Drained {
+        return Err(anyhow!("worker {} 已排空", worker_id));
+    }
+
+    let record = WorkerControlRecord {
+        worker_id: worker_id.to_string(),
+        state: target,
+        reason,
+        updated_by: operator.to_string(),
+        updated_at: Utc::now(),
+    };
+    database.save_worker_control(&record).await?;
+    apply(record.clone());
+    info!(
+        worker_id = %worker_id,
+        from = current.as_str(),
+        to = target.as_str(),
+        operator = %operator,
+        "worker 运维状态变更"
+    );
+    Ok(record)
+}
+
+fn apply(record: WorkerControlRecord) {
+    let mut controls = CONTROLS.write();
+    if record.state == WorkerControlState::Active {
+        controls.remove(&record.worker_id);
+        return;
+    }
+    let pause_delivered = controls.get(&record.worker_id).map_or(false, |control| {
+        control.pause_delivered && !control.record.state.accepts_jobs()
+    });
+    controls.insert(
+        record.worker_id.clone(),
+        Control {
+            record,
+            pause_delivered,
+        },
+    );
+}
+
+/// 处理心跳时调用：排空中的 worker 已收到停止接单且没有执行中的任务时，
+/// 转为 drained 并要求其退出
+pub async fn heartbeat_directive(
+    database: &Arc<dyn Database>,
+    worker_id: &str,
+    running_tasks: usize,
+) -> HeartbeatDirective {
+    let (directive, drained) = {
+        let mut controls = CONTROLS.write();
+        let Some(control) = controls.get_mut(worker_id) else {
+            return HeartbeatDirective::default();
+        };
+        let state = control.record.state;
+        let drained =
+            state == WorkerControlState::Draining && control.pause_delivered && running_tasks == 0;
+        control.pause_delivered = true;
+        (
+            HeartbeatDirective {
+                intake_paused: !state.accepts_jobs(),
+                shutdown: drained,
+            },
+            drained,
+        )
+    };
+
+    if drained {
+        let record = WorkerControlRecord {
+            worker_id: worker_id.to_string(),
+            state: WorkerControlState::Drained,
+            reason: record(worker_id).and_then(|record| record.reason),
+            updated_by: "system".to_string(),
+            updated_at: Utc::now(),
+        };
+        if let Err(err) = database.save_worker_control(&record).await {
+            tracing::warn!(worker_id = %worker_id, error = %err, "保存 worker 排空状态失败");
+        }
+        apply(record);
+        info!(worker_id = %worker_id, "worker 已排空，通知其退出");
+    }
+    directive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(worker_id: &str, state: WorkerControlState) {
+        apply(WorkerControlRecord {
+            worker_id: worker_id.to_string(),
+            state,
+            reason: None,
+            updated_by: "admin".to_string(),
+            updated_at: Utc::now(),
+        });
+    }
+
+    #[test]
+    fn cordon_pauses_intake_and_active_clears_entry() {
+        seed("fleet-w1", WorkerControlState::Cordoned);
+        assert!(!accepts_jobs("fleet-w1"));
+        assert!(!is_revoked("fleet-w1"));
+
+        seed("fleet-w1", WorkerControlState::Revoked);
+        assert!(is_revoked("fleet-w1"));
+
+        seed("fleet-w1", WorkerControlState::Active);
+        assert!(accepts_jobs("fleet-w1"));
+        assert!(record("fleet-w1").is_none());
+    }
+
+    #[test]
+    fn drain_waits_for_pause_to_be_delivered() {
+        seed("fleet-w2", WorkerControlState::Draining);
+        {
+            let mut controls = CONTROLS.write();
+            let control = controls.get_mut("fleet-w2").unwrap();
+            assert!(!control.pause_delivered);
+            control.pause_delivered = true;
+        }
+        // 状态在停止接单的状态之间切换时保留已下发标记
+        seed("fleet-w2", WorkerControlState::Cordoned);
+        seed("fleet-w2", WorkerControlState::Draining);
+        assert!(CONTROLS.read()["fleet-w2"].pause_delivered);
+
+        seed("fleet-w2", WorkerControlState::Active);
+        seed("fleet-w2", WorkerControlState::Draining);
+        assert!(!CONTROLS.read()["fleet-w2"].pause_delivered);
+    }
+}
@@ -3,9 +3,16 @@
 use crate::db::traits::{Database as DbTrait, MaterialFileFilter, MaterialFileRecord};
 use crate::model::evaluation::ProcessingStatus;
 use crate::model::preview::{Attachment, MaterialValue, Preview, UserInfo};
+use crate::storage::signing::proxy_url;
 use crate::storage::Storage;
+use crate::util::blob_store;
 use crate::util::logging::runtime::ATTACHMENT_LOGGING_RUNTIME;
 use crate::util::logging::standards::events;
+use crate::util::ocr_cache;
+use crate::util::ocr_export;
+use crate::util::pdf_shard::{self, ShardJobRequest};
+use crate::util::preview_cancel;
+use crate::util::preview_progress;
 use crate::util::processing::multi_stage_controller::MULTI_STAGE_CONTROLLER;
 use crate::util::processing::TaskResourcePredictor;
 use crate::util::system_info::get_memory_usage;
@@ -14,19 +21,19 @@ use crate::util::tracing::request_tracker::{LogLevel, RequestTracker, TraceStatu
 use crate::util::worker;
 use crate::CONFIG;
 use anyhow::{anyhow, Result};
-use ocr_conn::ocr::{OcrEngineOptions, GLOBAL_POOL};
+use ocr_conn::ocr::{ContentData, OcrEngineOptions, GLOBAL_POOL};
+use ocr_conn::table::{self, TableGrid};
 use ocr_conn::{pdf_page_count, pdf_render_jpg_range};
 use serde_json::{to_value, Value};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, error, info, warn};
-use urlencoding::encode;
 
 use crate::util::extract::{self, ExtractedData};
 use crate::util::processing::optimized_pipeline::OPTIMIZED_PIPELINE;
 use crate::util::rules::{
-    compute_definition_fingerprint, MaterialRule, MaterialScope, MaterialValidity,
+    compute_definition_fingerprint, MaterialRepeat, MaterialRule, MaterialScope, MaterialValidity,
     MatterRuleConfig, MatterRuleDefinition, RuleMode, RuleRepository,
 };
 use ocr_conn::CURRENT_DIR;
@@ -43,6 +50,8 @@ pub struct PreviewEvaluator {
     rule_fingerprint: Option<String>,
     material_rule_index: HashMap<String, MaterialRule>,
     extracted_map: HashMap<String, ExtractedData>,
+    table_map: HashMap<String, Vec<TableGrid>>,
+    force_reocr: bool,
 }
 
 struct AttachmentDownload {
@@ -87,14 +96,35 @@ impl PreviewEvaluator {
         let ocr_start = Instant::now();
         let ocr_text = if download.is_pdf && download.local_path.is_some() {
             let path = download.local_path.as_ref().unwrap();
-            let results = OPTIMIZED_PIPELINE
-                .process_pdf_optimized(
-                    path.clone(),
-                    self.preview.request_id.clone(),
-                    material.code.clone(),
-                    self.storage.clone(),
-                )
-                .await?;
+            let sharded = match download.page_count {
+                Some(page_count) => {
+                    let request = ShardJobRequest {
+                        preview_id: self.preview.request_id.clone(),
+                        matter_id: self.preview.matter_id.clone(),
+                        material_code: material.code.clone(),
+                        attach_name: attachment.attach_name.clone(),
+                        attach_url: attachment.attach_url.clone(),
+                        page_count,
+                        force_reocr: self.force_reocr,
+                    };
+                    pdf_shard::process_sharded(request, path, self.storage.clone()).await?
+                }
+                None => None,
+            };
+            let results = match sharded {
+                Some(results) => results,
+                None => {
+                    OPTIMIZED_PIPELINE
+                        .process_pdf_optimized(
+                            path.clone(),
+                            self.preview.request_id.clone(),
+                            material.code.clone(),
+                            self.storage.clone(),
+                            self.force_reocr,
+                        )
+                        .await?
+                }
+            };
 
             results.join("\n\n")
         } else {
@@ -325,6 +355,7 @@ impl PreviewEvaluator {
 
         let mut record_id = None;
         let mut checksum = None;
+        let digest = hex::encode(Sha256::digest(&file_content));
 
         let mut stored_original_key = None;
         if let Some(storage) = &self.storage {
@@ -342,24 +373,33 @@ impl PreviewEvaluator {
                 preview_id, material_code, filename
             );
 
-            if let Err(e) = storage.put(&key, &file_content).await {
-                warn!("保存原始附件失败: {}", e);
-                METRICS_COLLECTOR.record_preview_persistence_failure("storage_put_original");
-            } else {
-                debug!(
-                    target: "attachment.pipeline",
-                    event = events::ATTACHMENT_COMPLETE,
-                    action = "stored_original",
-                    storage_key = %key
-                );
-                stored_original_key = Some(key);
+            match blob_store::store_original(
+                self.database.as_deref(),
+                storage.as_ref(),
+                &key,
+                &digest,
+                ext,
+                &file_content,
+            )
+            .await
+            {
+                Ok(stored_key) => {
+                    debug!(
+                        target: "attachment.pipeline",
+                        event = events::ATTACHMENT_COMPLETE,
+                        action = "stored_original",
+                        storage_key = %stored_key
+                    );
+                    stored_original_key = Some(stored_key);
+                }
+                Err(e) => {
+                    warn!("保存原始附件失败: {}", e);
+                    METRICS_COLLECTOR.record_preview_persistence_failure("storage_put_original");
+                }
             }
         }
 
         if let Some(db) = &self.database {
-            let mut hasher = Sha256::new();
-            hasher.update(&file_content);
-            let digest = hex::encode(hasher.finalize());
             checksum = Some(digest.clone());
 
             let now = chrono::Utc::now();
@@ -544,6 +584,9 @@ impl PreviewEvaluator {
         attachment_index: usize,
         download: &AttachmentDownload,
     ) -> Result<Option<String>> {
+        if self.force_reocr {
+            return Ok(None);
+        }
         let (db, storage) = match (&self.database, &self.storage) {
             (Some(db), Some(storage)) => (db, storage),
             _ => return Ok(None),
@@ -622,6 +665,8 @@ impl PreviewEvaluator {
             rule_fingerprint: None,
             material_rule_index: HashMap::new(),
             extracted_map: HashMap::new(),
+            table_map: HashMap::new(),
+            force_reocr: false,
         }
     }
 
@@ -636,6 +681,8 @@ impl PreviewEvaluator {
             rule_fingerprint: None,
             material_rule_index: HashMap::new(),
             extracted_map: HashMap::new(),
+            table_map: HashMap::new(),
+            force_reocr: false,
         }
     }
 
@@ -655,6 +702,8 @@ impl PreviewEvaluator {
             rule_fingerprint: None,
             material_rule_index: HashMap::new(),
             extracted_map: HashMap::new(),
+            table_map: HashMap::new(),
+            force_reocr: false,
         }
     }
 
@@ -673,9 +722,16 @@ impl PreviewEvaluator {
             rule_fingerprint: None,
             material_rule_index: HashMap::new(),
             extracted_map: HashMap::new(),
+            table_map: HashMap::new(),
+            force_reocr: false,
         }
     }
 
+    /// 重新执行时跳过 OCR 缓存与已识别附件的复用
+    pub fn set_force_reocr(&mut self, force: bool) {
+        self.force_reocr = force;
+    }
+
     pub fn set_embedded_rule_definition(&mut self, definition: Option<Arc<MatterRuleDefinition>>) {
         self.embedded_rule_definition = definition;
 
@@ -1042,13 +1098,7 @@ impl PreviewEvaluator {
                 record.material_code.clone()
             };
 
-            let base = CONFIG.base_url();
-            let proxy_url = format!(
-                "{}/api/storage/files/{}",
-                base.trim_end_matches('/'),
-                encode(record.stored_original_key.trim_start_matches('/'))
-            );
-            url_map.insert(lookup_key, proxy_url);
+            url_map.insert(lookup_key, proxy_url(&record.stored_original_key));
         }
 
         debug!(
@@ -1186,6 +1236,7 @@ impl PreviewEvaluator {
             processing_status,
             display_summary: Some(friendly_summary),
             display_detail: Some(friendly_detail),
+            tables: local_result.tables,
         }
     }
 
@@ -1400,6 +1451,8 @@ impl PreviewEvaluator {
         let material_data = self.preview.material_data.clone();
 
         for (index, material) in material_data.iter().enumerate() {
+            preview_cancel::check(&preview_id)?;
+            preview_progress::material(&preview_id, index, material_total, &material.code);
             debug!(
                 target: "attachment.pipeline",
                 event = events::MATERIAL_START,
@@ -1432,6 +1485,8 @@ impl PreviewEvaluator {
                 }
             }
         }
+        // 最后一份材料处理中被取消时，单份材料的错误已被吞掉，这里再确认一次
+        preview_cancel::check(&preview_id)?;
 
         if self.should_check_missing_materials() {
             for (code, rule) in &self.material_rule_index {
@@ -1492,6 +1547,7 @@ impl PreviewEvaluator {
 
         let mut attachment_texts = Vec::new();
         for (idx, attachment) in material.attachment_list.iter().enumerate() {
+            preview_cancel::check(&preview_id)?;
             let attachment_start = Instant::now();
             let sample_logging = attachment_settings.should_sample(idx);
 
@@ -1545,6 +1601,7 @@ impl PreviewEvaluator {
         }
 
         let combined_text = attachment_texts.join("\n\n");
+        preview_progress::evaluating(&preview_id);
 
         let result = self
             .process_evaluation_result(combined_text, material, material_start)
@@ -1671,6 +1728,7 @@ impl PreviewEvaluator {
                 batch_size = window
             );
             while start <= allowed_pages {
+                preview_cancel::check(&self.preview.request_id)?;
                 let end = (start + window - 1).min(allowed_pages);
                 let batch_start = Instant::now();
                 debug!(
@@ -1736,6 +1794,7 @@ impl PreviewEvaluator {
 
                 let mut converted_keys: Vec<String> = Vec::new();
                 for (offset, image) in image_paths.iter().enumerate() {
+                    preview_cancel::check(&self.preview.request_id)?;
                     if crate::CONFIG.ocr_tuning.logging_detail {
                         match image::image_dimensions(image) {
                             Ok((w, h)) => {
@@ -1758,22 +1817,43 @@ impl PreviewEvaluator {
                             }
                         }
                     }
-                    let mut engine = GLOBAL_POOL
-                        .acquire()
-                        .await
-                        .map_err(|e| anyhow::anyhow!("获取OCR引擎失败: {}", e))?;
-                    let ocr_started = Instant::now();
-                    let ocr_result = engine.ocr_and_parse(std::path::PathBuf::from(&image).into());
-                    let duration = ocr_started.elapsed();
-                    METRICS_COLLECTOR.record_ocr_invocation(ocr_result.is_ok(), duration);
+                    // 引擎预处理会原地改写图片，需在识别前读取原始渲染结果作为缓存键
+                    let page_bytes = fs::read(&image).await.ok();
+                    let cached = match &page_bytes {
+                        Some(bytes) => self.lookup_ocr_cache(bytes, Some(lim.pdf_render_dpi)).await,
+                        None => None,
+                    };
+                    let from_cache = cached.is_some();
+                    let (ocr_result, duration) = match cached {
+                        Some(contents) => (Ok(contents), Duration::ZERO),
+                        None => {
+                            let mut engine = GLOBAL_POOL
+                                .acquire()
+                                .await
+                                .map_err(|e| anyhow::anyhow!("获取OCR引擎失败: {}", e))?;
+                            let ocr_started = Instant::now();
+                            let ocr_result =
+                                engine.ocr_and_parse(std::path::PathBuf::from(&image).into());
+                            let duration = ocr_started.elapsed();
+                            METRICS_COLLECTOR.record_ocr_invocation(ocr_result.is_ok(), duration);
+                            (ocr_result, duration)
+                        }
+                    };
 
                     let abs_page = start as usize + offset;
                     let mut stage_labels = HashMap::new();
                     stage_labels.insert("material".to_string(), material_label.clone());
                     stage_labels.insert("page".to_string(), abs_page.to_string());
+                    if from_cache {
+                        stage_labels.insert("cache".to_string(), "hit".to_string());
+                    }
 
                     match ocr_result {
                         Ok(contents) => {
+                            if let (false, Some(bytes)) = (from_cache, page_bytes.as_deref()) {
+                                self.store_ocr_cache(bytes, Some(lim.pdf_render_dpi), &contents)
+                                    .await;
+                            }
                             METRICS_COLLECTOR.record_pipeline_stage(
                                 "ocr",
                                 true,
@@ -1819,21 +1899,48 @@ impl PreviewEvaluator {
                                 }
                             }
 
+                            // 文本框坐标对应送入引擎的图片：识别时页面已被原地预处理，
+                            // 命中缓存时未经引擎，需自行预处理
+                            let engine_page = if from_cache {
+                                match page_bytes {
+                                    Some(raw) => self.engine_page(&contents, raw).await,
+                                    None => None,
+                                }
+                            } else if self.storage.is_some() || table::looks_tabular(&contents) {
+                                fs::read(&image).await.ok()
+                            } else {
+                                None
+                            };
+                            self.collect_tables(material_code, &contents, engine_page.as_deref())
+                                .await;
                             let page_text = contents
-                                .into_iter()
-                                .map(|c| c.text)
+                                .iter()
+                                .map(|c| c.text.as_str())
                                 .collect::<Vec<_>>()
                                 .join("\n");
                             all_text.push(page_text);
                             if let Some(storage) = &self.storage {
-                                if let Ok(bytes) = std::fs::read(&image) {
+                                if let Some(bytes) = engine_page {
                                     let key = format!(
-                                        "uploads/{}/{}/converted/page-{}.jpg",
-                                        self.preview.request_id, material_code, abs_page
+                                        "{}page-{}.{}",
+                                        ocr_export::converted_prefix(
+                                            &self.preview.request_id,
+                                            material_code
+                                        ),
+                                        abs_page,
+                                        image_extension(&bytes)
                                     );
                                     let upload_started = Instant::now();
                                     match storage.put(&key, &bytes).await {
                                         Ok(_) => {
+                                            ocr_export::store_page_boxes(
+                                                storage,
+                                                &key,
+                                                &bytes,
+                                                Some(lim.pdf_render_dpi),
+                                                &contents,
+                                            )
+                                            .await;
                                             let mut upload_labels = HashMap::new();
                                             upload_labels.insert(
                                                 "material".to_string(),
@@ -1893,6 +2000,11 @@ impl PreviewEvaluator {
                             tracing::warn!("OCR失败 页{}: {}", start as usize + offset, err_msg);
                         }
                     }
+                    preview_progress::ocr_page(
+                        &self.preview.request_id,
+                        abs_page as u32,
+                        allowed_pages,
+                    );
                 }
 
                 if let (Some(db), Some(id)) = (&self.database, record_id) {
@@ -1941,6 +2053,14 @@ impl PreviewEvaluator {
                 text_chars = all_text.iter().map(|s| s.len()).sum::<usize>()
             );
             all_text.join("\n\n")
+        } else if let Some(contents) = self.lookup_ocr_cache(file_content, None).await {
+            self.finish_image_page(material_code, file_content, &contents)
+                .await;
+            contents
+                .into_iter()
+                .map(|content| content.text)
+                .collect::<Vec<_>>()
+                .join("\n")
         } else {
             let mut engine = GLOBAL_POOL
                 .acquire()
@@ -1951,11 +2071,16 @@ impl PreviewEvaluator {
             let duration = ocr_started.elapsed();
             METRICS_COLLECTOR.record_ocr_invocation(ocr_result.is_ok(), duration);
             match ocr_result {
-                Ok(contents) => contents
-                    .into_iter()
-                    .map(|content| content.text)
-                    .collect::<Vec<_>>()
-                    .join("\n"),
+                Ok(contents) => {
+                    self.store_ocr_cache(file_content, None, &contents).await;
+                    self.finish_image_page(material_code, file_content, &contents)
+                        .await;
+                    contents
+                        .into_iter()
+                        .map(|content| content.text)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
                 Err(e) => {
                     let err_msg = e.to_string();
                     if err_msg.contains("超时")
@@ -1987,6 +2112,9 @@ impl PreviewEvaluator {
                     match ocr_result {
                         Ok(contents) => {
                             let _ = std::fs::remove_file(&tmp_path);
+                            self.store_ocr_cache(file_content, None, &contents).await;
+                            self.finish_image_page(material_code, file_content, &contents)
+                                .await;
                             contents
                                 .into_iter()
                                 .map(|content| content.text)
@@ -2011,6 +2139,9 @@ impl PreviewEvaluator {
                 }
             }
         };
+        if !is_pdf {
+            preview_progress::ocr_page(&self.preview.request_id, 1, 1);
+        }
 
         let ocr_duration = ocr_start.elapsed();
         debug!(
@@ -2059,6 +2190,119 @@ impl PreviewEvaluator {
         Ok(text_content)
     }
 
+    async fn lookup_ocr_cache(
+        &self,
+        image_bytes: &[u8],
+        render_dpi: Option<u32>,
+    ) -> Option<Vec<ContentData>> {
+        if self.force_reocr {
+            return None;
+        }
+        let storage = self.storage.as_ref()?;
+        ocr_cache::lookup(storage, image_bytes, render_dpi).await
+    }
+
+    async fn store_ocr_cache(
+        &self,
+        image_bytes: &[u8],
+        render_dpi: Option<u32>,
+        contents: &[ContentData],
+    ) {
+        if let Some(storage) = &self.storage {
+            ocr_cache::store(storage, image_bytes, render_dpi, contents).await;
+        }
+    }
+
+    /// 按当前配置预处理得到送入引擎的同一张图（未启用或失败时为原图），
+    /// 供表格线检测与识别图片上传共用；两者都用不到时不做预处理。
+    /// 解码、纠偏与编码都较重，放到阻塞线程池
+    async fn engine_page(&self, contents: &[ContentData], raw: Vec<u8>) -> Option<Vec<u8>> {
+        if self.storage.is_none() && !table::looks_tabular(contents) {
+            return None;
+        }
+        tokio::task::spawn_blocking(move || {
+            ocr_conn::preprocess::preprocess_bytes(&raw).unwrap_or(raw)
+        })
+        .await
+        .ok()
+    }
+
+    /// 单图附件识别完成后：预处理一次，还原表格并保存识别图片
+    async fn finish_image_page(
+        &mut self,
+        material_code: &str,
+        file_content: &[u8],
+        contents: &[ContentData],
+    ) {
+        let Some(page) = self.engine_page(contents, file_content.to_vec()).await else {
+            return;
+        };
+        self.collect_tables(material_code, contents, Some(&page))
+            .await;
+        self.persist_image_page(material_code, &page, contents)
+            .await;
+    }
+
+    /// 单图附件没有渲染页，这里保存送入引擎的图片（预处理后）及文本框，供 OCR 导出使用
+    async fn persist_image_page(&self, material_code: &str, page: &[u8], contents: &[ContentData]) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        let key = format!(
+            "{}image-{}.{}",
+            ocr_export::converted_prefix(&self.preview.request_id, material_code),
+            uuid::Uuid::new_v4().simple(),
+            image_extension(page)
+        );
+        if let Err(err) = storage.put(&key, page).await {
+            warn!("保存识别图片失败: {}", err);
+            METRICS_COLLECTOR.record_preview_persistence_failure("storage_put_converted_image");
+            return;
+        }
+        ocr_export::store_page_boxes(storage, &key, page, None, contents).await;
+    }
+
+    /// 从单页/单图的识别结果中还原表格，按材料累积；`page` 为送入引擎的图片，
+    /// 表格线需与文本框在同一坐标系
+    async fn collect_tables(
+        &mut self,
+        material_code: &str,
+        contents: &[ContentData],
+        page: Option<&[u8]>,
+    ) {
+        if !table::looks_tabular(contents) {
+            return;
+        }
+        let rulings = match page {
+            Some(page) => {
+                let page = page.to_vec();
+                let analysis_max = ocr_conn::preprocess::current_options().analysis_max_dimension;
+                tokio::task::spawn_blocking(move || {
+                    ::image::load_from_memory(&page)
+                        .ok()
+                        .map(|image| table::detect_rulings(&image, analysis_max))
+                })
+                .await
+                .ok()
+                .flatten()
+            }
+            None => None,
+        };
+        if let Some(grid) = table::reconstruct_table(contents, rulings.as_ref()) {
+            debug!(
+                material_code = %material_code,
+                rows = grid.rows,
+                cols = grid.cols,
+                ruled = grid.ruled,
+                "识别到表格结构"
+            );
+            self.table_map
+                .entry(material_code.to_string())
+                .or_default()
+                .push(grid);
+        }
+    }
+
     async fn process_evaluation_result(
         &mut self,
         ocr_text: String,
@@ -2076,6 +2320,9 @@ impl PreviewEvaluator {
         let mut extracted_info = self.extract_key_information(&ocr_text);
         extracted_info.extend(self.describe_extracted_fields(&extracted_struct));
         material_result.set_ocr_content(ocr_text.clone());
+        if let Some(tables) = self.table_map.get(&material.code) {
+            material_result.tables = tables.clone();
+        }
 
         let rule_start = Instant::now();
         let evaluation = self.evaluate_material_with_rules(&ocr_text, material).await;
@@ -2386,6 +2633,125 @@ impl PreviewEvaluator {
         (notes, tags, severe)
     }
 
+    /// 用材料表格中的关键列核对案例列表：缺失、多出、重复的条目均提示人工确认。
+    /// 表单中找不到案例列表或材料中没有识别出对应列时，退回人工确认。
+    fn check_case_list_repeat(
+        &self,
+        material_code: &str,
+        repeat: &MaterialRepeat,
+        warnings: &mut Vec<String>,
+    ) {
+        let (Some(expected), Some(found)) = (
+            self.case_list_keys(repeat),
+            self.table_column_keys(material_code, repeat),
+        ) else {
+            warnings.push(format!(
+                "材料与案例列表 {} 存在重复校验要求，需人工确认 (caseKeyField={}, ocrKeyField={})",
+                repeat.case_list, repeat.case_key_field, repeat.ocr_key_field
+            ));
+            return;
+        };
+
+        let normalize = |value: &str| -> String {
+            value
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .flat_map(|c| c.to_uppercase())
+                .collect()
+        };
+        let mut found_counts: HashMap<String, usize> = HashMap::new();
+        for key in &found {
+            *found_counts.entry(normalize(key)).or_default() += 1;
+        }
+        let expected_set: HashSet<String> = expected.iter().map(|k| normalize(k)).collect();
+
+        for key in &expected {
+            if !found_counts.contains_key(&normalize(key)) {
+                warnings.push(format!(
+                    "案例列表 {} 中的 {} 未在材料表格中找到",
+                    repeat.case_list, key
+                ));
+            }
+        }
+        let mut reported = HashSet::new();
+        for key in &found {
+            let normalized = normalize(key);
+            if !reported.insert(normalized.clone()) {
+                continue;
+            }
+            if !expected_set.contains(&normalized) {
+                warnings.push(format!(
+                    "材料表格中的 {} 不在案例列表 {} 中",
+                    key, repeat.case_list
+                ));
+            }
+            if found_counts.get(&normalized).copied().unwrap_or(0) > 1 {
+                warnings.push(format!("材料表格中 {} 重复出现", key));
+            }
+        }
+    }
+
+    /// 从表单数据中取出案例列表各项的关键字段值
+    fn case_list_keys(&self, repeat: &MaterialRepeat) -> Option<Vec<String>> {
+        let list = self.preview.form_data.iter().find_map(|entry| {
+            let obj = entry.as_object()?;
+            if let Some(value) = obj.get(&repeat.case_list) {
+                return Some(value.clone());
+            }
+            let matches = ["code", "key", "fieldCode", "name"]
+                .iter()
+                .any(|k| obj.get(*k).and_then(|v| v.as_str()) == Some(repeat.case_list.as_str()));
+            if !matches {
+                return None;
+            }
+            ["value", "list", "data"]
+                .iter()
+                .find_map(|k| obj.get(*k).cloned())
+        })?;
+
+        // 表单值可能是 JSON 字符串形式的数组
+        let list = match list {
+            Value::String(raw) => serde_json::from_str::<Value>(&raw).ok()?,
+            other => other,
+        };
+        let keys: Vec<String> = list
+            .as_array()?
+            .iter()
+            .filter_map(|item| match item.get(&repeat.case_key_field)? {
+                Value::String(s) => Some(s.trim().to_string()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .filter(|key| !key.is_empty())
+            .collect();
+        Some(keys)
+    }
+
+    /// 在材料的表格中按表头候选查找关键列，跨页表格的取值依次拼接
+    fn table_column_keys(
+        &self,
+        material_code: &str,
+        repeat: &MaterialRepeat,
+    ) -> Option<Vec<String>> {
+        let tables = self.table_map.get(material_code)?;
+        let candidates: Vec<&str> = repeat
+            .table_headers
+            .iter()
+            .map(|h| h.as_str())
+            .chain(std::iter::once(repeat.ocr_key_field.as_str()))
+            .collect();
+
+        let mut matched = false;
+        let mut keys = Vec::new();
+        for grid in tables {
+            if let Some(col) = candidates.iter().find_map(|name| grid.column_index(name)) {
+                matched = true;
+                keys.extend(grid.column_values(col));
+            }
+        }
+        matched.then_some(keys)
+    }
+
     async fn evaluate_material_with_rules(
         &self,
         _ocr_text: &str,
@@ -2488,10 +2854,7 @@ impl PreviewEvaluator {
             }
 
             if let Some(repeat) = &rule.repeat {
-                warnings.push(format!(
-                    "材料与案例列表 {} 存在重复校验要求，需人工确认 (caseKeyField={}, ocrKeyField={})",
-                    repeat.case_list, repeat.case_key_field, repeat.ocr_key_field
-                ));
+                self.check_case_list_repeat(&material.code, repeat, &mut warnings);
             }
 
             if let Some(notes) = &rule.notes {
@@ -2578,6 +2941,14 @@ fn attachment_log_settings() -> AttachmentLogSettings {
         slow_threshold_ms: snapshot.slow_threshold_ms,
     }
 }
+/// 按内容判断图片扩展名；预处理后的页面是 PNG，未预处理的渲染页是 JPEG
+fn image_extension(bytes: &[u8]) -> &'static str {
+    ::image::guess_format(bytes)
+        .ok()
+        .and_then(|format| format.extensions_str().first().copied())
+        .unwrap_or("img")
+}
+
 fn sanitize_name(name: &str) -> String {
     let mut out = String::with_capacity(name.len());
     for c in name.chars() {
@@ -2659,6 +3030,8 @@ pub struct MaterialEvaluationResult {
     pub evaluation_message: String,
     pub evaluation_status: String,
     pub is_success: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<TableGrid>,
 }
 
 fn estimate_pdf_pages(data: &[u8]) -> Option<usize> {
@@ -2683,6 +3056,7 @@ impl MaterialEvaluationResult {
             evaluation_message: "未处理".to_string(),
             evaluation_status: "pending".to_string(),
             is_success: false,
+            tables: Vec::new(),
         }
     }
 
@@ -2694,6 +3068,7 @@ impl MaterialEvaluationResult {
             evaluation_message: error_msg,
             evaluation_status: "error".to_string(),
             is_success: false,
+            tables: Vec::new(),
         }
     }
 
@@ -196,6 +196,32 @@ class ApiService {
         return await this.request(`/preview/data/${previewId}`);
     }
 
+    // 订阅预审进度推送，浏览器不支持 EventSource 时返回 null，由调用方回退到轮询
+    openProgressStream(previewId, onProgress, onClosed) {
+        if (typeof window.EventSource !== 'function') {
+            return null;
+        }
+
+        const url = this.appendMonitorSessionParam(
+            `${this.baseUrl}/preview/progress/${encodeURIComponent(previewId)}`,
+        );
+        const source = new EventSource(url, { withCredentials: true });
+        source.onmessage = (event) => {
+            try {
+                onProgress(JSON.parse(event.data));
+            } catch (error) {
+                console.warn('解析预审进度失败:', error);
+            }
+        };
+        source.onerror = () => {
+            // 服务端拒绝（如未登录）时浏览器不再重连
+            if (source.readyState === EventSource.CLOSED) {
+                onClosed?.();
+            }
+        };
+        return source;
+    }
+
     async getDocumentPreview(previewId, options = {}) {
         if (typeof options === 'number') {
             return this.getDocumentPreviewUrl(previewId, options);
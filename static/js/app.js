@@ -218,15 +218,24 @@ class IntelligentAuditApp {
         }
         try {
             const loaded = await this.loadData();
-            this.finishLoading();
+            const streaming = loaded && this.dataPending && this.watchProgress();
+            if (!streaming) {
+                this.stopProgressStream();
+                this.finishLoading();
+            }
             if (!loaded) {
                 return;
             }
             this.renderUI();
             if (this.dataPending) {
                 this.currentStatus = 'loading';
-                this.showLoading();
-                this.pendingTimer = setTimeout(() => this.loadDataAsync(), 3000);
+                if (streaming) {
+                    // 有进度推送时只做低频兜底刷新
+                    this.pendingTimer = setTimeout(() => this.loadDataAsync(), 15000);
+                } else {
+                    this.showLoading();
+                    this.pendingTimer = setTimeout(() => this.loadDataAsync(), 3000);
+                }
             }
         } catch (error) {
             console.error('数据加载失败:', error);
@@ -836,6 +845,52 @@ class IntelligentAuditApp {
         }, 250);
     }
 
+    watchProgress() {
+        if (this.progressSource) {
+            return true;
+        }
+
+        const source = this.apiService.openProgressStream(
+            this.previewId,
+            (event) => this.applyProgress(event),
+            () => this.stopProgressStream(),
+        );
+        if (!source) {
+            return false;
+        }
+        this.progressSource = source;
+        if (!this.loadingActive) {
+            this.showLoading();
+        }
+        return true;
+    }
+
+    stopProgressStream() {
+        if (this.progressSource) {
+            this.progressSource.close();
+            this.progressSource = null;
+        }
+    }
+
+    applyProgress(event) {
+        if (this.loadingInterval) {
+            clearInterval(this.loadingInterval);
+            this.loadingInterval = null;
+        }
+
+        const percent = Math.max(this.loadingProgress || 0, Math.min(event.percent || 0, 100));
+        this.loadingProgress = percent;
+        document.getElementById('progress-fill').style.width = `${percent}%`;
+        document.getElementById('progress-percent').textContent = event.message
+            ? `${percent}% ${event.message}`
+            : `${percent}%`;
+
+        if (['completed', 'failed', 'cancelled'].includes(event.stage)) {
+            this.stopProgressStream();
+            this.loadDataAsync();
+        }
+    }
+
     showErrorModal() {
         document.getElementById('error-modal').classList.add('show');
     }
@@ -2018,6 +2018,121 @@
         }
     }
 
+    const SEARCH_PAGE_SIZE = 20;
+
+    async function searchPreviews(offset = 0) {
+        const container = document.getElementById('searchResultsContent');
+        const footer = document.getElementById('searchPagination');
+        if (!container) return;
+
+        const params = new URLSearchParams({
+            limit: String(SEARCH_PAGE_SIZE),
+            offset: String(offset),
+        });
+        const fields = {
+            q: 'searchKeyword',
+            matter: 'searchMatter',
+            status: 'searchStatus',
+            from: 'searchDateFrom',
+            to: 'searchDateTo',
+        };
+        Object.entries(fields).forEach(([key, id]) => {
+            const value = document.getElementById(id)?.value?.trim();
+            if (value) params.set(key, value);
+        });
+
+        container.innerHTML = '<div class="loading">正在检索...</div>';
+        if (footer) footer.innerHTML = '';
+
+        try {
+            const response = await apiFetch(
+                `/api/monitor/search?${params.toString()}`,
+            );
+            const data = response.ok ? await response.json() : null;
+            if (!data || !data.success) {
+                container.innerHTML = `<p style="color:#f5222d;padding:16px;">检索失败：${h(
+                    data?.errorMsg || '请稍后重试',
+                )}</p>`;
+                return;
+            }
+            renderSearchResults(data.data || {}, offset);
+        } catch (error) {
+            console.error('全文检索失败:', error);
+            container.innerHTML =
+                '<p style="color:#f5222d;padding:16px;">检索失败，请稍后重试</p>';
+        }
+    }
+
+    function renderSearchResults(results, offset) {
+        const container = document.getElementById('searchResultsContent');
+        const footer = document.getElementById('searchPagination');
+        const hits = results.hits || [];
+        const total = results.total || 0;
+
+        if (!hits.length) {
+            container.innerHTML =
+                '<p style="padding:16px;color:#999;">没有匹配的预审</p>';
+            return;
+        }
+
+        const rows = hits
+            .map((hit) => {
+                const previewId = h(hit.preview_id);
+                const applicant = [hit.applicant_name, hit.applicant_id]
+                    .filter(Boolean)
+                    .map(h)
+                    .join(' / ');
+                // snippet 已在服务端转义，仅包含 <mark> 标签
+                const snippet = hit.snippet || '-';
+                return `
+                    <tr>
+                        <td><span class="text-clip" title="${previewId}">${previewId}</span></td>
+                        <td><span class="text-clip" title="${h(hit.matter_name || '-')}">${h(
+                    hit.matter_name || hit.matter_id || '-',
+                )}</span></td>
+                        <td>${applicant || '-'}</td>
+                        <td>${formatStatus(hit.status)}</td>
+                        <td class="search-snippet">${snippet}</td>
+                        <td>${formatDateTime(hit.created_at)}</td>
+                        <td>
+                            <button class="btn btn-secondary btn-small" onclick="viewPreview('${previewId}', '')">查看</button>
+                        </td>
+                    </tr>
+                `;
+            })
+            .join('');
+
+        container.innerHTML = `
+            <table class="data-table">
+                <thead>
+                    <tr>
+                        <th>预审ID</th>
+                        <th>事项</th>
+                        <th>申请人</th>
+                        <th>状态</th>
+                        <th>命中内容</th>
+                        <th>创建时间</th>
+                        <th>操作</th>
+                    </tr>
+                </thead>
+                <tbody>${rows}</tbody>
+            </table>
+        `;
+
+        if (footer) {
+            const prevDisabled = offset <= 0 ? 'disabled' : '';
+            const nextDisabled =
+                offset + SEARCH_PAGE_SIZE >= total ? 'disabled' : '';
+            footer.innerHTML = `
+                <span>共 ${total} 条</span>
+                <button class="btn btn-secondary btn-small" ${prevDisabled}
+                    onclick="searchPreviews(${Math.max(0, offset - SEARCH_PAGE_SIZE)})">上一页</button>
+                <button class="btn btn-secondary btn-small" ${nextDisabled}
+                    onclick="searchPreviews(${offset + SEARCH_PAGE_SIZE})">下一页</button>
+            `;
+        }
+    }
+
     function fetchFailoverStatus(options) {
         return runSingleFlight(
             'failover-status',
@@ -3288,6 +3403,7 @@
     window.resetMonitorUserPassword = resetMonitorUserPassword;
     window.toggleMonitorUserStatus = toggleMonitorUserStatus;
     window.loadRecentFailures = loadRecentFailures;
+    window.searchPreviews = searchPreviews;
 
 
     function toggleAdvancedOps() {